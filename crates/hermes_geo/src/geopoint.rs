@@ -1,13 +1,11 @@
 use rstar::{AABB, Envelope, PointDistance, RTreeObject};
 use serde::Deserialize;
 
-use crate::{
-    constants::EARTH_RADIUS_METERS,
-    degrees::Degrees,
-    distance::{Distance, Meters},
-    meters,
-};
+use crate::{EARTH_RADIUS_METERS, degrees::Degrees};
 
+/// A point on the earth, shared by the routing, the optimizer and the API. The constructors take
+/// the longitude first like `geo` and GeoJSON, `from_lat_lon` is there for the inputs in the other
+/// order.
 #[derive(
     PartialEq, Copy, Clone, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Deserialize,
 )]
@@ -24,6 +22,10 @@ impl GeoPoint {
         }
     }
 
+    pub fn from_lat_lon(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint::new(lon, lat)
+    }
+
     pub fn from_nano(lon: i32, lat: i32) -> GeoPoint {
         GeoPoint {
             lat: Degrees::from(lat),
@@ -58,13 +60,7 @@ impl RTreeObject for GeoPoint {
 
 impl PointDistance for GeoPoint {
     fn distance_2(&self, point: &<Self::Envelope as Envelope>::Point) -> f64 {
-        f64::from(haversine_distance(
-            self.lat(),
-            self.lon(),
-            point[1],
-            point[0],
-        ))
-        .powi(2)
+        haversine_distance(self.lat(), self.lon(), point[1], point[0]).powi(2)
     }
 }
 
@@ -86,6 +82,12 @@ impl From<&GeoPoint> for geo::Coord<f64> {
     }
 }
 
+impl From<GeoPoint> for geo::Point {
+    fn from(value: GeoPoint) -> Self {
+        geo::Point::new(value.lon(), value.lat())
+    }
+}
+
 impl From<&GeoPoint> for geo::Point {
     fn from(value: &GeoPoint) -> Self {
         geo::Point::new(value.lon(), value.lat())
@@ -105,12 +107,13 @@ impl From<geo::Coord> for GeoPoint {
 }
 
 impl GeoPoint {
-    pub fn haversine_distance(&self, other: &GeoPoint) -> Distance<Meters> {
+    /// Distance in meters
+    pub fn haversine_distance(&self, other: &GeoPoint) -> f64 {
         haversine_distance(self.lat(), self.lon(), other.lat(), other.lon())
     }
 }
 
-pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Distance<Meters> {
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
     let lat1_rad = lat1.to_radians();
     let lon1_rad = lon1.to_radians();
     let lat2_rad = lat2.to_radians();
@@ -123,8 +126,7 @@ pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Distanc
         + lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
     let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
 
-    // Calculate distance
-    meters!(EARTH_RADIUS_METERS * c)
+    EARTH_RADIUS_METERS * c
 }
 
 #[cfg(test)]
@@ -136,7 +138,17 @@ mod tests {
         let brussels = GeoPoint::new(4.3517, 50.8503);
         let paris = GeoPoint::new(2.3522, 48.8566);
 
-        let distance = brussels.haversine_distance(&paris).value().floor();
+        let distance = brussels.haversine_distance(&paris).floor();
         assert_eq!(distance, 263975.0);
     }
+
+    #[test]
+    fn test_geo_conversions() {
+        let brussels = GeoPoint::from_lat_lon(50.8503, 4.3517);
+        assert_eq!(brussels, GeoPoint::new(4.3517, 50.8503));
+
+        let point = geo::Point::from(brussels);
+        assert_eq!((point.x(), point.y()), (4.3517, 50.8503));
+        assert_eq!(GeoPoint::from(point), brussels);
+    }
 }
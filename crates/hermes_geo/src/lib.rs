@@ -0,0 +1,7 @@
+pub mod degrees;
+pub mod geopoint;
+
+pub use geopoint::GeoPoint;
+
+/// Radius of the earth used for the haversine distances
+pub const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
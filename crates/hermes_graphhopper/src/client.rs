@@ -51,12 +51,32 @@ pub enum GraphHopperError {
     Deserialize(#[from] serde_json::Error),
 }
 
+impl GraphHopperError {
+    /// Timeouts, connection failures and overloaded servers, the same request may succeed later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            GraphHopperError::Request(error) => error.is_timeout() || error.is_connect(),
+            GraphHopperError::Api { status, .. } => *status >= 500 || *status == 429,
+            GraphHopperError::Timeout(_) => true,
+            GraphHopperError::JobFailed(_) | GraphHopperError::Deserialize(_) => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct MatrixRequestBody {
     /// Points for symmetric matrix (all-to-all)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub points: Option<Vec<GHPoint>>,
 
+    /// Source points, with `to_points` instead of `points` for a rectangular matrix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_points: Option<Vec<GHPoint>>,
+
+    /// Destination points
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_points: Option<Vec<GHPoint>>,
+
     /// Street hints for source points (helps snapping)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub from_point_hints: Option<Vec<String>>,
@@ -101,6 +121,7 @@ struct AsyncMatrixResponse {
     solution: Option<GraphhopperMatrices>,
 }
 
+#[derive(Clone)]
 pub struct GraphhopperMatrixClientParams {
     pub api_key: String,
     pub poll_interval: Duration,
@@ -113,6 +134,7 @@ pub const GRAPHOPPER_MATRIX_ASYNC_POST_API_URL: &str =
 pub const GRAPHOPPER_MATRIX_ASYNC_POLL_API_URL: &str =
     "https://graphhopper.com/api/1/matrix/solution";
 
+#[derive(Clone)]
 pub struct GraphHopperMatrixClient {
     params: GraphhopperMatrixClientParams,
     client: reqwest::Client,
@@ -134,34 +156,18 @@ impl GraphHopperMatrixClient {
     where
         for<'a> &'a P: Into<geo_types::Point>,
     {
-        let gh_points: Vec<GHPoint> = points
-            .iter()
-            .map(|p| {
-                let point: geo_types::Point = p.into();
-                [point.x(), point.y()]
-            })
-            .collect();
+        let gh_points = points.iter().map(|point| gh_point(point.into())).collect();
 
         // TODO: validate profile
 
         let body = MatrixRequestBody {
             points: Some(gh_points),
-            from_point_hints: None,
-            to_point_hints: None,
-            out_arrays: Some(vec![
-                "times".to_string(),
-                "distances".to_string(),
-                "weights".to_string(),
-            ]),
-            profile: Some(profile.to_string()),
-            fail_fast: Some(true),
+            ..matrix_request_body(profile)
         };
 
-        let result = if points.len() < 25 {
-            self.sync_matrix_request(&body).await
-        } else {
-            self.async_matrix_requset(&body).await
-        };
+        let result = self
+            .matrix_request(&body, points.len() * points.len())
+            .await;
 
         match result {
             Ok(solution) => Ok(solution),
@@ -169,6 +175,35 @@ impl GraphHopperMatrixClient {
         }
     }
 
+    /// Rectangular matrix from the `from` points to the `to` points
+    pub async fn fetch_matrix_tile(
+        &self,
+        from: &[geo_types::Point],
+        to: &[geo_types::Point],
+        profile: GraphHopperProfile,
+    ) -> Result<GraphhopperMatrices, GraphHopperError> {
+        let body = MatrixRequestBody {
+            from_points: Some(from.iter().copied().map(gh_point).collect()),
+            to_points: Some(to.iter().copied().map(gh_point).collect()),
+            ..matrix_request_body(profile)
+        };
+
+        self.matrix_request(&body, from.len() * to.len()).await
+    }
+
+    /// Small matrices are computed synchronously, the other ones by an async job
+    async fn matrix_request(
+        &self,
+        body: &MatrixRequestBody,
+        size: usize,
+    ) -> Result<GraphhopperMatrices, GraphHopperError> {
+        if size < 25 * 25 {
+            self.sync_matrix_request(body).await
+        } else {
+            self.async_matrix_requset(body).await
+        }
+    }
+
     async fn sync_matrix_request(
         &self,
         body: &MatrixRequestBody,
@@ -269,3 +304,24 @@ impl GraphHopperMatrixClient {
         }
     }
 }
+
+fn gh_point(point: geo_types::Point) -> GHPoint {
+    [point.x(), point.y()]
+}
+
+fn matrix_request_body(profile: GraphHopperProfile) -> MatrixRequestBody {
+    MatrixRequestBody {
+        points: None,
+        from_points: None,
+        to_points: None,
+        from_point_hints: None,
+        to_point_hints: None,
+        out_arrays: Some(vec![
+            "times".to_string(),
+            "distances".to_string(),
+            "weights".to_string(),
+        ]),
+        profile: Some(profile.to_string()),
+        fail_fast: Some(true),
+    }
+}
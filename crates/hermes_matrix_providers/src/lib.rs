@@ -1,5 +1,7 @@
 mod as_the_crow_flies;
 pub mod cache;
+pub mod matrix_store;
+pub mod tiled_matrix;
 pub mod travel_matrices;
 pub mod travel_matrix_client;
 pub mod travel_matrix_provider;
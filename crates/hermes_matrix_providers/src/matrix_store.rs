@@ -0,0 +1,222 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::{Arc, RwLock},
+};
+
+use fxhash::FxHasher64;
+use thiserror::Error;
+
+use crate::{travel_matrices::TravelMatrices, travel_matrix_provider::CustomMatrices};
+
+#[derive(Error, Debug)]
+pub enum MatrixStoreError {
+    #[error("Unknown matrix {0}")]
+    UnknownMatrix(String),
+    #[error("Location index {0} out of bounds")]
+    LocationIndexOutOfBounds(usize),
+    #[error("Matrices must be square and of the same size")]
+    InvalidMatrices,
+    #[error("The uploaded matrices can't hold more than {0} entries")]
+    StoreFull(usize),
+}
+
+/// Entries of all the uploaded matrices together, about 600MB of distances, times and costs
+pub const DEFAULT_MAX_ENTRIES: usize = 25_000_000;
+
+/// Master matrices uploaded once and shared by problems using a subset of their locations
+pub struct MatrixStore {
+    matrices: RwLock<HashMap<String, Arc<TravelMatrices>>>,
+    /// Uploads are rejected once the matrices would hold more entries, the stored matrices are
+    /// never evicted since problems reference them by ID
+    max_entries: usize,
+}
+
+impl Default for MatrixStore {
+    fn default() -> Self {
+        MatrixStore::new(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl MatrixStore {
+    pub fn new(max_entries: usize) -> Self {
+        MatrixStore {
+            matrices: RwLock::new(HashMap::new()),
+            max_entries,
+        }
+    }
+
+    /// Stores the matrices and returns their ID, uploading the same matrices twice returns the same ID
+    pub fn upload(&self, matrices: CustomMatrices) -> Result<String, MatrixStoreError> {
+        let num_locations = matrices.distances.len();
+        let is_valid = [&matrices.distances, &matrices.times, &matrices.costs]
+            .iter()
+            .all(|matrix| {
+                matrix.len() == num_locations && matrix.iter().all(|row| row.len() == num_locations)
+            });
+
+        if !is_valid {
+            return Err(MatrixStoreError::InvalidMatrices);
+        }
+
+        let mut hasher = FxHasher64::default();
+        matrices.hash(&mut hasher);
+        let matrix_id = format!("{:016x}", hasher.finish());
+
+        let mut stored_matrices = self.matrices.write().unwrap();
+        if stored_matrices.contains_key(&matrix_id) {
+            return Ok(matrix_id);
+        }
+
+        let stored_entries = stored_matrices
+            .values()
+            .map(|matrices| matrices.distances.len())
+            .sum::<usize>();
+        if stored_entries + num_locations * num_locations > self.max_entries {
+            return Err(MatrixStoreError::StoreFull(self.max_entries));
+        }
+
+        let travel_matrices = TravelMatrices {
+            distances: matrices.distances.into_iter().flatten().collect(),
+            times: matrices.times.into_iter().flatten().collect(),
+            costs: Some(matrices.costs.into_iter().flatten().collect()),
+        };
+
+        stored_matrices.insert(matrix_id.clone(), Arc::new(travel_matrices));
+
+        Ok(matrix_id)
+    }
+
+    /// Copy of all the uploaded matrices by ID, e.g. to back them up
+    pub fn export(&self) -> HashMap<String, TravelMatrices> {
+        self.matrices
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(matrix_id, matrices)| (matrix_id.clone(), matrices.as_ref().clone()))
+            .collect()
+    }
+
+    /// Stores matrices previously exported, keeping their ID
+    pub fn import(&self, matrix_id: String, matrices: TravelMatrices) {
+        self.matrices
+            .write()
+            .unwrap()
+            .insert(matrix_id, Arc::new(matrices));
+    }
+
+    /// Extracts the matrices between `location_indices` of an uploaded matrix, in the given order
+    pub fn slice(
+        &self,
+        matrix_id: &str,
+        location_indices: &[usize],
+    ) -> Result<TravelMatrices, MatrixStoreError> {
+        let matrices = self
+            .matrices
+            .read()
+            .unwrap()
+            .get(matrix_id)
+            .cloned()
+            .ok_or_else(|| MatrixStoreError::UnknownMatrix(matrix_id.to_owned()))?;
+
+        matrices.slice(location_indices)
+    }
+}
+
+impl TravelMatrices {
+    fn num_locations(&self) -> usize {
+        (self.distances.len() as f64).sqrt() as usize
+    }
+
+    fn slice(&self, location_indices: &[usize]) -> Result<TravelMatrices, MatrixStoreError> {
+        let num_locations = self.num_locations();
+
+        if let Some(&index) = location_indices
+            .iter()
+            .find(|&&index| index >= num_locations)
+        {
+            return Err(MatrixStoreError::LocationIndexOutOfBounds(index));
+        }
+
+        let slice_matrix = |matrix: &[f64]| {
+            location_indices
+                .iter()
+                .flat_map(|&from| {
+                    location_indices
+                        .iter()
+                        .map(move |&to| matrix[from * num_locations + to])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        Ok(TravelMatrices {
+            distances: slice_matrix(&self.distances),
+            times: slice_matrix(&self.times),
+            costs: self.costs.as_deref().map(slice_matrix),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrices(num_locations: usize, value: f64) -> CustomMatrices {
+        let matrix = vec![vec![value; num_locations]; num_locations];
+        CustomMatrices {
+            times: matrix.clone(),
+            distances: matrix.clone(),
+            costs: matrix,
+        }
+    }
+
+    #[test]
+    fn test_upload_and_slice() {
+        let store = MatrixStore::default();
+        let mut uploaded = matrices(3, 0.0);
+        uploaded.times[0][2] = 20.0;
+        uploaded.times[2][0] = 30.0;
+
+        let matrix_id = store.upload(uploaded.clone()).unwrap();
+        assert_eq!(store.upload(uploaded).unwrap(), matrix_id);
+
+        let sliced = store.slice(&matrix_id, &[2, 0]).unwrap();
+        assert_eq!(sliced.times, vec![0.0, 30.0, 20.0, 0.0]);
+
+        assert!(matches!(
+            store.slice(&matrix_id, &[3]),
+            Err(MatrixStoreError::LocationIndexOutOfBounds(3))
+        ));
+        assert!(matches!(
+            store.slice("unknown", &[0]),
+            Err(MatrixStoreError::UnknownMatrix(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_matrices() {
+        let store = MatrixStore::default();
+        let mut uploaded = matrices(3, 1.0);
+        uploaded.costs.pop();
+
+        assert!(matches!(
+            store.upload(uploaded),
+            Err(MatrixStoreError::InvalidMatrices)
+        ));
+    }
+
+    #[test]
+    fn test_store_full() {
+        let store = MatrixStore::new(10);
+
+        let matrix_id = store.upload(matrices(3, 1.0)).unwrap();
+        assert!(matches!(
+            store.upload(matrices(2, 2.0)),
+            Err(MatrixStoreError::StoreFull(10))
+        ));
+
+        // Uploading the stored matrices again doesn't add entries
+        assert_eq!(store.upload(matrices(3, 1.0)).unwrap(), matrix_id);
+        store.upload(matrices(1, 2.0)).unwrap();
+    }
+}
@@ -0,0 +1,418 @@
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use tokio::{sync::Semaphore, task::JoinSet, time::Instant};
+use tracing::{debug, warn};
+
+use crate::travel_matrices::TravelMatrices;
+
+/// How the matrices of problems with more locations than a router accepts are split in tiles
+#[derive(Clone, Debug)]
+pub struct MatrixTiling {
+    /// Maximum number of sources and of destinations of a single router request
+    pub max_size: usize,
+    /// Maximum number of tiles requested at the same time
+    pub concurrency: usize,
+    /// Number of times a tile is requested again after a transient failure
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every following one
+    pub retry_delay: Duration,
+    /// A tile slower than this is cancelled and retried as a transient failure
+    pub tile_timeout: Duration,
+}
+
+impl Default for MatrixTiling {
+    fn default() -> Self {
+        Self {
+            // Default `--max-table-size` of osrm-routed
+            max_size: 100,
+            concurrency: 4,
+            max_retries: 3,
+            retry_delay: Duration::from_millis(500),
+            tile_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Tile {
+    sources: Range<usize>,
+    destinations: Range<usize>,
+}
+
+/// Points of a tile, the sources and destinations are ranges of `points`
+#[derive(Clone)]
+pub(crate) struct TileRequest {
+    pub points: Vec<geo_types::Point>,
+    pub sources: Range<usize>,
+    pub destinations: Range<usize>,
+}
+
+pub(crate) struct TileError {
+    error: anyhow::Error,
+    transient: bool,
+}
+
+impl TileError {
+    pub fn new(error: impl Into<anyhow::Error>, transient: bool) -> Self {
+        Self {
+            error: error.into(),
+            transient,
+        }
+    }
+}
+
+impl Tile {
+    fn request(&self, points: &[geo_types::Point]) -> TileRequest {
+        // The diagonal tiles are square, their points are sent only once
+        if self.sources == self.destinations {
+            let points = points[self.sources.clone()].to_vec();
+            let size = points.len();
+
+            return TileRequest {
+                points,
+                sources: 0..size,
+                destinations: 0..size,
+            };
+        }
+
+        let mut tile_points = points[self.sources.clone()].to_vec();
+        tile_points.extend_from_slice(&points[self.destinations.clone()]);
+
+        TileRequest {
+            points: tile_points,
+            sources: 0..self.sources.len(),
+            destinations: self.sources.len()..self.sources.len() + self.destinations.len(),
+        }
+    }
+}
+
+impl MatrixTiling {
+    pub fn requires_tiles(&self, size: usize) -> bool {
+        size > self.max_size
+    }
+
+    fn tiles(&self, size: usize) -> Vec<Tile> {
+        let max_size = self.max_size.max(1);
+        let ranges = (0..size)
+            .step_by(max_size)
+            .map(|start| start..(start + max_size).min(size))
+            .collect::<Vec<_>>();
+
+        ranges
+            .iter()
+            .flat_map(|sources| {
+                ranges.iter().map(|destinations| Tile {
+                    sources: sources.clone(),
+                    destinations: destinations.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Fetches the tiles of the matrices of `points` in parallel with `fetch_tile` and assembles
+    /// them. The costs are only kept when every tile has them
+    pub(crate) async fn fetch<F, Fut>(
+        &self,
+        points: Vec<geo_types::Point>,
+        fetch_tile: F,
+    ) -> anyhow::Result<TravelMatrices>
+    where
+        F: Fn(TileRequest) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<TravelMatrices, TileError>> + Send + 'static,
+    {
+        let size = points.len();
+        let tiles = self.tiles(size);
+        debug!(
+            "Fetching the {size}x{size} matrices in {} tiles",
+            tiles.len()
+        );
+
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+
+        for tile in tiles {
+            let request = tile.request(&points);
+            let semaphore = Arc::clone(&semaphore);
+            let fetch_tile = fetch_tile.clone();
+            let tiling = self.clone();
+
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                let matrices = tiling.fetch_tile(&tile, request, fetch_tile).await?;
+                anyhow::Ok((tile, matrices))
+            });
+        }
+
+        let mut distances = vec![0.0; size * size];
+        let mut times = vec![0.0; size * size];
+        let mut costs = Some(vec![0.0; size * size]);
+
+        // Returning early drops the set, which aborts the remaining tiles
+        while let Some(result) = tasks.join_next().await {
+            let (tile, matrices) = result??;
+            let columns = tile.destinations.len();
+
+            for (row, from) in tile.sources.clone().enumerate() {
+                let tile_row = row * columns..(row + 1) * columns;
+                let matrix_row =
+                    from * size + tile.destinations.start..from * size + tile.destinations.end;
+
+                distances[matrix_row.clone()]
+                    .copy_from_slice(&matrices.distances[tile_row.clone()]);
+                times[matrix_row.clone()].copy_from_slice(&matrices.times[tile_row.clone()]);
+
+                match (&mut costs, &matrices.costs) {
+                    (Some(costs), Some(tile_costs)) => {
+                        costs[matrix_row].copy_from_slice(&tile_costs[tile_row]);
+                    }
+                    _ => costs = None,
+                }
+            }
+        }
+
+        Ok(TravelMatrices {
+            distances,
+            times,
+            costs,
+        })
+    }
+
+    async fn fetch_tile<F, Fut>(
+        &self,
+        tile: &Tile,
+        request: TileRequest,
+        fetch_tile: F,
+    ) -> anyhow::Result<TravelMatrices>
+    where
+        F: Fn(TileRequest) -> Fut,
+        Fut: Future<Output = Result<TravelMatrices, TileError>>,
+    {
+        let expected_len = tile.sources.len() * tile.destinations.len();
+        let mut retries = 0;
+
+        loop {
+            let started_at = Instant::now();
+            let result = tokio::time::timeout(self.tile_timeout, fetch_tile(request.clone()))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(TileError::new(
+                        anyhow::anyhow!("Tile timed out after {:?}", self.tile_timeout),
+                        true,
+                    ))
+                });
+
+            let error = match result {
+                Ok(matrices) => {
+                    debug!(
+                        "Fetched tile {:?}x{:?} in {:?}",
+                        tile.sources,
+                        tile.destinations,
+                        started_at.elapsed()
+                    );
+
+                    let complete = matrices.distances.len() == expected_len
+                        && matrices.times.len() == expected_len
+                        && matrices
+                            .costs
+                            .as_ref()
+                            .is_none_or(|costs| costs.len() == expected_len);
+                    if !complete {
+                        return Err(anyhow::anyhow!(
+                            "Incomplete tile {:?}x{:?}",
+                            tile.sources,
+                            tile.destinations
+                        ));
+                    }
+
+                    return Ok(matrices);
+                }
+                Err(error) if error.transient && retries < self.max_retries => error.error,
+                Err(error) => return Err(error.error),
+            };
+
+            let delay = self.retry_delay * 2u32.pow(retries);
+            retries += 1;
+            warn!(
+                "Tile {:?}x{:?} failed after {:?}, retry {retries}/{} in {delay:?}: {error}",
+                tile.sources,
+                tile.destinations,
+                started_at.elapsed(),
+                self.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn tiling(max_size: usize) -> MatrixTiling {
+        MatrixTiling {
+            max_size,
+            retry_delay: Duration::from_millis(10),
+            ..MatrixTiling::default()
+        }
+    }
+
+    /// The x coordinate of a point is its index, the distance from `i` to `j` is `10 * i + j`
+    fn points(size: usize) -> Vec<geo_types::Point> {
+        (0..size)
+            .map(|index| geo_types::Point::new(index as f64, 0.0))
+            .collect()
+    }
+
+    fn tile_matrices(tile: &TileRequest) -> TravelMatrices {
+        let values = tile.points[tile.sources.clone()]
+            .iter()
+            .flat_map(|from| {
+                tile.points[tile.destinations.clone()]
+                    .iter()
+                    .map(move |to| 10.0 * from.x() + to.x())
+            })
+            .collect::<Vec<_>>();
+
+        TravelMatrices {
+            distances: values.clone(),
+            times: values.clone(),
+            costs: Some(values),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_assembles_tiles() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+
+        let matrices = tiling(2)
+            .fetch(points(5), move |tile: TileRequest| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    // The points of the diagonal tiles are sent once
+                    if tile.sources == tile.destinations {
+                        assert_eq!(tile.points.len(), tile.sources.len());
+                    }
+
+                    Ok(tile_matrices(&tile))
+                }
+            })
+            .await
+            .unwrap();
+
+        // 3 ranges of sources and of destinations: 0..2, 2..4 and 4..5
+        assert_eq!(requests.load(Ordering::Relaxed), 9);
+        for from in 0..5 {
+            for to in 0..5 {
+                let expected = (10 * from + to) as f64;
+                assert_eq!(matrices.distances[from * 5 + to], expected);
+                assert_eq!(matrices.times[from * 5 + to], expected);
+                assert_eq!(matrices.costs.as_ref().unwrap()[from * 5 + to], expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_drops_partial_costs() {
+        let matrices = tiling(2)
+            .fetch(points(3), |tile: TileRequest| async move {
+                let mut matrices = tile_matrices(&tile);
+                if tile.points[tile.sources.start].x() == 2.0 {
+                    matrices.costs = None;
+                }
+                Ok(matrices)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(matrices.distances.len(), 9);
+        assert!(matrices.costs.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetch_retries_transient_failures() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+
+        let matrices = tiling(2)
+            .fetch(points(2), move |tile: TileRequest| {
+                let attempt = counter.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if attempt < 2 {
+                        Err(TileError::new(anyhow::anyhow!("unavailable"), true))
+                    } else {
+                        Ok(tile_matrices(&tile))
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+        assert_eq!(matrices.distances, vec![0.0, 1.0, 10.0, 11.0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetch_gives_up() {
+        // Transient failures are retried `max_retries` times
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+        let result = tiling(2)
+            .fetch(points(2), move |_: TileRequest| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                async { Err(TileError::new(anyhow::anyhow!("unavailable"), true)) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 4);
+
+        // Other failures are not retried
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+        let result = tiling(2)
+            .fetch(points(2), move |_: TileRequest| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                async { Err(TileError::new(anyhow::anyhow!("invalid"), false)) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+
+        // A tile missing values fails the matrices
+        let result = tiling(2)
+            .fetch(points(3), |tile: TileRequest| async move {
+                let mut matrices = tile_matrices(&tile);
+                matrices.times.pop();
+                Ok(matrices)
+            })
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("Incomplete tile"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetch_retries_slow_tiles() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+
+        let matrices = tiling(2)
+            .fetch(points(2), move |tile: TileRequest| {
+                let attempt = counter.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if attempt == 0 {
+                        tokio::time::sleep(Duration::from_secs(120)).await;
+                    }
+                    Ok(tile_matrices(&tile))
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+        assert_eq!(matrices.times, vec![0.0, 1.0, 10.0, 11.0]);
+    }
+}
@@ -1,10 +1,14 @@
-use hermes_graphhopper::client::{GraphHopperMatrixClient, GraphhopperMatrixClientParams};
-use hermes_osrm::client::{OsrmClient, OsrmClientParams};
-use tracing::instrument;
+use hermes_graphhopper::client::{
+    GraphHopperMatrixClient, GraphHopperProfile, GraphhopperMatrixClientParams,
+};
+use hermes_osrm::client::{OSRM_MAX_TABLE_SIZE, OsrmClient, OsrmClientParams};
+use tracing::{instrument, warn};
 
 use crate::{
     as_the_crow_flies::as_the_crow_flies_matrices,
     cache::{FileCache, MatricesCache},
+    matrix_store::{DEFAULT_MAX_ENTRIES, MatrixStore},
+    tiled_matrix::{MatrixTiling, TileError, TileRequest},
     travel_matrices::TravelMatrices,
     travel_matrix_provider::TravelMatrixProvider,
 };
@@ -16,6 +20,8 @@ where
     graphhopper_client: Option<GraphHopperMatrixClient>,
     osrm_client: OsrmClient,
     cache: C,
+    matrix_store: MatrixStore,
+    tiling: MatrixTiling,
 }
 
 impl<C> TravelMatrixClient<C>
@@ -27,15 +33,56 @@ where
             cache,
             graphhopper_client: Self::create_default_graphhopper_client(),
             osrm_client: Self::create_default_osrm_client(),
+            matrix_store: Self::create_default_matrix_store(),
+            tiling: Self::create_default_tiling(),
         }
     }
 
+    pub fn matrix_store(&self) -> &MatrixStore {
+        &self.matrix_store
+    }
+
     fn create_default_osrm_client() -> OsrmClient {
         let osrm_url =
             std::env::var("OSRM_URL").unwrap_or(String::from("http://router.project-osrm.org"));
         OsrmClient::new(OsrmClientParams { osrm_url })
     }
 
+    /// `MATRIX_MAX_SIZE` should match the table size limit of the router, it can't exceed the one
+    /// of the OSRM client
+    fn create_default_tiling() -> MatrixTiling {
+        let default = MatrixTiling::default();
+        let env_var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+        };
+
+        let mut max_size = env_var("MATRIX_MAX_SIZE").unwrap_or(default.max_size);
+        if max_size > OSRM_MAX_TABLE_SIZE {
+            warn!(
+                "MATRIX_MAX_SIZE {max_size} exceeds the OSRM table size limit {OSRM_MAX_TABLE_SIZE}"
+            );
+            max_size = OSRM_MAX_TABLE_SIZE;
+        }
+
+        MatrixTiling {
+            max_size,
+            concurrency: env_var("MATRIX_CONCURRENCY").unwrap_or(default.concurrency),
+            ..default
+        }
+    }
+
+    /// `MATRIX_STORE_MAX_ENTRIES` limits the entries of all the uploaded matrices together
+    fn create_default_matrix_store() -> MatrixStore {
+        let max_entries = std::env::var("MATRIX_STORE_MAX_ENTRIES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_ENTRIES);
+
+        MatrixStore::new(max_entries)
+    }
+
     fn create_default_graphhopper_client() -> Option<GraphHopperMatrixClient> {
         if let Ok(api_key) = std::env::var("GRAPHHOPPER_API_KEY") {
             Some(GraphHopperMatrixClient::new(
@@ -59,6 +106,30 @@ where
     where
         for<'a> &'a P: Into<geo_types::Point>,
     {
+        // Uploaded matrices are already in memory, slicing them is cheaper than caching the result
+        if let TravelMatrixProvider::Uploaded {
+            matrix_id,
+            location_indices,
+        } = &provider
+        {
+            if location_indices.len() != points.len() {
+                return Err(anyhow::anyhow!(
+                    "Expected {} location indices, got {}",
+                    points.len(),
+                    location_indices.len()
+                ));
+            }
+
+            return Ok(self.matrix_store.slice(matrix_id, location_indices)?);
+        }
+
+        // Registered providers are resolved before reaching the client, their matrices are not cached
+        if let TravelMatrixProvider::Provider { name } = &provider {
+            return Err(anyhow::anyhow!(
+                "Travel time provider {name} is not registered"
+            ));
+        }
+
         let cached = self.cache.get_cached(&provider, points);
 
         if let Ok(Some(cached_matrices)) = cached {
@@ -74,12 +145,20 @@ where
                     .as_ref()
                     .ok_or(anyhow::anyhow!("Missing GH api key"))?;
 
-                let response = gh_client.fetch_matrix(points, *profile).await?;
-                Ok(TravelMatrices {
-                    distances: response.distances.into_iter().flatten().collect(),
-                    times: response.times.into_iter().flatten().collect(),
-                    costs: Some(response.weights.into_iter().flatten().collect()),
-                })
+                if self.tiling.requires_tiles(points.len()) {
+                    self.fetch_graphhopper_tiles(gh_client, points, *profile)
+                        .await
+                } else {
+                    let response = gh_client.fetch_matrix(points, *profile).await?;
+                    Ok(TravelMatrices {
+                        distances: response.distances.into_iter().flatten().collect(),
+                        times: response.times.into_iter().flatten().collect(),
+                        costs: Some(response.weights.into_iter().flatten().collect()),
+                    })
+                }
+            }
+            TravelMatrixProvider::Osrm { .. } if self.tiling.requires_tiles(points.len()) => {
+                self.fetch_osrm_tiles(points).await
             }
             TravelMatrixProvider::Osrm { .. } => {
                 // TODO: profile
@@ -98,6 +177,9 @@ where
                 times: matrices.times.iter().flatten().copied().collect(),
                 costs: Some(matrices.costs.iter().flatten().copied().collect()),
             }),
+            TravelMatrixProvider::Uploaded { .. } | TravelMatrixProvider::Provider { .. } => Err(
+                anyhow::anyhow!("Uploaded and registered matrices can't be fetched"),
+            ),
         };
 
         if let Ok(ref matrices) = result {
@@ -106,6 +188,76 @@ where
 
         result
     }
+
+    async fn fetch_graphhopper_tiles<P>(
+        &self,
+        gh_client: &GraphHopperMatrixClient,
+        points: &[P],
+        profile: GraphHopperProfile,
+    ) -> anyhow::Result<TravelMatrices>
+    where
+        for<'a> &'a P: Into<geo_types::Point>,
+    {
+        let gh_client = gh_client.clone();
+
+        self.tiling
+            .fetch(geo_points(points), move |tile: TileRequest| {
+                let gh_client = gh_client.clone();
+                async move {
+                    let from = &tile.points[tile.sources];
+                    let to = &tile.points[tile.destinations];
+                    gh_client
+                        .fetch_matrix_tile(from, to, profile)
+                        .await
+                        .map(|response| TravelMatrices {
+                            distances: response.distances.into_iter().flatten().collect(),
+                            times: response.times.into_iter().flatten().collect(),
+                            costs: Some(response.weights.into_iter().flatten().collect()),
+                        })
+                        .map_err(|error| {
+                            let transient = error.is_transient();
+                            TileError::new(error, transient)
+                        })
+                }
+            })
+            .await
+    }
+
+    async fn fetch_osrm_tiles<P>(&self, points: &[P]) -> anyhow::Result<TravelMatrices>
+    where
+        for<'a> &'a P: Into<geo_types::Point>,
+    {
+        let osrm_client = self.osrm_client.clone();
+
+        self.tiling
+            .fetch(geo_points(points), move |tile: TileRequest| {
+                let osrm_client = osrm_client.clone();
+                async move {
+                    let sources = tile.sources.collect::<Vec<_>>();
+                    let destinations = tile.destinations.collect::<Vec<_>>();
+                    osrm_client
+                        .fetch_table(&tile.points, &sources, &destinations)
+                        .await
+                        .map(|response| TravelMatrices {
+                            distances: response.distances,
+                            times: response.times,
+                            costs: None,
+                        })
+                        .map_err(|error| {
+                            let transient = error.is_transient();
+                            TileError::new(error, transient)
+                        })
+                }
+            })
+            .await
+    }
+}
+
+fn geo_points<P>(points: &[P]) -> Vec<geo_types::Point>
+where
+    for<'a> &'a P: Into<geo_types::Point>,
+{
+    points.iter().map(Into::into).collect()
 }
 
 impl Default for TravelMatrixClient<FileCache> {
@@ -116,6 +268,8 @@ impl Default for TravelMatrixClient<FileCache> {
             ),
             graphhopper_client: Self::create_default_graphhopper_client(),
             osrm_client: Self::create_default_osrm_client(),
+            matrix_store: Self::create_default_matrix_store(),
+            tiling: Self::create_default_tiling(),
         }
     }
 }
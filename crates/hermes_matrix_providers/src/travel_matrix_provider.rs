@@ -2,7 +2,7 @@ use hermes_graphhopper::client::GraphHopperProfile;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone)]
 pub struct CustomMatrices {
     pub times: Vec<Vec<f64>>,
     pub distances: Vec<Vec<f64>>,
@@ -24,7 +24,7 @@ impl std::hash::Hash for CustomMatrices {
     }
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(tag = "type", content = "config", rename_all = "snake_case")]
 pub enum TravelMatrixProvider {
     /// https://docs.graphhopper.com/openapi/map-data-and-routing-profiles/openstreetmap/standard-routing-profiles
@@ -42,6 +42,19 @@ pub enum TravelMatrixProvider {
     Custom {
         matrices: CustomMatrices,
     },
+
+    /// Subset of a matrix previously uploaded with `/matrix/upload`, `location_indices[i]` is the
+    /// index in the uploaded matrix of the i-th location of the problem
+    Uploaded {
+        matrix_id: String,
+        location_indices: Vec<usize>,
+    },
+
+    /// Travel time provider registered by the application under `name`, see `TravelTimeProviders`
+    /// in the optimizer
+    Provider {
+        name: String,
+    },
 }
 
 impl std::hash::Hash for TravelMatrixProvider {
@@ -63,6 +76,18 @@ impl std::hash::Hash for TravelMatrixProvider {
                 state.write_u8(2);
                 matrices.hash(state);
             }
+            TravelMatrixProvider::Uploaded {
+                matrix_id,
+                location_indices,
+            } => {
+                state.write_u8(3);
+                matrix_id.hash(state);
+                location_indices.hash(state);
+            }
+            TravelMatrixProvider::Provider { name } => {
+                state.write_u8(4);
+                name.hash(state);
+            }
         }
     }
 }
@@ -5,6 +5,8 @@ use fxhash::FxHashSet;
 use hermes_optimizer::problem::{
     amount::{Amount, AmountExpression, AmountSum},
     capacity::Capacity,
+    location::Location,
+    travel_cost_matrix::{DistanceRounding, TravelMatrices},
 };
 use rand::{Rng, SeedableRng, rng, rngs::SmallRng};
 use thread_local::ThreadLocal;
@@ -37,6 +39,57 @@ fn capacity_benchmark(c: &mut Criterion) -> () {
     });
 }
 
+fn load_peaks_clone(loads: &[Capacity], peaks: &mut [Capacity]) {
+    let mut peak = loads[0].clone();
+    for (load, load_peak) in loads.iter().zip(peaks.iter_mut()) {
+        peak = Capacity::from_vec(
+            peak.iter()
+                .zip(load.iter())
+                .map(|(a, b)| a.max(b))
+                .collect(),
+        );
+        *load_peak = peak.clone();
+    }
+}
+
+fn load_peaks_in_place(loads: &[Capacity], peaks: &mut [Capacity]) {
+    peaks[0].update(&loads[0]);
+    for i in 1..loads.len() {
+        let (previous, next) = peaks.split_at_mut(i);
+        next[0].update(&previous[i - 1]);
+        next[0].update_max(&loads[i]);
+    }
+}
+
+fn load_peaks_benchmark(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(42);
+
+    let mut group = c.benchmark_group("load_peaks");
+
+    for dimensions in [1, 3, 6] {
+        let loads: Vec<Capacity> = (0..100)
+            .map(|_| {
+                Capacity::from_vec(
+                    (0..dimensions)
+                        .map(|_| rng.random_range(0.0..100.0))
+                        .collect(),
+                )
+            })
+            .collect();
+        let mut peaks = vec![Capacity::with_dimensions(dimensions); loads.len()];
+
+        group.bench_function(format!("clone_{dimensions}d"), |b| {
+            b.iter(|| load_peaks_clone(black_box(&loads), black_box(&mut peaks)))
+        });
+
+        group.bench_function(format!("in_place_{dimensions}d"), |b| {
+            b.iter(|| load_peaks_in_place(black_box(&loads), black_box(&mut peaks)))
+        });
+    }
+
+    group.finish();
+}
+
 fn satisfies_demand_zip(capacity: &Capacity, demand: &Capacity) -> bool {
     if capacity.len() < demand.len() {
         return false;
@@ -248,14 +301,41 @@ fn rng_bench(c: &mut Criterion) {
     group.finish();
 }
 
+fn travel_matrices_benchmark(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(42);
+    let locations = (0..10_000)
+        .map(|_| Location::from_lat_lon(rng.random_range(49.5..51.5), rng.random_range(2.5..6.4)))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("travel_matrices");
+    group.sample_size(10);
+
+    group.bench_function("haversine 10k", |b| {
+        b.iter(|| black_box(TravelMatrices::from_haversine(black_box(&locations))))
+    });
+
+    group.bench_function("euclidean 10k", |b| {
+        b.iter(|| {
+            black_box(TravelMatrices::from_euclidean(
+                black_box(&locations),
+                DistanceRounding::Exact,
+            ))
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     // bench_direct_access,
-    // capacity_benchmark,
+    capacity_benchmark,
+    load_peaks_benchmark,
     // satisfies_demand_benchmark,
     // over_capacity_demand_benchmark,
     // find_in_set_benchmark,
     // sort_benchmark,
-    rng_bench
+    rng_bench,
+    travel_matrices_benchmark
 );
 criterion_main!(benches);
@@ -0,0 +1,70 @@
+//! Capacitated vehicle routing problem: 3 vehicles of capacity 40 deliver 20 customers around a
+//! single depot, on a plane with euclidean distances.
+//!
+//! ```sh
+//! cargo run --release -p hermes_optimizer --example basic_cvrp
+//! ```
+
+mod common;
+
+use std::sync::Arc;
+
+use hermes_optimizer::problem::{
+    capacity::Capacity,
+    distance_method::DistanceMethod,
+    fleet::Fleet,
+    location::Location,
+    service::ServiceBuilder,
+    travel_cost_matrix::{DistanceRounding, TravelMatrices},
+    vehicle::VehicleBuilder,
+    vehicle_profile::VehicleProfile,
+    vehicle_routing_problem::VehicleRoutingProblemBuilder,
+};
+
+const CUSTOMERS: usize = 20;
+
+fn main() {
+    // The depot is the first location, the customers are on two rings around it
+    let mut locations = vec![Location::from_cartesian(0.0, 0.0)];
+    locations.extend((0..CUSTOMERS).map(|index| {
+        let angle = index as f64 * std::f64::consts::TAU / CUSTOMERS as f64;
+        let radius = if index % 2 == 0 { 10.0 } else { 25.0 };
+        Location::from_cartesian(radius * angle.cos(), radius * angle.sin())
+    }));
+
+    let services = (0..CUSTOMERS)
+        .map(|index| {
+            let mut builder = ServiceBuilder::default();
+            builder.set_external_id(format!("customer_{}", index + 1));
+            builder.set_location_id(index + 1);
+            builder.set_demand(Capacity::from_vec(vec![(index % 4 + 3) as f64]));
+            builder.build()
+        })
+        .collect();
+
+    let vehicles = (0..3)
+        .map(|index| {
+            let mut builder = VehicleBuilder::default();
+            builder.set_vehicle_id(format!("vehicle_{}", index + 1));
+            builder.set_profile_id(0);
+            builder.set_depot_location_id(0);
+            builder.set_capacity(Capacity::from_vec(vec![40.0]));
+            builder.build()
+        })
+        .collect();
+
+    let mut builder = VehicleRoutingProblemBuilder::default();
+    builder.set_distance_method(DistanceMethod::Euclidean);
+    builder.set_vehicle_profiles(vec![VehicleProfile::new(
+        String::from("plane"),
+        TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+    )]);
+    builder.set_locations(locations);
+    builder.set_services(services);
+    builder.set_fleet(Fleet::Finite(vehicles));
+
+    let problem = builder.build().expect("Expected a valid problem");
+    let solution = common::solve(Arc::new(problem), None);
+
+    common::print_solution(&solution);
+}
@@ -0,0 +1,83 @@
+//! Helpers shared by the examples, each example is compiled with its own copy of this module
+
+use std::sync::Arc;
+
+use hermes_optimizer::{
+    problem::{job::ActivityId, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        accepted_solution::AcceptedSolution,
+        solution::working_solution::WorkingSolution,
+        solver::Solver,
+        solver_params::{SolverParams, Termination},
+    },
+};
+
+/// Enough for the small problems of the examples, real problems usually stop on a duration
+pub const ITERATIONS: usize = 2000;
+
+/// Solves the problem with the default parameters of the problem, from `initial_solution` when
+/// there is one
+pub fn solve(
+    problem: Arc<VehicleRoutingProblem>,
+    initial_solution: Option<WorkingSolution>,
+) -> AcceptedSolution {
+    let params = SolverParams {
+        terminations: vec![Termination::Iterations(ITERATIONS)],
+        ..SolverParams::default_from_problem(&problem)
+    };
+    let solver = Solver::new(problem, params).expect("Expected valid solver parameters");
+
+    if let Some(initial_solution) = initial_solution {
+        solver.set_initial_solution(initial_solution);
+    }
+
+    solver.solve().expect("Expected the search to complete");
+    solver.current_best_solution().expect("Expected a solution")
+}
+
+pub fn activity_label(problem: &VehicleRoutingProblem, activity_id: ActivityId) -> String {
+    let external_id = problem.job(activity_id.job_id()).external_id();
+
+    match activity_id {
+        ActivityId::Service(_) => external_id.to_owned(),
+        ActivityId::ShipmentPickup(_) => format!("pickup {external_id}"),
+        ActivityId::ShipmentDelivery(_) => format!("delivery {external_id}"),
+    }
+}
+
+/// Prints the score, then the activities, distance and duration of each route
+pub fn print_solution(accepted_solution: &AcceptedSolution) {
+    let solution = &accepted_solution.solution;
+    let problem = solution.problem();
+
+    println!(
+        "Score: hard {}, soft {:.2}",
+        accepted_solution.score.hard_score, accepted_solution.score.soft_score
+    );
+
+    for (_, route) in solution.ordered_non_empty_routes() {
+        let activities = route
+            .activity_ids()
+            .iter()
+            .map(|&activity_id| activity_label(problem, activity_id))
+            .collect::<Vec<_>>();
+
+        println!(
+            "  {}: {} ({:.0} m, {} min, starts at {})",
+            route.vehicle(problem).external_id(),
+            activities.join(" -> "),
+            route.distance(problem).value(),
+            route.duration(problem).as_mins(),
+            route.start(problem),
+        );
+    }
+
+    let unassigned = solution
+        .unassigned_jobs()
+        .iter()
+        .map(|&job_id| problem.job(job_id).external_id())
+        .collect::<Vec<_>>();
+    if !unassigned.is_empty() {
+        println!("  Unassigned: {}", unassigned.join(", "));
+    }
+}
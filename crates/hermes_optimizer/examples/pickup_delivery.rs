@@ -0,0 +1,78 @@
+//! Pickup and delivery problem: two couriers carrying at most 3 parcels at a time bring 8 parcels
+//! from the shops where they are picked up to the customers, each parcel within 40 minutes of its
+//! pickup. The couriers ride at 50 km/h as the crow flies.
+//!
+//! ```sh
+//! cargo run --release -p hermes_optimizer --example pickup_delivery
+//! ```
+
+mod common;
+
+use std::sync::Arc;
+
+use hermes_optimizer::problem::{
+    capacity::Capacity, distance_method::DistanceMethod, fleet::Fleet, location::Location,
+    shipment::ShipmentBuilder, travel_cost_matrix::TravelMatrices, vehicle::VehicleBuilder,
+    vehicle_profile::VehicleProfile, vehicle_routing_problem::VehicleRoutingProblemBuilder,
+};
+use jiff::SignedDuration;
+
+/// Latitude and longitude of the shop where each parcel is picked up and of its customer
+const PARCELS: [((f64, f64), (f64, f64)); 8] = [
+    ((50.8467, 4.3525), (50.8275, 4.3724)),
+    ((50.8467, 4.3525), (50.8126, 4.3817)),
+    ((50.8467, 4.3525), (50.8357, 4.3363)),
+    ((50.8403, 4.3933), (50.8474, 4.4374)),
+    ((50.8403, 4.3933), (50.8668, 4.3475)),
+    ((50.8403, 4.3933), (50.8949, 4.3415)),
+    ((50.8668, 4.3475), (50.8812, 4.3720)),
+    ((50.8668, 4.3475), (50.8580, 4.3270)),
+];
+
+fn main() {
+    let mut locations = vec![Location::from_lat_lon(50.8503, 4.3517)];
+    for &((pickup_lat, pickup_lon), (delivery_lat, delivery_lon)) in &PARCELS {
+        locations.push(Location::from_lat_lon(pickup_lat, pickup_lon));
+        locations.push(Location::from_lat_lon(delivery_lat, delivery_lon));
+    }
+
+    let shipments = (0..PARCELS.len())
+        .map(|index| {
+            let mut builder = ShipmentBuilder::default();
+            builder.set_external_id(format!("parcel_{}", index + 1));
+            builder.set_demand(Capacity::from_vec(vec![1.0]));
+            builder.set_pickup_location_id(1 + index * 2);
+            builder.set_pickup_duration(SignedDuration::from_mins(3));
+            builder.set_delivery_location_id(2 + index * 2);
+            builder.set_delivery_duration(SignedDuration::from_mins(5));
+            builder.set_max_ride_duration(SignedDuration::from_mins(40));
+            builder.build()
+        })
+        .collect();
+
+    let vehicles = (0..2)
+        .map(|index| {
+            let mut builder = VehicleBuilder::default();
+            builder.set_vehicle_id(format!("courier_{}", index + 1));
+            builder.set_profile_id(0);
+            builder.set_depot_location_id(0);
+            builder.set_capacity(Capacity::from_vec(vec![3.0]));
+            builder.build()
+        })
+        .collect();
+
+    let mut builder = VehicleRoutingProblemBuilder::default();
+    builder.set_distance_method(DistanceMethod::Haversine);
+    builder.set_vehicle_profiles(vec![VehicleProfile::new(
+        String::from("crow_flies"),
+        TravelMatrices::from_haversine(&locations),
+    )]);
+    builder.set_locations(locations);
+    builder.set_shipments(shipments);
+    builder.set_fleet(Fleet::Finite(vehicles));
+
+    let problem = builder.build().expect("Expected a valid problem");
+    let solution = common::solve(Arc::new(problem), None);
+
+    common::print_solution(&solution);
+}
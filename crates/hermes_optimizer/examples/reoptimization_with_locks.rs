@@ -0,0 +1,106 @@
+//! Re-optimization during the day: the routes of the morning are planned, each vehicle has
+//! already visited its first 2 customers when 4 new orders arrive. The routes are re-optimized
+//! from the morning plan with the visited customers locked, so that only the rest of the day
+//! changes.
+//!
+//! ```sh
+//! cargo run --release -p hermes_optimizer --example reoptimization_with_locks
+//! ```
+
+mod common;
+
+use std::sync::Arc;
+
+use hermes_optimizer::{
+    json::initial_solution::JsonInitialSolution,
+    problem::{
+        capacity::Capacity,
+        distance_method::DistanceMethod,
+        fleet::Fleet,
+        location::Location,
+        service::ServiceBuilder,
+        travel_cost_matrix::{DistanceRounding, TravelMatrices},
+        vehicle::VehicleBuilder,
+        vehicle_profile::VehicleProfile,
+        vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
+    },
+};
+
+const MORNING_CUSTOMERS: usize = 12;
+const NEW_CUSTOMERS: usize = 4;
+/// Customers already visited by each vehicle when the new orders arrive
+const VISITED: usize = 2;
+
+/// Customers on a 4x4 grid next to the depot, the same customer always has the same ID
+fn build_problem(customers: usize) -> VehicleRoutingProblem {
+    let mut locations = vec![Location::from_cartesian(0.0, 0.0)];
+    locations.extend((0..customers).map(|index| {
+        Location::from_cartesian((index % 4 + 1) as f64 * 5.0, (index / 4) as f64 * 5.0)
+    }));
+
+    let services = (0..customers)
+        .map(|index| {
+            let mut builder = ServiceBuilder::default();
+            builder.set_external_id(format!("customer_{}", index + 1));
+            builder.set_location_id(index + 1);
+            builder.set_demand(Capacity::from_vec(vec![1.0]));
+            builder.build()
+        })
+        .collect();
+
+    let vehicles = (0..2)
+        .map(|index| {
+            let mut builder = VehicleBuilder::default();
+            builder.set_vehicle_id(format!("vehicle_{}", index + 1));
+            builder.set_profile_id(0);
+            builder.set_depot_location_id(0);
+            builder.set_capacity(Capacity::from_vec(vec![10.0]));
+            builder.build()
+        })
+        .collect();
+
+    let mut builder = VehicleRoutingProblemBuilder::default();
+    builder.set_distance_method(DistanceMethod::Euclidean);
+    builder.set_vehicle_profiles(vec![VehicleProfile::new(
+        String::from("plane"),
+        TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+    )]);
+    builder.set_locations(locations);
+    builder.set_services(services);
+    builder.set_fleet(Fleet::Finite(vehicles));
+
+    builder.build().expect("Expected a valid problem")
+}
+
+fn main() {
+    let morning = common::solve(Arc::new(build_problem(MORNING_CUSTOMERS)), None);
+    println!("Morning plan");
+    common::print_solution(&morning);
+
+    // The solution refers to the jobs by their external IDs, it can be applied to the new problem
+    let mut dispatched = JsonInitialSolution::from(&morning.solution);
+    for route in &mut dispatched.routes {
+        route.locked_activities = Some(VISITED.min(route.activity_ids.len()));
+    }
+
+    let problem = Arc::new(build_problem(MORNING_CUSTOMERS + NEW_CUSTOMERS));
+    let initial_solution = dispatched
+        .build_solution(Arc::clone(&problem))
+        .expect("Expected the morning plan to match the new problem");
+    let reoptimized = common::solve(Arc::clone(&problem), Some(initial_solution));
+
+    println!("Re-optimized with {NEW_CUSTOMERS} new customers");
+    common::print_solution(&reoptimized);
+
+    for (_, route) in reoptimized.solution.ordered_non_empty_routes() {
+        let visited = route.activity_ids()[..route.locked_len()]
+            .iter()
+            .map(|&activity_id| common::activity_label(&problem, activity_id))
+            .collect::<Vec<_>>();
+        println!(
+            "  {} kept its visited customers: {}",
+            route.vehicle(&problem).external_id(),
+            visited.join(", ")
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Vehicle routing problem with time windows in Brussels: two technicians with a morning and an
+//! afternoon shift visit customers who are only available part of the day. Distances are computed
+//! as the crow flies, see `JsonVehicleRoutingProblem` to fetch road matrices instead.
+//!
+//! ```sh
+//! cargo run --release -p hermes_optimizer --example vrptw_shifts
+//! ```
+
+mod common;
+
+use std::sync::Arc;
+
+use hermes_optimizer::problem::{
+    distance_method::DistanceMethod,
+    fleet::Fleet,
+    location::Location,
+    service::ServiceBuilder,
+    time_window::TimeWindow,
+    travel_cost_matrix::TravelMatrices,
+    vehicle::{VehicleBuilder, VehicleShiftBuilder},
+    vehicle_profile::VehicleProfile,
+    vehicle_routing_problem::VehicleRoutingProblemBuilder,
+};
+use jiff::{SignedDuration, Timestamp};
+
+/// Opening and closing time of a customer
+type Availability = Option<(&'static str, &'static str)>;
+
+/// Name, latitude, longitude and availability of each customer
+const CUSTOMERS: [(&str, f64, f64, Availability); 8] = [
+    ("grand_place", 50.8467, 4.3525, None),
+    ("atomium", 50.8949, 4.3415, Some(("08:00", "11:00"))),
+    ("cinquantenaire", 50.8403, 4.3933, Some(("13:00", "17:00"))),
+    ("flagey", 50.8275, 4.3724, None),
+    ("ulb", 50.8126, 4.3817, Some(("09:00", "12:00"))),
+    ("gare_du_midi", 50.8357, 4.3363, None),
+    ("tour_et_taxis", 50.8668, 4.3475, Some(("14:00", "18:00"))),
+    ("woluwe", 50.8474, 4.4374, Some(("08:00", "10:00"))),
+];
+
+fn at(time: &str) -> Timestamp {
+    format!("2025-06-02T{time}:00Z")
+        .parse()
+        .expect("Expected a valid time")
+}
+
+fn main() {
+    let depot = Location::from_lat_lon(50.8503, 4.3517);
+    let mut locations = vec![depot];
+    locations.extend(
+        CUSTOMERS
+            .iter()
+            .map(|&(_, lat, lon, _)| Location::from_lat_lon(lat, lon)),
+    );
+
+    let services = CUSTOMERS
+        .iter()
+        .enumerate()
+        .map(|(index, &(name, _, _, availability))| {
+            let mut builder = ServiceBuilder::default();
+            builder.set_external_id(name.to_owned());
+            builder.set_location_id(index + 1);
+            builder.set_service_duration(SignedDuration::from_mins(30));
+            if let Some((start, end)) = availability {
+                builder.set_time_window(TimeWindow::new(Some(at(start)), Some(at(end))));
+            }
+            builder.build()
+        })
+        .collect();
+
+    let shifts = [
+        ("morning", "07:30", "13:00"),
+        ("afternoon", "12:00", "18:30"),
+    ];
+    let vehicles = shifts
+        .iter()
+        .map(|&(name, start, end)| {
+            let mut shift = VehicleShiftBuilder::default();
+            shift.set_earliest_start(at(start));
+            shift.set_latest_end(at(end));
+            shift.set_maximum_working_duration(SignedDuration::from_hours(5));
+
+            let mut builder = VehicleBuilder::default();
+            builder.set_vehicle_id(format!("technician_{name}"));
+            builder.set_profile_id(0);
+            builder.set_depot_location_id(0);
+            builder.set_vehicle_shift(shift.build());
+            builder.build()
+        })
+        .collect();
+
+    let mut builder = VehicleRoutingProblemBuilder::default();
+    builder.set_distance_method(DistanceMethod::Haversine);
+    builder.set_vehicle_profiles(vec![VehicleProfile::new(
+        String::from("crow_flies"),
+        TravelMatrices::from_haversine(&locations),
+    )]);
+    builder.set_locations(locations);
+    builder.set_services(services);
+    builder.set_fleet(Fleet::Finite(vehicles));
+
+    let problem = builder.build().expect("Expected a valid problem");
+    let solution = common::solve(Arc::new(problem), None);
+
+    common::print_solution(&solution);
+}
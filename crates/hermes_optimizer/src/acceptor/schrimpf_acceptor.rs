@@ -42,9 +42,14 @@ impl AcceptSolution for SchrimpfAcceptor {
         let worst_current_solution = current_solutions.iter().max_by_key(|s| s.score);
 
         if let Some(worst_solution) = worst_current_solution {
-            let threshold = self.compute_threshold(&context);
-
-            let new_score = worst_solution.score + Score::soft(threshold);
+            // The threshold applies to the level the solutions are first ranked by
+            let threshold = Score::soft(self.compute_threshold(&context));
+            let threshold = worst_solution
+                .score
+                .leading_objective_level()
+                .map_or(threshold, |level| threshold.at_objective_level(level));
+
+            let new_score = worst_solution.score + threshold;
             if score < &new_score {
                 return true;
             }
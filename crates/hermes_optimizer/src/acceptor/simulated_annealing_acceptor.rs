@@ -109,6 +109,16 @@ impl AcceptSolution for SimulatedAnnealingAcceptor {
         let delta = if score.hard_score > best.score.hard_score {
             // Hard constraint violation - use a large penalty
             score.hard_score - best.score.hard_score
+        } else if let Some(objective_delta) = score
+            .objective_scores
+            .iter()
+            .zip(&best.score.objective_scores)
+            .map(|(level_score, best_level_score)| level_score - best_level_score)
+            .find(|delta| *delta != 0.0)
+        {
+            // The first objective level that differs ranks the solutions of a lexicographic
+            // objective
+            objective_delta
         } else {
             score.soft_score - best.score.soft_score
         };
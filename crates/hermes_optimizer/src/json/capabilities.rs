@@ -0,0 +1,26 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Features of the JSON problems accepted by this build of the solver, clients can adapt their
+/// payloads instead of failing on older deployments
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename = "SolverCapabilities")]
+pub struct JsonSolverCapabilities {
+    /// Pickup and delivery pairs, only available to the benchmark parsers for now
+    pub shipments: bool,
+    /// Breaks of the drivers during their shifts
+    pub breaks: bool,
+    /// Several trips per vehicle, see `reload_duration` and `maximum_trips`
+    pub multi_trip: bool,
+    /// Search statistics of the running jobs, requires the `statistics` feature
+    pub statistics: bool,
+}
+
+impl JsonSolverCapabilities {
+    pub const CURRENT: JsonSolverCapabilities = JsonSolverCapabilities {
+        shipments: false,
+        breaks: false,
+        multi_trip: true,
+        statistics: cfg!(feature = "statistics"),
+    };
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    json::initial_solution::{InitialSolutionError, JsonInitialSolution},
+    solver::{alns::AlnsCheckpoint, solver_params::SolverParamsError},
+};
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("Failed to access checkpoint file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed checkpoint file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Checkpoint was written for problem {0}")]
+    ProblemMismatch(String),
+
+    #[error("Invalid solution in checkpoint: {0}")]
+    InvalidSolution(#[from] InitialSolutionError),
+
+    #[error("Unknown cancelled job {0} in checkpoint")]
+    UnknownJob(String),
+
+    #[error("Invalid solver parameters: {0}")]
+    InvalidParams(#[from] SolverParamsError),
+}
+
+/// State of the search written to disk, see `Alns::resume_from_checkpoint`
+#[derive(Serialize, Deserialize)]
+pub struct JsonCheckpoint {
+    pub problem_id: String,
+
+    /// Iterations completed by each search thread
+    pub iterations: usize,
+
+    /// Seed of the random number generator for the next run
+    pub seed: u64,
+    pub solutions: Vec<JsonInitialSolution>,
+    pub ruin_weights: FxHashMap<String, f64>,
+    pub recreate_weights: FxHashMap<String, f64>,
+
+    /// IDs of the jobs cancelled during the search, see `Alns::cancel_jobs`
+    #[serde(default)]
+    pub cancelled_jobs: Vec<String>,
+}
+
+impl From<&AlnsCheckpoint> for JsonCheckpoint {
+    fn from(checkpoint: &AlnsCheckpoint) -> Self {
+        let problem = checkpoint
+            .solutions
+            .first()
+            .map(|accepted_solution| accepted_solution.solution.problem());
+        let mut cancelled_jobs = problem
+            .map(|problem| {
+                checkpoint
+                    .cancelled_jobs
+                    .iter()
+                    .map(|&job_id| problem.job(job_id).external_id().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        cancelled_jobs.sort_unstable();
+
+        JsonCheckpoint {
+            problem_id: checkpoint.problem_id.clone(),
+            iterations: checkpoint.iterations,
+            seed: checkpoint.seed,
+            solutions: checkpoint
+                .solutions
+                .iter()
+                .map(|accepted_solution| JsonInitialSolution::from(&accepted_solution.solution))
+                .collect(),
+            ruin_weights: checkpoint.ruin_weights.named_weights(),
+            recreate_weights: checkpoint.recreate_weights.named_weights(),
+            cancelled_jobs,
+        }
+    }
+}
+
+impl JsonCheckpoint {
+    pub fn read(path: &Path) -> Result<Self, CheckpointError> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Writes to a temporary file first so that an interrupted write never corrupts the previous checkpoint
+    pub fn write(&self, path: &Path) -> Result<(), CheckpointError> {
+        let tmp_path = path.with_extension("tmp");
+        serde_json::to_writer(BufWriter::new(File::create(&tmp_path)?), self)?;
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            job::{ActivityId, JobIdx},
+            vehicle::VehicleBuilder,
+        },
+        solver::{
+            alns::Alns,
+            ruin::ruin_strategy::RuinStrategy,
+            solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+            solver_params::SolverParams,
+        },
+        test_utils,
+    };
+
+    use super::JsonCheckpoint;
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let locations = test_utils::create_location_grid(1, 4);
+        let services = test_utils::create_basic_services(vec![1, 2, 3]);
+
+        let mut builder = VehicleBuilder::default();
+        builder.set_depot_location_id(0);
+        builder.set_vehicle_id(String::from("vehicle"));
+        builder.set_profile_id(0);
+
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations,
+            services,
+            vec![builder.build()],
+        ));
+
+        let mut solution = WorkingSolution::new(Arc::clone(&problem));
+        solution.assign_route(
+            RouteIdx::new(0),
+            &[
+                ActivityId::Service(JobIdx::new(1)),
+                ActivityId::Service(JobIdx::new(0)),
+            ],
+        );
+
+        let alns = Alns::new(SolverParams::default(), Arc::clone(&problem)).unwrap();
+        alns.set_initial_solution(solution);
+
+        let mut checkpoint = alns.checkpoint();
+        checkpoint.iterations = 500;
+        let mut ruin_weights = checkpoint.ruin_weights.named_weights();
+        ruin_weights.insert(RuinStrategy::RuinShaw.to_string(), 2.5);
+        checkpoint.ruin_weights.set_named_weights(&ruin_weights);
+
+        let path = std::env::temp_dir().join("hermes_test_resume_from_checkpoint.json");
+        JsonCheckpoint::from(&checkpoint).write(&path).unwrap();
+
+        let resumed =
+            Alns::resume_from_checkpoint(SolverParams::default(), Arc::clone(&problem), &path)
+                .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let resumed_checkpoint = resumed.checkpoint();
+        assert_eq!(resumed_checkpoint.iterations, 500);
+        assert_eq!(resumed_checkpoint.solutions.len(), 1);
+        assert_eq!(
+            resumed_checkpoint.solutions[0].score,
+            checkpoint.solutions[0].score
+        );
+        assert!(
+            resumed_checkpoint.solutions[0]
+                .solution
+                .is_identical(&checkpoint.solutions[0].solution)
+        );
+        assert_eq!(
+            resumed_checkpoint.ruin_weights.named_weights()[&RuinStrategy::RuinShaw.to_string()],
+            2.5
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::f64::consts::PI;
+
+use hermes_matrix_providers::travel_matrix_provider::TravelMatrixProvider;
+use jiff::{SignedDuration, Timestamp};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+use crate::{
+    json::types::{
+        JsonLocation, JsonService, JsonVehicle, JsonVehicleProfile, JsonVehicleRoutingProblem,
+        JsonVehicleShift,
+    },
+    problem::time_window::TimeWindow,
+};
+
+const KM_PER_DEGREE: f64 = 111.32;
+
+const PROFILE_ID: &str = "car";
+
+/// How the customers are spread around the depot
+#[derive(Clone, Copy, Debug)]
+pub enum CustomerDistribution {
+    Uniform,
+
+    /// Customers around `clusters` centers, each spread over a tenth of the instance radius
+    Clustered {
+        clusters: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum DemandDistribution {
+    Constant(f64),
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+
+    /// Mostly small demands with a few large ones, between `min` and `max`
+    Skewed {
+        min: f64,
+        max: f64,
+    },
+}
+
+/// Synthetic instance, the same parameters and seed always generate the same instance
+#[derive(Clone, Debug)]
+pub struct GeneratorParams {
+    pub seed: u64,
+    pub customers: usize,
+    pub distribution: CustomerDistribution,
+
+    /// Depot of every vehicle, `[lon, lat]`
+    pub center: [f64; 2],
+    pub radius_km: f64,
+
+    pub horizon_start: Timestamp,
+    pub horizon: SignedDuration,
+
+    /// Width of the time windows as a share of the horizon, from 0 (tight) to 1 (the whole
+    /// horizon), no time windows when `None`
+    pub time_window_tightness: Option<f64>,
+    pub service_duration: SignedDuration,
+    pub demand: DemandDistribution,
+
+    /// Capacities of the vehicles, cycled over the fleet to mix vehicle types
+    pub vehicle_capacities: Vec<f64>,
+    pub vehicles: usize,
+    pub speed_kmh: f64,
+}
+
+pub fn generate_instance(params: &GeneratorParams) -> JsonVehicleRoutingProblem {
+    let mut rng = SmallRng::seed_from_u64(params.seed);
+
+    let cluster_centers = match params.distribution {
+        CustomerDistribution::Uniform => vec![],
+        CustomerDistribution::Clustered { clusters } => (0..clusters.max(1))
+            .map(|_| random_point(&mut rng, params.center, params.radius_km))
+            .collect(),
+    };
+
+    let mut locations = vec![JsonLocation {
+        coordinates: params.center,
+    }];
+
+    let services = (0..params.customers)
+        .map(|index| {
+            let coordinates = if cluster_centers.is_empty() {
+                random_point(&mut rng, params.center, params.radius_km)
+            } else {
+                let center = cluster_centers[rng.random_range(0..cluster_centers.len())];
+                random_point(&mut rng, center, params.radius_km / 10.0)
+            };
+
+            locations.push(JsonLocation { coordinates });
+
+            JsonService {
+                id: format!("customer_{}", index + 1),
+                location_id: index + 1,
+                duration: Some(params.service_duration),
+                demand: Some(vec![random_demand(&mut rng, params.demand)]),
+                skills: None,
+                tags: None,
+                time_windows: params
+                    .time_window_tightness
+                    .map(|tightness| vec![random_time_window(&mut rng, params, tightness)]),
+                service_type: None,
+                position_preference: None,
+                stop_sequence: None,
+            }
+        })
+        .collect();
+
+    let vehicles = (0..params.vehicles)
+        .map(|index| JsonVehicle {
+            id: format!("vehicle_{}", index + 1),
+            profile: PROFILE_ID.to_owned(),
+            shift: Some(JsonVehicleShift {
+                earliest_start: Some(params.horizon_start),
+                latest_start: None,
+                latest_end: Some(params.horizon_start + params.horizon),
+                maximum_transport_duration: None,
+                maximum_working_duration: None,
+            }),
+            capacity: params
+                .vehicle_capacities
+                .get(index % params.vehicle_capacities.len().max(1))
+                .map(|&capacity| vec![capacity]),
+            depot_location_id: Some(0),
+            depot_duration: None,
+            should_return_to_depot: Some(true),
+            return_depot_duration: None,
+            skills: None,
+            allowed_tags: None,
+            forbidden_tags: None,
+            maximum_activities: None,
+            minimum_activities: None,
+            minimum_utilization: None,
+            reload_duration: None,
+            maximum_reloads: None,
+            maximum_trips: None,
+            maximum_trips_per_day: None,
+            battery: None,
+            axle_loads: None,
+            fixed_cost: None,
+            cost_per_km: None,
+            cost_per_hour: None,
+            territory: None,
+        })
+        .collect();
+
+    JsonVehicleRoutingProblem {
+        id: Some(format!("generated_{}_{}", params.customers, params.seed)),
+        locations,
+        services,
+        vehicle_profiles: vec![JsonVehicleProfile {
+            id: PROFILE_ID.to_owned(),
+            cost_provider: Some(TravelMatrixProvider::AsTheCrowFlies {
+                speed_kmh: params.speed_kmh,
+            }),
+            routing: None,
+        }],
+        vehicles,
+        relations: None,
+        depots: None,
+        depot_inventories: None,
+        charging_stations: None,
+        tolerances: None,
+        constraint_overrides: None,
+    }
+}
+
+/// Uniform point in the disc of `radius_km` around `center`
+fn random_point(rng: &mut SmallRng, center: [f64; 2], radius_km: f64) -> [f64; 2] {
+    let distance = radius_km * rng.random::<f64>().sqrt();
+    let angle = rng.random_range(0.0..2.0 * PI);
+
+    let [lon, lat] = center;
+    let dlat = distance * angle.sin() / KM_PER_DEGREE;
+    let dlon = distance * angle.cos() / (KM_PER_DEGREE * lat.to_radians().cos());
+
+    [lon + dlon, lat + dlat]
+}
+
+fn random_demand(rng: &mut SmallRng, distribution: DemandDistribution) -> f64 {
+    match distribution {
+        DemandDistribution::Constant(demand) => demand,
+        DemandDistribution::Uniform { min, max } => {
+            (min + (max - min) * rng.random::<f64>()).round()
+        }
+        DemandDistribution::Skewed { min, max } => {
+            (min + (max - min) * rng.random::<f64>().powi(3)).round()
+        }
+    }
+}
+
+fn random_time_window(rng: &mut SmallRng, params: &GeneratorParams, tightness: f64) -> TimeWindow {
+    let horizon = params.horizon.as_secs_f64();
+    let width = horizon * tightness.clamp(0.0, 1.0);
+    let start = rng.random_range(0.0..=horizon - width);
+
+    TimeWindow::new(
+        Some(params.horizon_start + SignedDuration::from_secs_f64(start.floor())),
+        Some(params.horizon_start + SignedDuration::from_secs_f64((start + width).floor())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use super::{CustomerDistribution, DemandDistribution, GeneratorParams, generate_instance};
+
+    fn params(seed: u64) -> GeneratorParams {
+        GeneratorParams {
+            seed,
+            customers: 50,
+            distribution: CustomerDistribution::Clustered { clusters: 3 },
+            center: [4.35, 50.85],
+            radius_km: 20.0,
+            horizon_start: "2025-06-10T08:00:00Z".parse().unwrap(),
+            horizon: SignedDuration::from_hours(10),
+            time_window_tightness: Some(0.2),
+            service_duration: SignedDuration::from_mins(5),
+            demand: DemandDistribution::Uniform {
+                min: 1.0,
+                max: 10.0,
+            },
+            vehicle_capacities: vec![50.0, 100.0],
+            vehicles: 5,
+            speed_kmh: 40.0,
+        }
+    }
+
+    #[test]
+    fn test_generate_instance() {
+        let instance = generate_instance(&params(1));
+
+        assert_eq!(instance.locations.len(), 51);
+        assert_eq!(instance.services.len(), 50);
+        assert_eq!(instance.vehicles.len(), 5);
+        assert_eq!(instance.vehicles[1].capacity, Some(vec![100.0]));
+
+        let horizon_start = params(1).horizon_start;
+        let horizon_end = horizon_start + params(1).horizon;
+        for service in &instance.services {
+            let demand = service.demand.as_ref().unwrap()[0];
+            assert!((1.0..=10.0).contains(&demand));
+
+            let time_window = &service.time_windows.as_ref().unwrap()[0];
+            assert!(time_window.earliest().unwrap() >= horizon_start);
+            assert!(time_window.latest().unwrap() <= horizon_end);
+        }
+    }
+
+    #[test]
+    fn test_generate_instance_seed() {
+        let serialize = |seed| serde_json::to_string(&generate_instance(&params(seed))).unwrap();
+
+        assert_eq!(serialize(1), serialize(1));
+        assert_ne!(serialize(1), serialize(2));
+    }
+}
@@ -0,0 +1,195 @@
+use serde_json::{Value, json};
+
+use crate::{
+    json::types::FromProblem,
+    problem::{
+        external_id::ExternalActivityId,
+        job::{ActivityId, Job},
+        location::LocationIdx,
+        vehicle_routing_problem::VehicleRoutingProblem,
+    },
+    solver::solution::{route::WorkingSolutionRoute, working_solution::WorkingSolution},
+};
+
+/// Converts a solution into a GeoJSON `FeatureCollection` which can be displayed on a map.
+///
+/// Each route is a `LineString` from its start depot to its end depot, or to its last activity for the
+/// open routes of the vehicles which don't return to the depot. Each activity and each unassigned job
+/// is a `Point`. Coordinates are `[lon, lat]`.
+pub fn solution_to_geojson(solution: &WorkingSolution) -> Value {
+    let problem = solution.problem();
+    let mut features = vec![];
+
+    for (_, route) in solution.ordered_non_empty_routes() {
+        features.push(route_feature(problem, route));
+        features.extend(stop_features(problem, route));
+    }
+
+    for job_id in solution.ordered_unassigned_jobs() {
+        let activity_ids = match problem.job(job_id) {
+            Job::Service(_) => vec![ActivityId::Service(job_id)],
+            Job::Shipment(_) => vec![
+                ActivityId::ShipmentPickup(job_id),
+                ActivityId::ShipmentDelivery(job_id),
+            ],
+        };
+
+        for activity_id in activity_ids {
+            features.push(point_feature(
+                problem,
+                problem.job_activity(activity_id).location_id(),
+                json!({
+                    "activity_id": ExternalActivityId::from_problem(activity_id, problem).to_string(),
+                    "unassigned": true,
+                }),
+            ));
+        }
+    }
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+fn coordinates(problem: &VehicleRoutingProblem, location_id: LocationIdx) -> [f64; 2] {
+    let location = problem.location(location_id);
+    [location.lon(), location.lat()]
+}
+
+fn point_feature(
+    problem: &VehicleRoutingProblem,
+    location_id: LocationIdx,
+    properties: Value,
+) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": coordinates(problem, location_id),
+        },
+        "properties": properties,
+    })
+}
+
+fn route_feature(problem: &VehicleRoutingProblem, route: &WorkingSolutionRoute) -> Value {
+    let coordinates = route
+        .compute_location_ids(problem)
+        .into_iter()
+        .map(|location_id| coordinates(problem, location_id))
+        .collect::<Vec<_>>();
+
+    let arrival_times = route
+        .optimized_activities_iter()
+        .map(|activity| activity.arrival_time())
+        .collect::<Vec<_>>();
+
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": coordinates,
+        },
+        "properties": {
+            "vehicle_id": route.vehicle(problem).external_id(),
+            "open_route": !route.has_end(problem),
+            "start_time": route.optimized_start(problem),
+            "end_time": route.end(problem),
+            "distance": route.distance(problem),
+            "arrival_times": arrival_times,
+            // Load after each step, the first step is the start depot and the last one is the end of
+            // the route
+            "load_profile": route.current_loads(),
+        },
+    })
+}
+
+fn stop_features<'a>(
+    problem: &'a VehicleRoutingProblem,
+    route: &'a WorkingSolutionRoute,
+) -> impl Iterator<Item = Value> + 'a {
+    let vehicle_id = route.vehicle(problem).external_id();
+
+    route
+        .optimized_activities_iter()
+        .enumerate()
+        .map(move |(position, activity)| {
+            let activity_id = activity.activity_id();
+
+            point_feature(
+                problem,
+                problem.job_activity(activity_id).location_id(),
+                json!({
+                    "activity_id": ExternalActivityId::from_problem(activity_id, problem).to_string(),
+                    "vehicle_id": vehicle_id,
+                    "position": position,
+                    "arrival_time": activity.arrival_time(),
+                    "departure_time": activity.departure_time(),
+                    "waiting_duration": activity.waiting_duration(),
+                    "load": route.load_at(position),
+                }),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            job::{ActivityId, JobIdx},
+            vehicle::VehicleBuilder,
+        },
+        solver::solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+        test_utils,
+    };
+
+    use super::solution_to_geojson;
+
+    #[test]
+    fn test_solution_to_geojson() {
+        let locations = test_utils::create_location_grid(1, 4);
+        let services = test_utils::create_basic_services(vec![1, 2, 3]);
+
+        let mut builder = VehicleBuilder::default();
+        builder.set_depot_location_id(0);
+        builder.set_vehicle_id(String::from("vehicle"));
+        builder.set_profile_id(0);
+
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations,
+            services,
+            vec![builder.build()],
+        ));
+
+        let mut solution = WorkingSolution::new(problem);
+        solution.assign_route(
+            RouteIdx::new(0),
+            &[
+                ActivityId::Service(JobIdx::new(1)),
+                ActivityId::Service(JobIdx::new(0)),
+            ],
+        );
+
+        let geojson = solution_to_geojson(&solution);
+        let features = geojson["features"].as_array().unwrap();
+
+        // One route, two stops and one unassigned job
+        assert_eq!(features.len(), 4);
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+        assert_eq!(features[0]["properties"]["vehicle_id"], "vehicle");
+        // The vehicle doesn't return to the depot, the line ends at the last stop
+        assert_eq!(features[0]["properties"]["open_route"], true);
+        assert_eq!(
+            features[0]["geometry"]["coordinates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(features[1]["geometry"]["type"], "Point");
+        assert_eq!(features[1]["properties"]["position"], 0);
+        assert_eq!(features[3]["properties"]["unassigned"], true);
+    }
+}
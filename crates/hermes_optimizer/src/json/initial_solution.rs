@@ -0,0 +1,417 @@
+use std::sync::Arc;
+
+use fxhash::FxHashSet;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    json::types::FromProblem,
+    problem::{
+        external_id::ExternalActivityId, job::ActivityId,
+        vehicle_routing_problem::VehicleRoutingProblem,
+    },
+    solver::solution::working_solution::WorkingSolution,
+    utils::enumerate_idx::EnumerateIdx,
+};
+
+/// Existing solution used to warm start the solver instead of constructing one from scratch
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename = "InitialSolution")]
+pub struct JsonInitialSolution {
+    pub routes: Vec<JsonInitialRoute>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename = "InitialRoute")]
+pub struct JsonInitialRoute {
+    pub vehicle_id: String,
+    pub activity_ids: Vec<ExternalActivityId>,
+
+    /// Number of activities at the start of the route which are already dispatched, the solver will not
+    /// move or unassign them
+    pub locked_activities: Option<usize>,
+}
+
+#[derive(Error, Debug)]
+pub enum InitialSolutionError {
+    #[error("Unknown vehicle ID {0}")]
+    UnknownVehicle(String),
+
+    #[error("Vehicle {0} has more routes than available")]
+    TooManyRoutes(String),
+
+    #[error("Unknown activity {0}")]
+    UnknownActivity(String),
+
+    #[error("Activity {0} is assigned more than once")]
+    DuplicateActivity(String),
+
+    #[error("Shipment {0} must be picked up before being delivered in the same route")]
+    InvalidShipment(String),
+
+    #[error("Vehicle {0} has more locked activities than activities")]
+    InvalidLock(String),
+
+    #[error("Vehicle {0} lacks the skills required by activity {1}")]
+    MissingSkills(String, String),
+
+    #[error("Vehicle {0} does not accept the tags of activity {1}")]
+    ForbiddenTags(String, String),
+}
+
+impl From<&WorkingSolution> for JsonInitialSolution {
+    fn from(solution: &WorkingSolution) -> Self {
+        let problem = solution.problem();
+
+        JsonInitialSolution {
+            routes: solution
+                .ordered_non_empty_routes()
+                .into_iter()
+                .map(|(_, route)| JsonInitialRoute {
+                    vehicle_id: route.vehicle(problem).external_id().to_owned(),
+                    activity_ids: route
+                        .activity_ids()
+                        .iter()
+                        .map(|&activity_id| ExternalActivityId::from_problem(activity_id, problem))
+                        .collect(),
+                    locked_activities: Some(route.locked_len()).filter(|&len| len > 0),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JsonInitialSolution {
+    /// Converts the initial solution into a working solution of `problem`.
+    ///
+    /// Jobs which are not part of any route are left unassigned. The feasibility of the routes is not checked,
+    /// the solver will repair them during the search.
+    pub fn build_solution(
+        &self,
+        problem: Arc<VehicleRoutingProblem>,
+    ) -> Result<WorkingSolution, InitialSolutionError> {
+        let mut solution = WorkingSolution::new(Arc::clone(&problem));
+        let mut assigned_activities: FxHashSet<ActivityId> = FxHashSet::default();
+
+        for route in &self.routes {
+            let vehicle_id = problem
+                .vehicles()
+                .iter()
+                .enumerate_idx()
+                .find(|(_, vehicle)| vehicle.external_id() == route.vehicle_id)
+                .map(|(vehicle_id, _)| vehicle_id)
+                .ok_or_else(|| InitialSolutionError::UnknownVehicle(route.vehicle_id.clone()))?;
+
+            let route_id = solution
+                .routes()
+                .iter()
+                .enumerate_idx()
+                .find(|(_, route)| route.vehicle_id() == vehicle_id && route.is_empty())
+                .map(|(route_id, _)| route_id)
+                .ok_or_else(|| InitialSolutionError::TooManyRoutes(route.vehicle_id.clone()))?;
+
+            let vehicle = problem.vehicle(vehicle_id);
+            let mut activity_ids = Vec::with_capacity(route.activity_ids.len());
+            let mut pending_pickups: FxHashSet<ActivityId> = FxHashSet::default();
+
+            for external_id in &route.activity_ids {
+                let activity_id = external_id.activity_id(problem.jobs()).ok_or_else(|| {
+                    InitialSolutionError::UnknownActivity(external_id.to_string())
+                })?;
+
+                // The skill and tag constraints rely on the insertions never breaking them
+                let job = problem.job(activity_id.job_id());
+                if !job.skills_satisfied_by_vehicle(vehicle) {
+                    return Err(InitialSolutionError::MissingSkills(
+                        route.vehicle_id.clone(),
+                        external_id.to_string(),
+                    ));
+                }
+
+                if !job.tags_allowed_by_vehicle(vehicle) {
+                    return Err(InitialSolutionError::ForbiddenTags(
+                        route.vehicle_id.clone(),
+                        external_id.to_string(),
+                    ));
+                }
+
+                if !assigned_activities.insert(activity_id) {
+                    return Err(InitialSolutionError::DuplicateActivity(
+                        external_id.to_string(),
+                    ));
+                }
+
+                match activity_id {
+                    ActivityId::ShipmentPickup(_) => {
+                        pending_pickups.insert(activity_id);
+                    }
+                    ActivityId::ShipmentDelivery(job_id) => {
+                        if !pending_pickups.remove(&ActivityId::ShipmentPickup(job_id)) {
+                            return Err(InitialSolutionError::InvalidShipment(
+                                external_id.external_id().to_owned(),
+                            ));
+                        }
+                    }
+                    ActivityId::Service(_) => {}
+                }
+
+                activity_ids.push(activity_id);
+            }
+
+            if let Some(pickup) = pending_pickups.iter().next() {
+                return Err(InitialSolutionError::InvalidShipment(
+                    problem.job(pickup.job_id()).external_id().to_owned(),
+                ));
+            }
+
+            solution.assign_route(route_id, &activity_ids);
+
+            if let Some(locked_activities) = route.locked_activities {
+                if locked_activities > activity_ids.len() {
+                    return Err(InitialSolutionError::InvalidLock(route.vehicle_id.clone()));
+                }
+
+                solution.lock_route(route_id, locked_activities);
+            }
+        }
+
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            external_id::ExternalActivityId,
+            job::{ActivityId, JobIdx},
+            service::ServiceBuilder,
+            vehicle::VehicleBuilder,
+            vehicle_routing_problem::VehicleRoutingProblem,
+        },
+        solver::solution::route_id::RouteIdx,
+        test_utils,
+    };
+
+    use super::{InitialSolutionError, JsonInitialRoute, JsonInitialSolution};
+
+    fn create_problem() -> Arc<VehicleRoutingProblem> {
+        let locations = test_utils::create_location_grid(1, 4);
+        let services = test_utils::create_basic_services(vec![1, 2, 3]);
+
+        let vehicles = (0..2)
+            .map(|index| {
+                let mut builder = VehicleBuilder::default();
+                builder.set_depot_location_id(0);
+                builder.set_vehicle_id(format!("vehicle_{index}"));
+                builder.set_profile_id(0);
+                builder.build()
+            })
+            .collect();
+
+        Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ))
+    }
+
+    #[test]
+    fn test_build_initial_solution() {
+        let initial_solution = JsonInitialSolution {
+            routes: vec![JsonInitialRoute {
+                vehicle_id: String::from("vehicle_1"),
+                activity_ids: vec![
+                    ExternalActivityId::Service(String::from("2")),
+                    ExternalActivityId::Service(String::from("0")),
+                ],
+                locked_activities: Some(1),
+            }],
+        };
+
+        let solution = initial_solution.build_solution(create_problem()).unwrap();
+
+        assert_eq!(
+            solution.route(RouteIdx::new(1)).activity_ids(),
+            &[
+                ActivityId::Service(JobIdx::new(2)),
+                ActivityId::Service(JobIdx::new(0))
+            ]
+        );
+        assert_eq!(solution.route(RouteIdx::new(1)).locked_len(), 1);
+        assert!(solution.route(RouteIdx::new(0)).is_empty());
+        assert!(solution.is_unassigned(JobIdx::new(1)));
+        assert_eq!(solution.unassigned_jobs().len(), 1);
+    }
+
+    #[test]
+    fn test_stable_serialization_order() {
+        let locations = test_utils::create_location_grid(1, 6);
+        let services = test_utils::create_basic_services(vec![1, 2, 3, 4, 5]);
+        let vehicles = ["vehicle_b", "vehicle_a"]
+            .into_iter()
+            .map(|vehicle_id| {
+                let mut builder = VehicleBuilder::default();
+                builder.set_depot_location_id(0);
+                builder.set_vehicle_id(vehicle_id.to_owned());
+                builder.set_profile_id(0);
+                builder.build()
+            })
+            .collect();
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ));
+
+        let initial_solution = JsonInitialSolution {
+            routes: vec![
+                JsonInitialRoute {
+                    vehicle_id: String::from("vehicle_b"),
+                    activity_ids: vec![ExternalActivityId::Service(String::from("3"))],
+                    locked_activities: None,
+                },
+                JsonInitialRoute {
+                    vehicle_id: String::from("vehicle_a"),
+                    activity_ids: vec![
+                        ExternalActivityId::Service(String::from("1")),
+                        ExternalActivityId::Service(String::from("0")),
+                    ],
+                    locked_activities: None,
+                },
+            ],
+        };
+
+        let solution = initial_solution.build_solution(problem).unwrap();
+
+        // Routes by vehicle ID whatever the order of the fleet
+        let serialized = JsonInitialSolution::from(&solution);
+        assert_eq!(
+            serialized
+                .routes
+                .iter()
+                .map(|route| route.vehicle_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["vehicle_a", "vehicle_b"]
+        );
+        assert_eq!(
+            serialized.routes[0]
+                .activity_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["service_1", "service_0"]
+        );
+
+        assert_eq!(
+            solution.ordered_unassigned_jobs(),
+            vec![JobIdx::new(2), JobIdx::new(4)]
+        );
+    }
+
+    #[test]
+    fn test_build_initial_solution_errors() {
+        let unknown_vehicle = JsonInitialSolution {
+            routes: vec![JsonInitialRoute {
+                vehicle_id: String::from("unknown"),
+                activity_ids: vec![],
+                locked_activities: None,
+            }],
+        };
+        assert!(matches!(
+            unknown_vehicle.build_solution(create_problem()),
+            Err(InitialSolutionError::UnknownVehicle(_))
+        ));
+
+        let duplicate_activity = JsonInitialSolution {
+            routes: vec![
+                JsonInitialRoute {
+                    vehicle_id: String::from("vehicle_0"),
+                    activity_ids: vec![ExternalActivityId::Service(String::from("1"))],
+                    locked_activities: None,
+                },
+                JsonInitialRoute {
+                    vehicle_id: String::from("vehicle_1"),
+                    activity_ids: vec![ExternalActivityId::Service(String::from("1"))],
+                    locked_activities: None,
+                },
+            ],
+        };
+        assert!(matches!(
+            duplicate_activity.build_solution(create_problem()),
+            Err(InitialSolutionError::DuplicateActivity(_))
+        ));
+
+        let unknown_activity = JsonInitialSolution {
+            routes: vec![JsonInitialRoute {
+                vehicle_id: String::from("vehicle_0"),
+                activity_ids: vec![ExternalActivityId::Service(String::from("42"))],
+                locked_activities: None,
+            }],
+        };
+        assert!(matches!(
+            unknown_activity.build_solution(create_problem()),
+            Err(InitialSolutionError::UnknownActivity(_))
+        ));
+    }
+
+    fn create_problem_with_requirements() -> Arc<VehicleRoutingProblem> {
+        let locations = test_utils::create_location_grid(1, 3);
+
+        let services = [(vec!["fridge"], vec![]), (vec![], vec!["fragile"])]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (skills, tags))| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(index + 1);
+                builder.set_external_id(index.to_string());
+                builder.set_skills(skills.into_iter().map(String::from).collect());
+                builder.set_tags(tags.into_iter().map(String::from).collect());
+                builder.build()
+            })
+            .collect();
+
+        let mut builder = VehicleBuilder::default();
+        builder.set_depot_location_id(0);
+        builder.set_vehicle_id(String::from("vehicle_0"));
+        builder.set_profile_id(0);
+        builder.set_forbidden_tags(vec![String::from("fragile")]);
+
+        Arc::new(test_utils::create_test_problem(
+            locations,
+            services,
+            vec![builder.build()],
+        ))
+    }
+
+    #[test]
+    fn test_build_initial_solution_missing_skills() {
+        let initial_solution = JsonInitialSolution {
+            routes: vec![JsonInitialRoute {
+                vehicle_id: String::from("vehicle_0"),
+                activity_ids: vec![ExternalActivityId::Service(String::from("0"))],
+                locked_activities: None,
+            }],
+        };
+
+        assert!(matches!(
+            initial_solution.build_solution(create_problem_with_requirements()),
+            Err(InitialSolutionError::MissingSkills(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_build_initial_solution_forbidden_tags() {
+        let initial_solution = JsonInitialSolution {
+            routes: vec![JsonInitialRoute {
+                vehicle_id: String::from("vehicle_0"),
+                activity_ids: vec![ExternalActivityId::Service(String::from("1"))],
+                locked_activities: None,
+            }],
+        };
+
+        assert!(matches!(
+            initial_solution.build_solution(create_problem_with_requirements()),
+            Err(InitialSolutionError::ForbiddenTags(_, _))
+        ));
+    }
+}
@@ -1,2 +1,11 @@
+pub mod capabilities;
+pub mod checkpoint;
+pub mod generator;
+pub mod geojson;
+pub mod initial_solution;
+pub mod plan_tournament;
+pub mod problem_update;
 pub mod schema;
+pub mod sensitivity;
+pub mod service_duration_history;
 pub mod types;
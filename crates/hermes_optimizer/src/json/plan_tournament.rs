@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use jiff::SignedDuration;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    json::initial_solution::JsonInitialSolution,
+    problem::{meters::Meters, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        constraints::constraint::Constraint,
+        score::{Score, ScoreAnalysis},
+    },
+};
+
+/// Score of a valid plan with its breakdown by constraint
+#[derive(Serialize, JsonSchema)]
+pub struct PlanScore {
+    /// 1 for the best plan, plans are ranked like the solutions of the search: fewer unassigned
+    /// jobs first, then the best score. Tied plans share the same rank
+    pub rank: usize,
+    pub score: Score,
+    pub score_analysis: ScoreAnalysis,
+    /// No hard constraint is violated
+    pub feasible: bool,
+    pub distance: Meters,
+    pub duration: SignedDuration,
+    pub vehicles: usize,
+    pub unassigned_jobs: usize,
+}
+
+impl PlanScore {
+    fn ranking_key(&self) -> (usize, Score) {
+        (self.unassigned_jobs, self.score)
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PlanEvaluationResult {
+    Scored(PlanScore),
+    /// The plan does not match the problem, e.g. an unknown vehicle or a job assigned twice
+    Invalid {
+        error: String,
+    },
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PlanEvaluation {
+    /// Index of the plan in the request
+    pub plan_index: usize,
+    #[serde(flatten)]
+    pub result: PlanEvaluationResult,
+}
+
+/// Scores each plan of `problem` with `constraints` without searching, the problem and its
+/// matrices are shared by every evaluation.
+///
+/// The evaluations are returned best first, the invalid plans last in the order of the request
+pub fn evaluate_plans(
+    problem: &Arc<VehicleRoutingProblem>,
+    plans: &[JsonInitialSolution],
+    constraints: &[Constraint],
+) -> Vec<PlanEvaluation> {
+    let results = plans
+        .par_iter()
+        .map(|plan| {
+            plan.build_solution(Arc::clone(problem))
+                .map(|solution| {
+                    let (score, score_analysis) = solution.compute_solution_score(constraints);
+                    PlanScore {
+                        rank: 0,
+                        score,
+                        score_analysis,
+                        feasible: !score.is_infeasible(),
+                        distance: solution.distance(),
+                        duration: solution
+                            .routes()
+                            .iter()
+                            .filter(|route| !route.is_empty())
+                            .fold(SignedDuration::ZERO, |duration, route| {
+                                duration + route.duration(problem)
+                            }),
+                        vehicles: solution.non_empty_routes_count(),
+                        unassigned_jobs: solution.unassigned_jobs().len(),
+                    }
+                })
+                .map_err(|error| error.to_string())
+        })
+        .collect::<Vec<_>>();
+
+    let (mut scored, invalid): (Vec<_>, Vec<_>) = results
+        .into_iter()
+        .enumerate()
+        .partition(|(_, result)| result.is_ok());
+
+    // Stable, tied plans stay in the order of the request
+    scored.sort_by_key(|(_, result)| result.as_ref().map(PlanScore::ranking_key).ok());
+
+    let mut previous = None;
+    let mut evaluations = Vec::with_capacity(plans.len());
+    for (index, (plan_index, result)) in scored.into_iter().enumerate() {
+        let Ok(mut plan) = result else {
+            continue;
+        };
+
+        plan.rank = match previous {
+            Some((key, rank)) if key == plan.ranking_key() => rank,
+            _ => index + 1,
+        };
+        previous = Some((plan.ranking_key(), plan.rank));
+
+        evaluations.push(PlanEvaluation {
+            plan_index,
+            result: PlanEvaluationResult::Scored(plan),
+        });
+    }
+
+    evaluations.extend(invalid.into_iter().filter_map(|(plan_index, result)| {
+        result.err().map(|error| PlanEvaluation {
+            plan_index,
+            result: PlanEvaluationResult::Invalid { error },
+        })
+    }));
+
+    evaluations
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        json::initial_solution::{JsonInitialRoute, JsonInitialSolution},
+        problem::{external_id::ExternalActivityId, vehicle::VehicleBuilder},
+        solver::constraints::constraint_set::ConstraintSet,
+        test_utils,
+    };
+
+    use super::{PlanEvaluationResult, evaluate_plans};
+
+    fn plan(vehicle_id: &str, services: &[&str]) -> JsonInitialSolution {
+        JsonInitialSolution {
+            routes: vec![JsonInitialRoute {
+                vehicle_id: vehicle_id.to_owned(),
+                activity_ids: services
+                    .iter()
+                    .map(|&id| ExternalActivityId::Service(id.to_owned()))
+                    .collect(),
+                locked_activities: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_plans_are_ranked_by_score() {
+        let locations = test_utils::create_location_grid(1, 4);
+        let services = test_utils::create_basic_services(vec![1, 2, 3]);
+        let mut builder = VehicleBuilder::default();
+        builder.set_depot_location_id(0);
+        builder.set_vehicle_id(String::from("vehicle"));
+        builder.set_profile_id(0);
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations,
+            services,
+            vec![builder.build()],
+        ));
+
+        let plans = vec![
+            plan("vehicle", &["2", "0", "1"]),
+            plan("unknown", &["0"]),
+            plan("vehicle", &["0", "1", "2"]),
+            plan("vehicle", &["0", "1"]),
+        ];
+
+        let evaluations = evaluate_plans(&problem, &plans, ConstraintSet::default().constraints());
+
+        assert_eq!(
+            evaluations
+                .iter()
+                .map(|evaluation| evaluation.plan_index)
+                .collect::<Vec<_>>(),
+            vec![2, 0, 3, 1]
+        );
+        assert!(matches!(
+            &evaluations[0].result,
+            PlanEvaluationResult::Scored(plan) if plan.rank == 1 && plan.feasible
+        ));
+        assert!(matches!(
+            &evaluations[3].result,
+            PlanEvaluationResult::Invalid { .. }
+        ));
+    }
+}
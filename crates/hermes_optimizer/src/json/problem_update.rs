@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use fxhash::FxHashSet;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    json::{
+        initial_solution::JsonInitialSolution,
+        types::{JsonLocation, JsonService, JsonVehicleRoutingProblem},
+    },
+    problem::{external_id::ExternalActivityId, relation::ExternalRelation},
+};
+
+/// Changes applied to a problem while its routes are being executed
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename = "ProblemUpdate")]
+pub struct JsonProblemUpdate {
+    /// Locations appended after the existing ones, new services can reference them by index
+    pub new_locations: Option<Vec<JsonLocation>>,
+    pub new_services: Option<Vec<JsonService>>,
+    pub cancelled_jobs: Option<Vec<String>>,
+
+    /// Vehicles which broke down, their remaining jobs are unassigned
+    pub unavailable_vehicles: Option<Vec<String>>,
+
+    /// Number of activities already dispatched at the start of the route of each vehicle, they are locked
+    /// when re-optimizing
+    pub dispatched_activities: Option<HashMap<String, usize>>,
+}
+
+#[derive(Error, Debug)]
+pub enum ProblemUpdateError {
+    #[error("Unknown job ID {0}")]
+    UnknownJob(String),
+
+    #[error("Unknown vehicle ID {0}")]
+    UnknownVehicle(String),
+
+    #[error("Duplicate job ID {0}")]
+    DuplicateJobId(String),
+}
+
+impl JsonProblemUpdate {
+    fn cancelled_jobs(&self) -> FxHashSet<&str> {
+        self.cancelled_jobs
+            .iter()
+            .flatten()
+            .map(|id| id.as_str())
+            .collect()
+    }
+
+    fn unavailable_vehicles(&self) -> FxHashSet<&str> {
+        self.unavailable_vehicles
+            .iter()
+            .flatten()
+            .map(|id| id.as_str())
+            .collect()
+    }
+
+    /// Whether the update only cancels jobs, which a running search can do without a restart, see
+    /// `Solver::cancel_jobs`
+    pub fn only_cancels_jobs(&self) -> bool {
+        self.new_locations.is_none()
+            && self.new_services.is_none()
+            && self.unavailable_vehicles.is_none()
+            && self.dispatched_activities.is_none()
+    }
+
+    /// IDs of the cancelled jobs
+    pub fn cancelled_job_ids(&self) -> impl Iterator<Item = &str> {
+        self.cancelled_jobs.iter().flatten().map(String::as_str)
+    }
+
+    /// Applies the update to the problem input.
+    ///
+    /// Relations referencing a cancelled job or an unavailable vehicle are dropped.
+    pub fn apply(&self, problem: &mut JsonVehicleRoutingProblem) -> Result<(), ProblemUpdateError> {
+        let cancelled_jobs = self.cancelled_jobs();
+        let unavailable_vehicles = self.unavailable_vehicles();
+
+        for &job_id in &cancelled_jobs {
+            if !problem.services.iter().any(|service| service.id == job_id) {
+                return Err(ProblemUpdateError::UnknownJob(job_id.to_owned()));
+            }
+        }
+
+        for vehicle_id in unavailable_vehicles.iter().copied().chain(
+            self.dispatched_activities
+                .iter()
+                .flat_map(|dispatched| dispatched.keys().map(|id| id.as_str())),
+        ) {
+            if !problem
+                .vehicles
+                .iter()
+                .any(|vehicle| vehicle.id == vehicle_id)
+            {
+                return Err(ProblemUpdateError::UnknownVehicle(vehicle_id.to_owned()));
+            }
+        }
+
+        let mut job_ids = problem
+            .services
+            .iter()
+            .map(|service| service.id.as_str())
+            .filter(|job_id| !cancelled_jobs.contains(job_id))
+            .collect::<FxHashSet<_>>();
+
+        for service in self.new_services.iter().flatten() {
+            if !job_ids.insert(service.id.as_str()) {
+                return Err(ProblemUpdateError::DuplicateJobId(service.id.clone()));
+            }
+        }
+
+        problem
+            .services
+            .retain(|service| !cancelled_jobs.contains(service.id.as_str()));
+        problem
+            .vehicles
+            .retain(|vehicle| !unavailable_vehicles.contains(vehicle.id.as_str()));
+
+        if let Some(relations) = &mut problem.relations {
+            relations.retain(|relation| {
+                !references_any(relation, &cancelled_jobs, &unavailable_vehicles)
+            });
+        }
+
+        if let Some(new_locations) = &self.new_locations {
+            problem.locations.extend(new_locations.iter().cloned());
+        }
+
+        if let Some(new_services) = &self.new_services {
+            problem.services.extend(new_services.iter().cloned());
+        }
+
+        Ok(())
+    }
+
+    /// Removes the cancelled jobs and the routes of unavailable vehicles from a previous solution and locks
+    /// the dispatched activities
+    pub fn apply_to_solution(&self, solution: &mut JsonInitialSolution) {
+        let cancelled_jobs = self.cancelled_jobs();
+        let unavailable_vehicles = self.unavailable_vehicles();
+
+        solution
+            .routes
+            .retain(|route| !unavailable_vehicles.contains(route.vehicle_id.as_str()));
+
+        for route in &mut solution.routes {
+            let is_cancelled = |activity_id: &ExternalActivityId| {
+                cancelled_jobs.contains(activity_id.external_id())
+            };
+
+            // Cancelled jobs which were locked are not locked anymore
+            if let Some(locked_activities) = &mut route.locked_activities {
+                *locked_activities -= route.activity_ids[..*locked_activities]
+                    .iter()
+                    .filter(|activity_id| is_cancelled(activity_id))
+                    .count();
+            }
+
+            route
+                .activity_ids
+                .retain(|activity_id| !is_cancelled(activity_id));
+
+            if let Some(&dispatched) = self
+                .dispatched_activities
+                .as_ref()
+                .and_then(|dispatched| dispatched.get(&route.vehicle_id))
+            {
+                let locked_activities = route.locked_activities.unwrap_or(0).max(dispatched);
+                route.locked_activities = Some(locked_activities.min(route.activity_ids.len()));
+            }
+        }
+    }
+}
+
+fn references_any(
+    relation: &ExternalRelation,
+    job_ids: &FxHashSet<&str>,
+    vehicle_ids: &FxHashSet<&str>,
+) -> bool {
+    let is_vehicle_referenced = |vehicle_id: &Option<String>| {
+        vehicle_id
+            .as_deref()
+            .is_some_and(|vehicle_id| vehicle_ids.contains(vehicle_id))
+    };
+
+    match relation {
+        ExternalRelation::InSameRoute(relation) => {
+            is_vehicle_referenced(&relation.vehicle_id)
+                || relation.ids.iter().any(|id| job_ids.contains(id.as_str()))
+        }
+        ExternalRelation::NotInSameRoute(relation) => {
+            relation.ids.iter().any(|id| job_ids.contains(id.as_str()))
+        }
+        ExternalRelation::InSequence(relation) | ExternalRelation::InDirectSequence(relation) => {
+            is_vehicle_referenced(&relation.vehicle_id)
+                || relation
+                    .ids
+                    .iter()
+                    .any(|id| job_ids.contains(id.external_id()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        json::{
+            initial_solution::{JsonInitialRoute, JsonInitialSolution},
+            types::{JsonService, JsonVehicle, JsonVehicleRoutingProblem},
+        },
+        problem::external_id::ExternalActivityId,
+    };
+
+    use super::{JsonProblemUpdate, ProblemUpdateError};
+
+    fn service(id: &str) -> JsonService {
+        JsonService {
+            id: id.to_owned(),
+            location_id: 0,
+            duration: None,
+            demand: None,
+            skills: None,
+            tags: None,
+            time_windows: None,
+            service_type: None,
+            position_preference: None,
+            stop_sequence: None,
+        }
+    }
+
+    fn vehicle(id: &str) -> JsonVehicle {
+        JsonVehicle {
+            id: id.to_owned(),
+            profile: String::from("profile"),
+            shift: None,
+            capacity: None,
+            depot_location_id: None,
+            depot_duration: None,
+            should_return_to_depot: None,
+            return_depot_duration: None,
+            skills: None,
+            allowed_tags: None,
+            forbidden_tags: None,
+            maximum_activities: None,
+            minimum_activities: None,
+            minimum_utilization: None,
+            reload_duration: None,
+            maximum_reloads: None,
+            maximum_trips: None,
+            maximum_trips_per_day: None,
+            battery: None,
+            axle_loads: None,
+            fixed_cost: None,
+            cost_per_km: None,
+            cost_per_hour: None,
+            territory: None,
+        }
+    }
+
+    fn problem() -> JsonVehicleRoutingProblem {
+        JsonVehicleRoutingProblem {
+            id: None,
+            locations: vec![],
+            services: vec![service("a"), service("b")],
+            vehicle_profiles: vec![],
+            vehicles: vec![vehicle("v1"), vehicle("v2")],
+            relations: None,
+            depots: None,
+            depot_inventories: None,
+            charging_stations: None,
+            tolerances: None,
+            constraint_overrides: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_update() {
+        let mut problem = problem();
+        let update = JsonProblemUpdate {
+            new_locations: None,
+            new_services: Some(vec![service("c")]),
+            cancelled_jobs: Some(vec![String::from("a")]),
+            unavailable_vehicles: Some(vec![String::from("v2")]),
+            dispatched_activities: None,
+        };
+
+        assert!(!update.only_cancels_jobs());
+        update.apply(&mut problem).unwrap();
+
+        let service_ids = problem
+            .services
+            .iter()
+            .map(|service| service.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(service_ids, vec!["b", "c"]);
+        assert_eq!(problem.vehicles.len(), 1);
+        assert_eq!(problem.vehicles[0].id, "v1");
+
+        let mut solution = JsonInitialSolution {
+            routes: vec![
+                JsonInitialRoute {
+                    vehicle_id: String::from("v1"),
+                    activity_ids: vec![ExternalActivityId::Service(String::from("a"))],
+                    locked_activities: None,
+                },
+                JsonInitialRoute {
+                    vehicle_id: String::from("v2"),
+                    activity_ids: vec![ExternalActivityId::Service(String::from("b"))],
+                    locked_activities: None,
+                },
+            ],
+        };
+
+        update.apply_to_solution(&mut solution);
+
+        assert_eq!(solution.routes.len(), 1);
+        assert!(solution.routes[0].activity_ids.is_empty());
+    }
+
+    #[test]
+    fn test_apply_update_unknown_job() {
+        let mut problem = problem();
+        let update = JsonProblemUpdate {
+            new_locations: None,
+            new_services: None,
+            cancelled_jobs: Some(vec![String::from("unknown")]),
+            unavailable_vehicles: None,
+            dispatched_activities: None,
+        };
+
+        assert!(update.only_cancels_jobs());
+        assert!(matches!(
+            update.apply(&mut problem),
+            Err(ProblemUpdateError::UnknownJob(_))
+        ));
+    }
+}
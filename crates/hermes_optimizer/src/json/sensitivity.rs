@@ -0,0 +1,165 @@
+use jiff::SignedDuration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::json::{problem_update::JsonProblemUpdate, types::JsonVehicleRoutingProblem};
+
+/// Change of the fleet whose effect on the cost is measured by re-solving the problem
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename = "SensitivityScenario"
+)]
+pub enum SensitivityScenario {
+    /// One more vehicle identical to the last one of the fleet
+    AddVehicle,
+
+    /// Without the last vehicle of the fleet, the relations referencing it are dropped
+    RemoveVehicle,
+
+    /// Longer (positive `delta`) or shorter (negative `delta`) shifts, the latest end and the
+    /// maximum working duration of every vehicle are moved by `delta`
+    ShiftLength { delta: SignedDuration },
+}
+
+#[derive(Error, Debug)]
+pub enum SensitivityError {
+    #[error("The fleet has no vehicle to copy")]
+    EmptyFleet,
+
+    #[error("The fleet cannot lose its only vehicle")]
+    SingleVehicle,
+}
+
+impl SensitivityScenario {
+    /// Fleet ± 1 vehicle and shifts relaxed and tightened by `shift_delta`
+    pub fn defaults(shift_delta: SignedDuration) -> Vec<SensitivityScenario> {
+        vec![
+            SensitivityScenario::AddVehicle,
+            SensitivityScenario::RemoveVehicle,
+            SensitivityScenario::ShiftLength { delta: shift_delta },
+            SensitivityScenario::ShiftLength {
+                delta: -shift_delta,
+            },
+        ]
+    }
+
+    pub fn apply(&self, problem: &mut JsonVehicleRoutingProblem) -> Result<(), SensitivityError> {
+        match self {
+            SensitivityScenario::AddVehicle => {
+                let mut vehicle = problem
+                    .vehicles
+                    .last()
+                    .cloned()
+                    .ok_or(SensitivityError::EmptyFleet)?;
+
+                vehicle.id = format!("{}_{}", vehicle.id, problem.vehicles.len());
+                problem.vehicles.push(vehicle);
+            }
+            SensitivityScenario::RemoveVehicle => {
+                if problem.vehicles.len() < 2 {
+                    return Err(SensitivityError::SingleVehicle);
+                }
+
+                let vehicle_id = problem.vehicles[problem.vehicles.len() - 1].id.clone();
+                let update = JsonProblemUpdate {
+                    new_locations: None,
+                    new_services: None,
+                    cancelled_jobs: None,
+                    unavailable_vehicles: Some(vec![vehicle_id]),
+                    dispatched_activities: None,
+                };
+
+                update
+                    .apply(problem)
+                    .expect("the removed vehicle is part of the fleet");
+            }
+            SensitivityScenario::ShiftLength { delta } => {
+                for shift in problem
+                    .vehicles
+                    .iter_mut()
+                    .filter_map(|vehicle| vehicle.shift.as_mut())
+                {
+                    if let Some(latest_end) = &mut shift.latest_end {
+                        *latest_end += *delta;
+                    }
+
+                    if let Some(maximum_working_duration) = &mut shift.maximum_working_duration {
+                        *maximum_working_duration =
+                            (*maximum_working_duration + *delta).max(SignedDuration::ZERO);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::{SignedDuration, Timestamp};
+
+    use crate::json::{
+        generator::{CustomerDistribution, DemandDistribution, GeneratorParams, generate_instance},
+        types::JsonVehicleRoutingProblem,
+    };
+
+    use super::SensitivityScenario;
+
+    fn problem(vehicles: usize) -> JsonVehicleRoutingProblem {
+        generate_instance(&GeneratorParams {
+            seed: 0,
+            customers: 10,
+            distribution: CustomerDistribution::Uniform,
+            center: [4.35, 50.85],
+            radius_km: 10.0,
+            horizon_start: "2025-06-10T08:00:00Z".parse().unwrap(),
+            horizon: SignedDuration::from_hours(8),
+            time_window_tightness: None,
+            service_duration: SignedDuration::from_mins(5),
+            demand: DemandDistribution::Constant(1.0),
+            vehicle_capacities: vec![10.0],
+            vehicles,
+            speed_kmh: 40.0,
+        })
+    }
+
+    #[test]
+    fn test_fleet_scenarios() {
+        let mut larger = problem(2);
+        SensitivityScenario::AddVehicle.apply(&mut larger).unwrap();
+        assert_eq!(larger.vehicles.len(), 3);
+        assert_eq!(larger.vehicles[2].id, "vehicle_2_2");
+
+        let mut smaller = problem(2);
+        SensitivityScenario::RemoveVehicle
+            .apply(&mut smaller)
+            .unwrap();
+        assert_eq!(smaller.vehicles.len(), 1);
+        assert_eq!(smaller.vehicles[0].id, "vehicle_1");
+
+        assert!(
+            SensitivityScenario::RemoveVehicle
+                .apply(&mut smaller)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_shift_length_scenario() {
+        let mut problem = problem(1);
+        SensitivityScenario::ShiftLength {
+            delta: SignedDuration::from_hours(-1),
+        }
+        .apply(&mut problem)
+        .unwrap();
+
+        assert_eq!(
+            problem.vehicles[0].shift.as_ref().unwrap().latest_end,
+            Some("2025-06-10T15:00:00Z".parse::<Timestamp>().unwrap())
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+
+use fxhash::{FxHashMap, FxHashSet};
+use jiff::SignedDuration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::json::types::JsonVehicleRoutingProblem;
+
+/// Samples kept per location and vehicle type, the oldest ones are dropped first so that the
+/// learned durations follow the changes of the customers
+const MAX_SAMPLES: usize = 200;
+
+/// Coordinates are rounded to 5 decimals, about a meter, to match the locations across problems
+const COORDINATES_PRECISION: f64 = 1e5;
+
+/// Actual duration of a completed stop
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JsonServiceDurationSample {
+    /// `[lon, lat]` of the stop, as in the `locations` of the problems
+    pub coordinates: [f64; 2],
+    /// Profile of the vehicle that served the stop
+    pub vehicle_type: String,
+    pub duration: SignedDuration,
+}
+
+#[derive(Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JsonServiceDurationCalibration {
+    /// Between 0 and 1, 0.8 by default, higher percentiles plan longer but safer stops
+    pub percentile: Option<f64>,
+    /// Samples needed before the learned duration overrides the planned one, 5 by default
+    pub min_samples: Option<usize>,
+}
+
+#[derive(Hash, PartialEq, Eq, Clone)]
+struct SampleKey {
+    location: [i64; 2],
+    vehicle_type: String,
+}
+
+impl SampleKey {
+    fn new(coordinates: [f64; 2], vehicle_type: &str) -> Self {
+        SampleKey {
+            location: coordinates.map(|value| (value * COORDINATES_PRECISION).round() as i64),
+            vehicle_type: vehicle_type.to_owned(),
+        }
+    }
+}
+
+/// Service durations observed per location and vehicle type
+#[derive(Default)]
+pub struct ServiceDurationHistory {
+    samples: FxHashMap<SampleKey, VecDeque<SignedDuration>>,
+}
+
+impl ServiceDurationHistory {
+    pub fn add_sample(&mut self, sample: &JsonServiceDurationSample) {
+        let samples = self
+            .samples
+            .entry(SampleKey::new(sample.coordinates, &sample.vehicle_type))
+            .or_default();
+
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(sample.duration);
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.samples.values().map(VecDeque::len).sum()
+    }
+
+    /// Nearest rank `percentile` of the durations at `coordinates` for `vehicle_type`, `None` with
+    /// fewer than `min_samples` samples
+    pub fn learned_duration(
+        &self,
+        coordinates: [f64; 2],
+        vehicle_type: &str,
+        percentile: f64,
+        min_samples: usize,
+    ) -> Option<SignedDuration> {
+        let samples = self
+            .samples
+            .get(&SampleKey::new(coordinates, vehicle_type))?;
+
+        if samples.is_empty() || samples.len() < min_samples {
+            return None;
+        }
+
+        let mut durations = samples.iter().copied().collect::<Vec<_>>();
+        durations.sort_unstable();
+
+        let rank = (percentile.clamp(0.0, 1.0) * durations.len() as f64).ceil() as usize;
+        Some(durations[rank.saturating_sub(1)])
+    }
+
+    /// Overrides the duration of the services of `problem` with the learned durations, returns the
+    /// number of services changed.
+    ///
+    /// A service has a single duration, the longest learned duration of the vehicle types of the
+    /// problem is used so that the planned arrivals hold whichever vehicle serves it
+    pub fn calibrate(
+        &self,
+        problem: &mut JsonVehicleRoutingProblem,
+        calibration: &JsonServiceDurationCalibration,
+    ) -> usize {
+        let percentile = calibration.percentile.unwrap_or(0.8);
+        let min_samples = calibration.min_samples.unwrap_or(5);
+
+        let vehicle_types = problem
+            .vehicles
+            .iter()
+            .map(|vehicle| vehicle.profile.as_str())
+            .collect::<FxHashSet<_>>();
+
+        let mut calibrated = 0;
+        for service in &mut problem.services {
+            let Some(location) = problem.locations.get(service.location_id) else {
+                continue;
+            };
+
+            let learned_duration = vehicle_types
+                .iter()
+                .filter_map(|vehicle_type| {
+                    self.learned_duration(
+                        location.coordinates,
+                        vehicle_type,
+                        percentile,
+                        min_samples,
+                    )
+                })
+                .max();
+
+            if let Some(duration) = learned_duration {
+                service.duration = Some(duration);
+                calibrated += 1;
+            }
+        }
+
+        calibrated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use super::{JsonServiceDurationSample, ServiceDurationHistory};
+
+    fn sample(coordinates: [f64; 2], minutes: i64) -> JsonServiceDurationSample {
+        JsonServiceDurationSample {
+            coordinates,
+            vehicle_type: String::from("car"),
+            duration: SignedDuration::from_mins(minutes),
+        }
+    }
+
+    #[test]
+    fn test_learned_duration_percentile() {
+        let mut history = ServiceDurationHistory::default();
+        for minutes in [4, 10, 6, 8, 2] {
+            history.add_sample(&sample([4.35, 50.85], minutes));
+        }
+
+        assert_eq!(
+            history.learned_duration([4.35, 50.85], "car", 0.8, 5),
+            Some(SignedDuration::from_mins(8))
+        );
+        assert_eq!(
+            history.learned_duration([4.35, 50.85], "car", 0.5, 5),
+            Some(SignedDuration::from_mins(6))
+        );
+
+        // Same location within a meter
+        assert_eq!(
+            history.learned_duration([4.350001, 50.85], "car", 1.0, 5),
+            Some(SignedDuration::from_mins(10))
+        );
+
+        assert_eq!(history.learned_duration([4.35, 50.85], "car", 0.8, 6), None);
+        assert_eq!(
+            history.learned_duration([4.35, 50.85], "truck", 0.8, 1),
+            None
+        );
+    }
+}
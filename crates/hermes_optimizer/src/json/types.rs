@@ -8,28 +8,43 @@ use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use crate::problem::{
+    axle_loads::{Axle, AxleLoads},
+    battery::Battery,
     capacity::Capacity,
+    charging_station::{ChargeCurve, ChargingStation},
+    constraint_overrides::ConstraintOverrides,
+    depot::Depot,
+    depot_inventory::DepotInventory,
     external_id::{ExternalActivityId, ExternalJobId},
     fleet::Fleet,
     job::ActivityId,
-    location::Location,
+    kilograms::Kilograms,
+    location::{Location, LocationIdx},
+    position_preference::PositionPreference,
     relation::{
         ExternalInDirectSequenceRelation, ExternalInSameRouteRelation,
         ExternalNotInSameRouteRelation, ExternalRelation, Relation,
     },
     service::{Service, ServiceBuilder, ServiceType},
+    stop_sequence::StopSequence,
+    territory::Territory,
     time_window::TimeWindow,
+    tolerances::Tolerances,
     travel_cost_matrix::TravelMatrices,
+    travel_time_provider::{TravelTimeOptions, TravelTimeProviders, fetch_travel_matrices},
     vehicle::{Vehicle, VehicleBuilder, VehicleShift},
     vehicle_profile::VehicleProfile,
-    vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
+    vehicle_routing_problem::{
+        VehicleRoutingProblem, VehicleRoutingProblemBuilder, VehicleRoutingProblemError,
+    },
 };
+use crate::solver::constraints::constraint_set::ConstraintSet;
 
 pub trait FromProblem<T> {
     fn from_problem(value: T, problem: &VehicleRoutingProblem) -> Self;
 }
 
-#[derive(Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename = "VehicleRoutingProblem")]
 pub struct JsonVehicleRoutingProblem {
     pub id: Option<String>,
@@ -38,9 +53,24 @@ pub struct JsonVehicleRoutingProblem {
     pub vehicle_profiles: Vec<JsonVehicleProfile>,
     pub vehicles: Vec<JsonVehicle>,
     pub relations: Option<Vec<ExternalRelation>>,
+
+    /// Opening hours and loading windows of the depots, the routes must start and end within them
+    pub depots: Option<Vec<JsonDepot>>,
+
+    /// Stock of the depots, the deliveries of the routes starting from a depot can't exceed it
+    pub depot_inventories: Option<Vec<JsonDepotInventory>>,
+
+    /// Stations where the vehicles with a battery can charge
+    pub charging_stations: Option<Vec<JsonChargingStation>>,
+
+    /// Margins of the time window and capacity checks
+    pub tolerances: Option<Tolerances>,
+
+    /// Constraints of the solver switched on or off for this problem
+    pub constraint_overrides: Option<ConstraintOverrides>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields, rename = "Service")]
 pub struct JsonService {
     pub id: String,
@@ -48,10 +78,18 @@ pub struct JsonService {
     pub duration: Option<SignedDuration>,
     pub demand: Option<Vec<f64>>,
     pub skills: Option<Vec<String>>,
+
+    /// Tags used by vehicles to allow or forbid the service, e.g. "bulky"
+    pub tags: Option<Vec<String>>,
     pub time_windows: Option<Vec<TimeWindow>>,
 
     #[serde(rename = "type")]
     pub service_type: Option<ServiceType>,
+
+    pub position_preference: Option<PositionPreference>,
+
+    /// Serve the service before (`first`) or after (`last`) every other stop of its route
+    pub stop_sequence: Option<StopSequence>,
 }
 
 impl FromProblem<&Service> for JsonService {
@@ -68,13 +106,22 @@ impl FromProblem<&Service> for JsonService {
                     .map(|skill| skill.to_string())
                     .collect::<Vec<_>>(),
             ),
+            tags: Some(
+                value
+                    .tags()
+                    .iter()
+                    .map(|tag| tag.to_string())
+                    .collect::<Vec<_>>(),
+            ),
             time_windows: Some(value.time_windows().to_vec()),
             service_type: value.service_type().into(),
+            position_preference: value.position_preference().copied(),
+            stop_sequence: value.stop_sequence(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields, rename = "Location")]
 pub struct JsonLocation {
     pub coordinates: [f64; 2],
@@ -94,14 +141,213 @@ impl From<&JsonLocation> for geo::Point {
     }
 }
 
-#[derive(Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "Depot")]
+pub struct JsonDepot {
+    pub location_id: usize,
+    pub opening_hours: Option<Vec<TimeWindow>>,
+
+    /// Vehicles leave the depot within one of these windows, defaults to the opening hours
+    pub loading_windows: Option<Vec<TimeWindow>>,
+}
+
+impl From<JsonDepot> for Depot {
+    fn from(value: JsonDepot) -> Self {
+        Depot::new(
+            value.location_id.into(),
+            value.opening_hours.unwrap_or_default(),
+            value.loading_windows.unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "DepotInventory")]
+pub struct JsonDepotInventory {
+    pub location_id: usize,
+    pub stock: Vec<f64>,
+}
+
+impl From<JsonDepotInventory> for DepotInventory {
+    fn from(value: JsonDepotInventory) -> Self {
+        DepotInventory::new(value.location_id.into(), Capacity::from_vec(value.stock))
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "ChargingStation")]
+pub struct JsonChargingStation {
+    pub id: String,
+    pub location_id: usize,
+
+    /// Charging power in kW
+    pub power: f64,
+
+    /// Power by state of charge, replaces `power`, e.g. charging slows down above 80%
+    pub charge_curve: Option<Vec<JsonChargeCurvePoint>>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "ChargeCurvePoint")]
+pub struct JsonChargeCurvePoint {
+    /// Between 0 and 1, the power applies from this state of charge up to the next point
+    pub state_of_charge: f64,
+    pub power: f64,
+}
+
+impl From<JsonChargingStation> for ChargingStation {
+    fn from(value: JsonChargingStation) -> Self {
+        let charge_curve = match value.charge_curve {
+            Some(points) => ChargeCurve::new(
+                points
+                    .into_iter()
+                    .map(|point| (point.state_of_charge, point.power))
+                    .collect(),
+            ),
+            None => ChargeCurve::constant(value.power),
+        };
+
+        ChargingStation::new(value.id, value.location_id.into(), charge_curve)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "Battery")]
+pub struct JsonBattery {
+    /// Usable energy in kWh, the vehicle leaves fully charged
+    pub capacity: f64,
+    pub consumption_per_km: f64,
+
+    /// Consumed while waiting or serving an activity
+    pub idle_consumption_per_hour: Option<f64>,
+
+    /// Charging stops the vehicle can make at each station, defaults to 1
+    pub maximum_charges: Option<usize>,
+}
+
+impl From<JsonBattery> for Battery {
+    fn from(value: JsonBattery) -> Self {
+        Battery::new(
+            value.capacity,
+            value.consumption_per_km,
+            value.idle_consumption_per_hour.unwrap_or(0.0),
+            value.maximum_charges.unwrap_or(1),
+        )
+    }
+}
+
+impl From<&Battery> for JsonBattery {
+    fn from(value: &Battery) -> Self {
+        JsonBattery {
+            capacity: value.capacity(),
+            consumption_per_km: value.consumption_per_km(),
+            idle_consumption_per_hour: Some(value.idle_consumption_per_hour()),
+            maximum_charges: Some(value.maximum_charges()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "Axle")]
+pub struct JsonAxle {
+    /// Weight on the axle in kg when the truck is empty
+    pub empty_weight: Kilograms,
+    pub maximum_weight: Kilograms,
+}
+
+/// The load is assumed to be spread evenly over its loading meters against the front wall of the
+/// loading space, a Euro pallet takes 0.4 loading meters
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, rename = "AxleLoads")]
+pub struct JsonAxleLoads {
+    /// Index of the capacity dimension holding the weight in kg
+    pub weight_dimension: usize,
+
+    /// Index of the capacity dimension holding the loading meters, its capacity is the length of
+    /// the loading space
+    pub loading_meters_dimension: usize,
+
+    /// Distance between the front and the rear axles in meters
+    pub wheelbase: f64,
+
+    /// Distance from the front axle to the front wall of the loading space in meters, negative
+    /// when the loading space starts before the front axle
+    pub loading_space_offset: f64,
+    pub front_axle: JsonAxle,
+    pub rear_axle: JsonAxle,
+}
+
+impl From<JsonAxle> for Axle {
+    fn from(value: JsonAxle) -> Self {
+        Axle {
+            empty_weight: value.empty_weight,
+            maximum_weight: value.maximum_weight,
+        }
+    }
+}
+
+impl From<&Axle> for JsonAxle {
+    fn from(value: &Axle) -> Self {
+        JsonAxle {
+            empty_weight: value.empty_weight,
+            maximum_weight: value.maximum_weight,
+        }
+    }
+}
+
+impl From<JsonAxleLoads> for AxleLoads {
+    fn from(value: JsonAxleLoads) -> Self {
+        AxleLoads::new(
+            value.weight_dimension,
+            value.loading_meters_dimension,
+            value.wheelbase,
+            value.loading_space_offset,
+            value.front_axle.into(),
+            value.rear_axle.into(),
+        )
+    }
+}
+
+impl From<&AxleLoads> for JsonAxleLoads {
+    fn from(value: &AxleLoads) -> Self {
+        JsonAxleLoads {
+            weight_dimension: value.weight_dimension(),
+            loading_meters_dimension: value.loading_meters_dimension(),
+            wheelbase: value.wheelbase(),
+            loading_space_offset: value.loading_space_offset(),
+            front_axle: value.front_axle().into(),
+            rear_axle: value.rear_axle().into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields, rename = "VehicleProfile")]
 pub struct JsonVehicleProfile {
     pub id: String,
-    pub cost_provider: TravelMatrixProvider,
+
+    /// When not set, the matrices are fetched from the travel time provider registered under the
+    /// id of the profile, e.g. the `car` or `truck` routing profiles loaded by the API
+    #[serde(default)]
+    pub cost_provider: Option<TravelMatrixProvider>,
+
+    /// Routing profile and vehicle dimensions passed to the travel time provider, only used when
+    /// the matrices are fetched from a registered provider
+    #[serde(default)]
+    pub routing: Option<TravelTimeOptions>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+impl JsonVehicleProfile {
+    pub fn cost_provider(&self) -> TravelMatrixProvider {
+        self.cost_provider
+            .clone()
+            .unwrap_or_else(|| TravelMatrixProvider::Provider {
+                name: self.id.clone(),
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields, rename = "Vehicle")]
 pub struct JsonVehicle {
     pub id: String,
@@ -110,10 +356,98 @@ pub struct JsonVehicle {
     pub capacity: Option<Vec<f64>>,
     pub depot_location_id: Option<usize>,
     pub depot_duration: Option<SignedDuration>,
+
+    /// The route ends at the depot when set to true. Otherwise the route is open and ends at its
+    /// last activity, the way back to the depot is neither scheduled nor part of the costs
     pub should_return_to_depot: Option<bool>,
     pub return_depot_duration: Option<SignedDuration>,
     pub skills: Option<Vec<String>>,
+
+    /// When set, the vehicle can only serve jobs whose tags are all in this list
+    pub allowed_tags: Option<Vec<String>>,
+
+    /// The vehicle cannot serve jobs with any of these tags
+    pub forbidden_tags: Option<Vec<String>>,
     pub maximum_activities: Option<usize>,
+
+    /// Dispatching the vehicle for fewer activities is penalized, see the `minimum_utilization`
+    /// constraint
+    pub minimum_activities: Option<usize>,
+
+    /// Dispatching the vehicle with a peak load under this share of its capacity, between 0 and
+    /// 1, is penalized
+    pub minimum_utilization: Option<f64>,
+
+    /// Time to unload and reload at the depot, the vehicle can do several trips when set
+    pub reload_duration: Option<SignedDuration>,
+
+    /// Defaults to 1 when `reload_duration` is set
+    pub maximum_reloads: Option<usize>,
+
+    /// Trips over the whole planning horizon, the reload duration is the turnaround time between
+    /// two trips. Defaults `maximum_reloads` to one less than the trips
+    pub maximum_trips: Option<usize>,
+
+    /// Trips starting on the same UTC day
+    pub maximum_trips_per_day: Option<usize>,
+
+    /// Electric vehicles stop at the charging stations when their battery doesn't last the route
+    pub battery: Option<JsonBattery>,
+
+    /// Axle weight limits of a truck, checked at each stop from the weight and the loading meters
+    /// of its load
+    pub axle_loads: Option<JsonAxleLoads>,
+    pub fixed_cost: Option<f64>,
+    pub cost_per_km: Option<f64>,
+    pub cost_per_hour: Option<f64>,
+
+    /// The vehicle only serves jobs inside this zone, see the `territory` constraint
+    pub territory: Option<JsonTerritory>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(tag = "type", rename_all = "snake_case", rename = "Territory")]
+pub enum JsonTerritory {
+    /// Ring of `[lon, lat]` coordinates
+    Polygon {
+        coordinates: Vec<[f64; 2]>,
+    },
+    Locations {
+        location_ids: Vec<usize>,
+    },
+}
+
+impl From<JsonTerritory> for Territory {
+    fn from(value: JsonTerritory) -> Self {
+        match value {
+            JsonTerritory::Polygon { coordinates } => Territory::from_polygon(geo::Polygon::new(
+                geo::LineString::from(coordinates),
+                vec![],
+            )),
+            JsonTerritory::Locations { location_ids } => {
+                Territory::from_location_ids(location_ids.into_iter().map(LocationIdx::new))
+            }
+        }
+    }
+}
+
+impl From<&Territory> for JsonTerritory {
+    fn from(value: &Territory) -> Self {
+        match value {
+            Territory::Polygon { polygon, .. } => JsonTerritory::Polygon {
+                coordinates: polygon
+                    .exterior()
+                    .coords()
+                    .map(|coord| [coord.x, coord.y])
+                    .collect(),
+            },
+            Territory::Locations(location_ids) => {
+                let mut location_ids: Vec<_> = location_ids.iter().map(|id| id.get()).collect();
+                location_ids.sort_unstable();
+                JsonTerritory::Locations { location_ids }
+            }
+        }
+    }
 }
 
 impl FromProblem<&Vehicle> for JsonVehicle {
@@ -137,12 +471,34 @@ impl FromProblem<&Vehicle> for JsonVehicle {
                     .map(|skill| skill.to_string())
                     .collect::<Vec<_>>(),
             ),
+            allowed_tags: value
+                .allowed_tags()
+                .map(|tags| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>()),
+            forbidden_tags: Some(
+                value
+                    .forbidden_tags()
+                    .iter()
+                    .map(|tag| tag.to_string())
+                    .collect::<Vec<_>>(),
+            ),
             maximum_activities: value.maximum_activities(),
+            minimum_activities: value.minimum_activities(),
+            minimum_utilization: value.minimum_utilization(),
+            reload_duration: value.reload_duration(),
+            maximum_reloads: value.reload_duration().map(|_| value.maximum_reloads()),
+            maximum_trips: value.maximum_trips(),
+            maximum_trips_per_day: value.maximum_trips_per_day(),
+            battery: value.battery().map(JsonBattery::from),
+            axle_loads: value.axle_loads().map(JsonAxleLoads::from),
+            fixed_cost: value.fixed_cost(),
+            cost_per_km: value.cost_per_distance(),
+            cost_per_hour: value.cost_per_duration(),
+            territory: value.territory().map(JsonTerritory::from),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields, rename = "VehicleShift")]
 pub struct JsonVehicleShift {
     pub earliest_start: Option<Timestamp>,
@@ -245,25 +601,98 @@ impl FromProblem<&Relation> for ExternalRelation {
 }
 
 impl JsonVehicleRoutingProblem {
-    #[instrument(skip_all, level = "debug")]
     pub async fn build_problem(
         self,
         client: &TravelMatrixClient<impl MatricesCache>,
     ) -> Result<VehicleRoutingProblem, anyhow::Error> {
-        let mut builder = VehicleRoutingProblemBuilder::default();
+        self.build_problem_with_providers(client, &TravelTimeProviders::default())
+            .await
+    }
 
-        if let Some(id) = self.id {
-            builder.set_id(id);
-        }
+    /// Builds the problem, the profiles using `TravelMatrixProvider::Provider` fetch their matrices
+    /// from the matching provider of `providers`
+    #[instrument(skip_all, level = "debug")]
+    pub async fn build_problem_with_providers(
+        self,
+        client: &TravelMatrixClient<impl MatricesCache>,
+        providers: &TravelTimeProviders,
+    ) -> Result<VehicleRoutingProblem, anyhow::Error> {
+        let vehicle_profiles = self.fetch_vehicle_profiles(client, providers).await?;
+        Ok(self.build_problem_with_profiles(vehicle_profiles)?)
+    }
+
+    /// Fetches the travel matrices of the profiles, they can be shared by the variants of the
+    /// problem with the same locations and profiles
+    pub async fn fetch_vehicle_profiles(
+        &self,
+        client: &TravelMatrixClient<impl MatricesCache>,
+        providers: &TravelTimeProviders,
+    ) -> Result<Vec<VehicleProfile>, anyhow::Error> {
+        let locations = self.problem_locations()?;
 
-        let locations = self
-            .locations
+        let futures = self
+            .vehicle_profiles
             .iter()
-            .map(|location| {
-                Location::from_lat_lon(location.coordinates[1], location.coordinates[0])
+            .map(|profile| async {
+                let travel_matrices = match profile.cost_provider() {
+                    TravelMatrixProvider::Provider { name } => {
+                        let provider = providers.get(&name).ok_or_else(|| {
+                            anyhow::anyhow!("Travel time provider {name} is not registered")
+                        })?;
+                        fetch_travel_matrices(
+                            provider.as_ref(),
+                            &locations,
+                            &profile.routing.clone().unwrap_or_default(),
+                        )
+                        .await?
+                    }
+                    cost_provider => client.fetch_matrix(&locations, cost_provider).await?,
+                };
+                Ok::<
+                    (
+                        String,
+                        hermes_matrix_providers::travel_matrices::TravelMatrices,
+                    ),
+                    anyhow::Error,
+                >((profile.id.clone(), travel_matrices))
             })
             .collect::<Vec<_>>();
 
+        let results = futures::future::try_join_all(futures).await?;
+
+        Ok(results
+            .into_iter()
+            .map(|(id, matrices)| {
+                VehicleProfile::new(id, TravelMatrices::from_travel_matrices(matrices))
+            })
+            .collect())
+    }
+
+    fn problem_locations(&self) -> Result<Vec<Location>, VehicleRoutingProblemError> {
+        self.locations
+            .iter()
+            .enumerate()
+            .map(|(index, location)| {
+                Location::try_from_lat_lon(location.coordinates[1], location.coordinates[0])
+                    .ok_or(VehicleRoutingProblemError::InvalidCoordinates(index))
+            })
+            .collect()
+    }
+
+    /// Builds the problem with the profiles of `fetch_vehicle_profiles`, in the same order as
+    /// `vehicle_profiles`
+    pub fn build_problem_with_profiles(
+        self,
+        vehicle_profiles: Vec<VehicleProfile>,
+    ) -> Result<VehicleRoutingProblem, VehicleRoutingProblemError> {
+        let mut builder = VehicleRoutingProblemBuilder::default();
+
+        let locations = self.problem_locations()?;
+
+        if let Some(id) = self.id {
+            builder.set_id(id);
+        }
+
         let services: Vec<Service> = self
             .services
             .into_iter()
@@ -285,6 +714,10 @@ impl JsonVehicleRoutingProblem {
                     builder.set_skills(skills);
                 }
 
+                if let Some(tags) = service.tags {
+                    builder.set_tags(tags);
+                }
+
                 if let Some(duration) = service.duration {
                     builder.set_service_duration(duration);
                 }
@@ -293,6 +726,14 @@ impl JsonVehicleRoutingProblem {
                     builder.set_time_windows(time_windows);
                 }
 
+                if let Some(position_preference) = service.position_preference {
+                    builder.set_position_preference(position_preference);
+                }
+
+                if let Some(stop_sequence) = service.stop_sequence {
+                    builder.set_stop_sequence(stop_sequence);
+                }
+
                 builder.build()
             })
             .collect();
@@ -341,10 +782,66 @@ impl JsonVehicleRoutingProblem {
                     builder.set_skills(skills);
                 }
 
+                if let Some(allowed_tags) = vehicle.allowed_tags {
+                    builder.set_allowed_tags(allowed_tags);
+                }
+
+                if let Some(forbidden_tags) = vehicle.forbidden_tags {
+                    builder.set_forbidden_tags(forbidden_tags);
+                }
+
                 if let Some(maximum_activities) = vehicle.maximum_activities {
                     builder.set_maximum_activities(maximum_activities);
                 }
 
+                if let Some(minimum_activities) = vehicle.minimum_activities {
+                    builder.set_minimum_activities(minimum_activities);
+                }
+
+                if let Some(minimum_utilization) = vehicle.minimum_utilization {
+                    builder.set_minimum_utilization(minimum_utilization);
+                }
+
+                if let Some(reload_duration) = vehicle.reload_duration {
+                    builder.set_reload_duration(reload_duration);
+                }
+
+                if let Some(maximum_reloads) = vehicle.maximum_reloads {
+                    builder.set_maximum_reloads(maximum_reloads);
+                }
+
+                if let Some(maximum_trips) = vehicle.maximum_trips {
+                    builder.set_maximum_trips(maximum_trips);
+                }
+
+                if let Some(maximum_trips_per_day) = vehicle.maximum_trips_per_day {
+                    builder.set_maximum_trips_per_day(maximum_trips_per_day);
+                }
+
+                if let Some(battery) = vehicle.battery {
+                    builder.set_battery(battery.into());
+                }
+
+                if let Some(axle_loads) = vehicle.axle_loads {
+                    builder.set_axle_loads(axle_loads.into());
+                }
+
+                if let Some(fixed_cost) = vehicle.fixed_cost {
+                    builder.set_fixed_cost(fixed_cost);
+                }
+
+                if let Some(cost_per_km) = vehicle.cost_per_km {
+                    builder.set_cost_per_distance(cost_per_km);
+                }
+
+                if let Some(cost_per_hour) = vehicle.cost_per_hour {
+                    builder.set_cost_per_duration(cost_per_hour);
+                }
+
+                if let Some(territory) = vehicle.territory {
+                    builder.set_territory(territory.into());
+                }
+
                 builder.build()
             })
             .collect();
@@ -353,38 +850,41 @@ impl JsonVehicleRoutingProblem {
             builder.set_external_relations(relations);
         }
 
-        builder.set_services(services);
-        builder.set_fleet(Fleet::Finite(vehicles));
+        if let Some(depots) = self.depots {
+            builder.set_depots(depots.into_iter().map(Depot::from).collect());
+        }
 
-        let futures = self
-            .vehicle_profiles
-            .into_iter()
-            .map(|profile| async {
-                let travel_matrices = client
-                    .fetch_matrix(&locations, profile.cost_provider)
-                    .await?;
-                Ok::<
-                    (
-                        String,
-                        hermes_matrix_providers::travel_matrices::TravelMatrices,
-                    ),
-                    anyhow::Error,
-                >((profile.id, travel_matrices))
-            })
-            .collect::<Vec<_>>();
+        if let Some(depot_inventories) = self.depot_inventories {
+            builder.set_depot_inventories(
+                depot_inventories
+                    .into_iter()
+                    .map(DepotInventory::from)
+                    .collect(),
+            );
+        }
 
-        let results = futures::future::try_join_all(futures).await?;
+        if let Some(charging_stations) = self.charging_stations {
+            builder.set_charging_stations(
+                charging_stations
+                    .into_iter()
+                    .map(ChargingStation::from)
+                    .collect(),
+            );
+        }
 
-        builder.set_vehicle_profiles(
-            results
-                .into_iter()
-                .map(|(id, matrices)| {
-                    VehicleProfile::new(id, TravelMatrices::from_travel_matrices(matrices))
-                })
-                .collect(),
-        );
+        if let Some(tolerances) = self.tolerances {
+            builder.set_tolerances(tolerances);
+        }
+
+        if let Some(constraint_overrides) = self.constraint_overrides {
+            ConstraintSet::default().validate_overrides(&constraint_overrides)?;
+            builder.set_constraint_overrides(constraint_overrides);
+        }
 
+        builder.set_services(services);
+        builder.set_fleet(Fleet::Finite(vehicles));
+        builder.set_vehicle_profiles(vehicle_profiles);
         builder.set_locations(locations);
-        Ok(builder.build()?)
+        builder.build()
     }
 }
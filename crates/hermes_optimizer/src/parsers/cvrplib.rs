@@ -12,34 +12,83 @@ use crate::{
         fleet::Fleet,
         location::Location,
         service::ServiceBuilder,
-        travel_cost_matrix::TravelMatrices,
+        travel_cost_matrix::{DistanceRounding, TravelMatrices},
         vehicle::VehicleBuilder,
         vehicle_profile::VehicleProfile,
         vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
     },
 };
 
-pub struct CVRPLibParser;
+/// Parser of the CVRPLIB instances, the distances follow the convention of the EDGE_WEIGHT_TYPE of
+/// the instance unless `rounding` is set
+#[derive(Default)]
+pub struct CVRPLibParser {
+    pub rounding: Option<DistanceRounding>,
+
+    /// The vehicles don't return to the depot, e.g. to run the OVRP benchmarks on the CVRP
+    /// instances. Defaults to the TYPE of the instance
+    pub open_routes: Option<bool>,
+}
+
+impl CVRPLibParser {
+    pub fn with_rounding(rounding: DistanceRounding) -> Self {
+        CVRPLibParser {
+            rounding: Some(rounding),
+            ..CVRPLibParser::default()
+        }
+    }
+
+    pub fn with_open_routes(open_routes: bool) -> Self {
+        CVRPLibParser {
+            open_routes: Some(open_routes),
+            ..CVRPLibParser::default()
+        }
+    }
+}
 
 impl DatasetParser for CVRPLibParser {
     #[instrument(skip_all, level = "debug")]
     fn parse(&self, content: &str) -> Result<VehicleRoutingProblem, anyhow::Error> {
         let instance = parse(content)?;
+        let rounding = self
+            .rounding
+            .unwrap_or_else(|| instance.distance_rounding());
+        if !rounding.is_valid() {
+            return Err(anyhow::anyhow!(format!(
+                "Invalid distance rounding: {rounding:?}"
+            )));
+        }
 
         let mut builder = VehicleRoutingProblemBuilder::default();
 
-        let locations = instance
-            .coords
-            .iter()
-            .map(|coord| Location::from_cartesian(coord.x, coord.y))
-            .collect::<Vec<_>>();
-
-        let services = instance
-            .coords
-            .iter()
-            .enumerate()
-            .filter(|(id, _)| !instance.depots.contains(id))
-            .map(|(id, _)| {
+        let is_geo = instance.edge_weight_type.as_deref() == Some("GEO");
+        let locations = if instance.coords.is_empty() {
+            // Explicit instances without display data, the neighborhoods are meaningless
+            (0..instance.demands.len())
+                .map(|_| Location::from_cartesian(0.0, 0.0))
+                .collect::<Vec<_>>()
+        } else if is_geo {
+            instance
+                .coords
+                .iter()
+                .map(|coord| {
+                    Location::from_lat_lon(
+                        geo_radians(coord.x).to_degrees(),
+                        geo_radians(coord.y).to_degrees(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        } else {
+            instance
+                .coords
+                .iter()
+                .map(|coord| Location::from_cartesian(coord.x, coord.y))
+                .collect::<Vec<_>>()
+        };
+
+        let services = (0..instance.demands.len())
+            .filter(|id| !instance.depots.contains(id))
+            .map(|id| {
                 let mut service_builder = ServiceBuilder::default();
 
                 service_builder.set_demand(Capacity::from_vec(vec![instance.demands[id]]));
@@ -55,18 +104,33 @@ impl DatasetParser for CVRPLibParser {
         vb.set_profile_id(0);
         vb.set_vehicle_id(String::from("vehicle"));
         vb.set_depot_location_id(instance.depots[0]);
-        vb.set_return(true);
+        vb.set_return(!self.open_routes.unwrap_or_else(|| instance.is_open()));
 
         let vehicle = vb.build();
 
-        builder.set_vehicle_profiles(vec![VehicleProfile::new(
-            String::from("profile"),
-            TravelMatrices::from_euclidean(&locations, true),
-        )]);
+        match instance.distance_matrix()? {
+            Some(mut distances) => {
+                distances
+                    .iter_mut()
+                    .flatten()
+                    .for_each(|distance| *distance = rounding.apply(*distance));
+                builder.set_vehicle_profiles(vec![VehicleProfile::new(
+                    String::from("profile"),
+                    TravelMatrices::new(distances.clone(), distances.clone(), distances),
+                )]);
+            }
+            None => {
+                builder.set_euclidean_profile(String::from("profile"), rounding);
+            }
+        }
         builder.set_fleet(Fleet::Infinite(vec![vehicle]));
         builder.set_locations(locations);
         builder.set_services(services);
-        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_distance_method(if is_geo {
+            DistanceMethod::Haversine
+        } else {
+            DistanceMethod::Euclidean
+        });
         builder.set_penalize_waiting_duration(false);
 
         Ok(builder.build()?)
@@ -77,14 +141,157 @@ impl DatasetParser for CVRPLibParser {
 pub struct CvrpInstance {
     pub dimension: usize,
     pub capacity: f64,
+    /// TYPE of the instance, e.g. CVRP or OVRP
+    pub problem_type: Option<String>,
+    pub edge_weight_type: Option<String>,
+    pub edge_weight_format: Option<String>,
+    /// Weights of the EDGE_WEIGHT_SECTION, laid out as given by `edge_weight_format`
+    pub edge_weights: Vec<f64>,
+    /// Coordinates of the NODE_COORD_SECTION, or of the DISPLAY_DATA_SECTION for the explicit
+    /// instances, empty when the instance has neither
     pub coords: Vec<geo::Coord<f64>>,
     pub demands: Vec<f64>,
     pub depots: Vec<usize>,
 }
 
+impl CvrpInstance {
+    /// The vehicles end their routes at their last customer
+    pub fn is_open(&self) -> bool {
+        self.problem_type.as_deref() == Some("OVRP")
+    }
+
+    /// Rounding of the distances in the published costs of the instance, EUC_2D is rounded to the
+    /// nearest integer as in TSPLIB
+    pub fn distance_rounding(&self) -> DistanceRounding {
+        match self.edge_weight_type.as_deref() {
+            Some("EUC_2D") | None => DistanceRounding::Round,
+            Some(_) => DistanceRounding::Exact,
+        }
+    }
+
+    /// Distances given by the instance instead of the euclidean distances between the
+    /// coordinates, for the EXPLICIT and GEO edge weight types
+    pub fn distance_matrix(&self) -> Result<Option<Vec<Vec<f64>>>, anyhow::Error> {
+        match self.edge_weight_type.as_deref() {
+            Some("EXPLICIT") => {
+                let format = self.edge_weight_format.as_deref().unwrap_or("FULL_MATRIX");
+                explicit_matrix(format, &self.edge_weights, self.dimension).map(Some)
+            }
+            Some("GEO") => Ok(Some(
+                self.coords
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &from)| {
+                        self.coords
+                            .iter()
+                            .enumerate()
+                            .map(|(j, &to)| if i == j { 0.0 } else { geo_distance(from, to) })
+                            .collect()
+                    })
+                    .collect(),
+            )),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Full matrix of the weights of an EDGE_WEIGHT_SECTION, the triangular formats are symmetric
+fn explicit_matrix(
+    format: &str,
+    weights: &[f64],
+    dimension: usize,
+) -> Result<Vec<Vec<f64>>, anyhow::Error> {
+    // Cells of the matrix in the order of the weights
+    let cells: Box<dyn Iterator<Item = (usize, usize)>> = match format {
+        "FULL_MATRIX" => Box::new((0..dimension).flat_map(|i| (0..dimension).map(move |j| (i, j)))),
+        "LOWER_ROW" => Box::new((0..dimension).flat_map(|i| (0..i).map(move |j| (i, j)))),
+        "LOWER_DIAG_ROW" => Box::new((0..dimension).flat_map(|i| (0..=i).map(move |j| (i, j)))),
+        "UPPER_ROW" => {
+            Box::new((0..dimension).flat_map(|i| (i + 1..dimension).map(move |j| (i, j))))
+        }
+        "UPPER_DIAG_ROW" => {
+            Box::new((0..dimension).flat_map(|i| (i..dimension).map(move |j| (i, j))))
+        }
+        _ => {
+            return Err(anyhow::anyhow!(format!(
+                "Unsupported edge weight format: {}",
+                format
+            )));
+        }
+    };
+
+    let cells = cells.collect::<Vec<_>>();
+    if cells.len() != weights.len() {
+        return Err(anyhow::anyhow!(format!(
+            "Expected {} edge weights for {} with dimension {}, found {}",
+            cells.len(),
+            format,
+            dimension,
+            weights.len()
+        )));
+    }
+
+    let mut matrix = vec![vec![0.0; dimension]; dimension];
+    for ((i, j), &weight) in cells.into_iter().zip(weights) {
+        matrix[i][j] = weight;
+        if format != "FULL_MATRIX" {
+            matrix[j][i] = weight;
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// Latitude or longitude in radians of a GEO coordinate, given in degrees and minutes as DDD.MM
+fn geo_radians(value: f64) -> f64 {
+    // TSPLIB truncates pi, the published distances depend on it
+    #[allow(clippy::approx_constant)]
+    const PI: f64 = 3.141592;
+
+    let degrees = value.trunc();
+    let minutes = value - degrees;
+    PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+}
+
+/// Distance in kilometers between two GEO coordinates, as computed by TSPLIB
+fn geo_distance(from: Coord<f64>, to: Coord<f64>) -> f64 {
+    const EARTH_RADIUS: f64 = 6378.388;
+
+    let (from_lat, from_lon) = (geo_radians(from.x), geo_radians(from.y));
+    let (to_lat, to_lon) = (geo_radians(to.x), geo_radians(to.y));
+
+    let q1 = (from_lon - to_lon).cos();
+    let q2 = (from_lat - to_lat).cos();
+    let q3 = (from_lat + to_lat).cos();
+    let cos = (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).clamp(-1.0, 1.0);
+
+    (EARTH_RADIUS * cos.acos() + 1.0).trunc()
+}
+
+/// Weights of the EDGE_WEIGHT_SECTION, the rows of the matrix can span multiple lines
+fn parse_edge_weights(lines: &[&str], i: &mut usize) -> Result<Vec<f64>, anyhow::Error> {
+    let mut values = Vec::new();
+    while *i < lines.len() && !lines[*i].contains("SECTION") && lines[*i] != "EOF" {
+        for part in lines[*i].split_whitespace() {
+            values.push(
+                part.parse()
+                    .map_err(|_| anyhow::anyhow!(format!("Invalid edge weight: {}", part)))?,
+            );
+        }
+        *i += 1;
+    }
+
+    Ok(values)
+}
+
 fn parse(text: &str) -> Result<CvrpInstance, anyhow::Error> {
     let mut dimension: Option<usize> = None;
     let mut capacity: Option<f64> = None;
+    let mut problem_type: Option<String> = None;
+    let mut edge_weight_type: Option<String> = None;
+    let mut edge_weight_format: Option<String> = None;
+    let mut edge_weights: Option<Vec<f64>> = None;
+    let mut display_coords: Option<Vec<geo::Coord<f64>>> = None;
     let mut coords: Option<Vec<geo::Coord<f64>>> = None;
     let mut demands: Option<Vec<f64>> = None;
     let mut depots: Option<Vec<usize>> = None;
@@ -118,6 +325,15 @@ fn parse(text: &str) -> Result<CvrpInstance, anyhow::Error> {
                             anyhow::anyhow!(format!("Invalid capacity: {}", value))
                         })?);
                 }
+                "TYPE" => {
+                    problem_type = Some(value.to_uppercase());
+                }
+                "EDGE_WEIGHT_TYPE" => {
+                    edge_weight_type = Some(value.to_uppercase());
+                }
+                "EDGE_WEIGHT_FORMAT" => {
+                    edge_weight_format = Some(value.to_uppercase());
+                }
                 _ => {} // Ignore other specifications
             }
             i += 1;
@@ -125,7 +341,8 @@ fn parse(text: &str) -> Result<CvrpInstance, anyhow::Error> {
         }
 
         // Parse sections
-        if line.contains("NODE_COORD_SECTION") {
+        if line.contains("NODE_COORD_SECTION") || line.contains("DISPLAY_DATA_SECTION") {
+            let is_display_data = line.contains("DISPLAY_DATA_SECTION");
             i += 1;
             let mut parsed_coords = Vec::new();
             while i < lines.len() && !lines[i].contains("SECTION") && lines[i] != "EOF" {
@@ -141,7 +358,17 @@ fn parse(text: &str) -> Result<CvrpInstance, anyhow::Error> {
                 }
                 i += 1;
             }
-            coords = Some(parsed_coords);
+            if is_display_data {
+                display_coords = Some(parsed_coords);
+            } else {
+                coords = Some(parsed_coords);
+            }
+            continue;
+        }
+
+        if line.contains("EDGE_WEIGHT_SECTION") {
+            i += 1;
+            edge_weights = Some(parse_edge_weights(&lines, &mut i)?);
             continue;
         }
 
@@ -173,6 +400,9 @@ fn parse(text: &str) -> Result<CvrpInstance, anyhow::Error> {
                     if idx == -1 {
                         break;
                     }
+                    if idx < 1 {
+                        return Err(anyhow::anyhow!(format!("Invalid depot index: {}", part)));
+                    }
                     // Convert to 0-indexed
                     parsed_depots.push((idx - 1) as usize);
                 }
@@ -185,12 +415,58 @@ fn parse(text: &str) -> Result<CvrpInstance, anyhow::Error> {
         i += 1;
     }
 
+    let is_explicit = edge_weight_type.as_deref() == Some("EXPLICIT");
+    if is_explicit && edge_weights.is_none() {
+        return Err(anyhow::anyhow!("Missing EDGE_WEIGHT_SECTION"));
+    }
+
+    let coords = match coords.or(display_coords) {
+        Some(coords) => coords,
+        None if is_explicit => vec![],
+        None => return Err(anyhow::anyhow!("Missing NODE_COORD_SECTION")),
+    };
+
+    let dimension = dimension.ok_or_else(|| anyhow::anyhow!("Missing DIMENSION"))?;
+    let demands = demands.ok_or_else(|| anyhow::anyhow!("Missing DEMAND_SECTION"))?;
+    let depots = depots.unwrap_or_else(|| vec![0]);
+
+    // One location per demand, the explicit matrices have `dimension` rows
+    if demands.len() != dimension {
+        return Err(anyhow::anyhow!(
+            "Expected {} demands for dimension {}, found {}",
+            dimension,
+            dimension,
+            demands.len()
+        ));
+    }
+
+    if !coords.is_empty() && coords.len() != dimension {
+        return Err(anyhow::anyhow!(
+            "Expected {} coordinates for dimension {}, found {}",
+            dimension,
+            dimension,
+            coords.len()
+        ));
+    }
+
+    if let Some(depot) = depots.iter().find(|&&depot| depot >= dimension) {
+        return Err(anyhow::anyhow!(
+            "Invalid depot index {} for dimension {}",
+            depot + 1,
+            dimension
+        ));
+    }
+
     Ok(CvrpInstance {
-        dimension: dimension.ok_or_else(|| anyhow::anyhow!("Missing DIMENSION"))?,
+        dimension,
         capacity: capacity.ok_or_else(|| anyhow::anyhow!("Missing CAPACITY"))?,
-        coords: coords.ok_or_else(|| anyhow::anyhow!("Missing NODE_COORD_SECTION"))?,
-        demands: demands.ok_or_else(|| anyhow::anyhow!("Missing DEMAND_SECTION"))?,
-        depots: depots.unwrap_or_else(|| vec![0]),
+        problem_type,
+        edge_weight_type,
+        edge_weight_format,
+        edge_weights: edge_weights.unwrap_or_default(),
+        coords,
+        demands,
+        depots,
     })
 }
 
@@ -271,13 +547,24 @@ pub fn parse_solution_file<P: AsRef<Path>>(path: P) -> Option<Bks> {
 
 #[cfg(test)]
 mod tests {
+    use std::{env, sync::Arc};
+
+    use crate::{
+        problem::location::LocationIdx,
+        solver::{
+            alns::Alns,
+            solution::verify::verify_solution,
+            solver_params::{SolverParams, Termination, Threads},
+        },
+    };
+
     use super::*;
 
     const SAMPLE: &str = r#"
 NAME : A-n32-k5
 COMMENT : (Augerat et al, No of trucks: 5, Optimal value: 784)
 TYPE : CVRP
-DIMENSION : 32
+DIMENSION : 5
 EDGE_WEIGHT_TYPE : EUC_2D
 CAPACITY : 100
 NODE_COORD_SECTION
@@ -302,7 +589,7 @@ EOF
     fn test_parse() {
         let instance = parse(SAMPLE).unwrap();
 
-        assert_eq!(instance.dimension, 32);
+        assert_eq!(instance.dimension, 5);
         assert_eq!(instance.capacity, 100.0);
         assert_eq!(instance.coords.len(), 5);
         assert_eq!(instance.demands.len(), 5);
@@ -312,5 +599,208 @@ EOF
         assert_eq!(instance.coords[0].y, 76.0);
         assert_eq!(instance.demands[0], 0.0);
         assert_eq!(instance.demands[1], 19.0);
+        assert_eq!(instance.edge_weight_type.as_deref(), Some("EUC_2D"));
+        assert_eq!(instance.distance_rounding(), DistanceRounding::Round);
+    }
+
+    #[test]
+    fn test_parse_mismatched_dimension() {
+        let error = parse(&SAMPLE.replace("DIMENSION : 5", "DIMENSION : 32")).unwrap_err();
+        assert!(error.to_string().contains("demands"), "{error}");
+
+        let error = parse(&SAMPLE.replace(" 5 13 7\n", "")).unwrap_err();
+        assert!(error.to_string().contains("coordinates"), "{error}");
+
+        let error = parse(&SAMPLE.replace("5 19\n", "")).unwrap_err();
+        assert!(error.to_string().contains("demands"), "{error}");
+
+        let error = parse(&SAMPLE.replace(" 1\n -1", " 6\n -1")).unwrap_err();
+        assert!(error.to_string().contains("depot"), "{error}");
+
+        let error = parse(&EXPLICIT_SAMPLE.replace("DIMENSION : 4", "DIMENSION : 5")).unwrap_err();
+        assert!(error.to_string().contains("demands"), "{error}");
+    }
+
+    #[test]
+    fn test_parse_with_rounding() {
+        let (from, to) = (LocationIdx::new(0), LocationIdx::new(1));
+
+        // sqrt(14² + 32²) = 34.93
+        let problem = CVRPLibParser::default().parse(SAMPLE).unwrap();
+        let profile = &problem.vehicle_profiles()[0];
+        assert_eq!(profile.travel_distance(from, to).value(), 35.0);
+
+        let problem = CVRPLibParser::with_rounding(DistanceRounding::Truncate)
+            .parse(SAMPLE)
+            .unwrap();
+        let profile = &problem.vehicle_profiles()[0];
+        assert_eq!(profile.travel_distance(from, to).value(), 34.0);
+    }
+
+    const EXPLICIT_SAMPLE: &str = r#"
+NAME : E-n4
+TYPE : CVRP
+DIMENSION : 4
+EDGE_WEIGHT_TYPE : EXPLICIT
+EDGE_WEIGHT_FORMAT : LOWER_ROW
+CAPACITY : 10
+EDGE_WEIGHT_SECTION
+ 1
+ 2 3
+ 4 5 6
+DEMAND_SECTION
+1 0
+2 1
+3 2
+4 3
+DEPOT_SECTION
+ 1
+ -1
+EOF
+"#;
+
+    #[test]
+    fn test_parse_explicit() {
+        let instance = parse(EXPLICIT_SAMPLE).unwrap();
+        assert!(instance.coords.is_empty());
+        assert_eq!(instance.edge_weights, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        let problem = CVRPLibParser::default().parse(EXPLICIT_SAMPLE).unwrap();
+        let profile = &problem.vehicle_profiles()[0];
+        let distance =
+            |from, to| profile.travel_distance(LocationIdx::new(from), LocationIdx::new(to));
+
+        assert_eq!(distance(0, 0).value(), 0.0);
+        assert_eq!(distance(3, 1).value(), 5.0);
+        assert_eq!(distance(1, 3).value(), 5.0);
+        assert_eq!(distance(2, 0).value(), 2.0);
+    }
+
+    #[test]
+    fn test_explicit_matrix_formats() {
+        let full = explicit_matrix("FULL_MATRIX", &[0.0, 1.0, 2.0, 0.0], 2).unwrap();
+        assert_eq!(full, vec![vec![0.0, 1.0], vec![2.0, 0.0]]);
+
+        let upper = explicit_matrix("UPPER_ROW", &[1.0, 2.0, 3.0], 3).unwrap();
+        assert_eq!(
+            upper,
+            vec![
+                vec![0.0, 1.0, 2.0],
+                vec![1.0, 0.0, 3.0],
+                vec![2.0, 3.0, 0.0]
+            ]
+        );
+
+        let lower_diag = explicit_matrix("LOWER_DIAG_ROW", &[0.0, 1.0, 0.0], 2).unwrap();
+        assert_eq!(lower_diag, vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+
+        assert!(explicit_matrix("UPPER_ROW", &[1.0, 2.0], 3).is_err());
+        assert!(explicit_matrix("UPPER_COL", &[1.0, 2.0, 3.0], 3).is_err());
+    }
+
+    #[test]
+    fn test_geo_distance() {
+        // First two cities of ulysses16 in TSPLIB
+        let from = Coord { x: 38.24, y: 20.42 };
+        let to = Coord { x: 39.57, y: 26.15 };
+
+        assert_eq!(geo_distance(from, to), 509.0);
+        assert_eq!(geo_distance(to, from), 509.0);
+    }
+
+    #[test]
+    fn test_parse_open_routes() {
+        let content = SAMPLE.replace("TYPE : CVRP", "TYPE : OVRP");
+        assert!(parse(&content).unwrap().is_open());
+        assert!(!parse(SAMPLE).unwrap().is_open());
+
+        let problem = CVRPLibParser::default().parse(&content).unwrap();
+        assert!(!problem.vehicles()[0].should_return_to_depot());
+
+        let problem = CVRPLibParser::default().parse(SAMPLE).unwrap();
+        assert!(problem.vehicles()[0].should_return_to_depot());
+
+        // The OVRP benchmarks reuse the CVRP instances
+        let problem = CVRPLibParser::with_open_routes(true).parse(SAMPLE).unwrap();
+        assert!(!problem.vehicles()[0].should_return_to_depot());
+    }
+
+    /// Gap to the best known OVRP cost allowed after the few iterations of the tests
+    const OVRP_GAP: f64 = 0.15;
+
+    /// Solves a CMT instance as an open VRP, the published OVRP costs use the exact distances
+    fn solve_open_instance(name: &str, best_known_cost: f64) {
+        let current_dir = env::current_dir().unwrap();
+        let path = current_dir
+            .parent()
+            .unwrap()
+            .join(format!("../data/cvrplib/CMT/{name}.vrp"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let parser = CVRPLibParser {
+            rounding: Some(DistanceRounding::Exact),
+            open_routes: Some(true),
+        };
+        let problem = Arc::new(parser.parse(&content).unwrap());
+
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(200)],
+            search_threads: Threads::Single,
+            insertion_threads: Threads::Single,
+            deterministic: true,
+            ..SolverParams::default_from_problem(&problem)
+        };
+        let constraints = params.constraints.clone();
+
+        let alns = Alns::new(params, Arc::clone(&problem)).unwrap();
+        let solution = alns.run().unwrap().best_solution.unwrap().solution;
+
+        let report = verify_solution(&solution, constraints.constraints());
+        assert!(report.is_valid(), "{name}: {:?}", report.violations);
+        assert!(solution.unassigned_jobs().is_empty());
+
+        // No return leg is driven nor costed
+        let mut cost = 0.0;
+        for route in solution.non_empty_routes_iter() {
+            assert!(!route.has_end(&problem));
+
+            let vehicle = route.vehicle(&problem);
+            let location_ids = route.compute_location_ids(&problem);
+            assert_eq!(
+                location_ids.last(),
+                route.location_id(&problem, route.len() - 1).as_ref()
+            );
+
+            cost += location_ids
+                .windows(2)
+                .map(|leg| problem.travel_cost(vehicle, leg[0], leg[1]))
+                .sum::<f64>();
+        }
+        assert!((solution.total_transport_costs() - cost).abs() < 1e-6);
+
+        // Cheaper than the best closed routes
+        let closed = parse_solution_file(path.with_extension("sol")).unwrap();
+        assert!(cost < closed.cost, "{name}: {cost} >= {}", closed.cost);
+
+        assert!(
+            cost >= best_known_cost - 1e-2,
+            "{name}: {cost} < {best_known_cost}"
+        );
+        assert!(
+            cost <= best_known_cost * (1.0 + OVRP_GAP),
+            "{name}: {cost} is more than {}% above {best_known_cost}",
+            OVRP_GAP * 100.0
+        );
+    }
+
+    // Best known OVRP costs of Fisher's and Li, Golden and Wasil's benchmarks
+    #[test]
+    fn test_solve_open_cmt1() {
+        solve_open_instance("CMT1", 416.06);
+    }
+
+    #[test]
+    fn test_solve_open_cmt2() {
+        solve_open_instance("CMT2", 567.14);
     }
 }
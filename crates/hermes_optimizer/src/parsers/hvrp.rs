@@ -0,0 +1,316 @@
+use anyhow::{Context, bail};
+use tracing::instrument;
+
+use crate::{
+    parsers::parser::DatasetParser,
+    problem::{
+        capacity::Capacity,
+        distance_method::DistanceMethod,
+        fleet::Fleet,
+        location::Location,
+        service::ServiceBuilder,
+        travel_cost_matrix::DistanceRounding,
+        vehicle::VehicleBuilder,
+        vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
+    },
+};
+
+/// Parser of the heterogeneous fleet instances of Golden et al. and Taillard:
+///
+/// ```text
+/// n                                      number of customers
+/// [id] x y demand                        n + 1 lines, the depot first
+/// k                                      number of vehicle types
+/// capacity fixed_cost variable_cost count   k lines
+/// ```
+///
+/// A negative or missing count means the type is unlimited, as in the fleet size and mix variant.
+/// Each vehicle of a limited type is a vehicle of its own, `type_{index}_{number}`, the routes of
+/// an unlimited type share the vehicle `type_{index}`. The distances are the raw euclidean
+/// distances of the benchmarks.
+pub struct HvrpParser;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HvrpVehicleType {
+    pub capacity: f64,
+    pub fixed_cost: f64,
+    pub variable_cost: f64,
+    /// `None` when the type is unlimited
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HvrpInstance {
+    /// The depot first, then the customers
+    pub coords: Vec<(f64, f64)>,
+    pub demands: Vec<f64>,
+    pub vehicle_types: Vec<HvrpVehicleType>,
+}
+
+impl DatasetParser for HvrpParser {
+    #[instrument(skip_all, level = "debug")]
+    fn parse(&self, content: &str) -> Result<VehicleRoutingProblem, anyhow::Error> {
+        let instance = parse(content)?;
+        let customers = instance.demands.len() - 1;
+
+        let locations = instance
+            .coords
+            .iter()
+            .map(|&(x, y)| Location::from_cartesian(x, y))
+            .collect::<Vec<_>>();
+
+        let services = (1..instance.demands.len())
+            .map(|id| {
+                let mut service_builder = ServiceBuilder::default();
+
+                service_builder.set_demand(Capacity::from_vec(vec![instance.demands[id]]));
+                service_builder.set_location_id(id);
+                service_builder.set_external_id(format!("{id}"));
+
+                service_builder.build()
+            })
+            .collect::<Vec<_>>();
+
+        let build_vehicle = |vehicle_type: &HvrpVehicleType, vehicle_id: String| {
+            let mut vb = VehicleBuilder::default();
+            vb.set_capacity(Capacity::from_vec(vec![vehicle_type.capacity]));
+            vb.set_profile_id(0);
+            vb.set_vehicle_id(vehicle_id);
+            vb.set_depot_location_id(0);
+            vb.set_return(true);
+            vb.set_fixed_cost(vehicle_type.fixed_cost);
+            // The cost is per kilometer, the distances of the benchmarks are unitless
+            vb.set_cost_per_distance(vehicle_type.variable_cost * 1000.0);
+            vb.build()
+        };
+
+        let mut vehicles = Vec::new();
+        for (index, vehicle_type) in instance.vehicle_types.iter().enumerate() {
+            match vehicle_type.count {
+                Some(count) => vehicles.extend((1..=count).map(|number| {
+                    (
+                        build_vehicle(vehicle_type, format!("type_{index}_{number}")),
+                        1,
+                    )
+                })),
+                // A route serves at least one customer, it bounds the unlimited types
+                None => vehicles.push((
+                    build_vehicle(vehicle_type, format!("type_{index}")),
+                    customers.max(1),
+                )),
+            }
+        }
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_euclidean_profile(String::from("profile"), DistanceRounding::Exact);
+        builder.set_fleet(Fleet::counted(vehicles));
+        builder.set_locations(locations);
+        builder.set_services(services);
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_penalize_waiting_duration(false);
+
+        Ok(builder.build()?)
+    }
+}
+
+fn parse(text: &str) -> Result<HvrpInstance, anyhow::Error> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.split_whitespace()
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("Invalid line: {line}"))
+        });
+
+    let mut next_line = |section: &str| -> Result<Vec<f64>, anyhow::Error> {
+        lines.next().with_context(|| format!("Missing {section}"))?
+    };
+
+    let customers = match next_line("number of customers")?[..] {
+        [customers] => whole_number(customers, "number of customers")?,
+        _ => bail!("Invalid number of customers"),
+    };
+
+    let mut coords = Vec::with_capacity(customers + 1);
+    let mut demands = Vec::with_capacity(customers + 1);
+    for _ in 0..=customers {
+        let (x, y, demand) = match next_line("customer")?[..] {
+            [x, y, demand] | [_, x, y, demand] => (x, y, demand),
+            _ => bail!("Invalid customer line"),
+        };
+
+        coords.push((x, y));
+        demands.push(demand);
+    }
+
+    let types = match next_line("number of vehicle types")?[..] {
+        [types] => whole_number(types, "number of vehicle types")?,
+        _ => bail!("Invalid number of vehicle types"),
+    };
+
+    let vehicle_types = (0..types)
+        .map(|_| -> Result<HvrpVehicleType, anyhow::Error> {
+            let (capacity, fixed_cost, variable_cost, count) = match next_line("vehicle type")?[..]
+            {
+                [capacity, fixed_cost, variable_cost] => {
+                    (capacity, fixed_cost, variable_cost, None)
+                }
+                [capacity, fixed_cost, variable_cost, count] => (
+                    capacity,
+                    fixed_cost,
+                    variable_cost,
+                    (count >= 0.0)
+                        .then(|| whole_number(count, "vehicle count"))
+                        .transpose()?,
+                ),
+                _ => bail!("Invalid vehicle type line"),
+            };
+
+            Ok(HvrpVehicleType {
+                capacity,
+                fixed_cost,
+                variable_cost,
+                count,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if vehicle_types.is_empty() {
+        bail!("Missing vehicle types");
+    }
+
+    Ok(HvrpInstance {
+        coords,
+        demands,
+        vehicle_types,
+    })
+}
+
+fn whole_number(value: f64, name: &str) -> Result<usize, anyhow::Error> {
+    if value < 0.0 || value.fract() != 0.0 {
+        bail!("Invalid {name}: {value}");
+    }
+
+    Ok(value as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::vehicle::VehicleIdx,
+        solver::{
+            alns::Alns,
+            solver_params::{SolverParams, Termination, Threads},
+        },
+    };
+
+    use super::*;
+
+    const INSTANCE: &str = "5
+0 30 40 0
+1 37 52 7
+2 49 49 30
+3 52 64 16
+4 20 26 9
+5 40 30 21
+2
+20 20 1.0 2
+60 50 1.2 1
+";
+
+    #[test]
+    fn test_parse() {
+        let instance = parse(INSTANCE).unwrap();
+        assert_eq!(instance.coords.len(), 6);
+        assert_eq!(instance.demands[2], 30.0);
+        assert_eq!(
+            instance.vehicle_types[1],
+            HvrpVehicleType {
+                capacity: 60.0,
+                fixed_cost: 50.0,
+                variable_cost: 1.2,
+                count: Some(1),
+            }
+        );
+
+        let problem = HvrpParser.parse(INSTANCE).unwrap();
+        assert_eq!(problem.jobs().len(), 5);
+        assert_eq!(
+            problem
+                .vehicles()
+                .iter()
+                .map(|vehicle| vehicle.external_id())
+                .collect::<Vec<_>>(),
+            vec!["type_0_1", "type_0_2", "type_1_1"]
+        );
+        assert_eq!(problem.fleet().vehicle_limit(VehicleIdx::new(0)), Some(1));
+        assert_eq!(problem.vehicle(VehicleIdx::new(2)).fixed_cost(), Some(50.0));
+
+        let error = parse(&INSTANCE.replacen("5\n", "6\n", 1)).unwrap_err();
+        assert!(error.to_string().contains("customer"));
+    }
+
+    #[test]
+    fn test_parse_rejects_fractional_numbers() {
+        let error = parse(&INSTANCE.replacen("5\n", "5.5\n", 1)).unwrap_err();
+        assert!(error.to_string().contains("number of customers"));
+
+        let error = parse(&INSTANCE.replacen("2\n20", "1.5\n20", 1)).unwrap_err();
+        assert!(error.to_string().contains("number of vehicle types"));
+
+        let error = parse(&INSTANCE.replacen("1.0 2\n", "1.0 2.5\n", 1)).unwrap_err();
+        assert!(error.to_string().contains("vehicle count"));
+    }
+
+    #[test]
+    fn test_parse_unlimited_types() {
+        let content = "2\n30 40 0\n37 52 7\n49 49 30\n1\n20 20 1.0 -1\n";
+        let instance = parse(content).unwrap();
+        assert_eq!(instance.vehicle_types[0].count, None);
+
+        let problem = HvrpParser.parse(content).unwrap();
+        assert_eq!(problem.vehicle(VehicleIdx::new(0)).external_id(), "type_0");
+        assert_eq!(problem.fleet().vehicle_limit(VehicleIdx::new(0)), Some(2));
+    }
+
+    #[test]
+    fn test_solve_respects_counts() {
+        let problem = Arc::new(HvrpParser.parse(INSTANCE).unwrap());
+
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(200)],
+            search_threads: Threads::Single,
+            insertion_threads: Threads::Single,
+            deterministic: true,
+            ..SolverParams::default_from_problem(&problem)
+        };
+
+        let solution = Alns::new(params, Arc::clone(&problem))
+            .unwrap()
+            .run()
+            .unwrap()
+            .best_solution
+            .unwrap()
+            .solution;
+
+        // 83 units of demand for 100 units of capacity, every vehicle is needed
+        assert!(!solution.has_unassigned());
+        for (vehicle_type, count) in [("type_0", 2), ("type_1", 1)] {
+            assert!(
+                solution
+                    .non_empty_routes_iter()
+                    .filter(|route| route
+                        .vehicle(&problem)
+                        .external_id()
+                        .starts_with(vehicle_type))
+                    .count()
+                    <= count
+            );
+        }
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
         location::Location,
         shipment::ShipmentBuilder,
         time_window::TimeWindow,
-        travel_cost_matrix::TravelMatrices,
+        travel_cost_matrix::{DistanceRounding, TravelMatrices},
         vehicle::{Vehicle, VehicleBuilder, VehicleShiftBuilder},
         vehicle_profile::VehicleProfile,
         vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
@@ -139,7 +139,8 @@ impl DatasetParser for LiLimParser {
             shipments.push(sb.build());
         }
 
-        let travel_costs_matrix = TravelMatrices::from_euclidean(&locations, false);
+        let travel_costs_matrix =
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Exact);
 
         let mut builder = VehicleRoutingProblemBuilder::default();
         builder
@@ -1,4 +1,5 @@
 pub mod cvrplib;
+pub mod hvrp;
 pub mod li_lim;
 pub mod parser;
 pub mod solomon;
@@ -6,7 +6,9 @@ use std::{
 
 use crate::problem::vehicle_routing_problem::VehicleRoutingProblem;
 
-use super::{cvrplib::CVRPLibParser, li_lim::LiLimParser, solomon::SolomonParser};
+use super::{
+    cvrplib::CVRPLibParser, hvrp::HvrpParser, li_lim::LiLimParser, solomon::SolomonParser,
+};
 
 pub trait DatasetParser {
     fn parse(&self, content: &str) -> Result<VehicleRoutingProblem, anyhow::Error>;
@@ -17,6 +19,7 @@ pub enum DatasetFormat {
     Solomon,
     CvrpLib,
     LiLim,
+    Hvrp,
 }
 
 /// Detect the dataset format by inspecting file contents.
@@ -50,6 +53,11 @@ fn detect_format(content: &str) -> Result<DatasetFormat, anyhow::Error> {
         let parts: Vec<&str> = trimmed.split_whitespace().collect();
         let all_numeric = parts.iter().all(|p| p.parse::<f64>().is_ok());
 
+        // HVRP files start with the number of customers alone
+        if all_numeric && parts.len() == 1 {
+            return Ok(DatasetFormat::Hvrp);
+        }
+
         if all_numeric {
             // Check the second non-empty line to count data columns.
             // Li-Lim data rows have 9 columns, Solomon has 7.
@@ -90,8 +98,9 @@ pub fn parse_dataset<P: AsRef<Path>>(path: P) -> Result<VehicleRoutingProblem, a
     let format = detect_format(&content)?;
     match format {
         DatasetFormat::Solomon => SolomonParser.parse(&content),
-        DatasetFormat::CvrpLib => CVRPLibParser.parse(&content),
+        DatasetFormat::CvrpLib => CVRPLibParser::default().parse(&content),
         DatasetFormat::LiLim => LiLimParser.parse(&content),
+        DatasetFormat::Hvrp => HvrpParser.parse(&content),
     }
 }
 
@@ -137,4 +146,15 @@ NODE_COORD_SECTION
                         2\t45\t70\t-20\t825\t870\t90\t6\t0\n";
         assert_eq!(detect_format(content).unwrap(), DatasetFormat::LiLim);
     }
+
+    #[test]
+    fn test_detect_hvrp() {
+        let content = "2\n\
+                        0 30 40 0\n\
+                        1 37 52 7\n\
+                        2 49 49 30\n\
+                        1\n\
+                        20 20 1.0 2\n";
+        assert_eq!(detect_format(content).unwrap(), DatasetFormat::Hvrp);
+    }
 }
@@ -9,7 +9,7 @@ use crate::{
         location::Location,
         service::{Service, ServiceBuilder},
         time_window::TimeWindow,
-        travel_cost_matrix::TravelMatrices,
+        travel_cost_matrix::{DistanceRounding, TravelMatrices},
         vehicle::{Vehicle, VehicleBuilder, VehicleShiftBuilder},
         vehicle_profile::VehicleProfile,
         vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
@@ -126,7 +126,8 @@ impl DatasetParser for SolomonParser {
             }
         }
 
-        let travel_costs_matrix = TravelMatrices::from_euclidean(&locations, false);
+        let travel_costs_matrix =
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Exact);
         builder
             .set_vehicle_profiles(vec![VehicleProfile::new(
                 "vehicle".to_owned(),
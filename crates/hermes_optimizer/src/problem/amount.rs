@@ -7,7 +7,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-use crate::utils;
+use crate::{problem::kilograms::Kilograms, utils};
 
 pub trait AmountExpression: Sized {
     fn get(&self, index: usize) -> f64;
@@ -20,7 +20,13 @@ pub trait AmountExpression: Sized {
         Self: Sized;
 }
 
-type Vector = SmallVec<[f64; 2]>;
+/// Number of dimensions stored inline, amounts with more dimensions are allocated on the heap.
+///
+/// Amounts are copied into the load bookkeeping of every route each time it changes, most problems
+/// have a few dimensions (weight, volume, pallets...) that should never need an allocation
+pub const INLINE_DIMENSIONS: usize = 4;
+
+type Vector = SmallVec<[f64; INLINE_DIMENSIONS]>;
 
 // 1. Blanket implementation for References
 // This allows &Amount to be used anywhere AmountExpression is required.
@@ -63,6 +69,11 @@ impl Amount {
         Amount(SmallVec::from_vec(vec))
     }
 
+    /// Amount of a single dimension, the weight
+    pub fn from_weight(weight: Kilograms) -> Self {
+        Amount(SmallVec::from_slice(&[weight.value()]))
+    }
+
     pub fn reset(&mut self) {
         self.0.clear();
     }
@@ -76,11 +87,14 @@ impl Amount {
         self.0.extend(other.iter());
     }
 
-    pub fn update_max(&mut self, other: &Amount) {
-        let max_len = self.len().max(other.len());
-        self.0.resize(max_len, 0.0);
-        for i in 0..max_len {
-            self.0[i] = self.get(i).max(other.get(i));
+    /// Takes the maximum of each dimension in place
+    pub fn update_max(&mut self, other: impl AmountExpression) {
+        if self.0.len() < other.len() {
+            self.0.resize(other.len(), 0.0);
+        }
+
+        for (i, value) in self.0.iter_mut().enumerate() {
+            *value = value.max(other.get(i));
         }
     }
 
@@ -131,6 +145,7 @@ impl IndexMut<usize> for Amount {
 }
 
 impl<E: AmountExpression> AddAssign<E> for Amount {
+    #[inline]
     fn add_assign(&mut self, rhs: E) {
         if self.0.len() < rhs.len() {
             self.0.resize(rhs.len(), 0.0);
@@ -143,6 +158,7 @@ impl<E: AmountExpression> AddAssign<E> for Amount {
 }
 
 impl<E: AmountExpression> SubAssign<E> for Amount {
+    #[inline]
     fn sub_assign(&mut self, rhs: E) {
         if self.0.len() < rhs.len() {
             self.0.resize(rhs.len(), 0.0);
@@ -521,6 +537,29 @@ mod tests {
         assert_eq!(a, Amount::from_vec(vec![9.0, 12.0, 11.0]));
     }
 
+    #[test]
+    fn test_amount_update_max() {
+        let mut a = Amount::from_vec(vec![1.0, 8.0]);
+        let b = Amount::from_vec(vec![4.0, 5.0, 2.0]);
+
+        a.update_max(&b);
+        assert_eq!(a, Amount::from_vec(vec![4.0, 8.0, 2.0]));
+
+        // Dimensions missing from the other amount are zero
+        let mut c = Amount::from_vec(vec![-1.0, 3.0, -2.0]);
+        c.update_max(Amount::from_vec(vec![1.0]));
+        assert_eq!(c, Amount::from_vec(vec![1.0, 3.0, 0.0]));
+    }
+
+    #[test]
+    fn test_inline_dimensions() {
+        let amount = Amount::with_dimensions(INLINE_DIMENSIONS);
+        assert!(!amount.0.spilled());
+
+        let amount = Amount::with_dimensions(INLINE_DIMENSIONS + 1);
+        assert!(amount.0.spilled());
+    }
+
     #[test]
     fn test_with_dimensions() {
         let demand = Amount::with_dimensions(1);
@@ -0,0 +1,202 @@
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::problem::{amount::AmountExpression, kilograms::Kilograms};
+
+/// Loading meters taken by a Euro pallet (1.2 m x 0.8 m) in a 2.4 m wide loading space
+pub const EURO_PALLET_LOADING_METERS: f64 = 0.4;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum AxleLoadsError {
+    #[error("The wheelbase must be positive, got {0}")]
+    InvalidWheelbase(f64),
+
+    #[error("Capacity dimension {0} out of bounds")]
+    DimensionOutOfBounds(usize),
+}
+
+/// Weight on an axle of a truck
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Axle {
+    /// Weight on the axle when the truck is empty
+    pub empty_weight: Kilograms,
+    pub maximum_weight: Kilograms,
+}
+
+/// Axle weight limits of a truck, checked at each stop from the weight and the loading meters of
+/// its load, see `AxleLoadConstraint`.
+///
+/// The load is assumed to be spread evenly over the loading meters it takes, against the front
+/// wall of the loading space. Its center of gravity is halfway along the loading meters and its
+/// weight is split between the front and rear axles by the lever rule.
+#[derive(Serialize, Debug, Clone)]
+pub struct AxleLoads {
+    /// Capacity dimension of the weight of the load in kg
+    weight_dimension: usize,
+
+    /// Capacity dimension of the length of the loading space taken by the load in loading meters,
+    /// its capacity is the length of the loading space
+    loading_meters_dimension: usize,
+
+    /// Distance between the front and the rear axles in meters
+    wheelbase: f64,
+
+    /// Distance from the front axle to the front wall of the loading space in meters, negative
+    /// when the loading space starts before the front axle
+    loading_space_offset: f64,
+    front_axle: Axle,
+    rear_axle: Axle,
+}
+
+impl AxleLoads {
+    pub fn new(
+        weight_dimension: usize,
+        loading_meters_dimension: usize,
+        wheelbase: f64,
+        loading_space_offset: f64,
+        front_axle: Axle,
+        rear_axle: Axle,
+    ) -> Self {
+        AxleLoads {
+            weight_dimension,
+            loading_meters_dimension,
+            wheelbase,
+            loading_space_offset,
+            front_axle,
+            rear_axle,
+        }
+    }
+
+    pub fn weight_dimension(&self) -> usize {
+        self.weight_dimension
+    }
+
+    pub fn loading_meters_dimension(&self) -> usize {
+        self.loading_meters_dimension
+    }
+
+    pub fn wheelbase(&self) -> f64 {
+        self.wheelbase
+    }
+
+    pub fn loading_space_offset(&self) -> f64 {
+        self.loading_space_offset
+    }
+
+    pub fn front_axle(&self) -> &Axle {
+        &self.front_axle
+    }
+
+    pub fn rear_axle(&self) -> &Axle {
+        &self.rear_axle
+    }
+
+    /// Checks the axle loads apply to a vehicle with `dimensions` capacity dimensions
+    pub fn validate(&self, dimensions: usize) -> Result<(), AxleLoadsError> {
+        if !(self.wheelbase > 0.0 && self.wheelbase.is_finite()) {
+            return Err(AxleLoadsError::InvalidWheelbase(self.wheelbase));
+        }
+
+        if let Some(dimension) = [self.weight_dimension, self.loading_meters_dimension]
+            .into_iter()
+            .find(|&dimension| dimension >= dimensions)
+        {
+            return Err(AxleLoadsError::DimensionOutOfBounds(dimension));
+        }
+
+        Ok(())
+    }
+
+    /// Weights on the front and rear axles with `load` on board
+    pub fn axle_weights(&self, load: &impl AmountExpression) -> (Kilograms, Kilograms) {
+        let weight = load.get(self.weight_dimension);
+        let center_of_gravity =
+            self.loading_space_offset + load.get(self.loading_meters_dimension) / 2.0;
+        let rear_share = center_of_gravity / self.wheelbase;
+
+        (
+            self.front_axle.empty_weight + Kilograms::new(weight * (1.0 - rear_share)),
+            self.rear_axle.empty_weight + Kilograms::new(weight * rear_share),
+        )
+    }
+
+    /// Weight over the limits of the axles with `load` on board, summed over both axles
+    pub fn overload(&self, load: &impl AmountExpression) -> Kilograms {
+        let (front, rear) = self.axle_weights(load);
+
+        Kilograms::new(
+            (front - self.front_axle.maximum_weight).value().max(0.0)
+                + (rear - self.rear_axle.maximum_weight).value().max(0.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::problem::amount::Amount;
+
+    use super::*;
+
+    fn axle_loads() -> AxleLoads {
+        AxleLoads::new(
+            0,
+            1,
+            4.0,
+            1.0,
+            Axle {
+                empty_weight: Kilograms::new(5000.0),
+                maximum_weight: Kilograms::new(7100.0),
+            },
+            Axle {
+                empty_weight: Kilograms::new(3000.0),
+                maximum_weight: Kilograms::new(11500.0),
+            },
+        )
+    }
+
+    #[test]
+    fn test_axle_weights() {
+        let axle_loads = axle_loads();
+
+        // 8 pallets against the front wall, the center of gravity is 2.6 m behind the front axle
+        let load = Amount::from_vec(vec![4000.0, 8.0 * EURO_PALLET_LOADING_METERS]);
+        let (front, rear) = axle_loads.axle_weights(&load);
+        assert!((front.value() - 6400.0).abs() < 1e-6);
+        assert!((rear.value() - 5600.0).abs() < 1e-6);
+        assert_eq!(axle_loads.overload(&load), Kilograms::ZERO);
+    }
+
+    #[test]
+    fn test_overload() {
+        let axle_loads = axle_loads();
+
+        // A short and heavy load stays over the front axle
+        let load = Amount::from_vec(vec![6000.0, 0.8]);
+        let (front, _) = axle_loads.axle_weights(&load);
+        assert!((front.value() - 8900.0).abs() < 1e-6);
+        assert!((axle_loads.overload(&load).value() - 1800.0).abs() < 1e-6);
+
+        assert_eq!(axle_loads.overload(&Amount::empty()), Kilograms::ZERO);
+    }
+
+    #[test]
+    fn test_validate() {
+        let axle_loads = axle_loads();
+        assert_eq!(axle_loads.validate(2), Ok(()));
+        assert_eq!(
+            axle_loads.validate(1),
+            Err(AxleLoadsError::DimensionOutOfBounds(1))
+        );
+
+        for wheelbase in [0.0, -4.0, f64::NAN, f64::INFINITY] {
+            let axle_loads = AxleLoads {
+                wheelbase,
+                ..axle_loads.clone()
+            };
+            assert!(matches!(
+                axle_loads.validate(2),
+                Err(AxleLoadsError::InvalidWheelbase(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use jiff::SignedDuration;
+use serde::Serialize;
+
+use crate::problem::meters::Meters;
+
+/// Battery of an electric vehicle, the energy is in kWh. The vehicle leaves fully charged and
+/// can stop at a `ChargingStation` to charge again.
+#[derive(Serialize, Debug, Clone)]
+pub struct Battery {
+    capacity: f64,
+    consumption_per_km: f64,
+
+    /// Consumed while the vehicle waits or serves an activity, e.g. for refrigeration
+    idle_consumption_per_hour: f64,
+
+    /// Number of charging stops the vehicle can make at each of its charging stations, see
+    /// `MAX_VEHICLE_CHARGING_STATIONS`
+    maximum_charges: usize,
+}
+
+impl Battery {
+    pub fn new(
+        capacity: f64,
+        consumption_per_km: f64,
+        idle_consumption_per_hour: f64,
+        maximum_charges: usize,
+    ) -> Self {
+        Battery {
+            capacity,
+            consumption_per_km,
+            idle_consumption_per_hour,
+            maximum_charges,
+        }
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    pub fn consumption_per_km(&self) -> f64 {
+        self.consumption_per_km
+    }
+
+    pub fn idle_consumption_per_hour(&self) -> f64 {
+        self.idle_consumption_per_hour
+    }
+
+    pub fn maximum_charges(&self) -> usize {
+        self.maximum_charges
+    }
+
+    pub fn driving_consumption(&self, distance: Meters) -> f64 {
+        distance.km() * self.consumption_per_km
+    }
+
+    pub fn idle_consumption(&self, duration: SignedDuration) -> f64 {
+        duration.as_secs_f64() / 3600.0 * self.idle_consumption_per_hour
+    }
+}
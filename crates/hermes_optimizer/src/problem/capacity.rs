@@ -2,7 +2,9 @@ use crate::problem::amount::{Amount, AmountExpression};
 
 pub type Capacity = Amount;
 
-pub fn is_capacity_satisfied<C, D>(capacity: &C, demand: &D) -> bool
+/// `demand` fits in `capacity` in every dimension, up to `tolerance` over it, see
+/// `Tolerances::capacity`
+pub fn is_capacity_satisfied<C, D>(capacity: &C, demand: &D, tolerance: f64) -> bool
 where
     C: AmountExpression,
     D: AmountExpression,
@@ -11,10 +13,29 @@ where
         return false;
     }
 
-    demand.iter().zip(capacity.iter()).all(|(d, c)| d <= c)
+    demand
+        .iter()
+        .zip(capacity.iter())
+        .all(|(d, c)| d <= c + tolerance)
+}
+
+/// Highest share of the capacity used by `load` across the dimensions, ignoring the dimensions
+/// without capacity
+pub fn utilization<C, D>(capacity: &C, load: &D) -> f64
+where
+    C: AmountExpression,
+    D: AmountExpression,
+{
+    load.iter()
+        .zip(capacity.iter())
+        .filter(|&(_, c)| c > 0.0)
+        .map(|(l, c)| l / c)
+        .fold(0.0, f64::max)
 }
 
-pub fn over_capacity_demand<C, D>(capacity: &C, demand: &D) -> f64
+/// Demand over the capacity summed across the dimensions, the dimensions within `tolerance` of
+/// their capacity are not counted
+pub fn over_capacity_demand<C, D>(capacity: &C, demand: &D, tolerance: f64) -> f64
 where
     C: AmountExpression,
     D: AmountExpression,
@@ -22,6 +43,6 @@ where
     demand
         .iter()
         .zip(capacity.iter())
-        .filter_map(|(d, c)| if d > c { Some(d - c) } else { None })
+        .filter_map(|(d, c)| if d > c + tolerance { Some(d - c) } else { None })
         .sum()
 }
@@ -0,0 +1,111 @@
+use jiff::SignedDuration;
+
+use crate::problem::location::LocationIdx;
+
+/// Charging power (in kW) of a station by state of charge of the battery, each point gives the
+/// power from its state of charge (between 0 and 1) up to the next point
+#[derive(Clone, Debug)]
+pub struct ChargeCurve {
+    points: Vec<(f64, f64)>,
+}
+
+impl ChargeCurve {
+    pub fn new(mut points: Vec<(f64, f64)>) -> Self {
+        points.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        ChargeCurve { points }
+    }
+
+    pub fn constant(power: f64) -> Self {
+        ChargeCurve {
+            points: vec![(0.0, power)],
+        }
+    }
+
+    /// Power at `state_of_charge`, the one of the first point below it is used before the curve
+    /// starts
+    fn power_at(&self, state_of_charge: f64) -> f64 {
+        self.points
+            .iter()
+            .rev()
+            .find(|&&(from, _)| from <= state_of_charge)
+            .or(self.points.first())
+            .map_or(0.0, |&(_, power)| power)
+    }
+
+    /// Time to charge a battery of `battery_capacity` kWh from the state of charge `from` to `to`
+    pub fn charge_duration(&self, battery_capacity: f64, from: f64, to: f64) -> SignedDuration {
+        let mut hours = 0.0;
+        let mut state_of_charge = from;
+
+        while state_of_charge < to {
+            let next = self
+                .points
+                .iter()
+                .map(|&(point, _)| point)
+                .find(|&point| point > state_of_charge)
+                .unwrap_or(to)
+                .min(to);
+
+            let power = self.power_at(state_of_charge);
+            if power <= 0.0 {
+                return SignedDuration::MAX;
+            }
+
+            hours += (next - state_of_charge) * battery_capacity / power;
+            state_of_charge = next;
+        }
+
+        SignedDuration::from_secs_f64(hours * 3600.0)
+    }
+}
+
+/// Station where the vehicles with a battery can recharge, see `Battery`
+#[derive(Clone, Debug)]
+pub struct ChargingStation {
+    external_id: String,
+    location_id: LocationIdx,
+    charge_curve: ChargeCurve,
+}
+
+impl ChargingStation {
+    pub fn new(external_id: String, location_id: LocationIdx, charge_curve: ChargeCurve) -> Self {
+        ChargingStation {
+            external_id,
+            location_id,
+            charge_curve,
+        }
+    }
+
+    pub fn external_id(&self) -> &str {
+        &self.external_id
+    }
+
+    pub fn location_id(&self) -> LocationIdx {
+        self.location_id
+    }
+
+    pub fn charge_curve(&self) -> &ChargeCurve {
+        &self.charge_curve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use super::ChargeCurve;
+
+    #[test]
+    fn test_charge_duration_slows_down_above_80_percent() {
+        let curve = ChargeCurve::new(vec![(0.8, 25.0), (0.0, 100.0)]);
+        let minutes = |duration: SignedDuration| (duration.as_secs_f64() / 60.0).round();
+
+        // 80 kWh at 100 kW then 20 kWh at 25 kW
+        assert_eq!(minutes(curve.charge_duration(100.0, 0.0, 1.0)), 96.0);
+        assert_eq!(minutes(curve.charge_duration(100.0, 0.5, 0.8)), 18.0);
+        assert_eq!(
+            minutes(ChargeCurve::constant(50.0).charge_duration(100.0, 0.0, 1.0)),
+            120.0
+        );
+    }
+}
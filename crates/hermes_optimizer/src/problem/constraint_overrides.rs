@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Constraints of the solver switched on or off for a problem, by constraint name.
+///
+/// All the built-in constraints are on by default. The hard constraints keep the solutions
+/// feasible, switching one off is only accepted with `allow_disabling_hard_constraints`
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConstraintOverrides {
+    /// e.g. `{ "waiting_duration": false, "maximum_activities": false }`
+    #[serde(default)]
+    pub enabled: BTreeMap<String, bool>,
+
+    #[serde(default)]
+    pub allow_disabling_hard_constraints: bool,
+
+    /// Makes the `territory` constraint soft, the vehicles then serve jobs outside of their
+    /// territory at this cost per activity
+    pub territory_penalty: Option<f64>,
+}
+
+impl ConstraintOverrides {
+    pub fn disabled(&self) -> impl Iterator<Item = &str> {
+        self.enabled
+            .iter()
+            .filter(|(_, enabled)| !**enabled)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ConstraintOverrideError {
+    #[error("Unknown constraint {0}")]
+    UnknownConstraint(String),
+
+    #[error("The hard constraint {0} can only be disabled with allow_disabling_hard_constraints")]
+    HardConstraintDisabled(String),
+
+    #[error("The territory penalty must be a non-negative number")]
+    InvalidTerritoryPenalty,
+}
@@ -0,0 +1,163 @@
+use jiff::{SignedDuration, Timestamp};
+
+use crate::problem::{location::LocationIdx, time_window::TimeWindow};
+
+/// Depot the vehicles start from and return to, the routes must leave and come back while it is
+/// open
+#[derive(Clone, Debug)]
+pub struct Depot {
+    location_id: LocationIdx,
+
+    /// Routes start and end within one of these windows, always open when empty
+    opening_hours: Vec<TimeWindow>,
+
+    /// Vehicles can only be loaded, and leave the depot, within one of these windows, any time
+    /// during the opening hours when empty
+    loading_windows: Vec<TimeWindow>,
+}
+
+impl Depot {
+    pub fn new(
+        location_id: LocationIdx,
+        opening_hours: Vec<TimeWindow>,
+        loading_windows: Vec<TimeWindow>,
+    ) -> Self {
+        Depot {
+            location_id,
+            opening_hours,
+            loading_windows,
+        }
+    }
+
+    pub fn location_id(&self) -> LocationIdx {
+        self.location_id
+    }
+
+    pub fn opening_hours(&self) -> &[TimeWindow] {
+        &self.opening_hours
+    }
+
+    pub fn loading_windows(&self) -> &[TimeWindow] {
+        &self.loading_windows
+    }
+
+    /// Earliest time a route can start from the depot, used when the vehicle doesn't have an
+    /// earliest start
+    pub fn earliest_departure(&self) -> Option<Timestamp> {
+        self.departure_windows()
+            .iter()
+            .filter_map(TimeWindow::earliest)
+            .min()
+    }
+
+    /// Latest time a route can start from the depot, used when the vehicle doesn't have a latest
+    /// start. None when one of the windows has no end
+    pub fn latest_departure(&self) -> Option<Timestamp> {
+        let mut latest = None;
+        for window in self.departure_windows() {
+            latest = latest.max(Some(window.latest()?));
+        }
+
+        latest
+    }
+
+    fn departure_windows(&self) -> &[TimeWindow] {
+        if self.loading_windows.is_empty() {
+            &self.opening_hours
+        } else {
+            &self.loading_windows
+        }
+    }
+
+    /// Time between a route start and the closest opening hour, and loading window
+    pub fn departure_violation(&self, start: Timestamp) -> SignedDuration {
+        outside_duration(&self.opening_hours, start)
+            + outside_duration(&self.loading_windows, start)
+    }
+
+    /// Time between a route end and the closest opening hour
+    pub fn arrival_violation(&self, end: Timestamp) -> SignedDuration {
+        outside_duration(&self.opening_hours, end)
+    }
+}
+
+/// Duration between `time` and the closest window, zero when it's within one of them or when there
+/// are no windows
+fn outside_duration(windows: &[TimeWindow], time: Timestamp) -> SignedDuration {
+    windows
+        .iter()
+        .map(|window| {
+            if let Some(earliest) = window.earliest()
+                && time < earliest
+            {
+                earliest.duration_since(time)
+            } else if let Some(latest) = window.latest()
+                && time > latest
+            {
+                time.duration_since(latest)
+            } else {
+                SignedDuration::ZERO
+            }
+        })
+        .min()
+        .unwrap_or(SignedDuration::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use crate::problem::time_window::TimeWindow;
+
+    use super::Depot;
+
+    #[test]
+    fn test_depot_hours_violations() {
+        let depot = Depot::new(
+            0.into(),
+            vec![
+                TimeWindow::from_iso(Some("2025-06-10T06:00:00Z"), Some("2025-06-10T12:00:00Z")),
+                TimeWindow::from_iso(Some("2025-06-10T14:00:00Z"), Some("2025-06-10T20:00:00Z")),
+            ],
+            vec![TimeWindow::from_iso(
+                Some("2025-06-10T06:00:00Z"),
+                Some("2025-06-10T08:00:00Z"),
+            )],
+        );
+
+        let at = |time: &str| time.parse().unwrap();
+
+        assert_eq!(depot.earliest_departure(), Some(at("2025-06-10T06:00:00Z")));
+        assert_eq!(depot.latest_departure(), Some(at("2025-06-10T08:00:00Z")));
+
+        assert_eq!(
+            depot.departure_violation(at("2025-06-10T07:00:00Z")),
+            SignedDuration::ZERO
+        );
+        // Open but not loading anymore
+        assert_eq!(
+            depot.departure_violation(at("2025-06-10T09:00:00Z")),
+            SignedDuration::from_hours(1)
+        );
+        assert_eq!(
+            depot.departure_violation(at("2025-06-10T05:00:00Z")),
+            SignedDuration::from_hours(2)
+        );
+
+        assert_eq!(
+            depot.arrival_violation(at("2025-06-10T13:30:00Z")),
+            SignedDuration::from_mins(30)
+        );
+        assert_eq!(
+            depot.arrival_violation(at("2025-06-10T21:00:00Z")),
+            SignedDuration::from_hours(1)
+        );
+
+        let depot = Depot::new(
+            0.into(),
+            vec![TimeWindow::from_iso(Some("2025-06-10T06:00:00Z"), None)],
+            vec![],
+        );
+        assert_eq!(depot.latest_departure(), None);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::problem::{capacity::Capacity, location::LocationIdx};
+
+/// Stock available at a depot, the deliveries loaded by all the vehicles starting from the depot can't
+/// exceed it in any dimension
+#[derive(Clone, Debug)]
+pub struct DepotInventory {
+    location_id: LocationIdx,
+    stock: Capacity,
+}
+
+impl DepotInventory {
+    pub fn new(location_id: LocationIdx, stock: Capacity) -> Self {
+        DepotInventory { location_id, stock }
+    }
+
+    pub fn location_id(&self) -> LocationIdx {
+        self.location_id
+    }
+
+    pub fn stock(&self) -> &Capacity {
+        &self.stock
+    }
+}
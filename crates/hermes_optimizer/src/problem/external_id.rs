@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 
 use crate::problem::job::{ActivityId, Job};
 
-#[derive(JsonSchema)]
+#[derive(JsonSchema, Clone)]
 #[schemars(with = "String")]
 pub enum ExternalActivityId {
     ShipmentPickup(String),
@@ -10,7 +10,7 @@ pub enum ExternalActivityId {
     Service(String),
 }
 
-#[derive(JsonSchema, serde::Serialize, serde::Deserialize)]
+#[derive(JsonSchema, serde::Serialize, serde::Deserialize, Clone)]
 pub struct ExternalJobId(pub String);
 
 impl ExternalJobId {
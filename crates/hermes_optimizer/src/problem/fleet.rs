@@ -3,18 +3,50 @@ use crate::problem::vehicle::{Vehicle, VehicleIdx};
 pub enum Fleet {
     Finite(Vec<Vehicle>),
     Infinite(Vec<Vehicle>),
+    /// Vehicle types with a number of available vehicles each, e.g. the HVRP instances. Like the
+    /// infinite fleet, the routes of a type are created on demand but never more than its count.
+    /// The problem builder rejects the fleets without one positive count per vehicle
+    Counted {
+        vehicles: Vec<Vehicle>,
+        counts: Vec<usize>,
+    },
 }
 
 impl Fleet {
+    pub fn counted(vehicles: Vec<(Vehicle, usize)>) -> Self {
+        let (vehicles, counts) = vehicles.into_iter().unzip();
+        Fleet::Counted { vehicles, counts }
+    }
+
     pub fn is_infinite(&self) -> bool {
         matches!(self, Fleet::Infinite(_))
     }
 
+    /// Maximum number of routes of the vehicle, `None` when unlimited
+    #[inline]
+    pub fn vehicle_limit(&self, vehicle_id: VehicleIdx) -> Option<usize> {
+        match self {
+            Fleet::Finite(_) => Some(1),
+            Fleet::Infinite(_) => None,
+            Fleet::Counted { counts, .. } => Some(counts[vehicle_id.get()]),
+        }
+    }
+
+    /// Maximum number of routes of the fleet, `None` when unlimited
+    pub fn maximum_routes(&self) -> Option<usize> {
+        match self {
+            Fleet::Finite(vehicles) => Some(vehicles.len()),
+            Fleet::Infinite(_) => None,
+            Fleet::Counted { counts, .. } => Some(counts.iter().sum()),
+        }
+    }
+
     #[inline]
     pub fn vehicles(&self) -> &[Vehicle] {
         match self {
             Fleet::Finite(vehicles) => vehicles,
             Fleet::Infinite(vehicles) => vehicles,
+            Fleet::Counted { vehicles, .. } => vehicles,
         }
     }
 
@@ -23,6 +55,7 @@ impl Fleet {
         match self {
             Fleet::Finite(vehicles) => vehicles,
             Fleet::Infinite(vehicles) => vehicles,
+            Fleet::Counted { vehicles, .. } => vehicles,
         }
     }
 
@@ -31,6 +64,7 @@ impl Fleet {
         match self {
             Fleet::Finite(vehicles) => &vehicles[vehicle_id],
             Fleet::Infinite(vehicles) => &vehicles[vehicle_id],
+            Fleet::Counted { vehicles, .. } => &vehicles[vehicle_id],
         }
     }
 }
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    location::Location,
+    travel_time_provider::{
+        TravelMatrixBlock, TravelMatrixBlockFuture, TravelTimeOptions, TravelTimeProvider,
+        TravelTimeVehicleDimensions,
+    },
+};
+
+#[derive(Serialize)]
+struct HttpMatrixRequest<'a> {
+    /// [lon, lat] of each source
+    sources: Vec<[f64; 2]>,
+    targets: Vec<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vehicle_dimensions: Option<TravelTimeVehicleDimensions>,
+}
+
+#[derive(Deserialize)]
+struct HttpMatrixResponse {
+    times: Vec<Vec<f64>>,
+    distances: Vec<Vec<f64>>,
+}
+
+/// Adapter for matrix services behind a plain HTTP endpoint.
+///
+/// The locations are posted as `{"sources": [[lon, lat], ...], "targets": [[lon, lat], ...]}` and
+/// the service answers with `{"times": [[...]], "distances": [[...]]}`, in seconds and meters. The
+/// `profile` and `vehicle_dimensions` of the vehicle profile are posted along when they are set
+pub struct HttpTravelTimeProvider {
+    client: reqwest::Client,
+    url: String,
+    max_locations_per_request: usize,
+}
+
+impl HttpTravelTimeProvider {
+    pub fn new(url: impl Into<String>, max_locations_per_request: usize) -> Self {
+        HttpTravelTimeProvider {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            max_locations_per_request,
+        }
+    }
+}
+
+fn lon_lat(locations: &[Location]) -> Vec<[f64; 2]> {
+    locations
+        .iter()
+        .map(|location| [location.lon(), location.lat()])
+        .collect()
+}
+
+impl TravelTimeProvider for HttpTravelTimeProvider {
+    fn max_locations_per_request(&self) -> usize {
+        self.max_locations_per_request
+    }
+
+    fn fetch_block<'a>(
+        &'a self,
+        sources: &'a [Location],
+        targets: &'a [Location],
+        options: &'a TravelTimeOptions,
+    ) -> TravelMatrixBlockFuture<'a> {
+        Box::pin(async move {
+            let response: HttpMatrixResponse = self
+                .client
+                .post(&self.url)
+                .json(&HttpMatrixRequest {
+                    sources: lon_lat(sources),
+                    targets: lon_lat(targets),
+                    profile: options.profile.as_deref(),
+                    vehicle_dimensions: options.vehicle_dimensions,
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            Ok(TravelMatrixBlock {
+                times: response.times,
+                distances: response.distances,
+            })
+        })
+    }
+}
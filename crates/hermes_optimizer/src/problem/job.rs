@@ -6,8 +6,16 @@ use jiff::SignedDuration;
 use crate::{
     define_index_newtype,
     problem::{
-        capacity::Capacity, location::LocationIdx, service::Service, shipment::Shipment,
-        skill::Skill, time_window::TimeWindows, vehicle::Vehicle,
+        capacity::Capacity,
+        location::LocationIdx,
+        position_preference::PositionPreference,
+        service::{Service, ServiceType},
+        shipment::Shipment,
+        skill::Skill,
+        stop_sequence::StopSequence,
+        tag::Tag,
+        time_window::TimeWindows,
+        vehicle::Vehicle,
     },
     utils::bitset::BitSet,
 };
@@ -104,6 +112,20 @@ impl JobActivity<'_> {
             JobActivity::ShipmentDelivery(shipment) => shipment.delivery().has_time_windows(),
         }
     }
+
+    pub fn position_preference(&self) -> Option<&PositionPreference> {
+        match self {
+            JobActivity::Service(service) => service.position_preference(),
+            JobActivity::ShipmentPickup(_) | JobActivity::ShipmentDelivery(_) => None,
+        }
+    }
+
+    pub fn stop_sequence(&self) -> Option<StopSequence> {
+        match self {
+            JobActivity::Service(service) => service.stop_sequence(),
+            JobActivity::ShipmentPickup(_) | JobActivity::ShipmentDelivery(_) => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -150,6 +172,44 @@ impl Job {
         }
     }
 
+    pub fn set_time_window_tolerance(&mut self, tolerance: SignedDuration) {
+        match self {
+            Job::Service(service) => service.set_time_window_tolerance(tolerance),
+            Job::Shipment(shipment) => shipment.set_time_window_tolerance(tolerance),
+        }
+    }
+
+    pub fn tags(&self) -> &FxHashSet<Tag> {
+        match self {
+            Job::Service(service) => service.tags(),
+            Job::Shipment(shipment) => shipment.tags(),
+        }
+    }
+
+    /// A job is allowed on a vehicle when none of its tags are forbidden by the vehicle and,
+    /// if the vehicle has allowed tags, all of its tags are allowed
+    pub fn tags_allowed_by_vehicle(&self, vehicle: &Vehicle) -> bool {
+        self.tags_bitset().is_subset(vehicle.accepted_tags_bitset())
+    }
+
+    pub fn tags_bitset(&self) -> &BitSet {
+        match self {
+            Job::Service(service) => service.tags_bitset(),
+            Job::Shipment(shipment) => shipment.tags_bitset(),
+        }
+    }
+
+    pub fn build_tags_bitset(&mut self, tag_registry: &[Tag]) {
+        self.set_tags_bitset(BitSet::from_registry(tag_registry, self.tags()));
+    }
+
+    fn set_tags_bitset(&mut self, tags_bitset: BitSet) {
+        match self {
+            Job::Service(service) => service.set_tags_bitset(tags_bitset),
+            Job::Shipment(shipment) => shipment.set_tags_bitset(tags_bitset),
+        }
+    }
+
     pub fn external_id(&self) -> &str {
         match self {
             Job::Service(service) => service.external_id(),
@@ -170,6 +230,50 @@ impl Job {
             Job::Shipment(shipment) => shipment.has_time_windows(),
         }
     }
+
+    pub fn max_ride_duration(&self) -> Option<SignedDuration> {
+        match self {
+            Job::Service(_) => None,
+            Job::Shipment(shipment) => shipment.max_ride_duration(),
+        }
+    }
+
+    pub fn is_reload(&self) -> bool {
+        matches!(self, Job::Service(service) if service.service_type() == ServiceType::Reload)
+    }
+
+    pub fn is_charge(&self) -> bool {
+        matches!(self, Job::Service(service) if service.service_type() == ServiceType::Charge)
+    }
+
+    /// Reloads and charges are generated for a vehicle, they are not jobs to serve
+    pub fn is_vehicle_stop(&self) -> bool {
+        self.is_reload() || self.is_charge()
+    }
+
+    pub fn has_time_window_preferences(&self) -> bool {
+        match self {
+            Job::Service(service) => service.time_windows().has_preferences(),
+            Job::Shipment(shipment) => {
+                shipment.pickup().time_windows().has_preferences()
+                    || shipment.delivery().time_windows().has_preferences()
+            }
+        }
+    }
+
+    pub fn has_position_preference(&self) -> bool {
+        match self {
+            Job::Service(service) => service.position_preference().is_some(),
+            Job::Shipment(_) => false,
+        }
+    }
+
+    pub fn has_stop_sequence(&self) -> bool {
+        match self {
+            Job::Service(service) => service.stop_sequence().is_some(),
+            Job::Shipment(_) => false,
+        }
+    }
 }
 
 #[cfg(test)]
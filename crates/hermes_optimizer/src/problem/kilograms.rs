@@ -0,0 +1,83 @@
+use std::{
+    iter::Sum,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Weight of a load, the capacities and demands are unitless amounts and a weight dimension is
+/// built with `Amount::from_weight` or `Kilograms::value`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize, Serialize, JsonSchema)]
+pub struct Kilograms(f64);
+
+impl Kilograms {
+    pub const ZERO: Kilograms = Kilograms(0.0);
+
+    pub fn new(value: f64) -> Self {
+        Kilograms(value)
+    }
+
+    pub fn from_tonnes(tonnes: f64) -> Self {
+        Kilograms(tonnes * 1000.0)
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    pub fn tonnes(&self) -> f64 {
+        self.0 / 1000.0
+    }
+}
+
+impl Add for Kilograms {
+    type Output = Kilograms;
+
+    fn add(self, other: Kilograms) -> Kilograms {
+        Kilograms(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Kilograms {
+    fn add_assign(&mut self, other: Kilograms) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Kilograms {
+    type Output = Kilograms;
+
+    fn sub(self, other: Kilograms) -> Kilograms {
+        Kilograms(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Kilograms {
+    fn sub_assign(&mut self, other: Kilograms) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Kilograms {
+    fn sum<I: Iterator<Item = Kilograms>>(iter: I) -> Kilograms {
+        iter.fold(Kilograms::ZERO, |acc, x| acc + x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::problem::amount::Amount;
+
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Kilograms::from_tonnes(3.5), Kilograms::new(3500.0));
+        assert_eq!(Kilograms::new(750.0).tonnes(), 0.75);
+        assert_eq!(
+            Amount::from_weight(Kilograms::new(20.0)).to_vec(),
+            vec![20.0]
+        );
+    }
+}
@@ -8,7 +8,26 @@ impl Kmh {
         Kmh(value)
     }
 
+    pub fn from_meters_per_second(value: f64) -> Self {
+        Kmh(value * 3.6)
+    }
+
     pub fn value(&self) -> f64 {
         self.0
     }
+
+    pub fn meters_per_second(&self) -> f64 {
+        self.0 / 3.6
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Kmh::from_meters_per_second(10.0), Kmh::new(36.0));
+        assert_eq!(Kmh::new(72.0).meters_per_second(), 20.0);
+    }
 }
@@ -1,62 +1,108 @@
 use geo::{Bearing, Distance, Euclidean, Haversine};
+use hermes_geo::GeoPoint;
 
 use crate::define_index_newtype;
 
 define_index_newtype!(LocationIdx, Location);
 
+/// A point on the earth, or in the plane of the benchmark instances whose coordinates are out of
+/// the range of a `GeoPoint`
 pub struct Location {
-    point: geo::Point,
+    coordinates: Coordinates,
+}
+
+#[derive(Clone, Copy)]
+enum Coordinates {
+    Geo(GeoPoint),
+    Cartesian { x: f64, y: f64 },
 }
 
 impl Location {
     pub fn from_cartesian(x: f64, y: f64) -> Self {
         Self {
-            point: geo::Point::new(x, y),
+            coordinates: Coordinates::Cartesian { x, y },
         }
     }
 
+    /// Panics when the coordinates are out of range, see `try_from_lat_lon`
     pub fn from_lat_lon(lat: f64, lon: f64) -> Self {
-        Self {
-            point: geo::Point::new(lon, lat),
-        }
+        Self::from(GeoPoint::from_lat_lon(lat, lon))
+    }
+
+    /// None when the latitude is not in [-90, 90] or the longitude not in [-180, 180]
+    pub fn try_from_lat_lon(lat: f64, lon: f64) -> Option<Self> {
+        ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon))
+            .then(|| Self::from_lat_lon(lat, lon))
     }
 
     pub fn x(&self) -> f64 {
-        self.point.x()
+        match self.coordinates {
+            Coordinates::Geo(point) => point.lon(),
+            Coordinates::Cartesian { x, .. } => x,
+        }
     }
 
     pub fn y(&self) -> f64 {
-        self.point.y()
+        match self.coordinates {
+            Coordinates::Geo(point) => point.lat(),
+            Coordinates::Cartesian { y, .. } => y,
+        }
     }
 
     pub fn lon(&self) -> f64 {
-        self.point.x()
+        self.x()
     }
 
     pub fn lat(&self) -> f64 {
-        self.point.y()
+        self.y()
+    }
+
+    /// Created from a latitude and longitude rather than cartesian coordinates
+    pub fn is_geographic(&self) -> bool {
+        matches!(self.coordinates, Coordinates::Geo(_))
+    }
+
+    /// The location as a point on the earth, only meaningful for the locations created with
+    /// `from_lat_lon` or from a `GeoPoint`
+    pub fn geo_point(&self) -> GeoPoint {
+        match self.coordinates {
+            Coordinates::Geo(point) => point,
+            Coordinates::Cartesian { x, y } => GeoPoint::new(x, y),
+        }
+    }
+
+    fn point(&self) -> geo::Point {
+        geo::Point::new(self.x(), self.y())
     }
 
     pub fn euclidean_distance(&self, to: &Location) -> f64 {
         let euclidean = Euclidean;
-        euclidean.distance(&self.point, &to.point)
+        euclidean.distance(&self.point(), &to.point())
     }
 
     pub fn haversine_distance(&self, to: &Location) -> f64 {
         let haversine = Haversine;
 
-        haversine.distance(self.point, to.point)
+        haversine.distance(self.point(), to.point())
     }
 
     pub fn bearing(&self, dest: &Self) -> f64 {
         let haversine = Haversine;
-        haversine.bearing(self.point, dest.point)
+        haversine.bearing(self.point(), dest.point())
+    }
+}
+
+impl From<GeoPoint> for Location {
+    fn from(point: GeoPoint) -> Self {
+        Self {
+            coordinates: Coordinates::Geo(point),
+        }
     }
 }
 
 impl From<&Location> for geo::Point<f64> {
     fn from(location: &Location) -> Self {
-        location.point
+        location.point()
     }
 }
 
@@ -79,4 +125,24 @@ mod tests {
         let dist = loc1.euclidean_distance(&loc2);
         assert_eq!(dist, 5.0);
     }
+
+    #[test]
+    fn geo_point_conversion() {
+        let point = hermes_geo::GeoPoint::new(4.3517, 50.8503);
+        let location = super::Location::from(point);
+
+        assert_eq!((location.lat(), location.lon()), (50.8503, 4.3517));
+        assert_eq!(location.geo_point(), point);
+    }
+
+    #[test]
+    fn coordinates_out_of_range() {
+        assert!(super::Location::try_from_lat_lon(50.8503, 4.3517).is_some());
+        assert!(super::Location::try_from_lat_lon(91.0, 4.3517).is_none());
+        assert!(super::Location::try_from_lat_lon(50.8503, f64::NAN).is_none());
+
+        // The benchmark coordinates are far outside of the range of a `GeoPoint`
+        let location = super::Location::from_cartesian(1000.0, -500.0);
+        assert_eq!((location.x(), location.y()), (1000.0, -500.0));
+    }
 }
@@ -19,10 +19,18 @@ impl Meters {
         Meters(value)
     }
 
+    pub fn from_km(km: f64) -> Self {
+        Meters(km * 1000.0)
+    }
+
     pub fn value(&self) -> f64 {
         self.0
     }
 
+    pub fn km(&self) -> f64 {
+        self.0 / 1000.0
+    }
+
     pub fn is_zero(&self) -> bool {
         self.0 == 0.0
     }
@@ -114,3 +122,14 @@ impl Sum for Meters {
         iter.fold(Meters::ZERO, |acc, x| acc + x)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Meters::from_km(2.5), Meters::new(2500.0));
+        assert_eq!(Meters::new(1500.0).km(), 1.5);
+    }
+}
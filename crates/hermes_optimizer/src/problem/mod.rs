@@ -1,20 +1,35 @@
 pub mod amount;
+pub mod axle_loads;
+pub mod battery;
 pub mod capacity;
+pub mod charging_station;
+pub mod constraint_overrides;
+pub mod depot;
+pub mod depot_inventory;
 pub mod distance_method;
 pub mod external_id;
 pub mod fleet;
+pub mod http_travel_time_provider;
 pub mod job;
+pub mod kilograms;
 pub mod kmh;
 pub mod location;
 pub mod meters;
+pub mod position_preference;
 pub mod relation;
 pub mod service;
 mod service_location_index;
 pub mod shipment;
 pub mod skill;
+pub mod stop_sequence;
+pub mod tag;
 pub mod task_dependencies;
+pub mod territory;
 pub mod time_window;
+pub mod tolerances;
 pub mod travel_cost_matrix;
+pub mod travel_time_provider;
 pub mod vehicle;
+pub mod vehicle_cost_class;
 pub mod vehicle_profile;
 pub mod vehicle_routing_problem;
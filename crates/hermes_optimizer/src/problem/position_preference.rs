@@ -0,0 +1,69 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Soft preference on the position of an activity in its route, e.g. "among the first 5 stops".
+///
+/// Positions are 1-based: the first activity of a route is at position 1.
+/// Every position outside of `[first_position, last_position]` is penalized by `penalty`.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Copy, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PositionPreference {
+    pub first_position: Option<usize>,
+    pub last_position: Option<usize>,
+    pub penalty: f64,
+}
+
+impl PositionPreference {
+    /// Number of positions between `position` and the preferred band
+    pub fn violation(&self, position: usize) -> usize {
+        if let Some(first_position) = self.first_position
+            && position < first_position
+        {
+            return first_position - position;
+        }
+
+        if let Some(last_position) = self.last_position
+            && position > last_position
+        {
+            return position - last_position;
+        }
+
+        0
+    }
+
+    pub fn cost(&self, position: usize) -> f64 {
+        self.violation(position) as f64 * self.penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PositionPreference;
+
+    #[test]
+    fn test_position_preference_violation() {
+        let preference = PositionPreference {
+            first_position: Some(2),
+            last_position: Some(5),
+            penalty: 10.0,
+        };
+
+        assert_eq!(preference.violation(1), 1);
+        assert_eq!(preference.violation(2), 0);
+        assert_eq!(preference.violation(5), 0);
+        assert_eq!(preference.violation(8), 3);
+        assert_eq!(preference.cost(8), 30.0);
+    }
+
+    #[test]
+    fn test_position_preference_open_band() {
+        let preference = PositionPreference {
+            first_position: None,
+            last_position: Some(5),
+            penalty: 1.0,
+        };
+
+        assert_eq!(preference.violation(1), 0);
+        assert_eq!(preference.violation(6), 1);
+    }
+}
@@ -39,30 +39,30 @@ pub enum Relation {
     InDirectSequence(InDirectSequenceRelation),
 }
 
-#[derive(JsonSchema, Serialize, Deserialize)]
+#[derive(JsonSchema, Serialize, Deserialize, Clone)]
 pub struct ExternalInDirectSequenceRelation {
     pub vehicle_id: Option<String>,
     pub ids: Vec<ExternalActivityId>,
 }
 
-#[derive(JsonSchema, Serialize, Deserialize)]
+#[derive(JsonSchema, Serialize, Deserialize, Clone)]
 pub struct ExternalInSequenceRelation {
     pub vehicle_id: Option<String>,
     pub ids: Vec<ExternalActivityId>,
 }
 
-#[derive(JsonSchema, Serialize, Deserialize)]
+#[derive(JsonSchema, Serialize, Deserialize, Clone)]
 pub struct ExternalInSameRouteRelation {
     pub vehicle_id: Option<String>,
     pub ids: Vec<ExternalJobId>,
 }
 
-#[derive(JsonSchema, Serialize, Deserialize)]
+#[derive(JsonSchema, Serialize, Deserialize, Clone)]
 pub struct ExternalNotInSameRouteRelation {
     pub ids: Vec<ExternalJobId>,
 }
 
-#[derive(JsonSchema, Serialize, Deserialize)]
+#[derive(JsonSchema, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ExternalRelation {
     InSameRoute(ExternalInSameRouteRelation),
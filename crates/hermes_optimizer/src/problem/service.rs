@@ -5,7 +5,10 @@ use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 use crate::{
-    problem::{skill::Skill, time_window::TimeWindows},
+    problem::{
+        position_preference::PositionPreference, skill::Skill, stop_sequence::StopSequence,
+        tag::Tag, time_window::TimeWindows,
+    },
     utils::bitset::BitSet,
 };
 
@@ -17,6 +20,16 @@ pub enum ServiceType {
     Pickup,
     #[default]
     Delivery,
+
+    /// Return to the depot mid-shift, the pickups are unloaded and the deliveries of the next trip
+    /// are loaded. Generated by the problem for the vehicles which can reload.
+    #[serde(skip_deserializing)]
+    Reload,
+
+    /// Stop at a charging station to charge the battery. Generated by the problem for the vehicles
+    /// with a battery.
+    #[serde(skip_deserializing)]
+    Charge,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -32,10 +45,20 @@ pub struct Service {
     #[serde(skip)]
     skills_bitset: BitSet,
 
+    #[serde(default)]
+    tags: FxHashSet<Tag>,
+
+    #[serde(skip)]
+    tags_bitset: BitSet,
+
     service_duration: SignedDuration,
 
     #[serde(default = "ServiceType::default")]
     service_type: ServiceType,
+
+    position_preference: Option<PositionPreference>,
+
+    stop_sequence: Option<StopSequence>,
 }
 
 impl Service {
@@ -43,6 +66,10 @@ impl Service {
         &self.skills
     }
 
+    pub fn tags(&self) -> &FxHashSet<Tag> {
+        &self.tags
+    }
+
     pub fn external_id(&self) -> &str {
         &self.external_id
     }
@@ -71,13 +98,33 @@ impl Service {
         !self.time_windows.is_empty()
     }
 
+    pub fn position_preference(&self) -> Option<&PositionPreference> {
+        self.position_preference.as_ref()
+    }
+
+    pub fn stop_sequence(&self) -> Option<StopSequence> {
+        self.stop_sequence
+    }
+
     pub fn skills_bitset(&self) -> &BitSet {
         &self.skills_bitset
     }
 
+    pub fn set_time_window_tolerance(&mut self, tolerance: SignedDuration) {
+        self.time_windows.set_tolerance(tolerance);
+    }
+
     pub fn set_skills_bitset(&mut self, skills_bitset: BitSet) {
         self.skills_bitset = skills_bitset;
     }
+
+    pub fn tags_bitset(&self) -> &BitSet {
+        &self.tags_bitset
+    }
+
+    pub fn set_tags_bitset(&mut self, tags_bitset: BitSet) {
+        self.tags_bitset = tags_bitset;
+    }
 }
 
 #[derive(Default)]
@@ -87,8 +134,11 @@ pub struct ServiceBuilder {
     time_windows: Option<Vec<TimeWindow>>,
     demand: Option<Capacity>,
     skills: Option<Vec<Skill>>,
+    tags: Option<Vec<Tag>>,
     service_duration: Option<SignedDuration>,
     service_type: Option<ServiceType>,
+    position_preference: Option<PositionPreference>,
+    stop_sequence: Option<StopSequence>,
 }
 
 impl ServiceBuilder {
@@ -132,11 +182,29 @@ impl ServiceBuilder {
         self
     }
 
+    pub fn set_tags(&mut self, tags: Vec<String>) -> &mut ServiceBuilder {
+        self.tags = Some(tags.into_iter().map(Tag::new).collect());
+        self
+    }
+
     pub fn set_service_duration(&mut self, service_time: SignedDuration) -> &mut ServiceBuilder {
         self.service_duration = Some(service_time);
         self
     }
 
+    pub fn set_position_preference(
+        &mut self,
+        position_preference: PositionPreference,
+    ) -> &mut ServiceBuilder {
+        self.position_preference = Some(position_preference);
+        self
+    }
+
+    pub fn set_stop_sequence(&mut self, stop_sequence: StopSequence) -> &mut ServiceBuilder {
+        self.stop_sequence = Some(stop_sequence);
+        self
+    }
+
     pub fn build(self) -> Service {
         Service {
             external_id: self.external_id.expect("Expected service id"),
@@ -152,8 +220,12 @@ impl ServiceBuilder {
             )),
             service_type: self.service_type.unwrap_or(ServiceType::Delivery),
             skills: FxHashSet::from_iter(self.skills.unwrap_or_default()),
+            tags: FxHashSet::from_iter(self.tags.unwrap_or_default()),
+            position_preference: self.position_preference,
+            stop_sequence: self.stop_sequence,
             // Will be filled later by the problem
             skills_bitset: BitSet::empty(),
+            tags_bitset: BitSet::empty(),
         }
     }
 }
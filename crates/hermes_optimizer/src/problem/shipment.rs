@@ -8,6 +8,7 @@ use crate::{
         capacity::Capacity,
         location::LocationIdx,
         skill::Skill,
+        tag::Tag,
         time_window::{TimeWindow, TimeWindows},
     },
     utils::bitset::BitSet,
@@ -48,6 +49,10 @@ impl ShipmentLocation {
     pub fn has_time_windows(&self) -> bool {
         !self.time_windows.is_empty()
     }
+
+    fn set_time_window_tolerance(&mut self, tolerance: SignedDuration) {
+        self.time_windows.set_tolerance(tolerance);
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -56,9 +61,16 @@ pub struct Shipment {
     demand: Capacity,
     pickup: ShipmentLocation,
     delivery: ShipmentLocation,
+
+    /// Longest time the shipment can stay in the vehicle, from the departure of its pickup to the
+    /// start of its delivery
+    max_ride_duration: Option<SignedDuration>,
     skills: FxHashSet<Skill>,
     #[serde(skip)]
     skills_bitset: BitSet,
+    tags: FxHashSet<Tag>,
+    #[serde(skip)]
+    tags_bitset: BitSet,
 }
 
 impl Shipment {
@@ -66,6 +78,10 @@ impl Shipment {
         &self.skills
     }
 
+    pub fn tags(&self) -> &FxHashSet<Tag> {
+        &self.tags
+    }
+
     pub fn external_id(&self) -> &str {
         &self.external_id
     }
@@ -82,6 +98,10 @@ impl Shipment {
         &self.delivery
     }
 
+    pub fn max_ride_duration(&self) -> Option<SignedDuration> {
+        self.max_ride_duration
+    }
+
     pub fn has_time_windows(&self) -> bool {
         !self.pickup.time_windows.is_empty() || !self.delivery.time_windows.is_empty()
     }
@@ -90,9 +110,22 @@ impl Shipment {
         &self.skills_bitset
     }
 
+    pub fn set_time_window_tolerance(&mut self, tolerance: SignedDuration) {
+        self.pickup.set_time_window_tolerance(tolerance);
+        self.delivery.set_time_window_tolerance(tolerance);
+    }
+
     pub fn set_skills_bitset(&mut self, skills_bitset: BitSet) {
         self.skills_bitset = skills_bitset;
     }
+
+    pub fn tags_bitset(&self) -> &BitSet {
+        &self.tags_bitset
+    }
+
+    pub fn set_tags_bitset(&mut self, tags_bitset: BitSet) {
+        self.tags_bitset = tags_bitset;
+    }
 }
 
 #[derive(Default)]
@@ -105,6 +138,7 @@ pub struct ShipmentBuilder {
     delivery_location_id: Option<usize>,
     delivery_duration: Option<SignedDuration>,
     delivery_time_windows: Option<Vec<TimeWindow>>,
+    max_ride_duration: Option<SignedDuration>,
 }
 
 impl ShipmentBuilder {
@@ -156,6 +190,11 @@ impl ShipmentBuilder {
         self
     }
 
+    pub fn set_max_ride_duration(&mut self, duration: SignedDuration) -> &mut Self {
+        self.max_ride_duration = Some(duration);
+        self
+    }
+
     pub fn build(self) -> Shipment {
         let pickup = ShipmentLocation {
             duration: self.pickup_duration.unwrap_or(SignedDuration::ZERO),
@@ -188,8 +227,11 @@ impl ShipmentBuilder {
             demand,
             pickup,
             delivery,
+            max_ride_duration: self.max_ride_duration,
             skills: FxHashSet::default(),
             skills_bitset: BitSet::empty(),
+            tags: FxHashSet::default(),
+            tags_bitset: BitSet::empty(),
         }
     }
 }
@@ -0,0 +1,25 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Hard constraint on where a job is served in the route that serves it, e.g. picking up keys
+/// before any other stop or returning equipment after every other stop.
+///
+/// When several jobs of a route are pinned, every `First` job is served before the unpinned jobs
+/// and every `Last` job after them.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopSequence {
+    First,
+    Last,
+}
+
+impl StopSequence {
+    /// Order of the group of an activity in a route: first stops, unpinned stops, last stops
+    pub fn rank(stop_sequence: Option<StopSequence>) -> u8 {
+        match stop_sequence {
+            Some(StopSequence::First) => 0,
+            None => 1,
+            Some(StopSequence::Last) => 2,
+        }
+    }
+}
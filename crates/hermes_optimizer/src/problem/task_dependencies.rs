@@ -476,7 +476,7 @@ mod tests {
                 ActivityId::service(3)
             ]
             .into_iter()
-            .collect()
+            .collect::<FxHashSet<_>>()
         );
         assert_eq!(
             dependencies.sequence_after.list[&ActivityId::service(1)]
@@ -0,0 +1,83 @@
+use fxhash::FxHashSet;
+use geo::{BoundingRect, Intersects};
+use serde::Serialize;
+
+use super::location::{Location, LocationIdx};
+
+/// Zone a vehicle is assigned to, see `TerritoryConstraint`
+#[derive(Serialize, Debug, Clone)]
+pub enum Territory {
+    /// Locations inside the polygon, or on its boundary, are part of the territory
+    Polygon {
+        polygon: geo::Polygon,
+        /// Checked before the polygon, most locations outside of the territory are also outside of
+        /// its bounding box
+        bbox: Option<geo::Rect>,
+    },
+    Locations(FxHashSet<LocationIdx>),
+}
+
+impl Territory {
+    pub fn from_polygon(polygon: geo::Polygon) -> Self {
+        Territory::Polygon {
+            bbox: polygon.bounding_rect(),
+            polygon,
+        }
+    }
+
+    pub fn from_location_ids(location_ids: impl IntoIterator<Item = LocationIdx>) -> Self {
+        Territory::Locations(location_ids.into_iter().collect())
+    }
+
+    pub fn contains(&self, location_id: LocationIdx, location: &Location) -> bool {
+        match self {
+            Territory::Polygon { polygon, bbox } => {
+                let Some(bbox) = bbox else {
+                    return false;
+                };
+
+                let (x, y) = (location.x(), location.y());
+                if x < bbox.min().x || x > bbox.max().x || y < bbox.min().y || y > bbox.max().y {
+                    return false;
+                }
+
+                polygon.intersects(&geo::Point::from(location))
+            }
+            Territory::Locations(location_ids) => location_ids.contains(&location_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::polygon;
+
+    use crate::problem::location::{Location, LocationIdx};
+
+    use super::Territory;
+
+    #[test]
+    fn test_polygon_territory() {
+        // Triangle, (4, 1) is in its bounding box but not in the triangle
+        let territory = Territory::from_polygon(polygon![
+            (x: 0.0, y: 0.0),
+            (x: 5.0, y: 0.0),
+            (x: 0.0, y: 5.0),
+        ]);
+
+        let id = LocationIdx::new(0);
+        assert!(territory.contains(id, &Location::from_cartesian(1.0, 1.0)));
+        assert!(territory.contains(id, &Location::from_cartesian(5.0, 0.0)));
+        assert!(!territory.contains(id, &Location::from_cartesian(4.0, 4.0)));
+        assert!(!territory.contains(id, &Location::from_cartesian(10.0, 1.0)));
+    }
+
+    #[test]
+    fn test_locations_territory() {
+        let territory = Territory::from_location_ids([LocationIdx::new(1), LocationIdx::new(2)]);
+        let location = Location::from_cartesian(0.0, 0.0);
+
+        assert!(territory.contains(LocationIdx::new(1), &location));
+        assert!(!territory.contains(LocationIdx::new(3), &location));
+    }
+}
@@ -7,20 +7,31 @@ use smallvec::SmallVec;
 pub struct TimeWindow {
     start: Option<Timestamp>,
     end: Option<Timestamp>,
+
+    /// Soft cost of serving the job in this window rather than in a preferred one, e.g. to prefer
+    /// the morning and accept the afternoon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    penalty: Option<f64>,
+
+    /// Margin after `end`, see `Tolerances::time_window`
+    #[serde(skip)]
+    tolerance: SignedDuration,
 }
 
 impl TimeWindow {
     pub fn new(start: Option<Timestamp>, end: Option<Timestamp>) -> Self {
-        TimeWindow { start, end }
+        TimeWindow {
+            start,
+            end,
+            penalty: None,
+            tolerance: SignedDuration::ZERO,
+        }
     }
 
     pub fn from_iso(start: Option<&str>, end: Option<&str>) -> Self {
         let start_ts = start.map(|s| s.parse().expect("Error parsing ISO"));
         let end_ts = end.map(|e| e.parse().expect("Error parsing ISO"));
-        TimeWindow {
-            start: start_ts,
-            end: end_ts,
-        }
+        TimeWindow::new(start_ts, end_ts)
     }
 
     pub fn earliest(&self) -> Option<Timestamp> {
@@ -31,21 +42,34 @@ impl TimeWindow {
         self.end
     }
 
+    pub fn penalty(&self) -> f64 {
+        self.penalty.unwrap_or(0.0)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.start.is_none() && self.end.is_none()
     }
+
+    /// Latest arrival satisfying the window, its end with the tolerance
+    pub fn deadline(&self) -> Option<Timestamp> {
+        self.end.map(|end| end + self.tolerance)
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: SignedDuration) {
+        self.tolerance = tolerance;
+    }
 }
 
 impl TimeWindow {
     pub fn is_satisfied(&self, arrival: Timestamp) -> bool {
-        match self.end {
-            Some(end) => arrival <= end,
+        match self.deadline() {
+            Some(deadline) => arrival <= deadline,
             None => true,
         }
     }
 
     pub fn overtime(&self, arrival: Timestamp) -> SignedDuration {
-        match self.end {
+        match self.deadline() {
             Some(end) => {
                 let secs = arrival.duration_since(end);
 
@@ -111,6 +135,17 @@ impl TimeWindows {
         self.0.iter().filter_map(|tw| tw.latest()).max()
     }
 
+    /// Latest arrival satisfying one of the windows, see `TimeWindow::deadline`
+    pub fn deadline(&self) -> Option<Timestamp> {
+        self.0.iter().filter_map(|tw| tw.deadline()).max()
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: SignedDuration) {
+        for time_window in &mut self.0 {
+            time_window.set_tolerance(tolerance);
+        }
+    }
+
     pub fn waiting_duration(&self, arrival: Timestamp) -> SignedDuration {
         self.0
             .iter()
@@ -120,6 +155,27 @@ impl TimeWindows {
             .unwrap_or(SignedDuration::ZERO)
     }
 
+    /// Index of the window the job is served in when arriving at `arrival`, the open window
+    /// with the least waiting, like `TimeWindows::waiting_duration`
+    pub fn served_window_index(&self, arrival: Timestamp) -> Option<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, tw)| tw.is_satisfied(arrival))
+            .min_by_key(|(_, tw)| tw.waiting_duration(arrival))
+            .map(|(index, _)| index)
+    }
+
+    /// Penalty of the window the job is served in when arriving at `arrival`
+    pub fn preference_penalty(&self, arrival: Timestamp) -> f64 {
+        self.served_window_index(arrival)
+            .map_or(0.0, |index| self.0[index].penalty())
+    }
+
+    pub fn has_preferences(&self) -> bool {
+        self.0.iter().any(|tw| tw.penalty() != 0.0)
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, TimeWindow> {
         self.0.iter()
     }
@@ -129,6 +185,7 @@ impl TimeWindows {
 pub struct TimeWindowBuilder {
     start: Option<Timestamp>,
     end: Option<Timestamp>,
+    penalty: Option<f64>,
 }
 
 impl TimeWindowBuilder {
@@ -152,10 +209,17 @@ impl TimeWindowBuilder {
         self
     }
 
+    pub fn with_penalty(mut self, penalty: f64) -> Self {
+        self.penalty = Some(penalty);
+        self
+    }
+
     pub fn build(self) -> TimeWindow {
         TimeWindow {
             start: self.start,
             end: self.end,
+            penalty: self.penalty,
+            tolerance: SignedDuration::ZERO,
         }
     }
 }
@@ -226,6 +290,34 @@ mod tests {
         assert!(time_window.is_satisfied("2025-06-10T10:00:01+02:00".parse().unwrap()));
     }
 
+    #[test]
+    fn test_preference_penalty() {
+        let time_windows = TimeWindows::from_vec(vec![
+            TimeWindowBuilder::default()
+                .with_iso_start("2025-06-10T08:00:00+02:00")
+                .with_iso_end("2025-06-10T12:00:00+02:00")
+                .build(),
+            TimeWindowBuilder::default()
+                .with_iso_start("2025-06-10T14:00:00+02:00")
+                .with_iso_end("2025-06-10T18:00:00+02:00")
+                .with_penalty(50.0)
+                .build(),
+        ]);
+
+        assert!(time_windows.has_preferences());
+
+        let morning = "2025-06-10T07:00:00+02:00".parse().unwrap();
+        assert_eq!(time_windows.served_window_index(morning), Some(0));
+        assert_eq!(time_windows.preference_penalty(morning), 0.0);
+
+        let lunch = "2025-06-10T13:00:00+02:00".parse().unwrap();
+        assert_eq!(time_windows.served_window_index(lunch), Some(1));
+        assert_eq!(time_windows.preference_penalty(lunch), 50.0);
+
+        let night = "2025-06-10T19:00:00+02:00".parse().unwrap();
+        assert_eq!(time_windows.served_window_index(night), None);
+    }
+
     #[test]
     fn test_overtime() {
         let time_window = TimeWindowBuilder::default()
@@ -413,4 +505,25 @@ mod tests {
             SignedDuration::ZERO
         );
     }
+
+    #[test]
+    fn test_tolerance() {
+        let mut time_window = TimeWindowBuilder::default()
+            .with_iso_end("2025-06-10T10:00:00+02:00")
+            .build();
+
+        assert!(!time_window.is_satisfied("2025-06-10T10:00:01+02:00".parse().unwrap()));
+
+        time_window.set_tolerance(SignedDuration::from_secs(1));
+
+        assert!(time_window.is_satisfied("2025-06-10T10:00:01+02:00".parse().unwrap()));
+        assert_eq!(
+            time_window.overtime("2025-06-10T10:00:03+02:00".parse().unwrap()),
+            SignedDuration::from_secs(2)
+        );
+        assert_eq!(
+            time_window.latest(),
+            Some("2025-06-10T10:00:00+02:00".parse().unwrap())
+        );
+    }
 }
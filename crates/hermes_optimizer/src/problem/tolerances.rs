@@ -0,0 +1,39 @@
+use jiff::SignedDuration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Absorbs the rounding of the summed demands, e.g. `0.1 + 0.2` over a capacity of `0.3`
+pub const DEFAULT_CAPACITY_TOLERANCE: f64 = 1e-9;
+
+fn default_capacity_tolerance() -> f64 {
+    DEFAULT_CAPACITY_TOLERANCE
+}
+
+/// Margins of the feasibility checks, a value within the margin of its limit satisfies it.
+///
+/// The same margins are used by the constraints, by the validity checks of the insertions and
+/// moves, and when scoring a given plan, so that a borderline stop is either feasible everywhere
+/// or nowhere
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Tolerances {
+    /// Arrivals up to this duration after the end of the time window of a job, and routes ending
+    /// up to this duration after the latest end of the shift of their vehicle, still satisfy them.
+    /// 0 by default
+    #[serde(default)]
+    pub time_window: SignedDuration,
+
+    /// Loads up to this amount over the capacity of a vehicle or the stock of a depot still fit,
+    /// in the unit of the demands, `1e-9` by default
+    #[serde(default = "default_capacity_tolerance")]
+    pub capacity: f64,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Tolerances {
+            time_window: SignedDuration::ZERO,
+            capacity: DEFAULT_CAPACITY_TOLERANCE,
+        }
+    }
+}
@@ -2,6 +2,10 @@ use std::sync::Arc;
 
 use jiff::SignedDuration;
 use rand::Rng;
+use rayon::{
+    iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
 use serde::Deserialize;
 
 use crate::problem::{kmh::Kmh, location::LocationIdx, meters::Meters};
@@ -14,7 +18,7 @@ pub type Cost = f64;
 /// This matrix use a flat structure to store distances, times, and costs between locations.
 /// To find the index for a pair of locations, use the formula:
 /// `index = from * num_locations + to`, where `num_locations` is the total
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct TravelMatrices {
     distances: Arc<Vec<Meters>>,
     times: Arc<Vec<Time>>,
@@ -23,6 +27,96 @@ pub struct TravelMatrices {
     is_symmetric: bool,
 }
 
+/// Convention applied to the euclidean distances, the published costs of the benchmarks are only
+/// reached with the rounding of their format
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DistanceRounding {
+    /// Raw distances
+    #[default]
+    Exact,
+    /// Nearest integer, as EUC_2D in TSPLIB and CVRPLIB
+    Round,
+    /// Integer part, as in the Solomon benchmarks of some papers
+    Truncate,
+    /// Nearest multiple of the inverse of the factor, e.g. 10.0 keeps one decimal. The factor must
+    /// be positive and finite
+    Scale(f64),
+}
+
+impl DistanceRounding {
+    pub fn apply(self, distance: f64) -> f64 {
+        match self {
+            DistanceRounding::Exact => distance,
+            DistanceRounding::Round => distance.round(),
+            DistanceRounding::Truncate => distance.trunc(),
+            DistanceRounding::Scale(factor) => (distance * factor).round() / factor,
+        }
+    }
+
+    /// Whether the factor of `DistanceRounding::Scale` is positive and finite
+    pub fn is_valid(self) -> bool {
+        match self {
+            DistanceRounding::Scale(factor) => factor.is_finite() && factor > 0.0,
+            _ => true,
+        }
+    }
+}
+
+/// Mean radius of the earth used by `geo::Haversine`
+const MEAN_EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Sines and cosines of the half latitudes and longitudes of the locations, computed once so that
+/// the haversine distance between two locations only needs a square root and an arcsine
+struct HaversinePoints {
+    sin_half_lat: Vec<f64>,
+    cos_half_lat: Vec<f64>,
+    sin_half_lon: Vec<f64>,
+    cos_half_lon: Vec<f64>,
+    cos_lat: Vec<f64>,
+}
+
+impl HaversinePoints {
+    fn new(locations: &[Location]) -> Self {
+        let half_lats = locations
+            .iter()
+            .map(|location| location.lat().to_radians() / 2.0);
+        let half_lons = locations
+            .iter()
+            .map(|location| location.lon().to_radians() / 2.0);
+
+        HaversinePoints {
+            sin_half_lat: half_lats.clone().map(f64::sin).collect(),
+            cos_half_lat: half_lats.clone().map(f64::cos).collect(),
+            sin_half_lon: half_lons.clone().map(f64::sin).collect(),
+            cos_half_lon: half_lons.map(f64::cos).collect(),
+            cos_lat: locations
+                .iter()
+                .map(|location| location.lat().to_radians().cos())
+                .collect(),
+        }
+    }
+
+    /// Distances from the location `from` to every location. The sines of the half differences
+    /// are expanded to `sin(a - b) = sin(a)cos(b) - cos(a)sin(b)`, the loop has no trigonometric
+    /// call left and is vectorized
+    fn fill_row(&self, from: usize, row: &mut [Meters]) {
+        let (sin_lat, cos_lat) = (self.sin_half_lat[from], self.cos_half_lat[from]);
+        let (sin_lon, cos_lon) = (self.sin_half_lon[from], self.cos_half_lon[from]);
+        let cos_from_lat = self.cos_lat[from];
+
+        for (to, distance) in row.iter_mut().enumerate() {
+            let sin_half_delta_lat =
+                self.sin_half_lat[to] * cos_lat - self.cos_half_lat[to] * sin_lat;
+            let sin_half_delta_lon =
+                self.sin_half_lon[to] * cos_lon - self.cos_half_lon[to] * sin_lon;
+
+            let a = sin_half_delta_lat * sin_half_delta_lat
+                + cos_from_lat * self.cos_lat[to] * sin_half_delta_lon * sin_half_delta_lon;
+            *distance = (2.0 * MEAN_EARTH_RADIUS * a.min(1.0).sqrt().asin()).into();
+        }
+    }
+}
+
 fn is_flat_matrix_symmetric(matrix: &[f64], num_locations: usize) -> bool {
     for i in 0..num_locations {
         for j in 0..num_locations {
@@ -85,75 +179,83 @@ impl TravelMatrices {
         }
     }
 
+    /// Matrices in the format of the providers, e.g. to back up the matrices of a problem and build
+    /// it again with `from_travel_matrices` without fetching them
+    pub fn to_travel_matrices(&self) -> hermes_matrix_providers::travel_matrices::TravelMatrices {
+        hermes_matrix_providers::travel_matrices::TravelMatrices {
+            distances: self
+                .distances
+                .iter()
+                .map(|distance| distance.value())
+                .collect(),
+            times: self.times.to_vec(),
+            costs: Some(self.costs.to_vec()),
+        }
+    }
+
     #[inline(always)]
     fn index(&self, from: LocationIdx, to: LocationIdx) -> usize {
         from.get() * self.num_locations + to.get()
     }
 
+    /// Distances along the great circle and times at 50km/h, the rows are computed in parallel
     pub fn from_haversine(locations: &[Location]) -> Self {
         let num_locations = locations.len();
-        let mut distances: Vec<Meters> = vec![Meters::ZERO; num_locations * num_locations];
-        let mut times: Vec<Time> = vec![0.0; num_locations * num_locations];
-        let mut costs: Vec<Cost> = vec![0.0; num_locations * num_locations];
-
-        for (i, from) in locations.iter().enumerate() {
-            for (j, to) in locations.iter().enumerate() {
-                distances[i * num_locations + j] = from.haversine_distance(to).into();
-                // Assume average speed of 50km/h
-                let speed = Kmh::new(50.0);
-                times[i * num_locations + j] =
-                    ((distances[i * num_locations + j]) / speed).as_secs_f64();
-                costs[i * num_locations + j] = distances[i * num_locations + j].value();
-            }
-        }
+        let points = HaversinePoints::new(locations);
 
-        let distances = Arc::new(distances);
-        let costs = Arc::new(costs);
-        let times = Arc::new(times);
+        let mut distances: Vec<Meters> = vec![Meters::ZERO; num_locations * num_locations];
+        distances
+            .par_chunks_mut(num_locations.max(1))
+            .enumerate()
+            .for_each(|(from, row)| points.fill_row(from, row));
+
+        // Assume average speed of 50km/h
+        let speed = Kmh::new(50.0);
+        let times = distances
+            .par_iter()
+            .map(|&distance| (distance / speed).as_secs_f64())
+            .collect::<Vec<Time>>();
+        let costs = distances
+            .par_iter()
+            .map(|distance| distance.value())
+            .collect::<Vec<Cost>>();
 
         TravelMatrices {
-            distances,
-            times,
-            costs,
+            distances: Arc::new(distances),
+            times: Arc::new(times),
+            costs: Arc::new(costs),
             num_locations,
             is_symmetric: true,
         }
     }
 
-    pub fn from_euclidean(locations: &[Location], round: bool) -> Self {
+    pub fn from_euclidean(locations: &[Location], rounding: DistanceRounding) -> Self {
         let num_locations = locations.len();
-        let mut distances: Vec<Meters> = vec![Meters::ZERO; num_locations * num_locations];
+        let xs = locations.iter().map(Location::x).collect::<Vec<_>>();
+        let ys = locations.iter().map(Location::y).collect::<Vec<_>>();
 
-        for (i, from) in locations.iter().enumerate() {
-            for (j, to) in locations.iter().enumerate() {
-                distances[i * num_locations + j] = if round {
-                    from.euclidean_distance(to).round().into()
-                } else {
-                    from.euclidean_distance(to).into()
+        let mut distances: Vec<Meters> = vec![Meters::ZERO; num_locations * num_locations];
+        distances
+            .par_chunks_mut(num_locations.max(1))
+            .enumerate()
+            .for_each(|(from, row)| {
+                let (x, y) = (xs[from], ys[from]);
+                for ((distance, &to_x), &to_y) in row.iter_mut().zip(&xs).zip(&ys) {
+                    let value = (to_x - x).hypot(to_y - y);
+                    *distance = rounding.apply(value).into();
                 }
-            }
-        }
+            });
 
-        let distances = Arc::new(distances);
-        let costs = Arc::new(
-            distances
-                .iter()
-                .copied()
-                .map(|d| d.value())
-                .collect::<Vec<_>>(),
-        );
-        let times = Arc::new(
-            distances
-                .iter()
-                .copied()
-                .map(|d| d.value())
-                .collect::<Vec<_>>(),
-        );
+        let costs = distances
+            .par_iter()
+            .map(|distance| distance.value())
+            .collect::<Vec<_>>();
+        let times = costs.clone();
 
         TravelMatrices {
-            distances,
-            times,
-            costs,
+            distances: Arc::new(distances),
+            times: Arc::new(times),
+            costs: Arc::new(costs),
             num_locations,
             is_symmetric: true,
         }
@@ -261,3 +363,70 @@ impl TravelMatrices {
         &self.costs
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::problem::location::{Location, LocationIdx};
+
+    use super::{DistanceRounding, TravelMatrices};
+
+    #[test]
+    fn test_matrices_match_the_pairwise_distances() {
+        let locations = vec![
+            Location::from_lat_lon(50.8503, 4.3517),
+            Location::from_lat_lon(48.8566, 2.3522),
+            Location::from_lat_lon(-33.8688, 151.2093),
+            Location::from_lat_lon(40.7128, -74.0060),
+            Location::from_lat_lon(50.8503, 4.3517),
+        ];
+
+        let matrices = TravelMatrices::from_haversine(&locations);
+        let euclidean = TravelMatrices::from_euclidean(&locations, DistanceRounding::Exact);
+
+        for (from, from_location) in locations.iter().enumerate() {
+            for (to, to_location) in locations.iter().enumerate() {
+                let (from_idx, to_idx) = (LocationIdx::new(from), LocationIdx::new(to));
+
+                let expected = from_location.haversine_distance(to_location);
+                let distance = matrices.travel_distance(from_idx, to_idx).value();
+                assert!((distance - expected).abs() < 1e-3);
+                assert_eq!(matrices.travel_cost(from_idx, to_idx), distance);
+
+                let expected = from_location.euclidean_distance(to_location);
+                let distance = euclidean.travel_distance(from_idx, to_idx).value();
+                assert!((distance - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_euclidean_rounding() {
+        let locations = vec![
+            Location::from_cartesian(0.0, 0.0),
+            Location::from_cartesian(1.0, 1.0),
+        ];
+        let (from, to) = (LocationIdx::new(0), LocationIdx::new(1));
+
+        let distance = |rounding| {
+            TravelMatrices::from_euclidean(&locations, rounding)
+                .travel_distance(from, to)
+                .value()
+        };
+
+        assert_eq!(distance(DistanceRounding::Exact), 2.0_f64.sqrt());
+        assert_eq!(distance(DistanceRounding::Round), 1.0);
+        assert_eq!(distance(DistanceRounding::Truncate), 1.0);
+        assert_eq!(distance(DistanceRounding::Scale(10.0)), 1.4);
+        assert_eq!(distance(DistanceRounding::Scale(100.0)), 1.41);
+    }
+
+    #[test]
+    fn test_distance_rounding_is_valid() {
+        assert!(DistanceRounding::Exact.is_valid());
+        assert!(DistanceRounding::Scale(10.0).is_valid());
+        assert!(!DistanceRounding::Scale(0.0).is_valid());
+        assert!(!DistanceRounding::Scale(-10.0).is_valid());
+        assert!(!DistanceRounding::Scale(f64::NAN).is_valid());
+        assert!(!DistanceRounding::Scale(f64::INFINITY).is_valid());
+    }
+}
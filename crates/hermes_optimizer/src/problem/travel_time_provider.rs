@@ -0,0 +1,282 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use futures::{StreamExt, TryStreamExt};
+use fxhash::FxHashMap;
+use hermes_matrix_providers::travel_matrices::TravelMatrices;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::location::Location;
+
+/// Times (in seconds) and distances (in meters) from each source to each target,
+/// `times[i][j]` is the time from `sources[i]` to `targets[j]`
+pub struct TravelMatrixBlock {
+    pub times: Vec<Vec<f64>>,
+    pub distances: Vec<Vec<f64>>,
+}
+
+/// Dimensions of the vehicles of a profile, the roads they don't fit on are avoided
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TravelTimeVehicleDimensions {
+    /// In meters
+    pub height: Option<f64>,
+    /// In meters
+    pub width: Option<f64>,
+    /// In tonnes, with the load
+    pub weight: Option<f64>,
+}
+
+/// Vehicle the travel times of a profile are computed for, passed to the travel time provider
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TravelTimeOptions {
+    /// Routing profile of the provider, e.g. `car` or `truck`, the provider uses its default
+    /// profile when not set
+    pub profile: Option<String>,
+    pub vehicle_dimensions: Option<TravelTimeVehicleDimensions>,
+}
+
+impl TravelTimeOptions {
+    /// Tells apart the travel times of different options computed by the same provider
+    pub fn cache_key(&self) -> String {
+        let dimensions = self.vehicle_dimensions.unwrap_or_default();
+        format!(
+            "{}:{:?}:{:?}:{:?}",
+            self.profile.as_deref().unwrap_or_default(),
+            dimensions.height,
+            dimensions.width,
+            dimensions.weight
+        )
+    }
+}
+
+pub type TravelMatrixBlockFuture<'a> =
+    Pin<Box<dyn Future<Output = anyhow::Result<TravelMatrixBlock>> + Send + 'a>>;
+
+/// Extension point to compute the travel times of a problem with a router that is not one of the
+/// built-in `TravelMatrixProvider`s, selected with `TravelMatrixProvider::Provider`
+pub trait TravelTimeProvider: Send + Sync {
+    /// Maximum number of sources, or targets, of a single request, larger problems are split in
+    /// blocks fetched concurrently
+    fn max_locations_per_request(&self) -> usize;
+
+    /// Maximum number of blocks fetched at the same time
+    fn max_concurrent_requests(&self) -> usize {
+        4
+    }
+
+    fn fetch_block<'a>(
+        &'a self,
+        sources: &'a [Location],
+        targets: &'a [Location],
+        options: &'a TravelTimeOptions,
+    ) -> TravelMatrixBlockFuture<'a>;
+}
+
+/// Travel time providers available to the problems, by name
+#[derive(Default, Clone)]
+pub struct TravelTimeProviders {
+    providers: FxHashMap<String, Arc<dyn TravelTimeProvider>>,
+}
+
+impl TravelTimeProviders {
+    pub fn register(&mut self, name: impl Into<String>, provider: Arc<dyn TravelTimeProvider>) {
+        self.providers.insert(name.into(), provider);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn TravelTimeProvider>> {
+        self.providers.get(name)
+    }
+}
+
+/// Fetches the full matrices of `locations` block by block
+pub async fn fetch_travel_matrices(
+    provider: &dyn TravelTimeProvider,
+    locations: &[Location],
+    options: &TravelTimeOptions,
+) -> anyhow::Result<TravelMatrices> {
+    let num_locations = locations.len();
+    let block_size = provider.max_locations_per_request().max(1);
+
+    let blocks = locations
+        .chunks(block_size)
+        .enumerate()
+        .flat_map(|(source_block, sources)| {
+            locations.chunks(block_size).enumerate().map(
+                move |(target_block, targets)| async move {
+                    let block = provider.fetch_block(sources, targets, options).await?;
+                    Ok::<_, anyhow::Error>((
+                        source_block,
+                        target_block,
+                        sources.len(),
+                        targets.len(),
+                        block,
+                    ))
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let blocks = futures::stream::iter(blocks)
+        .buffer_unordered(provider.max_concurrent_requests().max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut times = vec![0.0; num_locations * num_locations];
+    let mut distances = vec![0.0; num_locations * num_locations];
+
+    for (source_block, target_block, num_sources, num_targets, block) in blocks {
+        if block.times.len() != num_sources
+            || block.distances.len() != num_sources
+            || block
+                .times
+                .iter()
+                .chain(&block.distances)
+                .any(|row| row.len() != num_targets)
+        {
+            return Err(anyhow::anyhow!(
+                "Expected a {num_sources}x{num_targets} block from the travel time provider"
+            ));
+        }
+
+        for (i, (times_row, distances_row)) in block.times.iter().zip(&block.distances).enumerate()
+        {
+            let from = source_block * block_size + i;
+            let to = target_block * block_size;
+            let row = from * num_locations + to;
+
+            times[row..row + num_targets].copy_from_slice(times_row);
+            distances[row..row + num_targets].copy_from_slice(distances_row);
+        }
+    }
+
+    Ok(TravelMatrices {
+        distances,
+        times,
+        costs: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use hermes_matrix_providers::travel_matrix_provider::TravelMatrixProvider;
+
+    use crate::{json::types::JsonVehicleProfile, problem::location::Location};
+
+    use super::{
+        TravelMatrixBlock, TravelMatrixBlockFuture, TravelTimeOptions, TravelTimeProvider,
+        fetch_travel_matrices,
+    };
+
+    /// Time and distance are the difference of the x coordinates
+    struct LineProvider;
+
+    impl TravelTimeProvider for LineProvider {
+        fn max_locations_per_request(&self) -> usize {
+            2
+        }
+
+        fn fetch_block<'a>(
+            &'a self,
+            sources: &'a [Location],
+            targets: &'a [Location],
+            _: &'a TravelTimeOptions,
+        ) -> TravelMatrixBlockFuture<'a> {
+            Box::pin(async move {
+                let rows: Vec<Vec<f64>> = sources
+                    .iter()
+                    .map(|from| targets.iter().map(|to| (to.x() - from.x()).abs()).collect())
+                    .collect();
+
+                Ok(TravelMatrixBlock {
+                    times: rows.clone(),
+                    distances: rows,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_travel_matrices_in_blocks() {
+        let locations: Vec<_> = (0..5)
+            .map(|x| Location::from_cartesian(x as f64, 0.0))
+            .collect();
+
+        let matrices =
+            fetch_travel_matrices(&LineProvider, &locations, &TravelTimeOptions::default())
+                .await
+                .unwrap();
+
+        for from in 0..5 {
+            for to in 0..5 {
+                assert_eq!(
+                    matrices.times[from * 5 + to],
+                    (to as f64 - from as f64).abs()
+                );
+            }
+        }
+        assert_eq!(matrices.distances, matrices.times);
+    }
+
+    /// The times are the height of the vehicle, to check the options reach the provider
+    struct HeightProvider;
+
+    impl TravelTimeProvider for HeightProvider {
+        fn max_locations_per_request(&self) -> usize {
+            2
+        }
+
+        fn fetch_block<'a>(
+            &'a self,
+            sources: &'a [Location],
+            targets: &'a [Location],
+            options: &'a TravelTimeOptions,
+        ) -> TravelMatrixBlockFuture<'a> {
+            Box::pin(async move {
+                assert_eq!(options.profile.as_deref(), Some("truck"));
+                let height = options
+                    .vehicle_dimensions
+                    .and_then(|dimensions| dimensions.height)
+                    .unwrap_or_default();
+                let rows = vec![vec![height; targets.len()]; sources.len()];
+
+                Ok(TravelMatrixBlock {
+                    times: rows.clone(),
+                    distances: rows,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_travel_matrices_with_options() {
+        let profile: JsonVehicleProfile = serde_json::from_str(
+            r#"{
+                "id": "heavy",
+                "routing": { "profile": "truck", "vehicle_dimensions": { "height": 4.0 } }
+            }"#,
+        )
+        .unwrap();
+        let locations: Vec<_> = (0..3)
+            .map(|x| Location::from_cartesian(x as f64, 0.0))
+            .collect();
+
+        let matrices =
+            fetch_travel_matrices(&HeightProvider, &locations, &profile.routing.unwrap())
+                .await
+                .unwrap();
+
+        assert_eq!(matrices.times, vec![4.0; 9]);
+    }
+
+    #[test]
+    fn test_profile_without_cost_provider_uses_provider_of_same_name() {
+        let profile: JsonVehicleProfile = serde_json::from_str(r#"{ "id": "truck" }"#).unwrap();
+
+        assert!(matches!(
+            profile.cost_provider(),
+            TravelMatrixProvider::Provider { name } if name == "truck"
+        ));
+    }
+}
@@ -4,11 +4,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     define_index_newtype,
-    problem::{skill::Skill, vehicle_profile::VehicleProfileIdx},
+    problem::{
+        axle_loads::AxleLoads, battery::Battery, skill::Skill, tag::Tag,
+        vehicle_profile::VehicleProfileIdx,
+    },
     utils::bitset::BitSet,
 };
 
-use super::{capacity::Capacity, location::LocationIdx};
+use super::{capacity::Capacity, location::LocationIdx, territory::Territory};
 
 define_index_newtype!(VehicleIdx, Vehicle);
 
@@ -23,10 +26,61 @@ pub struct Vehicle {
     end_depot_duration: Option<SignedDuration>,
     should_return_to_depot: bool,
     maximum_activities: Option<usize>,
+
+    /// Routes with fewer activities are penalized, see `MinimumUtilizationConstraint`
+    minimum_activities: Option<usize>,
+
+    /// Routes whose peak load is under this share of the capacity are penalized, see
+    /// `MinimumUtilizationConstraint`
+    minimum_utilization: Option<f64>,
+
+    /// Time to unload and reload at the depot, the vehicle can only do multiple trips when set
+    reload_duration: Option<SignedDuration>,
+    maximum_reloads: usize,
+
+    /// Trips of the vehicle over the planning horizon and per day, a trip starts at the start of
+    /// the route or after a reload, see `MaximumTripsConstraint`
+    maximum_trips: Option<usize>,
+    maximum_trips_per_day: Option<usize>,
+
+    /// Electric vehicles consume energy and stop at the charging stations to charge
+    battery: Option<Battery>,
+
+    /// Axle weight limits of a truck, see `AxleLoadConstraint`
+    axle_loads: Option<AxleLoads>,
     skills: FxHashSet<Skill>,
 
+    /// Only jobs whose tags are all in this list can be served by the vehicle
+    allowed_tags: Option<FxHashSet<Tag>>,
+
+    /// Jobs with any of these tags cannot be served by the vehicle
+    forbidden_tags: FxHashSet<Tag>,
+
+    /// Cost of using the vehicle, replaces the default fixed cost of a route
+    fixed_cost: Option<f64>,
+
+    /// Cost per kilometer travelled
+    cost_per_distance: Option<f64>,
+
+    /// Cost per hour travelled
+    cost_per_duration: Option<f64>,
+
+    /// Zone the vehicle is assigned to, see `TerritoryConstraint`
+    territory: Option<Territory>,
+
     #[serde(skip)]
     skills_bitset: BitSet,
+
+    /// Tags the vehicle accepts, allowed tags minus forbidden tags
+    #[serde(skip)]
+    accepted_tags_bitset: BitSet,
+
+    #[serde(skip)]
+    cost_class_id: usize,
+
+    /// Margin after the latest end of the shift, see `Tolerances::time_window`
+    #[serde(skip)]
+    latest_end_tolerance: SignedDuration,
 }
 
 impl Vehicle {
@@ -54,6 +108,22 @@ impl Vehicle {
         &self.skills_bitset
     }
 
+    pub fn allowed_tags(&self) -> Option<&FxHashSet<Tag>> {
+        self.allowed_tags.as_ref()
+    }
+
+    pub fn forbidden_tags(&self) -> &FxHashSet<Tag> {
+        &self.forbidden_tags
+    }
+
+    pub fn accepted_tags_bitset(&self) -> &BitSet {
+        &self.accepted_tags_bitset
+    }
+
+    pub fn territory(&self) -> Option<&Territory> {
+        self.territory.as_ref()
+    }
+
     pub fn depot_location_id(&self) -> Option<LocationIdx> {
         self.depot_location_id
     }
@@ -82,6 +152,16 @@ impl Vehicle {
         self.shift.as_ref().and_then(|shift| shift.latest_end)
     }
 
+    /// Latest end of the route satisfying the shift, its latest end with the tolerance
+    pub fn latest_end_deadline(&self) -> Option<Timestamp> {
+        self.latest_end_time()
+            .map(|latest_end| latest_end + self.latest_end_tolerance)
+    }
+
+    pub fn set_latest_end_tolerance(&mut self, tolerance: SignedDuration) {
+        self.latest_end_tolerance = tolerance;
+    }
+
     pub fn should_return_to_depot(&self) -> bool {
         self.should_return_to_depot
     }
@@ -94,6 +174,43 @@ impl Vehicle {
         self.maximum_activities
     }
 
+    pub fn minimum_activities(&self) -> Option<usize> {
+        self.minimum_activities
+    }
+
+    pub fn minimum_utilization(&self) -> Option<f64> {
+        self.minimum_utilization
+    }
+
+    pub fn reload_duration(&self) -> Option<SignedDuration> {
+        self.reload_duration
+    }
+
+    /// Number of times the vehicle can reload at its depot, 0 when it cannot reload
+    pub fn maximum_reloads(&self) -> usize {
+        if self.reload_duration.is_some() && self.depot_location_id.is_some() {
+            self.maximum_reloads
+        } else {
+            0
+        }
+    }
+
+    pub fn maximum_trips(&self) -> Option<usize> {
+        self.maximum_trips
+    }
+
+    pub fn maximum_trips_per_day(&self) -> Option<usize> {
+        self.maximum_trips_per_day
+    }
+
+    pub fn battery(&self) -> Option<&Battery> {
+        self.battery.as_ref()
+    }
+
+    pub fn axle_loads(&self) -> Option<&AxleLoads> {
+        self.axle_loads.as_ref()
+    }
+
     pub fn depot_duration(&self) -> SignedDuration {
         self.depot_duration.unwrap_or(SignedDuration::ZERO)
     }
@@ -110,6 +227,26 @@ impl Vehicle {
         self.end_depot_duration.unwrap_or(SignedDuration::ZERO)
     }
 
+    pub fn fixed_cost(&self) -> Option<f64> {
+        self.fixed_cost
+    }
+
+    pub fn cost_per_distance(&self) -> Option<f64> {
+        self.cost_per_distance
+    }
+
+    pub fn cost_per_duration(&self) -> Option<f64> {
+        self.cost_per_duration
+    }
+
+    pub fn cost_class_id(&self) -> usize {
+        self.cost_class_id
+    }
+
+    pub(crate) fn set_cost_class_id(&mut self, cost_class_id: usize) {
+        self.cost_class_id = cost_class_id;
+    }
+
     pub fn build_skills_bitset(&mut self, skill_registry: &[Skill]) {
         self.set_skills_bitset(BitSet::from_registry(skill_registry, self.skills()));
     }
@@ -117,6 +254,22 @@ impl Vehicle {
     fn set_skills_bitset(&mut self, skills_bitset: BitSet) {
         self.skills_bitset = skills_bitset;
     }
+
+    pub fn build_tags_bitset(&mut self, tag_registry: &[Tag]) {
+        let mut accepted_tags_bitset = match &self.allowed_tags {
+            Some(allowed_tags) => BitSet::from_registry(tag_registry, allowed_tags),
+            None => {
+                let mut bitset = BitSet::with_capacity(tag_registry.len());
+                bitset.fill_ones();
+                bitset
+            }
+        };
+
+        accepted_tags_bitset
+            .difference_with(&BitSet::from_registry(tag_registry, &self.forbidden_tags));
+
+        self.accepted_tags_bitset = accepted_tags_bitset;
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
@@ -213,7 +366,21 @@ pub struct VehicleBuilder {
     depot_duration: Option<SignedDuration>,
     end_depot_duration: Option<SignedDuration>,
     skills: Option<Vec<Skill>>,
+    allowed_tags: Option<Vec<Tag>>,
+    forbidden_tags: Option<Vec<Tag>>,
     maximum_activities: Option<usize>,
+    minimum_activities: Option<usize>,
+    minimum_utilization: Option<f64>,
+    reload_duration: Option<SignedDuration>,
+    maximum_reloads: Option<usize>,
+    maximum_trips: Option<usize>,
+    maximum_trips_per_day: Option<usize>,
+    battery: Option<Battery>,
+    axle_loads: Option<AxleLoads>,
+    fixed_cost: Option<f64>,
+    cost_per_distance: Option<f64>,
+    cost_per_duration: Option<f64>,
+    territory: Option<Territory>,
 }
 
 impl VehicleBuilder {
@@ -232,6 +399,54 @@ impl VehicleBuilder {
         self
     }
 
+    pub fn set_minimum_activities(&mut self, minimum_activities: usize) -> &mut VehicleBuilder {
+        self.minimum_activities = Some(minimum_activities);
+        self
+    }
+
+    /// Share of the capacity between 0 and 1
+    pub fn set_minimum_utilization(&mut self, minimum_utilization: f64) -> &mut VehicleBuilder {
+        self.minimum_utilization = Some(minimum_utilization);
+        self
+    }
+
+    /// Allows the vehicle to return to its depot to reload, once unless `set_maximum_reloads` is
+    /// used
+    pub fn set_reload_duration(&mut self, reload_duration: SignedDuration) -> &mut VehicleBuilder {
+        self.reload_duration = Some(reload_duration);
+        self
+    }
+
+    pub fn set_maximum_reloads(&mut self, maximum_reloads: usize) -> &mut VehicleBuilder {
+        self.maximum_reloads = Some(maximum_reloads);
+        self
+    }
+
+    /// Without `set_maximum_reloads`, the vehicle can also reload enough times to do all its trips
+    pub fn set_maximum_trips(&mut self, maximum_trips: usize) -> &mut VehicleBuilder {
+        self.maximum_trips = Some(maximum_trips);
+        self
+    }
+
+    /// The trips are counted on the UTC day they start
+    pub fn set_maximum_trips_per_day(
+        &mut self,
+        maximum_trips_per_day: usize,
+    ) -> &mut VehicleBuilder {
+        self.maximum_trips_per_day = Some(maximum_trips_per_day);
+        self
+    }
+
+    pub fn set_battery(&mut self, battery: Battery) -> &mut VehicleBuilder {
+        self.battery = Some(battery);
+        self
+    }
+
+    pub fn set_axle_loads(&mut self, axle_loads: AxleLoads) -> &mut VehicleBuilder {
+        self.axle_loads = Some(axle_loads);
+        self
+    }
+
     pub fn set_vehicle_shift(&mut self, shift: VehicleShift) -> &mut VehicleBuilder {
         self.shift = Some(shift);
         self
@@ -268,6 +483,38 @@ impl VehicleBuilder {
         self
     }
 
+    pub fn set_allowed_tags(&mut self, tags: Vec<String>) -> &mut VehicleBuilder {
+        self.allowed_tags = Some(tags.into_iter().map(Tag::new).collect());
+        self
+    }
+
+    pub fn set_forbidden_tags(&mut self, tags: Vec<String>) -> &mut VehicleBuilder {
+        self.forbidden_tags = Some(tags.into_iter().map(Tag::new).collect());
+        self
+    }
+
+    pub fn set_fixed_cost(&mut self, fixed_cost: f64) -> &mut VehicleBuilder {
+        self.fixed_cost = Some(fixed_cost);
+        self
+    }
+
+    /// Cost per kilometer
+    pub fn set_cost_per_distance(&mut self, cost_per_distance: f64) -> &mut VehicleBuilder {
+        self.cost_per_distance = Some(cost_per_distance);
+        self
+    }
+
+    /// Cost per hour
+    pub fn set_cost_per_duration(&mut self, cost_per_duration: f64) -> &mut VehicleBuilder {
+        self.cost_per_duration = Some(cost_per_duration);
+        self
+    }
+
+    pub fn set_territory(&mut self, territory: Territory) -> &mut VehicleBuilder {
+        self.territory = Some(territory);
+        self
+    }
+
     pub fn build(self) -> Vehicle {
         Vehicle {
             external_id: self.external_id.expect("External ID is required"),
@@ -282,10 +529,30 @@ impl VehicleBuilder {
             depot_duration: self.depot_duration,
             end_depot_duration: self.end_depot_duration,
             maximum_activities: self.maximum_activities,
+            minimum_activities: self.minimum_activities,
+            minimum_utilization: self.minimum_utilization,
+            reload_duration: self.reload_duration,
+            maximum_reloads: self
+                .maximum_reloads
+                .or(self.maximum_trips.map(|trips| trips.saturating_sub(1)))
+                .unwrap_or(1),
+            maximum_trips: self.maximum_trips,
+            maximum_trips_per_day: self.maximum_trips_per_day,
+            battery: self.battery,
+            axle_loads: self.axle_loads,
             skills: FxHashSet::from_iter(self.skills.unwrap_or_default()),
+            allowed_tags: self.allowed_tags.map(FxHashSet::from_iter),
+            forbidden_tags: FxHashSet::from_iter(self.forbidden_tags.unwrap_or_default()),
+            fixed_cost: self.fixed_cost,
+            cost_per_distance: self.cost_per_distance,
+            cost_per_duration: self.cost_per_duration,
+            territory: self.territory,
 
             // Will be set later by the problem
             skills_bitset: BitSet::empty(),
+            accepted_tags_bitset: BitSet::empty(),
+            cost_class_id: 0,
+            latest_end_tolerance: SignedDuration::ZERO,
         }
     }
 }
@@ -0,0 +1,106 @@
+use crate::problem::{
+    location::LocationIdx,
+    travel_cost_matrix::Cost,
+    vehicle::Vehicle,
+    vehicle_profile::{VehicleProfile, VehicleProfileIdx},
+};
+
+/// Vehicles sharing a profile and the same cost coefficients have identical travel costs.
+/// Routes cache their cumulative transport costs per cost class rather than per vehicle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VehicleCostClass {
+    profile_id: VehicleProfileIdx,
+    cost_per_distance: Option<f64>,
+    cost_per_duration: Option<f64>,
+}
+
+impl VehicleCostClass {
+    pub fn from_vehicle(vehicle: &Vehicle) -> Self {
+        VehicleCostClass {
+            profile_id: vehicle.profile_id(),
+            cost_per_distance: vehicle.cost_per_distance(),
+            cost_per_duration: vehicle.cost_per_duration(),
+        }
+    }
+
+    pub fn profile_id(&self) -> VehicleProfileIdx {
+        self.profile_id
+    }
+
+    /// Without coefficients, the cost matrix of the profile is used as is.
+    /// Otherwise the cost is `cost_per_distance` per kilometer plus `cost_per_duration` per hour.
+    #[inline(always)]
+    pub fn travel_cost(
+        &self,
+        profile: &VehicleProfile,
+        from: LocationIdx,
+        to: LocationIdx,
+    ) -> Cost {
+        if self.cost_per_distance.is_none() && self.cost_per_duration.is_none() {
+            return profile.travel_cost(from, to);
+        }
+
+        let mut cost = 0.0;
+
+        if let Some(cost_per_distance) = self.cost_per_distance {
+            cost += cost_per_distance * profile.travel_distance(from, to).value() / 1000.0;
+        }
+
+        if let Some(cost_per_duration) = self.cost_per_duration {
+            cost += cost_per_duration * profile.travel_time(from, to).as_secs_f64() / 3600.0;
+        }
+
+        cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        problem::{
+            location::LocationIdx,
+            vehicle::{VehicleBuilder, VehicleIdx},
+        },
+        test_utils,
+    };
+
+    #[test]
+    fn test_vehicle_cost_classes() {
+        let locations = test_utils::create_location_grid(1, 4);
+        let services = test_utils::create_basic_services(vec![1, 2, 3]);
+
+        let vehicles = (0..3)
+            .map(|index| {
+                let mut builder = VehicleBuilder::default();
+                builder.set_depot_location_id(0);
+                builder.set_vehicle_id(index.to_string());
+                builder.set_profile_id(0);
+                if index > 0 {
+                    builder.set_cost_per_distance(1000.0);
+                    builder.set_cost_per_duration(3600.0);
+                }
+                builder.build()
+            })
+            .collect();
+
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        // Vehicles 1 and 2 share the same coefficients
+        assert_eq!(problem.vehicle_cost_classes().len(), 2);
+        assert_eq!(problem.vehicle(VehicleIdx::new(1)).cost_class_id(), 1);
+        assert_eq!(problem.vehicle(VehicleIdx::new(2)).cost_class_id(), 1);
+
+        let from = LocationIdx::new(0);
+        let to = LocationIdx::new(3);
+
+        // 3 meters and 3 seconds
+        assert_eq!(
+            problem.travel_cost(problem.vehicle(VehicleIdx::new(0)), from, to),
+            3.0
+        );
+        assert_eq!(
+            problem.travel_cost(problem.vehicle(VehicleIdx::new(1)), from, to),
+            6.0
+        );
+    }
+}
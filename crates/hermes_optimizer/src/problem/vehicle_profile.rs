@@ -11,6 +11,8 @@ use crate::{
 
 define_index_newtype!(VehicleProfileIdx, VehicleProfile);
 
+/// Cloning shares the travel matrices
+#[derive(Clone)]
 pub struct VehicleProfile {
     external_id: String,
     travel_costs: TravelMatrices,
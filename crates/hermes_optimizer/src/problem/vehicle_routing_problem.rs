@@ -1,6 +1,6 @@
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use jiff::SignedDuration;
 use thiserror::Error;
 use tracing::instrument;
@@ -9,15 +9,24 @@ use uuid::Uuid;
 use crate::{
     problem::{
         amount::AmountExpression,
+        axle_loads::AxleLoadsError,
         capacity::Capacity,
+        charging_station::ChargingStation,
+        constraint_overrides::{ConstraintOverrideError, ConstraintOverrides},
+        depot::Depot,
+        depot_inventory::DepotInventory,
         fleet::Fleet,
         job::{ActivityId, Job, JobActivity, JobIdx},
         meters::Meters,
         relation::{ExternalRelation, MalformedRelationError, Relation},
-        service::Service,
+        service::{Service, ServiceBuilder, ServiceType},
         shipment::Shipment,
         skill::Skill,
+        tag::Tag,
         task_dependencies::TaskDependencies,
+        territory::Territory,
+        tolerances::Tolerances,
+        vehicle_cost_class::VehicleCostClass,
         vehicle_profile::{VehicleProfile, VehicleProfileIdx},
     },
     solver::constraints::transport_cost_constraint::TRANSPORT_COST_WEIGHT,
@@ -31,33 +40,62 @@ use super::{
     distance_method::DistanceMethod,
     location::{Location, LocationIdx},
     service_location_index::ServiceLocationIndex,
-    travel_cost_matrix::Cost,
+    travel_cost_matrix::{Cost, DistanceRounding, TravelMatrices},
     vehicle::{Vehicle, VehicleIdx},
 };
 
 type PrecomputedAverageCostFromDepot = Vec<Cost>;
 type PrecomputedNormalizedDemands = Vec<Capacity>;
+type GeneratedCharges = (Vec<Vec<JobIdx>>, FxHashMap<JobIdx, (VehicleIdx, usize)>);
+
+/// Number of close activities precomputed for each location
+pub const MAX_NEIGHBORHOOD_SIZE: usize = 200;
+pub const DEFAULT_NEIGHBORHOOD_SIZE: usize = 50;
+
+/// Charging stations a vehicle with a depot can charge at, the closest to its depot
+pub const MAX_VEHICLE_CHARGING_STATIONS: usize = 10;
 
 pub struct VehicleRoutingProblem {
     id: String,
     locations: Vec<Location>,
     fleet: Fleet,
     vehicle_profiles: Vec<VehicleProfile>,
+    vehicle_cost_classes: Vec<VehicleCostClass>,
     jobs: Vec<Job>,
     service_location_index: ServiceLocationIndex,
 
     has_services: bool,
     has_shipments: bool,
     has_time_windows: bool,
+    has_time_window_preferences: bool,
+    has_ride_durations: bool,
+    has_position_preferences: bool,
+    has_stop_sequences: bool,
     has_capacity: bool,
     has_task_dependencies: bool,
 
-    neighborhoods: Vec<FxHashSet<ActivityId>>,
+    /// Reload jobs of each vehicle, appended after the jobs of the problem
+    vehicle_reloads: Vec<Vec<JobIdx>>,
+    reload_vehicles: FxHashMap<JobIdx, VehicleIdx>,
+
+    charging_stations: Vec<ChargingStation>,
+
+    /// Charging jobs of each vehicle with a battery, appended after the reloads
+    vehicle_charges: Vec<Vec<JobIdx>>,
+    /// Vehicle and charging station of each charging job
+    charge_stops: FxHashMap<JobIdx, (VehicleIdx, usize)>,
+
+    /// Activities close to each location with their rank, precomputed up to `MAX_NEIGHBORHOOD_SIZE`
+    neighborhoods: Vec<FxHashMap<ActivityId, usize>>,
 
     relations: Vec<Relation>,
     task_dependencies: TaskDependencies,
 
+    depots: Vec<Depot>,
+    depot_inventories: Vec<DepotInventory>,
+
     skill_registry: Vec<Skill>,
+    tag_registry: Vec<Tag>,
     precomputed_capacity_dimensions: usize,
     precomputed_normalized_demands: PrecomputedNormalizedDemands,
     precomputed_average_cost_from_depot: PrecomputedAverageCostFromDepot,
@@ -65,6 +103,9 @@ pub struct VehicleRoutingProblem {
     /// Normalized weight for converting waiting duration into cost
     waiting_duration_weight: f64,
 
+    tolerances: Tolerances,
+    constraint_overrides: ConstraintOverrides,
+
     version_counter: AtomicUsize,
 }
 
@@ -79,6 +120,8 @@ pub enum VehicleRoutingProblemError {
     },
     #[error("Location ID {0} out of bounds")]
     LocationIdOutOfBounds(usize),
+    #[error("Invalid coordinates of location {0}, expected [lon, lat]")]
+    InvalidCoordinates(usize),
     #[error("Missing jobs")]
     MissingJobs,
     #[error("Missing locations")]
@@ -89,6 +132,14 @@ pub enum VehicleRoutingProblemError {
     MissingFleet,
     #[error("Empty fleet")]
     EmptyFleet,
+    #[error("Invalid vehicle counts, one positive count is needed per vehicle")]
+    InvalidVehicleCounts,
+    #[error("Invalid tolerances, the tolerances can't be negative")]
+    InvalidTolerances,
+    #[error("Invalid distance rounding {0:?}, the scale must be positive and finite")]
+    InvalidDistanceRounding(DistanceRounding),
+    #[error("No vehicle available for {0}")]
+    UnavailableVehicle(String),
 
     #[error("Duplicate job ID {0}")]
     DuplicateJobId(String),
@@ -96,11 +147,29 @@ pub enum VehicleRoutingProblemError {
     #[error("Duplicate vehicle ID {0}")]
     DuplicateVehicleId(String),
 
+    #[error("Duplicate depot {0}")]
+    DuplicateDepot(usize),
+
+    #[error("Opening hours or loading window of depot {0} end before they start")]
+    InvalidDepotHours(usize),
+
+    #[error("Duplicate inventory for depot {0}")]
+    DuplicateDepotInventory(usize),
+
     #[error("Unknown activity ID {0} in relation {1}")]
     UnknownActivityIdInRelation(String, usize),
 
     #[error("Unknown job ID {0} in relation {1}")]
     UnknownJobIdInRelation(String, usize),
+
+    #[error("{0}")]
+    InvalidConstraintOverride(#[from] ConstraintOverrideError),
+
+    #[error("Invalid axle loads of vehicle {vehicle_id}: {error}")]
+    InvalidAxleLoads {
+        vehicle_id: String,
+        error: AxleLoadsError,
+    },
 }
 
 enum VehicleRoutingRelationParams {
@@ -133,16 +202,35 @@ struct VehicleRoutingProblemParams {
     distance_method: DistanceMethod,
     penalize_waiting_duration: bool,
     relations: Option<VehicleRoutingRelationParams>,
+    depots: Vec<Depot>,
+    depot_inventories: Vec<DepotInventory>,
+    charging_stations: Vec<ChargingStation>,
+    tolerances: Tolerances,
+    constraint_overrides: ConstraintOverrides,
 }
 
 impl VehicleRoutingProblem {
     fn try_from_params(
-        params: VehicleRoutingProblemParams,
+        mut params: VehicleRoutingProblemParams,
     ) -> Result<Self, VehicleRoutingProblemError> {
         if params.fleet.vehicles().is_empty() {
             return Err(VehicleRoutingProblemError::EmptyFleet);
         }
 
+        if let Fleet::Counted { vehicles, counts } = &params.fleet
+            && (counts.len() != vehicles.len() || counts.contains(&0))
+        {
+            return Err(VehicleRoutingProblemError::InvalidVehicleCounts);
+        }
+
+        let tolerances = &params.tolerances;
+        if tolerances.time_window.is_negative()
+            || tolerances.capacity.is_nan()
+            || tolerances.capacity < 0.0
+        {
+            return Err(VehicleRoutingProblemError::InvalidTolerances);
+        }
+
         if let Some(duplicate) = find_duplicate(params.jobs.iter().map(|job| job.external_id())) {
             return Err(VehicleRoutingProblemError::DuplicateJobId(
                 duplicate.to_owned(),
@@ -161,6 +249,56 @@ impl VehicleRoutingProblem {
             ));
         }
 
+        for depot in &params.depots {
+            if depot.location_id().get() >= params.locations.len() {
+                return Err(VehicleRoutingProblemError::LocationIdOutOfBounds(
+                    depot.location_id().get(),
+                ));
+            }
+
+            if depot
+                .opening_hours()
+                .iter()
+                .chain(depot.loading_windows())
+                .any(|window| match (window.earliest(), window.latest()) {
+                    (Some(start), Some(end)) => start > end,
+                    _ => false,
+                })
+            {
+                return Err(VehicleRoutingProblemError::InvalidDepotHours(
+                    depot.location_id().get(),
+                ));
+            }
+        }
+
+        let depot_locations = params
+            .depots
+            .iter()
+            .map(Depot::location_id)
+            .collect::<Vec<_>>();
+        if let Some(duplicate) = find_duplicate(depot_locations.iter()) {
+            return Err(VehicleRoutingProblemError::DuplicateDepot(duplicate.get()));
+        }
+
+        for inventory in &params.depot_inventories {
+            if inventory.location_id().get() >= params.locations.len() {
+                return Err(VehicleRoutingProblemError::LocationIdOutOfBounds(
+                    inventory.location_id().get(),
+                ));
+            }
+        }
+
+        let depot_location_ids = params
+            .depot_inventories
+            .iter()
+            .map(DepotInventory::location_id)
+            .collect::<Vec<_>>();
+        if let Some(duplicate) = find_duplicate(depot_location_ids.iter()) {
+            return Err(VehicleRoutingProblemError::DuplicateDepotInventory(
+                duplicate.get(),
+            ));
+        }
+
         for (vehicle_id, vehicle) in params.fleet.vehicles().iter().enumerate() {
             if vehicle.profile_id().get() >= params.vehicle_profiles.len() {
                 return Err(VehicleRoutingProblemError::InvalidVehicleProfile {
@@ -168,8 +306,50 @@ impl VehicleRoutingProblem {
                     profile_id: vehicle.profile_id().get(),
                 });
             }
+
+            if let Some(axle_loads) = vehicle.axle_loads() {
+                axle_loads
+                    .validate(vehicle.capacity().len())
+                    .map_err(|error| VehicleRoutingProblemError::InvalidAxleLoads {
+                        vehicle_id: vehicle.external_id().to_owned(),
+                        error,
+                    })?;
+            }
+
+            if params.fleet.vehicle_limit(VehicleIdx::new(vehicle_id)) == Some(0) {
+                return Err(VehicleRoutingProblemError::UnavailableVehicle(
+                    vehicle.external_id().to_owned(),
+                ));
+            }
+
+            if let Some(Territory::Locations(location_ids)) = vehicle.territory()
+                && let Some(location_id) = location_ids
+                    .iter()
+                    .find(|location_id| location_id.get() >= params.locations.len())
+            {
+                return Err(VehicleRoutingProblemError::LocationIdOutOfBounds(
+                    location_id.get(),
+                ));
+            }
         }
 
+        for station in &params.charging_stations {
+            if station.location_id().get() >= params.locations.len() {
+                return Err(VehicleRoutingProblemError::LocationIdOutOfBounds(
+                    station.location_id().get(),
+                ));
+            }
+        }
+
+        let (vehicle_reloads, reload_vehicles) =
+            VehicleRoutingProblem::generate_reloads(params.fleet.vehicles(), &mut params.jobs);
+        let (vehicle_charges, charge_stops) = VehicleRoutingProblem::generate_charges(
+            params.fleet.vehicles(),
+            &params.vehicle_profiles,
+            &params.charging_stations,
+            &mut params.jobs,
+        );
+
         let service_location_index =
             ServiceLocationIndex::new(&params.locations, &params.jobs, params.distance_method);
 
@@ -218,6 +398,7 @@ impl VehicleRoutingProblem {
         }
 
         let skills = VehicleRoutingProblem::collect_skills(params.fleet.vehicles(), &params.jobs);
+        let tags = VehicleRoutingProblem::collect_tags(params.fleet.vehicles(), &params.jobs);
 
         let has_services = params.jobs.iter().any(|job| matches!(job, Job::Service(_)));
         let has_shipments = params
@@ -242,32 +423,71 @@ impl VehicleRoutingProblem {
         let mut problem = Self {
             id: params.id,
             has_time_windows: params.jobs.iter().any(|job| job.has_time_windows()),
+            has_time_window_preferences: params
+                .jobs
+                .iter()
+                .any(|job| job.has_time_window_preferences()),
+            has_ride_durations: params
+                .jobs
+                .iter()
+                .any(|job| job.max_ride_duration().is_some()),
+            has_position_preferences: params.jobs.iter().any(|job| job.has_position_preference()),
+            has_stop_sequences: params.jobs.iter().any(|job| job.has_stop_sequence()),
             has_capacity: params.jobs.iter().any(|job| !job.demand().is_empty()),
             has_task_dependencies,
+            vehicle_reloads,
+            reload_vehicles,
+            charging_stations: params.charging_stations,
+            vehicle_charges,
+            charge_stops,
             locations: params.locations,
             fleet: params.fleet,
             vehicle_profiles: params.vehicle_profiles,
+            vehicle_cost_classes: vec![],
             jobs: params.jobs,
             relations,
             task_dependencies,
+            depots: params.depots,
+            depot_inventories: params.depot_inventories,
             neighborhoods,
             service_location_index,
             precomputed_average_cost_from_depot,
             precomputed_normalized_demands,
             precomputed_capacity_dimensions,
             waiting_duration_weight,
+            tolerances: params.tolerances,
+            constraint_overrides: params.constraint_overrides,
             has_services,
             has_shipments,
             skill_registry: skills,
+            tag_registry: tags,
             version_counter: AtomicUsize::new(0),
         };
 
         for vehicle in problem.fleet.vehicles_mut() {
             vehicle.build_skills_bitset(&problem.skill_registry);
+            vehicle.build_tags_bitset(&problem.tag_registry);
+            vehicle.set_latest_end_tolerance(problem.tolerances.time_window);
+
+            let cost_class = VehicleCostClass::from_vehicle(vehicle);
+            let cost_class_id = match problem
+                .vehicle_cost_classes
+                .iter()
+                .position(|existing| *existing == cost_class)
+            {
+                Some(cost_class_id) => cost_class_id,
+                None => {
+                    problem.vehicle_cost_classes.push(cost_class);
+                    problem.vehicle_cost_classes.len() - 1
+                }
+            };
+            vehicle.set_cost_class_id(cost_class_id);
         }
 
         for job in &mut problem.jobs {
             job.build_skills_bitset(&problem.skill_registry);
+            job.build_tags_bitset(&problem.tag_registry);
+            job.set_time_window_tolerance(problem.tolerances.time_window);
         }
 
         Ok(problem)
@@ -277,17 +497,172 @@ impl VehicleRoutingProblem {
         &self.id
     }
 
+    /// Appends `maximum_reloads` reload services at the depot of each vehicle which can reload
+    fn generate_reloads(
+        vehicles: &[Vehicle],
+        jobs: &mut Vec<Job>,
+    ) -> (Vec<Vec<JobIdx>>, FxHashMap<JobIdx, VehicleIdx>) {
+        let mut vehicle_reloads = Vec::with_capacity(vehicles.len());
+        let mut reload_vehicles = FxHashMap::default();
+
+        for (vehicle_id, vehicle) in vehicles.iter().enumerate_idx() {
+            let mut reloads = vec![];
+            if let (Some(depot_location_id), Some(reload_duration)) =
+                (vehicle.depot_location_id(), vehicle.reload_duration())
+            {
+                for reload in 0..vehicle.maximum_reloads() {
+                    let mut builder = ServiceBuilder::default();
+                    builder
+                        .set_external_id(format!("reload_{}_{reload}", vehicle.external_id()))
+                        .set_service_type(ServiceType::Reload)
+                        .set_location_id(depot_location_id.get())
+                        .set_service_duration(reload_duration);
+
+                    let job_id = JobIdx::new(jobs.len());
+                    jobs.push(Job::Service(builder.build()));
+                    reloads.push(job_id);
+                    reload_vehicles.insert(job_id, vehicle_id);
+                }
+            }
+            vehicle_reloads.push(reloads);
+        }
+
+        (vehicle_reloads, reload_vehicles)
+    }
+
+    pub fn has_reloads(&self) -> bool {
+        !self.reload_vehicles.is_empty()
+    }
+
+    pub fn vehicle_reloads(&self, vehicle_id: VehicleIdx) -> &[JobIdx] {
+        &self.vehicle_reloads[vehicle_id.get()]
+    }
+
+    /// Vehicle owning the reload job, `None` when the job is not a reload
+    pub fn reload_vehicle(&self, job_id: JobIdx) -> Option<VehicleIdx> {
+        self.reload_vehicles.get(&job_id).copied()
+    }
+
+    /// Appends `maximum_charges` charging services at the `MAX_VEHICLE_CHARGING_STATIONS` charging
+    /// stations closest to the depot of each vehicle with a battery, at every station without a
+    /// depot. The duration of a charge depends on the energy consumed before it, see
+    /// `compute_activity_duration`.
+    fn generate_charges(
+        vehicles: &[Vehicle],
+        vehicle_profiles: &[VehicleProfile],
+        charging_stations: &[ChargingStation],
+        jobs: &mut Vec<Job>,
+    ) -> GeneratedCharges {
+        let mut vehicle_charges = Vec::with_capacity(vehicles.len());
+        let mut charge_stops = FxHashMap::default();
+
+        for (vehicle_id, vehicle) in vehicles.iter().enumerate_idx() {
+            let mut charges = vec![];
+            if let Some(battery) = vehicle.battery() {
+                let mut station_indices = (0..charging_stations.len()).collect::<Vec<_>>();
+                if let Some(depot_location_id) = vehicle.depot_location_id() {
+                    let profile = &vehicle_profiles[vehicle.profile_id().get()];
+                    station_indices.sort_by(|&a, &b| {
+                        let distance = |index: usize| {
+                            profile
+                                .travel_distance(
+                                    depot_location_id,
+                                    charging_stations[index].location_id(),
+                                )
+                                .value()
+                        };
+                        distance(a).total_cmp(&distance(b))
+                    });
+                    station_indices.truncate(MAX_VEHICLE_CHARGING_STATIONS);
+                    station_indices.sort_unstable();
+                }
+
+                for station_index in station_indices {
+                    let station = &charging_stations[station_index];
+                    for charge in 0..battery.maximum_charges() {
+                        let mut builder = ServiceBuilder::default();
+                        builder
+                            .set_external_id(format!(
+                                "charge_{}_{}_{charge}",
+                                vehicle.external_id(),
+                                station.external_id()
+                            ))
+                            .set_service_type(ServiceType::Charge)
+                            .set_location_id(station.location_id().get())
+                            .set_service_duration(SignedDuration::ZERO);
+
+                        let job_id = JobIdx::new(jobs.len());
+                        jobs.push(Job::Service(builder.build()));
+                        charges.push(job_id);
+                        charge_stops.insert(job_id, (vehicle_id, station_index));
+                    }
+                }
+            }
+            vehicle_charges.push(charges);
+        }
+
+        (vehicle_charges, charge_stops)
+    }
+
+    pub fn has_batteries(&self) -> bool {
+        self.fleet
+            .vehicles()
+            .iter()
+            .any(|vehicle| vehicle.battery().is_some())
+    }
+
+    pub fn has_charges(&self) -> bool {
+        !self.charge_stops.is_empty()
+    }
+
+    pub fn charging_stations(&self) -> &[ChargingStation] {
+        &self.charging_stations
+    }
+
+    pub fn vehicle_charges(&self, vehicle_id: VehicleIdx) -> &[JobIdx] {
+        &self.vehicle_charges[vehicle_id.get()]
+    }
+
+    /// Charging station of the charging job, `None` when the job is not a charge
+    pub fn charging_station(&self, job_id: JobIdx) -> Option<&ChargingStation> {
+        self.charge_stops
+            .get(&job_id)
+            .map(|&(_, station_index)| &self.charging_stations[station_index])
+    }
+
+    /// Number of reloads and charges generated for the vehicles
+    pub fn vehicle_stops_count(&self) -> usize {
+        self.reload_vehicles.len() + self.charge_stops.len()
+    }
+
+    /// Vehicle owning the reload or charging job, `None` for the jobs to serve
+    pub fn vehicle_stop_owner(&self, job_id: JobIdx) -> Option<VehicleIdx> {
+        self.reload_vehicle(job_id).or_else(|| {
+            self.charge_stops
+                .get(&job_id)
+                .map(|&(vehicle_id, _)| vehicle_id)
+        })
+    }
+
     pub(crate) fn next_route_version(&self) -> usize {
-        self.version_counter
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        self.version_counter.fetch_add(1, Ordering::Relaxed)
     }
 
     pub fn jobs(&self) -> &[Job] {
         &self.jobs
     }
 
-    pub fn neighbors(&self, location_id: LocationIdx) -> &FxHashSet<ActivityId> {
-        &self.neighborhoods[location_id.get()]
+    /// The `neighborhood_size` closest activities of the location, at most `MAX_NEIGHBORHOOD_SIZE`,
+    /// see `SolverParams::neighborhood_size`
+    pub fn neighbors(
+        &self,
+        location_id: LocationIdx,
+        neighborhood_size: usize,
+    ) -> impl Iterator<Item = ActivityId> + '_ {
+        self.neighborhoods[location_id.get()]
+            .iter()
+            .filter(move |&(_, &rank)| rank < neighborhood_size)
+            .map(|(&activity_id, _)| activity_id)
     }
 
     pub fn has_services(&self) -> bool {
@@ -424,8 +799,7 @@ impl VehicleRoutingProblem {
 
     #[inline(always)]
     pub fn travel_cost(&self, vehicle: &Vehicle, from: LocationIdx, to: LocationIdx) -> Cost {
-        let profile_id = vehicle.profile_id();
-        self.vehicle_profiles[profile_id].travel_cost(from, to)
+        self.cost_class_travel_cost(vehicle.cost_class_id(), from, to)
     }
 
     #[inline(always)]
@@ -435,7 +809,26 @@ impl VehicleRoutingProblem {
         from: Option<LocationIdx>,
         to: Option<LocationIdx>,
     ) -> Cost {
-        self.vehicle_profiles[vehicle.profile_id()].travel_cost_or_zero(from, to)
+        if let (Some(from), Some(to)) = (from, to) {
+            self.travel_cost(vehicle, from, to)
+        } else {
+            0.0
+        }
+    }
+
+    #[inline(always)]
+    pub fn cost_class_travel_cost(
+        &self,
+        cost_class_id: usize,
+        from: LocationIdx,
+        to: LocationIdx,
+    ) -> Cost {
+        let cost_class = &self.vehicle_cost_classes[cost_class_id];
+        cost_class.travel_cost(&self.vehicle_profiles[cost_class.profile_id()], from, to)
+    }
+
+    pub fn vehicle_cost_classes(&self) -> &[VehicleCostClass] {
+        &self.vehicle_cost_classes
     }
 
     pub fn travel_distance_between_jobs(&self, a: JobIdx, b: JobIdx) -> Meters {
@@ -491,6 +884,14 @@ impl VehicleRoutingProblem {
         self.waiting_duration_weight
     }
 
+    pub fn tolerances(&self) -> &Tolerances {
+        &self.tolerances
+    }
+
+    pub fn constraint_overrides(&self) -> &ConstraintOverrides {
+        &self.constraint_overrides
+    }
+
     pub fn has_waiting_duration_cost(&self) -> bool {
         self.waiting_duration_weight() > 0.0
     }
@@ -504,6 +905,13 @@ impl VehicleRoutingProblem {
         100000.0 //self.max_cost() // Placeholder for the static cost of a route
     }
 
+    /// Cost of using a vehicle, falls back to `fixed_vehicle_costs` when the vehicle doesn't define one
+    pub fn fixed_vehicle_cost(&self, vehicle: &Vehicle) -> f64 {
+        vehicle
+            .fixed_cost()
+            .unwrap_or_else(|| self.fixed_vehicle_costs())
+    }
+
     pub fn nearest_jobs_of_location(
         &self,
         location_id: LocationIdx,
@@ -517,9 +925,16 @@ impl VehicleRoutingProblem {
         self.nearest_jobs_of_location(job_location_id)
     }
 
-    pub fn in_nearest_neighborhood_of(&self, of: ActivityId, activity_id: ActivityId) -> bool {
+    pub fn in_nearest_neighborhood_of(
+        &self,
+        of: ActivityId,
+        activity_id: ActivityId,
+        neighborhood_size: usize,
+    ) -> bool {
         let location_id = self.job_activity(activity_id).location_id();
-        self.neighborhoods[location_id.get()].contains(&of)
+        self.neighborhoods[location_id.get()]
+            .get(&of)
+            .is_some_and(|&rank| rank < neighborhood_size)
     }
 
     pub fn is_symmetric(&self) -> bool {
@@ -536,14 +951,62 @@ impl VehicleRoutingProblem {
         self.has_time_windows
     }
 
+    pub fn has_time_window_preferences(&self) -> bool {
+        self.has_time_window_preferences
+    }
+
+    pub fn has_ride_durations(&self) -> bool {
+        self.has_ride_durations
+    }
+
+    pub fn has_position_preferences(&self) -> bool {
+        self.has_position_preferences
+    }
+
+    pub fn has_stop_sequences(&self) -> bool {
+        self.has_stop_sequences
+    }
+
     pub fn has_capacity(&self) -> bool {
         self.has_capacity
     }
 
+    pub fn has_depots(&self) -> bool {
+        !self.depots.is_empty()
+    }
+
+    pub fn depots(&self) -> &[Depot] {
+        &self.depots
+    }
+
+    pub fn depot(&self, location_id: LocationIdx) -> Option<&Depot> {
+        self.depots
+            .iter()
+            .find(|depot| depot.location_id() == location_id)
+    }
+
+    pub fn has_depot_inventories(&self) -> bool {
+        !self.depot_inventories.is_empty()
+    }
+
+    pub fn depot_inventories(&self) -> &[DepotInventory] {
+        &self.depot_inventories
+    }
+
+    pub fn depot_inventory(&self, location_id: LocationIdx) -> Option<&DepotInventory> {
+        self.depot_inventories
+            .iter()
+            .find(|inventory| inventory.location_id() == location_id)
+    }
+
     pub fn has_skills(&self) -> bool {
         !self.skill_registry.is_empty()
     }
 
+    pub fn has_tags(&self) -> bool {
+        !self.tag_registry.is_empty()
+    }
+
     pub fn has_task_dependencies(&self) -> bool {
         self.has_task_dependencies
     }
@@ -589,14 +1052,15 @@ impl VehicleRoutingProblem {
         locations: &[Location],
         jobs: &[Job],
         vehicle_profiles: &[VehicleProfile],
-    ) -> Vec<FxHashSet<ActivityId>> {
-        let target_size = 50;
+    ) -> Vec<FxHashMap<ActivityId, usize>> {
         let num_locations = locations.len();
 
         // Build location -> activities mapping
         let mut location_activities: Vec<Vec<ActivityId>> = vec![vec![]; num_locations];
         for (job_idx, job) in jobs.iter().enumerate_idx() {
             match job {
+                // Reloads and charges are inserted on purpose, they are nobody's neighbor
+                Job::Service(_) if job.is_vehicle_stop() => {}
                 Job::Service(service) => {
                     location_activities[service.location_id().get()]
                         .push(ActivityId::Service(job_idx));
@@ -623,19 +1087,21 @@ impl VehicleRoutingProblem {
         );
 
         // For each location, take locations with smallest alpha values until we
-        // reach the target neighborhood size in activities
+        // reach the maximum neighborhood size in activities. The activities of a location share
+        // the same rank so that a location is either fully in a neighborhood or not at all
         locations
             .iter()
             .enumerate()
             .map(|(location_id, _)| {
-                let mut neighborhood = FxHashSet::default();
+                let mut neighborhood = FxHashMap::default();
 
                 for &neighbor_loc in &alpha_neighbors[location_id] {
-                    if neighborhood.len() >= target_size {
+                    let rank = neighborhood.len();
+                    if rank >= MAX_NEIGHBORHOOD_SIZE {
                         break;
                     }
                     for &activity in &location_activities[neighbor_loc] {
-                        neighborhood.insert(activity);
+                        neighborhood.entry(activity).or_insert(rank);
                     }
                 }
 
@@ -739,6 +1205,21 @@ impl VehicleRoutingProblem {
 
         skills.into_iter().collect()
     }
+
+    fn collect_tags(vehicles: &[Vehicle], jobs: &[Job]) -> Vec<Tag> {
+        let mut tags = FxHashSet::<Tag>::default();
+
+        for vehicle in vehicles {
+            tags.extend(vehicle.allowed_tags().into_iter().flatten().cloned());
+            tags.extend(vehicle.forbidden_tags().iter().cloned());
+        }
+
+        for job in jobs {
+            tags.extend(job.tags().iter().cloned());
+        }
+
+        tags.into_iter().collect()
+    }
 }
 
 #[derive(Default)]
@@ -749,10 +1230,16 @@ pub struct VehicleRoutingProblemBuilder {
     locations: Option<Vec<Location>>,
     fleet: Option<Fleet>,
     vehicle_profiles: Option<Vec<VehicleProfile>>,
+    euclidean_profile: Option<(String, DistanceRounding)>,
     distance_method: Option<DistanceMethod>,
     penalize_waiting_duration: Option<bool>,
     relations: Option<Vec<Relation>>,
     external_relations: Option<Vec<ExternalRelation>>,
+    depots: Option<Vec<Depot>>,
+    depot_inventories: Option<Vec<DepotInventory>>,
+    charging_stations: Option<Vec<ChargingStation>>,
+    tolerances: Option<Tolerances>,
+    constraint_overrides: Option<ConstraintOverrides>,
 }
 
 impl VehicleRoutingProblemBuilder {
@@ -818,6 +1305,17 @@ impl VehicleRoutingProblemBuilder {
         self
     }
 
+    /// Adds a profile with the euclidean distances between the locations, computed when the
+    /// problem is built. The travel times and costs are equal to the distances
+    pub fn set_euclidean_profile(
+        &mut self,
+        id: String,
+        rounding: DistanceRounding,
+    ) -> &mut VehicleRoutingProblemBuilder {
+        self.euclidean_profile = Some((id, rounding));
+        self
+    }
+
     pub fn set_fleet(&mut self, fleet: Fleet) -> &mut VehicleRoutingProblemBuilder {
         self.fleet = Some(fleet);
         self
@@ -841,6 +1339,41 @@ impl VehicleRoutingProblemBuilder {
         self
     }
 
+    pub fn set_depots(&mut self, depots: Vec<Depot>) -> &mut VehicleRoutingProblemBuilder {
+        self.depots = Some(depots);
+        self
+    }
+
+    pub fn set_depot_inventories(
+        &mut self,
+        depot_inventories: Vec<DepotInventory>,
+    ) -> &mut VehicleRoutingProblemBuilder {
+        self.depot_inventories = Some(depot_inventories);
+        self
+    }
+
+    pub fn set_charging_stations(
+        &mut self,
+        charging_stations: Vec<ChargingStation>,
+    ) -> &mut VehicleRoutingProblemBuilder {
+        self.charging_stations = Some(charging_stations);
+        self
+    }
+
+    pub fn set_tolerances(&mut self, tolerances: Tolerances) -> &mut VehicleRoutingProblemBuilder {
+        self.tolerances = Some(tolerances);
+        self
+    }
+
+    /// Switches constraints of the solver on or off, see `ConstraintSet::validate_overrides`
+    pub fn set_constraint_overrides(
+        &mut self,
+        constraint_overrides: ConstraintOverrides,
+    ) -> &mut VehicleRoutingProblemBuilder {
+        self.constraint_overrides = Some(constraint_overrides);
+        self
+    }
+
     pub fn build(self) -> Result<VehicleRoutingProblem, VehicleRoutingProblemError> {
         let locations = self
             .locations
@@ -865,9 +1398,23 @@ impl VehicleRoutingProblemBuilder {
 
         let distance_method = self.distance_method.unwrap_or(DistanceMethod::Haversine);
 
-        let vehicle_profiles = self
-            .vehicle_profiles
-            .ok_or(VehicleRoutingProblemError::MissingVehicleProfiles)?;
+        if self.vehicle_profiles.is_none() && self.euclidean_profile.is_none() {
+            return Err(VehicleRoutingProblemError::MissingVehicleProfiles);
+        }
+
+        let mut vehicle_profiles = self.vehicle_profiles.unwrap_or_default();
+        if let Some((id, rounding)) = self.euclidean_profile {
+            if !rounding.is_valid() {
+                return Err(VehicleRoutingProblemError::InvalidDistanceRounding(
+                    rounding,
+                ));
+            }
+
+            vehicle_profiles.push(VehicleProfile::new(
+                id,
+                TravelMatrices::from_euclidean(&locations, rounding),
+            ));
+        }
 
         let fleet = self.fleet.ok_or(VehicleRoutingProblemError::MissingFleet)?;
 
@@ -879,6 +1426,11 @@ impl VehicleRoutingProblemBuilder {
             jobs,
             distance_method,
             penalize_waiting_duration: self.penalize_waiting_duration.unwrap_or(true),
+            depots: self.depots.unwrap_or_default(),
+            depot_inventories: self.depot_inventories.unwrap_or_default(),
+            charging_stations: self.charging_stations.unwrap_or_default(),
+            tolerances: self.tolerances.unwrap_or_default(),
+            constraint_overrides: self.constraint_overrides.unwrap_or_default(),
             relations: self
                 .external_relations
                 .map(|relations| VehicleRoutingRelationParams::External(relations))
@@ -888,3 +1440,115 @@ impl VehicleRoutingProblemBuilder {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::{
+            distance_method::DistanceMethod, fleet::Fleet, job::ActivityId, location::LocationIdx,
+            tolerances::Tolerances, travel_cost_matrix::DistanceRounding,
+        },
+        test_utils,
+    };
+
+    use super::{
+        DEFAULT_NEIGHBORHOOD_SIZE, VehicleRoutingProblemBuilder, VehicleRoutingProblemError,
+    };
+
+    #[test]
+    fn test_invalid_vehicle_counts() {
+        for counts in [vec![1], vec![1, 0]] {
+            let mut builder = VehicleRoutingProblemBuilder::default();
+            builder.set_euclidean_profile(String::from("profile"), DistanceRounding::Exact);
+            builder.set_distance_method(DistanceMethod::Euclidean);
+            builder.set_locations(test_utils::create_location_grid(1, 3));
+            builder.set_services(test_utils::create_basic_services(vec![1, 2]));
+            builder.set_fleet(Fleet::Counted {
+                vehicles: test_utils::create_basic_vehicles(vec![0, 0]),
+                counts,
+            });
+
+            assert!(matches!(
+                builder.build(),
+                Err(VehicleRoutingProblemError::InvalidVehicleCounts)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_negative_tolerances() {
+        let tolerances = [
+            Tolerances {
+                time_window: SignedDuration::from_secs(-1),
+                ..Tolerances::default()
+            },
+            Tolerances {
+                capacity: -1.0,
+                ..Tolerances::default()
+            },
+            Tolerances {
+                capacity: f64::NAN,
+                ..Tolerances::default()
+            },
+        ];
+
+        for tolerances in tolerances {
+            let mut builder = VehicleRoutingProblemBuilder::default();
+            builder.set_euclidean_profile(String::from("profile"), DistanceRounding::Exact);
+            builder.set_distance_method(DistanceMethod::Euclidean);
+            builder.set_locations(test_utils::create_location_grid(1, 3));
+            builder.set_services(test_utils::create_basic_services(vec![1, 2]));
+            builder.set_fleet(Fleet::Finite(test_utils::create_basic_vehicles(vec![0])));
+            builder.set_tolerances(tolerances);
+
+            assert!(matches!(
+                builder.build(),
+                Err(VehicleRoutingProblemError::InvalidTolerances)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_distance_rounding() {
+        for factor in [0.0, -10.0, f64::NAN, f64::INFINITY] {
+            let mut builder = VehicleRoutingProblemBuilder::default();
+            builder.set_euclidean_profile(String::from("profile"), DistanceRounding::Scale(factor));
+            builder.set_distance_method(DistanceMethod::Euclidean);
+            builder.set_locations(test_utils::create_location_grid(1, 3));
+            builder.set_services(test_utils::create_basic_services(vec![1, 2]));
+            builder.set_fleet(Fleet::Finite(test_utils::create_basic_vehicles(vec![0])));
+
+            assert!(matches!(
+                builder.build(),
+                Err(VehicleRoutingProblemError::InvalidDistanceRounding(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_neighborhood_size() {
+        let locations = test_utils::create_location_grid(1, 10);
+        let services = test_utils::create_basic_services((1..10).collect());
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        let location_id = LocationIdx::new(1);
+        let default_neighbors = problem
+            .neighbors(location_id, DEFAULT_NEIGHBORHOOD_SIZE)
+            .count();
+        assert!(default_neighbors > 3);
+        assert_eq!(problem.neighbors(location_id, 3).count(), 3);
+
+        // Service 0 is at location 1
+        let neighbors = problem.neighbors(location_id, 3).collect::<Vec<_>>();
+        for job_id in 1..9 {
+            let activity_id = ActivityId::service(job_id);
+            assert_eq!(
+                problem.in_nearest_neighborhood_of(activity_id, ActivityId::service(0), 3),
+                neighbors.contains(&activity_id)
+            );
+        }
+    }
+}
@@ -1,14 +1,18 @@
 use std::{
-    sync::{Arc, atomic::AtomicBool},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize},
+    },
     thread,
 };
 
 use anyhow::anyhow;
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use jiff::{SignedDuration, Timestamp};
 use parking_lot::{Mutex, RwLock};
 use rand::{Rng, SeedableRng, rngs::SmallRng};
-use tracing::{debug, instrument, warn};
+use tracing::{debug, error, instrument, warn};
 
 use crate::{
     acceptor::{
@@ -18,7 +22,8 @@ use crate::{
         simulated_annealing_acceptor::SimulatedAnnealingAcceptor,
         solution_acceptor::SolutionAcceptor,
     },
-    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    json::checkpoint::{CheckpointError, JsonCheckpoint},
+    problem::{job::JobIdx, vehicle_routing_problem::VehicleRoutingProblem},
     selector::{
         select_best_selector::SelectBestSelector,
         select_binary_tournament::BinaryTournamentSelector,
@@ -27,24 +32,17 @@ use crate::{
     },
     solver::{
         alns_weights::{AlnsScores, AlnsWeights, UpdateScoreParams},
-        constraints::{
-            activity_constraint::ActivityConstraintType, capacity_constraint::CapacityConstraint,
-            global_constraint::GlobalConstraintType,
-            maximum_activities_constraint::MaximumActivitiesConstraint,
-            maximum_working_duration_constraint::MaximumWorkingDurationConstraint,
-            relation_constraint::RelationConstraint, route_constraint::RouteConstraintType,
-            shift_constraint::ShiftConstraint, skill_constraint::SkillConstraint,
-            time_window_constraint::TimeWindowConstraint,
-            transport_cost_constraint::TransportCostConstraint,
-            vehicle_cost_constraint::VehicleCostConstraint,
-            waiting_duration_constraint::WaitingDurationConstraint,
-        },
+        best_score_history::BestScoreHistory,
+        insertion_thread_pool::InsertionThreadPool,
         ls::local_search::LocalSearch,
         noise::NoiseParams,
+        repair::route_split::repair_infeasible_routes,
+        route_orientation::orient_routes,
         score::RUN_SCORE_ASSERTIONS,
-        solution::population::Population,
+        solution::{population::Population, verify::verify_solution},
         solver_params::{PopulationParams, SolverParamsDebugOptions},
         statistics::SearchStatisticsIteration,
+        statistics_sink::StatisticsSink,
     },
     timer_debug,
     utils::cancellable_barrier::{CancellableBarrier, WaitResult},
@@ -60,15 +58,27 @@ use super::{
     },
     ruin::{ruin_context::RuinContext, ruin_solution::RuinSolution, ruin_strategy::RuinStrategy},
     score::Score,
-    solution::working_solution::WorkingSolution,
+    solution::{solution_pool::SolutionPool, working_solution::WorkingSolution},
+    solver::panic_message,
     solver_params::{
-        SolverAcceptorStrategy, SolverParams, SolverSelectorStrategy, Termination, Threads,
+        SolverAcceptorStrategy, SolverParams, SolverParamsError, SolverSelectorStrategy,
+        Termination, Threads,
     },
     statistics::{GlobalStatistics, ScoreEvolutionRow},
+    unassigned_reason::{UnassignedReason, unassigned_reasons},
+};
+
+#[cfg(feature = "statistics")]
+use super::{
+    events::{EVENTS_CAPACITY, SolverEvent, TerminationReason},
+    statistics::SearchTelemetry,
 };
 
 use super::statistics::{SearchStatistics, ThreadSearchStatistics};
 
+#[cfg(feature = "chaos")]
+use super::chaos::ChaosEvent;
+
 type BestSolutionHandler = Arc<Mutex<dyn FnMut(&AcceptedSolution) + Send + Sync + 'static>>;
 
 pub struct AlnsRunResult {
@@ -77,6 +87,20 @@ pub struct AlnsRunResult {
     pub duration: SignedDuration,
 }
 
+/// State of a stopped search, running the search again after restoring it continues from the same
+/// population and operator weights
+pub struct AlnsCheckpoint {
+    pub problem_id: String,
+    pub solutions: Vec<AcceptedSolution>,
+    pub ruin_weights: AlnsWeights<RuinStrategy>,
+    pub recreate_weights: AlnsWeights<RecreateStrategy>,
+    pub iterations: usize,
+    pub seed: u64,
+    /// Jobs cancelled since the search was created, including the ones not yet removed from the
+    /// population
+    pub cancelled_jobs: FxHashSet<JobIdx>,
+}
+
 pub struct Alns {
     problem: Arc<VehicleRoutingProblem>,
     constraints: Vec<Constraint>,
@@ -89,19 +113,34 @@ pub struct Alns {
     on_best_solution_handler: Option<BestSolutionHandler>,
     is_stopped: Arc<AtomicBool>,
     statistics: Arc<SearchStatistics>,
+
+    /// Iterations completed by each search thread in previous runs, a new run continues from there
+    completed_iterations: AtomicUsize,
+    seed: AtomicU64,
+    last_checkpoint: Mutex<Timestamp>,
+
+    /// Jobs cancelled while the search is running, removed from the population at the next sync barrier
+    pending_cancellations: Mutex<FxHashSet<JobIdx>>,
+
+    #[cfg(feature = "statistics")]
+    events: tokio::sync::broadcast::Sender<SolverEvent>,
 }
 
 impl Alns {
-    pub fn new(params: SolverParams, problem: Arc<VehicleRoutingProblem>) -> Self {
-        if params.terminations.is_empty() {
-            panic!(
-                "At least one termination condition must be specified in the solver parameters."
-            );
-        }
-
-        Alns {
+    /// Fails when the parameters can't run a search, see `SolverParams::validate`
+    pub fn new(
+        params: SolverParams,
+        problem: Arc<VehicleRoutingProblem>,
+    ) -> Result<Self, SolverParamsError> {
+        params.validate()?;
+
+        Ok(Alns {
             problem: Arc::clone(&problem),
-            constraints: Self::create_constraints(),
+            constraints: params
+                .objective
+                .apply(&params.constraints)
+                .constraints()
+                .to_vec(),
             population: Arc::new(RwLock::new(Population::new(params.population.clone()))),
             // best_solutions: Arc::new(RwLock::new(Vec::with_capacity(params.max_solutions))),
             global_alns_ruin_weights: Arc::new(RwLock::new(AlnsWeights::new(
@@ -120,18 +159,99 @@ impl Alns {
             on_best_solution_handler: None,
 
             is_stopped: Arc::new(AtomicBool::new(false)),
+            completed_iterations: AtomicUsize::new(0),
+            seed: AtomicU64::new(params.seed),
+            last_checkpoint: Mutex::new(Timestamp::now()),
+            pending_cancellations: Mutex::new(FxHashSet::default()),
             statistics: Arc::new(SearchStatistics::new(
                 params.search_threads.number_of_threads(),
+                params.constraints.constraints(),
+                Self::create_statistics_sink(&params),
             )),
+            #[cfg(feature = "statistics")]
+            events: tokio::sync::broadcast::channel(EVENTS_CAPACITY).0,
             params,
+        })
+    }
+
+    /// Creates a search continuing from a checkpoint file written during a previous run of the same problem
+    pub fn resume_from_checkpoint(
+        params: SolverParams,
+        problem: Arc<VehicleRoutingProblem>,
+        path: &Path,
+    ) -> Result<Self, CheckpointError> {
+        let checkpoint = JsonCheckpoint::read(path)?;
+
+        let alns = Self::new(params, problem)?;
+        alns.restore_json_checkpoint(&checkpoint)?;
+
+        Ok(alns)
+    }
+
+    /// Restores the population, weights and iterations of a checkpoint written for the same problem
+    pub fn restore_json_checkpoint(
+        &self,
+        checkpoint: &JsonCheckpoint,
+    ) -> Result<(), CheckpointError> {
+        if checkpoint.problem_id != self.problem.id() {
+            return Err(CheckpointError::ProblemMismatch(
+                checkpoint.problem_id.clone(),
+            ));
+        }
+
+        let cancelled_jobs = checkpoint
+            .cancelled_jobs
+            .iter()
+            .map(|external_id| {
+                self.problem
+                    .jobs()
+                    .iter()
+                    .position(|job| job.external_id() == external_id)
+                    .map(JobIdx::new)
+                    .ok_or_else(|| CheckpointError::UnknownJob(external_id.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for solution in &checkpoint.solutions {
+            self.set_initial_solution(solution.build_solution(Arc::clone(&self.problem))?);
         }
+
+        // The cancelled jobs are unassigned in the solutions of the checkpoint, they must not be
+        // inserted again
+        self.cancel_jobs(cancelled_jobs);
+        self.apply_pending_cancellations();
+
+        self.global_alns_ruin_weights
+            .write()
+            .set_named_weights(&checkpoint.ruin_weights);
+        self.global_alns_recreate_weights
+            .write()
+            .set_named_weights(&checkpoint.recreate_weights);
+        self.completed_iterations
+            .store(checkpoint.iterations, std::sync::atomic::Ordering::Relaxed);
+        self.seed
+            .store(checkpoint.seed, std::sync::atomic::Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    pub fn params(&self) -> &SolverParams {
+        &self.params
     }
 
     pub fn problem(&self) -> &Arc<VehicleRoutingProblem> {
         &self.problem
     }
 
-    fn set_initial_solution(&self, solution: WorkingSolution) {
+    /// Adds an existing solution to the population, its infeasible routes are split first.
+    /// The construction heuristic is skipped when the population is not empty.
+    pub fn set_initial_solution(&self, mut solution: WorkingSolution) {
+        solution.set_neighborhood_size(self.params.neighborhood_size);
+
+        // A solution carried over from an updated problem can have routes overloaded by the
+        // updated jobs, see `JsonProblemUpdate`
+        repair_infeasible_routes(&mut solution, &self.constraints);
+
         let (score, score_analysis) = solution.compute_solution_score(&self.constraints);
         self.population
             .write()
@@ -149,11 +269,11 @@ impl Alns {
             .unwrap()
     }
 
-    fn create_insertion_thread_pool(&self) -> rayon::ThreadPool {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(self.params.insertion_threads.number_of_threads())
-            .build()
-            .unwrap()
+    fn create_insertion_thread_pool(&self) -> InsertionThreadPool {
+        InsertionThreadPool::new(
+            self.params.insertion_threads.number_of_threads(),
+            self.problem.jobs().len(),
+        )
     }
 
     fn create_solution_selector(&self) -> SolutionSelector {
@@ -192,10 +312,12 @@ impl Alns {
                         solver_selector: SolverSelectorStrategy::SelectBest,
                         run_intensify_search: false,
                         intensify_probability: 0.0,
+                        checkpoint: None,
+                        statistics_sink: None,
                         ..self.params.clone()
                     },
                     Arc::clone(&self.problem),
-                );
+                )?;
 
                 if let Some(best_solution) = self.best_solution() {
                     shrimpf_initial_threshold_search
@@ -209,7 +331,7 @@ impl Alns {
                     .read()
                     .solutions()
                     .iter()
-                    .map(|accepted_solution| accepted_solution.score.soft_score)
+                    .map(|accepted_solution| accepted_solution.score.leading_soft_score())
                     .sum::<f64>();
                 let mean = total_score / random_walks as f64;
 
@@ -218,7 +340,9 @@ impl Alns {
                     .read()
                     .solutions()
                     .iter()
-                    .map(|accepted_solution| (accepted_solution.score.soft_score - mean).powf(2.0))
+                    .map(|accepted_solution| {
+                        (accepted_solution.score.leading_soft_score() - mean).powf(2.0)
+                    })
                     .sum::<f64>()
                     / ((random_walks - 1) as f64);
 
@@ -240,6 +364,7 @@ impl Alns {
                         population: PopulationParams {
                             size: 1,
                             elite_size: 1,
+                            max_memory_bytes: None,
                         },
                         solver_acceptor: SolverAcceptorStrategy::Any,
                         search_threads: Threads::Single,
@@ -248,11 +373,14 @@ impl Alns {
                         intensify_probability: 0.0,
                         debug_options: SolverParamsDebugOptions {
                             enable_local_search: false,
+                            verify_after_local_search: false,
                         },
+                        checkpoint: None,
+                        statistics_sink: None,
                         ..self.params.clone()
                     },
                     Arc::clone(&self.problem),
-                );
+                )?;
 
                 if let Some(best_solution) = self.best_solution() {
                     initial_temperature_search.set_initial_solution(best_solution.solution.clone());
@@ -263,7 +391,7 @@ impl Alns {
                     .best_solution()
                     .unwrap()
                     .score
-                    .soft_score;
+                    .leading_soft_score();
 
                 let w = 0.3;
                 let start_temperature = w * soft_score / (0.5_f64.ln().abs());
@@ -275,31 +403,6 @@ impl Alns {
         }
     }
 
-    fn create_constraints() -> Vec<Constraint> {
-        vec![
-            // Hard constraints
-            Constraint::Global(GlobalConstraintType::Relation(RelationConstraint)),
-            Constraint::Route(RouteConstraintType::MaximumJobs(
-                MaximumActivitiesConstraint,
-            )),
-            Constraint::Route(RouteConstraintType::Shift(ShiftConstraint)),
-            Constraint::Route(RouteConstraintType::MaximumWorkingDuration(
-                MaximumWorkingDurationConstraint,
-            )),
-            Constraint::Activity(ActivityConstraintType::TimeWindow(
-                TimeWindowConstraint::default(),
-            )),
-            Constraint::Route(RouteConstraintType::Capacity(CapacityConstraint::default())),
-            Constraint::Activity(ActivityConstraintType::Skill(SkillConstraint)),
-            // Soft constraints
-            Constraint::Global(GlobalConstraintType::TransportCost(TransportCostConstraint)),
-            Constraint::Route(RouteConstraintType::VehicleCost(VehicleCostConstraint)),
-            Constraint::Route(RouteConstraintType::WaitingDuration(
-                WaitingDurationConstraint,
-            )),
-        ]
-    }
-
     pub fn on_best_solution<F>(&mut self, callback: F)
     where
         F: FnMut(&AcceptedSolution) + Send + Sync + 'static,
@@ -314,11 +417,59 @@ impl Alns {
             .map(|accepted_solution| accepted_solution.clone())
     }
 
+    /// Reason of each unassigned job of the solution, see `unassigned_reasons`
+    pub fn unassigned_reasons(&self, solution: &WorkingSolution) -> Vec<UnassignedReason> {
+        unassigned_reasons(solution, &self.constraints)
+    }
+
     #[cfg(feature = "statistics")]
     pub fn statistics(&self) -> Arc<SearchStatistics> {
         Arc::clone(&self.statistics)
     }
 
+    #[cfg(feature = "statistics")]
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<SolverEvent> {
+        self.events.subscribe()
+    }
+
+    #[cfg(feature = "statistics")]
+    fn emit_event(&self, event: SolverEvent) {
+        // Sending only fails when there are no subscribers
+        let _ = self.events.send(event);
+    }
+
+    #[cfg(feature = "statistics")]
+    pub fn telemetry(&self) -> SearchTelemetry {
+        SearchTelemetry {
+            threads: self.statistics.telemetry(),
+            pool_scores: self
+                .population
+                .read()
+                .solutions()
+                .iter()
+                .map(|accepted_solution| accepted_solution.score)
+                .collect(),
+        }
+    }
+
+    #[cfg(feature = "statistics")]
+    pub fn progress(&self) -> SolverEvent {
+        SolverEvent::Progress {
+            timestamp: Timestamp::now(),
+            iterations: self
+                .statistics
+                .telemetry()
+                .iter()
+                .map(|thread| thread.iterations)
+                .sum(),
+            best_score: self
+                .population
+                .read()
+                .best()
+                .map(|accepted_solution| accepted_solution.score),
+        }
+    }
+
     pub fn weights_cloned(&self) -> (AlnsWeights<RuinStrategy>, AlnsWeights<RecreateStrategy>) {
         (
             self.global_alns_ruin_weights.read().clone(),
@@ -326,9 +477,111 @@ impl Alns {
         )
     }
 
+    pub fn checkpoint(&self) -> AlnsCheckpoint {
+        let (ruin_weights, recreate_weights) = self.weights_cloned();
+        let iterations = self
+            .completed_iterations
+            .load(std::sync::atomic::Ordering::Relaxed);
+        let solutions = self.population.read().solutions().to_vec();
+
+        let mut cancelled_jobs = self.pending_cancellations.lock().clone();
+        for accepted_solution in &solutions {
+            cancelled_jobs.extend(accepted_solution.solution.cancelled_jobs());
+        }
+
+        AlnsCheckpoint {
+            problem_id: self.problem.id().to_owned(),
+            solutions,
+            ruin_weights,
+            recreate_weights,
+            iterations,
+            // Resumed runs should not replay the random choices of the previous run
+            seed: self.params.seed.wrapping_add(iterations as u64),
+            cancelled_jobs,
+        }
+    }
+
+    fn create_statistics_sink(params: &SolverParams) -> Option<StatisticsSink> {
+        let sink_params = params.statistics_sink.as_ref()?;
+
+        match StatisticsSink::create(sink_params) {
+            Ok(sink) => Some(sink),
+            Err(error) => {
+                warn!(
+                    "Failed to create the statistics file {}: {error}",
+                    sink_params.path.display()
+                );
+                None
+            }
+        }
+    }
+
+    fn write_checkpoint(&self, path: &Path) {
+        if let Err(error) = JsonCheckpoint::from(&self.checkpoint()).write(path) {
+            warn!("Failed to write checkpoint to {}: {error}", path.display());
+        }
+
+        *self.last_checkpoint.lock() = Timestamp::now();
+    }
+
+    fn is_checkpoint_due(&self, interval: SignedDuration) -> bool {
+        Timestamp::now().duration_since(*self.last_checkpoint.lock()) >= interval
+    }
+
+    /// Replaces the population and the global weights with the ones of the checkpoint
+    pub fn restore(&self, checkpoint: AlnsCheckpoint) {
+        let mut population = Population::new(self.params.population.clone());
+        for mut accepted_solution in checkpoint.solutions {
+            accepted_solution
+                .solution
+                .set_neighborhood_size(self.params.neighborhood_size);
+            population.add_solution(
+                accepted_solution.solution,
+                accepted_solution.score,
+                accepted_solution.score_analysis,
+            );
+        }
+
+        *self.population.write() = population;
+        *self.global_alns_ruin_weights.write() = checkpoint.ruin_weights;
+        *self.global_alns_recreate_weights.write() = checkpoint.recreate_weights;
+        self.completed_iterations
+            .store(checkpoint.iterations, std::sync::atomic::Ordering::Relaxed);
+        self.seed
+            .store(checkpoint.seed, std::sync::atomic::Ordering::Relaxed);
+        self.cancel_jobs(checkpoint.cancelled_jobs);
+    }
+
     pub fn stop(&self) {
-        self.is_stopped
-            .store(true, std::sync::atomic::Ordering::Relaxed);
+        if !self
+            .is_stopped
+            .swap(true, std::sync::atomic::Ordering::Relaxed)
+        {
+            #[cfg(feature = "statistics")]
+            self.emit_event(SolverEvent::Terminated {
+                reason: TerminationReason::Stopped,
+            });
+        }
+    }
+
+    /// Cancels jobs without restarting the search, they are removed from every solution of the
+    /// population the next time the search threads are synced
+    pub fn cancel_jobs(&self, job_ids: impl IntoIterator<Item = JobIdx>) {
+        self.pending_cancellations.lock().extend(job_ids);
+    }
+
+    /// Removes the pending cancelled jobs from the population, must only be called when no search
+    /// thread is in the middle of an iteration
+    pub fn apply_pending_cancellations(&self) {
+        let cancelled_jobs = std::mem::take(&mut *self.pending_cancellations.lock());
+        if cancelled_jobs.is_empty() {
+            return;
+        }
+
+        debug!("Cancelling {} jobs", cancelled_jobs.len());
+        self.population
+            .write()
+            .cancel_jobs(&cancelled_jobs, &self.constraints);
     }
 
     #[instrument(skip_all, level = "debug")]
@@ -340,13 +593,21 @@ impl Alns {
 
         let thread_pool = self.create_construction_thread_pool();
 
-        let initial_solution = timer_debug!(
+        #[cfg(feature = "statistics")]
+        let construction_start = Timestamp::now();
+
+        let mut initial_solution = timer_debug!(
             "Construction",
             thread_pool.install(|| {
                 construct_solution(&self.problem, &self.params, rng, &self.constraints)
             })
         );
 
+        // When inserting on failure, the construction can leave overloaded routes behind
+        if self.params.recreate.insert_on_failure {
+            repair_infeasible_routes(&mut initial_solution, &self.constraints);
+        }
+
         let (score, score_analysis) = initial_solution.compute_solution_score(&self.constraints);
 
         #[cfg(feature = "statistics")]
@@ -362,10 +623,22 @@ impl Alns {
                 });
         }
 
+        #[cfg(feature = "statistics")]
+        self.emit_event(SolverEvent::ConstructionFinished {
+            score,
+            unassigned_jobs: initial_solution.unassigned_jobs().len(),
+            duration: Timestamp::now().duration_since(construction_start),
+        });
+
         self.population
             .write()
             .add_solution(initial_solution, score, score_analysis);
 
+        #[cfg(feature = "statistics")]
+        if let Some(best) = self.population.read().best() {
+            self.emit_event(SolverEvent::best_solution(best));
+        }
+
         if let Some(callback) = &self.on_best_solution_handler
             && let Some(best) = self.population.read().best()
         {
@@ -377,10 +650,14 @@ impl Alns {
         self.is_stopped
             .store(false, std::sync::atomic::Ordering::Relaxed);
 
-        let mut rng = SmallRng::seed_from_u64(2427121);
+        let mut rng = SmallRng::seed_from_u64(self.seed.load(std::sync::atomic::Ordering::Relaxed));
+        let completed_iterations = self
+            .completed_iterations
+            .load(std::sync::atomic::Ordering::Relaxed);
         let start = Timestamp::now();
 
         self.run_construction(&mut rng);
+        self.apply_pending_cancellations();
 
         if !self.params.debug_options.enable_local_search {
             return Ok(AlnsRunResult {
@@ -399,12 +676,38 @@ impl Alns {
 
         let barrier = Arc::new(CancellableBarrier::new(num_threads));
 
+        let thread_populations = (0..num_threads)
+            .map(|_| {
+                if self.params.deterministic {
+                    Arc::new(RwLock::new(self.copy_population()))
+                } else {
+                    Arc::clone(&self.population)
+                }
+            })
+            .collect::<Vec<_>>();
+        let thread_populations = thread_populations.as_slice();
+
+        // Scores of each thread since the last sync, accumulated in the global scores in thread order
+        let thread_scores = (0..num_threads)
+            .map(|_| {
+                Mutex::new((
+                    AlnsScores::new(self.params.ruin_strategies().clone()),
+                    AlnsScores::new(self.params.recreate_strategies().clone()),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let thread_scores = thread_scores.as_slice();
+
+        // Spans are per thread, the search threads have to enter the span of the caller themselves
+        let span = tracing::Span::current();
+
         thread::scope(|scope| {
             let mut handles: Vec<_> = vec![];
             for thread_index in 0..num_threads {
                 let thread_barrier = Arc::clone(&barrier);
+                let thread_span = span.clone();
 
-                let population = Arc::clone(&self.population);
+                let population = Arc::clone(&thread_populations[thread_index]);
 
                 let global_statistics = Arc::clone(self.statistics.global_statistics());
                 let thread_statistics = Arc::clone(self.statistics.thread_statistics(thread_index));
@@ -430,17 +733,23 @@ impl Alns {
 
                 let handle = builder
                     .spawn_scoped(scope, move || {
+                        let _span = thread_span.enter();
+
+                        // A panicking thread would leave the others waiting on the barrier forever
+                        let _cancel_on_panic = CancelOnPanic {
+                            barrier: &thread_barrier,
+                            is_stopped: &self.is_stopped,
+                        };
+
                         let mut state = ThreadedSearchState {
                             start,
                             thread: thread_index,
-                            iteration: 0,
+                            iteration: completed_iterations,
                             iterations_without_improvement: 0,
-                            alns_ruin_weights: AlnsWeights::new(
-                                self.params.ruin_strategies().clone(),
-                            ),
-                            alns_recreate_weights: AlnsWeights::new(
-                                self.params.recreate_strategies().clone(),
-                            ),
+                            // Start from the global weights, they are only different from the initial
+                            // weights when resuming a search
+                            alns_ruin_weights: self.global_alns_ruin_weights.read().clone(),
+                            alns_recreate_weights: self.global_alns_recreate_weights.read().clone(),
                             alns_ruin_scores: AlnsScores::new(
                                 self.params.ruin_strategies().clone(),
                             ),
@@ -457,15 +766,20 @@ impl Alns {
                                 &self.problem,
                                 self.constraints.to_vec(),
                             ),
+                            solution_pool: SolutionPool::new(self.params.population.size),
                             solution_acceptor,
                             solution_selector,
+                            best_score_history: self.create_best_score_history(),
                         };
 
                         loop {
                             // Every 100 iterations, clear local search cache
                             if state.iteration.is_multiple_of(100) {
                                 debug!("Clear LS cache");
-                                state.local_search.clear_stale(&self.population.read());
+                                state.local_search.clear_stale(
+                                    &state.population.read(),
+                                    self.params.local_search_cache_size,
+                                );
                             }
 
                             let should_intensify = false;
@@ -493,7 +807,7 @@ impl Alns {
                                             .select_solution(&best_selector, &mut thread_rng)
                                     {
                                         (
-                                            solution.clone(),
+                                            state.solution_pool.copy_of(solution),
                                             *score,
                                             score_analysis.clone(),
                                             population.best().unwrap().score,
@@ -579,20 +893,52 @@ impl Alns {
                                 self.run_iteration(&mut state, &mut thread_rng);
                             }
 
+                            #[cfg(feature = "chaos")]
+                            match self.params.chaos.next_event(&mut thread_rng) {
+                                Some(ChaosEvent::Panic) => {
+                                    panic!("Chaos: thread {thread_index} panicked")
+                                }
+                                Some(ChaosEvent::SlowIteration(duration)) => {
+                                    thread::sleep(duration)
+                                }
+                                Some(ChaosEvent::Stop) => self.stop(),
+                                None => {}
+                            }
+
                             if state
                                 .iteration
                                 .is_multiple_of(self.params.threads_sync_iterations_interval)
                             {
-                                // Update accumulated global stats from local stats
-                                self.global_alns_ruin_scores
-                                    .write()
-                                    .accumulate(&mut state.alns_ruin_scores);
-                                self.global_alns_recreate_scores
-                                    .write()
-                                    .accumulate(&mut state.alns_recreate_scores);
+                                {
+                                    let mut scores = thread_scores[thread_index].lock();
+                                    scores.0.accumulate(&mut state.alns_ruin_scores);
+                                    scores.1.accumulate(&mut state.alns_recreate_scores);
+                                }
 
                                 match thread_barrier.wait() {
                                     WaitResult::Leader => {
+                                        // Update accumulated global stats from local stats, in thread
+                                        // order so that the result doesn't depend on the arrival order
+                                        {
+                                            let mut ruin_scores =
+                                                self.global_alns_ruin_scores.write();
+                                            let mut recreate_scores =
+                                                self.global_alns_recreate_scores.write();
+                                            for scores in thread_scores {
+                                                let mut scores = scores.lock();
+                                                ruin_scores.accumulate(&mut scores.0);
+                                                recreate_scores.accumulate(&mut scores.1);
+                                            }
+                                        }
+
+                                        if self.params.deterministic {
+                                            self.merge_populations(thread_populations);
+                                        }
+
+                                        // The other threads are waiting on the barrier, deterministic
+                                        // threads get the cancellations when copying the population
+                                        self.apply_pending_cancellations();
+
                                         debug!("Updating global weights from leader");
                                         // Update global weights
                                         self.global_alns_ruin_weights.write().update_weights(
@@ -604,6 +950,26 @@ impl Alns {
                                             &mut self.global_alns_recreate_scores.write(),
                                             self.params.alns_reaction_factor,
                                         );
+
+                                        if let Some(checkpoint_params) = &self.params.checkpoint
+                                            && self.is_checkpoint_due(checkpoint_params.interval)
+                                        {
+                                            // All threads are at the same iteration on the barrier
+                                            self.completed_iterations.fetch_max(
+                                                state.iteration,
+                                                std::sync::atomic::Ordering::Relaxed,
+                                            );
+                                            self.write_checkpoint(&checkpoint_params.path);
+                                        }
+
+                                        #[cfg(feature = "statistics")]
+                                        {
+                                            let (ruin, recreate) = self.weights_cloned();
+                                            self.emit_event(SolverEvent::Weights {
+                                                ruin,
+                                                recreate,
+                                            });
+                                        }
                                     }
                                     WaitResult::Cancelled => {
                                         break;
@@ -616,28 +982,59 @@ impl Alns {
                                     break;
                                 }
 
+                                if self.params.deterministic {
+                                    *state.population.write() = self.copy_population();
+                                }
+
                                 // Update local weights from global
                                 state.alns_ruin_weights =
                                     self.global_alns_ruin_weights.read().clone();
 
                                 state.alns_recreate_weights =
                                     self.global_alns_recreate_weights.read().clone();
+
+                                #[cfg(feature = "statistics")]
+                                state.thread_statistics.write().record_weights(
+                                    &state.alns_ruin_weights,
+                                    &state.alns_recreate_weights,
+                                );
                             }
 
+                            state.record_best_score();
+
                             let is_stopped =
                                 self.is_stopped.load(std::sync::atomic::Ordering::Relaxed);
-                            let should_terminate = is_stopped || self.should_terminate(&state);
-                            if should_terminate {
-                                // Make sure other threads stop as well
-                                if !is_stopped {
-                                    self.stop();
+                            let termination = if is_stopped {
+                                None
+                            } else {
+                                self.should_terminate(&state)
+                            };
+
+                            if is_stopped || termination.is_some() {
+                                // The first thread to stop reports the termination reason
+                                #[cfg(feature = "statistics")]
+                                if let Some(termination) = termination
+                                    && !self
+                                        .is_stopped
+                                        .swap(true, std::sync::atomic::Ordering::Relaxed)
+                                {
+                                    self.emit_event(SolverEvent::Terminated {
+                                        reason: TerminationReason::from(termination),
+                                    });
                                 }
 
+                                // Make sure other threads stop as well
+                                self.is_stopped
+                                    .store(true, std::sync::atomic::Ordering::Relaxed);
+
                                 thread_barrier.cancel();
                                 break;
                             }
                         }
 
+                        self.completed_iterations
+                            .fetch_max(state.iteration, std::sync::atomic::Ordering::Relaxed);
+
                         return state.iteration;
                     })
                     .unwrap();
@@ -651,15 +1048,27 @@ impl Alns {
                     Ok(iterations) => {
                         total_iterations += iterations;
                     }
-                    Err(_) => {
+                    Err(payload) => {
                         self.is_stopped
                             .store(true, std::sync::atomic::Ordering::Relaxed);
 
-                        return Err(anyhow!("Thread {i} panicked"));
+                        return Err(anyhow!("Thread {i} panicked: {}", panic_message(&*payload)));
                     }
                 }
             }
 
+            if self.params.deterministic {
+                self.merge_populations(thread_populations);
+            }
+
+            self.apply_pending_cancellations();
+
+            if let Some(checkpoint_params) = &self.params.checkpoint {
+                self.write_checkpoint(&checkpoint_params.path);
+            }
+
+            self.statistics.flush_sink();
+
             Ok(AlnsRunResult {
                 best_solution: self.best_solution(),
                 iterations: total_iterations,
@@ -668,6 +1077,19 @@ impl Alns {
         })
     }
 
+    fn copy_population(&self) -> Population {
+        let mut population = Population::new(self.params.population.clone());
+        population.merge(&self.population.read());
+        population
+    }
+
+    fn merge_populations(&self, thread_populations: &[Arc<RwLock<Population>>]) {
+        let mut population = self.population.write();
+        for thread_population in thread_populations {
+            population.merge(&thread_population.read());
+        }
+    }
+
     fn check_termination(&self, state: &ThreadedSearchState, termination: &Termination) -> bool {
         match *termination {
             Termination::Iterations(max_iterations) => state.iteration >= max_iterations,
@@ -696,11 +1118,31 @@ impl Alns {
                     false
                 }
             }
+            Termination::Stagnation {
+                min_improvement, ..
+            } => state
+                .best_score_history
+                .as_ref()
+                .and_then(|history| history.relative_improvement(Timestamp::now()))
+                .is_some_and(|improvement| improvement < min_improvement),
         }
     }
 
-    fn should_terminate(&self, state: &ThreadedSearchState) -> bool {
-        self.params.terminations.iter().any(|termination| {
+    fn create_best_score_history(&self) -> Option<BestScoreHistory> {
+        self.params
+            .terminations
+            .iter()
+            .filter_map(|termination| match termination {
+                Termination::Stagnation { window, .. } => Some(*window),
+                _ => None,
+            })
+            .max()
+            .map(BestScoreHistory::new)
+    }
+
+    /// Returns the first termination condition met by the thread
+    fn should_terminate(&self, state: &ThreadedSearchState) -> Option<&Termination> {
+        self.params.terminations.iter().find(|termination| {
             if self.check_termination(state, termination) {
                 if !matches!(termination, Termination::Iterations(_)) {
                     debug!(
@@ -727,7 +1169,7 @@ impl Alns {
                 }) = population.select_solution(state.solution_selector.as_ref(), rng)
             {
                 (
-                    solution.clone(),
+                    state.solution_pool.copy_of(solution),
                     *score,
                     population.best().unwrap().score,
                     population.best().unwrap().solution.unassigned_jobs().len(),
@@ -744,17 +1186,21 @@ impl Alns {
         self.ruin(&mut working_solution, ruin_strategy, state, rng);
 
         // Maximum duration is not optimized right now and can break after a ruin, we just ignore the iteration for now if it happens
-        for route in working_solution.routes() {
-            let vehicle = route.vehicle(&self.problem);
-            if let Some(max_working_duration) = vehicle.maximum_working_duration() {
-                let route_duration = route.duration(&self.problem);
-                if route_duration > max_working_duration {
-                    tracing::warn!(
-                        "Ignoring ruin iteration: route duration exceeds vehicle's maximum working duration"
-                    );
-                    return;
-                }
-            }
+        let exceeds_maximum_working_duration = working_solution.routes().iter().any(|route| {
+            route
+                .vehicle(&self.problem)
+                .maximum_working_duration()
+                .is_some_and(|max_working_duration| {
+                    route.duration(&self.problem) > max_working_duration
+                })
+        });
+
+        if exceeds_maximum_working_duration {
+            tracing::warn!(
+                "Ignoring ruin iteration: route duration exceeds vehicle's maximum working duration"
+            );
+            state.solution_pool.recycle(working_solution);
+            return;
         }
 
         if RUN_SCORE_ASSERTIONS
@@ -773,6 +1219,9 @@ impl Alns {
         self.recreate(&mut working_solution, recreate_strategy, state, rng);
 
         let recreate_duration = Timestamp::now().duration_since(now);
+        state
+            .insertion_thread_pool
+            .record_insertion_duration(recreate_duration);
 
         let (score, _) = working_solution.compute_solution_score(&self.constraints);
         let improved = score < current_score
@@ -784,6 +1233,19 @@ impl Alns {
                 state
                     .local_search
                     .intensify(&self.problem, &mut working_solution, 500);
+
+            orient_routes(&self.problem, &self.constraints, &mut working_solution);
+
+            if self.params.debug_options.verify_after_local_search {
+                let report = verify_solution(&working_solution, &self.constraints);
+                for violation in &report.violations {
+                    error!(
+                        iteration = state.iteration,
+                        ?violation,
+                        "Invalid solution after the local search"
+                    );
+                }
+            }
         }
 
         self.update_population(
@@ -812,6 +1274,11 @@ impl Alns {
     ) {
         let (score, score_analysis) = solution.compute_solution_score(&self.constraints);
 
+        #[cfg(feature = "statistics")]
+        self.statistics
+            .constraint_statistics()
+            .record_score_analysis(&score_analysis);
+
         if RUN_SCORE_ASSERTIONS && !self.params.recreate.insert_on_failure && score.is_infeasible()
         {
             tracing::error!(
@@ -864,6 +1331,8 @@ impl Alns {
 
             #[cfg(feature = "statistics")]
             {
+                state.thread_statistics.write().record_acceptance(true);
+
                 match iteration_info {
                     IterationInfo::RuinRecreate {
                         current_score,
@@ -899,17 +1368,25 @@ impl Alns {
                 }
             }
 
-            guard.with_upgraded(|guard| {
-                guard.add_solution(solution, score, score_analysis);
+            let discarded_solution = guard.with_upgraded(|guard| {
+                let discarded_solution = guard.add_solution(solution, score, score_analysis);
 
-                if is_best
-                    && let Some(callback) = &self.on_best_solution_handler
-                    && let Some(best) = guard.best()
-                {
-                    callback.lock()(best);
+                if is_best && let Some(best) = guard.best() {
+                    #[cfg(feature = "statistics")]
+                    self.emit_event(SolverEvent::best_solution(best));
+
+                    if let Some(callback) = &self.on_best_solution_handler {
+                        callback.lock()(best);
+                    }
                 }
+
+                discarded_solution
             });
 
+            if let Some(discarded_solution) = discarded_solution {
+                state.solution_pool.recycle(discarded_solution);
+            }
+
             if let Some(strategy) = iteration_info.strategy() {
                 state.alns_ruin_scores.update_scores(
                     strategy.0,
@@ -930,25 +1407,32 @@ impl Alns {
                     },
                 );
             }
-        } else if let Some(strategy) = iteration_info.strategy() {
-            state.alns_ruin_scores.update_scores(
-                strategy.0,
-                &self.params,
-                UpdateScoreParams {
-                    is_best: false,
-                    improved: false,
-                    accepted: false,
-                },
-            );
-            state.alns_recreate_scores.update_scores(
-                strategy.1,
-                &self.params,
-                UpdateScoreParams {
-                    is_best: false,
-                    improved: false,
-                    accepted: false,
-                },
-            );
+        } else {
+            state.solution_pool.recycle(solution);
+
+            #[cfg(feature = "statistics")]
+            state.thread_statistics.write().record_acceptance(false);
+
+            if let Some(strategy) = iteration_info.strategy() {
+                state.alns_ruin_scores.update_scores(
+                    strategy.0,
+                    &self.params,
+                    UpdateScoreParams {
+                        is_best: false,
+                        improved: false,
+                        accepted: false,
+                    },
+                );
+                state.alns_recreate_scores.update_scores(
+                    strategy.1,
+                    &self.params,
+                    UpdateScoreParams {
+                        is_best: false,
+                        improved: false,
+                        accepted: false,
+                    },
+                );
+            }
         }
 
         if state.iteration > 0 {
@@ -961,6 +1445,12 @@ impl Alns {
                 state.alns_recreate_weights.reset();
                 state.alns_ruin_scores.reset();
                 state.alns_recreate_scores.reset();
+
+                #[cfg(feature = "statistics")]
+                state
+                    .thread_statistics
+                    .write()
+                    .record_weights(&state.alns_ruin_weights, &state.alns_recreate_weights);
             } else if state
                 .iteration
                 .is_multiple_of(self.params.alns_segment_iterations)
@@ -974,6 +1464,12 @@ impl Alns {
                     &mut state.alns_recreate_scores,
                     self.params.alns_reaction_factor,
                 );
+
+                #[cfg(feature = "statistics")]
+                state
+                    .thread_statistics
+                    .write()
+                    .record_weights(&state.alns_ruin_weights, &state.alns_recreate_weights);
             }
         }
     }
@@ -1045,6 +1541,9 @@ impl Alns {
                     },
                     problem: &self.problem,
                     insert_on_failure: self.params.recreate.insert_on_failure,
+                    custom_strategies: &self.params.recreate.custom_strategies,
+                    #[cfg(feature = "statistics")]
+                    constraint_statistics: Some(self.statistics.constraint_statistics()),
                 },
             );
         });
@@ -1123,6 +1622,22 @@ impl IterationInfo {
     }
 }
 
+/// Stops the search and releases the threads waiting on the barrier when a search thread panics
+struct CancelOnPanic<'a> {
+    barrier: &'a CancellableBarrier,
+    is_stopped: &'a AtomicBool,
+}
+
+impl Drop for CancelOnPanic<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.is_stopped
+                .store(true, std::sync::atomic::Ordering::Relaxed);
+            self.barrier.cancel();
+        }
+    }
+}
+
 struct ThreadedSearchState {
     start: Timestamp,
     thread: usize,
@@ -1138,8 +1653,185 @@ struct ThreadedSearchState {
     max_iterations: Option<usize>,
     global_statistics: Arc<RwLock<GlobalStatistics>>,
     thread_statistics: Arc<RwLock<ThreadSearchStatistics>>,
-    insertion_thread_pool: rayon::ThreadPool,
+    insertion_thread_pool: InsertionThreadPool,
     local_search: LocalSearch,
+    solution_pool: SolutionPool,
     solution_acceptor: Arc<SolutionAcceptor>,
     solution_selector: Arc<SolutionSelector>,
+    /// Only kept when the search has a `Termination::Stagnation`
+    best_score_history: Option<BestScoreHistory>,
+}
+
+impl ThreadedSearchState {
+    fn record_best_score(&mut self) {
+        if let Some(history) = &mut self.best_score_history
+            && let Some(best) = self.population.read().best()
+        {
+            history.record(Timestamp::now(), best.score);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        json::checkpoint::JsonCheckpoint,
+        problem::{
+            job::{ActivityId, JobIdx},
+            vehicle::VehicleBuilder,
+            vehicle_routing_problem::VehicleRoutingProblem,
+        },
+        solver::{
+            accepted_solution::AcceptedSolution,
+            solver_params::{SolverParams, Termination, Threads},
+        },
+        test_utils,
+    };
+
+    use super::Alns;
+
+    fn create_problem() -> Arc<VehicleRoutingProblem> {
+        let locations = test_utils::create_location_grid(5, 5);
+        let services = test_utils::create_basic_services((1..25).collect());
+
+        let vehicles = (0..3)
+            .map(|index| {
+                let mut builder = VehicleBuilder::default();
+                builder.set_depot_location_id(0);
+                builder.set_vehicle_id(index.to_string());
+                builder.set_profile_id(0);
+                builder.build()
+            })
+            .collect();
+
+        Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ))
+    }
+
+    fn run_deterministic(seed: u64) -> AcceptedSolution {
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(500)],
+            search_threads: Threads::Multi(4),
+            insertion_threads: Threads::Single,
+            threads_sync_iterations_interval: 50,
+            seed,
+            deterministic: true,
+            ..SolverParams::default()
+        };
+
+        let alns = Alns::new(params, create_problem()).unwrap();
+        alns.run().unwrap().best_solution.unwrap()
+    }
+
+    fn assert_jobs_cancelled(alns: &Alns, job_ids: &[JobIdx]) {
+        let population = alns.population.read();
+        assert!(!population.is_empty());
+
+        for accepted_solution in population.solutions() {
+            let solution = &accepted_solution.solution;
+            for &job_id in job_ids {
+                assert!(solution.cancelled_jobs().contains(&job_id));
+                assert!(!solution.is_unassigned(job_id));
+                assert!(
+                    !solution
+                        .routes()
+                        .iter()
+                        .any(|route| route.contains_activity(ActivityId::Service(job_id)))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_deterministic_runs_are_reproducible() {
+        let first = run_deterministic(42);
+        let second = run_deterministic(42);
+
+        assert_eq!(first.score, second.score);
+        assert!(first.solution.is_identical(&second.solution));
+    }
+
+    #[test]
+    fn test_cancel_jobs_between_runs() {
+        let cancelled = [JobIdx::new(3), JobIdx::new(7), JobIdx::new(12)];
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(300)],
+            search_threads: Threads::Multi(2),
+            insertion_threads: Threads::Single,
+            threads_sync_iterations_interval: 50,
+            deterministic: true,
+            ..SolverParams::default()
+        };
+
+        let mut alns = Alns::new(params, create_problem()).unwrap();
+        let best_score_before = alns.run().unwrap().best_solution.unwrap().score;
+
+        alns.cancel_jobs(cancelled);
+        alns.params.terminations = vec![Termination::Iterations(600)];
+        let result = alns.run().unwrap();
+
+        // The search continues from where it stopped, with a pool which didn't get worse
+        assert_eq!(
+            alns.completed_iterations
+                .load(std::sync::atomic::Ordering::Relaxed),
+            600
+        );
+        assert!(result.best_solution.unwrap().score <= best_score_before);
+        assert_jobs_cancelled(&alns, &cancelled);
+    }
+
+    #[test]
+    fn test_cancel_jobs_during_run() {
+        let cancelled = [JobIdx::new(1), JobIdx::new(20)];
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(2000)],
+            search_threads: Threads::Multi(4),
+            insertion_threads: Threads::Single,
+            threads_sync_iterations_interval: 10,
+            ..SolverParams::default()
+        };
+
+        let alns = Alns::new(params, create_problem()).unwrap();
+
+        let result = std::thread::scope(|scope| {
+            let handle = scope.spawn(|| alns.run());
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            alns.cancel_jobs(cancelled);
+            handle.join().unwrap().unwrap()
+        });
+
+        // The cancellation can arrive after the last sync when the run is fast enough
+        alns.apply_pending_cancellations();
+
+        let best_solution = alns.best_solution().unwrap();
+        assert!(result.iterations > 0);
+        assert!(best_solution.solution.unassigned_jobs().is_empty());
+        assert_jobs_cancelled(&alns, &cancelled);
+    }
+
+    #[test]
+    fn test_checkpoint_keeps_cancelled_jobs() {
+        let cancelled = [JobIdx::new(2), JobIdx::new(5)];
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(100)],
+            search_threads: Threads::Single,
+            insertion_threads: Threads::Single,
+            ..SolverParams::default()
+        };
+
+        let problem = create_problem();
+        let alns = Alns::new(params.clone(), Arc::clone(&problem)).unwrap();
+        alns.run().unwrap();
+        alns.cancel_jobs(cancelled);
+
+        let checkpoint = JsonCheckpoint::from(&alns.checkpoint());
+        assert_eq!(checkpoint.cancelled_jobs.len(), cancelled.len());
+
+        let restored = Alns::new(params, problem).unwrap();
+        restored.restore_json_checkpoint(&checkpoint).unwrap();
+        assert_jobs_cancelled(&restored, &cancelled);
+    }
 }
@@ -74,6 +74,23 @@ where
             operator.reset();
         }
     }
+
+    /// Weights keyed by the name of their strategy
+    pub fn named_weights(&self) -> FxHashMap<String, f64> {
+        self.weights
+            .iter()
+            .map(|operator| (operator.strategy.to_string(), operator.weight))
+            .collect()
+    }
+
+    /// Restores weights returned by `named_weights`, strategies missing from `weights` are left unchanged
+    pub fn set_named_weights(&mut self, weights: &FxHashMap<String, f64>) {
+        for operator in self.weights.iter_mut() {
+            if let Some(&weight) = weights.get(&operator.strategy.to_string()) {
+                operator.weight = weight;
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+
+use jiff::{SignedDuration, Timestamp};
+
+use super::score::Score;
+
+/// Best scores seen by a search thread over the last `window`, used to stop the search when the
+/// best score improves too slowly, see `Termination::Stagnation`
+pub struct BestScoreHistory {
+    window: SignedDuration,
+    samples: VecDeque<(Timestamp, Score)>,
+}
+
+impl BestScoreHistory {
+    pub fn new(window: SignedDuration) -> Self {
+        BestScoreHistory {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, timestamp: Timestamp, best_score: Score) {
+        if self
+            .samples
+            .back()
+            .is_none_or(|(_, last_score)| *last_score != best_score)
+        {
+            self.samples.push_back((timestamp, best_score));
+        }
+
+        // The newest sample older than the window is the reference of the improvement, the ones
+        // before it are not needed anymore
+        let since = timestamp - self.window;
+        while self.samples.len() > 1 && self.samples[1].0 <= since {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Relative improvement of the soft score over the window, `None` when the history doesn't
+    /// cover the whole window yet.
+    /// An improvement of the hard score, or of an objective level, counts as an infinite
+    /// improvement
+    pub fn relative_improvement(&self, now: Timestamp) -> Option<f64> {
+        let (first_timestamp, reference) = self.samples.front()?;
+        let (_, best) = self.samples.back()?;
+
+        if now.duration_since(*first_timestamp) < self.window {
+            return None;
+        }
+
+        let without_soft = |score: &Score| Score {
+            soft_score: 0.0,
+            ..*score
+        };
+        if without_soft(best) < without_soft(reference) {
+            return Some(f64::INFINITY);
+        }
+
+        let improvement = reference.soft_score - best.soft_score;
+        if reference.soft_score.abs() > f64::EPSILON {
+            Some(improvement / reference.soft_score.abs())
+        } else {
+            Some(improvement)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::{SignedDuration, Timestamp};
+
+    use crate::solver::score::Score;
+
+    use super::BestScoreHistory;
+
+    #[test]
+    fn test_relative_improvement_over_window() {
+        let start = Timestamp::UNIX_EPOCH;
+        let at = |seconds: i64| start + SignedDuration::from_secs(seconds);
+
+        let mut history = BestScoreHistory::new(SignedDuration::from_secs(60));
+
+        history.record(at(0), Score::soft(1000.0));
+        assert_eq!(history.relative_improvement(at(30)), None);
+
+        history.record(at(30), Score::soft(900.0));
+        history.record(at(70), Score::soft(899.0));
+
+        // The score at 10s was 1000, it is 899 now
+        assert_eq!(history.relative_improvement(at(70)), Some(0.101));
+
+        // At 100s, the score at 40s was 900
+        history.record(at(100), Score::soft(899.0));
+        let improvement = history.relative_improvement(at(100)).unwrap();
+        assert!((improvement - 1.0 / 900.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hard_score_improvement() {
+        let start = Timestamp::UNIX_EPOCH;
+        let mut history = BestScoreHistory::new(SignedDuration::from_secs(10));
+
+        history.record(start, Score::new(1.0, 100.0));
+        history.record(
+            start + SignedDuration::from_secs(20),
+            Score::new(0.0, 200.0),
+        );
+
+        assert_eq!(
+            history.relative_improvement(start + SignedDuration::from_secs(20)),
+            Some(f64::INFINITY)
+        );
+    }
+}
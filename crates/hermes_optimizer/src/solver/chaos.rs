@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Failures injected in the search threads when the `chaos` feature is enabled.
+///
+/// Only meant for testing that a run always reaches a terminal state, whatever happens to its threads.
+#[derive(Clone, Debug, Default)]
+pub struct ChaosParams {
+    /// Probability for a search thread to panic after an iteration
+    pub panic_probability: f64,
+
+    /// Probability for an iteration to be slowed down by `slow_iteration_duration`
+    pub slow_iteration_probability: f64,
+    pub slow_iteration_duration: Duration,
+
+    /// Probability for a search thread to stop the solver after an iteration, racing with the other threads
+    pub stop_probability: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ChaosEvent {
+    Panic,
+    SlowIteration(Duration),
+    Stop,
+}
+
+impl ChaosParams {
+    pub fn next_event(&self, rng: &mut impl Rng) -> Option<ChaosEvent> {
+        if self.panic_probability > 0.0 && rng.random_bool(self.panic_probability) {
+            Some(ChaosEvent::Panic)
+        } else if self.stop_probability > 0.0 && rng.random_bool(self.stop_probability) {
+            Some(ChaosEvent::Stop)
+        } else if self.slow_iteration_probability > 0.0
+            && rng.random_bool(self.slow_iteration_probability)
+        {
+            Some(ChaosEvent::SlowIteration(self.slow_iteration_duration))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::{
+        problem::vehicle::VehicleBuilder,
+        solver::{
+            solver::{Solver, SolverStatus},
+            solver_params::{SolverParams, Termination, Threads},
+        },
+        test_utils,
+    };
+
+    use super::ChaosParams;
+
+    fn create_solver(chaos: ChaosParams) -> Arc<Solver> {
+        let locations = test_utils::create_location_grid(5, 5);
+        let services = test_utils::create_basic_services((1..25).collect());
+
+        let vehicles = (0..3)
+            .map(|index| {
+                let mut builder = VehicleBuilder::default();
+                builder.set_depot_location_id(0);
+                builder.set_vehicle_id(index.to_string());
+                builder.set_profile_id(0);
+                builder.build()
+            })
+            .collect();
+
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        let params = SolverParams {
+            terminations: vec![Termination::Iterations(2000)],
+            search_threads: Threads::Multi(4),
+            insertion_threads: Threads::Single,
+            threads_sync_iterations_interval: 10,
+            retry_on_failure: true,
+            chaos,
+            ..SolverParams::default()
+        };
+
+        Arc::new(Solver::new(problem, params).unwrap())
+    }
+
+    fn assert_terminal(solver: &Solver) {
+        assert!(matches!(
+            solver.status(),
+            SolverStatus::Completed | SolverStatus::Error
+        ));
+    }
+
+    #[test]
+    fn test_worker_panics_do_not_hang() {
+        let solver = create_solver(ChaosParams {
+            panic_probability: 0.01,
+            ..ChaosParams::default()
+        });
+
+        let result = solver.solve();
+
+        assert!(result.is_err());
+        assert!(matches!(solver.status(), SolverStatus::Error));
+    }
+
+    #[test]
+    fn test_failed_search_is_retried_once() {
+        let solver = create_solver(ChaosParams {
+            panic_probability: 0.01,
+            ..ChaosParams::default()
+        });
+
+        assert!(solver.solve().is_err());
+        let failures = solver.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].attempt, 1);
+        assert!(failures[0].reason.contains("Chaos"));
+
+        let retry = solver.retry().expect("Failed search should be retried");
+        assert_eq!(retry.attempt(), 2);
+        assert!(matches!(retry.status(), SolverStatus::Pending));
+        assert_eq!(retry.failures().len(), 1);
+
+        // The retry keeps the chaos, it fails too and is not retried again
+        assert!(retry.solve().is_err());
+        assert_eq!(retry.failures().len(), 2);
+        assert!(retry.retry().is_none());
+    }
+
+    #[test]
+    fn test_slow_iterations_and_stop_races() {
+        let solver = create_solver(ChaosParams {
+            slow_iteration_probability: 0.05,
+            slow_iteration_duration: Duration::from_millis(5),
+            stop_probability: 0.001,
+            ..ChaosParams::default()
+        });
+
+        let _ = solver.solve();
+        assert_terminal(&solver);
+    }
+
+    #[test]
+    fn test_external_stop_races_with_termination() {
+        for _ in 0..5 {
+            let solver = create_solver(ChaosParams {
+                slow_iteration_probability: 0.1,
+                slow_iteration_duration: Duration::from_millis(1),
+                ..ChaosParams::default()
+            });
+
+            let handle = std::thread::spawn({
+                let solver = Arc::clone(&solver);
+                move || solver.solve()
+            });
+
+            std::thread::sleep(Duration::from_millis(20));
+            solver.stop();
+
+            let _ = handle.join().expect("Solver thread should not panic");
+            assert_terminal(&solver);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fxhash::FxHashMap;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::{constraints::constraint::Constraint, score::ScoreAnalysis};
+
+#[derive(Default)]
+struct SoftScoreTotal {
+    total: f64,
+    samples: usize,
+}
+
+/// Tracks over a run how often each constraint rejected a candidate insertion and the soft score it
+/// added to the evaluated solutions, shared by the search and insertion threads
+pub struct ConstraintStatistics {
+    names: Vec<&'static str>,
+    /// Indexed like the constraints of the search
+    rejections: Vec<AtomicUsize>,
+    soft_scores: Mutex<FxHashMap<&'static str, SoftScoreTotal>>,
+}
+
+impl ConstraintStatistics {
+    pub fn new(constraints: &[Constraint]) -> Self {
+        ConstraintStatistics {
+            names: constraints
+                .iter()
+                .map(|constraint| constraint.constraint_name())
+                .collect(),
+            rejections: constraints.iter().map(|_| AtomicUsize::new(0)).collect(),
+            soft_scores: Mutex::new(FxHashMap::default()),
+        }
+    }
+
+    /// Called with the index of a hard constraint that made an insertion infeasible
+    pub fn record_rejection(&self, constraint_index: usize) {
+        if let Some(rejections) = self.rejections.get(constraint_index) {
+            rejections.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_score_analysis(&self, score_analysis: &ScoreAnalysis) {
+        let mut soft_scores = self.soft_scores.lock();
+        for (name, score) in &score_analysis.scores {
+            let total = soft_scores.entry(*name).or_default();
+            total.total += score.soft_score;
+            total.samples += 1;
+        }
+    }
+
+    /// Constraints ranked by rejected insertions, then by the mean soft score they added
+    pub fn binding_constraints(&self) -> Vec<BindingConstraint> {
+        let soft_scores = self.soft_scores.lock();
+
+        let mut binding_constraints: Vec<BindingConstraint> = self
+            .names
+            .iter()
+            .zip(&self.rejections)
+            .map(|(name, rejections)| BindingConstraint {
+                constraint: name.to_string(),
+                rejected_insertions: rejections.load(Ordering::Relaxed),
+                mean_soft_score: soft_scores
+                    .get(name)
+                    .filter(|total| total.samples > 0)
+                    .map(|total| total.total / total.samples as f64)
+                    .unwrap_or(0.0),
+            })
+            .collect();
+
+        binding_constraints.sort_by(|a, b| {
+            b.rejected_insertions
+                .cmp(&a.rejected_insertions)
+                .then(b.mean_soft_score.total_cmp(&a.mean_soft_score))
+        });
+
+        binding_constraints
+    }
+}
+
+#[derive(Serialize, Clone, Debug, JsonSchema)]
+pub struct BindingConstraint {
+    pub constraint: String,
+    /// Candidate insertions made infeasible by this constraint, always 0 for soft constraints
+    pub rejected_insertions: usize,
+    /// Mean soft score of this constraint in the solutions evaluated by the search
+    pub mean_soft_score: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use fxhash::FxHashMap;
+
+    use crate::solver::{
+        constraints::{
+            capacity_constraint::CapacityConstraint, constraint::Constraint,
+            global_constraint::GlobalConstraintType, route_constraint::RouteConstraintType,
+            transport_cost_constraint::TransportCostConstraint,
+        },
+        score::{Score, ScoreAnalysis},
+    };
+
+    use super::ConstraintStatistics;
+
+    #[test]
+    fn test_binding_constraints_ranking() {
+        let constraints = vec![
+            Constraint::Global(GlobalConstraintType::TransportCost(TransportCostConstraint)),
+            Constraint::Route(RouteConstraintType::Capacity(CapacityConstraint::default())),
+        ];
+        let statistics = ConstraintStatistics::new(&constraints);
+
+        statistics.record_rejection(1);
+        statistics.record_rejection(1);
+
+        let mut scores = FxHashMap::default();
+        scores.insert(constraints[0].constraint_name(), Score::soft(100.0));
+        statistics.record_score_analysis(&ScoreAnalysis { scores });
+
+        let mut scores = FxHashMap::default();
+        scores.insert(constraints[0].constraint_name(), Score::soft(50.0));
+        statistics.record_score_analysis(&ScoreAnalysis { scores });
+
+        let binding_constraints = statistics.binding_constraints();
+
+        assert_eq!(
+            binding_constraints[0].constraint,
+            constraints[1].constraint_name()
+        );
+        assert_eq!(binding_constraints[0].rejected_insertions, 2);
+        assert_eq!(
+            binding_constraints[1].constraint,
+            constraints[0].constraint_name()
+        );
+        assert_eq!(binding_constraints[1].mean_soft_score, 75.0);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     problem::vehicle_routing_problem::VehicleRoutingProblem,
     solver::{
-        constraints::skill_constraint::SkillConstraint,
+        constraints::{skill_constraint::SkillConstraint, tag_constraint::TagConstraint},
         insertion_context::InsertionContext,
         score::Score,
         score_level::ScoreLevel,
@@ -9,7 +9,11 @@ use crate::{
     },
 };
 
-use super::time_window_constraint::TimeWindowConstraint;
+use super::{
+    ride_duration_constraint::RideDurationConstraint, territory_constraint::TerritoryConstraint,
+    time_window_constraint::TimeWindowConstraint,
+    time_window_preference_constraint::TimeWindowPreferenceConstraint,
+};
 
 pub trait ActivityConstraint {
     fn score_level(&self) -> ScoreLevel;
@@ -26,6 +30,10 @@ pub trait ActivityConstraint {
 pub enum ActivityConstraintType {
     TimeWindow(TimeWindowConstraint),
     Skill(SkillConstraint),
+    Tag(TagConstraint),
+    Territory(TerritoryConstraint),
+    TimeWindowPreference(TimeWindowPreferenceConstraint),
+    RideDuration(RideDurationConstraint),
 }
 
 impl ActivityConstraintType {
@@ -33,6 +41,10 @@ impl ActivityConstraintType {
         match self {
             Self::TimeWindow(_) => "time_window",
             Self::Skill(_) => "skill",
+            Self::Tag(_) => "tag",
+            Self::Territory(_) => "territory",
+            Self::TimeWindowPreference(_) => "time_window_preference",
+            Self::RideDuration(_) => "ride_duration",
         }
     }
 }
@@ -42,12 +54,20 @@ impl ActivityConstraint for ActivityConstraintType {
         match self {
             Self::TimeWindow(constraint) => constraint.score_level(),
             Self::Skill(constraint) => constraint.score_level(),
+            Self::Tag(constraint) => constraint.score_level(),
+            Self::Territory(constraint) => constraint.score_level(),
+            Self::TimeWindowPreference(constraint) => constraint.score_level(),
+            Self::RideDuration(constraint) => constraint.score_level(),
         }
     }
     fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
         match self {
             Self::TimeWindow(constraint) => constraint.compute_insertion_score(context),
             Self::Skill(constraint) => constraint.compute_insertion_score(context),
+            Self::Tag(constraint) => constraint.compute_insertion_score(context),
+            Self::Territory(constraint) => constraint.compute_insertion_score(context),
+            Self::TimeWindowPreference(constraint) => constraint.compute_insertion_score(context),
+            Self::RideDuration(constraint) => constraint.compute_insertion_score(context),
         }
     }
 
@@ -60,6 +80,12 @@ impl ActivityConstraint for ActivityConstraintType {
         match self {
             Self::TimeWindow(constraint) => constraint.compute_score(problem, route, activity),
             Self::Skill(constraint) => constraint.compute_score(problem, route, activity),
+            Self::Tag(constraint) => constraint.compute_score(problem, route, activity),
+            Self::Territory(constraint) => constraint.compute_score(problem, route, activity),
+            Self::TimeWindowPreference(constraint) => {
+                constraint.compute_score(problem, route, activity)
+            }
+            Self::RideDuration(constraint) => constraint.compute_score(problem, route, activity),
         }
     }
 }
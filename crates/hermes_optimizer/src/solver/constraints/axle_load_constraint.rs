@@ -0,0 +1,163 @@
+use crate::{
+    problem::{
+        job::ActivityId, kilograms::Kilograms, vehicle_routing_problem::VehicleRoutingProblem,
+    },
+    solver::{
+        insertion::Insertion, insertion_context::InsertionContext, score::Score,
+        score_level::ScoreLevel, solution::route::WorkingSolutionRoute,
+    },
+};
+
+use super::route_constraint::RouteConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Hard;
+
+/// The axles of the trucks with axle limits must not be overloaded when leaving a stop, the
+/// score is the weight over the limits in kg summed over the stops, see `AxleLoads`
+#[derive(Clone)]
+pub struct AxleLoadConstraint;
+
+impl RouteConstraint for AxleLoadConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        if route.vehicle(problem).axle_loads().is_none() || route.is_empty() {
+            return Score::zero();
+        }
+
+        Score::of(
+            SCORE_LEVEL,
+            route
+                .axle_overload(problem, std::iter::empty(), route.len(), route.len())
+                .value(),
+        )
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        let route = context.route();
+        if route.vehicle(problem).axle_loads().is_none() {
+            return Score::zero();
+        }
+
+        let new_overload = match context.insertion {
+            Insertion::Service(insertion) => route.axle_overload(
+                problem,
+                std::iter::once(ActivityId::Service(insertion.job_index)),
+                insertion.position,
+                insertion.position,
+            ),
+            Insertion::Shipment(insertion) => route.axle_overload(
+                problem,
+                insertion.inserted_activity_ids(route),
+                insertion.pickup_position,
+                insertion.delivery_position,
+            ),
+        };
+        let current_overload = if route.is_empty() {
+            Kilograms::ZERO
+        } else {
+            route.axle_overload(problem, std::iter::empty(), route.len(), route.len())
+        };
+
+        Score::of(SCORE_LEVEL, (new_overload - current_overload).value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            amount::Amount,
+            axle_loads::{Axle, AxleLoads},
+            kilograms::Kilograms,
+            service::ServiceBuilder,
+            vehicle::VehicleBuilder,
+            vehicle_routing_problem::VehicleRoutingProblem,
+        },
+        solver::{
+            constraints::route_constraint::RouteConstraint, score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::AxleLoadConstraint;
+
+    fn create_problem() -> VehicleRoutingProblem {
+        let locations = test_utils::create_location_grid(5, 5);
+
+        // Weight and loading meters of the deliveries, short and heavy pallets then long pallets
+        let services = [[4000.0, 0.8], [1000.0, 2.4]]
+            .into_iter()
+            .enumerate()
+            .map(|(index, demand)| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_external_id(index.to_string());
+                builder.set_location_id(index + 1);
+                builder.set_demand(Amount::from_vec(demand.to_vec()));
+                builder.build()
+            })
+            .collect();
+
+        let mut builder = VehicleBuilder::default();
+        builder.set_vehicle_id(String::from("truck"));
+        builder.set_profile_id(0);
+        builder.set_depot_location_id(0);
+        builder.set_capacity(Amount::from_vec(vec![10000.0, 13.6]));
+        builder.set_axle_loads(AxleLoads::new(
+            0,
+            1,
+            4.0,
+            1.0,
+            Axle {
+                empty_weight: Kilograms::new(5000.0),
+                maximum_weight: Kilograms::new(7100.0),
+            },
+            Axle {
+                empty_weight: Kilograms::new(3000.0),
+                maximum_weight: Kilograms::new(11500.0),
+            },
+        ));
+
+        test_utils::create_test_problem(locations, services, vec![builder.build()])
+    }
+
+    #[test]
+    fn test_axle_overload_per_stop() {
+        let problem = Arc::new(create_problem());
+
+        // 5000 kg over 3.2 loading meters leaving the depot put 6750 kg on the front axle
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1],
+            }],
+        );
+        assert_eq!(
+            AxleLoadConstraint.compute_score(&problem, solution.route(RouteIdx::new(0))),
+            Score::zero()
+        );
+
+        // Once the long pallets are delivered first, the heavy pallets left at the front wall put
+        // 7600 kg on the front axle
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![1, 0],
+            }],
+        );
+        let score = AxleLoadConstraint.compute_score(&problem, solution.route(RouteIdx::new(0)));
+        assert!((score.hard_score - 500.0).abs() < 1e-6);
+    }
+}
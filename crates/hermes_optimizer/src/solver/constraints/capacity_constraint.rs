@@ -50,10 +50,10 @@ impl RouteConstraint for CapacityConstraint {
         let mut score = Score::zero();
 
         for load in route.current_loads() {
-            if !is_capacity_satisfied(vehicle.capacity(), &load) {
+            if !is_capacity_satisfied(vehicle.capacity(), &load, problem.tolerances().capacity) {
                 score += Score::of(
                     self.score_level,
-                    over_capacity_demand(vehicle.capacity(), &load),
+                    over_capacity_demand(vehicle.capacity(), &load, problem.tolerances().capacity),
                 );
             }
         }
@@ -82,12 +82,14 @@ impl RouteConstraint for CapacityConstraint {
                         if !is_capacity_satisfied(
                             vehicle.capacity(),
                             &(service.demand() + route.bwd_load_peak(insertion.position)),
+                            problem.tolerances().capacity,
                         ) {
                             score += Score::of(
                                 self.score_level,
                                 over_capacity_demand(
                                     vehicle.capacity(),
                                     &(service.demand() + route.bwd_load_peak(insertion.position)),
+                                    problem.tolerances().capacity,
                                 ),
                             )
                         }
@@ -96,6 +98,7 @@ impl RouteConstraint for CapacityConstraint {
                         if !is_capacity_satisfied(
                             vehicle.capacity(),
                             &(service.demand() + route.fwd_load_peak(insertion.position)),
+                            problem.tolerances().capacity,
                         ) {
                             // if !context.insert_on_failure {
                             //     return Score::hard(1.0);
@@ -106,10 +109,13 @@ impl RouteConstraint for CapacityConstraint {
                                 over_capacity_demand(
                                     vehicle.capacity(),
                                     &(service.demand() + route.fwd_load_peak(insertion.position)),
+                                    problem.tolerances().capacity,
                                 ),
                             );
                         }
                     }
+                    // Reloads and charges have no demand
+                    ServiceType::Reload | ServiceType::Charge => {}
                 }
             }
             Insertion::Shipment(insertion) => {
@@ -124,9 +130,17 @@ impl RouteConstraint for CapacityConstraint {
                     return Score::zero();
                 } else if !context.insert_on_failure {
                     return Score::hard(1.0);
-                } else {
-                    todo!("Shipment failure not implemented on insert_on_failure=true")
                 }
+
+                // Only the overload added by the shipment is scored
+                let overload = route.capacity_overload(
+                    problem,
+                    insertion.inserted_activity_ids(route),
+                    insertion.pickup_position,
+                    insertion.delivery_position,
+                ) - route.capacity_overload(problem, std::iter::empty(), 0, 0);
+
+                score += Score::of(self.score_level, overload.max(0.0));
             }
         }
 
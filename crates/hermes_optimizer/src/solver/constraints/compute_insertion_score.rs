@@ -6,6 +6,17 @@ pub fn compute_insertion_score(
     constraints: &[Constraint],
     context: &InsertionContext,
     best_score: Option<&Score>,
+) -> Score {
+    compute_insertion_score_with_rejections(constraints, context, best_score, |_| {})
+}
+
+/// Same as `compute_insertion_score`, `on_rejection` is called with the index of each hard
+/// constraint that makes the insertion infeasible
+pub fn compute_insertion_score_with_rejections(
+    constraints: &[Constraint],
+    context: &InsertionContext,
+    best_score: Option<&Score>,
+    mut on_rejection: impl FnMut(usize),
 ) -> Score {
     let mut score = Score::zero();
 
@@ -14,11 +25,17 @@ pub fn compute_insertion_score(
             .map(|best_score| !best_score.is_infeasible())
             .unwrap_or(false);
 
-    for constraint in constraints
+    for (index, constraint) in constraints
         .iter()
-        .filter(|c| c.score_level() == ScoreLevel::Hard)
+        .enumerate()
+        .filter(|(_, c)| c.score_level() == ScoreLevel::Hard)
     {
-        score += constraint.compute_insertion_score(context);
+        let c_score = constraint.compute_insertion_score(context);
+        if c_score.is_infeasible() {
+            on_rejection(index);
+        }
+
+        score += c_score;
 
         if score.is_infeasible() && skip_on_failure {
             return score;
@@ -1,8 +1,12 @@
+use std::sync::Arc;
+
 use crate::{
     problem::vehicle_routing_problem::VehicleRoutingProblem,
     solver::{
-        insertion_context::InsertionContext, score::Score, score_level::ScoreLevel,
-        solution::working_solution::WorkingSolution,
+        insertion_context::InsertionContext,
+        score::Score,
+        score_level::ScoreLevel,
+        solution::{route::WorkingSolutionRoute, working_solution::WorkingSolution},
     },
 };
 
@@ -12,11 +16,33 @@ use super::{
     route_constraint::{RouteConstraint, RouteConstraintType},
 };
 
+/// Extension point for constraints defined outside of the crate, see `ConstraintSet::add_custom`
+pub trait CustomConstraint: Send + Sync {
+    fn constraint_name(&self) -> &'static str;
+    fn score_level(&self) -> ScoreLevel;
+    fn compute_score(&self, problem: &VehicleRoutingProblem, solution: &WorkingSolution) -> Score;
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score;
+}
+
 #[derive(Clone)]
 pub enum Constraint {
     Global(GlobalConstraintType),
     Route(RouteConstraintType),
     Activity(ActivityConstraintType),
+    Custom(Arc<dyn CustomConstraint>),
+
+    /// Multiplies the scores of the inner constraint by `weight`
+    Weighted {
+        constraint: Box<Constraint>,
+        weight: f64,
+    },
+
+    /// Moves the soft scores of the inner constraint to the objective level `level`, compared
+    /// before the soft scores of the other constraints
+    Prioritized {
+        constraint: Box<Constraint>,
+        level: usize,
+    },
 }
 
 impl Constraint {
@@ -25,6 +51,9 @@ impl Constraint {
             Constraint::Global(constraint) => constraint.score_level(),
             Constraint::Route(constraint) => constraint.score_level(),
             Constraint::Activity(constraint) => constraint.score_level(),
+            Constraint::Custom(constraint) => constraint.score_level(),
+            Constraint::Weighted { constraint, .. } => constraint.score_level(),
+            Constraint::Prioritized { constraint, .. } => constraint.score_level(),
         }
     }
 
@@ -33,6 +62,13 @@ impl Constraint {
             Constraint::Global(constraint) => constraint.compute_insertion_score(context),
             Constraint::Route(constraint) => constraint.compute_insertion_score(context),
             Constraint::Activity(constraint) => constraint.compute_insertion_score(context),
+            Constraint::Custom(constraint) => constraint.compute_insertion_score(context),
+            Constraint::Weighted { constraint, weight } => {
+                constraint.compute_insertion_score(context) * *weight
+            }
+            Constraint::Prioritized { constraint, level } => constraint
+                .compute_insertion_score(context)
+                .at_objective_level(*level),
         }
     }
 
@@ -64,6 +100,43 @@ impl Constraint {
                         )
                     })
             }
+            Constraint::Custom(constraint) => constraint.compute_score(problem, solution),
+            Constraint::Weighted { constraint, weight } => {
+                constraint.compute_score(problem, solution) * *weight
+            }
+            Constraint::Prioritized { constraint, level } => constraint
+                .compute_score(problem, solution)
+                .at_objective_level(*level),
+        }
+    }
+
+    /// Score of the constraints on `route` alone. The transport cost is summed over the routes,
+    /// the other global and the custom constraints span several routes and are left out
+    pub fn compute_route_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        if route.is_empty() {
+            return Score::zero();
+        }
+
+        match self {
+            Constraint::Global(GlobalConstraintType::TransportCost(constraint)) => {
+                constraint.compute_route_score(problem, route)
+            }
+            Constraint::Global(_) | Constraint::Custom(_) => Score::zero(),
+            Constraint::Route(constraint) => constraint.compute_score(problem, route),
+            Constraint::Activity(constraint) => (0..route.len())
+                .fold(Score::zero(), |acc, index| {
+                    acc + constraint.compute_score(problem, route, &route.activity(index))
+                }),
+            Constraint::Weighted { constraint, weight } => {
+                constraint.compute_route_score(problem, route) * *weight
+            }
+            Constraint::Prioritized { constraint, level } => constraint
+                .compute_route_score(problem, route)
+                .at_objective_level(*level),
         }
     }
 
@@ -72,6 +145,9 @@ impl Constraint {
             Constraint::Global(c) => c.constraint_name(),
             Constraint::Route(c) => c.constraint_name(),
             Constraint::Activity(c) => c.constraint_name(),
+            Constraint::Custom(c) => c.constraint_name(),
+            Constraint::Weighted { constraint, .. } => constraint.constraint_name(),
+            Constraint::Prioritized { constraint, .. } => constraint.constraint_name(),
         }
     }
 }
@@ -0,0 +1,400 @@
+use std::sync::Arc;
+
+use crate::{
+    problem::constraint_overrides::{ConstraintOverrideError, ConstraintOverrides},
+    solver::score_level::ScoreLevel,
+};
+
+use super::{
+    activity_constraint::ActivityConstraintType,
+    axle_load_constraint::AxleLoadConstraint,
+    capacity_constraint::CapacityConstraint,
+    constraint::{Constraint, CustomConstraint},
+    depot_hours_constraint::DepotHoursConstraint,
+    depot_inventory_constraint::DepotInventoryConstraint,
+    energy_constraint::EnergyConstraint,
+    global_constraint::GlobalConstraintType,
+    maximum_activities_constraint::MaximumActivitiesConstraint,
+    maximum_trips_constraint::MaximumTripsConstraint,
+    maximum_working_duration_constraint::MaximumWorkingDurationConstraint,
+    minimum_utilization_constraint::MinimumUtilizationConstraint,
+    position_preference_constraint::PositionPreferenceConstraint,
+    relation_constraint::RelationConstraint,
+    ride_duration_constraint::RideDurationConstraint,
+    route_constraint::RouteConstraintType,
+    shift_constraint::ShiftConstraint,
+    skill_constraint::SkillConstraint,
+    tag_constraint::TagConstraint,
+    territory_constraint::TerritoryConstraint,
+    time_window_constraint::TimeWindowConstraint,
+    time_window_preference_constraint::TimeWindowPreferenceConstraint,
+    transport_cost_constraint::TransportCostConstraint,
+    vehicle_cost_constraint::VehicleCostConstraint,
+    waiting_duration_constraint::WaitingDurationConstraint,
+};
+
+/// Constraints used by the solver, identified by their `constraint_name`.
+///
+/// The default set contains all the built-in constraints, they can be removed, weighted or
+/// completed with custom constraints.
+#[derive(Clone)]
+pub struct ConstraintSet {
+    constraints: Vec<Constraint>,
+}
+
+impl Default for ConstraintSet {
+    fn default() -> Self {
+        ConstraintSet {
+            constraints: vec![
+                // Hard constraints
+                Constraint::Global(GlobalConstraintType::Relation(RelationConstraint)),
+                Constraint::Route(RouteConstraintType::MaximumJobs(
+                    MaximumActivitiesConstraint,
+                )),
+                Constraint::Route(RouteConstraintType::Shift(ShiftConstraint)),
+                Constraint::Route(RouteConstraintType::DepotHours(DepotHoursConstraint)),
+                Constraint::Route(RouteConstraintType::MaximumWorkingDuration(
+                    MaximumWorkingDurationConstraint,
+                )),
+                Constraint::Activity(ActivityConstraintType::TimeWindow(
+                    TimeWindowConstraint::default(),
+                )),
+                Constraint::Activity(ActivityConstraintType::RideDuration(RideDurationConstraint)),
+                Constraint::Route(RouteConstraintType::Capacity(CapacityConstraint::default())),
+                Constraint::Route(RouteConstraintType::Energy(EnergyConstraint)),
+                Constraint::Route(RouteConstraintType::AxleLoad(AxleLoadConstraint)),
+                Constraint::Route(RouteConstraintType::MaximumTrips(MaximumTripsConstraint)),
+                Constraint::Global(GlobalConstraintType::DepotInventory(
+                    DepotInventoryConstraint::default(),
+                )),
+                Constraint::Activity(ActivityConstraintType::Skill(SkillConstraint)),
+                Constraint::Activity(ActivityConstraintType::Tag(TagConstraint)),
+                Constraint::Activity(ActivityConstraintType::Territory(
+                    TerritoryConstraint::default(),
+                )),
+                // Soft constraints
+                Constraint::Global(GlobalConstraintType::TransportCost(TransportCostConstraint)),
+                Constraint::Route(RouteConstraintType::VehicleCost(VehicleCostConstraint)),
+                Constraint::Route(RouteConstraintType::WaitingDuration(
+                    WaitingDurationConstraint,
+                )),
+                Constraint::Route(RouteConstraintType::PositionPreference(
+                    PositionPreferenceConstraint,
+                )),
+                Constraint::Route(RouteConstraintType::MinimumUtilization(
+                    MinimumUtilizationConstraint::default(),
+                )),
+                Constraint::Activity(ActivityConstraintType::TimeWindowPreference(
+                    TimeWindowPreferenceConstraint,
+                )),
+            ],
+        }
+    }
+}
+
+impl std::fmt::Debug for ConstraintSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.constraints.iter().map(Constraint::constraint_name))
+            .finish()
+    }
+}
+
+impl ConstraintSet {
+    pub fn empty() -> Self {
+        ConstraintSet {
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Adds a constraint, replacing the existing constraint with the same name
+    pub fn add(&mut self, constraint: Constraint) -> &mut ConstraintSet {
+        self.remove(constraint.constraint_name());
+        self.constraints.push(constraint);
+        self
+    }
+
+    pub fn add_custom(
+        &mut self,
+        constraint: impl CustomConstraint + 'static,
+    ) -> &mut ConstraintSet {
+        self.add(Constraint::Custom(Arc::new(constraint)))
+    }
+
+    pub fn remove(&mut self, name: &str) -> &mut ConstraintSet {
+        self.constraints
+            .retain(|constraint| constraint.constraint_name() != name);
+        self
+    }
+
+    /// Multiplies the scores of the constraint by `weight`, e.g. to make waiting more expensive
+    pub fn set_weight(&mut self, name: &str, weight: f64) -> &mut ConstraintSet {
+        for constraint in self
+            .constraints
+            .iter_mut()
+            .filter(|constraint| constraint.constraint_name() == name)
+        {
+            let inner = match &*constraint {
+                Constraint::Weighted { constraint, .. } => constraint.as_ref().clone(),
+                constraint => constraint.clone(),
+            };
+
+            *constraint = Constraint::Weighted {
+                constraint: Box::new(inner),
+                weight,
+            };
+        }
+
+        self
+    }
+
+    /// Compares the soft scores of the constraint at the objective level `level`, before the soft
+    /// scores of the other constraints, see `Objective::Lexicographic`
+    pub fn set_objective_level(&mut self, name: &str, level: usize) -> &mut ConstraintSet {
+        for constraint in self
+            .constraints
+            .iter_mut()
+            .filter(|constraint| constraint.constraint_name() == name)
+        {
+            let inner = match &*constraint {
+                Constraint::Prioritized { constraint, .. } => constraint.as_ref().clone(),
+                constraint => constraint.clone(),
+            };
+
+            *constraint = Constraint::Prioritized {
+                constraint: Box::new(inner),
+                level,
+            };
+        }
+
+        self
+    }
+
+    /// Checks that the overrides only name constraints of the set, and only switch off the hard
+    /// ones when explicitly allowed
+    pub fn validate_overrides(
+        &self,
+        overrides: &ConstraintOverrides,
+    ) -> Result<(), ConstraintOverrideError> {
+        if overrides
+            .territory_penalty
+            .is_some_and(|penalty| !penalty.is_finite() || penalty < 0.0)
+        {
+            return Err(ConstraintOverrideError::InvalidTerritoryPenalty);
+        }
+
+        for name in overrides.enabled.keys() {
+            if !self.contains(name) {
+                return Err(ConstraintOverrideError::UnknownConstraint(name.clone()));
+            }
+        }
+
+        for name in overrides.disabled() {
+            let is_hard = self.constraints.iter().any(|constraint| {
+                constraint.constraint_name() == name && constraint.score_level() == ScoreLevel::Hard
+            });
+
+            if is_hard && !overrides.allow_disabling_hard_constraints {
+                return Err(ConstraintOverrideError::HardConstraintDisabled(
+                    name.to_owned(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the constraints switched off by the overrides and softens the territory constraint,
+    /// see `validate_overrides`
+    pub fn apply_overrides(&mut self, overrides: &ConstraintOverrides) -> &mut ConstraintSet {
+        for name in overrides.disabled() {
+            self.remove(name);
+        }
+
+        if let Some(penalty) = overrides.territory_penalty {
+            for constraint in &mut self.constraints {
+                if let Constraint::Activity(ActivityConstraintType::Territory(territory)) =
+                    constraint
+                {
+                    *territory = TerritoryConstraint::soft(penalty);
+                }
+            }
+        }
+
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.constraints
+            .iter()
+            .any(|constraint| constraint.constraint_name() == name)
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::vehicle_routing_problem::VehicleRoutingProblem,
+        solver::{
+            insertion_context::InsertionContext, score::Score, score_level::ScoreLevel,
+            solution::working_solution::WorkingSolution,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use crate::problem::constraint_overrides::{ConstraintOverrideError, ConstraintOverrides};
+
+    use super::{Constraint, ConstraintSet, CustomConstraint};
+
+    struct RouteCountConstraint;
+
+    impl CustomConstraint for RouteCountConstraint {
+        fn constraint_name(&self) -> &'static str {
+            "route_count"
+        }
+
+        fn score_level(&self) -> ScoreLevel {
+            ScoreLevel::Soft
+        }
+
+        fn compute_score(
+            &self,
+            _problem: &VehicleRoutingProblem,
+            solution: &WorkingSolution,
+        ) -> Score {
+            Score::soft(solution.non_empty_routes_iter().count() as f64)
+        }
+
+        fn compute_insertion_score(&self, _context: &InsertionContext) -> Score {
+            Score::zero()
+        }
+    }
+
+    fn create_solution() -> WorkingSolution {
+        let locations = test_utils::create_location_grid(5, 5);
+        let services = test_utils::create_basic_services(vec![1, 2, 3]);
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        test_utils::create_test_working_solution(
+            Arc::new(problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1, 2],
+            }],
+        )
+    }
+
+    #[test]
+    fn test_set_weight() {
+        let solution = create_solution();
+
+        let mut constraints = ConstraintSet::empty();
+        constraints.add_custom(RouteCountConstraint);
+        let (score, _) = solution.compute_solution_score(constraints.constraints());
+        assert_eq!(score, Score::soft(1.0));
+
+        constraints.set_weight("route_count", 3.0);
+        let (score, analysis) = solution.compute_solution_score(constraints.constraints());
+        assert_eq!(score, Score::soft(3.0));
+        assert_eq!(analysis.scores["route_count"], Score::soft(3.0));
+
+        // Weights are not compounded
+        constraints.set_weight("route_count", 2.0);
+        let (score, _) = solution.compute_solution_score(constraints.constraints());
+        assert_eq!(score, Score::soft(2.0));
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut constraints = ConstraintSet::default();
+        assert!(constraints.contains("waiting_duration"));
+
+        constraints.remove("waiting_duration");
+        assert!(!constraints.contains("waiting_duration"));
+
+        constraints.add_custom(RouteCountConstraint);
+        constraints.add_custom(RouteCountConstraint);
+        assert_eq!(
+            constraints
+                .constraints()
+                .iter()
+                .filter(|constraint| constraint.constraint_name() == "route_count")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let constraints = ConstraintSet::default();
+
+        let mut overrides = ConstraintOverrides::default();
+        overrides
+            .enabled
+            .insert(String::from("waiting_duration"), false);
+        overrides.enabled.insert(String::from("capacity"), true);
+        assert_eq!(constraints.validate_overrides(&overrides), Ok(()));
+
+        let mut applied = constraints.clone();
+        applied.apply_overrides(&overrides);
+        assert!(!applied.contains("waiting_duration"));
+        assert!(applied.contains("capacity"));
+
+        overrides
+            .enabled
+            .insert(String::from("maximum_activities"), false);
+        assert_eq!(
+            constraints.validate_overrides(&overrides),
+            Err(ConstraintOverrideError::HardConstraintDisabled(
+                String::from("maximum_activities")
+            ))
+        );
+
+        overrides.allow_disabling_hard_constraints = true;
+        assert_eq!(constraints.validate_overrides(&overrides), Ok(()));
+
+        overrides.enabled.insert(String::from("unknown"), false);
+        assert_eq!(
+            constraints.validate_overrides(&overrides),
+            Err(ConstraintOverrideError::UnknownConstraint(String::from(
+                "unknown"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_territory_penalty_override() {
+        let constraints = ConstraintSet::default();
+        let territory_level = |constraints: &ConstraintSet| {
+            constraints
+                .constraints()
+                .iter()
+                .find(|constraint| constraint.constraint_name() == "territory")
+                .map(Constraint::score_level)
+        };
+
+        let mut overrides = ConstraintOverrides {
+            territory_penalty: Some(100.0),
+            ..ConstraintOverrides::default()
+        };
+        assert_eq!(constraints.validate_overrides(&overrides), Ok(()));
+
+        let mut applied = constraints.clone();
+        applied.apply_overrides(&overrides);
+        assert!(territory_level(&constraints) == Some(ScoreLevel::Hard));
+        assert!(territory_level(&applied) == Some(ScoreLevel::Soft));
+
+        overrides.territory_penalty = Some(-1.0);
+        assert_eq!(
+            constraints.validate_overrides(&overrides),
+            Err(ConstraintOverrideError::InvalidTerritoryPenalty)
+        );
+    }
+}
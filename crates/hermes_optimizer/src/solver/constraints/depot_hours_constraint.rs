@@ -0,0 +1,194 @@
+use jiff::{SignedDuration, Timestamp};
+
+use crate::{
+    problem::{vehicle::Vehicle, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        insertion_context::InsertionContext, score::Score, score_level::ScoreLevel,
+        solution::route::WorkingSolutionRoute,
+    },
+};
+
+use super::route_constraint::RouteConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Hard;
+
+/// Routes leave their depot during its opening hours and loading windows, and come back before it
+/// closes, see `VehicleRoutingProblem::depots`
+#[derive(Clone)]
+pub struct DepotHoursConstraint;
+
+impl DepotHoursConstraint {
+    fn violation(
+        problem: &VehicleRoutingProblem,
+        vehicle: &Vehicle,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> SignedDuration {
+        let Some(depot) = vehicle
+            .depot_location_id()
+            .and_then(|location_id| problem.depot(location_id))
+        else {
+            return SignedDuration::ZERO;
+        };
+
+        let mut violation = depot.departure_violation(start);
+        if vehicle.should_return_to_depot() {
+            violation += depot.arrival_violation(end);
+        }
+
+        violation
+    }
+}
+
+impl RouteConstraint for DepotHoursConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        if !problem.has_depots() {
+            return Score::zero();
+        }
+
+        let violation = Self::violation(
+            problem,
+            route.vehicle(problem),
+            route.start(problem),
+            route.end(problem),
+        );
+
+        Score::of(self.score_level(), violation.as_secs_f64())
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        if !problem.has_depots() {
+            return Score::zero();
+        }
+
+        let route = context.route();
+        let vehicle = route.vehicle(problem);
+
+        let new_violation = Self::violation(
+            problem,
+            vehicle,
+            context.compute_vehicle_start(),
+            context.compute_vehicle_end(),
+        );
+
+        let current_violation = if route.is_empty() {
+            SignedDuration::ZERO
+        } else {
+            Self::violation(problem, vehicle, route.start(problem), route.end(problem))
+        };
+
+        Score::of(
+            self.score_level(),
+            (new_violation - current_violation).as_secs_f64(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jiff::Timestamp;
+
+    use crate::{
+        problem::{
+            depot::Depot,
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::JobIdx,
+            location::LocationIdx,
+            service::ServiceBuilder,
+            time_window::TimeWindow,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle::VehicleBuilder,
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            constraints::route_constraint::RouteConstraint,
+            insertion::{Insertion, ServiceInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::DepotHoursConstraint;
+
+    #[test]
+    fn test_depot_hours() {
+        let locations = test_utils::create_location_grid(1, 10);
+        let services = (1..4)
+            .map(|location_id| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(location_id);
+                builder.set_external_id(location_id.to_string());
+                builder.build()
+            })
+            .collect();
+
+        let opens_at: Timestamp = "2025-06-10T08:00:00Z".parse().unwrap();
+
+        let mut vehicle = VehicleBuilder::default();
+        vehicle.set_depot_location_id(0);
+        vehicle.set_vehicle_id("0".to_owned());
+        vehicle.set_profile_id(0);
+        vehicle.set_return(true);
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_services(services);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vec![vehicle.build()]));
+        // Only open for the departures
+        builder.set_depots(vec![Depot::new(
+            LocationIdx::new(0),
+            vec![TimeWindow::new(Some(opens_at), Some(opens_at))],
+            vec![],
+        )]);
+        let problem = Arc::new(builder.build().unwrap());
+
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1],
+            }],
+        );
+
+        let route = solution.route(RouteIdx::new(0));
+
+        // Routes start when the depot opens without any vehicle shift
+        assert_eq!(route.start(&problem), opens_at);
+
+        let constraint = DepotHoursConstraint;
+        let overtime = route.end(&problem).duration_since(opens_at);
+        assert_eq!(
+            constraint.compute_score(&problem, route),
+            Score::hard(overtime.as_secs_f64())
+        );
+
+        // Going further from the depot comes back later
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(2),
+            position: 2,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert!(constraint.compute_insertion_score(&context) > Score::zero());
+    }
+}
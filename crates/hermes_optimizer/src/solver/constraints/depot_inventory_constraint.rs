@@ -0,0 +1,221 @@
+use crate::{
+    problem::{
+        amount::AmountExpression,
+        capacity::{is_capacity_satisfied, over_capacity_demand},
+        service::ServiceType,
+    },
+    solver::{
+        insertion::Insertion, insertion_context::InsertionContext, score::Score,
+        score_level::ScoreLevel, solution::working_solution::WorkingSolution,
+    },
+};
+
+use super::global_constraint::GlobalConstraint;
+
+/// Deliveries loaded across all the routes starting from a depot can't exceed its stock,
+/// see `VehicleRoutingProblem::depot_inventories`
+#[derive(Clone)]
+pub struct DepotInventoryConstraint {
+    score_level: ScoreLevel,
+}
+
+impl Default for DepotInventoryConstraint {
+    fn default() -> Self {
+        DepotInventoryConstraint {
+            score_level: ScoreLevel::Hard,
+        }
+    }
+}
+
+impl DepotInventoryConstraint {
+    pub fn new(score_level: ScoreLevel) -> Self {
+        DepotInventoryConstraint { score_level }
+    }
+}
+
+impl GlobalConstraint for DepotInventoryConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        self.score_level
+    }
+
+    fn compute_score(&self, solution: &WorkingSolution) -> Score {
+        let problem = solution.problem();
+        let mut score = Score::zero();
+
+        for inventory in problem.depot_inventories() {
+            let usage = solution.depot_inventory_usage(inventory.location_id());
+            if !is_capacity_satisfied(inventory.stock(), &usage, problem.tolerances().capacity) {
+                score += Score::of(
+                    self.score_level,
+                    over_capacity_demand(inventory.stock(), &usage, problem.tolerances().capacity),
+                );
+            }
+        }
+
+        score
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        if !problem.has_depot_inventories() {
+            return Score::zero();
+        }
+
+        // Only deliveries are loaded at the depot, shipments are loaded at their pickup
+        let Insertion::Service(insertion) = context.insertion else {
+            return Score::zero();
+        };
+
+        let service = problem.service(insertion.job_index);
+        if service.service_type() != ServiceType::Delivery || service.demand().is_empty() {
+            return Score::zero();
+        }
+
+        let Some(inventory) = context
+            .route()
+            .vehicle(problem)
+            .depot_location_id()
+            .and_then(|location_id| problem.depot_inventory(location_id))
+        else {
+            return Score::zero();
+        };
+
+        let usage = context
+            .solution
+            .depot_inventory_usage(inventory.location_id());
+        let new_usage = &usage + service.demand();
+
+        if is_capacity_satisfied(inventory.stock(), &new_usage, problem.tolerances().capacity) {
+            return Score::zero();
+        }
+
+        // Only the stock missing because of this insertion is penalized
+        Score::of(
+            self.score_level,
+            over_capacity_demand(inventory.stock(), &new_usage, problem.tolerances().capacity)
+                - over_capacity_demand(inventory.stock(), &usage, problem.tolerances().capacity),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            capacity::Capacity,
+            depot_inventory::DepotInventory,
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::JobIdx,
+            location::LocationIdx,
+            service::ServiceBuilder,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            constraints::global_constraint::GlobalConstraint,
+            insertion::{Insertion, ServiceInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::DepotInventoryConstraint;
+
+    #[test]
+    fn test_depot_inventory() {
+        let locations = test_utils::create_location_grid(1, 10);
+        let services = (1..6)
+            .map(|location_id| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(location_id);
+                builder.set_external_id(location_id.to_string());
+                builder.set_demand(Capacity::from_vec(vec![1.0]));
+                builder.build()
+            })
+            .collect();
+
+        // The first two vehicles share the stock of depot 0, the last one loads at depot 9
+        let vehicles = test_utils::create_basic_vehicles(vec![0, 0, 9]);
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_services(services);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vehicles));
+        builder.set_depot_inventories(vec![DepotInventory::new(
+            LocationIdx::new(0),
+            Capacity::from_vec(vec![3.0]),
+        )]);
+        let problem = Arc::new(builder.build().unwrap());
+
+        let mut solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![
+                TestRoute {
+                    vehicle_id: 0,
+                    service_ids: vec![0, 1],
+                },
+                TestRoute {
+                    vehicle_id: 1,
+                    service_ids: vec![2, 3],
+                },
+            ],
+        );
+
+        let constraint = DepotInventoryConstraint::default();
+        assert_eq!(
+            solution.depot_inventory_usage(LocationIdx::new(0)),
+            Capacity::from_vec(vec![4.0])
+        );
+        assert_eq!(constraint.compute_score(&solution), Score::hard(1.0));
+
+        // Loading one more delivery at depot 0 is missing one more unit of stock
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(4),
+            position: 0,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(
+            constraint.compute_insertion_score(&context),
+            Score::hard(1.0)
+        );
+
+        // Depot 9 has no inventory
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(2),
+            job_index: JobIdx::new(4),
+            position: 0,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(constraint.compute_insertion_score(&context), Score::zero());
+
+        // The usage follows the changes of the routes
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(1),
+            job_index: JobIdx::new(4),
+            position: 0,
+        });
+        solution.insert(&insertion);
+        assert_eq!(
+            solution.depot_inventory_usage(LocationIdx::new(0)),
+            Capacity::from_vec(vec![5.0])
+        );
+
+        solution.remove_route(RouteIdx::new(0));
+        assert_eq!(
+            solution.depot_inventory_usage(LocationIdx::new(0)),
+            Capacity::from_vec(vec![3.0])
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    problem::{job::ActivityId, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        insertion::Insertion, insertion_context::InsertionContext, score::Score,
+        score_level::ScoreLevel, solution::route::WorkingSolutionRoute,
+    },
+};
+
+use super::route_constraint::RouteConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Hard;
+
+/// The battery of electric vehicles must last between two charges, the score is the energy over
+/// the battery capacity in kWh
+#[derive(Clone)]
+pub struct EnergyConstraint;
+
+impl RouteConstraint for EnergyConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        if route.vehicle(problem).battery().is_none() || route.is_empty() {
+            return Score::zero();
+        }
+
+        Score::of(
+            SCORE_LEVEL,
+            route.energy_excess(problem, std::iter::empty(), route.len(), route.len()),
+        )
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        let route = context.route();
+        if route.vehicle(problem).battery().is_none() {
+            return Score::zero();
+        }
+
+        let new_excess = match context.insertion {
+            Insertion::Service(insertion) => route.energy_excess(
+                problem,
+                std::iter::once(ActivityId::Service(insertion.job_index)),
+                insertion.position,
+                insertion.position,
+            ),
+            Insertion::Shipment(insertion) => route.energy_excess(
+                problem,
+                insertion.inserted_activity_ids(route),
+                insertion.pickup_position,
+                insertion.delivery_position,
+            ),
+        };
+        let current_excess = if route.is_empty() {
+            0.0
+        } else {
+            route.energy_excess(problem, std::iter::empty(), route.len(), route.len())
+        };
+
+        Score::of(SCORE_LEVEL, new_excess - current_excess)
+    }
+}
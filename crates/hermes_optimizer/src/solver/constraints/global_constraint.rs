@@ -1,6 +1,12 @@
 use crate::solver::{
-    constraints::relation_constraint::RelationConstraint, insertion_context::InsertionContext,
-    score::Score, score_level::ScoreLevel, solution::working_solution::WorkingSolution,
+    constraints::{
+        depot_inventory_constraint::DepotInventoryConstraint,
+        relation_constraint::RelationConstraint,
+    },
+    insertion_context::InsertionContext,
+    score::Score,
+    score_level::ScoreLevel,
+    solution::working_solution::WorkingSolution,
 };
 
 use super::transport_cost_constraint::TransportCostConstraint;
@@ -15,6 +21,7 @@ pub trait GlobalConstraint {
 pub enum GlobalConstraintType {
     TransportCost(TransportCostConstraint),
     Relation(RelationConstraint),
+    DepotInventory(DepotInventoryConstraint),
 }
 
 impl GlobalConstraintType {
@@ -22,6 +29,7 @@ impl GlobalConstraintType {
         match self {
             Self::TransportCost(_) => "transport_cost",
             Self::Relation(_) => "relation",
+            Self::DepotInventory(_) => "depot_inventory",
         }
     }
 }
@@ -31,6 +39,7 @@ impl GlobalConstraint for GlobalConstraintType {
         match self {
             Self::TransportCost(constraint) => constraint.score_level(),
             Self::Relation(constraint) => constraint.score_level(),
+            Self::DepotInventory(constraint) => constraint.score_level(),
         }
     }
 
@@ -38,6 +47,7 @@ impl GlobalConstraint for GlobalConstraintType {
         match self {
             Self::TransportCost(constraint) => constraint.compute_insertion_score(context),
             Self::Relation(constraint) => constraint.compute_insertion_score(context),
+            Self::DepotInventory(constraint) => constraint.compute_insertion_score(context),
         }
     }
 
@@ -45,6 +55,7 @@ impl GlobalConstraint for GlobalConstraintType {
         match self {
             Self::TransportCost(constraint) => constraint.compute_score(context),
             Self::Relation(constraint) => constraint.compute_score(context),
+            Self::DepotInventory(constraint) => constraint.compute_score(context),
         }
     }
 }
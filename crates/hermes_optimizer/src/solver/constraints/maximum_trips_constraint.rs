@@ -0,0 +1,173 @@
+use jiff::Timestamp;
+
+use crate::{
+    problem::{vehicle::Vehicle, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        insertion::Insertion, insertion_context::InsertionContext, score::Score,
+        score_level::ScoreLevel, solution::route::WorkingSolutionRoute,
+    },
+};
+
+use super::route_constraint::RouteConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Hard;
+const WEIGHT: f64 = 1000.0;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Vehicles doing several trips cannot start more trips than their limits over the planning
+/// horizon and per day, the score is the number of trips over the limits
+#[derive(Clone)]
+pub struct MaximumTripsConstraint;
+
+/// Trips over the limits of the vehicle, over the horizon and summed over the days
+fn trips_excess(vehicle: &Vehicle, trip_starts: &[Timestamp]) -> usize {
+    let mut excess = vehicle.maximum_trips().map_or(0, |maximum_trips| {
+        trip_starts.len().saturating_sub(maximum_trips)
+    });
+
+    if let Some(maximum_trips_per_day) = vehicle.maximum_trips_per_day() {
+        let mut days = trip_starts
+            .iter()
+            .map(|start| start.as_second().div_euclid(SECONDS_PER_DAY))
+            .collect::<Vec<_>>();
+        days.sort_unstable();
+
+        excess += days
+            .chunk_by(|a, b| a == b)
+            .map(|trips| trips.len().saturating_sub(maximum_trips_per_day))
+            .sum::<usize>();
+    }
+
+    excess
+}
+
+fn has_trip_limits(vehicle: &Vehicle) -> bool {
+    vehicle.maximum_trips().is_some() || vehicle.maximum_trips_per_day().is_some()
+}
+
+impl RouteConstraint for MaximumTripsConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        let vehicle = route.vehicle(problem);
+        if !has_trip_limits(vehicle) {
+            return Score::zero();
+        }
+
+        let excess = trips_excess(vehicle, &route.trip_starts(problem));
+        Score::of(SCORE_LEVEL, excess as f64 * WEIGHT)
+    }
+
+    /// Only the reloads start a new trip, the trip starts moved by the other insertions are
+    /// scored with the route
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        let route = context.route();
+        let vehicle = route.vehicle(problem);
+        if !has_trip_limits(vehicle) {
+            return Score::zero();
+        }
+
+        let Insertion::Service(insertion) = context.insertion else {
+            return Score::zero();
+        };
+        if !problem.job(insertion.job_index).is_reload() || route.is_empty() {
+            return Score::zero();
+        }
+
+        let mut trip_starts = route.trip_starts(problem);
+        let current_excess = trips_excess(vehicle, &trip_starts);
+
+        // The new trip starts about when the vehicle leaves the activity before the reload
+        trip_starts.push(if insertion.position == 0 {
+            route.start(problem)
+        } else {
+            route.departure_time(insertion.position - 1)
+        });
+        let new_excess = trips_excess(vehicle, &trip_starts);
+
+        Score::of(
+            SCORE_LEVEL,
+            (new_excess as f64 - current_excess as f64) * WEIGHT,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::{
+            amount::Amount,
+            service::ServiceBuilder,
+            vehicle::{VehicleBuilder, VehicleIdx},
+            vehicle_routing_problem::VehicleRoutingProblem,
+        },
+        solver::{
+            constraints::route_constraint::RouteConstraint, score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::MaximumTripsConstraint;
+
+    fn create_problem(maximum_trips: usize, maximum_trips_per_day: usize) -> VehicleRoutingProblem {
+        let locations = test_utils::create_location_grid(5, 5);
+
+        let services = (0..3)
+            .map(|index| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_external_id(index.to_string());
+                builder.set_location_id(index + 1);
+                builder.set_demand(Amount::from_vec(vec![10.0]));
+                builder.build()
+            })
+            .collect();
+
+        let mut builder = VehicleBuilder::default();
+        builder.set_vehicle_id(String::from("vehicle"));
+        builder.set_profile_id(0);
+        builder.set_depot_location_id(0);
+        builder.set_capacity(Amount::from_vec(vec![10.0]));
+        builder.set_reload_duration(SignedDuration::from_mins(15));
+        builder.set_maximum_reloads(2);
+        builder.set_maximum_trips(maximum_trips);
+        builder.set_maximum_trips_per_day(maximum_trips_per_day);
+
+        test_utils::create_test_problem(locations, services, vec![builder.build()])
+    }
+
+    fn trips_score(problem: VehicleRoutingProblem) -> Score {
+        let problem = Arc::new(problem);
+        assert_eq!(problem.vehicle_reloads(VehicleIdx::new(0)).len(), 2);
+
+        // The reloads are generated after the services
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 3, 1, 4, 2],
+            }],
+        );
+        MaximumTripsConstraint.compute_score(&problem, solution.route(RouteIdx::new(0)))
+    }
+
+    #[test]
+    fn test_maximum_trips() {
+        assert_eq!(trips_score(create_problem(3, 3)), Score::zero());
+
+        // The three trips start on the same day
+        assert_eq!(trips_score(create_problem(3, 2)), Score::hard(1000.0));
+        assert_eq!(trips_score(create_problem(2, 1)), Score::hard(3000.0));
+    }
+}
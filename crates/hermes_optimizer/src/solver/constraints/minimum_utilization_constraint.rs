@@ -0,0 +1,220 @@
+use crate::{
+    problem::{
+        capacity::utilization, vehicle::Vehicle, vehicle_routing_problem::VehicleRoutingProblem,
+    },
+    solver::{
+        insertion::Insertion, insertion_context::InsertionContext, score::Score,
+        score_level::ScoreLevel, solution::route::WorkingSolutionRoute,
+    },
+};
+
+use super::route_constraint::RouteConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Soft;
+
+const DEFAULT_PENALTY: f64 = 1000.0;
+
+/// Vehicles dispatched for fewer activities than their `minimum_activities`, or with a peak load
+/// under their `minimum_utilization`, cost `penalty` per missing activity and `penalty` times the
+/// missing share of the minimum utilization.
+///
+/// Unused vehicles are never penalized, the solver is pushed toward consolidating the routes
+#[derive(Clone)]
+pub struct MinimumUtilizationConstraint {
+    penalty: f64,
+}
+
+impl Default for MinimumUtilizationConstraint {
+    fn default() -> Self {
+        MinimumUtilizationConstraint {
+            penalty: DEFAULT_PENALTY,
+        }
+    }
+}
+
+impl MinimumUtilizationConstraint {
+    pub fn new(penalty: f64) -> Self {
+        MinimumUtilizationConstraint { penalty }
+    }
+
+    fn has_minimums(vehicle: &Vehicle) -> bool {
+        vehicle.minimum_activities().is_some() || vehicle.minimum_utilization().is_some()
+    }
+
+    fn route_penalty(&self, vehicle: &Vehicle, activities: usize, utilization: f64) -> f64 {
+        if activities == 0 {
+            return 0.0;
+        }
+
+        let missing_activities = vehicle
+            .minimum_activities()
+            .map_or(0, |minimum| minimum.saturating_sub(activities));
+
+        let missing_utilization = match vehicle.minimum_utilization() {
+            Some(minimum) if minimum > 0.0 => ((minimum - utilization) / minimum).max(0.0),
+            _ => 0.0,
+        };
+
+        self.penalty * (missing_activities as f64 + missing_utilization)
+    }
+
+    fn route_utilization(vehicle: &Vehicle, route: &WorkingSolutionRoute) -> f64 {
+        if route.is_empty() {
+            0.0
+        } else {
+            utilization(vehicle.capacity(), route.fwd_load_peak(route.len()))
+        }
+    }
+}
+
+impl RouteConstraint for MinimumUtilizationConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        let vehicle = route.vehicle(problem);
+        if !Self::has_minimums(vehicle) {
+            return Score::zero();
+        }
+
+        Score::of(
+            SCORE_LEVEL,
+            self.route_penalty(
+                vehicle,
+                route.len(),
+                Self::route_utilization(vehicle, route),
+            ),
+        )
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        let route = context.route();
+        let vehicle = route.vehicle(problem);
+        if !Self::has_minimums(vehicle) {
+            return Score::zero();
+        }
+
+        let inserted_activities = match context.insertion {
+            Insertion::Service(_) => 1,
+            Insertion::Shipment(_) => 2,
+        };
+
+        // The peak load grows by at most the demand of the job
+        let demand = problem.job(context.insertion.job_idx()).demand();
+        let new_utilization = if route.is_empty() {
+            utilization(vehicle.capacity(), demand)
+        } else {
+            utilization(
+                vehicle.capacity(),
+                &(route.fwd_load_peak(route.len()) + demand),
+            )
+        };
+
+        let new_penalty =
+            self.route_penalty(vehicle, route.len() + inserted_activities, new_utilization);
+        let current_penalty = self.route_penalty(
+            vehicle,
+            route.len(),
+            Self::route_utilization(vehicle, route),
+        );
+
+        Score::of(SCORE_LEVEL, new_penalty - current_penalty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            capacity::Capacity,
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::JobIdx,
+            service::ServiceBuilder,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle::VehicleBuilder,
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            constraints::route_constraint::RouteConstraint,
+            insertion::{Insertion, ServiceInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::MinimumUtilizationConstraint;
+
+    #[test]
+    fn test_minimum_utilization() {
+        let locations = test_utils::create_location_grid(1, 10);
+        let services = (1..4)
+            .map(|location_id| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(location_id);
+                builder.set_external_id(location_id.to_string());
+                builder.set_demand(Capacity::from_vec(vec![10.0]));
+                builder.build()
+            })
+            .collect();
+
+        let mut vehicle = VehicleBuilder::default();
+        vehicle.set_depot_location_id(0);
+        vehicle.set_vehicle_id("0".to_owned());
+        vehicle.set_profile_id(0);
+        vehicle.set_capacity(Capacity::from_vec(vec![40.0]));
+        vehicle.set_minimum_activities(3);
+        vehicle.set_minimum_utilization(0.5);
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_services(services);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vec![vehicle.build()]));
+        let problem = Arc::new(builder.build().unwrap());
+
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0],
+            }],
+        );
+
+        let constraint = MinimumUtilizationConstraint::new(100.0);
+
+        // Two missing activities, 10 / 40 is half of the minimum utilization
+        let route = solution.route(RouteIdx::new(0));
+        assert_eq!(
+            constraint.compute_score(&problem, route),
+            Score::soft(250.0)
+        );
+
+        // One missing activity, at the minimum utilization
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(1),
+            position: 1,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(
+            constraint.compute_insertion_score(&context),
+            Score::soft(-150.0)
+        );
+    }
+}
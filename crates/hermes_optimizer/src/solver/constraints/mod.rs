@@ -1,15 +1,27 @@
 pub mod activity_constraint;
+pub mod axle_load_constraint;
 pub mod capacity_constraint;
 pub mod compute_insertion_score;
 pub mod constraint;
+pub mod constraint_set;
+pub mod depot_hours_constraint;
+pub mod depot_inventory_constraint;
+pub mod energy_constraint;
 pub mod global_constraint;
 pub mod maximum_activities_constraint;
+pub mod maximum_trips_constraint;
 pub mod maximum_working_duration_constraint;
+pub mod minimum_utilization_constraint;
+pub mod position_preference_constraint;
 pub mod relation_constraint;
+pub mod ride_duration_constraint;
 pub mod route_constraint;
 pub mod shift_constraint;
 pub mod skill_constraint;
+pub mod tag_constraint;
+pub mod territory_constraint;
 pub mod time_window_constraint;
+pub mod time_window_preference_constraint;
 pub mod transport_cost_constraint;
 pub mod vehicle_cost_constraint;
 pub mod waiting_duration_constraint;
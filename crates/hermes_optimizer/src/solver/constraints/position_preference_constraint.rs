@@ -0,0 +1,195 @@
+use crate::{
+    problem::{job::ActivityId, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        insertion::Insertion, insertion_context::InsertionContext, score::Score,
+        score_level::ScoreLevel, solution::route::WorkingSolutionRoute,
+    },
+};
+
+use super::route_constraint::RouteConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Soft;
+
+/// Penalizes activities served outside of their preferred band of positions in the route
+#[derive(Clone)]
+pub struct PositionPreferenceConstraint;
+
+impl PositionPreferenceConstraint {
+    /// Cost of the activity at `index` in the route, positions being 1-based
+    fn activity_cost(
+        problem: &VehicleRoutingProblem,
+        activity_id: ActivityId,
+        index: usize,
+    ) -> f64 {
+        problem
+            .job_activity(activity_id)
+            .position_preference()
+            .map_or(0.0, |preference| preference.cost(index + 1))
+    }
+
+    /// Cost change of the activities in `start..end` when they are moved `shift` positions later
+    fn shift_cost_delta(
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+        start: usize,
+        end: usize,
+        shift: usize,
+    ) -> f64 {
+        route
+            .activity_ids_iter(start, end)
+            .zip(start..end)
+            .map(|(activity_id, index)| {
+                Self::activity_cost(problem, activity_id, index + shift)
+                    - Self::activity_cost(problem, activity_id, index)
+            })
+            .sum()
+    }
+}
+
+impl RouteConstraint for PositionPreferenceConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        if !problem.has_position_preferences() {
+            return Score::zero();
+        }
+
+        let cost = route
+            .activity_ids()
+            .iter()
+            .enumerate()
+            .map(|(index, &activity_id)| Self::activity_cost(problem, activity_id, index))
+            .sum();
+
+        Score::of(SCORE_LEVEL, cost)
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        if !problem.has_position_preferences() {
+            return Score::zero();
+        }
+
+        let route = context.route();
+
+        let cost = match context.insertion {
+            Insertion::Service(insertion) => {
+                Self::activity_cost(
+                    problem,
+                    ActivityId::Service(insertion.job_index),
+                    insertion.position,
+                ) + Self::shift_cost_delta(problem, route, insertion.position, route.len(), 1)
+            }
+            // Shipments don't have position preferences, but they move the activities after them
+            Insertion::Shipment(insertion) => {
+                Self::shift_cost_delta(
+                    problem,
+                    route,
+                    insertion.pickup_position,
+                    insertion.delivery_position,
+                    1,
+                ) + Self::shift_cost_delta(
+                    problem,
+                    route,
+                    insertion.delivery_position,
+                    route.len(),
+                    2,
+                )
+            }
+        };
+
+        Score::of(SCORE_LEVEL, cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            job::JobIdx, position_preference::PositionPreference, service::ServiceBuilder,
+            vehicle::VehicleBuilder,
+        },
+        solver::{
+            constraints::route_constraint::RouteConstraint,
+            insertion::{Insertion, ServiceInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::PositionPreferenceConstraint;
+
+    #[test]
+    fn test_position_preference_insertion_score() {
+        let locations = test_utils::create_location_grid(1, 4);
+
+        let services = (1..4)
+            .map(|location_id| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(location_id);
+                builder.set_external_id(location_id.to_string());
+                if location_id == 1 {
+                    builder.set_position_preference(PositionPreference {
+                        first_position: None,
+                        last_position: Some(1),
+                        penalty: 10.0,
+                    });
+                }
+                builder.build()
+            })
+            .collect();
+
+        let mut vehicle_builder = VehicleBuilder::default();
+        vehicle_builder.set_depot_location_id(0);
+        vehicle_builder.set_vehicle_id(String::from("vehicle"));
+        vehicle_builder.set_profile_id(0);
+
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations,
+            services,
+            vec![vehicle_builder.build()],
+        ));
+
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1],
+            }],
+        );
+
+        let constraint = PositionPreferenceConstraint;
+        let route = solution.route(RouteIdx::new(0));
+        assert_eq!(constraint.compute_score(&problem, route), Score::zero());
+
+        // Inserting before the preferred service pushes it to the second position
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(2),
+            position: 0,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(
+            constraint.compute_insertion_score(&context),
+            Score::soft(10.0)
+        );
+
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(2),
+            position: 1,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(constraint.compute_insertion_score(&context), Score::zero());
+    }
+}
@@ -0,0 +1,173 @@
+use jiff::SignedDuration;
+
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::{
+        insertion::Insertion,
+        insertion_context::InsertionContext,
+        score::Score,
+        score_level::ScoreLevel,
+        solution::route::{RouteActivityInfo, WorkingSolutionRoute},
+    },
+};
+
+use super::activity_constraint::ActivityConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Hard;
+
+/// Shipments must be delivered within their maximum ride duration after the pickup, the score is
+/// the time over it in seconds
+#[derive(Clone)]
+pub struct RideDurationConstraint;
+
+impl ActivityConstraint for RideDurationConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+        activity: &RouteActivityInfo,
+    ) -> Score {
+        if !problem.has_ride_durations() {
+            return Score::zero();
+        }
+
+        match route.job_position(activity.activity_id()) {
+            Some(position) => Score::of(
+                SCORE_LEVEL,
+                route
+                    .ride_duration_excess_at(problem, position)
+                    .as_secs_f64(),
+            ),
+            None => Score::zero(),
+        }
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        if !problem.has_ride_durations() {
+            return Score::zero();
+        }
+
+        let route = context.route();
+        let start = match context.insertion {
+            Insertion::Service(insertion) => insertion.position,
+            Insertion::Shipment(insertion) => insertion.pickup_position,
+        };
+
+        let new_excess = route.ride_duration_excess(problem, context.updated_activities_iter());
+        let current_excess = (start..route.len()).fold(SignedDuration::ZERO, |excess, position| {
+            excess + route.ride_duration_excess_at(problem, position)
+        });
+
+        Score::of(SCORE_LEVEL, (new_excess - current_excess).as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::{
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::ActivityId,
+            service::ServiceBuilder,
+            shipment::ShipmentBuilder,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            constraints::activity_constraint::ActivityConstraint,
+            insertion::{Insertion, ServiceInsertion, ShipmentInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+        },
+        test_utils,
+    };
+
+    use super::RideDurationConstraint;
+
+    #[test]
+    fn test_ride_duration() {
+        let locations = test_utils::create_location_grid(1, 10);
+
+        let mut service = ServiceBuilder::default();
+        service.set_location_id(9);
+        service.set_external_id("service".to_owned());
+
+        let mut shipment = ShipmentBuilder::default();
+        shipment.set_external_id("shipment".to_owned());
+        shipment.set_pickup_location_id(1);
+        shipment.set_delivery_location_id(2);
+        shipment.set_max_ride_duration(SignedDuration::from_secs(5));
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_services(vec![service.build()]);
+        builder.set_shipments(vec![shipment.build()]);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(test_utils::create_basic_vehicles(vec![0])));
+        let problem = Arc::new(builder.build().unwrap());
+
+        assert!(problem.has_ride_durations());
+
+        let mut solution = WorkingSolution::new(Arc::clone(&problem));
+        solution.insert(&Insertion::Shipment(ShipmentInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: 1.into(),
+            pickup_position: 0,
+            delivery_position: 0,
+        }));
+
+        // Detour to location 9 between the pickup and the delivery: 8s + 7s instead of 1s
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: 0.into(),
+            position: 1,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(
+            RideDurationConstraint.compute_insertion_score(&context),
+            Score::hard(10.0)
+        );
+
+        let route = solution.route(RouteIdx::new(0));
+        assert!(
+            !route.is_valid_ride_duration_change(
+                &problem,
+                [
+                    ActivityId::Service(0.into()),
+                    ActivityId::ShipmentDelivery(1.into())
+                ]
+                .into_iter(),
+                1,
+                2,
+            )
+        );
+
+        // Before the pickup, the ride is not affected
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: 0.into(),
+            position: 0,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(
+            RideDurationConstraint.compute_insertion_score(&context),
+            Score::zero()
+        );
+    }
+}
@@ -8,8 +8,12 @@ use crate::{
 };
 
 use super::{
-    capacity_constraint::CapacityConstraint,
+    axle_load_constraint::AxleLoadConstraint, capacity_constraint::CapacityConstraint,
+    depot_hours_constraint::DepotHoursConstraint, energy_constraint::EnergyConstraint,
+    maximum_trips_constraint::MaximumTripsConstraint,
     maximum_working_duration_constraint::MaximumWorkingDurationConstraint,
+    minimum_utilization_constraint::MinimumUtilizationConstraint,
+    position_preference_constraint::PositionPreferenceConstraint,
     shift_constraint::ShiftConstraint, vehicle_cost_constraint::VehicleCostConstraint,
     waiting_duration_constraint::WaitingDurationConstraint,
 };
@@ -30,6 +34,12 @@ pub enum RouteConstraintType {
     WaitingDuration(WaitingDurationConstraint),
     VehicleCost(VehicleCostConstraint),
     MaximumJobs(MaximumActivitiesConstraint),
+    PositionPreference(PositionPreferenceConstraint),
+    DepotHours(DepotHoursConstraint),
+    MinimumUtilization(MinimumUtilizationConstraint),
+    Energy(EnergyConstraint),
+    AxleLoad(AxleLoadConstraint),
+    MaximumTrips(MaximumTripsConstraint),
 }
 
 impl RouteConstraintType {
@@ -41,6 +51,12 @@ impl RouteConstraintType {
             RouteConstraintType::VehicleCost(_) => "vehicle_cost",
             RouteConstraintType::MaximumWorkingDuration(_) => "maximum_working_duration",
             RouteConstraintType::MaximumJobs(_) => "maximum_activities",
+            RouteConstraintType::PositionPreference(_) => "position_preference",
+            RouteConstraintType::DepotHours(_) => "depot_hours",
+            RouteConstraintType::MinimumUtilization(_) => "minimum_utilization",
+            RouteConstraintType::Energy(_) => "energy",
+            RouteConstraintType::AxleLoad(_) => "axle_load",
+            RouteConstraintType::MaximumTrips(_) => "maximum_trips",
         }
     }
 }
@@ -54,6 +70,12 @@ impl RouteConstraint for RouteConstraintType {
             RouteConstraintType::VehicleCost(c) => c.score_level(),
             RouteConstraintType::MaximumWorkingDuration(c) => c.score_level(),
             RouteConstraintType::MaximumJobs(c) => c.score_level(),
+            RouteConstraintType::PositionPreference(c) => c.score_level(),
+            RouteConstraintType::DepotHours(c) => c.score_level(),
+            RouteConstraintType::MinimumUtilization(c) => c.score_level(),
+            RouteConstraintType::Energy(c) => c.score_level(),
+            RouteConstraintType::AxleLoad(c) => c.score_level(),
+            RouteConstraintType::MaximumTrips(c) => c.score_level(),
         }
     }
     fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
@@ -64,6 +86,12 @@ impl RouteConstraint for RouteConstraintType {
             RouteConstraintType::VehicleCost(c) => c.compute_insertion_score(context),
             RouteConstraintType::MaximumWorkingDuration(c) => c.compute_insertion_score(context),
             RouteConstraintType::MaximumJobs(c) => c.compute_insertion_score(context),
+            RouteConstraintType::PositionPreference(c) => c.compute_insertion_score(context),
+            RouteConstraintType::DepotHours(c) => c.compute_insertion_score(context),
+            RouteConstraintType::MinimumUtilization(c) => c.compute_insertion_score(context),
+            RouteConstraintType::Energy(c) => c.compute_insertion_score(context),
+            RouteConstraintType::AxleLoad(c) => c.compute_insertion_score(context),
+            RouteConstraintType::MaximumTrips(c) => c.compute_insertion_score(context),
         }
     }
 
@@ -79,6 +107,12 @@ impl RouteConstraint for RouteConstraintType {
             RouteConstraintType::VehicleCost(c) => c.compute_score(problem, route),
             RouteConstraintType::MaximumWorkingDuration(c) => c.compute_score(problem, route),
             RouteConstraintType::MaximumJobs(c) => c.compute_score(problem, route),
+            RouteConstraintType::PositionPreference(c) => c.compute_score(problem, route),
+            RouteConstraintType::DepotHours(c) => c.compute_score(problem, route),
+            RouteConstraintType::MinimumUtilization(c) => c.compute_score(problem, route),
+            RouteConstraintType::Energy(c) => c.compute_score(problem, route),
+            RouteConstraintType::AxleLoad(c) => c.compute_score(problem, route),
+            RouteConstraintType::MaximumTrips(c) => c.compute_score(problem, route),
         }
     }
 }
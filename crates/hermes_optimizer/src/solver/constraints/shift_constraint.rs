@@ -25,12 +25,12 @@ impl RouteConstraint for ShiftConstraint {
     ) -> Score {
         let vehicle = route.vehicle(problem);
 
-        if let Some(latest_end) = vehicle.latest_end_time()
-            && route.end(problem) > latest_end
+        if let Some(deadline) = vehicle.latest_end_deadline()
+            && route.end(problem) > deadline
         {
             Score::of(
                 self.score_level(),
-                route.end(problem).duration_since(latest_end).as_secs_f64(),
+                route.end(problem).duration_since(deadline).as_secs_f64(),
             )
         } else {
             Score::zero()
@@ -42,19 +42,19 @@ impl RouteConstraint for ShiftConstraint {
         let route = context.route();
         let vehicle = route.vehicle(problem);
 
-        if vehicle.latest_end_time().is_none() {
+        if vehicle.latest_end_deadline().is_none() {
             return Score::zero();
         }
 
         let new_end = context.compute_vehicle_end();
 
         if route.is_empty() {
-            if let Some(latest_end) = vehicle.latest_end_time()
-                && new_end > latest_end
+            if let Some(deadline) = vehicle.latest_end_deadline()
+                && new_end > deadline
             {
                 Score::of(
                     self.score_level(),
-                    new_end.duration_since(latest_end).as_secs_f64(),
+                    new_end.duration_since(deadline).as_secs_f64(),
                 )
             } else {
                 Score::zero()
@@ -62,25 +62,25 @@ impl RouteConstraint for ShiftConstraint {
         } else {
             let current_end = route.end(problem);
 
-            if let Some(latest_end) = vehicle.latest_end_time() {
+            if let Some(deadline) = vehicle.latest_end_deadline() {
                 // New violation, old route was not violating the constraint
-                if new_end > latest_end && current_end <= latest_end {
+                if new_end > deadline && current_end <= deadline {
                     return Score::of(
                         self.score_level(),
-                        new_end.duration_since(latest_end).as_secs_f64(),
+                        new_end.duration_since(deadline).as_secs_f64(),
                     );
 
                     // Both are violating the constraint, we compute the delta between the two
-                } else if current_end > latest_end && new_end > latest_end {
+                } else if current_end > deadline && new_end > deadline {
                     return Score::of(
                         self.score_level(),
                         new_end.duration_since(current_end).as_secs_f64(),
                     );
                     // Current duration is violating, new one is not
-                } else if current_end > latest_end && new_end <= latest_end {
+                } else if current_end > deadline && new_end <= deadline {
                     return Score::of(
                         self.score_level(),
-                        latest_end.duration_since(current_end).as_secs_f64(),
+                        deadline.duration_since(current_end).as_secs_f64(),
                     );
                 } else {
                     return Score::zero();
@@ -91,3 +91,90 @@ impl RouteConstraint for ShiftConstraint {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::{
+            capacity::Capacity,
+            fleet::Fleet,
+            service::ServiceBuilder,
+            tolerances::Tolerances,
+            travel_cost_matrix::TravelMatrices,
+            vehicle::{VehicleBuilder, VehicleShift},
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
+        },
+        solver::{
+            constraints::{route_constraint::RouteConstraint, shift_constraint::ShiftConstraint},
+            score::Score,
+            score_level::ScoreLevel,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    /// The route of the service ends at 09:10, 10 minutes after the latest end of the shift
+    fn create_problem(time_window_tolerance: SignedDuration) -> VehicleRoutingProblem {
+        let locations = test_utils::create_location_grid(1, 2);
+
+        let mut vehicle_builder = VehicleBuilder::default();
+        vehicle_builder.set_depot_location_id(0);
+        vehicle_builder.set_return(true);
+        vehicle_builder.set_capacity(Capacity::from_vec(vec![10.0]));
+        vehicle_builder.set_vehicle_id(String::from("vehicle"));
+        vehicle_builder.set_profile_id(0);
+        vehicle_builder.set_vehicle_shift(VehicleShift {
+            earliest_start: Some("2025-11-30T08:00:00+02:00".parse().unwrap()),
+            latest_start: Some("2025-11-30T08:00:00+02:00".parse().unwrap()),
+            latest_end: Some("2025-11-30T09:00:00+02:00".parse().unwrap()),
+            maximum_working_duration: None,
+            maximum_transport_duration: None,
+        });
+
+        let mut service_builder = ServiceBuilder::default();
+        service_builder.set_external_id(String::from("service"));
+        service_builder.set_service_duration(SignedDuration::from_mins(10));
+        service_builder.set_location_id(1);
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_constant(
+                &locations,
+                SignedDuration::from_mins(30).as_secs_f64(),
+                100.0,
+                SignedDuration::from_mins(30).as_secs_f64(),
+            ),
+        )]);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vec![vehicle_builder.build()]));
+        builder.set_services(vec![service_builder.build()]);
+        builder.set_tolerances(Tolerances {
+            time_window: time_window_tolerance,
+            ..Tolerances::default()
+        });
+
+        builder.build().expect("Expect valid problem")
+    }
+
+    #[test]
+    fn test_shift_end_tolerance() {
+        for (tolerance, violation) in [(0, 600.0), (5, 300.0), (10, 0.0)] {
+            let problem = Arc::new(create_problem(SignedDuration::from_mins(tolerance)));
+            let solution = test_utils::create_test_working_solution(
+                Arc::clone(&problem),
+                vec![TestRoute {
+                    vehicle_id: 0,
+                    service_ids: vec![0],
+                }],
+            );
+
+            let score = ShiftConstraint.compute_score(&problem, solution.route(0.into()));
+            assert_eq!(score, Score::of(ScoreLevel::Hard, violation));
+        }
+    }
+}
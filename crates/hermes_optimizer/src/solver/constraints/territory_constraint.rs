@@ -0,0 +1,175 @@
+use crate::{
+    problem::{job::ActivityId, vehicle::Vehicle, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        constraints::activity_constraint::ActivityConstraint,
+        insertion::Insertion,
+        insertion_context::InsertionContext,
+        score::Score,
+        score_level::ScoreLevel,
+        solution::route::{RouteActivityInfo, WorkingSolutionRoute},
+    },
+};
+
+/// Vehicles with a territory serve jobs outside of it at a cost of `penalty` per activity.
+///
+/// Hard by default, use `TerritoryConstraint::soft` or `ConstraintOverrides::territory_penalty` to
+/// only prefer serving the jobs of a territory with the vehicles assigned to it
+#[derive(Clone)]
+pub struct TerritoryConstraint {
+    score_level: ScoreLevel,
+    penalty: f64,
+}
+
+impl Default for TerritoryConstraint {
+    fn default() -> Self {
+        TerritoryConstraint {
+            score_level: ScoreLevel::Hard,
+            penalty: 1.0,
+        }
+    }
+}
+
+impl TerritoryConstraint {
+    pub fn soft(penalty: f64) -> Self {
+        TerritoryConstraint {
+            score_level: ScoreLevel::Soft,
+            penalty,
+        }
+    }
+
+    fn is_outside_territory(
+        problem: &VehicleRoutingProblem,
+        vehicle: &Vehicle,
+        activity_id: ActivityId,
+    ) -> bool {
+        vehicle.territory().is_some_and(|territory| {
+            let location_id = problem.job_activity(activity_id).location_id();
+            !territory.contains(location_id, problem.location(location_id))
+        })
+    }
+}
+
+impl ActivityConstraint for TerritoryConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        self.score_level
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+        activity: &RouteActivityInfo,
+    ) -> Score {
+        if Self::is_outside_territory(problem, route.vehicle(problem), activity.activity_id()) {
+            Score::of(self.score_level, self.penalty)
+        } else {
+            Score::zero()
+        }
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        let vehicle = context.route().vehicle(problem);
+        if vehicle.territory().is_none() {
+            return Score::zero();
+        }
+
+        let outside_activities = match context.insertion {
+            Insertion::Service(insertion) => usize::from(Self::is_outside_territory(
+                problem,
+                vehicle,
+                ActivityId::Service(insertion.job_index),
+            )),
+            Insertion::Shipment(insertion) => [
+                ActivityId::ShipmentPickup(insertion.job_index),
+                ActivityId::ShipmentDelivery(insertion.job_index),
+            ]
+            .into_iter()
+            .filter(|&activity_id| Self::is_outside_territory(problem, vehicle, activity_id))
+            .count(),
+        };
+
+        Score::of(self.score_level, self.penalty * outside_activities as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::JobIdx,
+            location::LocationIdx,
+            territory::Territory,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle::VehicleBuilder,
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            constraints::activity_constraint::ActivityConstraint,
+            insertion::{Insertion, ServiceInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::TerritoryConstraint;
+
+    #[test]
+    fn test_territory() {
+        let locations = test_utils::create_location_grid(1, 10);
+        let services = test_utils::create_basic_services(vec![1, 2, 8]);
+
+        // Only serves the first half of the grid
+        let mut vehicle = VehicleBuilder::default();
+        vehicle.set_depot_location_id(0);
+        vehicle.set_vehicle_id("0".to_owned());
+        vehicle.set_profile_id(0);
+        vehicle.set_territory(Territory::from_location_ids((0..5).map(LocationIdx::new)));
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_services(services);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vec![vehicle.build()]));
+        let problem = Arc::new(builder.build().unwrap());
+
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0],
+            }],
+        );
+
+        let inside = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(1),
+            position: 1,
+        });
+        let outside = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(2),
+            position: 1,
+        });
+
+        let hard = TerritoryConstraint::default();
+        let context = InsertionContext::new(&problem, &solution, &inside, false);
+        assert_eq!(hard.compute_insertion_score(&context), Score::zero());
+        let context = InsertionContext::new(&problem, &solution, &outside, false);
+        assert_eq!(hard.compute_insertion_score(&context), Score::hard(1.0));
+
+        let soft = TerritoryConstraint::soft(100.0);
+        assert_eq!(soft.compute_insertion_score(&context), Score::soft(100.0));
+    }
+}
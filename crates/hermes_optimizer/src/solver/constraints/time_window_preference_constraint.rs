@@ -0,0 +1,169 @@
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::{
+        insertion::Insertion,
+        insertion_context::InsertionContext,
+        score::Score,
+        score_level::ScoreLevel,
+        solution::route::{RouteActivityInfo, WorkingSolutionRoute},
+    },
+};
+
+use super::activity_constraint::ActivityConstraint;
+
+const SCORE_LEVEL: ScoreLevel = ScoreLevel::Soft;
+
+/// Jobs with several time windows are served in the window with the least waiting, at the cost of
+/// its penalty, see `TimeWindows::preference_penalty`
+#[derive(Clone)]
+pub struct TimeWindowPreferenceConstraint;
+
+impl ActivityConstraint for TimeWindowPreferenceConstraint {
+    fn score_level(&self) -> ScoreLevel {
+        SCORE_LEVEL
+    }
+
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        _route: &WorkingSolutionRoute,
+        activity: &RouteActivityInfo,
+    ) -> Score {
+        if !problem.has_time_window_preferences() {
+            return Score::zero();
+        }
+
+        Score::of(
+            SCORE_LEVEL,
+            activity
+                .job_activity(problem)
+                .time_windows()
+                .preference_penalty(activity.arrival_time()),
+        )
+    }
+
+    fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
+        let problem = context.problem();
+        if !problem.has_time_window_preferences() {
+            return Score::zero();
+        }
+
+        let route = context.route();
+        let start = match context.insertion {
+            Insertion::Service(insertion) => insertion.position,
+            Insertion::Shipment(insertion) => insertion.pickup_position,
+        };
+
+        let new_penalty: f64 = context
+            .updated_activities_iter()
+            .map(|data| {
+                problem
+                    .job_activity(data.job_id)
+                    .time_windows()
+                    .preference_penalty(data.arrival_time)
+            })
+            .sum();
+
+        let current_penalty: f64 = (start..route.len())
+            .map(|position| {
+                problem
+                    .job_activity(route.activity_id(position))
+                    .time_windows()
+                    .preference_penalty(route.arrival_time(position))
+            })
+            .sum();
+
+        Score::of(SCORE_LEVEL, new_penalty - current_penalty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::JobIdx,
+            service::ServiceBuilder,
+            time_window::{TimeWindow, TimeWindowBuilder},
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle::{VehicleBuilder, VehicleShiftBuilder},
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            constraints::activity_constraint::ActivityConstraint,
+            insertion::{Insertion, ServiceInsertion},
+            insertion_context::InsertionContext,
+            score::Score,
+            solution::route_id::RouteIdx,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::TimeWindowPreferenceConstraint;
+
+    #[test]
+    fn test_time_window_preference() {
+        let locations = test_utils::create_location_grid(1, 10);
+
+        let mut service = ServiceBuilder::default();
+        service.set_location_id(1);
+        service.set_external_id("0".to_owned());
+
+        // Prefers the evening, the vehicle can only be there in the morning
+        let mut preferred = ServiceBuilder::default();
+        preferred.set_location_id(2);
+        preferred.set_external_id("1".to_owned());
+        preferred.set_time_windows(vec![
+            TimeWindowBuilder::default()
+                .with_iso_start("2025-06-10T00:00:00Z")
+                .with_iso_end("2025-06-10T12:00:00Z")
+                .with_penalty(10.0)
+                .build(),
+            TimeWindow::from_iso(Some("2025-06-10T18:00:00Z"), Some("2025-06-10T20:00:00Z")),
+        ]);
+
+        let mut vehicle = VehicleBuilder::default();
+        vehicle.set_depot_location_id(0);
+        vehicle.set_vehicle_id("0".to_owned());
+        vehicle.set_profile_id(0);
+        let mut shift = VehicleShiftBuilder::default();
+        shift.set_earliest_start("2025-06-10T08:00:00Z".parse().unwrap());
+        vehicle.set_vehicle_shift(shift.build());
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_services(vec![service.build(), preferred.build()]);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vec![vehicle.build()]));
+        let problem = Arc::new(builder.build().unwrap());
+
+        assert!(problem.has_time_window_preferences());
+
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0],
+            }],
+        );
+
+        let insertion = Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(1),
+            position: 1,
+        });
+        let context = InsertionContext::new(&problem, &solution, &insertion, false);
+        assert_eq!(
+            TimeWindowPreferenceConstraint.compute_insertion_score(&context),
+            Score::soft(10.0)
+        );
+    }
+}
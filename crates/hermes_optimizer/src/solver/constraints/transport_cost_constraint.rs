@@ -1,6 +1,12 @@
-use crate::solver::{
-    insertion::Insertion, insertion_context::InsertionContext, score::Score,
-    score_level::ScoreLevel, solution::working_solution::WorkingSolution,
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::{
+        insertion::Insertion,
+        insertion_context::InsertionContext,
+        score::Score,
+        score_level::ScoreLevel,
+        solution::{route::WorkingSolutionRoute, working_solution::WorkingSolution},
+    },
 };
 
 use super::global_constraint::GlobalConstraint;
@@ -12,6 +18,20 @@ pub const TRANSPORT_COST_WEIGHT: f64 = 1.0;
 
 const SCORE_LEVEL: ScoreLevel = ScoreLevel::Soft;
 
+impl TransportCostConstraint {
+    /// Share of the route in the score of the solution
+    pub fn compute_route_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        Score::of(
+            SCORE_LEVEL,
+            route.transport_costs(problem) * TRANSPORT_COST_WEIGHT,
+        )
+    }
+}
+
 impl GlobalConstraint for TransportCostConstraint {
     fn score_level(&self) -> ScoreLevel {
         SCORE_LEVEL
@@ -73,6 +93,10 @@ impl GlobalConstraint for TransportCostConstraint {
 
                 let previous_pickup_location_id =
                     route.previous_location_id(problem, pickup_position);
+                // The depot when inserting at the end of a closed route, none for an open route
+                let next_pickup_location_id = route
+                    .location_id(problem, pickup_position)
+                    .or_else(|| route.end_location(problem));
 
                 let mut delta = 0.0;
 
@@ -85,7 +109,7 @@ impl GlobalConstraint for TransportCostConstraint {
                 delta -= problem.travel_cost_or_zero(
                     vehicle,
                     previous_pickup_location_id,
-                    route.location_id(problem, pickup_position),
+                    next_pickup_location_id,
                 );
 
                 if pickup_position == delivery_position {
@@ -93,27 +117,27 @@ impl GlobalConstraint for TransportCostConstraint {
                     delta += problem.travel_cost_or_zero(
                         vehicle,
                         Some(delivery_location_id),
-                        route.location_id(problem, pickup_position),
+                        next_pickup_location_id,
                     );
                 } else {
                     delta += problem.travel_cost_or_zero(
                         vehicle,
                         Some(pickup_location_id),
-                        route.location_id(problem, pickup_position),
+                        next_pickup_location_id,
                     );
 
                     let previous_delivery_location_id =
                         route.previous_location_id(problem, delivery_position);
+                    let next_delivery_location_id = route
+                        .location_id(problem, delivery_position)
+                        .or_else(|| route.end_location(problem));
+
                     delta -= problem.travel_cost_or_zero(
                         vehicle,
                         previous_delivery_location_id,
-                        route.location_id(problem, delivery_position),
+                        next_delivery_location_id,
                     );
 
-                    let next_delivery_location_id = route
-                        .location_id(problem, delivery_position)
-                        .or_else(|| route.end_location(problem));
-
                     delta += problem.travel_cost_or_zero(
                         vehicle,
                         previous_delivery_location_id,
@@ -18,15 +18,27 @@ impl RouteConstraint for VehicleCostConstraint {
         SCORE_LEVEL
     }
 
-    fn compute_score(&self, problem: &VehicleRoutingProblem, _: &WorkingSolutionRoute) -> Score {
-        Score::soft(problem.fixed_vehicle_costs())
+    fn compute_score(
+        &self,
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+    ) -> Score {
+        Score::of(
+            self.score_level(),
+            problem.fixed_vehicle_cost(route.vehicle(problem)),
+        )
     }
 
     fn compute_insertion_score(&self, context: &InsertionContext) -> Score {
         let route = context.route();
 
         if route.is_empty() {
-            Score::of(self.score_level(), context.problem().fixed_vehicle_costs())
+            Score::of(
+                self.score_level(),
+                context
+                    .problem()
+                    .fixed_vehicle_cost(route.vehicle(context.problem())),
+            )
         } else {
             Score::zero()
         }
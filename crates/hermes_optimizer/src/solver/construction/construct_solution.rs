@@ -34,8 +34,13 @@ use crate::{
 
 /// Kmin = Q / D where Q = total demand and D = vehicle capacity
 /// Kmin = max(Q_i / D_i) for each capacity dimension i
+///
+/// Bounded by the number of vehicles of a finite or counted fleet
 fn find_minimum_vehicles(problem: &VehicleRoutingProblem) -> usize {
-    let mut minimum_vehicles = problem.vehicles().len();
+    let mut minimum_vehicles = problem
+        .fleet()
+        .maximum_routes()
+        .unwrap_or(problem.vehicles().len());
     let total_demand: Capacity = problem
         .jobs()
         .iter()
@@ -81,6 +86,7 @@ fn compute_convex_hull(problem: &VehicleRoutingProblem) -> (Vec<JobIdx>, Vec<Job
         problem
             .jobs()
             .iter()
+            .filter(|job| !job.is_vehicle_stop())
             .flat_map(|job| {
                 let location_ids = match job {
                     Job::Service(service) => vec![service.location_id()],
@@ -122,6 +128,7 @@ fn compute_convex_hull(problem: &VehicleRoutingProblem) -> (Vec<JobIdx>, Vec<Job
                 .iter()
                 .enumerate_idx()
                 .filter(move |(_, job)| match job {
+                    Job::Service(_) if job.is_vehicle_stop() => false,
                     Job::Service(service) => service.location_id() == location_id,
                     Job::Shipment(shipment) => {
                         shipment.pickup().location_id() == location_id
@@ -134,7 +141,7 @@ fn compute_convex_hull(problem: &VehicleRoutingProblem) -> (Vec<JobIdx>, Vec<Job
 
     let interior: Vec<JobIdx> = (0..problem.jobs().len())
         .map(JobIdx::new)
-        .filter(|i| !exterior.contains(i))
+        .filter(|&i| !exterior.contains(&i) && !problem.job(i).is_vehicle_stop())
         .collect();
 
     (exterior, interior)
@@ -361,6 +368,7 @@ pub fn construct_solution(
 ) -> WorkingSolution {
     debug!("Start construction heuristic");
     let mut solution = WorkingSolution::new(Arc::clone(problem));
+    solution.set_neighborhood_size(params.neighborhood_size);
     create_initial_routes(problem, &mut solution);
 
     let (score, score_analysis) = solution.compute_solution_score(constraints);
@@ -391,6 +399,9 @@ pub fn construct_solution(
                 },
                 problem,
                 insert_on_failure: false,
+                custom_strategies: &params.recreate.custom_strategies,
+                #[cfg(feature = "statistics")]
+                constraint_statistics: None,
             },
         );
     } else {
@@ -406,6 +417,9 @@ pub fn construct_solution(
                 },
                 problem,
                 insert_on_failure: false,
+                custom_strategies: &params.recreate.custom_strategies,
+                #[cfg(feature = "statistics")]
+                constraint_statistics: None,
             },
         );
     }
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+/// Operators defined outside of the crate, identified by a unique name.
+///
+/// See `RuinParams::register_custom` and `RecreateParams::register_custom`.
+pub struct CustomStrategies<T: ?Sized> {
+    strategies: Vec<(&'static str, Arc<T>)>,
+}
+
+impl<T: ?Sized> Default for CustomStrategies<T> {
+    fn default() -> Self {
+        CustomStrategies {
+            strategies: Vec::new(),
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for CustomStrategies<T> {
+    fn clone(&self) -> Self {
+        CustomStrategies {
+            strategies: self.strategies.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized> std::fmt::Debug for CustomStrategies<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.strategies.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+impl<T: ?Sized> CustomStrategies<T> {
+    /// Registers the strategy, replacing the strategy with the same name
+    pub fn insert(&mut self, name: &'static str, strategy: Arc<T>) {
+        self.strategies.retain(|(existing, _)| *existing != name);
+        self.strategies.push((name, strategy));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.strategies
+            .iter()
+            .find(|(existing, _)| *existing == name)
+            .map(|(_, strategy)| strategy.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use rand::RngCore;
+
+    use crate::{
+        problem::vehicle::VehicleBuilder,
+        solver::{
+            alns::Alns,
+            recreate::{
+                best_insertion::BestInsertionSortStrategy,
+                recreate_context::RecreateContext,
+                recreate_solution::{CustomRecreateSolution, RecreateSolution},
+                recreate_strategy::RecreateStrategy,
+            },
+            ruin::{
+                ruin_context::RuinContext,
+                ruin_solution::{CustomRuinSolution, RuinSolution},
+                ruin_strategy::RuinStrategy,
+            },
+            solution::working_solution::WorkingSolution,
+            solver_params::{SolverParams, SolverParamsError, Termination, Threads},
+        },
+        test_utils,
+    };
+
+    struct CountingRuin(Arc<AtomicUsize>);
+
+    impl CustomRuinSolution for CountingRuin {
+        fn name(&self) -> &'static str {
+            "CountingRuin"
+        }
+
+        fn ruin_solution<'a>(
+            &self,
+            solution: &mut WorkingSolution,
+            mut context: RuinContext<'a, dyn RngCore + 'a>,
+        ) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            RuinStrategy::Random.ruin_solution(
+                solution,
+                RuinContext {
+                    params: context.params,
+                    problem: context.problem,
+                    rng: &mut context.rng,
+                    num_jobs_to_remove: context.num_jobs_to_remove,
+                },
+            );
+        }
+    }
+
+    struct CountingRecreate(Arc<AtomicUsize>);
+
+    impl CustomRecreateSolution for CountingRecreate {
+        fn name(&self) -> &'static str {
+            "CountingRecreate"
+        }
+
+        fn recreate_solution(&self, solution: &mut WorkingSolution, context: RecreateContext) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            RecreateStrategy::BestInsertion(BestInsertionSortStrategy::Random)
+                .recreate_solution(solution, context);
+        }
+    }
+
+    #[test]
+    fn test_custom_strategies_are_selected() {
+        let locations = test_utils::create_location_grid(5, 5);
+        let services = test_utils::create_basic_services((1..25).collect());
+
+        let mut builder = VehicleBuilder::default();
+        builder.set_depot_location_id(0);
+        builder.set_vehicle_id(String::from("vehicle"));
+        builder.set_profile_id(0);
+
+        let problem = test_utils::create_test_problem(locations, services, vec![builder.build()]);
+
+        let ruin_count = Arc::new(AtomicUsize::new(0));
+        let recreate_count = Arc::new(AtomicUsize::new(0));
+
+        let mut params = SolverParams {
+            terminations: vec![Termination::Iterations(200)],
+            search_threads: Threads::Single,
+            insertion_threads: Threads::Single,
+            ..SolverParams::default()
+        };
+
+        // Only keep the custom strategies so that every iteration uses them
+        params.ruin.ruin_strategies.clear();
+        params.recreate.recreate_strategies.clear();
+        let ruin_strategy = params
+            .ruin
+            .register_custom(CountingRuin(Arc::clone(&ruin_count)));
+        let recreate_strategy = params
+            .recreate
+            .register_custom(CountingRecreate(Arc::clone(&recreate_count)));
+
+        assert_eq!(ruin_strategy.to_string(), "CountingRuin");
+        assert_eq!(
+            recreate_strategy,
+            RecreateStrategy::Custom("CountingRecreate")
+        );
+
+        let alns = Alns::new(params, Arc::new(problem)).unwrap();
+        alns.run().unwrap();
+
+        assert!(ruin_count.load(Ordering::Relaxed) > 0);
+        assert!(recreate_count.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_unregistered_custom_strategy() {
+        let problem = test_utils::create_test_problem(
+            test_utils::create_location_grid(3, 3),
+            test_utils::create_basic_services(vec![1, 2, 3]),
+            test_utils::create_basic_vehicles(vec![0]),
+        );
+
+        let mut params = SolverParams::default();
+        params
+            .ruin
+            .ruin_strategies
+            .push(RuinStrategy::Custom("Missing"));
+
+        assert_eq!(
+            Alns::new(params, Arc::new(problem)).err(),
+            Some(SolverParamsError::UnregisteredRuinStrategy("Missing"))
+        );
+    }
+}
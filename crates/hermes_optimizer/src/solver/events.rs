@@ -0,0 +1,101 @@
+use jiff::{SignedDuration, Timestamp};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::problem::meters::Meters;
+
+use super::{
+    accepted_solution::AcceptedSolution, alns_weights::AlnsWeights,
+    recreate::recreate_strategy::RecreateStrategy, ruin::ruin_strategy::RuinStrategy, score::Score,
+    solver_params::Termination,
+};
+
+/// Maximum number of events buffered for a slow subscriber before it starts missing events
+pub const EVENTS_CAPACITY: usize = 64;
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct RouteSummary {
+    pub vehicle_id: String,
+    pub activities: usize,
+    pub distance: Meters,
+    pub duration: SignedDuration,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationReason {
+    /// The solver was stopped before any termination condition was met
+    Stopped,
+    Duration,
+    Iterations,
+    IterationsWithoutImprovement,
+    Score,
+    VehiclesAndCosts,
+    Stagnation,
+}
+
+impl From<&Termination> for TerminationReason {
+    fn from(termination: &Termination) -> Self {
+        match termination {
+            Termination::Duration(_) => TerminationReason::Duration,
+            Termination::Iterations(_) => TerminationReason::Iterations,
+            Termination::IterationsWithoutImprovement(_) => {
+                TerminationReason::IterationsWithoutImprovement
+            }
+            Termination::Score(_) => TerminationReason::Score,
+            Termination::VehiclesAndCosts { .. } => TerminationReason::VehiclesAndCosts,
+            Termination::Stagnation { .. } => TerminationReason::Stagnation,
+        }
+    }
+}
+
+/// Events emitted by the solver while it runs, subscribers receive them through a broadcast channel
+#[derive(Serialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SolverEvent {
+    ConstructionFinished {
+        score: Score,
+        unassigned_jobs: usize,
+        duration: SignedDuration,
+    },
+    BestSolution {
+        timestamp: Timestamp,
+        score: Score,
+        unassigned_jobs: usize,
+        routes: Vec<RouteSummary>,
+    },
+    Weights {
+        ruin: AlnsWeights<RuinStrategy>,
+        recreate: AlnsWeights<RecreateStrategy>,
+    },
+    Progress {
+        timestamp: Timestamp,
+        iterations: usize,
+        best_score: Option<Score>,
+    },
+    Terminated {
+        reason: TerminationReason,
+    },
+}
+
+impl SolverEvent {
+    pub fn best_solution(accepted_solution: &AcceptedSolution) -> Self {
+        let solution = &accepted_solution.solution;
+        let problem = solution.problem();
+
+        SolverEvent::BestSolution {
+            timestamp: Timestamp::now(),
+            score: accepted_solution.score,
+            unassigned_jobs: solution.unassigned_jobs().len(),
+            routes: solution
+                .non_empty_routes_iter()
+                .map(|route| RouteSummary {
+                    vehicle_id: route.vehicle(problem).external_id().to_owned(),
+                    activities: route.len(),
+                    distance: route.distance(problem),
+                    duration: route.duration(problem),
+                })
+                .collect(),
+        }
+    }
+}
@@ -124,6 +124,18 @@ pub fn for_each_route_insertion(
     }
 }
 
+/// Insertion range of the activity in the route, restricted by its task dependencies and stop sequence
+fn insertion_range(
+    problem: &VehicleRoutingProblem,
+    route: &WorkingSolutionRoute,
+    activity_id: ActivityId,
+) -> (usize, usize) {
+    let (start, end) = route.insertion_range(activity_id);
+    let (sequence_start, sequence_end) = route.stop_sequence_range(problem, activity_id);
+
+    (start.max(sequence_start), end.min(sequence_end))
+}
+
 fn route_with_dependencies(
     problem: &VehicleRoutingProblem,
     solution: &WorkingSolution,
@@ -174,12 +186,14 @@ fn for_each_service_insertion(
                 return;
             }
 
-            let (start, end) = route.insertion_range(ActivityId::Service(job_index));
+            let (start, end) =
+                insertion_range(solution.problem(), route, ActivityId::Service(job_index));
 
             (start..=end)
                 .filter(|position| {
                     route.in_insertion_neighborhood(
                         solution.problem(),
+                        solution.neighborhood_size(),
                         ActivityId::Service(job_index),
                         *position,
                     )
@@ -218,11 +232,12 @@ fn for_each_route_service_insertion(
         return;
     }
 
-    let (start, end) = route.insertion_range(ActivityId::Service(job_index));
+    let (start, end) = insertion_range(solution.problem(), route, ActivityId::Service(job_index));
 
     for position in start..=end {
         if !route.in_insertion_neighborhood(
             solution.problem(),
+            solution.neighborhood_size(),
             ActivityId::Service(job_index),
             position,
         ) {
@@ -251,14 +266,21 @@ fn for_each_shipment_insertion(
             continue;
         }
 
-        let (start_pickup, end_pickup) =
-            route.insertion_range(ActivityId::ShipmentPickup(job_index));
-        let (start_delivery, end_delivery) =
-            route.insertion_range(ActivityId::ShipmentDelivery(job_index));
+        let (start_pickup, end_pickup) = insertion_range(
+            solution.problem(),
+            route,
+            ActivityId::ShipmentPickup(job_index),
+        );
+        let (start_delivery, end_delivery) = insertion_range(
+            solution.problem(),
+            route,
+            ActivityId::ShipmentDelivery(job_index),
+        );
 
         for pickup_position in start_pickup..=end_pickup {
             if !route.in_insertion_neighborhood(
                 solution.problem(),
+                solution.neighborhood_size(),
                 ActivityId::ShipmentPickup(job_index),
                 pickup_position,
             ) {
@@ -268,6 +290,7 @@ fn for_each_shipment_insertion(
             for delivery_position in (pickup_position.max(start_delivery))..=end_delivery {
                 if !route.in_insertion_neighborhood(
                     solution.problem(),
+                    solution.neighborhood_size(),
                     ActivityId::ShipmentDelivery(job_index),
                     delivery_position,
                 ) {
@@ -308,13 +331,21 @@ fn for_each_route_shipment_insertion(
         return;
     }
 
-    let (start_pickup, end_pickup) = route.insertion_range(ActivityId::ShipmentPickup(job_index));
-    let (start_delivery, end_delivery) =
-        route.insertion_range(ActivityId::ShipmentDelivery(job_index));
+    let (start_pickup, end_pickup) = insertion_range(
+        solution.problem(),
+        route,
+        ActivityId::ShipmentPickup(job_index),
+    );
+    let (start_delivery, end_delivery) = insertion_range(
+        solution.problem(),
+        route,
+        ActivityId::ShipmentDelivery(job_index),
+    );
 
     for pickup_position in start_pickup..=end_pickup {
         if !route.in_insertion_neighborhood(
             solution.problem(),
+            solution.neighborhood_size(),
             ActivityId::ShipmentPickup(job_index),
             pickup_position,
         ) {
@@ -324,6 +355,7 @@ fn for_each_route_shipment_insertion(
         for delivery_position in (pickup_position.max(start_delivery))..=end_delivery {
             if !route.in_insertion_neighborhood(
                 solution.problem(),
+                solution.neighborhood_size(),
                 ActivityId::ShipmentDelivery(job_index),
                 delivery_position,
             ) {
@@ -0,0 +1,137 @@
+use jiff::SignedDuration;
+use tracing::debug;
+
+/// Problems with fewer jobs start with sequential insertions
+const PARALLEL_MIN_JOBS: usize = 200;
+
+/// Below this mean insertion duration, the parallel insertions spend more time synchronizing the
+/// threads than inserting
+const SEQUENTIAL_BELOW_MICROS: f64 = 2_000.0;
+
+/// Above this mean insertion duration, the sequential insertions are worth splitting across threads
+const PARALLEL_ABOVE_MICROS: f64 = 20_000.0;
+
+const DURATION_SMOOTHING: f64 = 0.1;
+
+/// Thread pool in which a search thread runs its ruin and recreate steps, owned by the search thread
+/// for the whole run.
+///
+/// Switches between a single thread and `max_threads` threads depending on the mean recreate
+/// duration, the gap between both thresholds avoids switching at every iteration.
+pub struct InsertionThreadPool {
+    sequential: rayon::ThreadPool,
+    /// Built the first time the insertions switch to parallel
+    parallel: Option<rayon::ThreadPool>,
+    max_threads: usize,
+    is_parallel: bool,
+    mean_insertion_micros: Option<f64>,
+}
+
+impl InsertionThreadPool {
+    pub fn new(max_threads: usize, number_of_jobs: usize) -> Self {
+        let mut pool = InsertionThreadPool {
+            sequential: build_thread_pool(1),
+            parallel: None,
+            max_threads,
+            is_parallel: false,
+            mean_insertion_micros: None,
+        };
+
+        if number_of_jobs >= PARALLEL_MIN_JOBS {
+            pool.set_parallel(true);
+        }
+
+        pool
+    }
+
+    pub fn install<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match &self.parallel {
+            Some(parallel) if self.is_parallel => parallel.install(op),
+            _ => self.sequential.install(op),
+        }
+    }
+
+    pub fn is_parallel(&self) -> bool {
+        self.is_parallel
+    }
+
+    pub fn record_insertion_duration(&mut self, duration: SignedDuration) {
+        let micros = duration.as_micros() as f64;
+        let mean = match self.mean_insertion_micros {
+            Some(mean) => mean + DURATION_SMOOTHING * (micros - mean),
+            None => micros,
+        };
+        self.mean_insertion_micros = Some(mean);
+
+        if self.is_parallel && mean < SEQUENTIAL_BELOW_MICROS {
+            self.set_parallel(false);
+        } else if !self.is_parallel && mean > PARALLEL_ABOVE_MICROS {
+            self.set_parallel(true);
+        }
+    }
+
+    fn set_parallel(&mut self, is_parallel: bool) {
+        if self.max_threads <= 1 {
+            return;
+        }
+
+        if is_parallel && self.parallel.is_none() {
+            self.parallel = Some(build_thread_pool(self.max_threads));
+        }
+
+        if self.is_parallel != is_parallel {
+            debug!(
+                "Switching insertions to {} thread(s)",
+                if is_parallel { self.max_threads } else { 1 }
+            );
+        }
+
+        self.is_parallel = is_parallel;
+        // The mean of the previous mode does not tell anything about the new one
+        self.mean_insertion_micros = None;
+    }
+}
+
+fn build_thread_pool(num_threads: usize) -> rayon::ThreadPool {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use super::InsertionThreadPool;
+
+    #[test]
+    fn test_initial_mode_from_number_of_jobs() {
+        assert!(!InsertionThreadPool::new(4, 50).is_parallel());
+        assert!(InsertionThreadPool::new(4, 1000).is_parallel());
+        assert!(!InsertionThreadPool::new(1, 1000).is_parallel());
+    }
+
+    #[test]
+    fn test_switches_on_insertion_duration() {
+        let mut pool = InsertionThreadPool::new(4, 50);
+
+        pool.record_insertion_duration(SignedDuration::from_millis(50));
+        assert!(pool.is_parallel());
+
+        // A single fast iteration does not switch back
+        pool.record_insertion_duration(SignedDuration::from_millis(15));
+        assert!(pool.is_parallel());
+
+        for _ in 0..50 {
+            pool.record_insertion_duration(SignedDuration::from_micros(100));
+        }
+        assert!(!pool.is_parallel());
+
+        assert_eq!(pool.install(rayon::current_num_threads), 1);
+    }
+}
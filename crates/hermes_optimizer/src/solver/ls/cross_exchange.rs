@@ -108,7 +108,9 @@ impl LocalSearchOperator for CrossExchangeOperator {
                         continue;
                     }
 
-                    let from_previous = from_route.get(from_pos - 1);
+                    let from_previous = from_pos
+                        .checked_sub(1)
+                        .and_then(|index| from_route.get(index));
                     let from_start = from_route.activity_id(from_pos);
                     let from_next = from_route.get(from_pos + from_length);
 
@@ -117,40 +119,59 @@ impl LocalSearchOperator for CrossExchangeOperator {
                             continue;
                         }
 
-                        let to_previous = to_route.get(to_pos - 1);
+                        let to_previous =
+                            to_pos.checked_sub(1).and_then(|index| to_route.get(index));
                         let to_start = to_route.activity_id(to_pos);
                         let to_next = to_route.get(to_pos + to_length);
 
-                        if from_route
-                            .will_break_maximum_activities(problem, to_length - from_length)
-                            || to_route
-                                .will_break_maximum_activities(problem, from_length - to_length)
-                        {
+                        if from_route.will_break_maximum_activities(
+                            problem,
+                            to_length.saturating_sub(from_length),
+                        ) || to_route.will_break_maximum_activities(
+                            problem,
+                            from_length.saturating_sub(to_length),
+                        ) {
                             continue;
                         }
 
                         // Neighborhoods checks
 
                         if let Some(from_previous) = from_previous
-                            && !problem.in_nearest_neighborhood_of(from_previous, to_start)
+                            && !problem.in_nearest_neighborhood_of(
+                                from_previous,
+                                to_start,
+                                solution.neighborhood_size(),
+                            )
                         {
                             continue;
                         }
 
                         if let Some(from_next) = from_next
-                            && !problem.in_nearest_neighborhood_of(from_next, to_start)
+                            && !problem.in_nearest_neighborhood_of(
+                                from_next,
+                                to_start,
+                                solution.neighborhood_size(),
+                            )
                         {
                             continue;
                         }
 
                         if let Some(to_previous) = to_previous
-                            && !problem.in_nearest_neighborhood_of(to_previous, from_start)
+                            && !problem.in_nearest_neighborhood_of(
+                                to_previous,
+                                from_start,
+                                solution.neighborhood_size(),
+                            )
                         {
                             continue;
                         }
 
                         if let Some(to_next) = to_next
-                            && !problem.in_nearest_neighborhood_of(to_next, from_start)
+                            && !problem.in_nearest_neighborhood_of(
+                                to_next,
+                                from_start,
+                                solution.neighborhood_size(),
+                            )
                         {
                             continue;
                         }
@@ -121,6 +121,7 @@ impl LocalSearchOperator for InterMixedExchange {
 
                 if !route1.in_swap_neighborhood(
                     problem,
+                    solution.neighborhood_size(),
                     position,
                     position + 1,
                     route2,
@@ -128,6 +129,7 @@ impl LocalSearchOperator for InterMixedExchange {
                     segment_start + segment_length,
                 ) || !route2.in_swap_neighborhood(
                     problem,
+                    solution.neighborhood_size(),
                     segment_start,
                     segment_start + segment_length,
                     route1,
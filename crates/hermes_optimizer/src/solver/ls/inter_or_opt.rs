@@ -100,9 +100,13 @@ impl LocalSearchOperator for InterOrOptOperator {
                 let from_end = from_route.activity_id(from_pos + segment_length - 1);
 
                 for to_pos in 0..=to_route.activity_ids().len() {
-                    if !to_route
-                        .in_segment_insertion_neighborhood(problem, from_start, from_end, to_pos)
-                    {
+                    if !to_route.in_segment_insertion_neighborhood(
+                        problem,
+                        solution.neighborhood_size(),
+                        from_start,
+                        from_end,
+                        to_pos,
+                    ) {
                         continue;
                     }
 
@@ -161,13 +165,17 @@ impl LocalSearchOperator for InterOrOptOperator {
         let r2 = solution.route(self.params.to_route_id);
 
         let r1_change = if r1.len() == self.params.segment_length {
-            -solution.problem().fixed_vehicle_costs()
+            -solution
+                .problem()
+                .fixed_vehicle_cost(r1.vehicle(solution.problem()))
         } else {
             0.0
         };
 
         let r2_change = if r2.is_empty() {
-            solution.problem().fixed_vehicle_costs()
+            solution
+                .problem()
+                .fixed_vehicle_cost(r2.vehicle(solution.problem()))
         } else {
             0.0
         };
@@ -82,7 +82,12 @@ impl LocalSearchOperator for InterRelocateOperator {
             let from_activity_id = from_route.activity_id(from_pos);
 
             for to_pos in 0..=to_route.activity_ids().len() {
-                if !to_route.in_insertion_neighborhood(problem, from_activity_id, to_pos) {
+                if !to_route.in_insertion_neighborhood(
+                    problem,
+                    solution.neighborhood_size(),
+                    from_activity_id,
+                    to_pos,
+                ) {
                     continue;
                 }
 
@@ -132,13 +137,17 @@ impl LocalSearchOperator for InterRelocateOperator {
         let r2 = solution.route(self.params.to_route_id);
 
         let r1_change = if r1.len() == 1 {
-            -solution.problem().fixed_vehicle_costs()
+            -solution
+                .problem()
+                .fixed_vehicle_cost(r1.vehicle(solution.problem()))
         } else {
             0.0
         };
 
         let r2_change = if r2.is_empty() {
-            solution.problem().fixed_vehicle_costs()
+            solution
+                .problem()
+                .fixed_vehicle_cost(r2.vehicle(solution.problem()))
         } else {
             0.0
         };
@@ -148,15 +148,17 @@ impl LocalSearchOperator for InterReverseTwoOptOperator {
                     continue;
                 }
 
-                if from_route
-                    .will_break_maximum_activities(problem, to_head_length - from_tail_length)
-                {
+                if from_route.will_break_maximum_activities(
+                    problem,
+                    to_head_length.saturating_sub(from_tail_length),
+                ) {
                     continue;
                 }
 
-                if to_route
-                    .will_break_maximum_activities(problem, from_tail_length - to_head_length)
-                {
+                if to_route.will_break_maximum_activities(
+                    problem,
+                    from_tail_length.saturating_sub(to_head_length),
+                ) {
                     continue;
                 }
 
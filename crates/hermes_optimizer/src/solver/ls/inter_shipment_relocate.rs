@@ -0,0 +1,434 @@
+use tracing::{Level, instrument};
+
+use crate::{
+    problem::{job::ActivityId, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        ls::r#move::LocalSearchOperator,
+        solution::{
+            route::WorkingSolutionRoute, route_id::RouteIdx, working_solution::WorkingSolution,
+        },
+    },
+};
+
+/// **Inter-Route Shipment Relocate**
+///
+/// Moves the pickup `P` at `pickup` and the delivery `D` at `delivery` of a shipment in
+/// `from_route_id` together to `to_pickup` and `to_delivery` in `to_route_id`.
+/// The other operators move the activities one by one and can't move a shipment to another route.
+///
+/// ```text
+/// BEFORE:
+///    R1: ... (A) -> [P] -> (B) ... (C) -> [D] -> (E) ...
+///    R2: ... (X) -> (Y) ... (Z) -> (W) ...
+///
+/// AFTER:
+///    R1: ... (A) -> (B) ... (C) -> (E) ...
+///    R2: ... (X) -> [P] -> (Y) ... (Z) -> [D] -> (W) ...
+/// ```
+#[derive(Debug)]
+pub struct InterShipmentRelocateOperator {
+    params: InterShipmentRelocateParams,
+}
+
+#[derive(Debug)]
+pub struct InterShipmentRelocateParams {
+    pub from_route_id: RouteIdx,
+    pub to_route_id: RouteIdx,
+    pub pickup: usize,
+    pub delivery: usize,
+
+    /// Positions in `to_route_id` before the move, like `ShipmentInsertion`, the delivery directly
+    /// follows the pickup when `to_delivery == to_pickup`
+    pub to_pickup: usize,
+    pub to_delivery: usize,
+}
+
+impl InterShipmentRelocateOperator {
+    pub fn new(params: InterShipmentRelocateParams) -> Self {
+        if params.from_route_id == params.to_route_id {
+            panic!("InterShipmentRelocateOperator cannot be used for intra-route relocation");
+        }
+
+        if params.pickup >= params.delivery || params.to_pickup > params.to_delivery {
+            panic!("InterShipmentRelocateOperator pickup must be before the delivery");
+        }
+
+        Self { params }
+    }
+
+    fn pickup_id(&self, solution: &WorkingSolution) -> ActivityId {
+        solution
+            .route(self.params.from_route_id)
+            .activity_id(self.params.pickup)
+    }
+
+    fn delivery_id(&self, solution: &WorkingSolution) -> ActivityId {
+        solution
+            .route(self.params.from_route_id)
+            .activity_id(self.params.delivery)
+    }
+
+    /// Activities between the pickup and the delivery in the source route
+    fn source_activity_ids<'a>(
+        &self,
+        route: &'a WorkingSolutionRoute,
+    ) -> impl DoubleEndedIterator<Item = ActivityId> + Clone + 'a {
+        route.activity_ids_iter(self.params.pickup + 1, self.params.delivery)
+    }
+
+    /// Activities replacing `[to_pickup, to_delivery)` in the target route
+    fn target_activity_ids<'a>(
+        &self,
+        solution: &'a WorkingSolution,
+    ) -> impl DoubleEndedIterator<Item = ActivityId> + Clone + 'a {
+        let target_route = solution.route(self.params.to_route_id);
+
+        std::iter::once(self.pickup_id(solution))
+            .chain(target_route.activity_ids_iter(self.params.to_pickup, self.params.to_delivery))
+            .chain(std::iter::once(self.delivery_id(solution)))
+    }
+
+    fn removal_cost_delta(
+        problem: &VehicleRoutingProblem,
+        route: &WorkingSolutionRoute,
+        position: usize,
+    ) -> f64 {
+        let vehicle = route.vehicle(problem);
+        let previous = route.previous_location_id(problem, position);
+        let current = route.location_id(problem, position);
+        let next = route
+            .location_id(problem, position + 1)
+            .or_else(|| route.end_location(problem));
+
+        problem.travel_cost_or_zero(vehicle, previous, next)
+            - problem.travel_cost_or_zero(vehicle, previous, current)
+            - problem.travel_cost_or_zero(vehicle, current, next)
+    }
+}
+
+impl LocalSearchOperator for InterShipmentRelocateOperator {
+    #[instrument(skip_all,level = Level::TRACE)]
+    fn generate_moves<C>(
+        problem: &VehicleRoutingProblem,
+        solution: &WorkingSolution,
+        (r1, r2): (RouteIdx, RouteIdx),
+        mut consumer: C,
+    ) where
+        C: FnMut(Self),
+    {
+        if !problem.has_shipments() {
+            return;
+        }
+
+        if r1 == r2 {
+            return;
+        }
+
+        let from_route = solution.route(r1);
+        let to_route = solution.route(r2);
+
+        if to_route.will_break_maximum_activities(problem, 2) {
+            return;
+        }
+
+        for pickup_pos in 0..from_route.len() {
+            let pickup_id = from_route.activity_id(pickup_pos);
+            let ActivityId::ShipmentPickup(job_index) = pickup_id else {
+                continue;
+            };
+
+            if from_route.is_locked(pickup_pos) || !to_route.can_deliver_job(problem, job_index) {
+                continue;
+            }
+
+            let delivery_pos = from_route.matching_shipment_position(pickup_id);
+            let delivery_id = ActivityId::ShipmentDelivery(job_index);
+
+            for to_pickup in 0..=to_route.len() {
+                if !to_route.in_insertion_neighborhood(
+                    problem,
+                    solution.neighborhood_size(),
+                    pickup_id,
+                    to_pickup,
+                ) {
+                    continue;
+                }
+
+                for to_delivery in to_pickup..=to_route.len() {
+                    if !to_route.in_insertion_neighborhood(
+                        problem,
+                        solution.neighborhood_size(),
+                        delivery_id,
+                        to_delivery,
+                    ) {
+                        continue;
+                    }
+
+                    consumer(InterShipmentRelocateOperator::new(
+                        InterShipmentRelocateParams {
+                            from_route_id: r1,
+                            to_route_id: r2,
+                            pickup: pickup_pos,
+                            delivery: delivery_pos,
+                            to_pickup,
+                            to_delivery,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    fn transport_cost_delta(&self, solution: &WorkingSolution) -> f64 {
+        let problem = solution.problem();
+        let r1 = solution.route(self.params.from_route_id);
+        let r2 = solution.route(self.params.to_route_id);
+        let v1 = r1.vehicle(problem);
+        let v2 = r2.vehicle(problem);
+
+        let pickup = r1.location_id(problem, self.params.pickup);
+        let delivery = r1.location_id(problem, self.params.delivery);
+
+        let mut delta = 0.0;
+
+        // Removal from the source route, the edges are shared when the delivery follows the pickup
+        if self.params.delivery == self.params.pickup + 1 {
+            let a = r1.previous_location_id(problem, self.params.pickup);
+            let b = r1
+                .location_id(problem, self.params.delivery + 1)
+                .or_else(|| r1.end_location(problem));
+
+            delta -= problem.travel_cost_or_zero(v1, a, pickup);
+            delta -= problem.travel_cost_or_zero(v1, pickup, delivery);
+            delta -= problem.travel_cost_or_zero(v1, delivery, b);
+            delta += problem.travel_cost_or_zero(v1, a, b);
+        } else {
+            delta += Self::removal_cost_delta(problem, r1, self.params.pickup);
+            delta += Self::removal_cost_delta(problem, r1, self.params.delivery);
+        }
+
+        // Insertion in the target route
+        let x = r2.previous_location_id(problem, self.params.to_pickup);
+        let y = r2
+            .location_id(problem, self.params.to_pickup)
+            .or_else(|| r2.end_location(problem));
+
+        if self.params.to_pickup == self.params.to_delivery {
+            delta += problem.travel_cost_or_zero(v2, x, pickup);
+            delta += problem.travel_cost_or_zero(v2, pickup, delivery);
+            delta += problem.travel_cost_or_zero(v2, delivery, y);
+            delta -= problem.travel_cost_or_zero(v2, x, y);
+        } else {
+            let z = r2.previous_location_id(problem, self.params.to_delivery);
+            let w = r2
+                .location_id(problem, self.params.to_delivery)
+                .or_else(|| r2.end_location(problem));
+
+            delta += problem.travel_cost_or_zero(v2, x, pickup);
+            delta += problem.travel_cost_or_zero(v2, pickup, y);
+            delta -= problem.travel_cost_or_zero(v2, x, y);
+
+            delta += problem.travel_cost_or_zero(v2, z, delivery);
+            delta += problem.travel_cost_or_zero(v2, delivery, w);
+            delta -= problem.travel_cost_or_zero(v2, z, w);
+        }
+
+        delta
+    }
+
+    fn fixed_route_cost_delta(&self, solution: &WorkingSolution) -> f64 {
+        let problem = solution.problem();
+        let r1 = solution.route(self.params.from_route_id);
+        let r2 = solution.route(self.params.to_route_id);
+
+        let r1_change = if r1.len() == 2 {
+            -problem.fixed_vehicle_cost(r1.vehicle(problem))
+        } else {
+            0.0
+        };
+
+        let r2_change = if r2.is_empty() {
+            problem.fixed_vehicle_cost(r2.vehicle(problem))
+        } else {
+            0.0
+        };
+
+        r1_change + r2_change
+    }
+
+    fn waiting_cost_delta(&self, solution: &WorkingSolution) -> f64 {
+        let problem = solution.problem();
+        let source_route = solution.route(self.params.from_route_id);
+        let target_route = solution.route(self.params.to_route_id);
+
+        problem.waiting_duration_cost(
+            target_route.waiting_duration_change_delta(
+                problem,
+                self.target_activity_ids(solution),
+                self.params.to_pickup,
+                self.params.to_delivery,
+            ) + source_route.waiting_duration_change_delta(
+                problem,
+                self.source_activity_ids(source_route),
+                self.params.pickup,
+                self.params.delivery + 1,
+            ),
+        )
+    }
+
+    fn is_valid(&self, solution: &WorkingSolution) -> bool {
+        let problem = solution.problem();
+        let source_route = solution.route(self.params.from_route_id);
+        let target_route = solution.route(self.params.to_route_id);
+
+        target_route.is_valid_change(
+            problem,
+            self.target_activity_ids(solution),
+            self.params.to_pickup,
+            self.params.to_delivery,
+        ) && source_route.is_valid_change(
+            problem,
+            self.source_activity_ids(source_route),
+            self.params.pickup,
+            self.params.delivery + 1,
+        )
+    }
+
+    fn apply(&self, problem: &VehicleRoutingProblem, solution: &mut WorkingSolution) {
+        let target_activity_ids = self.target_activity_ids(solution).collect::<Vec<_>>();
+        let source_activity_ids = self
+            .source_activity_ids(solution.route(self.params.from_route_id))
+            .collect::<Vec<_>>();
+
+        solution
+            .route_mut(self.params.from_route_id)
+            .replace_activities(
+                problem,
+                &source_activity_ids,
+                self.params.pickup,
+                self.params.delivery + 1,
+            );
+
+        solution
+            .route_mut(self.params.to_route_id)
+            .replace_activities(
+                problem,
+                &target_activity_ids,
+                self.params.to_pickup,
+                self.params.to_delivery,
+            );
+    }
+
+    fn updated_routes(&self) -> Vec<RouteIdx> {
+        vec![self.params.from_route_id, self.params.to_route_id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::ActivityId,
+            shipment::ShipmentBuilder,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::VehicleRoutingProblemBuilder,
+        },
+        solver::{
+            insertion::{Insertion, ShipmentInsertion},
+            ls::{
+                inter_shipment_relocate::{
+                    InterShipmentRelocateOperator, InterShipmentRelocateParams,
+                },
+                r#move::LocalSearchOperator,
+            },
+            solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+        },
+        test_utils,
+    };
+
+    #[test]
+    fn test_inter_shipment_relocate() {
+        let locations = test_utils::create_location_grid(5, 5);
+        let shipments = [(1, 2), (3, 4), (6, 7)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (pickup, delivery))| {
+                let mut builder = ShipmentBuilder::default();
+                builder.set_external_id(index.to_string());
+                builder.set_pickup_location_id(pickup);
+                builder.set_delivery_location_id(delivery);
+                builder.build()
+            })
+            .collect();
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_shipments(shipments);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(test_utils::create_basic_vehicles(vec![0, 0])));
+        let problem = Arc::new(builder.build().unwrap());
+
+        let mut solution = WorkingSolution::new(Arc::clone(&problem));
+        // R0: P0 P1 D0 D1, R1: P2 D2
+        for (route_id, job_index, pickup_position, delivery_position) in
+            [(0, 0, 0, 0), (0, 1, 1, 2), (1, 2, 0, 0)]
+        {
+            solution.insert(&Insertion::Shipment(ShipmentInsertion {
+                route_id: RouteIdx::new(route_id),
+                job_index: job_index.into(),
+                pickup_position,
+                delivery_position,
+            }));
+        }
+
+        // Moves shipment 1 around shipment 2
+        let operator = InterShipmentRelocateOperator::new(InterShipmentRelocateParams {
+            from_route_id: 0.into(),
+            to_route_id: 1.into(),
+            pickup: 1,
+            delivery: 3,
+            to_pickup: 0,
+            to_delivery: 2,
+        });
+
+        assert!(operator.is_valid(&solution));
+
+        let distances = solution.route(0.into()).transport_costs(&problem)
+            + solution.route(1.into()).transport_costs(&problem);
+        let delta = operator.transport_cost_delta(&solution);
+        operator.apply(&problem, &mut solution);
+        assert!(
+            (solution.route(0.into()).transport_costs(&problem)
+                + solution.route(1.into()).transport_costs(&problem)
+                - (distances + delta))
+                .abs()
+                < 1e-9
+        );
+
+        assert_eq!(
+            solution.route(0.into()).activity_ids(),
+            &[
+                ActivityId::ShipmentPickup(0.into()),
+                ActivityId::ShipmentDelivery(0.into()),
+            ],
+        );
+        assert_eq!(
+            solution.route(1.into()).activity_ids(),
+            &[
+                ActivityId::ShipmentPickup(1.into()),
+                ActivityId::ShipmentPickup(2.into()),
+                ActivityId::ShipmentDelivery(2.into()),
+                ActivityId::ShipmentDelivery(1.into()),
+            ],
+        );
+    }
+}
@@ -86,6 +86,7 @@ impl LocalSearchOperator for InterSwapOperator {
 
                 if !from_route.in_swap_neighborhood(
                     problem,
+                    solution.neighborhood_size(),
                     from_pos,
                     from_pos + 1,
                     to_route,
@@ -93,6 +94,7 @@ impl LocalSearchOperator for InterSwapOperator {
                     to_pos + 1,
                 ) || !to_route.in_swap_neighborhood(
                     problem,
+                    solution.neighborhood_size(),
                     to_pos,
                     to_pos + 1,
                     from_route,
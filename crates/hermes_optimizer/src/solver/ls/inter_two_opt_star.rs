@@ -152,15 +152,17 @@ impl LocalSearchOperator for InterTwoOptStarOperator {
                     continue;
                 }
 
-                if from_route
-                    .will_break_maximum_activities(problem, to_tail_length - from_tail_length)
-                {
+                if from_route.will_break_maximum_activities(
+                    problem,
+                    to_tail_length.saturating_sub(from_tail_length),
+                ) {
                     continue;
                 }
 
-                if to_route
-                    .will_break_maximum_activities(problem, from_tail_length - to_tail_length)
-                {
+                if to_route.will_break_maximum_activities(
+                    problem,
+                    from_tail_length.saturating_sub(to_tail_length),
+                ) {
                     continue;
                 }
 
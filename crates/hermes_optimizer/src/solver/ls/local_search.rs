@@ -6,7 +6,7 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use tracing::{debug, instrument, trace, warn};
 
 use crate::{
-    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    problem::{location::LocationIdx, vehicle_routing_problem::VehicleRoutingProblem},
     solver::{
         constraints::constraint::Constraint,
         ls::{
@@ -15,6 +15,7 @@ use crate::{
             inter_or_opt::InterOrOptOperator,
             inter_relocate::InterRelocateOperator,
             inter_reverse_two_opt::InterReverseTwoOptOperator,
+            inter_shipment_relocate::InterShipmentRelocateOperator,
             inter_swap::InterSwapOperator,
             inter_two_opt_star::InterTwoOptStarOperator,
             mixed_exchange::MixedExchangeOperator,
@@ -22,7 +23,7 @@ use crate::{
             or_opt::OrOptOperator,
             relocate::RelocateOperator,
             swap::SwapOperator,
-            swap_star::find_best_swap_star_move,
+            swap_star::{find_best_shipment_swap_star_move, find_best_swap_star_move},
             two_opt::TwoOptOperator,
         },
         score::RUN_SCORE_ASSERTIONS,
@@ -208,6 +209,14 @@ impl LocalSearch {
                     }
                 });
 
+                InterShipmentRelocateOperator::generate_moves(problem, solution, (r1, r2), |op| {
+                    let delta = op.delta(solution);
+                    if delta < best_delta && op.is_valid(solution) {
+                        best_delta = delta;
+                        best_move = Some(LocalSearchMove::InterShipmentRelocate(op));
+                    }
+                });
+
                 InterOrOptOperator::generate_moves(problem, solution, (r1, r2), |op| {
                     let delta = op.delta(solution);
                     if delta < best_delta && op.is_valid(solution) {
@@ -250,6 +259,19 @@ impl LocalSearch {
                     }
                 }
 
+                if let Some(swap_star) = find_best_shipment_swap_star_move(
+                    problem,
+                    solution,
+                    &self.constraints,
+                    (r1, r2),
+                ) {
+                    let delta = swap_star.delta(solution);
+                    if delta < best_delta {
+                        best_delta = delta;
+                        best_move = Some(LocalSearchMove::ShipmentSwapStar(swap_star));
+                    }
+                }
+
                 (r1, r2, best_delta, best_move)
             })
             .collect::<Vec<_>>();
@@ -399,14 +421,26 @@ impl LocalSearch {
             }
 
             self.pairs.clear();
+            solution.update_route_bbox_index();
 
             let updated_routes = op.updated_routes();
+            let empty_routes = empty_routes(solution);
+
+            for &updated_route in &updated_routes {
+                if solution.route(updated_route).is_empty() {
+                    // Jobs of any other route can be moved to the route that was emptied
+                    for (i, route) in solution.routes().iter().enumerate_idx() {
+                        if !route.is_empty() && is_exchange_allowed(solution, i, updated_route) {
+                            self.pairs.push((i, updated_route));
+                        }
+                    }
+                    continue;
+                }
 
-            for i in 0..solution.routes().len() {
-                for &updated_route in &updated_routes {
-                    self.pairs.push((RouteIdx::new(i), updated_route));
-                    if i != updated_route.get() {
-                        self.pairs.push((updated_route, RouteIdx::new(i)));
+                for other_route in paired_routes(solution, updated_route, &empty_routes) {
+                    self.pairs.push((updated_route, other_route));
+                    if other_route != updated_route && !solution.route(other_route).is_empty() {
+                        self.pairs.push((other_route, updated_route));
                     }
                 }
             }
@@ -420,22 +454,31 @@ impl LocalSearch {
         }
     }
 
-    pub fn clear_stale(&mut self, population: &Population) {
-        self.state.clear_stale(population);
+    /// Removes the cached moves of routes which are not in the population anymore, and the moves of
+    /// the oldest routes when more than `max_entries` remain
+    pub fn clear_stale(&mut self, population: &Population, max_entries: usize) {
+        self.state.clear_stale(population, max_entries);
     }
 
     fn delta(&self, solution: &WorkingSolution, r1: RouteIdx, r2: RouteIdx) -> f64 {
         self.state.delta(solution, r1, r2)
     }
 
-    fn build_pairs(&mut self, solution: &WorkingSolution) {
+    fn build_pairs(&mut self, solution: &mut WorkingSolution) {
         self.pairs.clear();
         let max = solution.routes().len().pow(2);
 
+        solution.update_route_bbox_index();
+        let empty_routes = empty_routes(solution);
+
         for (i, r1) in solution.routes().iter().enumerate_idx() {
-            for (j, r2) in solution.routes().iter().enumerate_idx() {
+            if r1.is_empty() {
+                continue;
+            }
+
+            for j in paired_routes(solution, i, &empty_routes) {
                 let v1 = r1.version();
-                let v2 = r2.version();
+                let v2 = solution.route(j).version();
                 if !self.state.contains_key((v1, v2)) {
                     self.pairs.push((i, j))
                 }
@@ -461,6 +504,49 @@ impl LocalSearch {
     }
 }
 
+fn empty_routes(solution: &WorkingSolution) -> Vec<RouteIdx> {
+    solution
+        .routes()
+        .iter()
+        .enumerate_idx()
+        .filter(|(_, route)| route.is_empty())
+        .map(|(route_id, _)| route_id)
+        .collect()
+}
+
+/// Routes where the jobs of a non empty route are moved or exchanged: the route itself, the routes
+/// whose bounding box intersects its own and the empty routes. Routes far apart are never paired,
+/// the ruin and recreate takes care of them.
+fn paired_routes<'a>(
+    solution: &'a WorkingSolution,
+    route_id: RouteIdx,
+    empty_routes: &'a [RouteIdx],
+) -> impl Iterator<Item = RouteIdx> + 'a {
+    std::iter::once(route_id)
+        .chain(solution.intersecting_routes(route_id))
+        .chain(empty_routes.iter().copied())
+        .filter(move |&other_route_id| is_exchange_allowed(solution, route_id, other_route_id))
+}
+
+/// Moving deliveries between routes loaded at different depots changes the inventory usage of the
+/// depots, these moves are left to the ruin and recreate which accounts for the stock
+fn is_exchange_allowed(solution: &WorkingSolution, r1: RouteIdx, r2: RouteIdx) -> bool {
+    let problem = solution.problem();
+    if r1 == r2 || !problem.has_depot_inventories() {
+        return true;
+    }
+
+    let d1 = solution.route(r1).vehicle(problem).depot_location_id();
+    let d2 = solution.route(r2).vehicle(problem).depot_location_id();
+    let has_inventory = |depot: Option<LocationIdx>| {
+        depot
+            .and_then(|location_id| problem.depot_inventory(location_id))
+            .is_some()
+    };
+
+    d1 == d2 || (!has_inventory(d1) && !has_inventory(d2))
+}
+
 type VersionPair = (usize, usize);
 struct LocalSearchState(FxHashMap<VersionPair, (f64, LocalSearchMove)>);
 
@@ -512,7 +598,7 @@ impl LocalSearchState {
         self.0.insert(key, (delta, best_move));
     }
 
-    fn clear_stale(&mut self, population: &Population) {
+    fn clear_stale(&mut self, population: &Population, max_entries: usize) {
         let versions = population
             .solutions()
             .iter()
@@ -521,7 +607,21 @@ impl LocalSearchState {
             .collect::<FxHashSet<_>>();
 
         self.0
-            .retain(|&k, _| versions.contains(&k.0) && versions.contains(&k.1))
+            .retain(|&k, _| versions.contains(&k.0) && versions.contains(&k.1));
+
+        if self.0.len() > max_entries {
+            if max_entries == 0 {
+                self.0.clear();
+                return;
+            }
+
+            // Route versions are increasing, the pairs with the smallest version are the oldest
+            let mut oldest_versions = self.0.keys().map(|k| k.0.min(k.1)).collect::<Vec<_>>();
+            let evicted = self.0.len() - max_entries;
+            let (_, &mut threshold, _) = oldest_versions.select_nth_unstable(evicted - 1);
+
+            self.0.retain(|&k, _| k.0.min(k.1) > threshold);
+        }
     }
 }
 
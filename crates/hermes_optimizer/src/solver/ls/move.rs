@@ -2,12 +2,20 @@ use crate::{
     problem::vehicle_routing_problem::VehicleRoutingProblem,
     solver::{
         ls::{
-            cross_exchange::CrossExchangeOperator, inter_mixed_exchange::InterMixedExchange,
-            inter_or_opt::InterOrOptOperator, inter_relocate::InterRelocateOperator,
-            inter_reverse_two_opt::InterReverseTwoOptOperator, inter_swap::InterSwapOperator,
-            inter_two_opt_star::InterTwoOptStarOperator, mixed_exchange::MixedExchangeOperator,
-            or_opt::OrOptOperator, relocate::RelocateOperator, swap::SwapOperator,
-            swap_star::SwapStar, two_opt::TwoOptOperator,
+            cross_exchange::CrossExchangeOperator,
+            inter_mixed_exchange::InterMixedExchange,
+            inter_or_opt::InterOrOptOperator,
+            inter_relocate::InterRelocateOperator,
+            inter_reverse_two_opt::InterReverseTwoOptOperator,
+            inter_shipment_relocate::InterShipmentRelocateOperator,
+            inter_swap::InterSwapOperator,
+            inter_two_opt_star::InterTwoOptStarOperator,
+            mixed_exchange::MixedExchangeOperator,
+            or_opt::OrOptOperator,
+            relocate::RelocateOperator,
+            swap::SwapOperator,
+            swap_star::{ShipmentSwapStar, SwapStar},
+            two_opt::TwoOptOperator,
         },
         solution::{route_id::RouteIdx, working_solution::WorkingSolution},
     },
@@ -51,6 +59,9 @@ pub enum LocalSearchMove {
 
     SwapStar(SwapStar),
 
+    /// SWAP* exchanging a shipment of each route
+    ShipmentSwapStar(ShipmentSwapStar),
+
     /// Swap one with a segment
     MixedExchange(MixedExchangeOperator),
 
@@ -65,6 +76,9 @@ pub enum LocalSearchMove {
     /// Inter-route Relocate operator that moves an activity from one route to another.
     InterRelocate(InterRelocateOperator),
 
+    /// Inter-route Shipment Relocate operator that moves a pickup and its delivery to another route.
+    InterShipmentRelocate(InterShipmentRelocateOperator),
+
     /// Inter-route Swap operator that exchanges activities between two different routes.
     InterSwap(InterSwapOperator),
 
@@ -85,9 +99,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate { .. } => "Relocate",
             LocalSearchMove::Swap { .. } => "Swap",
             LocalSearchMove::SwapStar { .. } => "SwapStar",
+            LocalSearchMove::ShipmentSwapStar { .. } => "Shipment-SwapStar",
             LocalSearchMove::OrOpt { .. } => "Or-Opt",
             LocalSearchMove::InterOrOpt { .. } => "Inter Or-Opt",
             LocalSearchMove::InterRelocate { .. } => "Inter-Relocate",
+            LocalSearchMove::InterShipmentRelocate { .. } => "Inter-Shipment-Relocate",
             LocalSearchMove::InterSwap { .. } => "Inter-Swap",
             LocalSearchMove::CrossExchange { .. } => "Cross-Exchange",
             LocalSearchMove::InterTwoOptStar { .. } => "Inter-2-Opt*",
@@ -103,9 +119,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate(op) => op.delta(solution),
             LocalSearchMove::Swap(op) => op.delta(solution),
             LocalSearchMove::SwapStar(op) => op.delta(solution),
+            LocalSearchMove::ShipmentSwapStar(op) => op.delta(solution),
             LocalSearchMove::OrOpt(op) => op.delta(solution),
             LocalSearchMove::InterOrOpt(op) => op.delta(solution),
             LocalSearchMove::InterRelocate(op) => op.delta(solution),
+            LocalSearchMove::InterShipmentRelocate(op) => op.delta(solution),
             LocalSearchMove::InterSwap(op) => op.delta(solution),
             LocalSearchMove::CrossExchange(op) => op.delta(solution),
             LocalSearchMove::InterTwoOptStar(op) => op.delta(solution),
@@ -121,9 +139,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate(op) => op.transport_cost_delta(solution),
             LocalSearchMove::Swap(op) => op.transport_cost_delta(solution),
             LocalSearchMove::SwapStar(op) => op.transport_cost_delta(solution),
+            LocalSearchMove::ShipmentSwapStar(op) => op.transport_cost_delta(solution),
             LocalSearchMove::OrOpt(op) => op.transport_cost_delta(solution),
             LocalSearchMove::InterOrOpt(op) => op.transport_cost_delta(solution),
             LocalSearchMove::InterRelocate(op) => op.transport_cost_delta(solution),
+            LocalSearchMove::InterShipmentRelocate(op) => op.transport_cost_delta(solution),
             LocalSearchMove::InterSwap(op) => op.transport_cost_delta(solution),
             LocalSearchMove::CrossExchange(op) => op.transport_cost_delta(solution),
             LocalSearchMove::InterTwoOptStar(op) => op.transport_cost_delta(solution),
@@ -139,9 +159,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::Swap(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::SwapStar(op) => op.waiting_cost_delta(solution),
+            LocalSearchMove::ShipmentSwapStar(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::OrOpt(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::InterOrOpt(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::InterRelocate(op) => op.waiting_cost_delta(solution),
+            LocalSearchMove::InterShipmentRelocate(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::InterSwap(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::CrossExchange(op) => op.waiting_cost_delta(solution),
             LocalSearchMove::InterTwoOptStar(op) => op.waiting_cost_delta(solution),
@@ -157,9 +179,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate(op) => op.is_valid(solution),
             LocalSearchMove::Swap(op) => op.is_valid(solution),
             LocalSearchMove::SwapStar(op) => op.is_valid(solution),
+            LocalSearchMove::ShipmentSwapStar(op) => op.is_valid(solution),
             LocalSearchMove::OrOpt(op) => op.is_valid(solution),
             LocalSearchMove::InterOrOpt(op) => op.is_valid(solution),
             LocalSearchMove::InterRelocate(op) => op.is_valid(solution),
+            LocalSearchMove::InterShipmentRelocate(op) => op.is_valid(solution),
             LocalSearchMove::InterSwap(op) => op.is_valid(solution),
             LocalSearchMove::CrossExchange(op) => op.is_valid(solution),
             LocalSearchMove::InterTwoOptStar(op) => op.is_valid(solution),
@@ -175,9 +199,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate(op) => op.apply(problem, solution),
             LocalSearchMove::Swap(op) => op.apply(problem, solution),
             LocalSearchMove::SwapStar(op) => op.apply(problem, solution),
+            LocalSearchMove::ShipmentSwapStar(op) => op.apply(problem, solution),
             LocalSearchMove::OrOpt(op) => op.apply(problem, solution),
             LocalSearchMove::InterOrOpt(op) => op.apply(problem, solution),
             LocalSearchMove::InterRelocate(op) => op.apply(problem, solution),
+            LocalSearchMove::InterShipmentRelocate(op) => op.apply(problem, solution),
             LocalSearchMove::InterSwap(op) => op.apply(problem, solution),
             LocalSearchMove::CrossExchange(op) => op.apply(problem, solution),
             LocalSearchMove::InterTwoOptStar(op) => op.apply(problem, solution),
@@ -193,9 +219,11 @@ impl LocalSearchMove {
             LocalSearchMove::Relocate(op) => op.updated_routes(),
             LocalSearchMove::Swap(op) => op.updated_routes(),
             LocalSearchMove::SwapStar(op) => op.updated_routes(),
+            LocalSearchMove::ShipmentSwapStar(op) => op.updated_routes(),
             LocalSearchMove::OrOpt(op) => op.updated_routes(),
             LocalSearchMove::InterOrOpt(op) => op.updated_routes(),
             LocalSearchMove::InterRelocate(op) => op.updated_routes(),
+            LocalSearchMove::InterShipmentRelocate(op) => op.updated_routes(),
             LocalSearchMove::InterSwap(op) => op.updated_routes(),
             LocalSearchMove::CrossExchange(op) => op.updated_routes(),
             LocalSearchMove::InterTwoOptStar(op) => op.updated_routes(),
@@ -95,7 +95,12 @@ impl LocalSearchOperator for RelocateOperator {
                     continue; // no change in this case
                 }
 
-                if !route.in_insertion_neighborhood(solution.problem(), from_id, to_pos) {
+                if !route.in_insertion_neighborhood(
+                    solution.problem(),
+                    solution.neighborhood_size(),
+                    from_id,
+                    to_pos,
+                ) {
                     continue;
                 }
 
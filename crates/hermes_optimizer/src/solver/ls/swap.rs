@@ -152,6 +152,7 @@ impl LocalSearchOperator for SwapOperator {
 
                 if !route.in_swap_neighborhood(
                     problem,
+                    solution.neighborhood_size(),
                     from_pos,
                     from_pos + 1,
                     route,
@@ -6,6 +6,8 @@ use tracing::{Level, instrument};
 use crate::{
     problem::{
         job::{ActivityId, JobIdx},
+        location::LocationIdx,
+        vehicle::Vehicle,
         vehicle_routing_problem::VehicleRoutingProblem,
     },
     solver::{
@@ -13,19 +15,17 @@ use crate::{
         insertion::{Insertion, ServiceInsertion, for_each_route_insertion},
         insertion_context::InsertionContext,
         ls::r#move::LocalSearchOperator,
+        score::Score,
         solution::{
             route::WorkingSolutionRoute, route_id::RouteIdx, working_solution::WorkingSolution,
         },
     },
 };
 
-/// The SWAP* operator is based on:
-/// Hybrid genetic search for the CVRP: Open-source implementation and SWAP* neighborhood
-/// Thibaut Vidal, 2022
-
+/// Best insertions of a job in a route, ranked by their objective levels then their soft score
 #[derive(Default, Clone)]
 struct TopThreeInsertions {
-    insertions: [Option<(Insertion, f64)>; 3],
+    insertions: [Option<(Insertion, Score)>; 3],
 }
 
 impl TopThreeInsertions {
@@ -34,13 +34,18 @@ impl TopThreeInsertions {
     }
 
     #[inline(always)]
-    fn delta(&self, i: usize) -> f64 {
+    fn delta(&self, i: usize) -> Score {
         self.insertions[i]
             .as_ref()
-            .map_or(f64::MAX, |(_, score)| *score)
+            .map_or(Score::MAX, |(_, score)| *score)
     }
 
-    fn update(&mut self, insertion: Insertion, delta: f64) {
+    fn update(&mut self, insertion: Insertion, score: Score) {
+        // The hard score is ignored, the move is checked once the other activity is removed
+        let delta = Score {
+            hard_score: 0.0,
+            ..score
+        };
         if delta < self.delta(0) {
             self.insertions[2] = self.insertions[1].take();
             self.insertions[1] = self.insertions[0].take();
@@ -53,10 +58,16 @@ impl TopThreeInsertions {
         }
     }
 
-    fn iter(&self) -> impl Iterator<Item = &(Insertion, f64)> {
+    /// Insertions with their cost delta, summed over the objective levels like the removal and
+    /// in place deltas they are added to
+    fn iter(&self) -> impl Iterator<Item = (&Insertion, f64)> {
         self.insertions
             .iter()
             .filter_map(|insertion| insertion.as_ref())
+            .map(|(insertion, score)| {
+                let delta = score.objective_scores.iter().sum::<f64>() + score.soft_score;
+                (insertion, delta)
+            })
     }
 }
 
@@ -75,7 +86,7 @@ fn find_top_three_insertions(
         let insertion_context =
             InsertionContext::new(solution.problem(), solution, &insertion, insert_on_failure);
         let score = compute_insertion_score(constraints, &insertion_context, None);
-        insertions.update(insertion, score.soft_score);
+        insertions.update(insertion, score);
     });
 
     insertions
@@ -161,7 +172,7 @@ pub fn find_best_swap_star_move(
 
         let job = problem.job(job_id);
 
-        // Don't support shipment for Swap*
+        // Shipments are exchanged by find_best_shipment_swap_star_move
         if job.is_shipment() {
             continue;
         }
@@ -178,7 +189,7 @@ pub fn find_best_swap_star_move(
 
         let job = problem.job(job_id);
 
-        // Don't support shipment for Swap*
+        // Shipments are exchanged by find_best_shipment_swap_star_move
         if job.is_shipment() {
             continue;
         }
@@ -231,7 +242,7 @@ pub fn find_best_swap_star_move(
             }
 
             for insertion2 in r2_insertions.iter() {
-                match insertion2.0 {
+                match *insertion2.0 {
                     Insertion::Service(ServiceInsertion { position, .. }) => {
                         if position == p2 || position == p2 + 1 {
                             continue;
@@ -325,6 +336,125 @@ pub fn find_best_swap_star_move(
     best_move
 }
 
+/// Best insertions of the shipment of `pickup_id` in `route_id`, with the positions mapped to the
+/// route once its shipment at `pickup` and `delivery` is removed
+fn shipment_insertions(
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+    route_id: RouteIdx,
+    job_id: JobIdx,
+    (pickup, delivery): (usize, usize),
+) -> Vec<Option<(usize, usize)>> {
+    let reduced_position = |position: usize| {
+        position - usize::from(position > pickup) - usize::from(position > delivery)
+    };
+
+    // The shipment can also take the place of the removed one
+    std::iter::once(None)
+        .chain(
+            find_top_three_insertions(solution, constraints, route_id, job_id)
+                .iter()
+                .filter_map(|(insertion, _)| match insertion {
+                    Insertion::Shipment(insertion) => Some(Some((
+                        reduced_position(insertion.pickup_position),
+                        reduced_position(insertion.delivery_position),
+                    ))),
+                    Insertion::Service(_) => None,
+                }),
+        )
+        .collect()
+}
+
+/// Shipments of the route that can leave it for `other`, as the positions of their pickup and delivery
+fn movable_shipments(
+    problem: &VehicleRoutingProblem,
+    route: &WorkingSolutionRoute,
+    other: &WorkingSolutionRoute,
+) -> Vec<(JobIdx, usize, usize)> {
+    route
+        .activity_ids()
+        .iter()
+        .enumerate()
+        .filter_map(|(position, &activity_id)| {
+            let ActivityId::ShipmentPickup(job_id) = activity_id else {
+                return None;
+            };
+
+            if route.is_locked(position) || !other.can_deliver_job(problem, job_id) {
+                return None;
+            }
+
+            let delivery = route.matching_shipment_position(activity_id);
+            route
+                .can_remove_segment(problem, position, delivery + 1)
+                .then_some((job_id, position, delivery))
+        })
+        .collect()
+}
+
+/// SWAP* for shipments: exchanges a shipment of each route, each one taking the place of the
+/// other or one of its best insertions in the other route
+#[instrument(skip_all,level = Level::TRACE)]
+pub fn find_best_shipment_swap_star_move(
+    problem: &VehicleRoutingProblem,
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+    (r1, r2): (RouteIdx, RouteIdx),
+) -> Option<ShipmentSwapStar> {
+    if !problem.has_shipments() || r1 <= r2 {
+        return None;
+    }
+
+    let route1 = solution.route(r1);
+    let route2 = solution.route(r2);
+
+    if !route1.bbox_intersects(route2) {
+        return None;
+    }
+
+    let shipments1 = movable_shipments(problem, route1, route2);
+    let shipments2 = movable_shipments(problem, route2, route1);
+
+    if shipments1.is_empty() || shipments2.is_empty() {
+        return None;
+    }
+
+    let mut best_delta = 0.0;
+    let mut best_move: Option<ShipmentSwapStar> = None;
+
+    for &(job1, pickup1, delivery1) in &shipments1 {
+        for &(job2, pickup2, delivery2) in &shipments2 {
+            let r1_insertions =
+                shipment_insertions(solution, constraints, r1, job2, (pickup1, delivery1));
+            let r2_insertions =
+                shipment_insertions(solution, constraints, r2, job1, (pickup2, delivery2));
+
+            for &first_insertion in &r1_insertions {
+                for &second_insertion in &r2_insertions {
+                    let swap_move = ShipmentSwapStar::new(ShipmentSwapStarParams {
+                        first_route: r1,
+                        second_route: r2,
+                        first_pickup: pickup1,
+                        first_delivery: delivery1,
+                        second_pickup: pickup2,
+                        second_delivery: delivery2,
+                        first_insertion,
+                        second_insertion,
+                    });
+
+                    let delta = swap_move.delta(solution);
+                    if delta < best_delta && swap_move.is_valid(solution) {
+                        best_delta = delta;
+                        best_move = Some(swap_move);
+                    }
+                }
+            }
+        }
+    }
+
+    best_move
+}
+
 #[derive(Debug)]
 pub struct SwapStarParams {
     pub first_route: RouteIdx,
@@ -336,6 +466,9 @@ pub struct SwapStarParams {
     pub delta: f64,
 }
 
+/// The SWAP* operator is based on:
+/// Hybrid genetic search for the CVRP: Open-source implementation and SWAP* neighborhood
+/// Thibaut Vidal, 2022
 #[derive(Debug)]
 pub struct SwapStar {
     params: SwapStarParams,
@@ -714,19 +847,282 @@ impl LocalSearchOperator for SwapStar {
     }
 }
 
+/// Travel cost from `from` through `locations` to `to`
+fn path_cost(
+    problem: &VehicleRoutingProblem,
+    vehicle: &Vehicle,
+    from: Option<LocationIdx>,
+    locations: impl Iterator<Item = Option<LocationIdx>>,
+    to: Option<LocationIdx>,
+) -> f64 {
+    let mut previous = from;
+    locations
+        .chain(std::iter::once(to))
+        .map(|location| {
+            let cost = problem.travel_cost_or_zero(vehicle, previous, location);
+            previous = location;
+            cost
+        })
+        .sum()
+}
+
+#[derive(Debug)]
+pub struct ShipmentSwapStarParams {
+    pub first_route: RouteIdx,
+    pub second_route: RouteIdx,
+    pub first_pickup: usize,
+    pub first_delivery: usize,
+    pub second_pickup: usize,
+    pub second_delivery: usize,
+
+    /// Positions of the shipment of the second route in the first route once its shipment is
+    /// removed, like `ShipmentInsertion`, `None` when it takes the place of the removed shipment
+    pub first_insertion: Option<(usize, usize)>,
+    /// Positions of the shipment of the first route in the second route, like `first_insertion`
+    pub second_insertion: Option<(usize, usize)>,
+}
+
+/// Change of a route of a [`ShipmentSwapStar`]: `activity_ids` replace [start, end)
+struct ShipmentSwapChange {
+    activity_ids: Vec<ActivityId>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug)]
+pub struct ShipmentSwapStar {
+    params: ShipmentSwapStarParams,
+}
+
+impl ShipmentSwapStar {
+    pub fn new(params: ShipmentSwapStarParams) -> Self {
+        if params.first_route == params.second_route {
+            panic!("ShipmentSwapStar: first_route cannot be equal to second_route");
+        }
+
+        if params.first_pickup >= params.first_delivery
+            || params.second_pickup >= params.second_delivery
+        {
+            panic!("ShipmentSwapStar: the pickup must be before the delivery");
+        }
+
+        ShipmentSwapStar { params }
+    }
+
+    fn changes(&self, solution: &WorkingSolution) -> [(RouteIdx, ShipmentSwapChange); 2] {
+        let route1 = solution.route(self.params.first_route);
+        let route2 = solution.route(self.params.second_route);
+
+        [
+            (
+                self.params.first_route,
+                Self::change(
+                    route1,
+                    (self.params.first_pickup, self.params.first_delivery),
+                    (
+                        route2.activity_id(self.params.second_pickup),
+                        route2.activity_id(self.params.second_delivery),
+                    ),
+                    self.params.first_insertion,
+                ),
+            ),
+            (
+                self.params.second_route,
+                Self::change(
+                    route2,
+                    (self.params.second_pickup, self.params.second_delivery),
+                    (
+                        route1.activity_id(self.params.first_pickup),
+                        route1.activity_id(self.params.first_delivery),
+                    ),
+                    self.params.second_insertion,
+                ),
+            ),
+        ]
+    }
+
+    fn change(
+        route: &WorkingSolutionRoute,
+        (pickup, delivery): (usize, usize),
+        (inserted_pickup, inserted_delivery): (ActivityId, ActivityId),
+        insertion: Option<(usize, usize)>,
+    ) -> ShipmentSwapChange {
+        let activity_ids = route.activity_ids();
+
+        let sequence = match insertion {
+            None => activity_ids
+                .iter()
+                .enumerate()
+                .map(|(position, &activity_id)| {
+                    if position == pickup {
+                        inserted_pickup
+                    } else if position == delivery {
+                        inserted_delivery
+                    } else {
+                        activity_id
+                    }
+                })
+                .collect::<Vec<_>>(),
+            Some((insertion_pickup, insertion_delivery)) => {
+                let reduced = activity_ids
+                    .iter()
+                    .enumerate()
+                    .filter(|&(position, _)| position != pickup && position != delivery)
+                    .map(|(_, &activity_id)| activity_id)
+                    .collect::<Vec<_>>();
+
+                reduced[..insertion_pickup]
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(inserted_pickup))
+                    .chain(
+                        reduced[insertion_pickup..insertion_delivery]
+                            .iter()
+                            .copied(),
+                    )
+                    .chain(std::iter::once(inserted_delivery))
+                    .chain(reduced[insertion_delivery..].iter().copied())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Both sequences have the same length, only the window where they differ is replaced
+        let start = activity_ids
+            .iter()
+            .zip(&sequence)
+            .take_while(|(current, new)| current == new)
+            .count();
+        let suffix = activity_ids[start..]
+            .iter()
+            .rev()
+            .zip(sequence[start..].iter().rev())
+            .take_while(|(current, new)| current == new)
+            .count();
+
+        ShipmentSwapChange {
+            activity_ids: sequence[start..sequence.len() - suffix].to_vec(),
+            start,
+            end: activity_ids.len() - suffix,
+        }
+    }
+}
+
+impl LocalSearchOperator for ShipmentSwapStar {
+    #[instrument(skip_all,level = Level::TRACE)]
+    fn generate_moves<C>(
+        _problem: &VehicleRoutingProblem,
+        _solution: &WorkingSolution,
+        _pair: (RouteIdx, RouteIdx),
+        _consumer: C,
+    ) where
+        C: FnMut(Self),
+    {
+    }
+
+    fn waiting_cost_delta(&self, solution: &WorkingSolution) -> f64 {
+        let problem = solution.problem();
+
+        let waiting_duration_delta = self
+            .changes(solution)
+            .iter()
+            .map(|(route_id, change)| {
+                solution.route(*route_id).waiting_duration_change_delta(
+                    problem,
+                    change.activity_ids.iter().copied(),
+                    change.start,
+                    change.end,
+                )
+            })
+            .fold(SignedDuration::ZERO, |total, delta| total + delta);
+
+        problem.waiting_duration_cost(waiting_duration_delta)
+    }
+
+    fn transport_cost_delta(&self, solution: &WorkingSolution) -> f64 {
+        let problem = solution.problem();
+
+        self.changes(solution)
+            .iter()
+            .map(|(route_id, change)| {
+                let route = solution.route(*route_id);
+                let vehicle = route.vehicle(problem);
+                let previous = route.previous_location_id(problem, change.start);
+                let next = route
+                    .location_id(problem, change.end)
+                    .or_else(|| route.end_location(problem));
+
+                let new_locations = change
+                    .activity_ids
+                    .iter()
+                    .map(|&activity_id| Some(problem.job_activity(activity_id).location_id()));
+                let current_locations =
+                    (change.start..change.end).map(|position| route.location_id(problem, position));
+
+                path_cost(problem, vehicle, previous, new_locations, next)
+                    - path_cost(problem, vehicle, previous, current_locations, next)
+            })
+            .sum()
+    }
+
+    fn fixed_route_cost_delta(&self, _solution: &WorkingSolution) -> f64 {
+        0.0
+    }
+
+    fn is_valid(&self, solution: &WorkingSolution) -> bool {
+        let problem = solution.problem();
+
+        self.changes(solution).iter().all(|(route_id, change)| {
+            solution.route(*route_id).is_valid_change(
+                problem,
+                change.activity_ids.iter().copied(),
+                change.start,
+                change.end,
+            )
+        })
+    }
+
+    fn apply(&self, problem: &VehicleRoutingProblem, solution: &mut WorkingSolution) {
+        for (route_id, change) in self.changes(solution) {
+            solution.route_mut(route_id).replace_activities(
+                problem,
+                &change.activity_ids,
+                change.start,
+                change.end,
+            );
+        }
+    }
+
+    fn updated_routes(&self) -> Vec<RouteIdx> {
+        vec![self.params.first_route, self.params.second_route]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use crate::{
-        problem::job::JobIdx,
+        problem::{
+            distance_method::DistanceMethod,
+            fleet::Fleet,
+            job::{ActivityId, JobIdx},
+            shipment::ShipmentBuilder,
+            travel_cost_matrix::{DistanceRounding, TravelMatrices},
+            vehicle_profile::VehicleProfile,
+            vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
+        },
         solver::{
-            insertion::{Insertion, ServiceInsertion},
+            constraints::constraint_set::ConstraintSet,
+            insertion::{Insertion, ServiceInsertion, ShipmentInsertion},
             ls::{
                 r#move::LocalSearchOperator,
-                swap_star::{SwapStar, SwapStarParams, TopThreeInsertions},
+                swap_star::{
+                    ShipmentSwapStar, ShipmentSwapStarParams, SwapStar, SwapStarParams,
+                    TopThreeInsertions, find_best_shipment_swap_star_move,
+                },
             },
-            solution::route_id::RouteIdx,
+            score::Score,
+            solution::{route_id::RouteIdx, working_solution::WorkingSolution},
         },
         test_utils::{self, TestRoute},
     };
@@ -742,30 +1138,38 @@ mod tests {
             route_id: RouteIdx::new(0),
         });
 
-        top3.update(test_insertion.clone(), 10.0);
-        assert_eq!(top3.delta(0), 10.0);
-        assert_eq!(top3.delta(1), f64::MAX);
-        assert_eq!(top3.delta(2), f64::MAX);
-
-        top3.update(test_insertion.clone(), 5.0);
-        assert_eq!(top3.delta(0), 5.0);
-        assert_eq!(top3.delta(1), 10.0);
-        assert_eq!(top3.delta(2), f64::MAX);
-
-        top3.update(test_insertion.clone(), 7.0);
-        assert_eq!(top3.delta(0), 5.0);
-        assert_eq!(top3.delta(1), 7.0);
-        assert_eq!(top3.delta(2), 10.0);
-
-        top3.update(test_insertion.clone(), 6.0);
-        assert_eq!(top3.delta(0), 5.0);
-        assert_eq!(top3.delta(1), 6.0);
-        assert_eq!(top3.delta(2), 7.0);
-
-        top3.update(test_insertion.clone(), 11.0);
-        assert_eq!(top3.delta(0), 5.0);
-        assert_eq!(top3.delta(1), 6.0);
-        assert_eq!(top3.delta(2), 7.0);
+        let soft = |score: f64| Score::new(0.0, score);
+
+        top3.update(test_insertion.clone(), soft(10.0));
+        assert_eq!(top3.delta(0), soft(10.0));
+        assert_eq!(top3.delta(1), Score::MAX);
+        assert_eq!(top3.delta(2), Score::MAX);
+
+        top3.update(test_insertion.clone(), soft(5.0));
+        assert_eq!(top3.delta(0), soft(5.0));
+        assert_eq!(top3.delta(1), soft(10.0));
+        assert_eq!(top3.delta(2), Score::MAX);
+
+        top3.update(test_insertion.clone(), soft(7.0));
+        assert_eq!(top3.delta(0), soft(5.0));
+        assert_eq!(top3.delta(1), soft(7.0));
+        assert_eq!(top3.delta(2), soft(10.0));
+
+        top3.update(test_insertion.clone(), soft(6.0));
+        assert_eq!(top3.delta(0), soft(5.0));
+        assert_eq!(top3.delta(1), soft(6.0));
+        assert_eq!(top3.delta(2), soft(7.0));
+
+        top3.update(test_insertion.clone(), soft(11.0));
+        assert_eq!(top3.delta(0), soft(5.0));
+        assert_eq!(top3.delta(1), soft(6.0));
+        assert_eq!(top3.delta(2), soft(7.0));
+
+        // The hard score is ignored
+        top3.update(test_insertion.clone(), Score::new(100.0, 1.0));
+        assert_eq!(top3.delta(0), soft(1.0));
+        assert_eq!(top3.delta(1), soft(5.0));
+        assert_eq!(top3.delta(2), soft(6.0));
     }
 
     #[test]
@@ -1289,4 +1693,116 @@ mod tests {
             vec![6, 7, 8, 10, 2],
         );
     }
+
+    /// R0: P0 D0 P2 D2, R1: P1 D1 P3 D3, the shipments 0 and 3 are on the first row of the grid,
+    /// 1 and 2 on the last one
+    fn create_shipment_solution() -> (Arc<VehicleRoutingProblem>, WorkingSolution) {
+        let locations = test_utils::create_location_grid(5, 5);
+        let shipments = [(1, 2), (21, 23), (20, 24), (3, 4)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (pickup, delivery))| {
+                let mut builder = ShipmentBuilder::default();
+                builder.set_external_id(index.to_string());
+                builder.set_pickup_location_id(pickup);
+                builder.set_delivery_location_id(delivery);
+                builder.build()
+            })
+            .collect();
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_distance_method(DistanceMethod::Euclidean);
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
+        )]);
+        builder.set_shipments(shipments);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(test_utils::create_basic_vehicles(vec![0, 0])));
+        let problem = Arc::new(builder.build().unwrap());
+
+        let mut solution = WorkingSolution::new(Arc::clone(&problem));
+        for (route_id, job_index, position) in [(0, 0, 0), (0, 2, 2), (1, 1, 0), (1, 3, 2)] {
+            solution.insert(&Insertion::Shipment(ShipmentInsertion {
+                route_id: RouteIdx::new(route_id),
+                job_index: job_index.into(),
+                pickup_position: position,
+                delivery_position: position,
+            }));
+        }
+
+        (problem, solution)
+    }
+
+    fn job_ids(solution: &WorkingSolution, route_id: usize) -> Vec<usize> {
+        solution
+            .route(route_id.into())
+            .activity_ids()
+            .iter()
+            .map(|activity| activity.job_id().get())
+            .collect()
+    }
+
+    #[test]
+    fn test_shipment_swap_star_apply() {
+        let (problem, mut solution) = create_shipment_solution();
+
+        // Shipment 2 takes the place of shipment 3, shipment 3 goes around shipment 0
+        let operator = ShipmentSwapStar::new(ShipmentSwapStarParams {
+            first_route: RouteIdx::new(1),
+            second_route: RouteIdx::new(0),
+            first_pickup: 2,
+            first_delivery: 3,
+            second_pickup: 2,
+            second_delivery: 3,
+            first_insertion: None,
+            second_insertion: Some((0, 2)),
+        });
+
+        assert!(operator.is_valid(&solution));
+
+        let distances = solution.route(0.into()).transport_costs(&problem)
+            + solution.route(1.into()).transport_costs(&problem);
+        let delta = operator.transport_cost_delta(&solution);
+        operator.apply(&problem, &mut solution);
+        assert!(
+            (solution.route(0.into()).transport_costs(&problem)
+                + solution.route(1.into()).transport_costs(&problem)
+                - (distances + delta))
+                .abs()
+                < 1e-9
+        );
+
+        assert_eq!(job_ids(&solution, 0), vec![3, 0, 0, 3]);
+        assert_eq!(job_ids(&solution, 1), vec![1, 1, 2, 2]);
+        assert_eq!(
+            solution.route(1.into()).activity_ids()[2],
+            ActivityId::ShipmentPickup(2.into())
+        );
+    }
+
+    #[test]
+    fn test_find_best_shipment_swap_star_move() {
+        let (problem, mut solution) = create_shipment_solution();
+        let constraints = ConstraintSet::default();
+
+        let operator = find_best_shipment_swap_star_move(
+            &problem,
+            &solution,
+            constraints.constraints(),
+            (RouteIdx::new(1), RouteIdx::new(0)),
+        )
+        .unwrap();
+
+        assert!(operator.delta(&solution) < 0.0);
+        operator.apply(&problem, &mut solution);
+
+        // Each route serves a row of the grid
+        let mut rows = [job_ids(&solution, 0), job_ids(&solution, 1)];
+        for row in &mut rows {
+            row.sort();
+        }
+        rows.sort();
+        assert_eq!(rows, [vec![0, 0, 3, 3], vec![1, 1, 2, 2]]);
+    }
 }
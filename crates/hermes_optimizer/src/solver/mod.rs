@@ -1,19 +1,34 @@
 pub mod accepted_solution;
 pub mod alns;
 pub mod alns_weights;
+pub mod best_score_history;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod constraint_statistics;
 pub mod constraints;
 pub mod construction;
+pub mod custom_strategies;
+#[cfg(feature = "statistics")]
+pub mod events;
 pub mod insertion;
 pub(crate) mod insertion_cache;
 pub mod insertion_context;
+pub mod insertion_thread_pool;
 pub mod ls;
 pub mod noise;
+pub mod objective;
 pub mod recreate;
+pub mod repair;
+pub mod route_orientation;
 pub mod ruin;
 pub mod score;
 pub mod score_level;
+pub mod shift_extension;
 pub mod solution;
 pub mod solver;
 pub mod solver_manager;
 pub mod solver_params;
 pub mod statistics;
+pub mod statistics_sink;
+pub mod time_window_compliance;
+pub mod unassigned_reason;
@@ -0,0 +1,90 @@
+use super::{constraints::constraint_set::ConstraintSet, score::OBJECTIVE_LEVELS};
+
+/// How the soft scores of the constraints are combined to rank the solutions, the hard scores
+/// always come first
+#[derive(Clone, Debug)]
+pub enum Objective {
+    /// Sum of the soft scores, each constraint multiplied by its weight, constraints without a
+    /// weight count once
+    Weighted(Vec<(String, f64)>),
+
+    /// Constraints compared one after the other, e.g. `["vehicle_cost", "transport_cost"]` to
+    /// minimize the vehicles first, then the distance.
+    /// The constraints that are not listed are summed and compared last, like the ones after the
+    /// first `OBJECTIVE_LEVELS`
+    Lexicographic(Vec<String>),
+}
+
+impl Default for Objective {
+    fn default() -> Self {
+        Objective::Weighted(vec![])
+    }
+}
+
+impl Objective {
+    /// Constraints of the search, scoring the solutions according to this objective
+    pub fn apply(&self, constraints: &ConstraintSet) -> ConstraintSet {
+        let mut constraints = constraints.clone();
+
+        match self {
+            Objective::Weighted(weights) => {
+                for (name, weight) in weights {
+                    constraints.set_weight(name, *weight);
+                }
+            }
+            Objective::Lexicographic(components) => {
+                for (level, name) in components.iter().take(OBJECTIVE_LEVELS).enumerate() {
+                    constraints.set_objective_level(name, level);
+                }
+            }
+        }
+
+        constraints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::constraints::{constraint::Constraint, constraint_set::ConstraintSet};
+
+    use super::Objective;
+
+    fn find<'a>(constraints: &'a ConstraintSet, name: &str) -> &'a Constraint {
+        constraints
+            .constraints()
+            .iter()
+            .find(|constraint| constraint.constraint_name() == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_lexicographic_objective() {
+        let objective =
+            Objective::Lexicographic(vec!["vehicle_cost".to_owned(), "transport_cost".to_owned()]);
+        let constraints = objective.apply(&ConstraintSet::default());
+
+        assert!(matches!(
+            find(&constraints, "vehicle_cost"),
+            Constraint::Prioritized { level: 0, .. }
+        ));
+        assert!(matches!(
+            find(&constraints, "transport_cost"),
+            Constraint::Prioritized { level: 1, .. }
+        ));
+        assert!(!matches!(
+            find(&constraints, "waiting_duration"),
+            Constraint::Prioritized { .. }
+        ));
+    }
+
+    #[test]
+    fn test_weighted_objective() {
+        let objective = Objective::Weighted(vec![("waiting_duration".to_owned(), 2.0)]);
+        let constraints = objective.apply(&ConstraintSet::default());
+
+        assert!(matches!(
+            find(&constraints, "waiting_duration"),
+            Constraint::Weighted { weight, .. } if *weight == 2.0
+        ));
+    }
+}
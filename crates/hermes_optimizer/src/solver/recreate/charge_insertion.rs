@@ -0,0 +1,139 @@
+use crate::{
+    problem::{
+        charging_station::ChargingStation, job::ActivityId,
+        vehicle_routing_problem::VehicleRoutingProblem,
+    },
+    solver::{
+        constraints::{compute_insertion_score::compute_insertion_score, constraint::Constraint},
+        insertion::{Insertion, ServiceInsertion, for_each_route_insertion},
+        insertion_context::InsertionContext,
+        score::Score,
+        solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+    },
+    utils::enumerate_idx::EnumerateIdx,
+};
+
+/// Inserts the jobs left unassigned by a recreate strategy in the routes of electric vehicles,
+/// each insertion comes with a charge of the vehicle when the battery is what prevents it.
+///
+/// The charge is placed where the battery is closest to half empty, at the charging station with
+/// the cheapest detour, the job is then inserted at its best position in the route with the charge.
+pub fn insert_charges(
+    solution: &mut WorkingSolution,
+    problem: &VehicleRoutingProblem,
+    constraints: &[Constraint],
+) {
+    while solution.has_unassigned() {
+        let mut unassigned_jobs = solution
+            .unassigned_jobs()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        unassigned_jobs.sort_unstable();
+
+        let mut best: Option<(Score, Insertion, Insertion)> = None;
+
+        for (route_id, route) in solution.routes().iter().enumerate_idx() {
+            if route.is_empty() || route.vehicle(problem).battery().is_none() {
+                continue;
+            }
+
+            let Some((charge_score, charge_insertion)) =
+                best_charge_insertion(solution, problem, constraints, route_id)
+            else {
+                continue;
+            };
+
+            let mut candidate = solution.clone();
+            candidate.insert(&charge_insertion);
+
+            for &job_id in &unassigned_jobs {
+                for_each_route_insertion(&candidate, route_id, job_id, |insertion| {
+                    let score = charge_score
+                        + compute_insertion_score(
+                            constraints,
+                            &InsertionContext::new(problem, &candidate, &insertion, false),
+                            None,
+                        );
+
+                    if !score.is_infeasible()
+                        && best
+                            .as_ref()
+                            .is_none_or(|(best_score, _, _)| score < *best_score)
+                    {
+                        best = Some((score, charge_insertion.clone(), insertion));
+                    }
+                });
+            }
+        }
+
+        match best {
+            Some((_, charge_insertion, insertion)) => {
+                solution.insert(&charge_insertion);
+                solution.insert(&insertion);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Feasible insertion of a free charge of the vehicle with its score, `None` when the vehicle has
+/// no charge left
+fn best_charge_insertion(
+    solution: &WorkingSolution,
+    problem: &VehicleRoutingProblem,
+    constraints: &[Constraint],
+    route_id: RouteIdx,
+) -> Option<(Score, Insertion)> {
+    let route = solution.route(route_id);
+    let battery = route.vehicle(problem).battery()?;
+
+    // First free charge at each station
+    let mut charges = problem
+        .vehicle_charges(route.vehicle_id())
+        .iter()
+        .copied()
+        .filter(|&job_id| {
+            solution
+                .route_of_activity(ActivityId::Service(job_id))
+                .is_none()
+        })
+        .collect::<Vec<_>>();
+    charges.dedup_by_key(|&mut job_id| {
+        problem
+            .charging_station(job_id)
+            .map(ChargingStation::location_id)
+    });
+
+    if charges.is_empty() {
+        return None;
+    }
+
+    let energy_levels = route.energy_levels(problem);
+    let mut positions = (route.locked_len().max(1)..route.len()).collect::<Vec<_>>();
+    positions.sort_by(|&a, &b| {
+        (energy_levels[a] - battery.capacity() / 2.0)
+            .abs()
+            .total_cmp(&(energy_levels[b] - battery.capacity() / 2.0).abs())
+    });
+
+    positions.into_iter().find_map(|position| {
+        charges
+            .iter()
+            .filter_map(|&job_id| {
+                let insertion = Insertion::Service(ServiceInsertion {
+                    route_id,
+                    job_index: job_id,
+                    position,
+                });
+                let score = compute_insertion_score(
+                    constraints,
+                    &InsertionContext::new(problem, solution, &insertion, false),
+                    None,
+                );
+
+                (!score.is_infeasible()).then_some((score, insertion))
+            })
+            .min_by_key(|(score, _)| *score)
+    })
+}
@@ -3,14 +3,22 @@ use std::hash::{Hash, Hasher};
 use fxhash::FxHasher64;
 use rand::{RngCore, rngs::SmallRng};
 
+#[cfg(feature = "statistics")]
+use crate::solver::constraint_statistics::ConstraintStatistics;
 use crate::{
     problem::{job::JobIdx, vehicle_routing_problem::VehicleRoutingProblem},
     solver::{
-        constraints::{compute_insertion_score::compute_insertion_score, constraint::Constraint},
+        constraints::{
+            compute_insertion_score::compute_insertion_score_with_rejections,
+            constraint::Constraint,
+        },
+        custom_strategies::CustomStrategies,
         insertion::Insertion,
         insertion_context::InsertionContext,
         noise::{JobNoiser, NoiseParams},
-        recreate::recreate_strategy::RecreateStrategy,
+        recreate::{
+            recreate_solution::CustomRecreateSolution, recreate_strategy::RecreateStrategy,
+        },
         score::Score,
         solution::working_solution::WorkingSolution,
     },
@@ -22,6 +30,11 @@ pub struct RecreateContext<'a> {
     pub problem: &'a VehicleRoutingProblem,
     pub noise_params: NoiseParams,
     pub insert_on_failure: bool,
+    pub custom_strategies: &'a CustomStrategies<dyn CustomRecreateSolution>,
+
+    /// Counts the insertions rejected by each constraint, None during the construction
+    #[cfg(feature = "statistics")]
+    pub constraint_statistics: Option<&'a ConstraintStatistics>,
 }
 
 impl<'a> RecreateContext<'a> {
@@ -49,9 +62,24 @@ impl<'a> RecreateContext<'a> {
     ) -> Score {
         let context =
             InsertionContext::new(self.problem, solution, insertion, self.insert_on_failure);
-        compute_insertion_score(self.constraints, &context, best_score)
+        compute_insertion_score_with_rejections(
+            self.constraints,
+            &context,
+            best_score,
+            |constraint_index| self.record_rejection(constraint_index),
+        )
     }
 
+    #[cfg(feature = "statistics")]
+    fn record_rejection(&self, constraint_index: usize) {
+        if let Some(constraint_statistics) = self.constraint_statistics {
+            constraint_statistics.record_rejection(constraint_index);
+        }
+    }
+
+    #[cfg(not(feature = "statistics"))]
+    fn record_rejection(&self, _constraint_index: usize) {}
+
     pub fn should_insert(&self, score: &Score) -> bool {
         if self.insert_on_failure {
             true
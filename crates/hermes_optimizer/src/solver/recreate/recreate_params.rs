@@ -1,14 +1,39 @@
-use crate::problem::vehicle_routing_problem::VehicleRoutingProblem;
+use std::sync::Arc;
 
-use super::{best_insertion::BestInsertionSortStrategy, recreate_strategy::RecreateStrategy};
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::custom_strategies::CustomStrategies,
+};
+
+use super::{
+    best_insertion::BestInsertionSortStrategy, recreate_solution::CustomRecreateSolution,
+    recreate_strategy::RecreateStrategy,
+};
 
 #[derive(Clone, Debug)]
 pub struct RecreateParams {
     pub recreate_strategies: Vec<RecreateStrategy>,
     pub insert_on_failure: bool,
+    pub custom_strategies: CustomStrategies<dyn CustomRecreateSolution>,
 }
 
 impl RecreateParams {
+    /// Adds a recreate operator defined outside of the crate, its weight is adapted like the built-in ones
+    pub fn register_custom(
+        &mut self,
+        strategy: impl CustomRecreateSolution + 'static,
+    ) -> RecreateStrategy {
+        let recreate_strategy = RecreateStrategy::Custom(strategy.name());
+        self.custom_strategies
+            .insert(strategy.name(), Arc::new(strategy));
+
+        if !self.recreate_strategies.contains(&recreate_strategy) {
+            self.recreate_strategies.push(recreate_strategy);
+        }
+
+        recreate_strategy
+    }
+
     pub fn default_from_problem(problem: &VehicleRoutingProblem) -> Self {
         let mut strategies: Vec<RecreateStrategy> = vec![
             RecreateStrategy::RegretInsertion(2),
@@ -37,6 +62,7 @@ impl Default for RecreateParams {
     fn default() -> Self {
         RecreateParams {
             insert_on_failure: false,
+            custom_strategies: CustomStrategies::default(),
             recreate_strategies: vec![
                 RecreateStrategy::RegretInsertion(2),
                 RecreateStrategy::BestInsertion(BestInsertionSortStrategy::Random),
@@ -5,3 +5,9 @@ use super::recreate_context::RecreateContext;
 pub trait RecreateSolution {
     fn recreate_solution(&self, solution: &mut WorkingSolution, context: RecreateContext);
 }
+
+/// Recreate operator defined outside of the crate, registered with `RecreateParams::register_custom`
+pub trait CustomRecreateSolution: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn recreate_solution(&self, solution: &mut WorkingSolution, context: RecreateContext);
+}
@@ -9,10 +9,12 @@ use crate::solver::{
 
 use super::{
     best_insertion::{BestInsertion, BestInsertionParams},
+    charge_insertion::insert_charges,
     construction_best_insertion::ConstructionBestInsertion,
     recreate_context::RecreateContext,
     recreate_solution::RecreateSolution,
     regret_insertion::RegretInsertion,
+    reload_insertion::insert_reloads,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -20,6 +22,8 @@ pub enum RecreateStrategy {
     CompleteBestInsertion,
     BestInsertion(BestInsertionSortStrategy),
     RegretInsertion(usize),
+    /// Strategy registered with `RecreateParams::register_custom`
+    Custom(&'static str),
 }
 
 impl Serialize for RecreateStrategy {
@@ -37,12 +41,24 @@ impl Display for RecreateStrategy {
             Self::CompleteBestInsertion => write!(f, "CompleteBestInsertion"),
             Self::BestInsertion(sort_method) => write!(f, "BestInsertion({sort_method})"),
             Self::RegretInsertion(k) => write!(f, "RegretInsertion({k})"),
+            Self::Custom(name) => write!(f, "{name}"),
         }
     }
 }
 
 impl RecreateSolution for RecreateStrategy {
     fn recreate_solution(&self, solution: &mut WorkingSolution, context: RecreateContext) {
+        let problem = context.problem;
+        let constraints = context.constraints;
+
+        if problem.has_reloads() {
+            solution.remove_idle_reloads();
+        }
+
+        if problem.has_charges() {
+            solution.remove_idle_charges();
+        }
+
         match self {
             RecreateStrategy::CompleteBestInsertion => {
                 let strategy = ConstructionBestInsertion;
@@ -59,6 +75,24 @@ impl RecreateSolution for RecreateStrategy {
                 let strategy = RegretInsertion::new(*k);
                 strategy.recreate_solution(solution, context);
             }
+            RecreateStrategy::Custom(name) => {
+                let custom_strategies = context.custom_strategies;
+                let strategy = custom_strategies
+                    .get(name)
+                    // Checked by `SolverParams::validate` when the search is created
+                    .unwrap_or_else(|| panic!("Custom recreate strategy {name} is not registered"));
+                strategy.recreate_solution(solution, context);
+            }
+        }
+
+        // Capacity may be what keeps the remaining jobs out, the vehicles can reload for them
+        if problem.has_reloads() && solution.has_unassigned() {
+            insert_reloads(solution, problem, constraints);
+        }
+
+        // Same for the battery with charges
+        if problem.has_charges() && solution.has_unassigned() {
+            insert_charges(solution, problem, constraints);
         }
 
         // solution.resync();
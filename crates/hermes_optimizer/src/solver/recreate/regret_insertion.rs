@@ -55,8 +55,7 @@ impl RegretInsertion {
                 let mut noiser = context.create_noiser(noiser_seed);
                 let mut potential_insertions: Vec<(Score, Insertion)> = Vec::with_capacity(
                     // One insertion after each activity
-                    (context.problem.jobs().len() - solution.unassigned_jobs().len())
-                        + solution.routes().len(), // One insertion at the start of every route
+                    solution.assigned_jobs_count() + solution.routes().len(), // One insertion at the start of every route
                 );
 
                 for_each_insertion(solution, job_id, |insertion| {
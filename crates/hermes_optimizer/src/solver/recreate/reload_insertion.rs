@@ -0,0 +1,124 @@
+use crate::{
+    problem::{job::ActivityId, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{
+        constraints::{compute_insertion_score::compute_insertion_score, constraint::Constraint},
+        insertion::{Insertion, ServiceInsertion, for_each_route_insertion},
+        insertion_context::InsertionContext,
+        score::Score,
+        solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+    },
+};
+
+/// Inserts the jobs left unassigned by a recreate strategy in the routes that can reload, each
+/// insertion comes with a reload of the vehicle when capacity is what prevents it.
+///
+/// The reload is placed where it splits the trip into the most balanced loads, the job is then
+/// inserted at its best position in the route with the reload.
+pub fn insert_reloads(
+    solution: &mut WorkingSolution,
+    problem: &VehicleRoutingProblem,
+    constraints: &[Constraint],
+) {
+    while solution.has_unassigned() {
+        let mut unassigned_jobs = solution
+            .unassigned_jobs()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        unassigned_jobs.sort_unstable();
+
+        let mut best: Option<(Score, Insertion, Insertion)> = None;
+
+        for route_id in (0..solution.routes().len()).map(RouteIdx::new) {
+            if solution.route(route_id).len() < 2 {
+                continue;
+            }
+
+            let Some(reload) = best_reload_insertion(solution, problem, route_id) else {
+                continue;
+            };
+            let reload_position = reload.position;
+            let reload_insertion = Insertion::Service(reload);
+
+            let reload_score = compute_insertion_score(
+                constraints,
+                &InsertionContext::new(problem, solution, &reload_insertion, false),
+                None,
+            );
+            if reload_score.is_infeasible() {
+                continue;
+            }
+
+            // The jobs are evaluated on the route with the reload, which is then removed again
+            solution.insert(&reload_insertion);
+
+            for &job_id in &unassigned_jobs {
+                for_each_route_insertion(solution, route_id, job_id, |insertion| {
+                    let score = reload_score
+                        + compute_insertion_score(
+                            constraints,
+                            &InsertionContext::new(problem, solution, &insertion, false),
+                            None,
+                        );
+
+                    if !score.is_infeasible()
+                        && best
+                            .as_ref()
+                            .is_none_or(|(best_score, _, _)| score < *best_score)
+                    {
+                        best = Some((score, reload_insertion.clone(), insertion));
+                    }
+                });
+            }
+
+            solution.remove_activity_at(route_id, reload_position);
+        }
+
+        match best {
+            Some((_, reload_insertion, insertion)) => {
+                solution.insert(&reload_insertion);
+                solution.insert(&insertion);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Insertion of a free reload of the vehicle at the position balancing the loads of the trip,
+/// `None` when the vehicle has no reload left
+fn best_reload_insertion(
+    solution: &WorkingSolution,
+    problem: &VehicleRoutingProblem,
+    route_id: RouteIdx,
+) -> Option<ServiceInsertion> {
+    let route = solution.route(route_id);
+    let reload_id = problem
+        .vehicle_reloads(route.vehicle_id())
+        .iter()
+        .copied()
+        .find(|&job_id| {
+            solution
+                .route_of_activity(ActivityId::Service(job_id))
+                .is_none()
+        })?;
+
+    (route.locked_len().max(1)..route.len())
+        .filter(|&position| {
+            route.is_valid_change(
+                problem,
+                std::iter::once(ActivityId::Service(reload_id)),
+                position,
+                position,
+            )
+        })
+        .min_by(|&a, &b| {
+            route
+                .reload_split_utilization(problem, a)
+                .total_cmp(&route.reload_split_utilization(problem, b))
+        })
+        .map(|position| ServiceInsertion {
+            route_id,
+            job_index: reload_id,
+            position,
+        })
+}
@@ -0,0 +1,263 @@
+use fxhash::FxHashSet;
+
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::{
+        constraints::constraint::Constraint,
+        score::Score,
+        solution::{
+            route::WorkingSolutionRoute, route_id::RouteIdx, working_solution::WorkingSolution,
+        },
+    },
+    utils::enumerate_idx::EnumerateIdx,
+};
+
+/// Splits a route in two: the activities from `position` to the end of the route are moved to the empty route `target_route_id`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteSplit {
+    pub route_id: RouteIdx,
+    pub target_route_id: RouteIdx,
+    pub position: usize,
+}
+
+impl RouteSplit {
+    pub fn apply(&self, solution: &mut WorkingSolution) {
+        solution.split_route(self.route_id, self.position, self.target_route_id);
+    }
+}
+
+/// One empty route per vehicle, splitting into two empty routes of the same vehicle would give the same result
+fn empty_target_routes(solution: &WorkingSolution) -> Vec<RouteIdx> {
+    let mut seen_vehicles = FxHashSet::default();
+
+    solution
+        .routes()
+        .iter()
+        .enumerate_idx()
+        .filter(|(_, route)| route.is_empty() && seen_vehicles.insert(route.vehicle_id()))
+        .map(|(route_id, _)| route_id)
+        .collect()
+}
+
+/// Sum of the scores of the constraints on `route` alone, see `Constraint::compute_route_score`
+pub fn route_score(
+    problem: &VehicleRoutingProblem,
+    constraints: &[Constraint],
+    route: &WorkingSolutionRoute,
+) -> Score {
+    constraints.iter().fold(Score::zero(), |acc, constraint| {
+        acc + constraint.compute_route_score(problem, route)
+    })
+}
+
+/// Best split of `route_id` with the change of score it brings to the two routes
+fn best_route_split(
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+    route_id: RouteIdx,
+) -> Option<(RouteSplit, Score)> {
+    let problem = solution.problem();
+    let route = solution.route(route_id);
+    if route.len() < 2 {
+        return None;
+    }
+
+    let current_score = route_score(problem, constraints, route);
+    let mut best: Option<(RouteSplit, Score)> = None;
+
+    for target_route_id in empty_target_routes(solution) {
+        // Locked activities must stay in the route
+        for position in route.locked_len().max(1)..route.len() {
+            // A shipment cannot have its pickup and delivery in different routes
+            if route.contains_pending_shipment(0, position) {
+                continue;
+            }
+
+            let mut head = route.clone();
+            head.replace_activities(problem, &[], position, route.len());
+
+            let mut tail = solution.route(target_route_id).clone();
+            tail.replace_activities(problem, &route.activity_ids()[position..], 0, 0);
+
+            let head_score = route_score(problem, constraints, &head);
+            let tail_score = route_score(problem, constraints, &tail);
+
+            // Both routes must be feasible, moving the violations to the other route is no repair
+            if head_score.is_infeasible() || tail_score.is_infeasible() {
+                continue;
+            }
+
+            let delta = head_score + tail_score - current_score;
+            if delta.hard_score >= 0.0 {
+                continue;
+            }
+
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_delta)| delta < *best_delta)
+            {
+                let split = RouteSplit {
+                    route_id,
+                    target_route_id,
+                    position,
+                };
+                best = Some((split, delta));
+            }
+        }
+    }
+
+    best
+}
+
+/// Finds the split of `route_id` into two feasible routes which adds the least cost.
+/// The split is scored on the two routes alone, see `Constraint::compute_route_score`.
+///
+/// Returns None when the route is feasible or cannot be split into two feasible routes.
+pub fn find_best_route_split(
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+    route_id: RouteIdx,
+) -> Option<RouteSplit> {
+    best_route_split(solution, constraints, route_id).map(|(split, _)| split)
+}
+
+/// Repeatedly splits the routes of an infeasible solution until no split improves its hard score
+pub fn repair_infeasible_routes(solution: &mut WorkingSolution, constraints: &[Constraint]) {
+    loop {
+        let (score, _) = solution.compute_solution_score(constraints);
+        if score.is_feasible() {
+            return;
+        }
+
+        let route_ids = solution
+            .routes()
+            .iter()
+            .enumerate_idx()
+            .filter(|(_, route)| !route.is_empty())
+            .map(|(route_id, _)| route_id)
+            .collect::<Vec<RouteIdx>>();
+
+        let best_split = route_ids
+            .into_iter()
+            .filter_map(|route_id| best_route_split(solution, constraints, route_id))
+            .min_by_key(|(_, delta)| *delta);
+
+        let Some((split, _)) = best_split else {
+            return;
+        };
+
+        // The constraints spanning several routes are not part of the score of the split, the
+        // split is undone when it breaks them
+        let previous_solution = solution.clone();
+        split.apply(solution);
+
+        let (new_score, _) = solution.compute_solution_score(constraints);
+        if new_score.hard_score >= score.hard_score {
+            solution.clone_from(&previous_solution);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{capacity::Capacity, service::ServiceBuilder, vehicle::VehicleBuilder},
+        solver::constraints::{
+            capacity_constraint::CapacityConstraint, global_constraint::GlobalConstraintType,
+            route_constraint::RouteConstraintType,
+            transport_cost_constraint::TransportCostConstraint,
+        },
+        test_utils::{self, TestRoute},
+    };
+
+    use super::*;
+
+    fn create_overloaded_solution() -> WorkingSolution {
+        let locations = test_utils::create_location_grid(1, 4);
+
+        let services = (1..4)
+            .map(|location_id| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(location_id);
+                builder.set_external_id(location_id.to_string());
+                builder.set_demand(Capacity::from_vec(vec![10.0]));
+                builder.build()
+            })
+            .collect();
+
+        let vehicles = (0..2)
+            .map(|index| {
+                let mut builder = VehicleBuilder::default();
+                builder.set_depot_location_id(0);
+                builder.set_vehicle_id(index.to_string());
+                builder.set_profile_id(0);
+                builder.set_capacity(Capacity::from_vec(vec![20.0]));
+                builder.build()
+            })
+            .collect();
+
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        test_utils::create_test_working_solution(
+            Arc::new(problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1, 2],
+            }],
+        )
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Route(RouteConstraintType::Capacity(CapacityConstraint::default())),
+            Constraint::Global(GlobalConstraintType::TransportCost(TransportCostConstraint)),
+        ]
+    }
+
+    #[test]
+    fn test_find_best_route_split() {
+        let solution = create_overloaded_solution();
+        let constraints = constraints();
+
+        let split = find_best_route_split(&solution, &constraints, RouteIdx::new(0));
+
+        // The first stop is the closest to the depot, serving it alone is the cheapest split
+        assert_eq!(
+            split,
+            Some(RouteSplit {
+                route_id: RouteIdx::new(0),
+                target_route_id: RouteIdx::new(1),
+                position: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_repair_infeasible_routes() {
+        let mut solution = create_overloaded_solution();
+        let constraints = constraints();
+
+        let (score, _) = solution.compute_solution_score(&constraints);
+        assert!(score.is_infeasible());
+
+        repair_infeasible_routes(&mut solution, &constraints);
+
+        let (score, _) = solution.compute_solution_score(&constraints);
+        assert!(score.is_feasible());
+        assert_eq!(solution.non_empty_routes_count(), 2);
+        assert!(!solution.has_unassigned());
+    }
+
+    #[test]
+    fn test_no_split_for_feasible_route() {
+        let mut solution = create_overloaded_solution();
+        solution.remove_job(2.into());
+        solution.resync_route(RouteIdx::new(0));
+
+        let split = find_best_route_split(&solution, &constraints(), RouteIdx::new(0));
+        assert_eq!(split, None);
+    }
+}
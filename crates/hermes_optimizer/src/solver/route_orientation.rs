@@ -0,0 +1,135 @@
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::{
+        constraints::constraint::Constraint,
+        ls::{
+            r#move::LocalSearchOperator,
+            two_opt::{TwoOptOperator, TwoOptParams},
+        },
+        repair::route_split::route_score,
+        score::Score,
+        solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+    },
+};
+
+/// Smallest soft score improvement for a route to be reversed, avoids flipping routes back and
+/// forth on rounding errors
+const MIN_IMPROVEMENT: f64 = 1e-9;
+
+/// Reverses the routes whose reversed orientation is valid and cheaper.
+///
+/// With a symmetric cost matrix a full reversal only changes the edges to the depots and the
+/// schedule of the route: its waiting durations, its duration and the time windows it meets. The
+/// reversed route is scored on its own, see `Constraint::compute_route_score`. The local search
+/// rarely explores it as it is a single 2-opt move spanning the whole route.
+///
+/// Routes with shipments are skipped, reversing them would deliver before picking up.
+///
+/// Returns the number of reversed routes.
+pub fn orient_routes(
+    problem: &VehicleRoutingProblem,
+    constraints: &[Constraint],
+    solution: &mut WorkingSolution,
+) -> usize {
+    if !problem.is_symmetric() {
+        return 0;
+    }
+
+    let mut reversed = 0;
+
+    for route_id in 0..solution.routes().len() {
+        let route = solution.route(RouteIdx::new(route_id));
+        let len = route.activity_ids().len();
+
+        if len < 2 || route.contains_shipments(0, len) {
+            continue;
+        }
+
+        let mut reversed_route = route.clone();
+        let reversed_activity_ids = route
+            .activity_ids()
+            .iter()
+            .rev()
+            .copied()
+            .collect::<Vec<_>>();
+        reversed_route.replace_activities(problem, &reversed_activity_ids, 0, len);
+
+        let improves = route_score(problem, constraints, &reversed_route)
+            + Score::soft(MIN_IMPROVEMENT)
+            < route_score(problem, constraints, route);
+
+        let operator = TwoOptOperator::new(TwoOptParams {
+            route_id: RouteIdx::new(route_id),
+            from: 0,
+            to: len - 1,
+        });
+
+        if improves && operator.is_valid(solution) {
+            operator.apply(problem, solution);
+            reversed += 1;
+        }
+    }
+
+    reversed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{job::ActivityId, time_window::TimeWindow},
+        solver::{constraints::constraint_set::ConstraintSet, solution::route_id::RouteIdx},
+        test_utils::{
+            TestProblemOptions, TestRoute, TestService, create_problem_for_tw_change,
+            create_test_working_solution,
+        },
+        timestamp,
+    };
+
+    use super::orient_routes;
+
+    #[test]
+    fn test_orient_routes_reduces_waiting() {
+        let problem = Arc::new(create_problem_for_tw_change(
+            vec![
+                TestService::with_time_window(TimeWindow::from_iso(
+                    Some("2025-11-30T10:00:00+02:00"),
+                    Some("2025-11-30T12:00:00+02:00"),
+                )),
+                TestService::default(),
+            ],
+            TestProblemOptions {
+                earliest_start: Some(timestamp!("2025-11-30T08:00:00+02:00")),
+                latest_start: Some(timestamp!("2025-11-30T08:00:00+02:00")),
+                ..TestProblemOptions::default()
+            },
+        ));
+
+        // Arrives at 08:30 on service 1 and waits until 10:00, serving service 2 first only waits
+        // until 09:10
+        let mut solution = create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1],
+            }],
+        );
+
+        let constraints = ConstraintSet::default();
+        assert_eq!(
+            orient_routes(&problem, constraints.constraints(), &mut solution),
+            1
+        );
+        assert_eq!(
+            solution.route(RouteIdx::new(0)).activity_ids().to_vec(),
+            vec![ActivityId::service(1), ActivityId::service(0)]
+        );
+
+        // Already in the best orientation
+        assert_eq!(
+            orient_routes(&problem, constraints.constraints(), &mut solution),
+            0
+        );
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
 
 pub struct RuinContext<'a, R>
 where
-    R: RngCore,
+    R: RngCore + ?Sized,
 {
     pub params: &'a RuinParams,
     pub problem: &'a VehicleRoutingProblem,
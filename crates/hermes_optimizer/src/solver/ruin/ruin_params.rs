@@ -1,4 +1,44 @@
-use super::ruin_strategy::RuinStrategy;
+use std::sync::Arc;
+
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::custom_strategies::CustomStrategies,
+};
+
+use super::{ruin_solution::CustomRuinSolution, ruin_strategy::RuinStrategy};
+
+/// Weights of each term of the Shaw relatedness, a lower relatedness means that two jobs are more
+/// likely to be removed together
+#[derive(Clone, Copy, Debug)]
+pub struct ShawRelatednessWeights {
+    /// Travel distance between the jobs
+    pub distance: f64,
+
+    /// Difference between the arrival times of the jobs in the current solution
+    pub time: f64,
+
+    /// Difference between the time windows of the jobs
+    pub time_window: f64,
+
+    /// Difference between the normalized demands of the jobs
+    pub demand: f64,
+}
+
+impl ShawRelatednessWeights {
+    pub const SHAW: ShawRelatednessWeights = ShawRelatednessWeights {
+        distance: 9.0,
+        time: 3.0,
+        time_window: 0.0,
+        demand: 2.0,
+    };
+
+    pub const TEMPORAL_SHAW: ShawRelatednessWeights = ShawRelatednessWeights {
+        distance: 2.0,
+        time: 6.0,
+        time_window: 6.0,
+        demand: 1.0,
+    };
+}
 
 #[derive(Clone, Debug)]
 pub struct RuinParams {
@@ -15,6 +55,39 @@ pub struct RuinParams {
 
     pub ruin_worst_determinism: f64,
     pub ruin_shaw_determinism: f64,
+
+    /// Relatedness weights of `RuinStrategy::RuinShaw`
+    pub shaw_weights: ShawRelatednessWeights,
+
+    /// Relatedness weights of `RuinStrategy::RuinTemporalShaw`
+    pub temporal_shaw_weights: ShawRelatednessWeights,
+
+    pub custom_strategies: CustomStrategies<dyn CustomRuinSolution>,
+}
+
+impl RuinParams {
+    /// Adds a ruin operator defined outside of the crate, its weight is adapted like the built-in ones
+    pub fn register_custom(&mut self, strategy: impl CustomRuinSolution + 'static) -> RuinStrategy {
+        let ruin_strategy = RuinStrategy::Custom(strategy.name());
+        self.custom_strategies
+            .insert(strategy.name(), Arc::new(strategy));
+
+        if !self.ruin_strategies.contains(&ruin_strategy) {
+            self.ruin_strategies.push(ruin_strategy);
+        }
+
+        ruin_strategy
+    }
+
+    pub fn default_from_problem(problem: &VehicleRoutingProblem) -> Self {
+        let mut params = RuinParams::default();
+
+        if problem.has_time_windows() {
+            params.ruin_strategies.push(RuinStrategy::RuinTemporalShaw);
+        }
+
+        params
+    }
 }
 
 impl Default for RuinParams {
@@ -36,6 +109,10 @@ impl Default for RuinParams {
 
             ruin_worst_determinism: 3.0,
             ruin_shaw_determinism: 6.0,
+            shaw_weights: ShawRelatednessWeights::SHAW,
+            temporal_shaw_weights: ShawRelatednessWeights::TEMPORAL_SHAW,
+
+            custom_strategies: CustomStrategies::default(),
         }
     }
 }
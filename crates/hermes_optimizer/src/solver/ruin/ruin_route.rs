@@ -19,7 +19,8 @@ impl RuinSolution for RuinRoute {
                 .routes()
                 .iter()
                 .enumerate()
-                .filter(|(_, route)| !route.is_empty())
+                // Routes with only locked activities cannot be ruined
+                .filter(|(_, route)| route.len() > route.locked_len())
                 .map(|(index, r1)| {
                     let fit_in_other_route = solution
                         .non_empty_routes_iter()
@@ -6,23 +6,30 @@ use crate::{
         amount::AmountExpression,
         job::{ActivityId, Job, JobIdx},
         meters::Meters,
+        vehicle_routing_problem::VehicleRoutingProblem,
     },
     solver::solution::working_solution::WorkingSolution,
 };
 
-use super::{ruin_context::RuinContext, ruin_solution::RuinSolution};
-
-pub struct RuinShaw;
+use super::{
+    ruin_context::RuinContext, ruin_params::ShawRelatednessWeights, ruin_solution::RuinSolution,
+};
 
-const DISTANCE_RELATEDNESS_WEIGHT: f64 = 9.0;
-const TIME_RELATEDNESS_WEIGHT: f64 = 3.0;
-const DEMAND_RELATEDNESS_WEIGHT: f64 = 2.0;
+pub struct RuinShaw {
+    weights: ShawRelatednessWeights,
+}
 
 impl RuinShaw {
+    pub fn new(weights: ShawRelatednessWeights) -> Self {
+        RuinShaw { weights }
+    }
+
     fn relatedness(
+        &self,
         activity: &RelatednessToTargetActivity,
         max_distance: Meters,
         max_time: SignedDuration,
+        max_time_window: SignedDuration,
     ) -> f64 {
         let time_relatedness = if max_time.is_zero() {
             0.0
@@ -36,9 +43,44 @@ impl RuinShaw {
             activity.distance / max_distance
         };
 
-        TIME_RELATEDNESS_WEIGHT * time_relatedness
-            + DISTANCE_RELATEDNESS_WEIGHT * distance_relatedness
-            + DEMAND_RELATEDNESS_WEIGHT * activity.normalized_demand
+        // Jobs without time windows are considered unrelated to the target on this term
+        let time_window_relatedness = match activity.time_window {
+            Some(_) if max_time_window.is_zero() => 0.0,
+            Some(time_window) => time_window.as_secs_f64() / max_time_window.as_secs_f64(),
+            None => 1.0,
+        };
+
+        self.weights.time * time_relatedness
+            + self.weights.distance * distance_relatedness
+            + self.weights.time_window * time_window_relatedness
+            + self.weights.demand * activity.normalized_demand
+    }
+
+    /// Difference between the start and end of the time windows of the first activity of both jobs,
+    /// `None` when one of them is not bounded
+    fn time_window_difference(
+        problem: &VehicleRoutingProblem,
+        a: JobIdx,
+        b: JobIdx,
+    ) -> Option<SignedDuration> {
+        let bounds = |job_idx: JobIdx| {
+            let activity_id = match problem.job(job_idx) {
+                Job::Service(_) => ActivityId::Service(job_idx),
+                Job::Shipment(_) => ActivityId::ShipmentPickup(job_idx),
+            };
+
+            let job_activity = problem.job_activity(activity_id);
+            let time_windows = job_activity.time_windows();
+            let start = time_windows.iter().filter_map(|tw| tw.earliest()).min()?;
+            let end = time_windows.end()?;
+
+            Some((start, end))
+        };
+
+        let (a_start, a_end) = bounds(a)?;
+        let (b_start, b_end) = bounds(b)?;
+
+        Some(a_start.duration_since(b_start).abs() + a_end.duration_since(b_end).abs())
     }
 }
 
@@ -54,6 +96,7 @@ impl RuinSolution for RuinShaw {
 
         let mut max_distance: Meters = Meters::ZERO;
         let mut max_time: SignedDuration = SignedDuration::ZERO;
+        let mut max_time_window: SignedDuration = SignedDuration::ZERO;
 
         let mut related_activities: Vec<RelatednessToTargetActivity> = Vec::new();
         let mut processed_jobs = FxHashSet::<JobIdx>::default();
@@ -191,24 +234,35 @@ impl RuinSolution for RuinShaw {
                 .sum::<f64>()
                     / solution.problem().capacity_dimensions() as f64;
 
+                let time_window_difference = if self.weights.time_window > 0.0 {
+                    RuinShaw::time_window_difference(
+                        context.problem,
+                        target_job,
+                        activity_id.job_id(),
+                    )
+                } else {
+                    None
+                };
+
                 related_activities.push(RelatednessToTargetActivity {
                     job_idx: activity_id.job_id(),
                     time: time_difference,
+                    time_window: time_window_difference,
                     distance,
                     normalized_demand: demand_difference,
                 });
 
                 max_distance = max_distance.max(distance);
                 max_time = max_time.max(time_difference);
+                if let Some(time_window_difference) = time_window_difference {
+                    max_time_window = max_time_window.max(time_window_difference);
+                }
             }
         }
 
         related_activities.sort_unstable_by(|a, b| {
-            RuinShaw::relatedness(a, max_distance, max_time).total_cmp(&RuinShaw::relatedness(
-                b,
-                max_distance,
-                max_time,
-            ))
+            self.relatedness(a, max_distance, max_time, max_time_window)
+                .total_cmp(&self.relatedness(b, max_distance, max_time, max_time_window))
         });
 
         let mut remaining_to_remove = context.num_jobs_to_remove;
@@ -236,6 +290,59 @@ impl RuinSolution for RuinShaw {
 struct RelatednessToTargetActivity {
     job_idx: JobIdx,
     time: SignedDuration,
+    time_window: Option<SignedDuration>,
     distance: Meters,
     normalized_demand: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::{job::JobIdx, service::ServiceBuilder, time_window::TimeWindow},
+        solver::ruin::ruin_shaw::RuinShaw,
+        test_utils,
+    };
+
+    #[test]
+    fn test_time_window_difference() {
+        let locations = test_utils::create_location_grid(2, 2);
+        let time_windows = [
+            Some(("2025-06-10T08:00:00+02:00", "2025-06-10T10:00:00+02:00")),
+            Some(("2025-06-10T08:30:00+02:00", "2025-06-10T10:00:00+02:00")),
+            Some(("2025-06-10T14:00:00+02:00", "2025-06-10T16:00:00+02:00")),
+            None,
+        ];
+
+        let services = time_windows
+            .iter()
+            .enumerate()
+            .map(|(index, time_window)| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(index);
+                builder.set_external_id(index.to_string());
+                if let Some((start, end)) = time_window {
+                    builder.set_time_windows(vec![TimeWindow::from_iso(Some(*start), Some(*end))]);
+                }
+                builder.build()
+            })
+            .collect();
+
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        assert_eq!(
+            RuinShaw::time_window_difference(&problem, JobIdx::new(0), JobIdx::new(1)),
+            Some(SignedDuration::from_mins(30))
+        );
+        assert_eq!(
+            RuinShaw::time_window_difference(&problem, JobIdx::new(0), JobIdx::new(2)),
+            Some(SignedDuration::from_hours(12))
+        );
+        assert_eq!(
+            RuinShaw::time_window_difference(&problem, JobIdx::new(0), JobIdx::new(3)),
+            None
+        );
+    }
+}
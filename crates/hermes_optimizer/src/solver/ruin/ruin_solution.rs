@@ -9,3 +9,13 @@ pub trait RuinSolution {
     where
         R: RngCore;
 }
+
+/// Ruin operator defined outside of the crate, registered with `RuinParams::register_custom`
+pub trait CustomRuinSolution: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn ruin_solution<'a>(
+        &self,
+        solution: &mut WorkingSolution,
+        context: RuinContext<'a, dyn RngCore + 'a>,
+    );
+}
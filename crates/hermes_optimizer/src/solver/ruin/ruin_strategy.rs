@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use rand::RngCore;
 use serde::Serialize;
 
 use crate::solver::solution::working_solution::WorkingSolution;
@@ -17,8 +18,12 @@ pub enum RuinStrategy {
     RuinWorst,
     RuinString,
     RuinShaw,
+    /// Shaw removal weighted towards arrival times and time windows
+    RuinTemporalShaw,
     RuinCluster,
     RuinRoute,
+    /// Strategy registered with `RuinParams::register_custom`
+    Custom(&'static str),
 }
 
 impl Display for RuinStrategy {
@@ -29,8 +34,10 @@ impl Display for RuinStrategy {
             Self::RuinWorst => write!(f, "RuinWorst"),
             Self::RuinString => write!(f, "RuinString"),
             Self::RuinShaw => write!(f, "RuinShaw"),
+            Self::RuinTemporalShaw => write!(f, "RuinTemporalShaw"),
             Self::RuinCluster => write!(f, "RuinCluster"),
             Self::RuinRoute => write!(f, "RuinRoute"),
+            Self::Custom(name) => write!(f, "{name}"),
         }
     }
 }
@@ -58,7 +65,11 @@ impl RuinSolution for RuinStrategy {
                 strategy.ruin_solution(solution, context);
             }
             RuinStrategy::RuinShaw => {
-                let strategy = RuinShaw;
+                let strategy = RuinShaw::new(context.params.shaw_weights);
+                strategy.ruin_solution(solution, context);
+            }
+            RuinStrategy::RuinTemporalShaw => {
+                let strategy = RuinShaw::new(context.params.temporal_shaw_weights);
                 strategy.ruin_solution(solution, context);
             }
             RuinStrategy::RuinCluster => {
@@ -69,6 +80,23 @@ impl RuinSolution for RuinStrategy {
                 let strategy = RuinRoute;
                 strategy.ruin_solution(solution, context);
             }
+            RuinStrategy::Custom(name) => {
+                let strategy = context
+                    .params
+                    .custom_strategies
+                    .get(name)
+                    // Checked by `SolverParams::validate` when the search is created
+                    .unwrap_or_else(|| panic!("Custom ruin strategy {name} is not registered"));
+                strategy.ruin_solution(
+                    solution,
+                    RuinContext {
+                        params: context.params,
+                        problem: context.problem,
+                        rng: context.rng as &mut dyn RngCore,
+                        num_jobs_to_remove: context.num_jobs_to_remove,
+                    },
+                );
+            }
         }
 
         solution.sync();
@@ -15,7 +15,8 @@ use crate::{
 
 use super::{ruin_context::RuinContext, ruin_solution::RuinSolution};
 
-// TODO: support shipments: right now it only compute savings from activity independently
+/// Removes the jobs whose removal saves the most, a shipment is removed with both its pickup and
+/// delivery
 pub struct RuinWorst;
 
 fn compute_savings(
@@ -76,6 +77,33 @@ fn compute_savings(
     new_travel_cost - (travel_cost_previous + travel_cost_next)
 }
 
+/// Savings of removing the pickup at `pickup_index` and its delivery, the edge between them is
+/// only counted once when the delivery directly follows the pickup
+fn compute_shipment_savings(
+    problem: &VehicleRoutingProblem,
+    route: &WorkingSolutionRoute,
+    pickup_index: usize,
+) -> f64 {
+    let delivery_index = route.matching_shipment_position(route.activity_id(pickup_index));
+    if delivery_index != pickup_index + 1 {
+        return compute_savings(problem, route, pickup_index)
+            + compute_savings(problem, route, delivery_index);
+    }
+
+    let vehicle = route.vehicle(problem);
+    let previous = route.previous_location_id(problem, pickup_index);
+    let pickup = route.location_id(problem, pickup_index);
+    let delivery = route.location_id(problem, delivery_index);
+    let next = route
+        .location_id(problem, delivery_index + 1)
+        .or_else(|| route.end_location(problem));
+
+    problem.travel_cost_or_zero(vehicle, previous, next)
+        - (problem.travel_cost_or_zero(vehicle, previous, pickup)
+            + problem.travel_cost_or_zero(vehicle, pickup, delivery)
+            + problem.travel_cost_or_zero(vehicle, delivery, next))
+}
+
 impl RuinSolution for RuinWorst {
     fn ruin_solution<R>(&self, solution: &mut WorkingSolution, context: RuinContext<R>)
     where
@@ -83,8 +111,7 @@ impl RuinSolution for RuinWorst {
     {
         let p = context.params.ruin_worst_determinism;
 
-        let mut candidates: Vec<Savings> =
-            Vec::with_capacity(solution.problem().jobs().len() - solution.unassigned_jobs().len());
+        let mut candidates: Vec<Savings> = Vec::with_capacity(solution.assigned_jobs_count());
 
         let mut route_ids = solution
             .routes()
@@ -107,13 +134,24 @@ impl RuinSolution for RuinWorst {
                     .activity_ids()
                     .iter()
                     .enumerate()
-                    .map(|(index, &activity_id)| {
-                        let savings = compute_savings(solution.problem(), route, index);
-                        Savings {
+                    .filter(|&(index, _)| !route.is_locked(index))
+                    .filter_map(|(index, &activity_id)| {
+                        let savings = match activity_id {
+                            ActivityId::Service(_) => {
+                                compute_savings(solution.problem(), route, index)
+                            }
+                            ActivityId::ShipmentPickup(_) => {
+                                compute_shipment_savings(solution.problem(), route, index)
+                            }
+                            // Already counted with its pickup
+                            ActivityId::ShipmentDelivery(_) => return None,
+                        };
+
+                        Some(Savings {
                             job_id: activity_id.job_id(),
                             route_id: *route_id,
                             savings,
-                        }
+                        })
                     })
             }));
 
@@ -124,7 +162,6 @@ impl RuinSolution for RuinWorst {
 
             // Remove the activity with the worst savings
             if let Some(candidate) = candidates.get(index) {
-                // TODO: check for shipments
                 let removed = solution.remove_job(candidate.job_id);
 
                 if removed {
@@ -6,37 +6,60 @@ use std::{
 
 use fxhash::FxHashMap;
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::score_level::ScoreLevel;
 
 pub const RUN_SCORE_ASSERTIONS: bool = true;
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, JsonSchema)]
+/// Number of levels a lexicographic objective can rank before the soft score
+pub const OBJECTIVE_LEVELS: usize = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Score {
     pub hard_score: f64,
+    /// Soft scores of the components of a lexicographic objective, compared in order after the
+    /// hard score and before the soft score
+    #[serde(default, skip_serializing_if = "is_zero_levels")]
+    pub objective_scores: [f64; OBJECTIVE_LEVELS],
     pub soft_score: f64,
 }
 
+fn is_zero_levels(levels: &[f64; OBJECTIVE_LEVELS]) -> bool {
+    levels.iter().all(|&level| level == 0.0)
+}
+
+fn zip_levels(
+    a: [f64; OBJECTIVE_LEVELS],
+    b: [f64; OBJECTIVE_LEVELS],
+    op: impl Fn(f64, f64) -> f64,
+) -> [f64; OBJECTIVE_LEVELS] {
+    std::array::from_fn(|level| op(a[level], b[level]))
+}
+
 impl Score {
     pub const MAX: Score = Score {
         hard_score: f64::MAX,
+        objective_scores: [f64::MAX; OBJECTIVE_LEVELS],
         soft_score: f64::MAX,
     };
 
     pub const MIN: Score = Score {
         hard_score: f64::MIN,
+        objective_scores: [f64::MIN; OBJECTIVE_LEVELS],
         soft_score: f64::MIN,
     };
 
     pub const ZERO: Score = Score {
         hard_score: 0.0,
+        objective_scores: [0.0; OBJECTIVE_LEVELS],
         soft_score: 0.0,
     };
 
     pub fn new(hard_score: f64, soft_score: f64) -> Self {
         Score {
             hard_score,
+            objective_scores: [0.0; OBJECTIVE_LEVELS],
             soft_score,
         }
     }
@@ -49,29 +72,46 @@ impl Score {
     }
 
     pub fn hard(hard_score: f64) -> Self {
-        Score {
-            hard_score,
-            soft_score: 0.0,
-        }
+        Score::new(hard_score, 0.0)
     }
 
     pub fn soft(soft_score: f64) -> Self {
-        Score {
-            hard_score: 0.0,
-            soft_score,
-        }
+        Score::new(0.0, soft_score)
     }
 
     pub fn zero() -> Self {
+        Score::ZERO
+    }
+
+    /// Moves the soft score to the objective level `level`, see `Objective::Lexicographic`
+    pub fn at_objective_level(self, level: usize) -> Self {
+        let mut objective_scores = self.objective_scores;
+        objective_scores[level] += self.soft_score;
+
         Score {
-            hard_score: 0.0,
+            hard_score: self.hard_score,
+            objective_scores,
             soft_score: 0.0,
         }
     }
 
+    /// First objective level in use, `None` when the soft score alone ranks the solutions after
+    /// the hard score
+    pub fn leading_objective_level(&self) -> Option<usize> {
+        self.objective_scores.iter().position(|&score| score != 0.0)
+    }
+
+    /// Soft score the solutions are first ranked by after the hard score, the one of the first
+    /// objective level in use with a lexicographic objective
+    pub fn leading_soft_score(&self) -> f64 {
+        self.leading_objective_level()
+            .map_or(self.soft_score, |level| self.objective_scores[level])
+    }
+
     pub fn round(&self) -> Self {
         Score {
             hard_score: self.hard_score.round(),
+            objective_scores: self.objective_scores.map(f64::round),
             soft_score: self.soft_score.round(),
         }
     }
@@ -91,6 +131,14 @@ impl Ord for Score {
     fn cmp(&self, other: &Self) -> Ordering {
         self.hard_score
             .total_cmp(&other.hard_score)
+            .then_with(|| {
+                self.objective_scores
+                    .iter()
+                    .zip(&other.objective_scores)
+                    .map(|(a, b)| a.total_cmp(b))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
             .then_with(|| self.soft_score.total_cmp(&other.soft_score))
     }
 }
@@ -103,10 +151,7 @@ impl PartialOrd for Score {
 
 impl iter::Sum for Score {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.fold(Self::zero(), |acc, score| Score {
-            hard_score: acc.hard_score + score.hard_score,
-            soft_score: acc.soft_score + score.soft_score,
-        })
+        iter.fold(Self::zero(), |acc, score| acc + score)
     }
 }
 
@@ -116,6 +161,9 @@ impl Add<Score> for Score {
     fn add(self, other: Self) -> Self::Output {
         Score {
             hard_score: self.hard_score + other.hard_score,
+            objective_scores: zip_levels(self.objective_scores, other.objective_scores, |a, b| {
+                a + b
+            }),
             soft_score: self.soft_score + other.soft_score,
         }
     }
@@ -123,8 +171,7 @@ impl Add<Score> for Score {
 
 impl AddAssign<Score> for Score {
     fn add_assign(&mut self, other: Score) {
-        self.hard_score += other.hard_score;
-        self.soft_score += other.soft_score;
+        *self = *self + other;
     }
 }
 
@@ -134,6 +181,9 @@ impl Sub<Score> for Score {
     fn sub(self, other: Self) -> Self::Output {
         Score {
             hard_score: self.hard_score - other.hard_score,
+            objective_scores: zip_levels(self.objective_scores, other.objective_scores, |a, b| {
+                a - b
+            }),
             soft_score: self.soft_score - other.soft_score,
         }
     }
@@ -145,6 +195,7 @@ impl Div<f64> for Score {
     fn div(self, divisor: f64) -> Self::Output {
         Score {
             hard_score: self.hard_score / divisor,
+            objective_scores: self.objective_scores.map(|score| score / divisor),
             soft_score: self.soft_score / divisor,
         }
     }
@@ -156,6 +207,7 @@ impl Mul<f64> for Score {
     fn mul(self, scalar: f64) -> Self::Output {
         Score {
             hard_score: self.hard_score * scalar,
+            objective_scores: self.objective_scores.map(|score| score * scalar),
             soft_score: self.soft_score * scalar,
         }
     }
@@ -163,8 +215,7 @@ impl Mul<f64> for Score {
 
 impl SubAssign<Score> for Score {
     fn sub_assign(&mut self, other: Score) {
-        self.hard_score -= other.hard_score;
-        self.soft_score -= other.soft_score;
+        *self = *self - other;
     }
 }
 
@@ -241,4 +292,26 @@ mod tests {
 
         assert!((Score::soft(1788382.5109717606) >= Score::soft(1788382.5109717606)));
     }
+
+    #[test]
+    fn test_objective_levels_cmp() {
+        let fewer_vehicles = Score::soft(1.0).at_objective_level(0) + Score::soft(500.0);
+        let more_vehicles = Score::soft(2.0).at_objective_level(0) + Score::soft(100.0);
+
+        assert!(fewer_vehicles < more_vehicles);
+        assert!(Score::hard(1.0) > more_vehicles);
+        assert_eq!(
+            (more_vehicles - fewer_vehicles).objective_scores,
+            [1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_leading_soft_score() {
+        assert_eq!(Score::new(3.0, 100.0).leading_soft_score(), 100.0);
+
+        let score = Score::soft(2.0).at_objective_level(1) + Score::soft(100.0);
+        assert_eq!(score.leading_objective_level(), Some(1));
+        assert_eq!(score.leading_soft_score(), 2.0);
+    }
 }
@@ -0,0 +1,193 @@
+use fxhash::FxHashMap;
+use jiff::SignedDuration;
+
+use crate::{
+    problem::{job::JobIdx, vehicle::VehicleIdx},
+    solver::{
+        insertion::{Insertion, for_each_insertion},
+        insertion_context::InsertionContext,
+        solution::working_solution::WorkingSolution,
+    },
+};
+
+/// Minimal extension of a vehicle shift which would allow an unassigned job to be served by that vehicle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShiftExtension {
+    pub job_id: JobIdx,
+    pub vehicle_id: VehicleIdx,
+    pub extension: SignedDuration,
+}
+
+impl ShiftExtension {
+    /// Extension rounded up to the next minute
+    pub fn extension_minutes(&self) -> i64 {
+        (self.extension.as_secs_f64() / 60.0).ceil() as i64
+    }
+}
+
+/// Computes the overtime needed to perform an insertion, ignoring the latest end and the maximum working duration of the vehicle.
+///
+/// Returns None if the insertion is infeasible for another reason than the vehicle shift.
+fn required_extension(solution: &WorkingSolution, insertion: &Insertion) -> Option<SignedDuration> {
+    let problem = solution.problem();
+    let context = InsertionContext::new(problem, solution, insertion, false);
+    let route = context.route();
+    let vehicle = route.vehicle(problem);
+
+    let is_valid_capacity = match insertion {
+        Insertion::Service(insertion) => route.is_valid_capacity_change(
+            problem,
+            insertion.inserted_activity_ids(),
+            insertion.position,
+            insertion.position,
+        ),
+        Insertion::Shipment(insertion) => route.is_valid_capacity_change(
+            problem,
+            insertion.inserted_activity_ids(route),
+            insertion.pickup_position,
+            insertion.delivery_position,
+        ),
+    };
+
+    if !is_valid_capacity {
+        return None;
+    }
+
+    // Time windows of the jobs are not negotiable, only the shift of the vehicle is
+    let violates_time_windows = context.updated_activities_iter().any(|data| {
+        let job_activity = problem.job_activity(data.job_id);
+        let time_windows = job_activity.time_windows();
+        !time_windows.is_empty() && !time_windows.is_satisfied(data.arrival_time)
+    });
+
+    if violates_time_windows {
+        return None;
+    }
+
+    let start = context.compute_vehicle_start();
+    let end = context.compute_vehicle_end();
+
+    let mut extension = SignedDuration::ZERO;
+
+    if let Some(deadline) = vehicle.latest_end_deadline() {
+        extension = extension.max(end.duration_since(deadline));
+    }
+
+    if let Some(maximum_working_duration) = vehicle.maximum_working_duration() {
+        extension = extension.max(end.duration_since(start) - maximum_working_duration);
+    }
+
+    Some(extension)
+}
+
+/// For each unassigned job, computes the minimal shift extension per vehicle that would allow serving it.
+///
+/// Jobs that cannot be served by extending a shift (time windows, capacity, skills...) are not reported,
+/// neither are jobs which don't need any extension to be inserted.
+pub fn compute_shift_extensions(solution: &WorkingSolution) -> Vec<ShiftExtension> {
+    let mut extensions: Vec<ShiftExtension> = vec![];
+
+    let mut unassigned_jobs = solution
+        .unassigned_jobs()
+        .iter()
+        .copied()
+        .collect::<Vec<_>>();
+    unassigned_jobs.sort();
+
+    for job_id in unassigned_jobs {
+        let mut best_per_vehicle: FxHashMap<VehicleIdx, SignedDuration> = FxHashMap::default();
+
+        for_each_insertion(solution, job_id, |insertion| {
+            if let Some(extension) = required_extension(solution, &insertion) {
+                let vehicle_id = insertion.route(solution).vehicle_id();
+                best_per_vehicle
+                    .entry(vehicle_id)
+                    .and_modify(|best| *best = (*best).min(extension))
+                    .or_insert(extension);
+            }
+        });
+
+        let mut job_extensions = best_per_vehicle
+            .into_iter()
+            .filter(|(_, extension)| extension.is_positive())
+            .map(|(vehicle_id, extension)| ShiftExtension {
+                job_id,
+                vehicle_id,
+                extension,
+            })
+            .collect::<Vec<_>>();
+
+        job_extensions.sort_by_key(|extension| (extension.extension, extension.vehicle_id));
+        extensions.extend(job_extensions);
+    }
+
+    extensions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::{job::JobIdx, time_window::TimeWindow, vehicle::VehicleIdx},
+        solver::{
+            insertion::{Insertion, ServiceInsertion},
+            solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+        },
+        test_utils::{TestProblemOptions, TestService, create_problem_for_tw_change},
+        timestamp,
+    };
+
+    use super::{ShiftExtension, compute_shift_extensions};
+
+    #[test]
+    fn test_compute_shift_extensions() {
+        let problem = create_problem_for_tw_change(
+            vec![
+                TestService::default(),
+                TestService::default(),
+                TestService::with_time_window(TimeWindow::from_iso(
+                    Some("2025-11-30T08:00:00+02:00"),
+                    Some("2025-11-30T08:05:00+02:00"),
+                )),
+            ],
+            TestProblemOptions {
+                earliest_start: Some(timestamp!("2025-11-30T07:00:00+02:00")),
+                latest_end: Some(timestamp!("2025-11-30T08:00:00+02:00")),
+                ..TestProblemOptions::default()
+            },
+        );
+
+        let mut solution = WorkingSolution::new(Arc::new(problem));
+        solution.insert(&Insertion::Service(ServiceInsertion {
+            route_id: RouteIdx::new(0),
+            job_index: JobIdx::new(0),
+            position: 0,
+        }));
+
+        // Route: 07:00 depot, 07:30 - 07:40 service 1, ends at 07:40.
+        // Service 2 after service 1: 08:10 - 08:20, 20 minutes of overtime.
+        // Service 3 cannot be reached after service 1 before its time window closes,
+        // serving it first (08:00 - 08:10) makes service 1 end at 08:50.
+        let extensions = compute_shift_extensions(&solution);
+
+        assert_eq!(
+            extensions,
+            vec![
+                ShiftExtension {
+                    job_id: JobIdx::new(1),
+                    vehicle_id: VehicleIdx::new(0),
+                    extension: SignedDuration::from_mins(20),
+                },
+                ShiftExtension {
+                    job_id: JobIdx::new(2),
+                    vehicle_id: VehicleIdx::new(0),
+                    extension: SignedDuration::from_mins(50),
+                },
+            ]
+        );
+        assert_eq!(extensions[0].extension_minutes(), 20);
+    }
+}
@@ -1,6 +1,9 @@
 pub mod population;
 pub mod route;
+pub mod route_bbox_index;
 pub mod route_id;
 pub mod route_update_iterator;
+pub mod solution_pool;
 pub(crate) mod utils;
+pub mod verify;
 pub mod working_solution;
@@ -1,11 +1,13 @@
 use std::{collections::BTreeMap, sync::atomic::AtomicUsize};
 
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 
 use crate::{
+    problem::job::JobIdx,
     selector::select_solution::SelectSolution,
     solver::{
         accepted_solution::{AcceptedSolution, AcceptedSolutionId},
+        constraints::constraint::Constraint,
         score::{Score, ScoreAnalysis},
         solution::working_solution::WorkingSolution,
         solver_params::PopulationParams,
@@ -91,48 +93,47 @@ impl Population {
         }
     }
 
-    fn remove_worst_fitness(&mut self) -> Option<AcceptedSolution> {
+    /// Removes the solution with the worst fitness, ignoring the first `skip` solutions
+    fn remove_worst_fitness(&mut self, skip: usize) -> Option<AcceptedSolution> {
         let worst_fitness = self
             .biased_fitnesses
             .iter()
+            .skip(skip)
             .max_by(|a, b| a.partial_cmp(b).unwrap())?;
 
-        let worst_index = self
-            .biased_fitnesses
-            .iter()
-            .position(|&x| x == *worst_fitness)?;
+        let worst_index = skip
+            + self
+                .biased_fitnesses
+                .iter()
+                .skip(skip)
+                .position(|&x| x == *worst_fitness)?;
 
         Some(self.solutions.remove(worst_index))
     }
 
+    /// Returns the solution that was discarded, either `solution` itself when the population already
+    /// has an identical one or the solution evicted to make room for it, see `SolutionPool`
     pub fn add_solution(
         &mut self,
         solution: WorkingSolution,
         score: Score,
         score_analysis: ScoreAnalysis,
-    ) {
+    ) -> Option<WorkingSolution> {
         let is_duplicate = self.solutions.iter().any(|accepted_solution| {
             accepted_solution.score == score && accepted_solution.solution.is_identical(&solution)
         });
 
         // We don't add it if duplicate to keep the population varied enough
         if is_duplicate {
-            return;
+            return Some(solution);
         }
 
-        #[allow(clippy::collapsible_if)] // I think it's clearer this way
-        if self.solutions.len() == self.params.size {
-            if let Some(removed_solution) = self.remove_worst_fitness() {
-                // TODO: remove based on fitness value instead of worst
-                // Cleanup data for removed solution
-                self.broken_pair_distances.remove(&removed_solution.id);
-                self.broken_pair_distances
-                    .iter_mut()
-                    .for_each(|(_, distances)| {
-                        distances.retain(|_, v| *v != removed_solution.id);
-                    });
-            }
-        }
+        let evicted_solution = if self.solutions.len() >= self.params.size {
+            // TODO: remove based on fitness value instead of worst
+            self.evict_worst_fitness(0)
+        } else {
+            None
+        };
 
         let id = AcceptedSolutionId::new(self.next_id());
 
@@ -173,6 +174,37 @@ impl Population {
         }
 
         self.update_fitnesses();
+
+        if let Some(max_memory_bytes) = self.params.max_memory_bytes {
+            while self.solutions.len() > 1 && self.estimated_size_bytes() > max_memory_bytes {
+                // The best solution is never evicted
+                self.evict_worst_fitness(1);
+                self.update_fitnesses();
+            }
+        }
+
+        evicted_solution
+    }
+
+    fn evict_worst_fitness(&mut self, skip: usize) -> Option<WorkingSolution> {
+        let removed_solution = self.remove_worst_fitness(skip)?;
+
+        // Cleanup data for removed solution
+        self.broken_pair_distances.remove(&removed_solution.id);
+        self.broken_pair_distances
+            .iter_mut()
+            .for_each(|(_, distances)| {
+                distances.retain(|_, v| *v != removed_solution.id);
+            });
+
+        Some(removed_solution.solution)
+    }
+
+    pub fn estimated_size_bytes(&self) -> usize {
+        self.solutions
+            .iter()
+            .map(|accepted_solution| accepted_solution.solution.estimated_size_bytes())
+            .sum()
     }
 
     pub fn biased_fitness(&self, solution: &AcceptedSolution) -> f64 {
@@ -197,6 +229,46 @@ impl Population {
         }
     }
 
+    /// Adds the solutions of another population, in their order
+    pub fn merge(&mut self, other: &Population) {
+        for accepted_solution in &other.solutions {
+            self.add_solution(
+                accepted_solution.solution.clone(),
+                accepted_solution.score,
+                accepted_solution.score_analysis.clone(),
+            );
+        }
+    }
+
+    /// Removes the cancelled jobs from every solution and rescores them, only the routes that
+    /// contained a cancelled job are synced again.
+    /// Solutions becoming identical once the jobs are removed are only kept once.
+    pub fn cancel_jobs(&mut self, job_ids: &FxHashSet<JobIdx>, constraints: &[Constraint]) {
+        let solutions = std::mem::take(&mut self.solutions);
+        self.broken_pair_distances.clear();
+        self.biased_fitnesses.clear();
+
+        for mut accepted_solution in solutions {
+            let mut changed = false;
+            for &job_id in job_ids {
+                changed |= accepted_solution.solution.cancel_job(job_id);
+            }
+
+            if changed {
+                accepted_solution.solution.sync();
+                (accepted_solution.score, accepted_solution.score_analysis) = accepted_solution
+                    .solution
+                    .compute_solution_score(constraints);
+            }
+
+            self.add_solution(
+                accepted_solution.solution,
+                accepted_solution.score,
+                accepted_solution.score_analysis,
+            );
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.solutions.is_empty()
     }
@@ -212,7 +284,11 @@ mod tests {
     use std::sync::Arc;
 
     use crate::{
-        solver::score::{Score, ScoreAnalysis},
+        problem::job::ActivityId,
+        solver::{
+            constraints::constraint_set::ConstraintSet,
+            score::{Score, ScoreAnalysis},
+        },
         test_utils,
     };
 
@@ -281,4 +357,144 @@ mod tests {
         assert_eq!(population.solutions[1].score, Score::soft(10.0));
         assert_eq!(population.solutions[2].score, Score::soft(15.0));
     }
+
+    #[test]
+    fn test_merge_population() {
+        let locations = test_utils::create_location_grid(10, 10);
+        let services = test_utils::create_basic_services(vec![0, 1, 2, 3]);
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ));
+
+        let solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![test_utils::TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1, 2, 3],
+            }],
+        );
+
+        let mut population = Population::new(PopulationParams::default());
+        population.add_solution(
+            solution.clone(),
+            Score::soft(10.0),
+            ScoreAnalysis::default(),
+        );
+
+        let mut other = Population::new(PopulationParams::default());
+        other.add_solution(
+            solution.clone(),
+            Score::soft(10.0),
+            ScoreAnalysis::default(),
+        );
+        other.add_solution(solution.clone(), Score::soft(5.0), ScoreAnalysis::default());
+
+        population.merge(&other);
+
+        // The identical solution is only kept once
+        assert_eq!(population.solutions.len(), 2);
+        assert_eq!(population.solutions[0].score, Score::soft(5.0));
+        assert_eq!(population.solutions[1].score, Score::soft(10.0));
+    }
+
+    #[test]
+    fn test_population_memory_cap() {
+        let locations = test_utils::create_location_grid(10, 10);
+        let services = test_utils::create_basic_services(vec![0, 1, 2, 3]);
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ));
+
+        let create_solution = |service_ids: Vec<usize>| {
+            test_utils::create_test_working_solution(
+                Arc::clone(&problem),
+                vec![test_utils::TestRoute {
+                    vehicle_id: 0,
+                    service_ids,
+                }],
+            )
+        };
+
+        let solution_size = create_solution(vec![0, 1, 2, 3]).estimated_size_bytes();
+
+        let mut population = Population::new(PopulationParams {
+            max_memory_bytes: Some(2 * solution_size),
+            ..PopulationParams::default()
+        });
+
+        population.add_solution(
+            create_solution(vec![0, 1, 2, 3]),
+            Score::soft(10.0),
+            ScoreAnalysis::default(),
+        );
+        population.add_solution(
+            create_solution(vec![3, 2, 1, 0]),
+            Score::soft(20.0),
+            ScoreAnalysis::default(),
+        );
+        population.add_solution(
+            create_solution(vec![1, 0, 3, 2]),
+            Score::soft(5.0),
+            ScoreAnalysis::default(),
+        );
+
+        assert_eq!(population.solutions.len(), 2);
+        assert!(population.estimated_size_bytes() <= 2 * solution_size);
+        assert_eq!(population.solutions[0].score, Score::soft(5.0));
+    }
+
+    #[test]
+    fn test_cancel_jobs() {
+        let locations = test_utils::create_location_grid(10, 10);
+        let services = test_utils::create_basic_services(vec![0, 1, 2, 3]);
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ));
+        let constraints = ConstraintSet::default();
+
+        let mut population = Population::new(PopulationParams::default());
+        for service_ids in [vec![0, 1, 2, 3], vec![3, 1, 0, 2]] {
+            let solution = test_utils::create_test_working_solution(
+                Arc::clone(&problem),
+                vec![test_utils::TestRoute {
+                    vehicle_id: 0,
+                    service_ids,
+                }],
+            );
+            let (score, score_analysis) =
+                solution.compute_solution_score(constraints.constraints());
+            population.add_solution(solution, score, score_analysis);
+        }
+
+        let best_score_before = population.best().unwrap().score;
+
+        let cancelled = FxHashSet::from_iter([JobIdx::new(1)]);
+        population.cancel_jobs(&cancelled, constraints.constraints());
+
+        // No solution is lost and the best one doesn't get worse
+        assert_eq!(population.solutions().len(), 2);
+        assert!(population.best().unwrap().score <= best_score_before);
+        for accepted_solution in population.solutions() {
+            let solution = &accepted_solution.solution;
+            assert_eq!(
+                accepted_solution.score,
+                solution.compute_solution_score(constraints.constraints()).0
+            );
+            assert!(solution.cancelled_jobs().contains(&JobIdx::new(1)));
+            assert!(!solution.is_unassigned(JobIdx::new(1)));
+            assert!(
+                !solution
+                    .routes()
+                    .iter()
+                    .any(|route| route.contains_activity(ActivityId::Service(JobIdx::new(1))))
+            );
+        }
+
+        // Cancelling the same jobs again leaves the population untouched
+        population.cancel_jobs(&cancelled, constraints.constraints());
+        assert_eq!(population.solutions().len(), 2);
+    }
 }
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use fxhash::FxHashMap;
 use jiff::{SignedDuration, Timestamp};
@@ -6,11 +6,13 @@ use jiff::{SignedDuration, Timestamp};
 use crate::{
     problem::{
         amount::AmountExpression,
-        capacity::{Capacity, is_capacity_satisfied},
+        capacity::{Capacity, is_capacity_satisfied, over_capacity_demand, utilization},
         job::{ActivityId, Job, JobActivity, JobIdx},
+        kilograms::Kilograms,
         location::LocationIdx,
         meters::Meters,
         service::ServiceType,
+        stop_sequence::StopSequence,
         task_dependencies::TaskDependencyType,
         vehicle::{Vehicle, VehicleIdx},
         vehicle_routing_problem::VehicleRoutingProblem,
@@ -18,18 +20,21 @@ use crate::{
     solver::{
         insertion::{Insertion, ServiceInsertion, ShipmentInsertion},
         solution::{
-            route_update_iterator::RouteUpdateIterator,
+            route_update_iterator::{RouteUpdateActivityData, RouteUpdateIterator},
             utils::{
-                compute_activity_arrival_time, compute_departure_time,
-                compute_first_activity_arrival_time, compute_time_slack, compute_vehicle_end,
-                compute_vehicle_start, compute_waiting_duration, compute_waiting_time_slack,
+                compute_activity_arrival_time, compute_arrival_energy, compute_departure_energy,
+                compute_departure_time, compute_first_activity_arrival_time, compute_time_slack,
+                compute_vehicle_end, compute_vehicle_start, compute_waiting_duration,
+                compute_waiting_time_slack,
             },
         },
     },
     utils::{bbox::BBox, bitset::BitSet, sparse_table::SparseTable},
 };
 
-#[derive(Clone)]
+/// Difference (in kWh) under which two energies are the same
+const ENERGY_EPSILON: f64 = 1e-9;
+
 pub struct WorkingSolutionRoute {
     pub(super) version: usize,
 
@@ -45,10 +50,10 @@ pub struct WorkingSolutionRoute {
     /// bwd_jobs[i] is the set of jobs delivered by the route from activity i to end
     pub(super) bwd_jobs: Vec<BitSet>,
 
-    /// fwd_transport_cost[profile_id][i] is the transport cost from activity 0 to activity i for vehicle profile 'profile_id'
+    /// fwd_transport_cost[cost_class_id][i] is the transport cost from activity 0 to activity i for vehicle cost class 'cost_class_id'
     pub(super) fwd_transport_cost: Vec<Vec<f64>>,
 
-    /// bwd_transport_cost[profile_id][i] is the transport cost from activity i to activity 0 for vehicle profile 'profile_id', useful for quickly computing segment reversals
+    /// bwd_transport_cost[cost_class_id][i] is the transport cost from activity i to activity 0 for vehicle cost class 'cost_class_id', useful for quickly computing segment reversals
     pub(super) bwd_transport_cost: Vec<Vec<f64>>,
 
     /// List of activity job IDs in the route order
@@ -73,6 +78,17 @@ pub struct WorkingSolutionRoute {
     /// step 0 is the depot, step[len + 1] is the last step
     pub(super) bwd_cumulative_waiting_durations: Vec<SignedDuration>,
 
+    /// departure_energies[i] is the energy (in kWh) consumed since the last charge when leaving
+    /// activity i, always 0 for the vehicles without a battery
+    pub(super) departure_energies: Vec<f64>,
+
+    /// fwd_energy_excess[i] is the energy over the battery capacity of the stretches between two
+    /// charges ended at or before activity i
+    pub(super) fwd_energy_excess: Vec<f64>,
+
+    /// Energy over the battery capacity of the whole route, see `energy_excess`
+    pub(super) energy_excess: f64,
+
     /// waiting_time_slacks[i] stored the maximum time a task can be moved "backward" in time without causing waiting time, or more waiting time if some already exists.
     pub(super) waiting_time_slacks: Vec<SignedDuration>,
 
@@ -108,6 +124,9 @@ pub struct WorkingSolutionRoute {
     // step 0 is the start depot
     pub(super) current_load: Vec<Capacity>,
 
+    /// Deliveries loaded at the depot over all the trips, at the start and at each reload
+    pub(super) depot_load: Capacity,
+
     /// Total available capacity for the route
     pub(super) delivery_load_slack: Capacity,
 
@@ -117,6 +136,9 @@ pub struct WorkingSolutionRoute {
     /// Store the total set of required skills for the services from the start to step i
     pub(super) skills_sparse_table: SparseTable,
 
+    /// Store the total set of tags of the jobs from the start to step i
+    pub(super) tags_sparse_table: SparseTable,
+
     /// pending_shipments[i] stores the set of pending shipments after visiting activity i
     pub(super) pending_shipments: Vec<BitSet>,
 
@@ -125,11 +147,113 @@ pub struct WorkingSolutionRoute {
 
     pub(super) insertion_ranges: FxHashMap<ActivityId, (usize, usize)>,
 
+    /// Number of activities at the start of the route which are locked, e.g. already dispatched.
+    /// Locked activities are never removed and no activity can be moved or inserted before them
+    locked_len: usize,
+
     bbox: BBox,
 
     out_of_sync: bool,
 }
 
+// Implemented by hand so that `clone_from` reuses the buffers of the route, see `SolutionPool`
+impl Clone for WorkingSolutionRoute {
+    fn clone(&self) -> Self {
+        WorkingSolutionRoute {
+            version: self.version,
+            vehicle_id: self.vehicle_id,
+            jobs: self.jobs.clone(),
+            total_transport_cost: self.total_transport_cost,
+            fwd_jobs: self.fwd_jobs.clone(),
+            bwd_jobs: self.bwd_jobs.clone(),
+            fwd_transport_cost: self.fwd_transport_cost.clone(),
+            bwd_transport_cost: self.bwd_transport_cost.clone(),
+            activity_ids: self.activity_ids.clone(),
+            arrival_times: self.arrival_times.clone(),
+            departure_times: self.departure_times.clone(),
+            waiting_durations: self.waiting_durations.clone(),
+            fwd_cumulative_waiting_durations: self.fwd_cumulative_waiting_durations.clone(),
+            bwd_cumulative_waiting_durations: self.bwd_cumulative_waiting_durations.clone(),
+            departure_energies: self.departure_energies.clone(),
+            fwd_energy_excess: self.fwd_energy_excess.clone(),
+            energy_excess: self.energy_excess,
+            waiting_time_slacks: self.waiting_time_slacks.clone(),
+            fwd_time_slacks: self.fwd_time_slacks.clone(),
+            fwd_load_pickups: self.fwd_load_pickups.clone(),
+            fwd_load_deliveries: self.fwd_load_deliveries.clone(),
+            fwd_load_shipments: self.fwd_load_shipments.clone(),
+            bwd_load_pickups: self.bwd_load_pickups.clone(),
+            bwd_load_deliveries: self.bwd_load_deliveries.clone(),
+            fwd_load_peaks: self.fwd_load_peaks.clone(),
+            bwd_load_peaks: self.bwd_load_peaks.clone(),
+            current_load: self.current_load.clone(),
+            depot_load: self.depot_load.clone(),
+            delivery_load_slack: self.delivery_load_slack.clone(),
+            pickup_load_slack: self.pickup_load_slack.clone(),
+            skills_sparse_table: self.skills_sparse_table.clone(),
+            tags_sparse_table: self.tags_sparse_table.clone(),
+            pending_shipments: self.pending_shipments.clone(),
+            num_shipments: self.num_shipments.clone(),
+            insertion_ranges: self.insertion_ranges.clone(),
+            locked_len: self.locked_len,
+            bbox: self.bbox.clone(),
+            out_of_sync: self.out_of_sync,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.version = source.version;
+        self.vehicle_id = source.vehicle_id;
+        self.jobs.clone_from(&source.jobs);
+        self.total_transport_cost = source.total_transport_cost;
+        self.fwd_jobs.clone_from(&source.fwd_jobs);
+        self.bwd_jobs.clone_from(&source.bwd_jobs);
+        self.fwd_transport_cost
+            .clone_from(&source.fwd_transport_cost);
+        self.bwd_transport_cost
+            .clone_from(&source.bwd_transport_cost);
+        self.activity_ids.clone_from(&source.activity_ids);
+        self.arrival_times.clone_from(&source.arrival_times);
+        self.departure_times.clone_from(&source.departure_times);
+        self.waiting_durations.clone_from(&source.waiting_durations);
+        self.fwd_cumulative_waiting_durations
+            .clone_from(&source.fwd_cumulative_waiting_durations);
+        self.bwd_cumulative_waiting_durations
+            .clone_from(&source.bwd_cumulative_waiting_durations);
+        self.departure_energies
+            .clone_from(&source.departure_energies);
+        self.fwd_energy_excess.clone_from(&source.fwd_energy_excess);
+        self.energy_excess = source.energy_excess;
+        self.waiting_time_slacks
+            .clone_from(&source.waiting_time_slacks);
+        self.fwd_time_slacks.clone_from(&source.fwd_time_slacks);
+        self.fwd_load_pickups.clone_from(&source.fwd_load_pickups);
+        self.fwd_load_deliveries
+            .clone_from(&source.fwd_load_deliveries);
+        self.fwd_load_shipments
+            .clone_from(&source.fwd_load_shipments);
+        self.bwd_load_pickups.clone_from(&source.bwd_load_pickups);
+        self.bwd_load_deliveries
+            .clone_from(&source.bwd_load_deliveries);
+        self.fwd_load_peaks.clone_from(&source.fwd_load_peaks);
+        self.bwd_load_peaks.clone_from(&source.bwd_load_peaks);
+        self.current_load.clone_from(&source.current_load);
+        self.depot_load.clone_from(&source.depot_load);
+        self.delivery_load_slack
+            .clone_from(&source.delivery_load_slack);
+        self.pickup_load_slack.clone_from(&source.pickup_load_slack);
+        self.skills_sparse_table
+            .clone_from(&source.skills_sparse_table);
+        self.tags_sparse_table.clone_from(&source.tags_sparse_table);
+        self.pending_shipments.clone_from(&source.pending_shipments);
+        self.num_shipments.clone_from(&source.num_shipments);
+        self.insertion_ranges.clone_from(&source.insertion_ranges);
+        self.locked_len = source.locked_len;
+        self.bbox.clone_from(&source.bbox);
+        self.out_of_sync = source.out_of_sync;
+    }
+}
+
 impl WorkingSolutionRoute {
     pub fn empty(problem: &VehicleRoutingProblem, vehicle_id: VehicleIdx) -> Self {
         let mut route = WorkingSolutionRoute {
@@ -141,18 +265,22 @@ impl WorkingSolutionRoute {
             total_transport_cost: 0.0,
             fwd_jobs: Vec::new(),
             bwd_jobs: Vec::new(),
-            fwd_transport_cost: vec![vec![]; problem.vehicle_profiles().len()],
-            bwd_transport_cost: vec![vec![]; problem.vehicle_profiles().len()],
+            fwd_transport_cost: vec![vec![]; problem.vehicle_cost_classes().len()],
+            bwd_transport_cost: vec![vec![]; problem.vehicle_cost_classes().len()],
             activity_ids: Vec::new(),
             arrival_times: Vec::new(),
             departure_times: Vec::new(),
             waiting_durations: Vec::new(),
             fwd_cumulative_waiting_durations: Vec::new(),
             bwd_cumulative_waiting_durations: Vec::new(),
+            departure_energies: Vec::new(),
+            fwd_energy_excess: Vec::new(),
+            energy_excess: 0.0,
             waiting_time_slacks: Vec::new(),
             fwd_load_peaks: Vec::new(),
             bwd_load_peaks: Vec::new(),
             current_load: Vec::new(),
+            depot_load: Capacity::with_dimensions(problem.capacity_dimensions()),
             bwd_load_deliveries: Vec::new(),
             bwd_load_pickups: Vec::new(),
             fwd_load_deliveries: Vec::new(),
@@ -162,9 +290,11 @@ impl WorkingSolutionRoute {
             pending_shipments: Vec::new(),
             num_shipments: Vec::new(),
             skills_sparse_table: SparseTable::empty(),
+            tags_sparse_table: SparseTable::empty(),
             delivery_load_slack: problem.vehicle(vehicle_id).capacity().clone(),
             pickup_load_slack: problem.vehicle(vehicle_id).capacity().clone(),
             insertion_ranges: FxHashMap::default(),
+            locked_len: 0,
         };
 
         route.update_data(problem);
@@ -184,6 +314,72 @@ impl WorkingSolutionRoute {
         self.version
     }
 
+    /// Approximate number of bytes used by the route, used to bound the memory of the population
+    pub fn estimated_size_bytes(&self) -> usize {
+        #[allow(clippy::ptr_arg)] // The capacity is not available on slices
+        fn vec_size<T>(vec: &Vec<T>) -> usize {
+            vec.capacity() * std::mem::size_of::<T>()
+        }
+
+        #[allow(clippy::ptr_arg)]
+        fn bitsets_size(bitsets: &Vec<BitSet>) -> usize {
+            vec_size(bitsets) + bitsets.iter().map(BitSet::heap_size).sum::<usize>()
+        }
+
+        std::mem::size_of::<Self>()
+            + self.jobs.capacity() * std::mem::size_of::<(ActivityId, usize)>()
+            + self.insertion_ranges.capacity() * std::mem::size_of::<(ActivityId, (usize, usize))>()
+            + bitsets_size(&self.fwd_jobs)
+            + bitsets_size(&self.bwd_jobs)
+            + bitsets_size(&self.pending_shipments)
+            + self.fwd_transport_cost.iter().map(vec_size).sum::<usize>()
+            + self.bwd_transport_cost.iter().map(vec_size).sum::<usize>()
+            + vec_size(&self.activity_ids)
+            + vec_size(&self.arrival_times)
+            + vec_size(&self.departure_times)
+            + vec_size(&self.waiting_durations)
+            + vec_size(&self.fwd_cumulative_waiting_durations)
+            + vec_size(&self.bwd_cumulative_waiting_durations)
+            + vec_size(&self.departure_energies)
+            + vec_size(&self.fwd_energy_excess)
+            + vec_size(&self.waiting_time_slacks)
+            + vec_size(&self.fwd_time_slacks)
+            + vec_size(&self.fwd_load_pickups)
+            + vec_size(&self.fwd_load_deliveries)
+            + vec_size(&self.fwd_load_shipments)
+            + vec_size(&self.bwd_load_pickups)
+            + vec_size(&self.bwd_load_deliveries)
+            + vec_size(&self.fwd_load_peaks)
+            + vec_size(&self.bwd_load_peaks)
+            + vec_size(&self.current_load)
+            + vec_size(&self.num_shipments)
+            + self.skills_sparse_table.heap_size()
+            + self.tags_sparse_table.heap_size()
+    }
+
+    pub fn locked_len(&self) -> usize {
+        self.locked_len
+    }
+
+    pub fn is_locked(&self, position: usize) -> bool {
+        position < self.locked_len
+    }
+
+    /// Locks the first `len` activities of the route
+    pub fn lock(&mut self, len: usize) {
+        assert!(len <= self.len());
+        assert!(
+            len == 0 || !self.contains_pending_shipment(0, len),
+            "Cannot lock a pickup without its delivery"
+        );
+
+        self.locked_len = len;
+    }
+
+    pub fn bbox(&self) -> &BBox {
+        &self.bbox
+    }
+
     pub fn bbox_intersects(&self, other: &WorkingSolutionRoute) -> bool {
         if self.is_empty() || other.is_empty() {
             return false; // TODO: build this into bbox properly
@@ -196,6 +392,21 @@ impl WorkingSolutionRoute {
         &self.current_load[position + 1]
     }
 
+    /// Highest share of the capacity needed by the part of the trip before `position` and by the
+    /// part after it, a reload at the position with the lowest value balances both trips
+    pub fn reload_split_utilization(
+        &self,
+        problem: &VehicleRoutingProblem,
+        position: usize,
+    ) -> f64 {
+        assert!(position > 0 && position < self.len());
+        let capacity = self.vehicle(problem).capacity();
+        let before = &self.fwd_load_deliveries[position - 1] + &self.fwd_load_pickups[position - 1];
+        let after = &self.bwd_load_deliveries[position - 1] + &self.bwd_load_pickups[position - 1];
+
+        utilization(capacity, &before).max(utilization(capacity, &after))
+    }
+
     pub fn bwd_load_peak(&self, i: usize) -> &Capacity {
         &self.bwd_load_peaks[i]
     }
@@ -471,10 +682,77 @@ impl WorkingSolutionRoute {
         &self.current_load
     }
 
+    /// Deliveries loaded at the depot by the route, over all its trips
+    pub fn depot_load(&self) -> &Capacity {
+        &self.depot_load
+    }
+
+    /// Maximum delay the route can absorb from its optimized start without violating a time window or the
+    /// end of the shift, `None` when the route has no time constraints.
+    pub fn time_slack(&self) -> Option<SignedDuration> {
+        if self.is_empty() || self.fwd_time_slacks[0] == SignedDuration::MAX {
+            return None;
+        }
+
+        Some(self.fwd_time_slacks[0] - self.departure_shift())
+    }
+
     pub fn total_waiting_duration(&self) -> SignedDuration {
         self.waiting_durations.iter().sum()
     }
 
+    /// Duration by which the departure from the depot can be postponed to absorb waiting durations.
+    ///
+    /// Any delay is absorbed by the waiting durations along the route, so postponing the departure
+    /// by at most the total waiting duration never moves the end of the route. The shift is also bounded
+    /// by fwd_time_slacks[0] so that no time window or latest start gets violated.
+    pub fn departure_shift(&self) -> SignedDuration {
+        if self.is_empty() {
+            return SignedDuration::ZERO;
+        }
+
+        self.fwd_time_slacks[0]
+            .min(self.total_waiting_duration())
+            .max(SignedDuration::ZERO)
+    }
+
+    /// Start of the route once the departure has been shifted by `departure_shift`
+    pub fn optimized_start(&self, problem: &VehicleRoutingProblem) -> Timestamp {
+        self.start(problem) + self.departure_shift()
+    }
+
+    pub fn optimized_duration(&self, problem: &VehicleRoutingProblem) -> SignedDuration {
+        if self.is_empty() {
+            return SignedDuration::ZERO;
+        }
+
+        self.end(problem)
+            .duration_since(self.optimized_start(problem))
+    }
+
+    pub fn optimized_total_waiting_duration(&self) -> SignedDuration {
+        self.total_waiting_duration() - self.departure_shift()
+    }
+
+    /// Activities of the route when the vehicle departs at `optimized_start`.
+    /// The delay is propagated along the route until it is fully absorbed by waiting durations.
+    pub fn optimized_activities_iter(&self) -> impl Iterator<Item = RouteActivityInfo> {
+        let mut delay = self.departure_shift();
+
+        self.activities_iter().map(move |activity| {
+            let absorbed = delay.min(activity.waiting_duration);
+            let arrival_time = activity.arrival_time + delay;
+            delay -= absorbed;
+
+            RouteActivityInfo {
+                activity_id: activity.activity_id,
+                arrival_time,
+                departure_time: activity.departure_time + delay,
+                waiting_duration: activity.waiting_duration - absorbed,
+            }
+        })
+    }
+
     pub fn vehicle_id(&self) -> VehicleIdx {
         self.vehicle_id
     }
@@ -576,19 +854,26 @@ impl WorkingSolutionRoute {
     pub fn in_insertion_neighborhood(
         &self,
         problem: &VehicleRoutingProblem,
+        neighborhood_size: usize,
         activity_id: ActivityId,
         position: usize,
     ) -> bool {
-        let previous = self.activity_ids.get(position - 1).copied();
+        let previous = position
+            .checked_sub(1)
+            .and_then(|previous| self.get(previous));
         let next = self.activity_ids.get(position).copied();
 
         match (previous, next) {
             (Some(previous), Some(next)) => {
-                problem.in_nearest_neighborhood_of(previous, activity_id)
-                    || problem.in_nearest_neighborhood_of(next, activity_id)
+                problem.in_nearest_neighborhood_of(previous, activity_id, neighborhood_size)
+                    || problem.in_nearest_neighborhood_of(next, activity_id, neighborhood_size)
+            }
+            (Some(previous), None) => {
+                problem.in_nearest_neighborhood_of(previous, activity_id, neighborhood_size)
+            }
+            (None, Some(next)) => {
+                problem.in_nearest_neighborhood_of(next, activity_id, neighborhood_size)
             }
-            (Some(previous), None) => problem.in_nearest_neighborhood_of(previous, activity_id),
-            (None, Some(next)) => problem.in_nearest_neighborhood_of(next, activity_id),
             (None, None) => true, // Route is empty
         }
     }
@@ -596,38 +881,47 @@ impl WorkingSolutionRoute {
     pub fn in_segment_insertion_neighborhood(
         &self,
         problem: &VehicleRoutingProblem,
+        neighborhood_size: usize,
         from_activity_id: ActivityId,
         to_activity_id: ActivityId,
         position: usize,
     ) -> bool {
         // position - 1 => from_activity_id ... to_activity_id ... position
 
-        let previous = self.activity_ids.get(position - 1).copied();
+        let previous = position
+            .checked_sub(1)
+            .and_then(|previous| self.get(previous));
         let next = self.activity_ids.get(position).copied();
 
         match (previous, next) {
             (Some(previous), Some(next)) => {
-                problem.in_nearest_neighborhood_of(previous, from_activity_id)
-                    && problem.in_nearest_neighborhood_of(next, to_activity_id)
+                problem.in_nearest_neighborhood_of(previous, from_activity_id, neighborhood_size)
+                    && problem.in_nearest_neighborhood_of(next, to_activity_id, neighborhood_size)
             }
             (Some(previous), None) => {
-                problem.in_nearest_neighborhood_of(previous, from_activity_id)
+                problem.in_nearest_neighborhood_of(previous, from_activity_id, neighborhood_size)
+            }
+            (None, Some(next)) => {
+                problem.in_nearest_neighborhood_of(next, to_activity_id, neighborhood_size)
             }
-            (None, Some(next)) => problem.in_nearest_neighborhood_of(next, to_activity_id),
             (None, None) => true, // Route is empty
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn in_swap_neighborhood(
         &self,
         problem: &VehicleRoutingProblem,
+        neighborhood_size: usize,
         self_pos_start: usize,
         self_pos_end: usize, // Exclusive
         other: &WorkingSolutionRoute,
         other_pos_start: usize,
         other_pos_end: usize, // Exclusive
     ) -> bool {
-        let self_previous = self.get(self_pos_start - 1);
+        let self_previous = self_pos_start
+            .checked_sub(1)
+            .and_then(|previous| self.get(previous));
         let self_next = self.get(self_pos_end);
 
         let other_activity_start = other.activity_id(other_pos_start);
@@ -635,13 +929,20 @@ impl WorkingSolutionRoute {
 
         match (self_previous, self_next) {
             (Some(previous), Some(next)) => {
-                problem.in_nearest_neighborhood_of(previous, other_activity_start)
-                    && problem.in_nearest_neighborhood_of(next, other_activity_end)
+                problem.in_nearest_neighborhood_of(
+                    previous,
+                    other_activity_start,
+                    neighborhood_size,
+                ) && problem.in_nearest_neighborhood_of(next, other_activity_end, neighborhood_size)
             }
-            (Some(previous), None) => {
-                problem.in_nearest_neighborhood_of(previous, other_activity_start)
+            (Some(previous), None) => problem.in_nearest_neighborhood_of(
+                previous,
+                other_activity_start,
+                neighborhood_size,
+            ),
+            (None, Some(next)) => {
+                problem.in_nearest_neighborhood_of(next, other_activity_end, neighborhood_size)
             }
-            (None, Some(next)) => problem.in_nearest_neighborhood_of(next, other_activity_end),
             (None, None) => true, // Route is empty
         }
     }
@@ -654,6 +955,7 @@ impl WorkingSolutionRoute {
     pub fn reset(&mut self, problem: &VehicleRoutingProblem) {
         self.jobs.clear();
         self.activity_ids.clear();
+        self.locked_len = 0;
         self.bbox = BBox::default();
 
         self.update_data(problem);
@@ -691,13 +993,29 @@ impl WorkingSolutionRoute {
 
         if let Some(&position) = self.jobs.get(&activity_id) {
             match activity_id {
-                ActivityId::Service(_) => self.remove(problem, position).is_some(),
+                ActivityId::Service(_) => {
+                    if self.is_locked(position) {
+                        return false;
+                    }
+
+                    self.remove(problem, position).is_some()
+                }
                 ActivityId::ShipmentPickup(id) => {
+                    // The pickup is always before the delivery, checking the pickup is enough
+                    if self.is_locked(position) {
+                        return false;
+                    }
+
                     let delivery = self.jobs.get(&ActivityId::ShipmentDelivery(id));
                     self.remove(problem, *delivery.unwrap());
                     self.remove(problem, position).is_some()
                 }
                 ActivityId::ShipmentDelivery(id) => {
+                    let pickup_position = self.jobs[&ActivityId::ShipmentPickup(id)];
+                    if self.is_locked(pickup_position) {
+                        return false;
+                    }
+
                     self.remove(problem, position);
                     let pickup = self.jobs.get(&ActivityId::ShipmentPickup(id));
                     self.remove(problem, *pickup.unwrap()).is_some()
@@ -835,6 +1153,8 @@ impl WorkingSolutionRoute {
         self.arrival_times.resize(len, Timestamp::MAX);
         self.departure_times.resize(len, Timestamp::MAX);
         self.waiting_durations.resize(len, SignedDuration::ZERO);
+        self.departure_energies.resize(len, 0.0);
+        self.fwd_energy_excess.resize(len, 0.0);
 
         self.waiting_time_slacks.resize(len, SignedDuration::MAX);
 
@@ -908,6 +1228,8 @@ impl WorkingSolutionRoute {
         let vehicle = self.vehicle(problem);
 
         self.total_transport_cost = 0.0;
+        self.energy_excess = 0.0;
+        self.depot_load.reset();
 
         if self.is_empty() {
             self.delivery_load_slack.update(vehicle.capacity());
@@ -930,6 +1252,19 @@ impl WorkingSolutionRoute {
             self.skills_sparse_table = SparseTable::build(bitsets)
         }
 
+        if problem.has_tags() {
+            let bitsets = self
+                .activity_ids
+                .iter()
+                .map(|&activity_id| {
+                    let job = problem.job(activity_id.job_id());
+                    job.tags_bitset()
+                })
+                .cloned()
+                .collect();
+            self.tags_sparse_table = SparseTable::build(bitsets)
+        }
+
         let mut current_load_pickups = Capacity::with_dimensions(problem.capacity_dimensions());
         let mut current_load_deliveries = Capacity::with_dimensions(problem.capacity_dimensions());
         let mut current_load_shipments = Capacity::with_dimensions(problem.capacity_dimensions());
@@ -1001,7 +1336,14 @@ impl WorkingSolutionRoute {
                             }
                             ServiceType::Delivery => {
                                 current_load_deliveries += job.demand();
+                                self.depot_load += job.demand();
+                            }
+                            // The pickups are unloaded and a new trip starts
+                            ServiceType::Reload => {
+                                current_load_pickups.reset();
+                                current_load_deliveries.reset();
                             }
+                            ServiceType::Charge => {}
                         }
                     }
                 }
@@ -1032,30 +1374,58 @@ impl WorkingSolutionRoute {
             self.waiting_durations[i] =
                 compute_waiting_duration(problem, activity_id, self.arrival_times[i]);
 
+            let arrival_energy = compute_arrival_energy(
+                problem,
+                self.vehicle_id,
+                (i > 0).then(|| (self.activity_ids[i - 1], self.departure_energies[i - 1])),
+                activity_id,
+            );
+
             self.departure_times[i] = compute_departure_time(
                 problem,
+                self.vehicle_id,
                 self.arrival_times[i],
                 self.waiting_durations[i],
                 activity_id,
+                arrival_energy,
+            );
+
+            self.departure_energies[i] = compute_departure_energy(
+                problem,
+                self.vehicle_id,
+                activity_id,
+                arrival_energy,
+                self.arrival_times[i],
+                self.departure_times[i],
             );
 
+            let previous_energy_excess = if i == 0 {
+                0.0
+            } else {
+                self.fwd_energy_excess[i - 1]
+            };
+            self.fwd_energy_excess[i] = previous_energy_excess
+                + self.charge_energy_excess(problem, activity_id, arrival_energy);
+
             self.fwd_cumulative_waiting_durations[i + 1] =
                 self.waiting_durations[i] + self.fwd_cumulative_waiting_durations[i];
 
-            for (profile_id, profile) in problem.vehicle_profiles().iter().enumerate() {
+            for cost_class_id in 0..problem.vehicle_cost_classes().len() {
                 if i == 0 {
-                    self.fwd_transport_cost[profile_id][i] = 0.0;
-                    self.bwd_transport_cost[profile_id][i] = 0.0;
+                    self.fwd_transport_cost[cost_class_id][i] = 0.0;
+                    self.bwd_transport_cost[cost_class_id][i] = 0.0;
                 } else {
-                    self.fwd_transport_cost[profile_id][i] = self.fwd_transport_cost[profile_id]
-                        [i - 1]
-                        + profile.travel_cost(
+                    self.fwd_transport_cost[cost_class_id][i] = self.fwd_transport_cost
+                        [cost_class_id][i - 1]
+                        + problem.cost_class_travel_cost(
+                            cost_class_id,
                             problem.job_activity(self.activity_ids[i - 1]).location_id(),
                             problem.job_activity(activity_id).location_id(),
                         );
-                    self.bwd_transport_cost[profile_id][i] = self.bwd_transport_cost[profile_id]
-                        [i - 1]
-                        + profile.travel_cost(
+                    self.bwd_transport_cost[cost_class_id][i] = self.bwd_transport_cost
+                        [cost_class_id][i - 1]
+                        + problem.cost_class_travel_cost(
+                            cost_class_id,
                             problem.job_activity(activity_id).location_id(),
                             problem.job_activity(self.activity_ids[i - 1]).location_id(),
                         );
@@ -1083,6 +1453,12 @@ impl WorkingSolutionRoute {
 
         self.fwd_cumulative_waiting_durations[len + 1] = self.fwd_cumulative_waiting_durations[len];
 
+        self.energy_excess = self.fwd_energy_excess[len - 1]
+            + self.end_energy_excess(
+                problem,
+                Some((self.activity_ids[len - 1], self.departure_energies[len - 1])),
+            );
+
         assert!(
             self.fwd_load_shipments[self.len() - 1].is_empty(),
             "{:?}",
@@ -1124,28 +1500,41 @@ impl WorkingSolutionRoute {
                     ServiceType::Delivery => {
                         current_load_deliveries += job.demand();
                     }
+                    // The deliveries of the previous trip are loaded at the start
+                    ServiceType::Reload => {
+                        current_load_pickups.reset();
+                        current_load_deliveries.reset();
+                    }
+                    ServiceType::Charge => {}
                 }
             }
         }
 
-        // The load at start is the load of all deliveries
+        // The load at start is the load of all deliveries of the first trip
         self.current_load[0].update(&current_load_deliveries);
 
+        // Peaks are built from the previous peak in place, without a temporary amount. They are
+        // per trip: a trip starts at the step after a reload and ends at the step of the reload
         self.fwd_load_peaks[0].update(&self.current_load[0]);
-
-        let mut peak = self.current_load[0].clone();
-        self.fwd_load_peaks[0].update(&peak);
         for i in 1..self.fwd_load_peaks.len() {
-            peak.update_max(&self.current_load[i]);
-
-            self.fwd_load_peaks[i].update(&peak);
+            let (previous, next) = self.fwd_load_peaks.split_at_mut(i);
+            if i <= len && problem.job(self.activity_ids[i - 1].job_id()).is_reload() {
+                next[0].update(&self.current_load[i]);
+            } else {
+                next[0].update(&previous[i - 1]);
+                next[0].update_max(&self.current_load[i]);
+            }
         }
 
-        peak.update(&self.current_load[len + 1]);
-        self.bwd_load_peaks[len + 1].update(&peak);
-        for i in (0..self.bwd_load_peaks.len()).rev() {
-            peak.update_max(&self.current_load[i]);
-            self.bwd_load_peaks[i].update(&peak);
+        self.bwd_load_peaks[len + 1].update(&self.current_load[len + 1]);
+        for i in (0..len + 1).rev() {
+            let (left, right) = self.bwd_load_peaks.split_at_mut(i + 1);
+            if i < len && problem.job(self.activity_ids[i].job_id()).is_reload() {
+                left[i].update(&self.current_load[i]);
+            } else {
+                left[i].update(&right[0]);
+                left[i].update_max(&self.current_load[i]);
+            }
         }
 
         let vehicle_capacity = self.vehicle(problem).capacity();
@@ -1156,7 +1545,7 @@ impl WorkingSolutionRoute {
             .update_expr(vehicle_capacity - &self.current_load[self.len()]);
 
         if problem.has_time_windows() || vehicle.latest_end_time().is_some() {
-            let latest_end = vehicle.latest_end_time().unwrap_or(Timestamp::MAX);
+            let latest_end = vehicle.latest_end_deadline().unwrap_or(Timestamp::MAX);
 
             self.fwd_time_slacks[len + 1] = latest_end.duration_since(self.end(problem));
             self.bwd_cumulative_waiting_durations[len + 1] = SignedDuration::ZERO;
@@ -1263,11 +1652,49 @@ impl WorkingSolutionRoute {
     /// activity can be inserted without violating task dependencies.
     ///
     /// The range is inclusive on both ends, i.e. `(start, end)` is a valid insertion range.
+    /// The range never starts before the locked activities, it is empty when `start > end`.
     pub fn insertion_range(&self, activity_id: ActivityId) -> (usize, usize) {
-        self.insertion_ranges
+        let (start, end) = self
+            .insertion_ranges
             .get(&activity_id)
             .copied()
-            .unwrap_or((0, self.len()))
+            .unwrap_or((0, self.len()));
+
+        (start.max(self.locked_len), end)
+    }
+
+    /// Returns the range of indices where the activity can be inserted without breaking the stop
+    /// sequences of the route: first stops, then unpinned stops, then last stops.
+    ///
+    /// The range is inclusive on both ends, like [`Self::insertion_range`].
+    pub fn stop_sequence_range(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_id: ActivityId,
+    ) -> (usize, usize) {
+        if !problem.has_stop_sequences() {
+            return (0, self.len());
+        }
+
+        let rank = |activity_id: &ActivityId| {
+            StopSequence::rank(problem.job_activity(*activity_id).stop_sequence())
+        };
+
+        let target_rank = rank(&activity_id);
+        let start = self
+            .activity_ids
+            .iter()
+            .take_while(|activity_id| rank(activity_id) < target_rank)
+            .count();
+        let end = self.len()
+            - self
+                .activity_ids
+                .iter()
+                .rev()
+                .take_while(|activity_id| rank(activity_id) > target_rank)
+                .count();
+
+        (start, end)
     }
 
     pub fn random_activity<R>(&self, rng: &mut R) -> usize
@@ -1356,6 +1783,17 @@ impl WorkingSolutionRoute {
             }
         }
 
+        if problem.has_tags() {
+            let vehicle = self.vehicle(problem);
+            if !other.tags_sparse_table.range_covered_by(
+                start,
+                end - 1,
+                vehicle.accepted_tags_bitset(),
+            ) {
+                return false;
+            }
+        }
+
         true
     }
 
@@ -1411,7 +1849,40 @@ impl WorkingSolutionRoute {
             return false;
         }
 
-        job.skills_satisfied_by_vehicle(vehicle)
+        if let Some(owner_vehicle_id) = problem.vehicle_stop_owner(job_id)
+            && owner_vehicle_id != self.vehicle_id
+        {
+            return false;
+        }
+
+        job.skills_satisfied_by_vehicle(vehicle) && job.tags_allowed_by_vehicle(vehicle)
+    }
+
+    pub fn has_charges(&self, problem: &VehicleRoutingProblem) -> bool {
+        self.activity_ids
+            .iter()
+            .any(|activity_id| problem.job(activity_id.job_id()).is_charge())
+    }
+
+    pub fn has_reloads(&self, problem: &VehicleRoutingProblem) -> bool {
+        self.activity_ids
+            .iter()
+            .any(|activity_id| problem.job(activity_id.job_id()).is_reload())
+    }
+
+    /// Departure times of the trips of the route, from the start of the route and from each reload
+    pub fn trip_starts(&self, problem: &VehicleRoutingProblem) -> Vec<Timestamp> {
+        if self.is_empty() {
+            return vec![];
+        }
+
+        std::iter::once(self.start(problem))
+            .chain(
+                self.activities_iter()
+                    .filter(|activity| activity.job(problem).is_reload())
+                    .map(|activity| activity.departure_time()),
+            )
+            .collect()
     }
 
     pub fn can_remove_segment(
@@ -1463,6 +1934,39 @@ impl WorkingSolutionRoute {
         true
     }
 
+    /// Checks that the activities replacing [start, end) keep the first stops before the unpinned
+    /// stops and the last stops after them
+    pub fn is_valid_stop_sequence_change(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> bool {
+        if !problem.has_stop_sequences() {
+            return true;
+        }
+
+        let rank = |activity_id: ActivityId| {
+            StopSequence::rank(problem.job_activity(activity_id).stop_sequence())
+        };
+
+        let mut previous_rank = start
+            .checked_sub(1)
+            .map(|index| rank(self.activity_ids[index]))
+            .unwrap_or(0);
+
+        for activity_id in activity_ids.chain(self.activity_ids.get(end).copied()) {
+            let rank = rank(activity_id);
+            if rank < previous_rank {
+                return false;
+            }
+            previous_rank = rank;
+        }
+
+        true
+    }
+
     pub fn is_valid_dependency_change(
         &self,
         problem: &VehicleRoutingProblem,
@@ -1566,9 +2070,240 @@ impl WorkingSolutionRoute {
         start: usize,
         end: usize,
     ) -> bool {
-        self.is_valid_dependency_change(problem, activity_ids.clone(), start, end)
+        // Locked activities cannot be moved, the change must start after them
+        start >= self.locked_len
+            && self.is_valid_vehicle_stop_change(problem, activity_ids.clone())
+            && self.is_valid_dependency_change(problem, activity_ids.clone(), start, end)
+            && self.is_valid_stop_sequence_change(problem, activity_ids.clone(), start, end)
             && self.is_valid_time_change(problem, activity_ids.clone(), start, end)
             && self.is_valid_capacity_change(problem, activity_ids.clone(), start, end)
+            && self.is_valid_ride_duration_change(problem, activity_ids.clone(), start, end)
+            && self.is_valid_energy_change(problem, activity_ids, start, end)
+    }
+
+    /// Checks the shipments are still delivered within their maximum ride duration once
+    /// [start, end) is replaced by `activity_ids`, the following activities may be delayed too
+    pub fn is_valid_ride_duration_change(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> bool {
+        if !problem.has_ride_durations() {
+            return true;
+        }
+
+        let end = end.min(self.len());
+        let updated_activities = self.updated_activities_iter(
+            problem,
+            activity_ids.chain(self.activity_ids_iter(end, self.len())),
+            start,
+            self.len() + 1,
+        );
+
+        self.ride_duration_excess(problem, updated_activities)
+            .is_zero()
+    }
+
+    /// Time over the maximum ride duration of the shipments delivered in `updated_activities`,
+    /// the pickups that are not part of them are taken from the route
+    pub fn ride_duration_excess(
+        &self,
+        problem: &VehicleRoutingProblem,
+        updated_activities: impl Iterator<Item = RouteUpdateActivityData>,
+    ) -> SignedDuration {
+        let mut pickup_departures: Vec<(JobIdx, Timestamp)> = vec![];
+        let mut excess = SignedDuration::ZERO;
+
+        for data in updated_activities {
+            match data.job_id {
+                ActivityId::ShipmentPickup(job_id) => {
+                    pickup_departures.push((job_id, data.departure_time));
+                }
+                ActivityId::ShipmentDelivery(job_id) => {
+                    let Some(max_ride_duration) = problem.job(job_id).max_ride_duration() else {
+                        continue;
+                    };
+
+                    let pickup_departure = pickup_departures
+                        .iter()
+                        .find(|&&(pickup_id, _)| pickup_id == job_id)
+                        .map(|&(_, departure)| departure)
+                        .or_else(|| {
+                            self.job_position(ActivityId::ShipmentPickup(job_id))
+                                .map(|position| self.departure_times[position])
+                        });
+
+                    if let Some(pickup_departure) = pickup_departure {
+                        let ride_duration = (data.arrival_time + data.waiting_duration)
+                            .duration_since(pickup_departure);
+                        excess += (ride_duration - max_ride_duration).max(SignedDuration::ZERO);
+                    }
+                }
+                ActivityId::Service(_) => {}
+            }
+        }
+
+        excess
+    }
+
+    /// Time over the maximum ride duration of the shipment delivered at `position`, zero for the
+    /// other activities
+    pub fn ride_duration_excess_at(
+        &self,
+        problem: &VehicleRoutingProblem,
+        position: usize,
+    ) -> SignedDuration {
+        let ActivityId::ShipmentDelivery(job_id) = self.activity_ids[position] else {
+            return SignedDuration::ZERO;
+        };
+
+        let Some(max_ride_duration) = problem.job(job_id).max_ride_duration() else {
+            return SignedDuration::ZERO;
+        };
+
+        let pickup_position = self.matching_shipment_position(self.activity_ids[position]);
+        let ride_duration = (self.arrival_times[position] + self.waiting_durations[position])
+            .duration_since(self.departure_times[pickup_position]);
+
+        (ride_duration - max_ride_duration).max(SignedDuration::ZERO)
+    }
+
+    /// Checks the battery of the vehicle lasts between its charges once [start, end) is replaced
+    /// by `activity_ids`
+    pub fn is_valid_energy_change(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> bool {
+        self.vehicle(problem).battery().is_none()
+            || self.energy_excess(problem, activity_ids, start, end) == 0.0
+    }
+
+    /// Energy (in kWh) over the battery capacity once [start, end) is replaced by `activity_ids`,
+    /// summed over the stretches between two charges. The activities after `end` are replayed
+    /// until one leaves at the same time with the same energy as in the route, the excess of the
+    /// rest of the route is unchanged.
+    pub fn energy_excess(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> f64 {
+        if self.vehicle(problem).battery().is_none() {
+            return 0.0;
+        }
+
+        let end = end.min(self.len());
+        let (mut excess, mut previous) = if start == 0 {
+            (0.0, None)
+        } else {
+            (
+                self.fwd_energy_excess[start - 1],
+                Some((
+                    self.activity_ids[start - 1],
+                    self.departure_energies[start - 1],
+                )),
+            )
+        };
+
+        // Counts the inserted activities, the ones after `end` follow them
+        let inserted_count = Cell::new(0);
+        let updated_activities = self.updated_activities_iter(
+            problem,
+            activity_ids
+                .inspect(|_| inserted_count.set(inserted_count.get() + 1))
+                .chain(self.activity_ids_iter(end, self.len())),
+            start,
+            self.len() + 1,
+        );
+
+        for (index, data) in updated_activities.enumerate() {
+            let arrival_energy =
+                compute_arrival_energy(problem, self.vehicle_id, previous, data.job_id);
+            excess += self.charge_energy_excess(problem, data.job_id, arrival_energy);
+
+            if let Some(position) = index
+                .checked_sub(inserted_count.get())
+                .map(|offset| end + offset)
+                && data.departure_time == self.departure_times[position]
+                && (data.departure_energy - self.departure_energies[position]).abs()
+                    < ENERGY_EPSILON
+            {
+                return excess + self.energy_excess - self.fwd_energy_excess[position];
+            }
+
+            previous = Some((data.job_id, data.departure_energy));
+        }
+
+        excess + self.end_energy_excess(problem, previous)
+    }
+
+    /// Energy over the battery capacity on arrival at a charge, 0 for the other activities
+    fn charge_energy_excess(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_id: ActivityId,
+        arrival_energy: f64,
+    ) -> f64 {
+        match self.vehicle(problem).battery() {
+            Some(battery) if problem.job(activity_id.job_id()).is_charge() => {
+                (arrival_energy - battery.capacity()).max(0.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Energy over the battery capacity on arrival at the end of the route, after leaving `last`
+    /// with the energy consumed since the last charge
+    fn end_energy_excess(
+        &self,
+        problem: &VehicleRoutingProblem,
+        last: Option<(ActivityId, f64)>,
+    ) -> f64 {
+        let vehicle = self.vehicle(problem);
+        let (Some(battery), Some((activity_id, departure_energy))) = (vehicle.battery(), last)
+        else {
+            return 0.0;
+        };
+
+        let consumed = departure_energy
+            + self.end_location(problem).map_or(0.0, |end_location| {
+                battery.driving_consumption(problem.travel_distance(
+                    vehicle,
+                    problem.job_activity(activity_id).location_id(),
+                    end_location,
+                ))
+            });
+
+        (consumed - battery.capacity()).max(0.0)
+    }
+
+    /// Energy (in kWh) left in the battery on arrival at each activity, empty when the vehicle has
+    /// no battery. The battery is full again when leaving a charge.
+    pub fn energy_levels(&self, problem: &VehicleRoutingProblem) -> Vec<f64> {
+        let Some(battery) = self.vehicle(problem).battery() else {
+            return vec![];
+        };
+
+        self.activity_ids
+            .iter()
+            .enumerate()
+            .map(|(position, &activity_id)| {
+                let previous = (position > 0).then(|| {
+                    (
+                        self.activity_ids[position - 1],
+                        self.departure_energies[position - 1],
+                    )
+                });
+                battery.capacity()
+                    - compute_arrival_energy(problem, self.vehicle_id, previous, activity_id)
+            })
+            .collect()
     }
 
     /// Return the transport cost delta of inserting [r2_start, r2_end) of r2 into [r1_start, r1_end) of r1
@@ -1588,7 +2323,7 @@ impl WorkingSolutionRoute {
         let r1 = self;
 
         let v1 = r1.vehicle(problem);
-        let p1 = v1.profile_id().get();
+        let p1 = v1.cost_class_id();
 
         // Removed cost of r1
         let r1_removed_cost = if r1_end > r1_start {
@@ -1695,6 +2430,12 @@ impl WorkingSolutionRoute {
             Some(self.departure_times[start - 1])
         };
 
+        let mut previous_departure_energy = if start == 0 {
+            0.0
+        } else {
+            self.departure_energies[start - 1]
+        };
+
         for activity_id in activity_ids {
             let arrival_time = if let Some(previous_activity_id) = previous_activity_id
                 && let Some(previous_departure_time) = previous_departure_time
@@ -1710,8 +2451,29 @@ impl WorkingSolutionRoute {
                 compute_first_activity_arrival_time(problem, self.vehicle_id, activity_id)
             };
             let waiting_duration = compute_waiting_duration(problem, activity_id, arrival_time);
-            let departure_time =
-                compute_departure_time(problem, arrival_time, waiting_duration, activity_id);
+            let arrival_energy = compute_arrival_energy(
+                problem,
+                self.vehicle_id,
+                previous_activity_id
+                    .map(|previous_activity_id| (previous_activity_id, previous_departure_energy)),
+                activity_id,
+            );
+            let departure_time = compute_departure_time(
+                problem,
+                self.vehicle_id,
+                arrival_time,
+                waiting_duration,
+                activity_id,
+                arrival_energy,
+            );
+            previous_departure_energy = compute_departure_energy(
+                problem,
+                self.vehicle_id,
+                activity_id,
+                arrival_energy,
+                arrival_time,
+                departure_time,
+            );
             previous_departure_time = Some(departure_time);
             previous_activity_id = Some(activity_id);
 
@@ -1797,6 +2559,12 @@ impl WorkingSolutionRoute {
             Some(self.departure_times[start - 1])
         };
 
+        let mut previous_departure_energy = if start == 0 {
+            0.0
+        } else {
+            self.departure_energies[start - 1]
+        };
+
         let mut vehicle_start: Option<Timestamp> = if self.is_empty() {
             None
         } else {
@@ -1838,8 +2606,29 @@ impl WorkingSolutionRoute {
 
             let waiting_duration = compute_waiting_duration(problem, activity_id, arrival_time);
 
-            let new_departure_time =
-                compute_departure_time(problem, arrival_time, waiting_duration, activity_id);
+            let arrival_energy = compute_arrival_energy(
+                problem,
+                self.vehicle_id,
+                previous_activity_id
+                    .map(|previous_activity_id| (previous_activity_id, previous_departure_energy)),
+                activity_id,
+            );
+            let new_departure_time = compute_departure_time(
+                problem,
+                self.vehicle_id,
+                arrival_time,
+                waiting_duration,
+                activity_id,
+                arrival_energy,
+            );
+            previous_departure_energy = compute_departure_energy(
+                problem,
+                self.vehicle_id,
+                activity_id,
+                arrival_energy,
+                arrival_time,
+                new_departure_time,
+            );
             vehicle_end = Some(compute_vehicle_end(
                 problem,
                 self.vehicle_id,
@@ -1847,11 +2636,9 @@ impl WorkingSolutionRoute {
                 new_departure_time,
             ));
 
-            if !problem
-                .job_activity(activity_id)
-                .time_windows()
-                .is_satisfied(arrival_time)
-            {
+            let job_activity = problem.job_activity(activity_id);
+            let time_windows = job_activity.time_windows();
+            if !time_windows.is_empty() && !time_windows.is_satisfied(arrival_time) {
                 return false;
             }
 
@@ -1940,7 +2727,7 @@ impl WorkingSolutionRoute {
             }
         }
 
-        if let Some(latest_end) = self.vehicle(problem).latest_end_time()
+        if let Some(latest_end) = self.vehicle(problem).latest_end_deadline()
             && let Some(vehicle_end) = vehicle_end
             // Other use cases are already handled by the fwd_time_slacks
             // In these two use cases, the vehicle_start and vehicle_end actually represent the start and end of the route
@@ -1962,6 +2749,10 @@ impl WorkingSolutionRoute {
         assert!(start <= end);
         assert!(end <= self.len() + 1);
 
+        if problem.has_reloads() {
+            return self.is_valid_trips_capacity_change(problem, activity_ids, start, end);
+        }
+
         let vehicle = self.vehicle(problem);
 
         // Added delivery load from the new activities
@@ -1990,6 +2781,7 @@ impl WorkingSolutionRoute {
                         added_delivery_load += service.demand();
                         load_delta -= service.demand();
                     }
+                    ServiceType::Reload | ServiceType::Charge => {}
                 },
                 JobActivity::ShipmentPickup(shipment) => {
                     load_delta += shipment.demand();
@@ -1999,9 +2791,7 @@ impl WorkingSolutionRoute {
                 }
             }
 
-            if load_delta > peak_load_delta {
-                peak_load_delta.update(&load_delta);
-            }
+            peak_load_delta.update_max(&load_delta);
         }
 
         // start < end means we delete existing activities
@@ -2017,38 +2807,190 @@ impl WorkingSolutionRoute {
             }
         }
 
-        // The new initial load is the initial load with the additional or removed deliveries
-        let new_initial_load = &self.current_load[0] + &delivery_load_delta;
+        // The new initial load is the initial load with the additional or removed deliveries
+        let new_initial_load = &self.current_load[0] + &delivery_load_delta;
+
+        // Check 1: check the new initial load against vehicle capacity
+        if !is_capacity_satisfied(
+            vehicle.capacity(),
+            &new_initial_load,
+            problem.tolerances().capacity,
+        ) {
+            return false;
+        }
+
+        // Check 2: check the peak load in the insertion range
+        // current_load[start] is the load before insertion, updated by delivery_load_delta
+        let peak_during_insertion =
+            &self.current_load[start] + &delivery_load_delta + &peak_load_delta;
+        if !is_capacity_satisfied(
+            vehicle.capacity(),
+            &peak_during_insertion,
+            problem.tolerances().capacity,
+        ) {
+            return false;
+        }
+
+        // Check 3: check the load at end
+        // self.current_load[start] + delivery_load_delta is the new initial load
+        // - added_delivery_load because we already delivered them
+        // + pickup_load_delta because we may have added or removed pickups
+        let load_at_end = &self.current_load[start] + &delivery_load_delta + &pickup_load_delta
+            - &added_delivery_load
+            - &self.current_load[end];
+
+        if !is_capacity_satisfied(
+            vehicle.capacity(),
+            &(load_at_end + &self.bwd_load_peaks[end]),
+            problem.tolerances().capacity,
+        ) {
+            return false;
+        }
+
+        true
+    }
+
+    /// Replays the loads of the updated route trip by trip, the deltas of
+    /// `is_valid_capacity_change` do not hold once a reload resets the load in the middle of
+    /// the route
+    fn is_valid_trips_capacity_change(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> bool {
+        let capacity = self.vehicle(problem).capacity();
+        self.replay_loads(problem, activity_ids, start, end, |load| {
+            is_capacity_satisfied(capacity, load, problem.tolerances().capacity)
+        })
+    }
+
+    /// Visits the load leaving each depot, reload and activity once [start, end) is replaced by
+    /// `activity_ids`, until `visit` returns false. Returns whether every load was visited.
+    fn replay_loads(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+        mut visit: impl FnMut(&Capacity) -> bool,
+    ) -> bool {
+        let end = end.min(self.len());
+        let sequence = self.activity_ids[..start]
+            .iter()
+            .copied()
+            .chain(activity_ids)
+            .chain(self.activity_ids[end..].iter().copied())
+            .collect::<Vec<_>>();
+
+        let mut load = Capacity::with_dimensions(problem.capacity_dimensions());
+        let mut shipments_load = Capacity::with_dimensions(problem.capacity_dimensions());
+        let mut trip_start = 0;
+
+        while trip_start <= sequence.len() {
+            let trip_end = sequence[trip_start..]
+                .iter()
+                .position(|activity_id| problem.job(activity_id.job_id()).is_reload())
+                .map_or(sequence.len(), |position| trip_start + position);
+
+            // The trip starts with its deliveries and the shipments still on board
+            load.update(&shipments_load);
+            for &activity_id in &sequence[trip_start..trip_end] {
+                if let JobActivity::Service(service) = problem.job_activity(activity_id)
+                    && service.service_type() == ServiceType::Delivery
+                {
+                    load += service.demand();
+                }
+            }
+
+            if !visit(&load) {
+                return false;
+            }
+
+            for &activity_id in &sequence[trip_start..trip_end] {
+                match problem.job_activity(activity_id) {
+                    JobActivity::Service(service) => match service.service_type() {
+                        ServiceType::Pickup => load += service.demand(),
+                        ServiceType::Delivery => load -= service.demand(),
+                        ServiceType::Reload | ServiceType::Charge => {}
+                    },
+                    JobActivity::ShipmentPickup(shipment) => {
+                        load += shipment.demand();
+                        shipments_load += shipment.demand();
+                    }
+                    JobActivity::ShipmentDelivery(shipment) => {
+                        load -= shipment.demand();
+                        shipments_load -= shipment.demand();
+                    }
+                }
+
+                if !visit(&load) {
+                    return false;
+                }
+            }
+
+            trip_start = trip_end + 1;
+        }
+
+        true
+    }
 
-        // Check 1: check the new initial load against vehicle capacity
-        if !is_capacity_satisfied(vehicle.capacity(), &new_initial_load) {
-            return false;
-        }
+    /// Demand over the capacity of the vehicle once [start, end) is replaced by `activity_ids`,
+    /// summed over the loads leaving the depot, the reloads and each activity
+    pub fn capacity_overload(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> f64 {
+        let capacity = self.vehicle(problem).capacity();
+        let tolerance = problem.tolerances().capacity;
+
+        let mut overload = 0.0;
+        self.replay_loads(problem, activity_ids, start, end, |load| {
+            overload += over_capacity_demand(capacity, load, tolerance);
+            true
+        });
 
-        // Check 2: check the peak load in the insertion range
-        // current_load[start] is the load before insertion, updated by delivery_load_delta
-        let peak_during_insertion =
-            &self.current_load[start] + &delivery_load_delta + &peak_load_delta;
-        if !is_capacity_satisfied(vehicle.capacity(), &peak_during_insertion) {
-            return false;
-        }
+        overload
+    }
 
-        // Check 3: check the load at end
-        // self.current_load[start] + delivery_load_delta is the new initial load
-        // - added_delivery_load because we already delivered them
-        // + pickup_load_delta because we may have added or removed pickups
-        let load_at_end = &self.current_load[start] + &delivery_load_delta + &pickup_load_delta
-            - &added_delivery_load
-            - &self.current_load[end];
+    /// Weight over the axle limits of the vehicle once [start, end) is replaced by `activity_ids`,
+    /// summed over the loads leaving the depot, the reloads and each activity
+    pub fn axle_overload(
+        &self,
+        problem: &VehicleRoutingProblem,
+        activity_ids: impl Iterator<Item = ActivityId>,
+        start: usize,
+        end: usize,
+    ) -> Kilograms {
+        let Some(axle_loads) = self.vehicle(problem).axle_loads() else {
+            return Kilograms::ZERO;
+        };
 
-        if !is_capacity_satisfied(
-            vehicle.capacity(),
-            &(load_at_end + &self.bwd_load_peaks[end]),
-        ) {
-            return false;
-        }
+        let mut overload = Kilograms::ZERO;
+        self.replay_loads(problem, activity_ids, start, end, |load| {
+            overload += axle_loads.overload(load);
+            true
+        });
 
-        true
+        overload
+    }
+
+    /// Whether the reloads and charges in `activity_ids` belong to the vehicle of the route
+    pub fn is_valid_vehicle_stop_change(
+        &self,
+        problem: &VehicleRoutingProblem,
+        mut activity_ids: impl Iterator<Item = ActivityId>,
+    ) -> bool {
+        !(problem.has_reloads() || problem.has_charges())
+            || activity_ids.all(|activity_id| {
+                problem
+                    .vehicle_stop_owner(activity_id.job_id())
+                    .is_none_or(|vehicle_id| vehicle_id == self.vehicle_id)
+            })
     }
 
     pub fn can_route_capacity_fit_in(&self, problem: &VehicleRoutingProblem, other: &Self) -> bool {
@@ -2056,6 +2998,16 @@ impl WorkingSolutionRoute {
             return false;
         }
 
+        // The reloads belong to their vehicle, the routes cannot be exchanged
+        if problem.has_reloads() && (self.has_reloads(problem) || other.has_reloads(problem)) {
+            return false;
+        }
+
+        // Same for the charges
+        if problem.has_charges() && (self.has_charges(problem) || other.has_charges(problem)) {
+            return false;
+        }
+
         let other_vehicle_capacity = other.vehicle(problem).capacity();
         let self_delivery_peak = &self.current_load[0];
         let self_pickup_peak = &self.current_load[self.len()];
@@ -2063,6 +3015,7 @@ impl WorkingSolutionRoute {
         if !is_capacity_satisfied(
             other_vehicle_capacity,
             &(other.delivery_load_slack() + self_delivery_peak),
+            problem.tolerances().capacity,
         ) {
             return false;
         }
@@ -2070,6 +3023,7 @@ impl WorkingSolutionRoute {
         if !is_capacity_satisfied(
             other_vehicle_capacity,
             &(other.pickup_load_slack() + self_pickup_peak),
+            problem.tolerances().capacity,
         ) {
             return false;
         }
@@ -2151,10 +3105,14 @@ mod tests {
 
     use crate::{
         problem::{
+            battery::Battery,
             capacity::Capacity,
+            charging_station::{ChargeCurve, ChargingStation},
             fleet::Fleet,
             job::{ActivityId, JobIdx},
+            location::LocationIdx,
             service::{ServiceBuilder, ServiceType},
+            stop_sequence::StopSequence,
             time_window::TimeWindow,
             travel_cost_matrix::TravelMatrices,
             vehicle::{VehicleBuilder, VehicleIdx},
@@ -2879,6 +3837,7 @@ mod tests {
                 arrival_time: "2025-11-30T10:40:00+02:00".parse().unwrap(),
                 waiting_duration: SignedDuration::ZERO,
                 departure_time: "2025-11-30T10:50:00+02:00".parse().unwrap(),
+                departure_energy: 0.0,
                 job_id: ActivityId::service(1),
                 current_position: Some(2)
             })
@@ -2890,6 +3849,7 @@ mod tests {
                 arrival_time: "2025-11-30T11:20:00+02:00".parse().unwrap(),
                 waiting_duration: SignedDuration::ZERO,
                 departure_time: "2025-11-30T11:30:00+02:00".parse().unwrap(),
+                departure_energy: 0.0,
                 job_id: ActivityId::service(2),
                 current_position: Some(1)
             })
@@ -3072,6 +4032,218 @@ mod tests {
     /// shipments (demand). Services are indexed first (0..n_services), then shipments
     /// (n_services..n_services+n_shipments), matching the convention used in
     /// `create_problem_for_capacity_change`.
+    #[test]
+    fn test_reload_starts_a_new_trip() {
+        let locations = test_utils::create_location_grid(1, 10);
+
+        let mut vehicle_builder = VehicleBuilder::default();
+        vehicle_builder.set_depot_location_id(0);
+        vehicle_builder.set_capacity(Capacity::from_vec(vec![20.0]));
+        vehicle_builder.set_vehicle_id(String::from("vehicle"));
+        vehicle_builder.set_profile_id(0);
+        vehicle_builder.set_reload_duration(SignedDuration::from_mins(15));
+        let vehicles = vec![vehicle_builder.build()];
+
+        let services = (1..=4)
+            .map(|location_id| {
+                let mut service_builder = ServiceBuilder::default();
+                service_builder.set_demand(Capacity::from_vec(vec![10.0]));
+                service_builder.set_external_id(format!("service_{location_id}"));
+                service_builder.set_location_id(location_id);
+                service_builder.build()
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_constant(&locations, 600.0, 100.0, 600.0),
+        )]);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vehicles));
+        builder.set_services(services);
+        let problem = builder.build().expect("Expected valid problem");
+
+        // The reload is generated after the services
+        let reload_id = JobIdx::new(4);
+        assert_eq!(problem.vehicle_reloads(VehicleIdx::new(0)), &[reload_id]);
+
+        let mut route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        route.insert_service(&problem, 0, JobIdx::new(0));
+        route.insert_service(&problem, 1, JobIdx::new(1));
+        route.insert_service(&problem, 2, reload_id);
+        route.insert_service(&problem, 3, JobIdx::new(2));
+
+        assert_eq!(
+            route.current_loads(),
+            &[
+                Capacity::from_vec(vec![20.0]),
+                Capacity::from_vec(vec![10.0]),
+                Capacity::EMPTY,
+                Capacity::from_vec(vec![10.0]),
+                Capacity::EMPTY,
+                Capacity::EMPTY,
+            ]
+        );
+        assert_eq!(route.fwd_load_peak(3), &Capacity::from_vec(vec![10.0]));
+        assert_eq!(route.bwd_load_peak(0), &Capacity::from_vec(vec![20.0]));
+        // Both trips are loaded at the depot
+        assert_eq!(route.depot_load(), &Capacity::from_vec(vec![30.0]));
+
+        // The second trip has room for the last service, the first one does not
+        assert!(route.is_valid_capacity_change(
+            &problem,
+            std::iter::once(ActivityId::service(3)),
+            4,
+            4
+        ));
+        assert!(!route.is_valid_capacity_change(
+            &problem,
+            std::iter::once(ActivityId::service(3)),
+            1,
+            1
+        ));
+    }
+
+    #[test]
+    fn test_charge_resets_energy_consumption() {
+        let locations = test_utils::create_location_grid(1, 10);
+
+        let mut vehicle_builder = VehicleBuilder::default();
+        vehicle_builder.set_depot_location_id(0);
+        vehicle_builder.set_vehicle_id(String::from("vehicle"));
+        vehicle_builder.set_profile_id(0);
+        vehicle_builder.set_battery(Battery::new(10.0, 0.5, 0.0, 1));
+        vehicle_builder.set_return(true);
+        let vehicles = vec![vehicle_builder.build()];
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        // Every leg is 10km, 5kWh
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_constant(&locations, 600.0, 10_000.0, 600.0),
+        )]);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vehicles));
+        builder.set_services(test_utils::create_basic_services(vec![1, 2]));
+        builder.set_charging_stations(vec![ChargingStation::new(
+            String::from("station"),
+            LocationIdx::new(9),
+            ChargeCurve::constant(50.0),
+        )]);
+        let problem = builder.build().expect("Expected valid problem");
+
+        let charge_id = problem.vehicle_charges(VehicleIdx::new(0))[0];
+
+        let mut route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        route.insert_service(&problem, 0, JobIdx::new(0));
+        route.insert_service(&problem, 1, JobIdx::new(1));
+
+        // Three legs of 5kWh with a 10kWh battery
+        assert_eq!(route.energy_excess(&problem, std::iter::empty(), 0, 0), 5.0);
+        assert!(!route.is_valid_energy_change(&problem, std::iter::empty(), 0, 0));
+
+        route.insert_service(&problem, 1, charge_id);
+
+        assert_eq!(route.energy_excess(&problem, std::iter::empty(), 0, 0), 0.0);
+        assert_eq!(route.energy_levels(&problem), vec![5.0, 0.0, 5.0]);
+
+        // The empty battery is charged at 50kW
+        assert_eq!(
+            route
+                .departure_time(1)
+                .duration_since(route.arrival_time(1)),
+            SignedDuration::from_mins(12)
+        );
+
+        // Removing the charge
+        assert_eq!(route.energy_excess(&problem, std::iter::empty(), 1, 2), 5.0);
+    }
+
+    #[test]
+    fn test_charge_duration_depends_on_energy_consumed() {
+        let locations = test_utils::create_location_grid(1, 10);
+
+        let mut vehicle_builder = VehicleBuilder::default();
+        vehicle_builder.set_depot_location_id(0);
+        vehicle_builder.set_vehicle_id(String::from("vehicle"));
+        vehicle_builder.set_profile_id(0);
+        vehicle_builder.set_battery(Battery::new(15.0, 0.5, 0.0, 1));
+        vehicle_builder.set_return(true);
+        let vehicles = vec![vehicle_builder.build()];
+
+        let mut builder = VehicleRoutingProblemBuilder::default();
+        // Every leg is 10km, 5kWh
+        builder.set_vehicle_profiles(vec![VehicleProfile::new(
+            "test_profile".to_owned(),
+            TravelMatrices::from_constant(&locations, 600.0, 10_000.0, 600.0),
+        )]);
+        builder.set_locations(locations);
+        builder.set_fleet(Fleet::Finite(vehicles));
+        builder.set_services(test_utils::create_basic_services(vec![1, 2, 3]));
+        builder.set_charging_stations(vec![ChargingStation::new(
+            String::from("station"),
+            LocationIdx::new(9),
+            ChargeCurve::constant(50.0),
+        )]);
+        let problem = builder.build().expect("Expected valid problem");
+
+        let charge_id = problem.vehicle_charges(VehicleIdx::new(0))[0];
+
+        let mut route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        route.insert_service(&problem, 0, JobIdx::new(0));
+        route.insert_service(&problem, 1, JobIdx::new(1));
+        route.insert_service(&problem, 2, JobIdx::new(2));
+
+        // Four legs of 5kWh with a 15kWh battery
+        assert_eq!(route.energy_excess(&problem, std::iter::empty(), 0, 0), 5.0);
+        assert_eq!(
+            route.energy_excess(
+                &problem,
+                std::iter::once(ActivityId::Service(charge_id)),
+                1,
+                1
+            ),
+            0.0
+        );
+        assert_eq!(
+            route.energy_excess(
+                &problem,
+                std::iter::once(ActivityId::Service(charge_id)),
+                3,
+                3
+            ),
+            5.0
+        );
+
+        // 10kWh consumed since the depot, charged at 50kW
+        route.insert_service(&problem, 1, charge_id);
+        assert_eq!(
+            route
+                .departure_time(1)
+                .duration_since(route.arrival_time(1)),
+            SignedDuration::from_mins(12)
+        );
+        assert_eq!(route.energy_excess(&problem, std::iter::empty(), 0, 0), 0.0);
+
+        // Moving the charge after the second service charges 15kWh
+        let activity_ids = [
+            ActivityId::Service(JobIdx::new(1)),
+            ActivityId::Service(charge_id),
+        ];
+        assert_eq!(
+            route.energy_excess(&problem, activity_ids.into_iter(), 1, 3),
+            0.0
+        );
+        route.replace_activities(&problem, &activity_ids, 1, 3);
+        assert_eq!(
+            route
+                .departure_time(2)
+                .duration_since(route.arrival_time(2)),
+            SignedDuration::from_mins(18)
+        );
+    }
+
     fn create_problem_for_capacity_change_with_shipments(
         vehicle_capacity: Capacity,
         services: Vec<(ServiceType, Capacity)>,
@@ -4908,6 +6080,68 @@ mod tests {
         assert!(!route.is_valid_time_change(&problem, [ActivityId::service(2)].into_iter(), 0, 0))
     }
 
+    #[test]
+    fn test_departure_shift() {
+        let problem = create_problem_for_tw_change(
+            vec![
+                TestService::with_time_window(TimeWindow::from_iso(
+                    Some("2025-11-30T08:00:00+02:00"),
+                    Some("2025-11-30T09:00:00+02:00"),
+                )),
+                TestService::with_time_window(TimeWindow::from_iso(
+                    Some("2025-11-30T10:00:00+02:00"),
+                    Some("2025-11-30T11:00:00+02:00"),
+                )),
+            ],
+            TestProblemOptions::default(),
+        );
+
+        let mut route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        assert_eq!(route.departure_shift(), SignedDuration::ZERO);
+
+        route.insert_service(&problem, 0, JobIdx::new(0));
+        route.insert_service(&problem, 1, JobIdx::new(1));
+
+        assert_eq!(
+            route.total_waiting_duration(),
+            SignedDuration::from_mins(80)
+        );
+
+        // The first time window closes at 09:00, only 60 of the 80 minutes can be absorbed
+        assert_eq!(route.departure_shift(), SignedDuration::from_mins(60));
+
+        // Leaving later uses all of the slack
+        assert_eq!(route.time_slack(), Some(SignedDuration::ZERO));
+        assert_eq!(
+            route.optimized_start(&problem),
+            timestamp!("2025-11-30T08:30:00+02:00")
+        );
+        assert_eq!(
+            route.optimized_total_waiting_duration(),
+            SignedDuration::from_mins(20)
+        );
+        assert_eq!(
+            route.optimized_duration(&problem),
+            route.duration(&problem) - SignedDuration::from_mins(60)
+        );
+
+        let activities = route.optimized_activities_iter().collect::<Vec<_>>();
+        assert_eq!(
+            activities[0].arrival_time(),
+            timestamp!("2025-11-30T09:00:00+02:00")
+        );
+        assert_eq!(activities[0].waiting_duration(), SignedDuration::ZERO);
+        assert_eq!(
+            activities[1].arrival_time(),
+            timestamp!("2025-11-30T09:40:00+02:00")
+        );
+        assert_eq!(
+            activities[1].waiting_duration(),
+            SignedDuration::from_mins(20)
+        );
+        assert_eq!(activities[1].departure_time(), route.departure_time(1));
+    }
+
     fn create_problem_with_n_services(services: usize) -> VehicleRoutingProblem {
         // 10 locations from (0, 0) to (9, 0)
         let locations = test_utils::create_location_grid(1, services + 1);
@@ -5786,4 +7020,143 @@ mod tests {
         // Now s1 must come after s0 (pos 1), so start = 2.
         assert_eq!(route.insertion_range(ActivityId::service(1)), (2, 2));
     }
+
+    #[test]
+    fn test_locked_activities() {
+        let problem = create_problem_with_relations(4, vec![]);
+        let mut route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        route.insert_service(&problem, 0, JobIdx::new(0)); // s0 @ pos 0
+        route.insert_service(&problem, 1, JobIdx::new(1)); // s1 @ pos 1
+        route.insert_service(&problem, 2, JobIdx::new(2)); // s2 @ pos 2
+
+        route.lock(2);
+
+        // Nothing can be inserted before the locked activities
+        assert_eq!(route.insertion_range(ActivityId::service(3)), (2, 3));
+
+        // Changes touching the locked activities are rejected
+        assert!(!route.is_valid_change(&problem, std::iter::empty(), 1, 2));
+        assert!(route.is_valid_change(&problem, std::iter::empty(), 2, 3));
+
+        assert!(!route.remove_activity(&problem, ActivityId::service(0)));
+        assert!(!route.remove_activity(&problem, ActivityId::service(1)));
+        assert!(route.remove_activity(&problem, ActivityId::service(2)));
+        assert_eq!(
+            route.activity_ids(),
+            &[ActivityId::service(0), ActivityId::service(1)]
+        );
+    }
+
+    #[test]
+    fn test_stop_sequences() {
+        let locations = test_utils::create_location_grid(1, 5);
+        let stop_sequences = [
+            Some(StopSequence::First),
+            None,
+            Some(StopSequence::Last),
+            None,
+            Some(StopSequence::First),
+        ];
+
+        let services = stop_sequences
+            .iter()
+            .enumerate()
+            .map(|(index, stop_sequence)| {
+                let mut builder = ServiceBuilder::default();
+                builder.set_location_id(index);
+                builder.set_external_id(index.to_string());
+                if let Some(stop_sequence) = stop_sequence {
+                    builder.set_stop_sequence(*stop_sequence);
+                }
+                builder.build()
+            })
+            .collect();
+
+        let vehicles = test_utils::create_basic_vehicles(vec![0]);
+        let problem = test_utils::create_test_problem(locations, services, vehicles);
+
+        let mut route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        route.insert_service(&problem, 0, JobIdx::new(0)); // first
+        route.insert_service(&problem, 1, JobIdx::new(1));
+        route.insert_service(&problem, 2, JobIdx::new(2)); // last
+
+        assert_eq!(
+            route.stop_sequence_range(&problem, ActivityId::service(3)),
+            (1, 2)
+        );
+        assert_eq!(
+            route.stop_sequence_range(&problem, ActivityId::service(4)),
+            (0, 1)
+        );
+
+        // Moving the last stop before an unpinned stop
+        assert!(!route.is_valid_change(
+            &problem,
+            [ActivityId::service(2), ActivityId::service(1)].into_iter(),
+            1,
+            3
+        ));
+
+        // Inserting an unpinned stop before the first stop
+        assert!(!route.is_valid_change(&problem, [ActivityId::service(3)].into_iter(), 0, 0));
+
+        assert!(route.is_valid_change(
+            &problem,
+            [ActivityId::service(1), ActivityId::service(3)].into_iter(),
+            1,
+            2
+        ));
+    }
+
+    #[test]
+    fn test_tags_allowed_by_vehicle() {
+        let locations = test_utils::create_location_grid(1, 4);
+
+        let services = [
+            vec![],
+            vec![String::from("bulky")],
+            vec![String::from("fragile")],
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, tags)| {
+            let mut builder = ServiceBuilder::default();
+            builder.set_location_id(index + 1);
+            builder.set_external_id(index.to_string());
+            builder.set_tags(tags);
+            builder.build()
+        })
+        .collect();
+
+        let mut bike = VehicleBuilder::default();
+        bike.set_depot_location_id(0);
+        bike.set_vehicle_id(String::from("bike"));
+        bike.set_profile_id(0);
+        bike.set_forbidden_tags(vec![String::from("bulky")]);
+
+        let mut van = VehicleBuilder::default();
+        van.set_depot_location_id(0);
+        van.set_vehicle_id(String::from("van"));
+        van.set_profile_id(0);
+        van.set_allowed_tags(vec![String::from("bulky")]);
+
+        let problem =
+            test_utils::create_test_problem(locations, services, vec![bike.build(), van.build()]);
+
+        let bike_route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(0));
+        assert!(bike_route.can_deliver_job(&problem, JobIdx::new(0)));
+        assert!(!bike_route.can_deliver_job(&problem, JobIdx::new(1)));
+        assert!(bike_route.can_deliver_job(&problem, JobIdx::new(2)));
+
+        let mut van_route = WorkingSolutionRoute::empty(&problem, VehicleIdx::new(1));
+        assert!(van_route.can_deliver_job(&problem, JobIdx::new(0)));
+        assert!(van_route.can_deliver_job(&problem, JobIdx::new(1)));
+        assert!(!van_route.can_deliver_job(&problem, JobIdx::new(2)));
+
+        van_route.insert_service(&problem, 0, JobIdx::new(0));
+        van_route.insert_service(&problem, 1, JobIdx::new(1));
+
+        assert!(bike_route.can_deliver_segment(&problem, &van_route, 0, 1));
+        assert!(!bike_route.can_deliver_segment(&problem, &van_route, 0, 2));
+    }
 }
@@ -0,0 +1,138 @@
+use rstar::{
+    RTree, RTreeObject,
+    primitives::{GeomWithData, Rectangle},
+};
+
+use crate::{
+    solver::solution::{route::WorkingSolutionRoute, route_id::RouteIdx},
+    utils::enumerate_idx::EnumerateIdx,
+};
+
+type RouteEnvelope = GeomWithData<Rectangle<[f64; 2]>, RouteIdx>;
+
+#[derive(Clone, Default)]
+struct IndexedRoute {
+    version: Option<usize>,
+    envelope: Option<RouteEnvelope>,
+}
+
+/// R-tree over the bounding boxes of the non empty routes, to find the routes overlapping a route
+/// without checking every pair of routes.
+///
+/// Only the routes whose version changed since the last update are indexed again.
+#[derive(Clone, Default)]
+pub struct RouteBBoxIndex {
+    tree: RTree<RouteEnvelope>,
+    routes: Vec<IndexedRoute>,
+}
+
+impl RouteBBoxIndex {
+    pub fn update(&mut self, routes: &[WorkingSolutionRoute]) {
+        // The routes removed since the last update
+        if self.routes.len() > routes.len() {
+            for indexed_route in self.routes.drain(routes.len()..) {
+                if let Some(envelope) = indexed_route.envelope {
+                    self.tree.remove(&envelope);
+                }
+            }
+        }
+        self.routes.resize_with(routes.len(), IndexedRoute::default);
+
+        for (route_id, route) in EnumerateIdx::<RouteIdx>::enumerate_idx(routes.iter()) {
+            let indexed_route = &mut self.routes[route_id.get()];
+            if indexed_route.version == Some(route.version()) {
+                continue;
+            }
+
+            if let Some(envelope) = indexed_route.envelope.take() {
+                self.tree.remove(&envelope);
+            }
+
+            if !route.is_empty() && !route.bbox().is_empty() {
+                let envelope =
+                    RouteEnvelope::new(Rectangle::from_aabb(route.bbox().envelope()), route_id);
+                self.tree.insert(envelope);
+                indexed_route.envelope = Some(envelope);
+            }
+
+            indexed_route.version = Some(route.version());
+        }
+    }
+
+    /// Routes whose bounding box intersects the one of `route_id`, as of the last update
+    pub fn intersecting_routes(&self, route_id: RouteIdx) -> impl Iterator<Item = RouteIdx> + '_ {
+        self.routes
+            .get(route_id.get())
+            .and_then(|indexed_route| indexed_route.envelope.as_ref())
+            .into_iter()
+            .flat_map(|envelope| {
+                self.tree
+                    .locate_in_envelope_intersecting(&envelope.envelope())
+            })
+            .map(|envelope| envelope.data)
+            .filter(move |&other_route_id| other_route_id != route_id)
+    }
+
+    pub fn estimated_size_bytes(&self) -> usize {
+        self.routes.capacity() * std::mem::size_of::<IndexedRoute>()
+            + self.tree.size() * std::mem::size_of::<RouteEnvelope>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::job::JobIdx,
+        solver::solution::{route_id::RouteIdx, working_solution::WorkingSolution},
+        test_utils::{self, TestRoute},
+    };
+
+    #[test]
+    fn test_intersecting_routes() {
+        let locations = test_utils::create_location_grid(10, 10);
+        let services = test_utils::create_basic_services(vec![0, 22, 11, 33, 88, 99]);
+        let vehicles = test_utils::create_basic_vehicles(vec![0, 0, 0]);
+        let problem = Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ));
+
+        let mut solution = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![
+                TestRoute {
+                    vehicle_id: 0,
+                    service_ids: vec![0, 1],
+                },
+                TestRoute {
+                    vehicle_id: 1,
+                    service_ids: vec![2, 3],
+                },
+                TestRoute {
+                    vehicle_id: 2,
+                    service_ids: vec![4, 5],
+                },
+            ],
+        );
+        solution.update_route_bbox_index();
+
+        let intersecting = |solution: &WorkingSolution, route_id: usize| {
+            solution
+                .intersecting_routes(RouteIdx::new(route_id))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(intersecting(&solution, 0), vec![RouteIdx::new(1)]);
+        assert_eq!(intersecting(&solution, 1), vec![RouteIdx::new(0)]);
+        assert!(intersecting(&solution, 2).is_empty());
+
+        // Only the emptied route is indexed again
+        solution.remove_job(JobIdx::new(0));
+        solution.remove_job(JobIdx::new(1));
+        solution.update_route_bbox_index();
+
+        assert!(intersecting(&solution, 0).is_empty());
+        assert!(intersecting(&solution, 1).is_empty());
+    }
+}
@@ -5,17 +5,19 @@ use crate::{
     solver::solution::{
         route::WorkingSolutionRoute,
         utils::{
-            compute_activity_arrival_time, compute_departure_time,
-            compute_first_activity_arrival_time, compute_waiting_duration,
+            compute_activity_arrival_time, compute_arrival_energy, compute_departure_energy,
+            compute_departure_time, compute_first_activity_arrival_time, compute_waiting_duration,
         },
     },
 };
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Debug)]
 pub struct RouteUpdateActivityData {
     pub arrival_time: Timestamp,
     pub departure_time: Timestamp,
     pub waiting_duration: SignedDuration,
+    /// Energy consumed since the last charge when leaving the activity, see `Battery`
+    pub departure_energy: f64,
     pub job_id: ActivityId,
     pub current_position: Option<usize>,
 }
@@ -32,6 +34,7 @@ pub struct RouteUpdateIterator<'a, I> {
 
     previous_job_id: Option<ActivityId>,
     previous_departure_time: Option<Timestamp>,
+    previous_departure_energy: f64,
 }
 
 impl<'a, I> RouteUpdateIterator<'a, I>
@@ -66,6 +69,11 @@ where
             succeeding_iter: succeeding_activities.iter(),
             previous_job_id: previous_activity.map(|activity| activity.activity_id),
             previous_departure_time: previous_activity.map(|activity| activity.departure_time),
+            previous_departure_energy: if start > 0 {
+                route.departure_energies[start - 1]
+            } else {
+                0.0
+            },
         }
     }
 }
@@ -100,11 +108,33 @@ where
 
             let waiting_duration = compute_waiting_duration(self.problem, job_id, arrival_time);
 
-            let departure_time =
-                compute_departure_time(self.problem, arrival_time, waiting_duration, job_id);
+            let arrival_energy = compute_arrival_energy(
+                self.problem,
+                self.route.vehicle_id,
+                self.previous_job_id
+                    .map(|previous_job_id| (previous_job_id, self.previous_departure_energy)),
+                job_id,
+            );
+            let departure_time = compute_departure_time(
+                self.problem,
+                self.route.vehicle_id,
+                arrival_time,
+                waiting_duration,
+                job_id,
+                arrival_energy,
+            );
+            let departure_energy = compute_departure_energy(
+                self.problem,
+                self.route.vehicle_id,
+                job_id,
+                arrival_energy,
+                arrival_time,
+                departure_time,
+            );
 
             self.previous_job_id = Some(job_id);
             self.previous_departure_time = Some(departure_time);
+            self.previous_departure_energy = departure_energy;
 
             let current_position = self.route.jobs.get(&job_id).copied();
 
@@ -112,6 +142,7 @@ where
                 arrival_time,
                 departure_time,
                 waiting_duration,
+                departure_energy,
                 job_id,
                 current_position,
             })
@@ -0,0 +1,119 @@
+use super::working_solution::WorkingSolution;
+
+/// Keeps the solutions discarded by the search so that the copy of the selected solution made at
+/// each iteration reuses their routes, vectors and sets instead of allocating new ones.
+///
+/// Each search thread owns its pool, there is no synchronization
+pub struct SolutionPool {
+    solutions: Vec<WorkingSolution>,
+    max_size: usize,
+}
+
+impl SolutionPool {
+    pub fn new(max_size: usize) -> Self {
+        SolutionPool {
+            solutions: Vec::with_capacity(max_size),
+            max_size,
+        }
+    }
+
+    /// Copies `source` into a recycled solution, or clones it when the pool is empty
+    pub fn copy_of(&mut self, source: &WorkingSolution) -> WorkingSolution {
+        match self.solutions.pop() {
+            Some(mut solution) => {
+                solution.clone_from(source);
+                solution
+            }
+            None => source.clone(),
+        }
+    }
+
+    /// Gives back a solution that is no longer used, it is dropped when the pool is full
+    pub fn recycle(&mut self, solution: WorkingSolution) {
+        if self.solutions.len() < self.max_size {
+            self.solutions.push(solution);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::vehicle_routing_problem::VehicleRoutingProblem,
+        solver::solution::route_id::RouteIdx,
+        test_utils::{self, TestRoute},
+    };
+
+    use super::SolutionPool;
+
+    fn create_problem() -> Arc<VehicleRoutingProblem> {
+        let locations = test_utils::create_location_grid(4, 4);
+        let services = test_utils::create_basic_services((1..10).collect());
+        let vehicles = test_utils::create_basic_vehicles(vec![0, 0]);
+
+        Arc::new(test_utils::create_test_problem(
+            locations, services, vehicles,
+        ))
+    }
+
+    #[test]
+    fn test_copy_of_recycled_solution() {
+        let problem = create_problem();
+        let mut pool = SolutionPool::new(2);
+
+        let source = test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![
+                TestRoute {
+                    vehicle_id: 0,
+                    service_ids: vec![0, 1, 2, 3, 4],
+                },
+                TestRoute {
+                    vehicle_id: 1,
+                    service_ids: vec![5, 6, 7, 8],
+                },
+            ],
+        );
+
+        // The recycled solution has a different assignment
+        pool.recycle(test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![8, 7],
+            }],
+        ));
+        assert_eq!(pool.len(), 1);
+
+        let copy = pool.copy_of(&source);
+        assert!(pool.is_empty());
+        assert!(copy.is_identical(&source));
+        assert_eq!(copy.unassigned_jobs(), source.unassigned_jobs());
+        assert_eq!(copy.route(RouteIdx::new(0)).len(), 5);
+        assert_eq!(copy.route(RouteIdx::new(1)).len(), 4);
+    }
+
+    #[test]
+    fn test_recycle_drops_when_full() {
+        let problem = create_problem();
+        let mut pool = SolutionPool::new(1);
+
+        pool.recycle(test_utils::create_test_working_solution(
+            Arc::clone(&problem),
+            vec![],
+        ));
+        pool.recycle(test_utils::create_test_working_solution(problem, vec![]));
+
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -1,7 +1,7 @@
 use jiff::{SignedDuration, Timestamp};
 
 use crate::problem::{
-    job::ActivityId, time_window::TimeWindows, vehicle::VehicleIdx,
+    depot::Depot, job::ActivityId, time_window::TimeWindows, vehicle::VehicleIdx,
     vehicle_routing_problem::VehicleRoutingProblem,
 };
 
@@ -14,8 +14,22 @@ pub(crate) fn compute_first_activity_arrival_time(
     let vehicle = problem.vehicle(vehicle_id);
     let vehicle_depot_location_id = vehicle.depot_location_id();
 
-    let earliest_start_time = vehicle.earliest_start_time().unwrap_or(Timestamp::MIN);
-    let latest_start_time = vehicle.latest_start_time().unwrap_or(Timestamp::MAX);
+    let earliest_start_time = vehicle
+        .earliest_start_time()
+        .or_else(|| {
+            vehicle_depot_location_id
+                .and_then(|depot_location_id| problem.depot(depot_location_id))
+                .and_then(Depot::earliest_departure)
+        })
+        .unwrap_or(Timestamp::MIN);
+    let latest_start_time = vehicle
+        .latest_start_time()
+        .or_else(|| {
+            vehicle_depot_location_id
+                .and_then(|depot_location_id| problem.depot(depot_location_id))
+                .and_then(Depot::latest_departure)
+        })
+        .unwrap_or(Timestamp::MAX);
 
     let travel_time = match vehicle_depot_location_id {
         Some(depot_location_id) => {
@@ -96,13 +110,100 @@ pub(crate) fn compute_waiting_duration(
         .waiting_duration(arrival_time)
 }
 
+/// `arrival_energy` is the energy consumed since the last charge on arrival, see
+/// `compute_activity_duration`
 pub(crate) fn compute_departure_time(
     problem: &VehicleRoutingProblem,
+    vehicle_id: VehicleIdx,
     arrival_time: Timestamp,
     waiting_duration: SignedDuration,
     activity_id: ActivityId,
+    arrival_energy: f64,
 ) -> Timestamp {
-    arrival_time + waiting_duration + problem.job_activity(activity_id).duration()
+    arrival_time
+        + waiting_duration
+        + compute_activity_duration(problem, vehicle_id, activity_id, arrival_energy)
+}
+
+/// Duration of the activity, a charge lasts until the `arrival_energy` consumed since the previous
+/// charge is charged back
+pub(crate) fn compute_activity_duration(
+    problem: &VehicleRoutingProblem,
+    vehicle_id: VehicleIdx,
+    activity_id: ActivityId,
+    arrival_energy: f64,
+) -> SignedDuration {
+    let duration = problem.job_activity(activity_id).duration();
+    if !problem.has_charges() {
+        return duration;
+    }
+
+    match (
+        problem.charging_station(activity_id.job_id()),
+        problem.vehicle(vehicle_id).battery(),
+    ) {
+        (Some(station), Some(battery)) => {
+            let state_of_charge = (1.0 - arrival_energy / battery.capacity()).clamp(0.0, 1.0);
+            duration
+                + station
+                    .charge_curve()
+                    .charge_duration(battery.capacity(), state_of_charge, 1.0)
+        }
+        _ => duration,
+    }
+}
+
+/// Energy (in kWh) consumed since the last charge on arrival at the activity, from the departure
+/// of `previous` with the energy consumed then, or from the depot. Always 0 without a battery
+pub(crate) fn compute_arrival_energy(
+    problem: &VehicleRoutingProblem,
+    vehicle_id: VehicleIdx,
+    previous: Option<(ActivityId, f64)>,
+    activity_id: ActivityId,
+) -> f64 {
+    let vehicle = problem.vehicle(vehicle_id);
+    let Some(battery) = vehicle.battery() else {
+        return 0.0;
+    };
+
+    let location_id = problem.job_activity(activity_id).location_id();
+    let (previous_location_id, departure_energy) = match previous {
+        Some((previous_activity_id, departure_energy)) => (
+            Some(problem.job_activity(previous_activity_id).location_id()),
+            departure_energy,
+        ),
+        None => (vehicle.depot_location_id(), 0.0),
+    };
+
+    departure_energy
+        + previous_location_id.map_or(0.0, |previous_location_id| {
+            battery.driving_consumption(problem.travel_distance(
+                vehicle,
+                previous_location_id,
+                location_id,
+            ))
+        })
+}
+
+/// Energy (in kWh) consumed since the last charge when leaving the activity, 0 after a charge.
+/// The vehicle consumes its idle consumption while it waits and serves the activity
+pub(crate) fn compute_departure_energy(
+    problem: &VehicleRoutingProblem,
+    vehicle_id: VehicleIdx,
+    activity_id: ActivityId,
+    arrival_energy: f64,
+    arrival_time: Timestamp,
+    departure_time: Timestamp,
+) -> f64 {
+    let Some(battery) = problem.vehicle(vehicle_id).battery() else {
+        return 0.0;
+    };
+
+    if problem.job(activity_id.job_id()).is_charge() {
+        0.0
+    } else {
+        arrival_energy + battery.idle_consumption(departure_time.duration_since(arrival_time))
+    }
 }
 
 pub(crate) fn compute_time_slack(
@@ -112,7 +213,7 @@ pub(crate) fn compute_time_slack(
 ) -> SignedDuration {
     let task = problem.job_activity(job_id);
 
-    if let Some(max_end) = task.time_windows().end() {
+    if let Some(max_end) = task.time_windows().deadline() {
         max_end.duration_since(arrival_time)
     } else {
         SignedDuration::MAX
@@ -146,7 +247,12 @@ fn compute_initial_arrival_time(
 ) -> Timestamp {
     // Ignoring time windows, this is the window between which the vehicle can depart from the depot
     let minimum_depot_departure_time = earliest_start_time + depot_duration;
-    let maximum_depot_departure_time = latest_start_time.saturating_add(depot_duration).unwrap();
+    // A vehicle starting after the depot closes leaves as early as it can, the depot constraint
+    // reports the violation
+    let maximum_depot_departure_time = latest_start_time
+        .saturating_add(depot_duration)
+        .unwrap()
+        .max(minimum_depot_departure_time);
 
     let time_window_start = time_windows
         .iter()
@@ -272,4 +378,23 @@ mod tests {
             "2026-01-16T15:30:00+01:00".parse().unwrap()
         );
     }
+
+    #[test]
+    fn test_compute_initial_arrival_time_after_latest_start() {
+        let time_windows = TimeWindows::from_vec(vec![TimeWindow::from_iso(
+            Some("2026-01-16T15:00:00+01:00"),
+            None,
+        )]);
+
+        assert_eq!(
+            compute_initial_arrival_time(
+                "2026-01-16T12:00:00+01:00".parse().unwrap(),
+                "2026-01-16T10:00:00+01:00".parse().unwrap(),
+                &time_windows,
+                SignedDuration::from_mins(10),
+                SignedDuration::from_mins(20)
+            ),
+            "2026-01-16T12:30:00+01:00".parse().unwrap()
+        );
+    }
 }
@@ -0,0 +1,581 @@
+use fxhash::FxHashMap;
+use jiff::{SignedDuration, Timestamp};
+use serde::Serialize;
+
+use crate::{
+    problem::{
+        capacity::{Capacity, is_capacity_satisfied},
+        job::{ActivityId, Job, JobIdx},
+        service::ServiceType,
+        vehicle_routing_problem::VehicleRoutingProblem,
+    },
+    solver::{
+        constraints::constraint::Constraint,
+        score_level::ScoreLevel,
+        solution::{
+            route::WorkingSolutionRoute,
+            utils::{compute_activity_duration, compute_arrival_energy, compute_departure_energy},
+            working_solution::WorkingSolution,
+        },
+    },
+    utils::enumerate_idx::EnumerateIdx,
+};
+
+/// Problem found by `verify_solution`, the jobs and vehicles are given by their external ID
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Violation {
+    /// The activity is served more than once
+    DuplicateActivity { job_id: String },
+    /// The job is neither in a route nor unassigned
+    MissingJob { job_id: String },
+    /// The job is in a route and unassigned at the same time
+    AssignedAndUnassigned { job_id: String },
+    /// The pickup and the delivery of the shipment are not both in the same route, pickup first
+    ShipmentOrder { job_id: String },
+    /// The schedule kept by the route differs from the one computed from the travel times
+    ScheduleMismatch {
+        route: usize,
+        position: usize,
+        job_id: String,
+        expected_arrival: Timestamp,
+        arrival: Timestamp,
+        expected_departure: Timestamp,
+        departure: Timestamp,
+    },
+    /// The vehicle arrives after the end of all the time windows of the activity
+    LateArrival {
+        route: usize,
+        position: usize,
+        job_id: String,
+        arrival: Timestamp,
+        deadline: Option<Timestamp>,
+    },
+    /// The load of the vehicle exceeds its capacity, when leaving the depot or after the activity
+    /// at `position`
+    OverCapacity {
+        route: usize,
+        vehicle_id: String,
+        position: Option<usize>,
+        load: Capacity,
+        capacity: Capacity,
+    },
+    /// The vehicle lacks a skill required by the job
+    MissingSkills {
+        route: usize,
+        vehicle_id: String,
+        job_id: String,
+    },
+    /// The route leaves the depot outside of the shift start of the vehicle, or of the departure
+    /// windows of the depot when the vehicle has none
+    ShiftStart {
+        route: usize,
+        vehicle_id: String,
+        start: Timestamp,
+        earliest_start: Option<Timestamp>,
+        latest_start: Option<Timestamp>,
+    },
+    /// The route ends after the latest end of the shift of the vehicle
+    ShiftEnd {
+        route: usize,
+        vehicle_id: String,
+        end: Timestamp,
+        latest_end: Timestamp,
+    },
+    /// The route lasts longer than the maximum working duration of the vehicle
+    WorkingDuration {
+        route: usize,
+        vehicle_id: String,
+        duration: SignedDuration,
+        maximum: SignedDuration,
+    },
+    /// A hard constraint of the search scores the solution as infeasible
+    HardConstraint {
+        constraint: &'static str,
+        hard_score: f64,
+    },
+}
+
+/// Route as recomputed by the verifier
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct VerifiedRoute {
+    pub vehicle_id: String,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub activities: usize,
+    /// Highest load of the vehicle in each capacity dimension
+    pub max_load: Capacity,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct VerificationReport {
+    /// Non-empty routes, in the order of the routes of the solution
+    pub routes: Vec<VerifiedRoute>,
+    pub unassigned_jobs: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl VerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Checks the solution without trusting the data kept by its routes: the timings and the loads are
+/// computed again from the travel times and the demands, only the first arrival of each route is
+/// taken from the solution, the assignment of every job is checked, and the solution is scored
+/// with the hard constraints in `constraints`.
+///
+/// Unlike the search, the verifier doesn't stop at the first violation, the report lists them all
+pub fn verify_solution(
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+) -> VerificationReport {
+    let problem = solution.problem();
+    let mut violations = vec![];
+    let mut routes = vec![];
+
+    // Route and position of each served activity
+    let mut served: FxHashMap<ActivityId, (usize, usize)> = FxHashMap::default();
+
+    for (route_index, route) in solution.routes().iter().enumerate() {
+        if route.is_empty() {
+            continue;
+        }
+
+        for (position, &activity_id) in route.activity_ids().iter().enumerate() {
+            if served
+                .insert(activity_id, (route_index, position))
+                .is_some()
+            {
+                violations.push(Violation::DuplicateActivity {
+                    job_id: job_external_id(problem, activity_id.job_id()),
+                });
+            }
+        }
+
+        routes.push(verify_route(problem, route_index, route, &mut violations));
+    }
+
+    verify_assignments(solution, &served, &mut violations);
+
+    let (_, score_analysis) = solution.compute_solution_score(constraints);
+    violations.extend(
+        constraints
+            .iter()
+            .filter(|constraint| constraint.score_level() == ScoreLevel::Hard)
+            .filter_map(|constraint| {
+                let name = constraint.constraint_name();
+                score_analysis
+                    .scores
+                    .get(name)
+                    .filter(|score| score.is_infeasible())
+                    .map(|score| Violation::HardConstraint {
+                        constraint: name,
+                        hard_score: score.hard_score,
+                    })
+            }),
+    );
+
+    VerificationReport {
+        routes,
+        unassigned_jobs: solution.unassigned_jobs().len(),
+        violations,
+    }
+}
+
+fn job_external_id(problem: &VehicleRoutingProblem, job_id: JobIdx) -> String {
+    problem.job(job_id).external_id().to_owned()
+}
+
+fn verify_route(
+    problem: &VehicleRoutingProblem,
+    route_index: usize,
+    route: &WorkingSolutionRoute,
+    violations: &mut Vec<Violation>,
+) -> VerifiedRoute {
+    let vehicle = route.vehicle(problem);
+    let vehicle_id = vehicle.external_id().to_owned();
+    let activity_ids = route.activity_ids();
+
+    // When the route leaves the depot is a choice of the search, the verifier takes it from the
+    // first arrival of the route and checks it against the shift and the depot instead
+    let depot = vehicle.depot_location_id();
+    let first_location = problem.job_activity(activity_ids[0]).location_id();
+    let start = match depot {
+        Some(depot) => {
+            route.arrival_time(0)
+                - problem.travel_time(vehicle, depot, first_location)
+                - vehicle.depot_duration()
+        }
+        None => route.arrival_time(0),
+    };
+
+    let depot_windows = depot.and_then(|depot| problem.depot(depot));
+    let earliest_start = vehicle
+        .earliest_start_time()
+        .or_else(|| depot_windows.and_then(|depot| depot.earliest_departure()));
+    let latest_start = vehicle
+        .latest_start_time()
+        .or_else(|| depot_windows.and_then(|depot| depot.latest_departure()));
+
+    if earliest_start.is_some_and(|earliest_start| start < earliest_start)
+        || latest_start.is_some_and(|latest_start| start > latest_start)
+    {
+        violations.push(Violation::ShiftStart {
+            route: route_index,
+            vehicle_id: vehicle_id.clone(),
+            start,
+            earliest_start,
+            latest_start,
+        });
+    }
+
+    let mut departure = Timestamp::MIN;
+    let mut departure_energy = 0.0;
+    for (position, &activity_id) in activity_ids.iter().enumerate() {
+        let activity = problem.job_activity(activity_id);
+
+        let arrival = if position == 0 {
+            route.arrival_time(0)
+        } else {
+            let previous = problem.job_activity(activity_ids[position - 1]);
+            departure + problem.travel_time(vehicle, previous.location_id(), activity.location_id())
+        };
+
+        let time_windows = activity.time_windows();
+        let arrival_energy = compute_arrival_energy(
+            problem,
+            route.vehicle_id(),
+            (position > 0).then(|| (activity_ids[position - 1], departure_energy)),
+            activity_id,
+        );
+        departure = arrival
+            + time_windows.waiting_duration(arrival)
+            + compute_activity_duration(problem, route.vehicle_id(), activity_id, arrival_energy);
+        departure_energy = compute_departure_energy(
+            problem,
+            route.vehicle_id(),
+            activity_id,
+            arrival_energy,
+            arrival,
+            departure,
+        );
+
+        if route.arrival_time(position) != arrival || route.departure_time(position) != departure {
+            violations.push(Violation::ScheduleMismatch {
+                route: route_index,
+                position,
+                job_id: job_external_id(problem, activity_id.job_id()),
+                expected_arrival: arrival,
+                arrival: route.arrival_time(position),
+                expected_departure: departure,
+                departure: route.departure_time(position),
+            });
+        }
+
+        if !time_windows.is_empty() && !time_windows.is_satisfied(arrival) {
+            violations.push(Violation::LateArrival {
+                route: route_index,
+                position,
+                job_id: job_external_id(problem, activity_id.job_id()),
+                arrival,
+                deadline: time_windows.end(),
+            });
+        }
+
+        let job = problem.job(activity_id.job_id());
+        if !job.skills_satisfied_by_vehicle(vehicle) {
+            violations.push(Violation::MissingSkills {
+                route: route_index,
+                vehicle_id: vehicle_id.clone(),
+                job_id: job.external_id().to_owned(),
+            });
+        }
+    }
+
+    let last_location = problem
+        .job_activity(activity_ids[activity_ids.len() - 1])
+        .location_id();
+    let end = match depot {
+        Some(depot) if vehicle.should_return_to_depot() => {
+            departure
+                + problem.travel_time(vehicle, last_location, depot)
+                + vehicle.end_depot_duration()
+        }
+        _ => departure,
+    };
+
+    if let Some(latest_end) = vehicle.latest_end_time()
+        && vehicle
+            .latest_end_deadline()
+            .is_some_and(|deadline| end > deadline)
+    {
+        violations.push(Violation::ShiftEnd {
+            route: route_index,
+            vehicle_id: vehicle_id.clone(),
+            end,
+            latest_end,
+        });
+    }
+
+    if let Some(maximum) = vehicle.maximum_working_duration()
+        && end.duration_since(start) > maximum
+    {
+        violations.push(Violation::WorkingDuration {
+            route: route_index,
+            vehicle_id: vehicle_id.clone(),
+            duration: end.duration_since(start),
+            maximum,
+        });
+    }
+
+    let max_load = verify_loads(problem, route_index, route, violations);
+
+    VerifiedRoute {
+        vehicle_id,
+        start,
+        end,
+        activities: activity_ids.len(),
+        max_load,
+    }
+}
+
+/// Deliveries of the trip made of `activity_ids` up to the next reload, loaded at the depot or at
+/// the reload before the trip
+fn trip_deliveries(problem: &VehicleRoutingProblem, activity_ids: &[ActivityId]) -> Capacity {
+    let mut deliveries = Capacity::with_dimensions(problem.capacity_dimensions());
+    for &activity_id in activity_ids {
+        match problem.job(activity_id.job_id()) {
+            Job::Service(service) if service.service_type() == ServiceType::Reload => break,
+            Job::Service(service) if service.service_type() == ServiceType::Delivery => {
+                deliveries += service.demand();
+            }
+            _ => {}
+        }
+    }
+
+    deliveries
+}
+
+/// Replays the loads of the vehicle along the route, returns the highest load
+fn verify_loads(
+    problem: &VehicleRoutingProblem,
+    route_index: usize,
+    route: &WorkingSolutionRoute,
+    violations: &mut Vec<Violation>,
+) -> Capacity {
+    let vehicle = route.vehicle(problem);
+    let tolerance = problem.tolerances().capacity;
+    let activity_ids = route.activity_ids();
+
+    let mut load = trip_deliveries(problem, activity_ids);
+    let mut max_load = load.clone();
+
+    let mut check_load = |load: &Capacity, position: Option<usize>| {
+        max_load.update_max(load);
+        if problem.has_capacity() && !is_capacity_satisfied(vehicle.capacity(), load, tolerance) {
+            violations.push(Violation::OverCapacity {
+                route: route_index,
+                vehicle_id: vehicle.external_id().to_owned(),
+                position,
+                load: load.clone(),
+                capacity: vehicle.capacity().clone(),
+            });
+        }
+    };
+
+    check_load(&load, None);
+
+    for (position, &activity_id) in activity_ids.iter().enumerate() {
+        let job = problem.job(activity_id.job_id());
+        match (activity_id, job) {
+            (ActivityId::Service(_), Job::Service(service)) => match service.service_type() {
+                ServiceType::Delivery => load -= service.demand(),
+                ServiceType::Pickup => load += service.demand(),
+                // The pickups are unloaded and the deliveries of the next trip are loaded
+                ServiceType::Reload => {
+                    load = trip_deliveries(problem, &activity_ids[position + 1..]);
+                }
+                ServiceType::Charge => {}
+            },
+            (ActivityId::ShipmentPickup(_), _) => load += job.demand(),
+            (ActivityId::ShipmentDelivery(_), _) => load -= job.demand(),
+            _ => {}
+        }
+
+        check_load(&load, Some(position));
+    }
+
+    max_load
+}
+
+/// Every job has to be served exactly once or be unassigned, the reloads and charges are optional
+fn verify_assignments(
+    solution: &WorkingSolution,
+    served: &FxHashMap<ActivityId, (usize, usize)>,
+    violations: &mut Vec<Violation>,
+) {
+    let problem = solution.problem();
+
+    for (job_id, job) in problem.jobs().iter().enumerate_idx() {
+        if job.is_vehicle_stop() {
+            continue;
+        }
+
+        let assigned = match job {
+            Job::Service(_) => served.contains_key(&ActivityId::Service(job_id)),
+            Job::Shipment(_) => {
+                let pickup = served.get(&ActivityId::ShipmentPickup(job_id));
+                let delivery = served.get(&ActivityId::ShipmentDelivery(job_id));
+
+                match (pickup, delivery) {
+                    (
+                        Some((pickup_route, pickup_position)),
+                        Some((delivery_route, delivery_position)),
+                    ) if pickup_route == delivery_route && pickup_position < delivery_position => {}
+                    (None, None) => {}
+                    _ => violations.push(Violation::ShipmentOrder {
+                        job_id: job.external_id().to_owned(),
+                    }),
+                }
+
+                pickup.is_some() || delivery.is_some()
+            }
+        };
+
+        match (assigned, solution.is_unassigned(job_id)) {
+            (true, true) => violations.push(Violation::AssignedAndUnassigned {
+                job_id: job.external_id().to_owned(),
+            }),
+            (false, false) => violations.push(Violation::MissingJob {
+                job_id: job.external_id().to_owned(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::time_window::TimeWindow,
+        test_utils::{
+            TestProblemOptions, TestRoute, TestService, create_problem_for_tw_change,
+            create_test_working_solution,
+        },
+        timestamp,
+    };
+
+    use super::{Violation, verify_solution};
+
+    #[test]
+    fn test_verify_valid_solution() {
+        let problem = Arc::new(create_problem_for_tw_change(
+            vec![TestService::default(), TestService::default()],
+            TestProblemOptions::default(),
+        ));
+        let solution = create_test_working_solution(
+            problem,
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![1, 0],
+            }],
+        );
+
+        let report = verify_solution(&solution, &[]);
+
+        assert!(report.is_valid(), "{:?}", report.violations);
+        assert_eq!(report.routes.len(), 1);
+        assert_eq!(report.routes[0].activities, 2);
+        assert_eq!(report.routes[0].max_load.to_vec(), vec![20.0]);
+    }
+
+    #[test]
+    fn test_verify_late_arrival_and_shift_end() {
+        let problem = Arc::new(create_problem_for_tw_change(
+            vec![
+                TestService::default(),
+                TestService::with_time_window(TimeWindow::from_iso(
+                    None,
+                    Some("2025-11-30T08:00:00+02:00"),
+                )),
+            ],
+            TestProblemOptions {
+                earliest_start: Some(timestamp!("2025-11-30T07:00:00+02:00")),
+                latest_end: Some(timestamp!("2025-11-30T08:15:00+02:00")),
+                ..TestProblemOptions::default()
+            },
+        ));
+
+        // Arrivals at 07:30 and 08:10, the vehicle doesn't return and ends after the last service
+        // at 08:20
+        let solution = create_test_working_solution(
+            problem,
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1],
+            }],
+        );
+
+        let report = verify_solution(&solution, &[]);
+
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation::LateArrival {
+                    route: 0,
+                    position: 1,
+                    job_id: String::from("service_2"),
+                    arrival: timestamp!("2025-11-30T08:10:00+02:00"),
+                    deadline: Some(timestamp!("2025-11-30T08:00:00+02:00")),
+                },
+                Violation::ShiftEnd {
+                    route: 0,
+                    vehicle_id: String::from("vehicle"),
+                    end: timestamp!("2025-11-30T08:20:00+02:00"),
+                    latest_end: timestamp!("2025-11-30T08:15:00+02:00"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_shift_start() {
+        // The shift can't start in time, the route leaves at the earliest start
+        let problem = Arc::new(create_problem_for_tw_change(
+            vec![TestService::default()],
+            TestProblemOptions {
+                earliest_start: Some(timestamp!("2025-11-30T08:00:00+02:00")),
+                latest_start: Some(timestamp!("2025-11-30T07:00:00+02:00")),
+                ..TestProblemOptions::default()
+            },
+        ));
+        let solution = create_test_working_solution(
+            problem,
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0],
+            }],
+        );
+
+        let report = verify_solution(&solution, &[]);
+
+        assert_eq!(
+            report.violations,
+            vec![Violation::ShiftStart {
+                route: 0,
+                vehicle_id: String::from("vehicle"),
+                start: timestamp!("2025-11-30T08:00:00+02:00"),
+                earliest_start: Some(timestamp!("2025-11-30T08:00:00+02:00")),
+                latest_start: Some(timestamp!("2025-11-30T07:00:00+02:00")),
+            }]
+        );
+        assert_eq!(
+            report.routes[0].start,
+            timestamp!("2025-11-30T08:00:00+02:00")
+        );
+    }
+}
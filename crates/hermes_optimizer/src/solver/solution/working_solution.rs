@@ -1,30 +1,76 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use fxhash::{FxHashMap, FxHashSet};
 use rand::seq::IteratorRandom;
 
 use crate::{
     problem::{
+        capacity::Capacity,
+        fleet::Fleet,
         job::{ActivityId, Job, JobIdx},
+        location::LocationIdx,
         meters::Meters,
         vehicle::{Vehicle, VehicleIdx},
-        vehicle_routing_problem::VehicleRoutingProblem,
+        vehicle_routing_problem::{DEFAULT_NEIGHBORHOOD_SIZE, VehicleRoutingProblem},
     },
     solver::{
         constraints::constraint::Constraint,
         insertion::Insertion,
         score::{Score, ScoreAnalysis},
-        solution::{route::WorkingSolutionRoute, route_id::RouteIdx},
+        solution::{
+            route::WorkingSolutionRoute, route_bbox_index::RouteBBoxIndex, route_id::RouteIdx,
+        },
     },
     utils::{broken_pairs_distance::broken_pairs_distance, enumerate_idx::EnumerateIdx},
 };
 
-#[derive(Clone)]
 pub struct WorkingSolution {
     problem: Arc<VehicleRoutingProblem>,
     routes: Vec<WorkingSolutionRoute>,
     vehicle_route_map: FxHashMap<VehicleIdx, FxHashSet<RouteIdx>>,
     unassigned_jobs: FxHashSet<JobIdx>,
+
+    /// Jobs cancelled while the search was running, they are neither assigned nor unassigned
+    cancelled_jobs: FxHashSet<JobIdx>,
+
+    route_bbox_index: RouteBBoxIndex,
+
+    /// Number of close activities considered around a job by the insertions and the moves, see
+    /// `SolverParams::neighborhood_size`
+    neighborhood_size: usize,
+
+    /// Deliveries loaded at each depot with an inventory, computed on the first insertion scored
+    /// after the routes changed, see `depot_inventory_usage`
+    depot_inventory_usages: OnceLock<FxHashMap<LocationIdx, Capacity>>,
+}
+
+// Implemented by hand so that `clone_from` reuses the routes and sets of the solution, see
+// `SolutionPool`
+impl Clone for WorkingSolution {
+    fn clone(&self) -> Self {
+        WorkingSolution {
+            problem: Arc::clone(&self.problem),
+            routes: self.routes.clone(),
+            vehicle_route_map: self.vehicle_route_map.clone(),
+            unassigned_jobs: self.unassigned_jobs.clone(),
+            cancelled_jobs: self.cancelled_jobs.clone(),
+            route_bbox_index: self.route_bbox_index.clone(),
+            neighborhood_size: self.neighborhood_size,
+            depot_inventory_usages: self.depot_inventory_usages.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.problem.clone_from(&source.problem);
+        self.routes.clone_from(&source.routes);
+        self.vehicle_route_map.clone_from(&source.vehicle_route_map);
+        self.unassigned_jobs.clone_from(&source.unassigned_jobs);
+        self.cancelled_jobs.clone_from(&source.cancelled_jobs);
+        self.route_bbox_index.clone_from(&source.route_bbox_index);
+        self.neighborhood_size = source.neighborhood_size;
+        self.depot_inventory_usages
+            .clone_from(&source.depot_inventory_usages);
+    }
 }
 
 impl WorkingSolution {
@@ -35,7 +81,10 @@ impl WorkingSolution {
             .enumerate_idx()
             .map(|(vehicle_id, _)| WorkingSolutionRoute::empty(&problem, vehicle_id))
             .collect::<Vec<_>>();
-        let unassigned_jobs = (0..problem.jobs().len()).map(JobIdx::new).collect();
+        let unassigned_jobs = (0..problem.jobs().len())
+            .map(JobIdx::new)
+            .filter(|&job_id| !problem.job(job_id).is_vehicle_stop())
+            .collect();
 
         let vehicle_route_map = problem
             .vehicles()
@@ -51,14 +100,34 @@ impl WorkingSolution {
         WorkingSolution {
             routes,
             unassigned_jobs,
+            cancelled_jobs: FxHashSet::default(),
+            route_bbox_index: RouteBBoxIndex::default(),
             vehicle_route_map,
             problem,
+            neighborhood_size: DEFAULT_NEIGHBORHOOD_SIZE,
+            depot_inventory_usages: OnceLock::new(),
         }
     }
 
+    pub fn neighborhood_size(&self) -> usize {
+        self.neighborhood_size
+    }
+
+    pub fn set_neighborhood_size(&mut self, neighborhood_size: usize) {
+        self.neighborhood_size = neighborhood_size;
+    }
+
     fn create_additional_route(&mut self, vehicle_id: VehicleIdx) {
-        // Don't create an additional route if the fleet is not infinite
-        if !self.problem.fleet().is_infinite() {
+        // Don't create an additional route once the vehicle reached its limit, a single route
+        // for the vehicles of a finite fleet
+        if let Some(limit) = self.problem.fleet().vehicle_limit(vehicle_id)
+            && self
+                .routes
+                .iter()
+                .filter(|route| route.vehicle_id == vehicle_id)
+                .count()
+                >= limit
+        {
             return;
         }
 
@@ -78,7 +147,27 @@ impl WorkingSolution {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.unassigned_jobs.len() == self.problem.jobs().len()
+        self.assigned_jobs_count() == 0
+    }
+
+    pub fn assigned_jobs_count(&self) -> usize {
+        self.problem.jobs().len()
+            - self.problem.vehicle_stops_count()
+            - self.unassigned_jobs.len()
+            - self.cancelled_jobs.len()
+    }
+
+    fn is_assigned(&self, job_id: JobIdx) -> bool {
+        !self.unassigned_jobs.contains(&job_id)
+            && !self.cancelled_jobs.contains(&job_id)
+            && !self.problem.job(job_id).is_vehicle_stop()
+    }
+
+    /// Reloads and charges are not jobs to serve, once removed they are simply available again
+    fn unassign(&mut self, job_id: JobIdx) {
+        if !self.problem.job(job_id).is_vehicle_stop() {
+            self.unassigned_jobs.insert(job_id);
+        }
     }
 
     pub fn has_unassigned(&self) -> bool {
@@ -130,12 +219,10 @@ impl WorkingSolution {
     // }
 
     pub fn has_available_vehicle(&self) -> bool {
-        let fleet = self.problem.fleet();
-        let is_infinite = fleet.is_infinite();
-        if is_infinite {
-            true
-        } else {
-            self.routes.len() < fleet.vehicles().len()
+        match self.problem.fleet() {
+            Fleet::Infinite(_) => true,
+            Fleet::Finite(vehicles) => self.routes.len() < vehicles.len(),
+            Fleet::Counted { .. } => self.available_vehicles_for_insertion().next().is_some(),
         }
     }
 
@@ -163,6 +250,28 @@ impl WorkingSolution {
         &self.unassigned_jobs
     }
 
+    pub fn cancelled_jobs(&self) -> &FxHashSet<JobIdx> {
+        &self.cancelled_jobs
+    }
+
+    /// Removes the job from its route and from the unassigned jobs, it is never inserted again.
+    ///
+    /// Returns false when the job is locked in its route or already cancelled.
+    pub fn cancel_job(&mut self, job_id: JobIdx) -> bool {
+        if self.cancelled_jobs.contains(&job_id) {
+            return false;
+        }
+
+        if !self.unassigned_jobs.contains(&job_id) && !self.remove_job(job_id) {
+            return false;
+        }
+
+        self.unassigned_jobs.remove(&job_id);
+        self.cancelled_jobs.insert(job_id);
+
+        true
+    }
+
     pub fn problem(&self) -> &VehicleRoutingProblem {
         self.problem.as_ref()
     }
@@ -171,10 +280,97 @@ impl WorkingSolution {
         self.routes.iter().filter(|route| !route.is_empty())
     }
 
+    /// Non-empty routes in their serialization order: by vehicle external ID, then in the order
+    /// the routes of a same vehicle were created. It does not depend on the search history.
+    pub fn ordered_non_empty_routes(&self) -> Vec<(RouteIdx, &WorkingSolutionRoute)> {
+        let mut routes = self
+            .routes
+            .iter()
+            .enumerate_idx()
+            .filter(|(_, route)| !route.is_empty())
+            .collect::<Vec<_>>();
+
+        // Stable, the routes of a same vehicle keep their creation order
+        routes.sort_by(|(_, a), (_, b)| {
+            a.vehicle(&self.problem)
+                .external_id()
+                .cmp(b.vehicle(&self.problem).external_id())
+        });
+
+        routes
+    }
+
+    /// Unassigned jobs in their serialization order, the order of the jobs in the problem
+    pub fn ordered_unassigned_jobs(&self) -> Vec<JobIdx> {
+        let mut unassigned_jobs = self.unassigned_jobs.iter().copied().collect::<Vec<_>>();
+        unassigned_jobs.sort_unstable();
+        unassigned_jobs
+    }
+
+    /// Indexes again the bounding boxes of the routes modified since the last update
+    pub fn update_route_bbox_index(&mut self) {
+        self.route_bbox_index.update(&self.routes);
+    }
+
+    /// Non empty routes whose bounding box intersects the one of `route_id`, as of the last
+    /// `update_route_bbox_index`
+    pub fn intersecting_routes(&self, route_id: RouteIdx) -> impl Iterator<Item = RouteIdx> + '_ {
+        self.route_bbox_index.intersecting_routes(route_id)
+    }
+
+    /// Deliveries loaded at the depot by all the routes starting from it
+    pub fn depot_inventory_usage(&self, location_id: LocationIdx) -> Capacity {
+        self.depot_inventory_usages
+            .get_or_init(|| {
+                let mut usages = FxHashMap::<LocationIdx, Capacity>::default();
+                for route in self.non_empty_routes_iter() {
+                    if let Some(depot_location_id) =
+                        route.vehicle(&self.problem).depot_location_id()
+                        && self.problem.depot_inventory(depot_location_id).is_some()
+                    {
+                        *usages
+                            .entry(depot_location_id)
+                            .or_insert_with(Capacity::empty) += route.depot_load();
+                    }
+                }
+
+                usages
+            })
+            .get(&location_id)
+            .cloned()
+            .unwrap_or_else(Capacity::empty)
+    }
+
+    /// Called by every change of the routes
+    fn invalidate_depot_inventory_usages(&mut self) {
+        self.depot_inventory_usages.take();
+    }
+
     pub fn non_empty_routes_count(&self) -> usize {
         self.routes.iter().filter(|route| !route.is_empty()).count()
     }
 
+    /// Approximate number of bytes used by the solution, the problem is shared and not counted
+    pub fn estimated_size_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self
+                .routes
+                .iter()
+                .map(WorkingSolutionRoute::estimated_size_bytes)
+                .sum::<usize>()
+            + self.unassigned_jobs.capacity() * std::mem::size_of::<JobIdx>()
+            + self.cancelled_jobs.capacity() * std::mem::size_of::<JobIdx>()
+            + self.route_bbox_index.estimated_size_bytes()
+            + self
+                .vehicle_route_map
+                .values()
+                .map(|routes| {
+                    std::mem::size_of::<(VehicleIdx, FxHashSet<RouteIdx>)>()
+                        + routes.capacity() * std::mem::size_of::<RouteIdx>()
+                })
+                .sum::<usize>()
+    }
+
     pub fn routes(&self) -> &[WorkingSolutionRoute] {
         &self.routes
     }
@@ -184,6 +380,8 @@ impl WorkingSolution {
     }
 
     pub fn route_mut(&mut self, route_id: RouteIdx) -> &mut WorkingSolutionRoute {
+        self.invalidate_depot_inventory_usages();
+
         &mut self.routes[route_id]
     }
 
@@ -191,13 +389,13 @@ impl WorkingSolution {
     where
         R: rand::Rng,
     {
-        if self.unassigned_jobs.len() == self.problem.jobs().len() {
+        if self.is_empty() {
             return None;
         }
 
         loop {
             let job_id = self.problem().random_job(rng);
-            if !self.unassigned_jobs.contains(&job_id) {
+            if self.is_assigned(job_id) {
                 return Some(job_id);
             }
         }
@@ -207,13 +405,13 @@ impl WorkingSolution {
     where
         R: rand::Rng,
     {
-        if self.unassigned_jobs.len() == self.problem.jobs().len() {
+        if self.is_empty() {
             return None;
         }
 
         loop {
             let job_id = self.problem().random_job(rng);
-            if !self.unassigned_jobs.contains(&job_id) {
+            if self.is_assigned(job_id) {
                 return match self.problem.job(job_id) {
                     Job::Service(_) => Some(ActivityId::Service(job_id)),
                     Job::Shipment(_) => {
@@ -274,6 +472,8 @@ impl WorkingSolution {
     }
 
     pub fn insert(&mut self, insertion: &Insertion) {
+        self.invalidate_depot_inventory_usages();
+
         match insertion {
             Insertion::Service(context) => {
                 let route = &mut self.routes[context.route_id];
@@ -301,7 +501,60 @@ impl WorkingSolution {
         }
     }
 
+    /// Moves the activities of `route_id` from `position` to the end of the route into the empty route `target_route_id`
+    pub fn split_route(&mut self, route_id: RouteIdx, position: usize, target_route_id: RouteIdx) {
+        self.invalidate_depot_inventory_usages();
+
+        assert_ne!(route_id, target_route_id);
+        assert!(
+            self.routes[target_route_id].is_empty(),
+            "Cannot split a route into a non-empty route"
+        );
+
+        let route = &mut self.routes[route_id];
+        let len = route.len();
+        let tail = route.activity_ids[position..].to_vec();
+        route.replace_activities(&self.problem, &[], position, len);
+
+        let target_route = &mut self.routes[target_route_id];
+        let vehicle_id = target_route.vehicle_id;
+        target_route.replace_activities(&self.problem, &tail, 0, 0);
+
+        self.create_additional_route(vehicle_id);
+    }
+
+    /// Fills the empty route `route_id` with the given activities, in order
+    pub fn assign_route(&mut self, route_id: RouteIdx, activity_ids: &[ActivityId]) {
+        self.invalidate_depot_inventory_usages();
+
+        assert!(
+            self.routes[route_id].is_empty(),
+            "Cannot assign activities to a non-empty route"
+        );
+
+        if activity_ids.is_empty() {
+            return;
+        }
+
+        let route = &mut self.routes[route_id];
+        let vehicle_id = route.vehicle_id;
+        route.replace_activities(&self.problem, activity_ids, 0, 0);
+
+        for activity_id in activity_ids {
+            self.unassigned_jobs.remove(&activity_id.job_id());
+        }
+
+        self.create_additional_route(vehicle_id);
+    }
+
+    /// Locks the first `len` activities of the route, they will not be moved or unassigned by the search
+    pub fn lock_route(&mut self, route_id: RouteIdx, len: usize) {
+        self.routes[route_id].lock(len);
+    }
+
     pub fn remove_activity_at(&mut self, route_id: RouteIdx, position: usize) {
+        self.invalidate_depot_inventory_usages();
+
         if route_id.get() >= self.routes.len() {
             return; // Invalid route ID
         }
@@ -311,22 +564,27 @@ impl WorkingSolution {
         if let Some(activity_id) = route.get(position) {
             let removed = route.remove_activity(&self.problem, activity_id);
             if removed {
-                self.unassigned_jobs.insert(activity_id.job_id());
+                self.unassign(activity_id.job_id());
             }
         }
     }
 
     pub fn remove_activity(&mut self, activity_id: ActivityId) -> bool {
+        self.invalidate_depot_inventory_usages();
+
         let mut removed = false;
         for route in self.routes.iter_mut() {
             removed = route.remove_activity(&self.problem, activity_id);
 
             if removed {
-                self.unassigned_jobs.insert(activity_id.job_id());
                 break;
             }
         }
 
+        if removed {
+            self.unassign(activity_id.job_id());
+        }
+
         removed
     }
 
@@ -340,51 +598,129 @@ impl WorkingSolution {
     }
 
     pub fn remove_service_from_route(&mut self, route_id: usize, service_id: JobIdx) -> bool {
+        self.invalidate_depot_inventory_usages();
+
         let mut removed = false;
         let route = &mut self.routes[route_id];
         if route.contains_activity(ActivityId::Service(service_id)) {
             removed = route.remove_activity(&self.problem, ActivityId::Service(service_id));
 
             if removed {
-                self.unassigned_jobs.insert(service_id);
+                self.unassign(service_id);
             }
         }
 
         removed
     }
 
+    /// Removes the reloads that no longer split two trips: at the start or the end of their
+    /// route, or right after another reload
+    pub fn remove_idle_reloads(&mut self) {
+        self.invalidate_depot_inventory_usages();
+
+        for route in &mut self.routes {
+            let idle_reloads = route
+                .activity_ids
+                .iter()
+                .enumerate()
+                .skip(route.locked_len())
+                .filter(|&(position, activity_id)| {
+                    self.problem.job(activity_id.job_id()).is_reload()
+                        && (position == 0
+                            || position == route.len() - 1
+                            || self
+                                .problem
+                                .job(route.activity_ids[position - 1].job_id())
+                                .is_reload())
+                })
+                .map(|(_, &activity_id)| activity_id)
+                .collect::<Vec<_>>();
+
+            for activity_id in idle_reloads {
+                route.remove_activity(&self.problem, activity_id);
+            }
+        }
+    }
+
+    /// Removes the charges the battery of their vehicle can do without
+    pub fn remove_idle_charges(&mut self) {
+        self.invalidate_depot_inventory_usages();
+
+        for route in &mut self.routes {
+            let charges = route
+                .activity_ids
+                .iter()
+                .enumerate()
+                .skip(route.locked_len())
+                .filter(|&(_, activity_id)| self.problem.job(activity_id.job_id()).is_charge())
+                .map(|(position, _)| position)
+                .collect::<Vec<_>>();
+
+            // From the end so that the positions of the remaining charges don't move
+            for position in charges.into_iter().rev() {
+                if route.is_valid_energy_change(
+                    &self.problem,
+                    std::iter::empty(),
+                    position,
+                    position + 1,
+                ) {
+                    let activity_id = route.activity_ids[position];
+                    route.remove_activity(&self.problem, activity_id);
+                }
+            }
+        }
+    }
+
     pub fn sync(&mut self) {
+        self.invalidate_depot_inventory_usages();
+
         for route in &mut self.routes {
             route.sync(&self.problem);
         }
 
         if !self.problem().fleet().is_infinite() {
-            // Assert that all vehicle_id are different when not using an infinite fleet
-            assert_eq!(
-                self.routes
-                    .iter()
-                    .map(|route| route.vehicle_id())
-                    .collect::<FxHashSet<_>>()
-                    .len(),
-                self.routes.len()
-            );
+            // Assert that no vehicle is used more than its limit, i.e. that all vehicle_id are
+            // different for a finite fleet
+            let mut route_counts = vec![0; self.problem.vehicles().len()];
+            for route in &self.routes {
+                route_counts[route.vehicle_id().get()] += 1;
+            }
+
+            for (vehicle_id, _) in self.problem.vehicles().iter().enumerate_idx() {
+                if let Some(limit) = self.problem.fleet().vehicle_limit(vehicle_id) {
+                    assert!(route_counts[vehicle_id.get()] <= limit);
+                }
+            }
         }
     }
 
     pub fn resync_route(&mut self, route_id: RouteIdx) {
+        self.invalidate_depot_inventory_usages();
+
         self.routes[route_id].sync(&self.problem);
     }
 
+    /// Removes the activities of the route, except for the locked ones
     pub fn remove_route(&mut self, route_id: RouteIdx) -> usize {
-        let mut removed = 0;
-        removed += self.routes[route_id].len();
-        for job_id in self.routes[route_id].activity_ids.iter() {
-            self.unassigned_jobs.insert(job_id.job_id());
+        self.invalidate_depot_inventory_usages();
+
+        let route = &mut self.routes[route_id];
+        let locked_len = route.locked_len();
+        let len = route.len();
+
+        for activity_id in route.activity_ids[locked_len..].iter() {
+            if !self.problem.job(activity_id.job_id()).is_vehicle_stop() {
+                self.unassigned_jobs.insert(activity_id.job_id());
+            }
         }
 
-        self.routes[route_id].reset(&self.problem);
+        if locked_len == 0 {
+            route.reset(&self.problem);
+        } else if locked_len < len {
+            route.replace_activities(&self.problem, &[], locked_len, len);
+        }
 
-        removed
+        len - locked_len
     }
 
     pub fn distance(&self) -> Meters {
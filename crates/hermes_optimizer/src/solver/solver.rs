@@ -1,45 +1,156 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use jiff::Timestamp;
 use parking_lot::RwLock;
 use schemars::JsonSchema;
 use serde::Serialize;
+use tracing::Span;
 
 #[cfg(feature = "statistics")]
-use crate::solver::statistics::SearchStatistics;
+use crate::solver::{
+    events::SolverEvent,
+    statistics::{SearchStatistics, SearchTelemetry},
+};
 use crate::{
-    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    json::checkpoint::{CheckpointError, JsonCheckpoint},
+    problem::{job::JobIdx, vehicle_routing_problem::VehicleRoutingProblem},
     solver::{
-        alns::AlnsRunResult, alns_weights::AlnsWeights,
-        recreate::recreate_strategy::RecreateStrategy, ruin::ruin_strategy::RuinStrategy,
+        alns::{AlnsCheckpoint, AlnsRunResult},
+        alns_weights::AlnsWeights,
+        recreate::recreate_strategy::RecreateStrategy,
+        ruin::ruin_strategy::RuinStrategy,
+        solution::working_solution::WorkingSolution,
     },
 };
 
-use super::{accepted_solution::AcceptedSolution, alns::Alns, solver_params::SolverParams};
+use super::{
+    accepted_solution::AcceptedSolution,
+    alns::Alns,
+    solver_params::{SolverParams, SolverParamsError, Threads},
+    unassigned_reason::UnassignedReason,
+};
 
 #[derive(Copy, Clone, Debug, Serialize, JsonSchema)]
 pub enum SolverStatus {
     Pending,
     Running,
+    /// Stopped with a checkpoint of the search, can be resumed
+    Paused,
     Completed,
+    Cancelled,
     Error,
 }
 
+/// Why a run of the search stopped with an error
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct SolverFailure {
+    pub reason: String,
+    pub failed_at: Timestamp,
+
+    /// 1 for the first run, 2 for its retry
+    pub attempt: usize,
+}
+
 pub struct Solver {
     search: Alns,
     status: RwLock<SolverStatus>,
+    checkpoint: RwLock<Option<AlnsCheckpoint>>,
     created_at: Timestamp,
+
+    /// 1, or 2 when the solver retries a failed run, see `Solver::retry`
+    attempt: usize,
+    failures: RwLock<Vec<SolverFailure>>,
+
+    /// Span entered by the search, see `Solver::with_span`
+    span: Span,
 }
 
 impl Solver {
-    pub fn new(problem: VehicleRoutingProblem, params: SolverParams) -> Self {
-        let search = Alns::new(params, Arc::new(problem));
+    pub fn new(
+        problem: impl Into<Arc<VehicleRoutingProblem>>,
+        params: SolverParams,
+    ) -> Result<Self, SolverParamsError> {
+        let search = Alns::new(params, problem.into())?;
 
-        Solver {
+        Ok(Solver {
             status: RwLock::new(SolverStatus::Pending),
+            checkpoint: RwLock::new(None),
             search,
             created_at: Timestamp::now(),
+            attempt: 1,
+            failures: RwLock::new(vec![]),
+            span: Span::none(),
+        })
+    }
+
+    /// Runs the search and its threads within `span`, e.g. to correlate the logs of a job with the
+    /// request that created it
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Continues a run interrupted after writing a checkpoint file, see `SolverParams::checkpoint`
+    pub fn resume_from_checkpoint(
+        problem: impl Into<Arc<VehicleRoutingProblem>>,
+        params: SolverParams,
+        path: &Path,
+    ) -> Result<Self, CheckpointError> {
+        let search = Alns::resume_from_checkpoint(params, problem.into(), path)?;
+
+        Ok(Solver {
+            status: RwLock::new(SolverStatus::Pending),
+            checkpoint: RwLock::new(None),
+            search,
+            created_at: Timestamp::now(),
+            attempt: 1,
+            failures: RwLock::new(vec![]),
+            span: Span::none(),
+        })
+    }
+
+    /// Solver running a failed search once more, on a single thread and in deterministic mode so
+    /// that a failure caused by the threads does not happen again. It starts from the best
+    /// solution of the failed run and keeps its failures.
+    ///
+    /// Returns `None` unless the search failed, `SolverParams::retry_on_failure` is set and the
+    /// solver is not a retry already. The callbacks of `on_best_solution` are not carried over.
+    pub fn retry(&self) -> Option<Solver> {
+        let params = self.search.params();
+        if !matches!(self.status(), SolverStatus::Error)
+            || !params.retry_on_failure
+            || self.attempt > 1
+        {
+            return None;
         }
+
+        let params = SolverParams {
+            search_threads: Threads::Single,
+            insertion_threads: Threads::Single,
+            deterministic: true,
+            ..params.clone()
+        };
+
+        let solver = Solver {
+            search: Alns::new(params, Arc::clone(self.problem())).ok()?,
+            status: RwLock::new(SolverStatus::Pending),
+            checkpoint: RwLock::new(None),
+            created_at: self.created_at,
+            attempt: self.attempt + 1,
+            failures: RwLock::new(self.failures()),
+            span: self.span.clone(),
+        };
+
+        if let Some(best_solution) = self.current_best_solution() {
+            solver.set_initial_solution(best_solution.solution);
+        }
+
+        Some(solver)
+    }
+
+    /// Warm starts the solver from an existing solution of the same problem
+    pub fn set_initial_solution(&self, solution: WorkingSolution) {
+        self.search.set_initial_solution(solution);
     }
 
     pub fn on_best_solution<F>(&mut self, callback: F)
@@ -50,14 +161,47 @@ impl Solver {
     }
 
     pub fn solve(&self) -> anyhow::Result<AlnsRunResult> {
+        let _span = self.span.enter();
         *self.status.write() = SolverStatus::Running;
-        match self.search.run() {
+
+        // A panic in the search must not leave the job running forever
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.search.run()))
+            .unwrap_or_else(|payload| {
+                Err(anyhow::anyhow!(
+                    "Search panicked: {}",
+                    panic_message(&*payload)
+                ))
+            });
+
+        match result {
             Ok(result) => {
-                *self.status.write() = SolverStatus::Completed;
+                let mut status = self.status.write();
+                match *status {
+                    // The search was stopped by `pause`, keep what is needed to resume it
+                    SolverStatus::Paused => {
+                        *self.checkpoint.write() = Some(self.search.checkpoint())
+                    }
+                    SolverStatus::Running => {
+                        // Cancellations received after the last sync of the search threads
+                        self.search.apply_pending_cancellations();
+                        *status = SolverStatus::Completed
+                    }
+                    _ => {}
+                }
                 Ok(result)
             }
             Err(err) => {
-                *self.status.write() = SolverStatus::Error;
+                tracing::error!("Search failed: {err:#}");
+                self.failures.write().push(SolverFailure {
+                    reason: format!("{err:#}"),
+                    failed_at: Timestamp::now(),
+                    attempt: self.attempt,
+                });
+                // A job stopped or cancelled while it was failing is not retried
+                let mut status = self.status.write();
+                if !matches!(*status, SolverStatus::Completed | SolverStatus::Cancelled) {
+                    *status = SolverStatus::Error;
+                }
                 Err(err)
             }
         }
@@ -68,10 +212,61 @@ impl Solver {
         *self.status.write() = SolverStatus::Completed;
     }
 
+    /// Stops a running search, the checkpoint is taken once the search threads have stopped.
+    /// Returns false when the solver is not running.
+    pub fn pause(&self) -> bool {
+        let mut status = self.status.write();
+        if !matches!(*status, SolverStatus::Running) {
+            return false;
+        }
+
+        *status = SolverStatus::Paused;
+        self.search.stop();
+        true
+    }
+
+    /// Restores the checkpoint of a paused solver, `solve` has to be called again to continue the search.
+    /// Returns false when the solver is not paused or its search has not stopped yet.
+    pub fn resume(&self) -> bool {
+        if !matches!(self.status(), SolverStatus::Paused) {
+            return false;
+        }
+
+        match self.checkpoint.write().take() {
+            Some(checkpoint) => {
+                self.search.restore(checkpoint);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops the search for good and drops the checkpoint, the best solution stays available
+    pub fn cancel(&self) {
+        *self.status.write() = SolverStatus::Cancelled;
+        self.search.stop();
+        self.checkpoint.write().take();
+    }
+
+    /// Removes jobs from the solutions without restarting the search, a running search drops them at
+    /// its next sync barrier and a paused one when it is resumed
+    pub fn cancel_jobs(&self, job_ids: impl IntoIterator<Item = JobIdx>) {
+        // The status lock orders the cancellation with the end of a running search
+        let status = self.status.read();
+        self.search.cancel_jobs(job_ids);
+        if !matches!(*status, SolverStatus::Running | SolverStatus::Paused) {
+            self.search.apply_pending_cancellations();
+        }
+    }
+
     pub fn problem(&self) -> &Arc<VehicleRoutingProblem> {
         self.search.problem()
     }
 
+    pub fn params(&self) -> &SolverParams {
+        self.search.params()
+    }
+
     pub fn status(&self) -> SolverStatus {
         *self.status.read()
     }
@@ -80,16 +275,70 @@ impl Solver {
         self.created_at
     }
 
+    pub fn attempt(&self) -> usize {
+        self.attempt
+    }
+
+    /// Failures of the previous runs, the last one explains the `Error` status
+    pub fn failures(&self) -> Vec<SolverFailure> {
+        self.failures.read().clone()
+    }
+
     pub fn current_best_solution(&self) -> Option<AcceptedSolution> {
         self.search.best_solution()
     }
 
+    /// Why the unassigned jobs of the solution could not be inserted
+    pub fn unassigned_reasons(&self, solution: &WorkingSolution) -> Vec<UnassignedReason> {
+        self.search.unassigned_reasons(solution)
+    }
+
     #[cfg(feature = "statistics")]
     pub fn statistics(&self) -> Arc<SearchStatistics> {
         self.search.statistics()
     }
 
+    #[cfg(feature = "statistics")]
+    pub fn telemetry(&self) -> SearchTelemetry {
+        self.search.telemetry()
+    }
+
+    #[cfg(feature = "statistics")]
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<SolverEvent> {
+        self.search.subscribe_events()
+    }
+
+    #[cfg(feature = "statistics")]
+    pub fn progress(&self) -> SolverEvent {
+        self.search.progress()
+    }
+
     pub fn weights(&self) -> (AlnsWeights<RuinStrategy>, AlnsWeights<RecreateStrategy>) {
         self.search.weights_cloned()
     }
+
+    /// State of the search which can be restored on another solver of the same problem, e.g. for backups
+    pub fn json_checkpoint(&self) -> JsonCheckpoint {
+        match self.checkpoint.read().as_ref() {
+            Some(checkpoint) => JsonCheckpoint::from(checkpoint),
+            None => JsonCheckpoint::from(&self.search.checkpoint()),
+        }
+    }
+
+    /// Restores a checkpoint on a solver which has not been started yet
+    pub fn restore_json_checkpoint(
+        &self,
+        checkpoint: &JsonCheckpoint,
+    ) -> Result<(), CheckpointError> {
+        self.search.restore_json_checkpoint(checkpoint)
+    }
+}
+
+/// Message given to `panic!`, when it is a string
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic payload")
 }
@@ -1,27 +1,267 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use thiserror::Error;
 use tokio::sync::RwLock;
+use tracing::Span;
 
-use crate::problem::vehicle_routing_problem::VehicleRoutingProblem;
+use crate::{
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::{accepted_solution::AcceptedSolution, solution::working_solution::WorkingSolution},
+};
 
-use super::{solver::Solver, solver_params::SolverParams};
+use super::{
+    solver::{Solver, SolverStatus},
+    solver_params::{SolverParams, SolverParamsError, Termination},
+};
+
+type Solvers = Arc<RwLock<HashMap<String, Arc<Solver>>>>;
+
+/// Counters of the failed searches since the manager was created
+#[derive(Default)]
+struct FailureCounters {
+    failed: AtomicUsize,
+    retried: AtomicUsize,
+    recovered: AtomicUsize,
+}
+
+#[derive(Serialize, JsonSchema, Debug, PartialEq)]
+pub struct SolverFailureMetrics {
+    /// Runs stopped by an error or a panic, retries included
+    pub failed: usize,
+    pub retried: usize,
+
+    /// Retries which completed
+    pub recovered: usize,
+}
+
+/// Owner of jobs, at most `max_running_jobs` searches of its jobs run at the same time
+#[derive(Clone, Debug)]
+pub struct JobOwner {
+    pub name: String,
+    pub max_running_jobs: Option<usize>,
+}
+
+#[derive(Error, Debug)]
+pub enum SolverManagerError {
+    #[error("Job {0} not found")]
+    JobNotFound(String),
+
+    #[error("{owner} already has {max_running_jobs} running jobs")]
+    TooManyRunningJobs {
+        owner: String,
+        max_running_jobs: usize,
+    },
+
+    #[error("Invalid solver parameters: {0}")]
+    InvalidParams(#[from] SolverParamsError),
+}
+
+/// Owners of the jobs and the number of search threads of each job
+#[derive(Default)]
+struct Ownership {
+    owners: HashMap<String, JobOwner>,
+    searches: HashMap<String, usize>,
+}
+
+impl Ownership {
+    /// A job searching twice, e.g. restarted before the search of its replaced solver returned,
+    /// counts twice
+    fn running_searches(&self, owner: &str) -> usize {
+        self.searches
+            .iter()
+            .filter(|(job_id, _)| {
+                self.owners
+                    .get(*job_id)
+                    .is_some_and(|job_owner| job_owner.name == owner)
+            })
+            .map(|(_, searches)| searches)
+            .sum()
+    }
+}
+
+/// Search thread of a job, counted against the limit of its owner until it is dropped
+struct RunningSearch {
+    ownership: Arc<Mutex<Ownership>>,
+    job_id: String,
+}
+
+impl Drop for RunningSearch {
+    fn drop(&mut self) {
+        let mut ownership = self.ownership.lock();
+        if let Some(searches) = ownership.searches.get_mut(&self.job_id) {
+            *searches -= 1;
+            if *searches == 0 {
+                ownership.searches.remove(&self.job_id);
+            }
+        }
+    }
+}
+
+/// Solvers of a batch listed with the other jobs. They are stopped and removed with the owner of
+/// the batch when the batch is done, or when its future is dropped
+struct BatchRegistration {
+    solvers: Solvers,
+    ownership: Arc<Mutex<Ownership>>,
+    batch_id: String,
+    batch: Vec<Arc<Solver>>,
+}
+
+impl BatchRegistration {
+    fn unregister(registered: &mut HashMap<String, Arc<Solver>>, batch: &[Arc<Solver>]) {
+        for solver in batch {
+            let job_id = solver.problem().id();
+            if registered
+                .get(job_id)
+                .is_some_and(|registered| Arc::ptr_eq(registered, solver))
+            {
+                registered.remove(job_id);
+            }
+        }
+    }
+}
+
+impl Drop for BatchRegistration {
+    fn drop(&mut self) {
+        self.ownership.lock().owners.remove(&self.batch_id);
+
+        for solver in &self.batch {
+            if matches!(
+                solver.status(),
+                SolverStatus::Pending | SolverStatus::Running
+            ) {
+                solver.stop();
+            }
+        }
+
+        // The lock can't be awaited here, it is released soon by the tasks holding it
+        if let Ok(mut registered) = self.solvers.try_write() {
+            Self::unregister(&mut registered, &self.batch);
+        } else if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let solvers = Arc::clone(&self.solvers);
+            let batch = std::mem::take(&mut self.batch);
+            runtime.spawn(async move {
+                Self::unregister(&mut *solvers.write().await, &batch);
+            });
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct SolverManager {
-    solvers: RwLock<HashMap<String, Arc<Solver>>>, // This struct will manage the solver instances and their configurations
+    solvers: Solvers, // This struct will manage the solver instances and their configurations
+    failures: Arc<FailureCounters>,
+    ownership: Arc<Mutex<Ownership>>,
 }
 
 impl SolverManager {
-    pub async fn solve(&self, job_id: String, problem: VehicleRoutingProblem) {
-        let solver = Arc::new(Solver::new(problem, SolverParams::default()));
+    /// Runs the search of `job_id` on a new thread. A failed search is replaced by its retry when
+    /// the job allows it, see `Solver::retry`, pollers then see the retry under the same job ID.
+    fn spawn_search(&self, job_id: String, solver: Arc<Solver>, search: RunningSearch) {
+        let solvers = Arc::clone(&self.solvers);
+        let failures = Arc::clone(&self.failures);
+
+        std::thread::spawn(move || {
+            // Released when the search and its retry are done
+            let _search = search;
+
+            if solver.solve().is_ok() {
+                return;
+            }
+
+            failures.failed.fetch_add(1, Ordering::Relaxed);
+
+            let Some(retry) = solver.retry() else {
+                return;
+            };
+
+            // The job may have been removed or replaced while the search was failing
+            let retry = Arc::new(retry);
+            {
+                let mut solvers = solvers.blocking_write();
+                match solvers.get_mut(&job_id) {
+                    Some(current) if Arc::ptr_eq(current, &solver) => {
+                        *current = Arc::clone(&retry);
+                    }
+                    _ => return,
+                }
+            }
+
+            tracing::warn!("Retrying job {job_id} on a single thread");
+            failures.retried.fetch_add(1, Ordering::Relaxed);
+
+            if retry.solve().is_ok() {
+                failures.recovered.fetch_add(1, Ordering::Relaxed);
+            } else {
+                failures.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+
+    /// Counts a new search of the job against the limit of its owner
+    fn reserve_search(&self, job_id: &str) -> Result<RunningSearch, SolverManagerError> {
+        let mut ownership = self.ownership.lock();
+
+        if let Some(owner) = ownership.owners.get(job_id)
+            && let Some(max_running_jobs) = owner.max_running_jobs
+            && ownership.running_searches(&owner.name) >= max_running_jobs
+        {
+            return Err(SolverManagerError::TooManyRunningJobs {
+                owner: owner.name.clone(),
+                max_running_jobs,
+            });
+        }
+
+        *ownership.searches.entry(job_id.to_owned()).or_default() += 1;
+
+        Ok(RunningSearch {
+            ownership: Arc::clone(&self.ownership),
+            job_id: job_id.to_owned(),
+        })
+    }
+
+    /// Limits the searches of the job with the other jobs of `owner`, the owner is kept when the
+    /// job is created again under the same ID
+    pub fn set_owner(&self, job_id: &str, owner: JobOwner) {
+        self.ownership
+            .lock()
+            .owners
+            .insert(job_id.to_owned(), owner);
+    }
+
+    pub fn failure_metrics(&self) -> SolverFailureMetrics {
+        SolverFailureMetrics {
+            failed: self.failures.failed.load(Ordering::Relaxed),
+            retried: self.failures.retried.load(Ordering::Relaxed),
+            recovered: self.failures.recovered.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Creates the job and starts its search right away, counted against the limit of the owner
+    /// set with `set_owner`
+    pub async fn solve(
+        &self,
+        job_id: String,
+        problem: VehicleRoutingProblem,
+    ) -> Result<(), SolverManagerError> {
+        let solver = Arc::new(Solver::new(problem, SolverParams::default())?);
+        let search = self.reserve_search(&job_id)?;
         self.solvers
             .write()
             .await
-            .insert(job_id, Arc::clone(&solver));
+            .insert(job_id.clone(), Arc::clone(&solver));
 
-        tokio::spawn(async move {
-            let _ = solver.solve();
-        });
+        self.spawn_search(job_id, solver, search);
+
+        Ok(())
     }
 
     pub async fn list_solvers(&self) -> Vec<(String, Arc<Solver>)> {
@@ -32,28 +272,207 @@ impl SolverManager {
             .collect()
     }
 
-    pub async fn create_job(&self, problem: VehicleRoutingProblem) -> String {
-        let job_id = problem.id().to_owned();
-        let solver_params = SolverParams::default_from_problem(&problem);
-        let solver = Arc::new(Solver::new(problem, solver_params));
-        self.solvers.write().await.insert(job_id.clone(), solver);
+    /// Creates a job that will run within `span` each time it is started or resumed
+    pub async fn create_job(
+        &self,
+        problem: Arc<VehicleRoutingProblem>,
+        initial_solution: Option<WorkingSolution>,
+        span: Span,
+    ) -> String {
+        self.insert_job(Self::job_solver(problem, initial_solution, span))
+            .await
+    }
+
+    /// Solver of a job as created by `create_job`, e.g. to restore it before adding it with
+    /// `insert_job`
+    pub fn job_solver(
+        problem: Arc<VehicleRoutingProblem>,
+        initial_solution: Option<WorkingSolution>,
+        span: Span,
+    ) -> Solver {
+        let solver_params = SolverParams {
+            retry_on_failure: true,
+            ..SolverParams::default_from_problem(&problem)
+        };
+        let solver = Solver::new(problem, solver_params)
+            .expect("The default parameters have terminations and no custom strategies")
+            .with_span(span);
+
+        if let Some(initial_solution) = initial_solution {
+            solver.set_initial_solution(initial_solution);
+        }
+
+        solver
+    }
+
+    /// Adds the job of `solver` under the ID of its problem. The job it replaces is stopped, its
+    /// search would otherwise keep running without being reachable
+    pub async fn insert_job(&self, solver: Solver) -> String {
+        let job_id = solver.problem().id().to_owned();
+        let replaced = self
+            .solvers
+            .write()
+            .await
+            .insert(job_id.clone(), Arc::new(solver));
+
+        if let Some(replaced) = replaced {
+            replaced.stop();
+        }
+
         job_id
     }
 
-    pub async fn start(&self, job_id: &str) -> bool {
+    /// Solves the problems side by side until `terminations` and returns their best solutions in
+    /// the same order.
+    ///
+    /// The batch counts as one running job of the owner of `batch_id` set with `set_owner`, the
+    /// owner is forgotten once the batch is done. The solvers are listed with the other jobs
+    /// while they run so that they can be stopped, they are removed once the batch is done or
+    /// dropped.
+    pub async fn solve_batch(
+        &self,
+        batch_id: &str,
+        problems: Vec<Arc<VehicleRoutingProblem>>,
+        terminations: Vec<Termination>,
+    ) -> Result<Vec<Option<AcceptedSolution>>, SolverManagerError> {
+        let mut registration = BatchRegistration {
+            solvers: Arc::clone(&self.solvers),
+            ownership: Arc::clone(&self.ownership),
+            batch_id: batch_id.to_owned(),
+            batch: vec![],
+        };
+        let _search = self.reserve_search(batch_id)?;
+
+        self.run_batch(&mut registration, problems, terminations)
+            .await
+    }
+
+    async fn run_batch(
+        &self,
+        registration: &mut BatchRegistration,
+        problems: Vec<Arc<VehicleRoutingProblem>>,
+        terminations: Vec<Termination>,
+    ) -> Result<Vec<Option<AcceptedSolution>>, SolverManagerError> {
+        let solvers = problems
+            .into_iter()
+            .map(|problem| {
+                let solver_params = SolverParams {
+                    terminations: terminations.clone(),
+                    ..SolverParams::default_from_problem(&problem)
+                };
+                Solver::new(problem, solver_params).map(Arc::new)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        {
+            let mut registered = self.solvers.write().await;
+            for solver in &solvers {
+                registered.insert(solver.problem().id().to_owned(), Arc::clone(solver));
+            }
+        }
+        registration.batch.clone_from(&solvers);
+
+        let handles = solvers.iter().map(|solver| {
+            let solver = Arc::clone(solver);
+            let failures = Arc::clone(&self.failures);
+            tokio::task::spawn_blocking(move || {
+                // Stopped before its turn came, the batch was dropped
+                if matches!(solver.status(), SolverStatus::Pending) && solver.solve().is_err() {
+                    failures.failed.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+        });
+        futures::future::join_all(handles).await;
+
+        Ok(solvers
+            .iter()
+            .map(|solver| solver.current_best_solution())
+            .collect())
+    }
+
+    pub async fn start(&self, job_id: &str) -> Result<(), SolverManagerError> {
+        let solver = self
+            .solver(job_id)
+            .await
+            .ok_or_else(|| SolverManagerError::JobNotFound(job_id.to_owned()))?;
+        let search = self.reserve_search(job_id)?;
+
+        self.spawn_search(job_id.to_owned(), solver, search);
+
+        Ok(())
+    }
+
+    pub async fn stop(&self, job_id: &str) -> bool {
         if let Some(solver) = self.solvers.read().await.get(job_id).cloned() {
-            std::thread::spawn(move || {
-                let _ = solver.solve();
-            });
+            solver.stop();
             true
         } else {
             false
         }
     }
 
-    pub async fn stop(&self, job_id: &str) -> bool {
+    pub async fn pause(&self, job_id: &str) -> Option<bool> {
+        let solver = self.solvers.read().await.get(job_id).cloned()?;
+        Some(solver.pause())
+    }
+
+    /// Continues a paused job from its checkpoint on a new search thread, false when the job is
+    /// not paused
+    pub async fn resume(&self, job_id: &str) -> Result<bool, SolverManagerError> {
+        let solver = self
+            .solver(job_id)
+            .await
+            .ok_or_else(|| SolverManagerError::JobNotFound(job_id.to_owned()))?;
+        let search = self.reserve_search(job_id)?;
+
+        if !solver.resume() {
+            return Ok(false);
+        }
+
+        self.spawn_search(job_id.to_owned(), solver, search);
+
+        Ok(true)
+    }
+
+    /// Pauses every running job, e.g. before the process exits, and returns their IDs. Their
+    /// checkpoints are taken once their search threads have stopped, see `running_searches`
+    pub async fn pause_all(&self) -> Vec<String> {
+        self.solvers
+            .read()
+            .await
+            .iter()
+            .filter(|(_, solver)| solver.pause())
+            .map(|(job_id, _)| job_id.clone())
+            .collect()
+    }
+
+    /// Jobs with a search thread started by `start` or `resume` which has not returned yet
+    pub fn running_searches(&self) -> usize {
+        self.ownership.lock().searches.len()
+    }
+
+    /// Whether a search thread of the job has not returned yet, e.g. the one of a solver replaced
+    /// by `insert_job` which was asked to stop
+    pub fn is_searching(&self, job_id: &str) -> bool {
+        self.ownership.lock().searches.contains_key(job_id)
+    }
+
+    pub async fn cancel(&self, job_id: &str) -> bool {
         if let Some(solver) = self.solvers.read().await.get(job_id).cloned() {
-            solver.stop();
+            solver.cancel();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Cancels the job and forgets it with its owner, false when the job does not exist
+    pub async fn remove(&self, job_id: &str) -> bool {
+        let removed = self.solvers.write().await.remove(job_id);
+        self.ownership.lock().owners.remove(job_id);
+
+        if let Some(solver) = removed {
+            solver.cancel();
             true
         } else {
             false
@@ -64,3 +483,146 @@ impl SolverManager {
         self.solvers.read().await.get(job_id).cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use super::*;
+    use crate::test_utils;
+
+    fn owner(max_running_jobs: Option<usize>) -> JobOwner {
+        JobOwner {
+            name: String::from("team"),
+            max_running_jobs,
+        }
+    }
+
+    #[test]
+    fn test_reserve_search_limits_owner() {
+        let solver_manager = SolverManager::default();
+        for job_id in ["job1", "job2", "job3"] {
+            solver_manager.set_owner(job_id, owner(Some(2)));
+        }
+
+        let search1 = solver_manager.reserve_search("job1").unwrap();
+        let _search2 = solver_manager.reserve_search("job2").unwrap();
+        assert!(matches!(
+            solver_manager.reserve_search("job3"),
+            Err(SolverManagerError::TooManyRunningJobs {
+                max_running_jobs: 2,
+                ..
+            })
+        ));
+
+        drop(search1);
+        assert!(!solver_manager.is_searching("job1"));
+        assert!(solver_manager.reserve_search("job3").is_ok());
+
+        // Jobs without an owner are not limited
+        assert!(solver_manager.reserve_search("job4").is_ok());
+    }
+
+    #[test]
+    fn test_reserve_search_counts_every_search_of_a_job() {
+        let solver_manager = SolverManager::default();
+        solver_manager.set_owner("job", owner(Some(1)));
+
+        let search = solver_manager.reserve_search("job").unwrap();
+        assert!(solver_manager.reserve_search("job").is_err());
+
+        drop(search);
+        assert!(solver_manager.reserve_search("job").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_solve_batch_limits_owner() {
+        let solver_manager = SolverManager::default();
+        solver_manager.set_owner("job", owner(Some(1)));
+        solver_manager.set_owner("batch", owner(Some(1)));
+
+        let search = solver_manager.reserve_search("job").unwrap();
+        assert!(
+            solver_manager
+                .solve_batch("batch", vec![], vec![])
+                .await
+                .is_err()
+        );
+
+        drop(search);
+        solver_manager.set_owner("batch", owner(Some(1)));
+        assert!(
+            solver_manager
+                .solve_batch("batch", vec![], vec![])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!solver_manager.ownership.lock().owners.contains_key("batch"));
+    }
+
+    #[cfg(feature = "chaos")]
+    #[tokio::test]
+    async fn test_failed_search_of_removed_job_is_not_retried() {
+        use crate::solver::chaos::ChaosParams;
+
+        let solver_manager = SolverManager::default();
+        let problem = test_utils::create_test_problem(
+            test_utils::create_location_grid(3, 3),
+            test_utils::create_basic_services(vec![1, 2, 3, 4, 5]),
+            test_utils::create_basic_vehicles(vec![0]),
+        );
+        let solver = Solver::new(
+            problem,
+            SolverParams {
+                terminations: vec![Termination::Iterations(1000)],
+                retry_on_failure: true,
+                chaos: ChaosParams {
+                    panic_probability: 1.0,
+                    ..ChaosParams::default()
+                },
+                ..SolverParams::default()
+            },
+        )
+        .unwrap();
+
+        let job_id = solver_manager.insert_job(solver).await;
+        let solver = solver_manager.solver(&job_id).await.unwrap();
+        let search = solver_manager.reserve_search(&job_id).unwrap();
+        assert!(solver_manager.remove(&job_id).await);
+
+        solver_manager.spawn_search(job_id.clone(), solver, search);
+        while solver_manager.is_searching(&job_id) {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        assert!(solver_manager.solver(&job_id).await.is_none());
+        assert_eq!(solver_manager.failure_metrics().retried, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_batch_removes_solvers() {
+        let solver_manager = SolverManager::default();
+        solver_manager.set_owner("batch", owner(Some(1)));
+        let problem = Arc::new(test_utils::create_test_problem(
+            test_utils::create_location_grid(3, 3),
+            test_utils::create_basic_services(vec![1, 2, 3, 4, 5]),
+            test_utils::create_basic_vehicles(vec![0]),
+        ));
+
+        let batch = solver_manager.solve_batch(
+            "batch",
+            vec![problem],
+            vec![Termination::Duration(SignedDuration::from_secs(60))],
+        );
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(200), batch)
+                .await
+                .is_err()
+        );
+
+        assert!(solver_manager.list_solvers().await.is_empty());
+        assert!(!solver_manager.is_searching("batch"));
+        assert!(!solver_manager.ownership.lock().owners.contains_key("batch"));
+    }
+}
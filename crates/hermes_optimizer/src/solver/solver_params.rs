@@ -1,23 +1,49 @@
+use std::path::PathBuf;
+
 use jiff::SignedDuration;
+use thiserror::Error;
 
 use crate::{
-    problem::vehicle_routing_problem::VehicleRoutingProblem,
-    solver::{recreate::recreate_strategy::RecreateStrategy, ruin::ruin_strategy::RuinStrategy},
+    problem::vehicle_routing_problem::{DEFAULT_NEIGHBORHOOD_SIZE, VehicleRoutingProblem},
+    solver::{
+        constraints::constraint_set::ConstraintSet, recreate::recreate_strategy::RecreateStrategy,
+        ruin::ruin_strategy::RuinStrategy,
+    },
 };
 
 use super::{
-    recreate::recreate_params::RecreateParams, ruin::ruin_params::RuinParams, score::Score,
+    objective::Objective, recreate::recreate_params::RecreateParams, ruin::ruin_params::RuinParams,
+    score::Score, statistics_sink::StatisticsSinkParams,
 };
 
+#[derive(Error, Debug, PartialEq)]
+pub enum SolverParamsError {
+    #[error("At least one termination condition must be specified in the solver parameters")]
+    MissingTermination,
+
+    #[error("Custom ruin strategy {0} is not registered")]
+    UnregisteredRuinStrategy(&'static str),
+
+    #[error("Custom recreate strategy {0} is not registered")]
+    UnregisteredRecreateStrategy(&'static str),
+}
+
 #[derive(Clone, Debug)]
 pub struct SolverParamsDebugOptions {
     pub enable_local_search: bool,
+    /// Runs `verify_solution` on every solution improved by the local search and logs the
+    /// violations, slow
+    pub verify_after_local_search: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct PopulationParams {
     pub size: usize,
     pub elite_size: usize,
+
+    /// Solutions with the worst fitness are evicted while the estimated size of the population is
+    /// above this limit, the best solution is always kept
+    pub max_memory_bytes: Option<usize>,
 }
 
 impl PopulationParams {
@@ -31,10 +57,18 @@ impl Default for PopulationParams {
         Self {
             size: 10,
             elite_size: 3,
+            max_memory_bytes: None,
         }
     }
 }
 
+/// Periodically writes the state of the search to `path` so that an interrupted run can be resumed
+#[derive(Clone, Debug)]
+pub struct CheckpointParams {
+    pub path: PathBuf,
+    pub interval: SignedDuration,
+}
+
 #[derive(Clone, Debug)]
 pub struct SolverParams {
     pub terminations: Vec<Termination>,
@@ -43,14 +77,28 @@ pub struct SolverParams {
 
     pub population: PopulationParams,
 
+    pub constraints: ConstraintSet,
+
+    /// How the soft scores of the constraints rank the solutions
+    pub objective: Objective,
+
     pub ruin: RuinParams,
     pub recreate: RecreateParams,
 
+    /// Maximum number of insertion threads of each search thread, small problems use a single one
     pub insertion_threads: Threads,
     pub search_threads: Threads,
 
     pub threads_sync_iterations_interval: usize,
 
+    /// Maximum number of route pairs cached by the local search of each thread, the oldest routes are
+    /// evicted first
+    pub local_search_cache_size: usize,
+
+    /// Number of closest activities around a job where it can be inserted, relocated or swapped,
+    /// at most `MAX_NEIGHBORHOOD_SIZE`, see `WorkingSolution::neighborhood_size`
+    pub neighborhood_size: usize,
+
     pub noise_probability: f64,
     pub noise_level: f64,
 
@@ -64,6 +112,26 @@ pub struct SolverParams {
     pub intensify_probability: f64,
     pub run_intensify_search: bool,
     pub debug_options: SolverParamsDebugOptions,
+
+    pub checkpoint: Option<CheckpointParams>,
+
+    /// Streams the statistics of each iteration to a file, requires the `statistics` feature
+    pub statistics_sink: Option<StatisticsSinkParams>,
+
+    /// Seed of the random number generator, each search thread derives its own seed from it
+    pub seed: u64,
+
+    /// Search threads keep their own population, merged in thread order at each sync barrier so that
+    /// the same problem, seed and number of threads always give the same solution.
+    /// Only runs stopped by `Termination::Iterations` are reproducible.
+    pub deterministic: bool,
+
+    /// Runs a failed search once more on a single thread in deterministic mode, see
+    /// `Solver::retry`
+    pub retry_on_failure: bool,
+
+    #[cfg(feature = "chaos")]
+    pub chaos: super::chaos::ChaosParams,
 }
 
 #[derive(Clone, Debug)]
@@ -72,7 +140,16 @@ pub enum Termination {
     Iterations(usize),
     IterationsWithoutImprovement(usize),
     Score(Score),
-    VehiclesAndCosts { vehicles: usize, costs: f64 },
+    VehiclesAndCosts {
+        vehicles: usize,
+        costs: f64,
+    },
+    /// Stops when the best score improved by less than `min_improvement` (a fraction of the
+    /// score, e.g. 0.001 for 0.1%) over the last `window`
+    Stagnation {
+        window: SignedDuration,
+        min_improvement: f64,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -118,6 +195,8 @@ impl Default for SolverParams {
             ],
 
             population: PopulationParams::default(),
+            constraints: ConstraintSet::default(),
+            objective: Objective::default(),
 
             solver_acceptor: SolverAcceptorStrategy::Schrimpf,
             solver_selector: SolverSelectorStrategy::SelectWeighted,
@@ -131,6 +210,8 @@ impl Default for SolverParams {
             alns_iterations_without_improvement_reset: 4000,
             alns_segment_iterations: 50,
             threads_sync_iterations_interval: 250,
+            local_search_cache_size: 100_000,
+            neighborhood_size: DEFAULT_NEIGHBORHOOD_SIZE,
             alns_reaction_factor: 0.3,
             alns_best_factor: 33.0,
             alns_improvement_factor: 9.0,
@@ -142,7 +223,18 @@ impl Default for SolverParams {
 
             debug_options: SolverParamsDebugOptions {
                 enable_local_search: true,
+                verify_after_local_search: false,
             },
+
+            checkpoint: None,
+            statistics_sink: None,
+
+            seed: 2427121,
+            deterministic: false,
+            retry_on_failure: false,
+
+            #[cfg(feature = "chaos")]
+            chaos: super::chaos::ChaosParams::default(),
         }
     }
 }
@@ -150,6 +242,18 @@ impl Default for SolverParams {
 impl SolverParams {
     pub fn default_from_problem(problem: &VehicleRoutingProblem) -> Self {
         Self {
+            // Scanning every candidate position is too slow on large instances
+            neighborhood_size: if problem.jobs().len() >= 1000 {
+                25
+            } else {
+                DEFAULT_NEIGHBORHOOD_SIZE
+            },
+            constraints: {
+                let mut constraints = ConstraintSet::default();
+                constraints.apply_overrides(problem.constraint_overrides());
+                constraints
+            },
+            ruin: RuinParams::default_from_problem(problem),
             recreate: RecreateParams::default_from_problem(problem),
             ..Self::default()
         }
@@ -162,4 +266,30 @@ impl SolverParams {
     pub fn recreate_strategies(&self) -> &Vec<RecreateStrategy> {
         &self.recreate.recreate_strategies
     }
+
+    /// Checks the search can run, the custom strategies must be registered with
+    /// `RuinParams::register_custom` and `RecreateParams::register_custom`
+    pub fn validate(&self) -> Result<(), SolverParamsError> {
+        if self.terminations.is_empty() {
+            return Err(SolverParamsError::MissingTermination);
+        }
+
+        for strategy in self.ruin_strategies() {
+            if let RuinStrategy::Custom(name) = *strategy
+                && self.ruin.custom_strategies.get(name).is_none()
+            {
+                return Err(SolverParamsError::UnregisteredRuinStrategy(name));
+            }
+        }
+
+        for strategy in self.recreate_strategies() {
+            if let RecreateStrategy::Custom(name) = *strategy
+                && self.recreate.custom_strategies.get(name).is_none()
+            {
+                return Err(SolverParamsError::UnregisteredRecreateStrategy(name));
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -2,33 +2,68 @@ use std::sync::Arc;
 
 use fxhash::FxHashMap;
 use jiff::{SignedDuration, Timestamp};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use schemars::JsonSchema;
 use serde::Serialize;
 use serde_with::{DisplayFromStr, serde_as};
+use tracing::warn;
 
 use super::{
+    alns_weights::AlnsWeights,
+    constraint_statistics::{BindingConstraint, ConstraintStatistics},
+    constraints::constraint::Constraint,
     recreate::recreate_strategy::RecreateStrategy,
     ruin::ruin_strategy::RuinStrategy,
     score::{Score, ScoreAnalysis},
+    statistics_sink::StatisticsSink,
 };
 
 #[derive(Serialize)]
 pub struct SearchStatistics {
     pub global_statistics: Arc<RwLock<GlobalStatistics>>,
     pub thread_statistics: Vec<Arc<RwLock<ThreadSearchStatistics>>>,
+    #[serde(skip_serializing)]
+    sink: Option<Arc<Mutex<StatisticsSink>>>,
+    #[serde(skip_serializing)]
+    constraint_statistics: ConstraintStatistics,
 }
 
 impl SearchStatistics {
-    pub fn new(number_of_threads: usize) -> Self {
+    pub fn new(
+        number_of_threads: usize,
+        constraints: &[Constraint],
+        sink: Option<StatisticsSink>,
+    ) -> Self {
+        let sink = sink.map(|sink| Arc::new(Mutex::new(sink)));
+
         Self {
             global_statistics: Arc::new(RwLock::new(GlobalStatistics::default())),
             thread_statistics: {
                 let mut v = Vec::with_capacity(number_of_threads);
-                (0..number_of_threads)
-                    .for_each(|_| v.push(Arc::new(RwLock::new(ThreadSearchStatistics::default()))));
+                (0..number_of_threads).for_each(|thread| {
+                    v.push(Arc::new(RwLock::new(ThreadSearchStatistics {
+                        thread,
+                        sink: sink.clone(),
+                        ..ThreadSearchStatistics::default()
+                    })))
+                });
                 v
             },
+            sink,
+            constraint_statistics: ConstraintStatistics::new(constraints),
+        }
+    }
+
+    pub fn constraint_statistics(&self) -> &ConstraintStatistics {
+        &self.constraint_statistics
+    }
+
+    /// Writes the iterations still buffered by the sink, called at the end of each run
+    pub fn flush_sink(&self) {
+        if let Some(sink) = &self.sink
+            && let Err(error) = sink.lock().flush()
+        {
+            warn!("Failed to flush the search statistics: {error}");
         }
     }
 
@@ -68,8 +103,19 @@ impl SearchStatistics {
             }
         }
 
+        aggregated_statistics.binding_constraints =
+            self.constraint_statistics.binding_constraints();
+
         aggregated_statistics
     }
+
+    pub fn telemetry(&self) -> Vec<ThreadTelemetry> {
+        self.thread_statistics
+            .iter()
+            .enumerate()
+            .map(|(thread, thread_stats)| thread_stats.read().telemetry(thread))
+            .collect()
+    }
 }
 
 #[derive(Serialize)]
@@ -114,12 +160,61 @@ pub enum SearchStatisticsIteration {
 #[serde_as]
 #[derive(Default, Serialize)]
 pub struct ThreadSearchStatistics {
+    #[serde(skip_serializing)]
+    thread: usize,
+    /// Only filled when there is no sink, a long search would otherwise keep millions of iterations
     #[serde(skip_serializing)]
     iterations: Vec<SearchStatisticsIteration>,
+    #[serde(skip_serializing)]
+    sink: Option<Arc<Mutex<StatisticsSink>>>,
     aggregated_statistics: AggregatedStatistics,
+
+    #[serde(skip_serializing)]
+    accepted_iterations: usize,
+    #[serde(skip_serializing)]
+    rejected_iterations: usize,
+
+    /// Last operator weights of the thread, None until the first weights update
+    #[serde(skip_serializing)]
+    ruin_weights: Option<AlnsWeights<RuinStrategy>>,
+    #[serde(skip_serializing)]
+    recreate_weights: Option<AlnsWeights<RecreateStrategy>>,
 }
 
 impl ThreadSearchStatistics {
+    pub fn record_acceptance(&mut self, accepted: bool) {
+        if accepted {
+            self.accepted_iterations += 1;
+        } else {
+            self.rejected_iterations += 1;
+        }
+    }
+
+    pub fn record_weights(
+        &mut self,
+        ruin_weights: &AlnsWeights<RuinStrategy>,
+        recreate_weights: &AlnsWeights<RecreateStrategy>,
+    ) {
+        self.ruin_weights = Some(ruin_weights.clone());
+        self.recreate_weights = Some(recreate_weights.clone());
+    }
+
+    pub fn telemetry(&self, thread: usize) -> ThreadTelemetry {
+        let iterations = self.accepted_iterations + self.rejected_iterations;
+
+        ThreadTelemetry {
+            thread,
+            iterations,
+            acceptance_rate: if iterations > 0 {
+                self.accepted_iterations as f64 / iterations as f64
+            } else {
+                0.0
+            },
+            ruin_weights: self.ruin_weights.clone(),
+            recreate_weights: self.recreate_weights.clone(),
+        }
+    }
+
     pub fn add_iteration_info(&mut self, iteration: SearchStatisticsIteration) {
         if let SearchStatisticsIteration::RuinRecreate {
             ruin_strategy,
@@ -141,7 +236,14 @@ impl ThreadSearchStatistics {
             Self::update_aggregated_statistics(ruin_statistics, recreate_statistics, &iteration);
         }
 
-        self.iterations.push(iteration);
+        match &self.sink {
+            Some(sink) => {
+                if let Err(error) = sink.lock().write(self.thread, &iteration) {
+                    warn!("Failed to write the search statistics: {error}");
+                }
+            }
+            None => self.iterations.push(iteration),
+        }
     }
 
     fn update_aggregated_statistics(
@@ -206,6 +308,24 @@ impl ThreadSearchStatistics {
     }
 }
 
+/// Snapshot of the search state of a thread, streamed to live dashboards
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct ThreadTelemetry {
+    pub thread: usize,
+    pub iterations: usize,
+    pub acceptance_rate: f64,
+    pub ruin_weights: Option<AlnsWeights<RuinStrategy>>,
+    pub recreate_weights: Option<AlnsWeights<RecreateStrategy>>,
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct SearchTelemetry {
+    pub threads: Vec<ThreadTelemetry>,
+
+    /// Scores of the solutions currently in the population, best first
+    pub pool_scores: Vec<Score>,
+}
+
 #[serde_as]
 #[derive(Serialize, Default, JsonSchema)]
 pub struct AggregatedStatistics {
@@ -213,6 +333,9 @@ pub struct AggregatedStatistics {
     aggregated_ruin_statistics: FxHashMap<RuinStrategy, AggregatedOperatorStatistics>,
     #[serde_as(as = "FxHashMap<DisplayFromStr, _>")]
     aggregated_recreate_statistics: FxHashMap<RecreateStrategy, AggregatedOperatorStatistics>,
+
+    /// Constraints driving the cost of the solutions, filled by `SearchStatistics::aggregate`
+    binding_constraints: Vec<BindingConstraint>,
 }
 
 #[derive(Serialize, Default, JsonSchema)]
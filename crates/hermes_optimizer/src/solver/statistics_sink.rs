@@ -0,0 +1,181 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use serde::Serialize;
+
+use super::statistics::SearchStatisticsIteration;
+
+const CSV_HEADER: &str = "thread,kind,timestamp,ruin_strategy,recreate_strategy,improved,is_best,score_before_hard,score_before_soft,score_after_hard,score_after_soft,ruin_duration_us,recreate_duration_us";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatisticsSinkFormat {
+    Csv,
+    JsonLines,
+}
+
+/// Writes the statistics of each iteration to `path` as the search runs instead of keeping them in
+/// memory
+#[derive(Clone, Debug)]
+pub struct StatisticsSinkParams {
+    pub path: PathBuf,
+    pub format: StatisticsSinkFormat,
+}
+
+/// Shared by the search threads, rows are buffered and flushed to the file when the buffer is full
+pub struct StatisticsSink {
+    format: StatisticsSinkFormat,
+    writer: BufWriter<File>,
+}
+
+impl StatisticsSink {
+    pub fn create(params: &StatisticsSinkParams) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(&params.path)?);
+
+        if params.format == StatisticsSinkFormat::Csv {
+            writeln!(writer, "{CSV_HEADER}")?;
+        }
+
+        Ok(StatisticsSink {
+            format: params.format,
+            writer,
+        })
+    }
+
+    pub fn write(
+        &mut self,
+        thread: usize,
+        iteration: &SearchStatisticsIteration,
+    ) -> io::Result<()> {
+        write_row(&mut self.writer, self.format, thread, iteration)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLinesRow<'a> {
+    thread: usize,
+    iteration: &'a SearchStatisticsIteration,
+}
+
+fn write_row(
+    writer: &mut impl Write,
+    format: StatisticsSinkFormat,
+    thread: usize,
+    iteration: &SearchStatisticsIteration,
+) -> io::Result<()> {
+    match format {
+        StatisticsSinkFormat::Csv => write_csv_row(writer, thread, iteration),
+        StatisticsSinkFormat::JsonLines => {
+            serde_json::to_writer(&mut *writer, &JsonLinesRow { thread, iteration })?;
+            writeln!(writer)
+        }
+    }
+}
+
+fn write_csv_row(
+    writer: &mut impl Write,
+    thread: usize,
+    iteration: &SearchStatisticsIteration,
+) -> io::Result<()> {
+    match iteration {
+        SearchStatisticsIteration::RuinRecreate {
+            timestamp,
+            ruin_strategy,
+            recreate_strategy,
+            improved,
+            is_best,
+            score_before,
+            score_after,
+            ruin_duration,
+            recreate_duration,
+        } => writeln!(
+            writer,
+            "{thread},ruin_recreate,{timestamp},{ruin_strategy},{recreate_strategy},{improved},{is_best},{},{},{},{},{},{}",
+            score_before.hard_score,
+            score_before.soft_score,
+            score_after.hard_score,
+            score_after.soft_score,
+            ruin_duration.as_micros(),
+            recreate_duration.as_micros(),
+        ),
+        SearchStatisticsIteration::Intensify {
+            timestamp,
+            improved,
+            is_best,
+        } => writeln!(
+            writer,
+            "{thread},intensify,{timestamp},,,{improved},{is_best},,,,,,"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::Timestamp;
+
+    use crate::solver::statistics::SearchStatisticsIteration;
+
+    use super::{CSV_HEADER, StatisticsSinkFormat, write_row};
+
+    fn intensify_iteration() -> SearchStatisticsIteration {
+        SearchStatisticsIteration::Intensify {
+            timestamp: Timestamp::UNIX_EPOCH,
+            improved: true,
+            is_best: false,
+        }
+    }
+
+    #[test]
+    fn test_csv_row_has_header_columns() {
+        let mut buffer = Vec::new();
+        write_row(
+            &mut buffer,
+            StatisticsSinkFormat::Csv,
+            3,
+            &intensify_iteration(),
+        )
+        .unwrap();
+
+        let row = String::from_utf8(buffer).unwrap();
+        assert!(row.starts_with("3,intensify,1970-01-01T00:00:00Z,,,true,false"));
+        assert_eq!(
+            row.trim_end().split(',').count(),
+            CSV_HEADER.split(',').count()
+        );
+    }
+
+    #[test]
+    fn test_json_lines_row() {
+        let mut buffer = Vec::new();
+        write_row(
+            &mut buffer,
+            StatisticsSinkFormat::JsonLines,
+            0,
+            &intensify_iteration(),
+        )
+        .unwrap();
+        write_row(
+            &mut buffer,
+            StatisticsSinkFormat::JsonLines,
+            1,
+            &intensify_iteration(),
+        )
+        .unwrap();
+
+        let content = String::from_utf8(buffer).unwrap();
+        let rows: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["thread"], 1);
+        assert_eq!(rows[0]["iteration"]["Intensify"]["improved"], true);
+    }
+}
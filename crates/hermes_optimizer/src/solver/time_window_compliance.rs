@@ -0,0 +1,159 @@
+use jiff::SignedDuration;
+
+use crate::solver::solution::working_solution::WorkingSolution;
+
+/// Stops served with less margin before the end of their window are near violations, a small
+/// delay on the road is enough to miss them
+pub const NEAR_VIOLATION_MARGIN: SignedDuration = SignedDuration::from_mins(5);
+
+/// Distribution of the margins between the arrival at the stops and the end of the window they are
+/// served in, to judge how much delay a plan can absorb before it is dispatched.
+///
+/// Only the stops with a window end are counted, the margin of a late stop is negative
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeWindowCompliance {
+    pub stops: usize,
+    pub min_margin: SignedDuration,
+    pub p10_margin: SignedDuration,
+    pub p25_margin: SignedDuration,
+    pub median_margin: SignedDuration,
+    pub p90_margin: SignedDuration,
+    /// Stops within their window with less than `NEAR_VIOLATION_MARGIN` left
+    pub near_violations: usize,
+    /// Stops arriving after the end of all their windows, tolerance included
+    pub violations: usize,
+}
+
+/// Nearest rank percentile of the sorted `margins`
+fn percentile(margins: &[SignedDuration], percentile: f64) -> SignedDuration {
+    let rank = (percentile * margins.len() as f64).ceil() as usize;
+    margins[rank.clamp(1, margins.len()) - 1]
+}
+
+/// Margins of the stops at the optimized departure times of the routes, `None` when no stop has a
+/// window end
+pub fn compute_time_window_compliance(solution: &WorkingSolution) -> Option<TimeWindowCompliance> {
+    let problem = solution.problem();
+
+    let mut margins = vec![];
+    let mut near_violations = 0;
+    let mut violations = 0;
+
+    for route in solution.non_empty_routes_iter() {
+        for activity in route.optimized_activities_iter() {
+            let job_activity = problem.job_activity(activity.activity_id());
+            let time_windows = job_activity.time_windows();
+            let arrival_time = activity.arrival_time();
+
+            let end = match time_windows.served_window_index(arrival_time) {
+                Some(index) => time_windows.iter().nth(index).and_then(|tw| tw.latest()),
+                None => time_windows.end(),
+            };
+            let Some(end) = end else {
+                continue;
+            };
+
+            let margin = end.duration_since(arrival_time);
+            if !time_windows.is_satisfied(arrival_time) {
+                violations += 1;
+            } else if margin < NEAR_VIOLATION_MARGIN {
+                near_violations += 1;
+            }
+
+            margins.push(margin);
+        }
+    }
+
+    if margins.is_empty() {
+        return None;
+    }
+
+    margins.sort();
+
+    Some(TimeWindowCompliance {
+        stops: margins.len(),
+        min_margin: margins[0],
+        p10_margin: percentile(&margins, 0.1),
+        p25_margin: percentile(&margins, 0.25),
+        median_margin: percentile(&margins, 0.5),
+        p90_margin: percentile(&margins, 0.9),
+        near_violations,
+        violations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use jiff::SignedDuration;
+
+    use crate::{
+        problem::time_window::TimeWindow,
+        test_utils::{
+            TestProblemOptions, TestRoute, TestService, create_problem_for_tw_change,
+            create_test_working_solution,
+        },
+        timestamp,
+    };
+
+    use super::{TimeWindowCompliance, compute_time_window_compliance};
+
+    #[test]
+    fn test_compute_time_window_compliance() {
+        let window_end =
+            |end: &str| TestService::with_time_window(TimeWindow::from_iso(None, Some(end)));
+        let problem = Arc::new(create_problem_for_tw_change(
+            vec![
+                window_end("2025-11-30T08:00:00+02:00"),
+                window_end("2025-11-30T08:12:00+02:00"),
+                window_end("2025-11-30T08:40:00+02:00"),
+                TestService::default(),
+            ],
+            TestProblemOptions {
+                earliest_start: Some(timestamp!("2025-11-30T07:00:00+02:00")),
+                ..TestProblemOptions::default()
+            },
+        ));
+
+        // Arrivals at 07:30, 08:10, 08:50 and 09:30, the last service has no window
+        let solution = create_test_working_solution(
+            problem,
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0, 1, 2, 3],
+            }],
+        );
+
+        assert_eq!(
+            compute_time_window_compliance(&solution),
+            Some(TimeWindowCompliance {
+                stops: 3,
+                min_margin: SignedDuration::from_mins(-10),
+                p10_margin: SignedDuration::from_mins(-10),
+                p25_margin: SignedDuration::from_mins(-10),
+                median_margin: SignedDuration::from_mins(2),
+                p90_margin: SignedDuration::from_mins(30),
+                near_violations: 1,
+                violations: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_compute_time_window_compliance_without_windows() {
+        let problem = Arc::new(create_problem_for_tw_change(
+            vec![TestService::default()],
+            TestProblemOptions::default(),
+        ));
+        let solution = create_test_working_solution(
+            problem,
+            vec![TestRoute {
+                vehicle_id: 0,
+                service_ids: vec![0],
+            }],
+        );
+
+        assert_eq!(compute_time_window_compliance(&solution), None);
+    }
+}
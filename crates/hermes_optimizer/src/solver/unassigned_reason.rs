@@ -0,0 +1,193 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::problem::job::JobIdx;
+
+use super::{
+    constraints::{
+        compute_insertion_score::compute_insertion_score_with_rejections, constraint::Constraint,
+    },
+    insertion::for_each_insertion,
+    insertion_context::InsertionContext,
+    score::Score,
+    solution::working_solution::WorkingSolution,
+};
+
+/// Family of the hard constraint which kept a job out of the routes
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnassignedReasonCode {
+    /// Capacity of the vehicles, axle loads or stock of the depots
+    Capacity,
+    /// Time windows of the job or opening hours of the depots
+    TimeWindow,
+    /// Shift, working duration or trips of the vehicles
+    Shift,
+    /// Skills or tags required by the job, also when no vehicle can serve it at all
+    Skills,
+    /// Any other hard constraint
+    Other,
+    /// No insertion of the job was rejected by a hard constraint
+    Unknown,
+}
+
+impl UnassignedReasonCode {
+    pub fn from_constraint_name(constraint_name: &str) -> Self {
+        match constraint_name {
+            "capacity" | "axle_load" | "depot_inventory" => UnassignedReasonCode::Capacity,
+            "time_window" | "depot_hours" => UnassignedReasonCode::TimeWindow,
+            "shift" | "maximum_working_duration" | "maximum_trips" => UnassignedReasonCode::Shift,
+            "skill" | "tag" => UnassignedReasonCode::Skills,
+            _ => UnassignedReasonCode::Other,
+        }
+    }
+}
+
+/// Constraint which rejected the best insertion of an unassigned job in the final solution
+#[derive(Clone, Debug)]
+pub struct UnassignedReason {
+    pub job_id: JobIdx,
+    pub code: UnassignedReasonCode,
+    /// Name of the binding constraint, not set for `Unknown` or when no vehicle can serve the job
+    pub constraint: Option<&'static str>,
+    /// Insertions of the job in the final solution rejected by the binding constraint
+    pub rejected_insertions: usize,
+}
+
+/// Reason of each unassigned job of the solution, in the order of the jobs of the problem. Every
+/// insertion of a job is attempted once more in the solution, the binding constraint is the first
+/// hard constraint rejecting the best of them
+pub fn unassigned_reasons(
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+) -> Vec<UnassignedReason> {
+    solution
+        .ordered_unassigned_jobs()
+        .into_iter()
+        .map(|job_id| unassigned_reason(solution, constraints, job_id))
+        .collect()
+}
+
+fn unassigned_reason(
+    solution: &WorkingSolution,
+    constraints: &[Constraint],
+    job_id: JobIdx,
+) -> UnassignedReason {
+    let mut rejections = vec![0; constraints.len()];
+    let mut attempted = false;
+    let mut best_score = Score::MAX;
+    let mut best_rejections = Vec::new();
+
+    for_each_insertion(solution, job_id, |insertion| {
+        attempted = true;
+
+        // All the hard constraints are evaluated, not only the first one rejecting the insertion
+        let context = InsertionContext::new(solution.problem(), solution, &insertion, true);
+        let mut insertion_rejections = Vec::new();
+        let score = compute_insertion_score_with_rejections(constraints, &context, None, |index| {
+            rejections[index] += 1;
+            insertion_rejections.push(index);
+        });
+
+        if score < best_score {
+            best_score = score;
+            best_rejections = insertion_rejections;
+        }
+    });
+
+    if !attempted {
+        // No route's vehicle has the skills, tags or identity required by the job
+        return UnassignedReason {
+            job_id,
+            code: UnassignedReasonCode::Skills,
+            constraint: None,
+            rejected_insertions: 0,
+        };
+    }
+
+    match best_rejections.first() {
+        Some(&index) => {
+            let name = constraints[index].constraint_name();
+            UnassignedReason {
+                job_id,
+                code: UnassignedReasonCode::from_constraint_name(name),
+                constraint: Some(name),
+                rejected_insertions: rejections[index],
+            }
+        }
+        None => UnassignedReason {
+            job_id,
+            code: UnassignedReasonCode::Unknown,
+            constraint: None,
+            rejected_insertions: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        problem::{capacity::Capacity, service::ServiceBuilder, vehicle::VehicleBuilder},
+        solver::constraints::{
+            capacity_constraint::CapacityConstraint, global_constraint::GlobalConstraintType,
+            route_constraint::RouteConstraintType,
+            transport_cost_constraint::TransportCostConstraint,
+        },
+        test_utils,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_unassigned_reasons() {
+        let locations = test_utils::create_location_grid(1, 4);
+        let services = [
+            (vec![10.0], vec![]),
+            (vec![1.0], vec!["fridge"]),
+            (vec![1.0], vec![]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, (demand, skills))| {
+            let mut builder = ServiceBuilder::default();
+            builder.set_location_id(index + 1);
+            builder.set_external_id(index.to_string());
+            builder.set_demand(Capacity::from_vec(demand));
+            builder.set_skills(skills.into_iter().map(String::from).collect());
+            builder.build()
+        })
+        .collect();
+
+        let mut vehicle = VehicleBuilder::default();
+        vehicle.set_depot_location_id(0);
+        vehicle.set_vehicle_id("0".to_owned());
+        vehicle.set_profile_id(0);
+        vehicle.set_capacity(Capacity::from_vec(vec![5.0]));
+
+        let problem = test_utils::create_test_problem(locations, services, vec![vehicle.build()]);
+        let solution = WorkingSolution::new(Arc::new(problem));
+        let constraints = vec![
+            Constraint::Global(GlobalConstraintType::TransportCost(TransportCostConstraint)),
+            Constraint::Route(RouteConstraintType::Capacity(CapacityConstraint::default())),
+        ];
+
+        let reasons = unassigned_reasons(&solution, &constraints);
+        assert_eq!(reasons.len(), 3);
+
+        // The demand exceeds the capacity of the vehicle
+        assert_eq!(reasons[0].job_id, JobIdx::new(0));
+        assert_eq!(reasons[0].code, UnassignedReasonCode::Capacity);
+        assert_eq!(reasons[0].constraint, Some("capacity"));
+        assert_eq!(reasons[0].rejected_insertions, 1);
+
+        // No vehicle has the skill, no insertion is attempted
+        assert_eq!(reasons[1].code, UnassignedReasonCode::Skills);
+        assert_eq!(reasons[1].constraint, None);
+
+        // The job fits, nothing rejects it
+        assert_eq!(reasons[2].code, UnassignedReasonCode::Unknown);
+        assert_eq!(reasons[2].rejected_insertions, 0);
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
         service::{Service, ServiceBuilder},
         shipment::ShipmentBuilder,
         time_window::TimeWindow,
-        travel_cost_matrix::TravelMatrices,
+        travel_cost_matrix::{DistanceRounding, TravelMatrices},
         vehicle::{Vehicle, VehicleBuilder, VehicleShift},
         vehicle_profile::VehicleProfile,
         vehicle_routing_problem::{VehicleRoutingProblem, VehicleRoutingProblemBuilder},
@@ -151,7 +151,7 @@ pub fn create_test_problem(
     builder.set_distance_method(DistanceMethod::Euclidean);
     builder.set_vehicle_profiles(vec![VehicleProfile::new(
         "test_profile".to_owned(),
-        TravelMatrices::from_euclidean(&locations, true),
+        TravelMatrices::from_euclidean(&locations, DistanceRounding::Round),
     )]);
     builder.set_services(services);
     builder.set_locations(locations);
@@ -1,5 +1,6 @@
 use std::f64;
 
+use rstar::AABB;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize)]
@@ -20,6 +21,14 @@ impl BBox {
         self.max.y = self.max.y.max(coord.y);
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y
+    }
+
+    pub fn envelope(&self) -> AABB<[f64; 2]> {
+        AABB::from_corners([self.min.x, self.min.y], [self.max.x, self.max.y])
+    }
+
     pub fn intersects(&self, other: &BBox) -> bool {
         other.min.x <= self.max.x
             && other.min.y <= self.max.y
@@ -1,11 +1,23 @@
 use fixedbitset::{FixedBitSet, Ones};
 use fxhash::FxHashSet;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct BitSet {
     repr: fixedbitset::FixedBitSet,
 }
 
+impl Clone for BitSet {
+    fn clone(&self) -> Self {
+        BitSet {
+            repr: self.repr.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.repr.clone_from(&source.repr);
+    }
+}
+
 impl BitSet {
     pub fn with_capacity(capacity: usize) -> Self {
         BitSet {
@@ -39,6 +51,11 @@ impl BitSet {
         self.repr.contains(bit)
     }
 
+    /// Approximate number of bytes allocated for the bits
+    pub fn heap_size(&self) -> usize {
+        self.repr.len().div_ceil(8)
+    }
+
     pub fn is_all_zeroes(&self) -> bool {
         self.repr.is_clear()
     }
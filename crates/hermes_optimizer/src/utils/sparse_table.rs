@@ -1,12 +1,24 @@
-
 use crate::utils::bitset::BitSet;
 
-#[derive(Clone)]
 pub struct SparseTable {
     table: Vec<Vec<BitSet>>,
     len: usize,
 }
 
+impl Clone for SparseTable {
+    fn clone(&self) -> Self {
+        SparseTable {
+            table: self.table.clone(),
+            len: self.len,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.table.clone_from(&source.table);
+        self.len = source.len;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +205,17 @@ impl SparseTable {
         }
     }
 
+    /// Approximate number of bytes allocated for the table
+    pub fn heap_size(&self) -> usize {
+        self.table
+            .iter()
+            .map(|row| {
+                row.capacity() * std::mem::size_of::<BitSet>()
+                    + row.iter().map(BitSet::heap_size).sum::<usize>()
+            })
+            .sum()
+    }
+
     pub fn build(bitsets: Vec<BitSet>) -> Self {
         let n = bitsets.len();
         assert!(n > 0);
@@ -21,6 +21,22 @@ pub enum OsrmError {
     IncompleteResponse,
 }
 
+impl OsrmError {
+    /// Timeouts, connection failures and overloaded servers, the same request may succeed later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            OsrmError::Request(error) => {
+                error.is_timeout()
+                    || error.is_connect()
+                    || error.status().is_some_and(|status| {
+                        status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    })
+            }
+            _ => false,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct OsrmMatrices {
     /// Travel times in seconds
@@ -30,6 +46,7 @@ pub struct OsrmMatrices {
     pub distances: Vec<f64>,
 }
 
+#[derive(Clone)]
 pub struct OsrmClientParams {
     pub osrm_url: String,
 }
@@ -37,6 +54,7 @@ pub struct OsrmClientParams {
 const OSRM_TABLE_API_PATH: &str = "/table/v1/driving/";
 const OSRM_ROUTE_API_PATH: &str = "/route/v1/driving/";
 
+#[derive(Clone)]
 pub struct OsrmClient {
     params: OsrmClientParams,
     client: reqwest::Client,
@@ -58,11 +76,53 @@ impl OsrmClient {
             return Err(OsrmError::MaximumTableSizeExceeded(points.len()));
         }
 
+        let points = points
+            .iter()
+            .map(Into::into)
+            .collect::<Vec<geo_types::Point>>();
+        self.table_request(&points, &[]).await
+    }
+
+    /// Table from the `sources` to the `destinations`, which are indices of `points`, row by row.
+    /// Only the number of sources and of destinations is limited, not the number of points
+    pub async fn fetch_table(
+        &self,
+        points: &[geo_types::Point],
+        sources: &[usize],
+        destinations: &[usize],
+    ) -> Result<OsrmMatrices, OsrmError> {
+        let size = sources.len().max(destinations.len());
+        if size > OSRM_MAX_TABLE_SIZE {
+            return Err(OsrmError::MaximumTableSizeExceeded(size));
+        }
+
+        let indices = |indices: &[usize]| {
+            indices
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(";")
+        };
+
+        self.table_request(
+            points,
+            &[
+                ("sources", indices(sources)),
+                ("destinations", indices(destinations)),
+            ],
+        )
+        .await
+    }
+
+    async fn table_request(
+        &self,
+        points: &[geo_types::Point],
+        query: &[(&str, String)],
+    ) -> Result<OsrmMatrices, OsrmError> {
         let mut url = self.params.osrm_url.clone();
         url.push_str(OSRM_TABLE_API_PATH);
 
         for (i, point) in points.iter().enumerate() {
-            let point: geo_types::Point = point.into();
             url.push_str(&format!("{},{}", point.x(), point.y()));
 
             if i < points.len() - 1 {
@@ -79,6 +139,7 @@ impl OsrmClient {
                 ("annotations", "duration,distance"),
                 ("skip_waypoints", "true"),
             ])
+            .query(query)
             .send()
             .await?
             .error_for_status()
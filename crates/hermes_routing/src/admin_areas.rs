@@ -0,0 +1,359 @@
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use geo::{
+    BoundingRect, Contains, Coord, Intersects, LineString, MultiPolygon, Point, Polygon, Rect,
+};
+use osmpbfreader::{OsmId, OsmObj, OsmPbfReader};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    error::{ImportError, StorageError},
+    geopoint::GeoPoint,
+    stopwatch::Stopwatch,
+    storage::{FileKind, read_file, write_file},
+};
+
+/// Ordered from the largest areas to the smallest ones, the postal codes last
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminAreaKind {
+    /// `boundary=administrative`, from the countries (level 2) down to the neighbourhoods
+    ///
+    /// https://wiki.openstreetmap.org/wiki/Key:admin_level
+    Administrative { level: u8 },
+    /// `boundary=postal_code`
+    PostalCode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AdminArea {
+    /// ID of the OSM boundary relation
+    id: i64,
+    name: String,
+    kind: AdminAreaKind,
+    bounding_rect: Rect<f64>,
+    geometry: MultiPolygon<f64>,
+}
+
+impl AdminArea {
+    pub fn new(
+        id: i64,
+        name: String,
+        kind: AdminAreaKind,
+        geometry: MultiPolygon<f64>,
+    ) -> Option<Self> {
+        let bounding_rect = geometry.bounding_rect()?;
+        Some(AdminArea {
+            id,
+            name,
+            kind,
+            bounding_rect,
+            geometry,
+        })
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> AdminAreaKind {
+        self.kind
+    }
+
+    fn contains(&self, point: &Point<f64>) -> bool {
+        self.bounding_rect.intersects(point) && self.geometry.contains(point)
+    }
+}
+
+/// Name and kind of a boundary relation, `None` for the other relations
+fn parse_boundary(relation: &osmpbfreader::Relation) -> Option<(String, AdminAreaKind)> {
+    match relation.tags.get("boundary")?.as_str() {
+        "administrative" => {
+            let level = relation.tags.get("admin_level")?.parse::<u8>().ok()?;
+            let name = relation.tags.get("name")?;
+            Some((name.to_string(), AdminAreaKind::Administrative { level }))
+        }
+        "postal_code" => {
+            let postal_code = relation
+                .tags
+                .get("postal_code")
+                .or_else(|| relation.tags.get("name"))?;
+            Some((postal_code.to_string(), AdminAreaKind::PostalCode))
+        }
+        _ => None,
+    }
+}
+
+/// Joins the ways of a boundary end to end into closed rings. The rings that cannot be closed,
+/// e.g. the boundaries cut by the border of the extract, are dropped
+fn assemble_rings(mut segments: Vec<Vec<Coord<f64>>>) -> Vec<LineString<f64>> {
+    segments.retain(|segment| segment.len() >= 2);
+
+    let mut rings = vec![];
+    while let Some(mut ring) = segments.pop() {
+        while ring.first() != ring.last() {
+            let end = ring[ring.len() - 1];
+            let Some(index) = segments
+                .iter()
+                .position(|segment| segment.first() == Some(&end) || segment.last() == Some(&end))
+            else {
+                break;
+            };
+
+            let mut segment = segments.swap_remove(index);
+            if segment.first() != Some(&end) {
+                segment.reverse();
+            }
+            ring.extend(segment.into_iter().skip(1));
+        }
+
+        if ring.len() >= 4 && ring.first() == ring.last() {
+            rings.push(LineString::new(ring));
+        }
+    }
+
+    rings
+}
+
+/// Polygons of the outer rings, each inner ring is a hole of the outer ring containing it
+fn assemble_multi_polygon(
+    outers: Vec<LineString<f64>>,
+    inners: Vec<LineString<f64>>,
+) -> MultiPolygon<f64> {
+    let mut polygons = outers
+        .into_iter()
+        .map(|outer| Polygon::new(outer, vec![]))
+        .collect::<Vec<_>>();
+
+    for inner in inners {
+        let point = Point::from(inner.0[0]);
+        if let Some(polygon) = polygons
+            .iter_mut()
+            .find(|polygon| polygon.intersects(&point))
+        {
+            polygon.interiors_push(inner);
+        }
+    }
+
+    MultiPolygon::new(polygons)
+}
+
+/// Administrative and postal boundaries of the imported region, to tell in which country, region,
+/// city or postcode a location is
+#[derive(Serialize, Deserialize, Default)]
+pub struct AdminAreas {
+    areas: Vec<AdminArea>,
+}
+
+impl AdminAreas {
+    pub fn new(areas: Vec<AdminArea>) -> Self {
+        AdminAreas { areas }
+    }
+
+    pub fn from_osm_file(file_path: &str) -> Result<AdminAreas, ImportError> {
+        let mut stopwatch = Stopwatch::new(String::from("admin_areas/from_osm_file"));
+        stopwatch.start();
+
+        let file = File::open(Path::new(file_path)).map_err(ImportError::OpenOsmFile)?;
+        let mut reader = OsmPbfReader::new(file);
+        let objects = reader
+            .get_objs_and_deps(|object| object.relation().and_then(parse_boundary).is_some())
+            .map_err(ImportError::ReadBoundaries)?;
+
+        let admin_areas = AdminAreas::from_osm_objects(&objects);
+
+        stopwatch.stop();
+        stopwatch.report();
+        info!("Imported {} administrative areas", admin_areas.len());
+
+        Ok(admin_areas)
+    }
+
+    fn from_osm_objects(objects: &BTreeMap<OsmId, OsmObj>) -> AdminAreas {
+        let way_coordinates = |osm_id: &OsmId| -> Option<Vec<Coord<f64>>> {
+            let way = objects.get(osm_id)?.way()?;
+            way.nodes
+                .iter()
+                .map(|node_id| {
+                    let node = objects.get(&OsmId::Node(*node_id))?.node()?;
+                    Some(Coord {
+                        x: node.lon(),
+                        y: node.lat(),
+                    })
+                })
+                .collect()
+        };
+
+        let areas = objects
+            .values()
+            .filter_map(OsmObj::relation)
+            .filter_map(|relation| {
+                let (name, kind) = parse_boundary(relation)?;
+
+                let mut outers = vec![];
+                let mut inners = vec![];
+                for member in relation.refs.iter().filter(|member| member.member.is_way()) {
+                    let Some(coordinates) = way_coordinates(&member.member) else {
+                        continue;
+                    };
+
+                    match member.role.as_str() {
+                        "outer" | "" => outers.push(coordinates),
+                        "inner" => inners.push(coordinates),
+                        _ => {}
+                    }
+                }
+
+                let geometry =
+                    assemble_multi_polygon(assemble_rings(outers), assemble_rings(inners));
+                AdminArea::new(relation.id.0, name, kind, geometry)
+            })
+            .collect();
+
+        AdminAreas { areas }
+    }
+
+    pub fn len(&self) -> usize {
+        self.areas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.areas.is_empty()
+    }
+
+    /// Areas containing the point, from the largest to the smallest
+    pub fn areas_at(&self, point: &GeoPoint) -> Vec<&AdminArea> {
+        let point = Point::new(point.lon(), point.lat());
+        let mut areas = self
+            .areas
+            .iter()
+            .filter(|area| area.contains(&point))
+            .collect::<Vec<_>>();
+
+        areas.sort_by_key(|area| (area.kind, area.id));
+        areas
+    }
+
+    /// Areas overlapping the bounding box of the region
+    pub fn within(&self, region: &MultiPolygon<f64>) -> AdminAreas {
+        let Some(region_rect) = region.bounding_rect() else {
+            return AdminAreas::default();
+        };
+
+        AdminAreas {
+            areas: self
+                .areas
+                .iter()
+                .filter(|area| area.bounding_rect.intersects(&region_rect))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), std::io::Error> {
+        let bytes = bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(std::io::Error::other)?;
+        write_file(path, FileKind::AdminAreas, &bytes)
+    }
+
+    /// The graphs imported before the boundaries were supported have no areas
+    pub fn load_from_file(path: &str) -> Result<Self, StorageError> {
+        if !Path::new(path).exists() {
+            return Ok(AdminAreas::default());
+        }
+
+        let bytes = read_file(path, FileKind::AdminAreas)?;
+        let (areas, _) = bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
+            .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
+        Ok(areas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::coord;
+
+    use super::*;
+
+    fn square(min: f64, max: f64) -> Vec<Coord<f64>> {
+        vec![
+            coord! { x: min, y: min },
+            coord! { x: max, y: min },
+            coord! { x: max, y: max },
+            coord! { x: min, y: max },
+            coord! { x: min, y: min },
+        ]
+    }
+
+    #[test]
+    fn test_assemble_rings() {
+        let segments = vec![
+            vec![coord! { x: 0.0, y: 0.0 }, coord! { x: 1.0, y: 0.0 }],
+            vec![coord! { x: 1.0, y: 1.0 }, coord! { x: 1.0, y: 0.0 }],
+            vec![
+                coord! { x: 1.0, y: 1.0 },
+                coord! { x: 0.0, y: 1.0 },
+                coord! { x: 0.0, y: 0.0 },
+            ],
+            // Cut by the border of the extract
+            vec![coord! { x: 5.0, y: 5.0 }, coord! { x: 6.0, y: 5.0 }],
+        ];
+
+        let rings = assemble_rings(segments);
+
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].0.len(), 5);
+        assert!(rings[0].is_closed());
+    }
+
+    #[test]
+    fn test_areas_at() {
+        let area = |id: i64, kind: AdminAreaKind, min: f64, max: f64| {
+            let polygon = Polygon::new(LineString::new(square(min, max)), vec![]);
+            AdminArea::new(id, id.to_string(), kind, MultiPolygon::new(vec![polygon])).unwrap()
+        };
+
+        let admin_areas = AdminAreas::new(vec![
+            area(3, AdminAreaKind::PostalCode, 0.0, 1.0),
+            area(2, AdminAreaKind::Administrative { level: 8 }, 0.0, 2.0),
+            area(1, AdminAreaKind::Administrative { level: 2 }, 0.0, 10.0),
+        ]);
+
+        let ids = |point: GeoPoint| {
+            admin_areas
+                .areas_at(&point)
+                .iter()
+                .map(|area| area.id())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(GeoPoint::new(0.5, 0.5)), vec![1, 2, 3]);
+        assert_eq!(ids(GeoPoint::new(1.5, 1.5)), vec![1, 2]);
+        assert_eq!(ids(GeoPoint::new(20.0, 20.0)), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_inner_ring_is_a_hole() {
+        let multi_polygon = assemble_multi_polygon(
+            vec![LineString::new(square(0.0, 4.0))],
+            vec![LineString::new(square(1.0, 2.0))],
+        );
+
+        assert_eq!(multi_polygon.0[0].interiors().len(), 1);
+        assert!(!multi_polygon.contains(&Point::new(1.5, 1.5)));
+        assert!(multi_polygon.contains(&Point::new(3.0, 3.0)));
+    }
+
+    #[test]
+    fn test_missing_osm_file() {
+        assert!(matches!(
+            AdminAreas::from_osm_file("/nonexistent/region.osm.pbf"),
+            Err(ImportError::OpenOsmFile(_))
+        ));
+    }
+}
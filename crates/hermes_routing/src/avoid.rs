@@ -0,0 +1,235 @@
+use fxhash::FxHashSet;
+use geo::MultiPolygon;
+use serde::Deserialize;
+
+use crate::base_graph::BaseGraphEdge;
+use crate::constants::{MAX_DURATION, MAX_WEIGHT};
+use crate::edge_direction::EdgeDirection;
+use crate::graph::Graph;
+use crate::graph_edge::GraphEdge;
+use crate::location_index::LocationIndex;
+use crate::properties::property::Property;
+use crate::types::EdgeId;
+use crate::weighting::{Milliseconds, Weight, Weighting};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoadClass {
+    /// Motorways and their links
+    Motorway,
+    /// Ways tagged `toll=yes`
+    Toll,
+    Ferry,
+}
+
+impl RoadClass {
+    pub(crate) fn property(self) -> Property {
+        match self {
+            RoadClass::Motorway => Property::Motorway,
+            RoadClass::Toll => Property::Toll,
+            RoadClass::Ferry => Property::Ferry,
+        }
+    }
+}
+
+/// Parts of the graph the paths of a request must not use. The contraction hierarchies are
+/// prepared without them, the requests avoiding something are computed on the base graph
+#[derive(Clone, Debug)]
+pub struct AvoidOptions {
+    /// The edges crossing or within these areas are avoided
+    pub areas: MultiPolygon<f64>,
+    pub osm_way_ids: Vec<usize>,
+    pub road_classes: Vec<RoadClass>,
+}
+
+/// Edges of the base graph matching the `AvoidOptions` of a request
+pub struct AvoidedEdges {
+    area_edges: FxHashSet<EdgeId>,
+    osm_way_ids: FxHashSet<usize>,
+    properties: Vec<Property>,
+}
+
+impl AvoidedEdges {
+    pub fn new(index: &LocationIndex, options: &AvoidOptions) -> Self {
+        AvoidedEdges {
+            area_edges: index.edges_in_area(&options.areas).into_iter().collect(),
+            osm_way_ids: options.osm_way_ids.iter().copied().collect(),
+            properties: options
+                .road_classes
+                .iter()
+                .map(|road_class| road_class.property())
+                .collect(),
+        }
+    }
+
+    /// The virtual edges of a query graph are only avoided by their way and road class, the
+    /// points are snapped to edges outside of the avoided areas
+    pub fn is_avoided(&self, edge: &BaseGraphEdge) -> bool {
+        let properties = edge.properties();
+
+        self.area_edges.contains(&edge.id())
+            || properties
+                .get_usize(Property::OsmId)
+                .is_some_and(|osm_id| self.osm_way_ids.contains(&osm_id))
+            || self.properties.iter().any(|property| {
+                properties
+                    .get_bool(property.clone(), EdgeDirection::Forward)
+                    .unwrap_or(false)
+            })
+    }
+}
+
+/// Weighting of a profile on which the avoided edges are not accessible
+pub struct AvoidWeighting<'a, W> {
+    weighting: W,
+    avoided_edges: Option<&'a AvoidedEdges>,
+}
+
+impl<'a, W> AvoidWeighting<'a, W> {
+    /// Same as `weighting` when there is nothing to avoid
+    pub fn new(weighting: W, avoided_edges: Option<&'a AvoidedEdges>) -> Self {
+        AvoidWeighting {
+            weighting,
+            avoided_edges,
+        }
+    }
+
+    fn is_avoided(&self, edge: &BaseGraphEdge) -> bool {
+        self.avoided_edges
+            .is_some_and(|avoided_edges| avoided_edges.is_avoided(edge))
+    }
+}
+
+impl<G, W> Weighting<G> for AvoidWeighting<'_, W>
+where
+    G: Graph<Edge = BaseGraphEdge>,
+    W: Weighting<G>,
+{
+    fn calc_edge_weight(&self, edge: &G::Edge, direction: EdgeDirection) -> Weight {
+        if self.is_avoided(edge) {
+            return MAX_WEIGHT;
+        }
+
+        self.weighting.calc_edge_weight(edge, direction)
+    }
+
+    fn calc_edge_ms(&self, edge: &G::Edge, direction: EdgeDirection) -> Milliseconds {
+        if self.is_avoided(edge) {
+            return MAX_DURATION;
+        }
+
+        self.weighting.calc_edge_ms(edge, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{MultiPolygon, polygon};
+
+    use crate::{
+        base_graph::BaseGraph,
+        geopoint::GeoPoint,
+        location_index::LocationIndex,
+        osm::{
+            osm_change::OsmChange,
+            osm_reader::{OsmWay, parse_way_properties},
+        },
+        routing::{dijkstra::Dijkstra, shortest_path_algorithm::CalcPath},
+        weighting::CarWeighting,
+    };
+
+    use super::{AvoidOptions, AvoidWeighting, AvoidedEdges, RoadClass};
+
+    const MOTORWAY_POINT: (f64, f64) = (0.01, 0.01);
+    const RESIDENTIAL_POINT: (f64, f64) = (-0.02, 0.01);
+
+    /// Square from node 0 to node 2, the motorway through node 1 is shorter than the residential
+    /// road through node 3
+    fn create_graph() -> BaseGraph {
+        let ways = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="1"><tag k="highway" v="motorway"/></way>
+                <way id="2"><tag k="highway" v="residential"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap()
+        .ways;
+        let properties = |index: usize| {
+            parse_way_properties(&OsmWay::new(ways[index].id as usize, &ways[index].tags))
+        };
+        let points = [
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(MOTORWAY_POINT.0, MOTORWAY_POINT.1),
+            GeoPoint::new(0.0, 0.02),
+            GeoPoint::new(RESIDENTIAL_POINT.0, RESIDENTIAL_POINT.1),
+        ];
+
+        let mut graph = BaseGraph::default();
+        for node in 0..points.len() {
+            graph.add_node(node);
+        }
+
+        for (start, end, way) in [(0, 1, 0), (1, 2, 0), (2, 3, 1), (3, 0, 1)] {
+            graph.add_edge(
+                start,
+                end,
+                properties(way),
+                vec![points[start], points[end]],
+            );
+        }
+
+        graph
+    }
+
+    /// Whether the path from node 0 to node 2 goes through the point
+    fn path_through(graph: &BaseGraph, options: Option<AvoidOptions>, point: (f64, f64)) -> bool {
+        let index = LocationIndex::build_from_graph(graph);
+        let avoided_edges = options.map(|options| AvoidedEdges::new(&index, &options));
+        let weighting = AvoidWeighting::new(CarWeighting::new(), avoided_edges.as_ref());
+
+        let mut dijkstra = Dijkstra::new(graph);
+        let result = dijkstra.calc_path(&weighting, 0, 2, None).unwrap();
+
+        result
+            .path
+            .points()
+            .contains(&GeoPoint::new(point.0, point.1))
+    }
+
+    fn avoid_options() -> AvoidOptions {
+        AvoidOptions {
+            areas: MultiPolygon::new(vec![]),
+            osm_way_ids: vec![],
+            road_classes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_avoid() {
+        let graph = create_graph();
+        assert!(path_through(&graph, None, MOTORWAY_POINT));
+
+        let road_classes = AvoidOptions {
+            road_classes: vec![RoadClass::Motorway],
+            ..avoid_options()
+        };
+        assert!(path_through(&graph, Some(road_classes), RESIDENTIAL_POINT));
+
+        let osm_way_ids = AvoidOptions {
+            osm_way_ids: vec![1],
+            ..avoid_options()
+        };
+        assert!(path_through(&graph, Some(osm_way_ids), RESIDENTIAL_POINT));
+
+        let areas = AvoidOptions {
+            areas: MultiPolygon::new(vec![polygon![
+                (x: 0.005, y: 0.005),
+                (x: 0.02, y: 0.005),
+                (x: 0.02, y: 0.015),
+                (x: 0.005, y: 0.015),
+            ]]),
+            ..avoid_options()
+        };
+        assert!(path_through(&graph, Some(areas), RESIDENTIAL_POINT));
+    }
+}
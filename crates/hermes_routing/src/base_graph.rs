@@ -1,17 +1,24 @@
 use std::cmp::max;
 
+use fxhash::FxHashMap;
+use geo::{Intersects, MultiPolygon, Point};
 use tracing::{debug, info};
 
 use crate::distance::{Distance, Meters};
+use crate::error::StorageError;
 use crate::edge_direction::EdgeDirection;
 use crate::geometry::compute_geometry_distance;
 use crate::geopoint::GeoPoint;
-use crate::graph::{GeometryAccess, Graph, UndirectedEdgeAccess};
+use crate::graph::{GeometryAccess, Graph, TurnRestrictionAccess, UndirectedEdgeAccess};
 use crate::graph_edge::GraphEdge;
-use crate::osm::osm_reader::OsmReader;
-use crate::properties::property_map::EdgePropertyMap;
-use crate::storage::{read_bytes, write_bytes};
+use crate::osm::osm_change::{OsmChange, OsmChangeAction, OsmChangeSummary};
+use crate::osm::osm_reader::{OsmReader, OsmWay, is_road, parse_way_properties};
+use crate::properties::property::Property;
+use crate::properties::property_map::{EdgePropertyMap, StringInterner};
+use crate::storage::{FileKind, read_file, write_file};
+use crate::turn_restrictions::{TurnRestriction, TurnRestrictions};
 use crate::types::{EdgeId, NodeId};
+use crate::weighting::{CarWeighting, Weighting};
 
 #[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
 pub struct BaseGraphEdge {
@@ -76,12 +83,14 @@ pub struct BaseGraph {
     edges: Vec<BaseGraphEdge>,
     adjacency_list: Vec<Vec<EdgeId>>,
     geometry: Vec<Vec<GeoPoint>>,
+    turn_restrictions: TurnRestrictions,
 }
 
-fn from_bytes(bytes: &[u8]) -> BaseGraph {
-    let graph = rkyv::from_bytes::<BaseGraph, rkyv::rancor::Error>(bytes).unwrap();
+fn from_bytes(path: &str, bytes: &[u8]) -> Result<BaseGraph, StorageError> {
+    let graph = rkyv::from_bytes::<BaseGraph, rkyv::rancor::Error>(bytes)
+        .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
     info!("Deserialized graph from buffer");
-    graph
+    Ok(graph)
 }
 
 impl BaseGraph {
@@ -89,7 +98,16 @@ impl BaseGraph {
         &self.edges
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
+    pub fn turn_restrictions(&self) -> &TurnRestrictions {
+        &self.turn_restrictions
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_turn_restrictions(&mut self, restrictions: Vec<TurnRestriction>) {
+        self.turn_restrictions = TurnRestrictions::new(restrictions);
+    }
+
+    pub(crate) fn add_node(&mut self, node_id: NodeId) {
         self.nodes = max(self.nodes, node_id + 1);
 
         if self.nodes > self.adjacency_list.len() {
@@ -105,21 +123,28 @@ impl BaseGraph {
 
     pub fn save_to_file(&self, path: &str) -> Result<(), std::io::Error> {
         let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(self).expect("to_bytes failed");
-        write_bytes(&bytes[..], path)
+        write_file(path, FileKind::Graph, &bytes[..])
     }
 
-    pub fn from_file(path: &str) -> BaseGraph {
+    pub fn from_file(path: &str) -> Result<BaseGraph, StorageError> {
         debug!("Reading from path {}", path);
-        let bytes = read_bytes(path);
+        let bytes = read_file(path, FileKind::Graph)?;
         debug!("Read from path {}, size {}", path, bytes.len());
-        from_bytes(&bytes)
+        from_bytes(path, &bytes)
     }
 
     pub fn from_osm_file(path: &str) -> BaseGraph {
         let mut osm_reader = OsmReader::default();
 
         let mut graph = BaseGraph::default();
-        osm_reader.parse_osm_file(path, |edge_segment| {
+        let mut way_edges: FxHashMap<i64, Vec<EdgeId>> = FxHashMap::default();
+        let mut strings = StringInterner::default();
+        osm_reader.parse_osm_file(path, |mut edge_segment| {
+            way_edges
+                .entry(edge_segment.osm_way_id)
+                .or_default()
+                .push(graph.edges.len());
+            edge_segment.properties.intern_strings(&mut strings);
             graph.add_node(edge_segment.start_node);
             graph.add_node(edge_segment.end_node);
             graph.add_edge(
@@ -129,15 +154,235 @@ impl BaseGraph {
                 edge_segment.geometry,
             );
         });
+        graph.mark_roundabout_exits();
+
+        // The from and to ways are split at the via node, the restricted edges are their segments
+        // ending at the via node
+        let restrictions = osm_reader
+            .turn_restrictions()
+            .iter()
+            .filter_map(|restriction| {
+                let via_node = osm_reader.routing_node_id(restriction.via_node)?;
+                let way_edge = |way_id: i64| {
+                    way_edges.get(&way_id)?.iter().copied().find(|&edge_id| {
+                        let edge = &graph.edges[edge_id];
+                        edge.start_node == via_node || edge.end_node == via_node
+                    })
+                };
+
+                Some(TurnRestriction {
+                    from_edge: way_edge(restriction.from_way)?,
+                    via_node,
+                    to_edge: way_edge(restriction.to_way)?,
+                    kind: restriction.kind,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        info!(
+            "Added {} of {} turn restrictions",
+            restrictions.len(),
+            osm_reader.turn_restrictions().len()
+        );
+        graph.turn_restrictions = TurnRestrictions::new(restrictions);
 
         graph
     }
 
+    /// Copies the edges with at least one point of their geometry within the region.
+    ///
+    /// Edges crossing the boundary are kept whole so that routes leaving the region near its border
+    /// are still found, the nodes are numbered again from 0
+    pub fn extract_region(&self, region: &MultiPolygon<f64>) -> BaseGraph {
+        let mut graph = BaseGraph::default();
+        let mut node_ids: FxHashMap<NodeId, NodeId> = FxHashMap::default();
+        let mut edge_ids: FxHashMap<EdgeId, EdgeId> = FxHashMap::default();
+
+        for (edge, geometry) in self.edges.iter().zip(&self.geometry) {
+            let is_in_region = geometry
+                .iter()
+                .any(|point| region.intersects(&Point::new(point.lon(), point.lat())));
+
+            if !is_in_region {
+                continue;
+            }
+
+            let mut region_node = |node_id: NodeId| {
+                let next_node_id = node_ids.len();
+                *node_ids.entry(node_id).or_insert(next_node_id)
+            };
+
+            let start_node = region_node(edge.start_node);
+            let end_node = region_node(edge.end_node);
+
+            edge_ids.insert(edge.id, graph.edges.len());
+            graph.add_node(start_node);
+            graph.add_node(end_node);
+            graph.add_edge(
+                start_node,
+                end_node,
+                edge.properties.clone(),
+                geometry.clone(),
+            );
+        }
+
+        graph.turn_restrictions = TurnRestrictions::new(
+            self.turn_restrictions
+                .iter()
+                .filter_map(|restriction| {
+                    Some(TurnRestriction {
+                        from_edge: *edge_ids.get(&restriction.from_edge)?,
+                        via_node: *node_ids.get(&restriction.via_node)?,
+                        to_edge: *edge_ids.get(&restriction.to_edge)?,
+                        kind: restriction.kind,
+                    })
+                })
+                .collect(),
+        );
+
+        debug!(
+            "Extracted {} of {} edges in region",
+            graph.edges.len(),
+            self.edges.len()
+        );
+
+        graph
+    }
+
+    /// Updates the properties of the edges of the modified and deleted ways, the deleted ways stay
+    /// in the graph without access. The topology and the geometry of the graph do not change, the
+    /// new ways and the moved nodes need a full import
+    pub fn apply_osm_change(&mut self, change: &OsmChange) -> OsmChangeSummary {
+        let mut way_edges: FxHashMap<i64, Vec<EdgeId>> = FxHashMap::default();
+        for edge in &self.edges {
+            if let Some(osm_id) = edge.properties.get_usize(Property::OsmId) {
+                way_edges.entry(osm_id as i64).or_default().push(edge.id);
+            }
+        }
+
+        let weighting = CarWeighting::<BaseGraph>::new();
+        let mut summary = OsmChangeSummary::default();
+
+        for way in &change.ways {
+            let Some(edge_ids) = way_edges.get(&way.id) else {
+                summary.skipped_ways += 1;
+                continue;
+            };
+
+            let osm_way = OsmWay::new(way.id as usize, &way.tags);
+            let properties = match way.action {
+                OsmChangeAction::Create => {
+                    summary.skipped_ways += 1;
+                    continue;
+                }
+                // The edges follow the nodes of the way, a way with added or removed nodes has a
+                // new geometry
+                OsmChangeAction::Modify
+                    if is_road(&way.tags)
+                        && !way.nodes.is_empty()
+                        && way.nodes.len() != self.way_point_count(edge_ids) =>
+                {
+                    summary.skipped_ways += 1;
+                    continue;
+                }
+                OsmChangeAction::Modify if is_road(&way.tags) => {
+                    summary.modified_ways += 1;
+                    parse_way_properties(&osm_way)
+                }
+                // Not a road anymore
+                OsmChangeAction::Modify | OsmChangeAction::Delete => {
+                    summary.deleted_ways += 1;
+                    let mut properties = EdgePropertyMap::default();
+                    properties.insert_usize(Property::OsmId, way.id as usize);
+                    properties
+                }
+            };
+
+            for &edge_id in edge_ids {
+                let previous_weights = [EdgeDirection::Forward, EdgeDirection::Backward]
+                    .map(|direction| weighting.calc_edge_weight(&self.edges[edge_id], direction));
+
+                self.edges[edge_id].properties = properties.clone();
+                summary.updated_edges += 1;
+
+                let weights = [EdgeDirection::Forward, EdgeDirection::Backward]
+                    .map(|direction| weighting.calc_edge_weight(&self.edges[edge_id], direction));
+                summary.car_weights_changed |= weights != previous_weights;
+            }
+        }
+
+        // The properties of the modified ways are parsed again without their exits
+        self.mark_roundabout_exits();
+
+        info!(
+            "osmChange: {} modified, {} deleted, {} skipped ways, {} skipped node changes",
+            summary.modified_ways, summary.deleted_ways, summary.skipped_ways, change.node_changes
+        );
+
+        summary
+    }
+
+    /// Marks the ends of the roundabout edges where a road accessible to cars leaves the
+    /// roundabout, in both directions of the edges. The roads only entering the roundabout are
+    /// not exits
+    fn mark_roundabout_exits(&mut self) {
+        let is_roundabout = |edge: &BaseGraphEdge| {
+            edge.properties
+                .get_bool(Property::Roundabout, EdgeDirection::Forward)
+                .unwrap_or(false)
+        };
+
+        for edge_id in 0..self.edges.len() {
+            let edge = &self.edges[edge_id];
+            if !is_roundabout(edge) {
+                continue;
+            }
+
+            let directions = [EdgeDirection::Forward, EdgeDirection::Backward];
+            let exits = directions.map(|direction| {
+                let node = match direction {
+                    EdgeDirection::Forward => edge.end_node,
+                    EdgeDirection::Backward => edge.start_node,
+                };
+
+                self.adjacency_list[node].iter().any(|&other_id| {
+                    let other = &self.edges[other_id];
+                    !is_roundabout(other)
+                        && other
+                            .properties
+                            .get_bool(
+                                Property::CarVehicleAccess,
+                                self.edge_direction(other_id, node),
+                            )
+                            .unwrap_or(false)
+                })
+            });
+
+            for (direction, exit) in directions.into_iter().zip(exits) {
+                self.edges[edge_id].properties.insert_bool(
+                    Property::RoundaboutExit,
+                    direction,
+                    exit,
+                );
+            }
+        }
+    }
+
+    /// Number of points of the geometry of a way split into `edge_ids`, the consecutive edges
+    /// share their end points
+    fn way_point_count(&self, edge_ids: &[EdgeId]) -> usize {
+        edge_ids
+            .iter()
+            .map(|&edge_id| self.geometry[edge_id].len() - 1)
+            .sum::<usize>()
+            + 1
+    }
+
     pub fn node_edges(&self, node: NodeId) -> &[EdgeId] {
         &self.adjacency_list[node]
     }
 
-    fn add_edge(
+    pub(crate) fn add_edge(
         &mut self,
         from_node: NodeId,
         to_node: NodeId,
@@ -210,6 +455,22 @@ impl GeometryAccess for BaseGraph {
     }
 }
 
+impl TurnRestrictionAccess for BaseGraph {
+    fn has_turn_restrictions(&self, node_id: NodeId) -> bool {
+        self.turn_restrictions.has_restrictions(node_id)
+    }
+
+    fn is_turn_allowed(
+        &self,
+        from_edge_id: EdgeId,
+        via_node_id: NodeId,
+        to_edge_id: EdgeId,
+    ) -> bool {
+        self.turn_restrictions
+            .is_turn_allowed(from_edge_id, via_node_id, to_edge_id)
+    }
+}
+
 impl UndirectedEdgeAccess for BaseGraph {
     type EdgeIterator<'a> = std::iter::Copied<std::slice::Iter<'a, usize>>;
 
@@ -217,3 +478,186 @@ impl UndirectedEdgeAccess for BaseGraph {
         self.adjacency_list[node].iter().copied()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use geo::{MultiPolygon, polygon};
+
+    use crate::{
+        edge_direction::EdgeDirection,
+        geopoint::GeoPoint,
+        graph::{GeometryAccess, Graph},
+        graph_edge::GraphEdge,
+        osm::{
+            osm_change::{OsmChange, OsmChangeSummary},
+            osm_reader::{OsmWay, parse_way_properties},
+        },
+        properties::{property::Property, property_map::EdgePropertyMap},
+        types::EdgeId,
+        weighting::{CarWeighting, Weighting},
+    };
+
+    use super::BaseGraph;
+
+    fn add_edge(graph: &mut BaseGraph, from: usize, to: usize, geometry: Vec<(f64, f64)>) {
+        graph.add_node(from);
+        graph.add_node(to);
+        graph.add_edge(
+            from,
+            to,
+            EdgePropertyMap::default(),
+            geometry
+                .into_iter()
+                .map(|(lon, lat)| GeoPoint::new(lon, lat))
+                .collect(),
+        );
+    }
+
+    #[test]
+    fn test_extract_region() {
+        let mut graph = BaseGraph::default();
+        // Inside the region
+        add_edge(&mut graph, 0, 1, vec![(0.1, 0.1), (0.2, 0.2)]);
+        // Crossing the boundary
+        add_edge(&mut graph, 1, 2, vec![(0.2, 0.2), (1.5, 1.5)]);
+        // Outside the region
+        add_edge(&mut graph, 2, 3, vec![(1.5, 1.5), (2.0, 2.0)]);
+        add_edge(&mut graph, 3, 4, vec![(2.0, 2.0), (3.0, 3.0)]);
+
+        let region = MultiPolygon::new(vec![polygon![
+            (x: 0.0, y: 0.0),
+            (x: 1.0, y: 0.0),
+            (x: 1.0, y: 1.0),
+            (x: 0.0, y: 1.0),
+        ]]);
+
+        let extracted = graph.extract_region(&region);
+
+        assert_eq!(extracted.edge_count(), 2);
+        assert_eq!(extracted.node_count(), 3);
+
+        let crossing_edge = extracted.edge(1);
+        assert_eq!(crossing_edge.start_node(), 1);
+        assert_eq!(crossing_edge.end_node(), 2);
+        assert_eq!(extracted.edge_geometry(1).len(), 2);
+        assert_eq!(extracted.node_geometry(2).lon(), 1.5);
+    }
+
+    #[test]
+    fn test_apply_osm_change() {
+        let imported = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="10"><tag k="highway" v="residential"/><tag k="name" v="A"/></way>
+                <way id="11"><tag k="highway" v="residential"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap();
+
+        let mut graph = BaseGraph::default();
+        for (index, way) in imported.ways.iter().enumerate() {
+            graph.add_node(index);
+            graph.add_node(index + 1);
+            graph.add_edge(
+                index,
+                index + 1,
+                parse_way_properties(&OsmWay::new(way.id as usize, &way.tags)),
+                vec![
+                    GeoPoint::new(index as f64 * 0.01, 0.0),
+                    GeoPoint::new((index + 1) as f64 * 0.01, 0.0),
+                ],
+            );
+        }
+
+        let renamed = OsmChange::parse(
+            r#"<osmChange>
+                <modify>
+                    <way id="10"><tag k="highway" v="residential"/><tag k="name" v="B"/></way>
+                </modify>
+                <create><way id="12"/></create>
+            </osmChange>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            graph.apply_osm_change(&renamed),
+            OsmChangeSummary {
+                modified_ways: 1,
+                deleted_ways: 0,
+                updated_edges: 1,
+                skipped_ways: 1,
+                car_weights_changed: false,
+            }
+        );
+        assert_eq!(
+            graph.edge(0).properties().get_string(Property::StreetName),
+            Some("B")
+        );
+
+        let deleted =
+            OsmChange::parse(r#"<osmChange><delete><way id="11"/></delete></osmChange>"#).unwrap();
+
+        assert!(graph.apply_osm_change(&deleted).car_weights_changed);
+        assert!(!CarWeighting::new().can_access_edge(graph.edge(1)));
+        assert_eq!(graph.edge(1).properties().get_usize(Property::OsmId), Some(11));
+
+        // A node was added to the way, its geometry changed
+        let reshaped = OsmChange::parse(
+            r#"<osmChange><modify>
+                <way id="10">
+                    <nd ref="1"/><nd ref="3"/><nd ref="2"/>
+                    <tag k="highway" v="residential"/><tag k="name" v="C"/>
+                </way>
+            </modify></osmChange>"#,
+        )
+        .unwrap();
+
+        assert_eq!(graph.apply_osm_change(&reshaped).skipped_ways, 1);
+        assert_eq!(
+            graph.edge(0).properties().get_string(Property::StreetName),
+            Some("B")
+        );
+    }
+
+    #[test]
+    fn test_mark_roundabout_exits() {
+        let ways = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="1"><tag k="highway" v="primary"/><tag k="junction" v="roundabout"/></way>
+                <way id="2"><tag k="highway" v="residential"/><tag k="oneway" v="yes"/></way>
+                <way id="3"><tag k="highway" v="residential"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap();
+        let properties = |index: usize| {
+            let way = &ways.ways[index];
+            parse_way_properties(&OsmWay::new(way.id as usize, &way.tags))
+        };
+
+        let mut graph = BaseGraph::default();
+        let mut add = |from: usize, to: usize, properties: EdgePropertyMap| {
+            graph.add_node(from);
+            graph.add_node(to);
+            let point = |node: usize| GeoPoint::new(node as f64 * 0.001, 0.0);
+            graph.add_edge(from, to, properties, vec![point(from), point(to)]);
+        };
+        // Roundabout 0 -> 1 -> 2 -> 0, a oneway road enters it at 1 and a road leaves it at 2
+        add(0, 1, properties(0));
+        add(1, 2, properties(0));
+        add(2, 0, properties(0));
+        add(3, 1, properties(1));
+        add(2, 4, properties(2));
+
+        graph.mark_roundabout_exits();
+
+        let is_exit = |edge_id: EdgeId| {
+            graph
+                .edge(edge_id)
+                .properties()
+                .get_bool(Property::RoundaboutExit, EdgeDirection::Forward)
+        };
+        assert_eq!(is_exit(0), Some(false));
+        assert_eq!(is_exit(1), Some(true));
+        assert_eq!(is_exit(2), Some(false));
+        assert_eq!(is_exit(3), None);
+    }
+}
@@ -0,0 +1,495 @@
+use std::ops::Range;
+
+use tracing::{debug, info};
+
+use crate::{
+    base_graph::{BaseGraph, BaseGraphEdge},
+    constants::{INVALID_EDGE, INVALID_NODE, MAX_WEIGHT},
+    distance::{Distance, Meters},
+    edge_direction::EdgeDirection,
+    error::StorageError,
+    graph::{Graph, TurnRestrictionAccess},
+    graph_edge::GraphEdge,
+    meters,
+    stopwatch::Stopwatch,
+    storage::{FileKind, read_file, write_file},
+    types::{EdgeId, NodeId},
+    weighting::{Milliseconds, Weight, Weighting},
+};
+
+use super::{ch_edge::CHBaseEdge, ch_storage::CHStorage, shortcut::Shortcut};
+
+/// Arcs of a customizable contraction hierarchy (CCH).
+///
+/// The nodes are contracted in a fixed order without witness searches, so every pair of upper
+/// neighbors of a contracted node is linked by an arc whatever the weighting. The topology is
+/// built once, the weights of the arcs are then customized for each weighting in a single pass
+/// over their lower triangles instead of contracting the graph again.
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct CCHTopology {
+    ranks: Vec<usize>,
+
+    /// The arcs from a node to its upper neighbors are `first_arcs[node]..first_arcs[node + 1]`,
+    /// sorted by the rank of their upper node
+    first_arcs: Vec<usize>,
+
+    /// Upper node of each arc
+    heads: Vec<NodeId>,
+}
+
+/// Best path found for one direction of an arc
+#[derive(Clone, Copy)]
+struct ArcMetric {
+    weight: Weight,
+    time: Milliseconds,
+    distance: Distance<Meters>,
+
+    /// Lower node the path goes through, `INVALID_NODE` when the path is the base edge `edge`
+    via: NodeId,
+    edge: EdgeId,
+
+    /// First and last base edges of the path, to check the turn restrictions
+    first_edge: EdgeId,
+    last_edge: EdgeId,
+}
+
+impl ArcMetric {
+    fn unreachable() -> Self {
+        ArcMetric {
+            weight: MAX_WEIGHT,
+            time: 0,
+            distance: meters!(0),
+            via: INVALID_NODE,
+            edge: INVALID_EDGE,
+            first_edge: INVALID_EDGE,
+            last_edge: INVALID_EDGE,
+        }
+    }
+
+    fn from_edge(
+        edge: &BaseGraphEdge,
+        weighting: &impl Weighting<BaseGraph>,
+        direction: EdgeDirection,
+    ) -> Self {
+        ArcMetric {
+            weight: weighting.calc_edge_weight(edge, direction),
+            time: weighting.calc_edge_ms(edge, direction),
+            distance: edge.distance(),
+            via: INVALID_NODE,
+            edge: edge.id(),
+            first_edge: edge.id(),
+            last_edge: edge.id(),
+        }
+    }
+
+    /// The path `self` then `next` through the lower node `via`
+    fn join(&self, next: &ArcMetric, via: NodeId) -> Option<ArcMetric> {
+        if self.weight == MAX_WEIGHT || next.weight == MAX_WEIGHT {
+            return None;
+        }
+
+        Some(ArcMetric {
+            weight: self.weight.saturating_add(next.weight),
+            time: self.time.saturating_add(next.time),
+            distance: self.distance + next.distance,
+            via,
+            edge: INVALID_EDGE,
+            first_edge: self.first_edge,
+            last_edge: next.last_edge,
+        })
+    }
+
+    fn keep_best(&mut self, other: ArcMetric) {
+        if other.weight < self.weight {
+            *self = other;
+        }
+    }
+}
+
+impl CCHTopology {
+    pub fn save_to_file(&self, path: &str) -> Result<(), std::io::Error> {
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(self).expect("to_bytes failed");
+        write_file(path, FileKind::CCHTopology, &bytes[..])
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, StorageError> {
+        let bytes = read_file(path, FileKind::CCHTopology)?;
+        let data = rkyv::from_bytes::<Self, rkyv::rancor::Error>(&bytes[..])
+            .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
+        info!("Deserialized cch topology from buffer");
+        Ok(data)
+    }
+
+    /// Contracts the nodes in the order of `ranks`, e.g. the ranks of a contraction hierarchy
+    /// already prepared for another weighting
+    pub fn build(base_graph: &BaseGraph, ranks: &[usize]) -> Self {
+        let mut stopwatch = Stopwatch::new(String::from("build_cch_topology"));
+        stopwatch.start();
+
+        let node_count = base_graph.node_count();
+        let mut upper_neighbors = vec![Vec::new(); node_count];
+
+        for edge in base_graph.edges() {
+            let (start, end) = (edge.start_node(), edge.end_node());
+            if start == end {
+                continue;
+            }
+
+            if ranks[start] < ranks[end] {
+                upper_neighbors[start].push(end);
+            } else {
+                upper_neighbors[end].push(start);
+            }
+        }
+
+        // The upper neighbors of a contracted node are linked to its lowest upper neighbor, they
+        // are linked to each other when this one is contracted in turn
+        for node in Self::nodes_by_rank(ranks) {
+            let mut neighbors = std::mem::take(&mut upper_neighbors[node]);
+            neighbors.sort_unstable_by_key(|&neighbor| ranks[neighbor]);
+            neighbors.dedup();
+
+            if let Some((&lowest, others)) = neighbors.split_first() {
+                upper_neighbors[lowest].extend_from_slice(others);
+            }
+
+            upper_neighbors[node] = neighbors;
+        }
+
+        let mut first_arcs = Vec::with_capacity(node_count + 1);
+        let mut heads = Vec::new();
+        for neighbors in upper_neighbors {
+            first_arcs.push(heads.len());
+            heads.extend(neighbors);
+        }
+        first_arcs.push(heads.len());
+
+        stopwatch.stop();
+        info!(
+            "Built CCH topology with {} arcs for {} base edges in {}ms",
+            heads.len(),
+            base_graph.edge_count(),
+            stopwatch.total_duration().as_millis()
+        );
+
+        CCHTopology {
+            ranks: ranks.to_vec(),
+            first_arcs,
+            heads,
+        }
+    }
+
+    fn nodes_by_rank(ranks: &[usize]) -> Vec<NodeId> {
+        let mut nodes = (0..ranks.len()).collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|&node| ranks[node]);
+        nodes
+    }
+
+    pub fn arc_count(&self) -> usize {
+        self.heads.len()
+    }
+
+    fn arcs(&self, node: NodeId) -> Range<usize> {
+        self.first_arcs[node]..self.first_arcs[node + 1]
+    }
+
+    /// Arc between `lower` and one of its upper neighbors
+    fn arc(&self, lower: NodeId, upper: NodeId) -> usize {
+        let arcs = self.arcs(lower);
+        let index = self.heads[arcs.clone()]
+            .binary_search_by_key(&self.ranks[upper], |&head| self.ranks[head])
+            .unwrap_or_else(|_| panic!("No arc between {lower} and {upper}"));
+
+        arcs.start + index
+    }
+
+    /// Contraction hierarchies of the weighting, queried like the ones of `CHGraphBuilder`. As
+    /// with those, a turn restriction is only checked at the node a shortcut goes through.
+    pub fn customize(
+        &self,
+        base_graph: &BaseGraph,
+        weighting: &impl Weighting<BaseGraph>,
+    ) -> CHStorage {
+        let mut stopwatch = Stopwatch::new(String::from("customize_cch"));
+        stopwatch.start();
+
+        // Paths from the lower node of the arcs to their upper node, and back
+        let mut upward = vec![ArcMetric::unreachable(); self.arc_count()];
+        let mut downward = vec![ArcMetric::unreachable(); self.arc_count()];
+
+        for edge in base_graph.edges() {
+            let (start, end) = (edge.start_node(), edge.end_node());
+            if start == end {
+                continue;
+            }
+
+            let forward = ArcMetric::from_edge(edge, weighting, EdgeDirection::Forward);
+            let backward = ArcMetric::from_edge(edge, weighting, EdgeDirection::Backward);
+
+            if self.ranks[start] < self.ranks[end] {
+                let arc = self.arc(start, end);
+                upward[arc].keep_best(forward);
+                downward[arc].keep_best(backward);
+            } else {
+                let arc = self.arc(end, start);
+                upward[arc].keep_best(backward);
+                downward[arc].keep_best(forward);
+            }
+        }
+
+        // The arcs of a node only go through lower nodes, they are final once it is reached
+        for node in Self::nodes_by_rank(&self.ranks) {
+            let arcs = self.arcs(node);
+            let is_turn_allowed = |from: &ArcMetric, to: &ArcMetric| {
+                !base_graph.has_turn_restrictions(node)
+                    || base_graph.is_turn_allowed(from.last_edge, node, to.first_edge)
+            };
+
+            for lower_arc in arcs.clone() {
+                for upper_arc in lower_arc + 1..arcs.end {
+                    let arc = self.arc(self.heads[lower_arc], self.heads[upper_arc]);
+
+                    if let Some(metric) = downward[lower_arc].join(&upward[upper_arc], node)
+                        && is_turn_allowed(&downward[lower_arc], &upward[upper_arc])
+                    {
+                        upward[arc].keep_best(metric);
+                    }
+
+                    if let Some(metric) = downward[upper_arc].join(&upward[lower_arc], node)
+                        && is_turn_allowed(&downward[upper_arc], &upward[lower_arc])
+                    {
+                        downward[arc].keep_best(metric);
+                    }
+                }
+            }
+        }
+
+        let ch_storage = self.to_ch_storage(base_graph, weighting, &upward, &downward);
+
+        stopwatch.stop();
+        info!(
+            "Customized contraction hierarchies in {}ms",
+            stopwatch.total_duration().as_millis()
+        );
+
+        ch_storage
+    }
+
+    fn to_ch_storage(
+        &self,
+        base_graph: &BaseGraph,
+        weighting: &impl Weighting<BaseGraph>,
+        upward: &[ArcMetric],
+        downward: &[ArcMetric],
+    ) -> CHStorage {
+        let mut ch_storage = CHStorage::new(base_graph);
+
+        for (node, &rank) in self.ranks.iter().enumerate() {
+            ch_storage.set_node_rank(node, rank);
+        }
+
+        for edge in base_graph.edges() {
+            ch_storage.add_edge(CHBaseEdge::new(edge, weighting));
+        }
+
+        // The shortcuts are numbered after the base edges, the paths made of a single base edge
+        // keep its id
+        let mut next_shortcut_id = base_graph.edge_count();
+        let mut edge_id = |metric: &ArcMetric| {
+            if metric.weight == MAX_WEIGHT {
+                INVALID_EDGE
+            } else if metric.via == INVALID_NODE {
+                metric.edge
+            } else {
+                next_shortcut_id += 1;
+                next_shortcut_id - 1
+            }
+        };
+        let upward_ids = upward.iter().map(&mut edge_id).collect::<Vec<_>>();
+        let downward_ids = downward.iter().map(&mut edge_id).collect::<Vec<_>>();
+
+        for lower in 0..base_graph.node_count() {
+            for arc in self.arcs(lower) {
+                let upper = self.heads[arc];
+                let directions = [
+                    (&upward[arc], upward_ids[arc], lower, upper),
+                    (&downward[arc], downward_ids[arc], upper, lower),
+                ];
+
+                for (metric, id, start, end) in directions {
+                    if metric.weight == MAX_WEIGHT || metric.via == INVALID_NODE {
+                        continue;
+                    }
+
+                    ch_storage.add_shortcut(Shortcut {
+                        id,
+                        start,
+                        end,
+                        incoming_edge: downward_ids[self.arc(metric.via, start)],
+                        outgoing_edge: upward_ids[self.arc(metric.via, end)],
+                        distance: metric.distance,
+                        time: metric.time,
+                        weight: metric.weight,
+                    });
+                }
+            }
+        }
+
+        debug!(
+            "Added {} shortcuts for {} arcs",
+            next_shortcut_id - base_graph.edge_count(),
+            self.arc_count()
+        );
+
+        ch_storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        base_graph::BaseGraph,
+        ch::{
+            ch_graph::CHGraph, ch_graph_builder::CHGraphBuilder, ch_weighting::CHWeighting,
+            nested_dissection::nested_dissection_ranks,
+        },
+        geopoint::GeoPoint,
+        matrix::{
+            dijkstra_matrix_algorithm::DijkstraMatrixAlgorithm, matrix_algorithm::MatrixAlgorithm,
+            one_to_many_algorithm::OneToManyAlgorithm,
+        },
+        osm::{
+            osm_change::OsmChange,
+            osm_reader::{OsmWay, parse_way_properties},
+        },
+        profile::ProfileConfig,
+        query::query_graph::QueryGraph,
+        weighting::{CarWeighting, ProfileWeighting, TruckWeighting, VehicleDimensions, Weighting},
+    };
+
+    use super::CCHTopology;
+
+    const GRID_SIZE: usize = 6;
+
+    /// Grid of roads, the first row is a primary road and the second one is forbidden to trucks.
+    /// The third column is one way northwards
+    fn create_grid_graph() -> BaseGraph {
+        let ways = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="1"><tag k="highway" v="residential"/></way>
+                <way id="2"><tag k="highway" v="primary"/></way>
+                <way id="3"><tag k="highway" v="residential"/><tag k="hgv" v="no"/></way>
+                <way id="4"><tag k="highway" v="residential"/><tag k="oneway" v="yes"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap()
+        .ways;
+        let properties = |index: usize| {
+            parse_way_properties(&OsmWay::new(ways[index].id as usize, &ways[index].tags))
+        };
+        let point = |node: usize| {
+            GeoPoint::new(
+                (node % GRID_SIZE) as f64 * 0.01,
+                (node / GRID_SIZE) as f64 * 0.01,
+            )
+        };
+
+        let mut graph = BaseGraph::default();
+        for node in 0..GRID_SIZE * GRID_SIZE {
+            graph.add_node(node);
+        }
+
+        for row in 0..GRID_SIZE {
+            for column in 0..GRID_SIZE {
+                let node = row * GRID_SIZE + column;
+
+                if column + 1 < GRID_SIZE {
+                    let way = match row {
+                        0 => 1,
+                        1 => 2,
+                        _ => 0,
+                    };
+                    graph.add_edge(
+                        node,
+                        node + 1,
+                        properties(way),
+                        vec![point(node), point(node + 1)],
+                    );
+                }
+
+                if row + 1 < GRID_SIZE {
+                    let way = if column == 2 { 3 } else { 0 };
+                    let above = node + GRID_SIZE;
+                    graph.add_edge(
+                        node,
+                        above,
+                        properties(way),
+                        vec![point(node), point(above)],
+                    );
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn assert_same_weights(
+        graph: &BaseGraph,
+        topology: &CCHTopology,
+        weighting: &impl Weighting<BaseGraph>,
+    ) {
+        let ch_storage = topology.customize(graph, weighting);
+        ch_storage.check();
+
+        let ch_graph = CHGraph::new(&ch_storage, graph);
+        let query_graph = QueryGraph::from_graph(&ch_graph, graph, &mut []);
+        let ch_weighting = CHWeighting::new();
+        let one_to_many = OneToManyAlgorithm::new(&query_graph, &ch_weighting);
+
+        let nodes = (0..graph.node_count()).collect::<Vec<_>>();
+        let expected = DijkstraMatrixAlgorithm::new(graph, weighting).calc_matrix(&nodes, &nodes);
+
+        for &source in &nodes {
+            let customized = one_to_many.calc_one_to_many(source, &nodes);
+            for &target in &nodes {
+                assert_eq!(
+                    customized.matrix.weight(0, target),
+                    expected.matrix.weight(source, target),
+                    "{source} -> {target}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_customize() {
+        let graph = create_grid_graph();
+        let ch_storage = CHGraphBuilder::from_base_graph(&graph).build(&CarWeighting::new());
+        let topology = CCHTopology::build(&graph, ch_storage.ranks());
+
+        assert_same_weights(&graph, &topology, &CarWeighting::new());
+        assert_same_weights(
+            &graph,
+            &topology,
+            &TruckWeighting::new(VehicleDimensions::default()),
+        );
+    }
+
+    #[test]
+    fn test_customize_nested_dissection() {
+        let graph = create_grid_graph();
+        let ranks = nested_dissection_ranks(&graph);
+
+        let mut sorted_ranks = ranks.clone();
+        sorted_ranks.sort_unstable();
+        assert_eq!(sorted_ranks, (0..graph.node_count()).collect::<Vec<_>>());
+
+        let topology = CCHTopology::build(&graph, &ranks);
+        for profile in ProfileConfig::defaults() {
+            let weighting = ProfileWeighting::new(Arc::new(profile), VehicleDimensions::default());
+            assert_same_weights(&graph, &topology, &weighting);
+        }
+    }
+}
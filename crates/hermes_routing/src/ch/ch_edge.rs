@@ -1,8 +1,10 @@
 use crate::{
+    base_graph::{BaseGraph, BaseGraphEdge},
     distance::{Distance, Meters},
+    edge_direction::EdgeDirection,
     graph_edge::GraphEdge,
     types::{EdgeId, NodeId},
-    weighting::{Milliseconds, Weight},
+    weighting::{Milliseconds, Weight, Weighting},
 };
 
 use super::shortcut::Shortcut;
@@ -20,6 +22,22 @@ pub struct CHBaseEdge {
     pub backward_weight: Weight,
 }
 
+impl CHBaseEdge {
+    /// The edge with its weights and times in both directions
+    pub fn new(edge: &BaseGraphEdge, weighting: &impl Weighting<BaseGraph>) -> Self {
+        CHBaseEdge {
+            id: edge.id(),
+            start: edge.start_node(),
+            end: edge.end_node(),
+            distance: edge.distance(),
+            forward_time: weighting.calc_edge_ms(edge, EdgeDirection::Forward),
+            backward_time: weighting.calc_edge_ms(edge, EdgeDirection::Backward),
+            forward_weight: weighting.calc_edge_weight(edge, EdgeDirection::Forward),
+            backward_weight: weighting.calc_edge_weight(edge, EdgeDirection::Backward),
+        }
+    }
+}
+
 #[derive(Debug, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
 pub enum CHGraphEdge {
     Shortcut(Shortcut),
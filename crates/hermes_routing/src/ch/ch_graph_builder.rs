@@ -4,13 +4,13 @@ use std::{
 };
 
 use rand::distr::{Distribution, Uniform};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use tracing::{debug, info};
 
 use crate::{
     base_graph::BaseGraph,
     ch::{ch_edge::CHBaseEdge, ch_storage::CHStorage, priority_queue::PriorityQueue},
-    edge_direction::EdgeDirection,
-    graph::{Graph, UndirectedEdgeAccess},
+    graph::{Graph, TurnRestrictionAccess, UndirectedEdgeAccess},
     graph_edge::GraphEdge,
     stopwatch::Stopwatch,
     types::NodeId,
@@ -25,8 +25,59 @@ use super::{
     witness_search::WitnessSearch,
 };
 
+/// Tuning of the contraction, the defaults are a good fit for road networks
+#[derive(Clone, Debug)]
+pub struct CHPreparationParams {
+    /// Nodes settled by the witness searches estimating the priority of a node, as a multiple of
+    /// the mean degree of the graph
+    pub priority_settled_nodes_factor: f64,
+
+    /// Nodes settled by the witness searches contracting a node, as a multiple of the mean degree
+    /// of the graph
+    pub contraction_settled_nodes_factor: f64,
+
+    /// Edges of a witness path, unlimited when not set
+    pub max_hops: Option<u16>,
+
+    /// Weight of the level of the node in the hierarchy in its priority
+    pub hierarchy_weight: f32,
+
+    /// Weight of the number of shortcuts added per edge of the node in its priority
+    pub edge_quotient_weight: f32,
+
+    /// Neighbors of a contracted node whose priority is recomputed
+    pub neighbor_updates: usize,
+
+    /// The priorities of all the remaining nodes are recomputed after this many contractions
+    pub full_update_interval: usize,
+
+    /// Nodes with the lowest priorities contracted at once, only the ones which share no neighbor
+    /// with each other. Their shortcuts are searched in parallel, 1 contracts the nodes one by one
+    pub batch_size: usize,
+
+    /// Share of the nodes contracted in percent, the other ones are only ranked
+    pub contracted_percentage: u32,
+}
+
+impl Default for CHPreparationParams {
+    fn default() -> Self {
+        CHPreparationParams {
+            priority_settled_nodes_factor: 5.0,
+            contraction_settled_nodes_factor: 200.0,
+            max_hops: None,
+            hierarchy_weight: 20.0,
+            edge_quotient_weight: 100.0,
+            neighbor_updates: 3,
+            full_update_interval: 500000,
+            batch_size: 128,
+            contracted_percentage: 100,
+        }
+    }
+}
+
 pub struct CHGraphBuilder<'a> {
     base_graph: &'a BaseGraph,
+    params: CHPreparationParams,
     build_stopwatch: Stopwatch,
     recompute_priority_stopwatch: Stopwatch,
     recompute_neighbors_priority_stopwatch: Stopwatch,
@@ -40,6 +91,7 @@ impl<'a> CHGraphBuilder<'a> {
     pub fn from_base_graph(base_graph: &'a BaseGraph) -> Self {
         Self {
             base_graph,
+            params: CHPreparationParams::default(),
             build_stopwatch: Stopwatch::new(String::from("build_ch_graph")),
             recompute_priority_stopwatch: Stopwatch::new(String::from("recompute_priority")),
             recompute_neighbors_priority_stopwatch: Stopwatch::new(String::from(
@@ -52,6 +104,11 @@ impl<'a> CHGraphBuilder<'a> {
         }
     }
 
+    pub fn set_params(&mut self, params: CHPreparationParams) -> &mut Self {
+        self.params = params;
+        self
+    }
+
     pub fn build<W>(&mut self, weighting: &W) -> CHStorage
     where
         W: Weighting<BaseGraph> + Send + Sync,
@@ -62,28 +119,31 @@ impl<'a> CHGraphBuilder<'a> {
         let mut rng = rand::rng();
         let dist = Uniform::new_inclusive(0, 100).unwrap();
 
+        let node_count = self.base_graph.node_count();
         let mut ch_storage = CHStorage::new(self.base_graph);
         let mut preparation_graph = CHPreparationGraph::new(self.base_graph, weighting);
         let preparation_weighting = PreparationGraphWeighting::new(weighting);
-        let mut witness_search = WitnessSearch::new();
-        let mut priority_queue = PriorityQueue::new(self.base_graph.node_count());
-        let mut hierarchies = vec![0; self.base_graph.node_count()];
+        let mut priority_queue = PriorityQueue::new(node_count);
+        let mut hierarchies = vec![0; node_count];
+
+        // The nodes of the batch being contracted and their neighbors
+        let mut reserved = vec![false; node_count];
+        let mut reserved_nodes = Vec::new();
 
         info!("Start CH contraction");
         info!(
-            node_count = self.base_graph.node_count(),
+            node_count = node_count,
             edge_count = self.base_graph.edge_count(),
+            threads = rayon::current_num_threads(),
         );
 
-        for node_id in 0..self.base_graph.node_count() {
-            let priority = self.calc_priority(
-                &mut preparation_graph,
-                &mut witness_search,
-                &preparation_weighting,
-                0,
-                node_id,
-            );
-
+        let nodes = (0..node_count).collect::<Vec<_>>();
+        for (node_id, priority) in self.calc_priorities(
+            &preparation_graph,
+            &preparation_weighting,
+            &hierarchies,
+            &nodes,
+        ) {
             priority_queue
                 .push(node_id, priority)
                 .unwrap_or_else(|err| panic!("{}", err));
@@ -92,134 +152,133 @@ impl<'a> CHGraphBuilder<'a> {
         info!("Finish computing priority for every node");
 
         let mut rank = 0;
+        let mut contracted_since_full_update = 0;
 
-        while let Some((node_id, priority)) = priority_queue.pop() {
-            // Lazy recomputation of the priority
-            // If the recomputed priority is less than the next node to be contracted, we re-enqueue the node
-
-            if priority != i32::MIN
-                && let Some((_, least_priority)) = priority_queue.peek() {
-                    self.recompute_priority_stopwatch.start();
-                    let recomputed_priority = self.calc_priority(
-                        &mut preparation_graph,
-                        &mut witness_search,
-                        &preparation_weighting,
-                        hierarchies[node_id],
-                        node_id,
-                    );
-                    self.recompute_priority_stopwatch.stop();
+        loop {
+            let batch = self.next_batch(
+                &preparation_graph,
+                &preparation_weighting,
+                &mut priority_queue,
+                &hierarchies,
+                &mut reserved,
+                &mut reserved_nodes,
+            );
 
-                    if recomputed_priority > *least_priority {
-                        priority_queue
-                            .push(node_id, recomputed_priority)
-                            .unwrap_or_else(|err| panic!("{}", err));
-                        continue;
-                    }
+            if batch.is_empty() {
+                if priority_queue.peek().is_none() {
+                    break;
                 }
 
-            let mut neighbors = Vec::new();
-
-            for edge_id in preparation_graph.node_edges_iter(node_id) {
-                let edge = preparation_graph.edge(edge_id);
-                let adj_node = edge.adj_node(node_id);
-
-                if node_id != adj_node {
-                    neighbors.push(adj_node);
-                }
+                continue;
+            }
 
-                match edge {
-                    CHPreparationGraphEdge::Edge(base_edge) => {
-                        let forward_weight =
-                            weighting.calc_edge_weight(base_edge, EdgeDirection::Forward);
-                        let forward_time =
-                            weighting.calc_edge_ms(base_edge, EdgeDirection::Forward);
-
-                        // From end to start
-                        let backward_weight =
-                            weighting.calc_edge_weight(base_edge, EdgeDirection::Backward);
-                        let backward_time =
-                            weighting.calc_edge_ms(base_edge, EdgeDirection::Backward);
-
-                        ch_storage.add_edge(CHBaseEdge {
-                            id: base_edge.id(),
-                            start: edge.start_node(),
-                            end: edge.end_node(),
-                            distance: base_edge.distance(),
-                            forward_time,
-                            backward_time,
-                            forward_weight,
-                            backward_weight,
-                        });
-                    }
-                    CHPreparationGraphEdge::Shortcut(shortcut) => {
-                        ch_storage.add_shortcut(shortcut.clone());
-                        self.added_shortcuts += 1;
-                    }
+            // Only contract a share of the nodes
+            let skipped = batch
+                .iter()
+                .map(|&node_id| {
+                    preparation_graph.node_degree(node_id) == 0
+                        || dist.sample(&mut rng) > self.params.contracted_percentage
+                })
+                .collect::<Vec<_>>();
+
+            // The nodes of the batch are at least 3 edges apart, contracting one of them does not
+            // change the edges around the others nor the shortcuts they need
+            self.contract_node_stopwatch.start();
+            let max_settled_nodes = (preparation_graph.mean_degree()
+                * self.params.contraction_settled_nodes_factor)
+                .round() as usize;
+            let batch_shortcuts = batch
+                .par_iter()
+                .zip(skipped.par_iter())
+                .map_init(
+                    WitnessSearch::new,
+                    |witness_search, (&node_id, &skipped)| {
+                        if skipped {
+                            return vec![];
+                        }
+
+                        CHGraphBuilder::find_shortcuts(
+                            &preparation_graph,
+                            witness_search,
+                            &preparation_weighting,
+                            node_id,
+                            max_settled_nodes,
+                            self.params.max_hops,
+                        )
+                    },
+                )
+                .collect::<Vec<_>>();
+            self.contract_node_stopwatch.stop();
+
+            let mut updated_neighbors = Vec::new();
+
+            for ((&node_id, skipped), shortcuts) in batch.iter().zip(skipped).zip(batch_shortcuts) {
+                let neighbors =
+                    self.add_node_edges(&mut ch_storage, &preparation_graph, weighting, node_id);
+
+                // Update hierarchy of neighbors
+                for &neighbor in neighbors.iter() {
+                    hierarchies[neighbor] =
+                        cmp::max(hierarchies[neighbor], hierarchies[node_id] + 1);
                 }
-            }
 
-            // Update hierarchy of neighbors
-            for &neighbor in neighbors.iter() {
-                hierarchies[neighbor] = cmp::max(hierarchies[neighbor], hierarchies[node_id] + 1);
-            }
+                ch_storage.set_node_rank(node_id, rank);
+                rank += 1;
 
-            ch_storage.set_node_rank(node_id, rank);
-            rank += 1;
+                if skipped {
+                    self.skipped_nodes += 1;
+                } else {
+                    for shortcut in shortcuts {
+                        preparation_graph.add_shortcut(shortcut);
+                    }
 
-            // Only contract 95% of nodes
-            let percentage = 100;
+                    self.contracted_nodes += 1;
+                    contracted_since_full_update += 1;
+                }
 
-            if preparation_graph.node_degree(node_id) == 0 || dist.sample(&mut rng) > percentage {
-                self.skipped_nodes += 1;
                 preparation_graph.disconnect_node(node_id);
-                continue;
-            }
 
-            self.contract_node(
-                &mut preparation_graph,
-                &mut witness_search,
-                &preparation_weighting,
-                node_id,
-            );
+                updated_neighbors.extend(neighbors.into_iter().take(self.params.neighbor_updates));
+            }
 
-            self.contracted_nodes += 1;
+            for node_id in reserved_nodes.drain(..) {
+                reserved[node_id] = false;
+            }
 
-            // TODO: better condition
-            if self.contracted_nodes.is_multiple_of(500000) && self.added_shortcuts > 0 {
+            if contracted_since_full_update >= self.params.full_update_interval
+                && self.added_shortcuts > 0
+            {
                 debug!("Recompute all remaining priorities");
-                let remaining_nodes: Vec<(NodeId, i32)> = priority_queue.to_vec();
-                priority_queue.clear();
-                for (node_id, _) in remaining_nodes {
-                    let priority = self.calc_priority(
-                        &mut preparation_graph,
-                        &mut witness_search,
-                        &preparation_weighting,
-                        hierarchies[node_id],
-                        node_id,
-                    );
+                contracted_since_full_update = 0;
 
+                let remaining_nodes = priority_queue
+                    .to_vec()
+                    .into_iter()
+                    .map(|(node_id, _)| node_id)
+                    .collect::<Vec<_>>();
+                priority_queue.clear();
+                for (node_id, priority) in self.calc_priorities(
+                    &preparation_graph,
+                    &preparation_weighting,
+                    &hierarchies,
+                    &remaining_nodes,
+                ) {
                     priority_queue
                         .push(node_id, priority)
                         .unwrap_or_else(|err| panic!("{}", err));
                 }
             } else {
-                let max_neighbor_update = 3;
-                let mut neighbor_count = 0;
-                self.recompute_neighbors_priority_stopwatch.start();
-                for neighbor in neighbors {
-                    let recomputed_neighbor_priority = self.calc_priority(
-                        &mut preparation_graph,
-                        &mut witness_search,
-                        &preparation_weighting,
-                        hierarchies[neighbor],
-                        neighbor,
-                    );
+                updated_neighbors.sort_unstable();
+                updated_neighbors.dedup();
 
-                    priority_queue.update_priority(neighbor, recomputed_neighbor_priority);
-                    neighbor_count += 1;
-                    if neighbor_count >= max_neighbor_update {
-                        break;
-                    }
+                self.recompute_neighbors_priority_stopwatch.start();
+                for (neighbor, priority) in self.calc_priorities(
+                    &preparation_graph,
+                    &preparation_weighting,
+                    &hierarchies,
+                    &updated_neighbors,
+                ) {
+                    priority_queue.update_priority(neighbor, priority);
                 }
                 self.recompute_neighbors_priority_stopwatch.stop();
             }
@@ -243,6 +302,107 @@ impl<'a> CHGraphBuilder<'a> {
         ch_storage
     }
 
+    /// Pops the next nodes to contract: the nodes with the lowest priorities which share no
+    /// neighbor with each other. A node is only taken when neither it nor its neighbors are
+    /// reserved, it then reserves itself and its neighbors until the batch is contracted. The
+    /// other popped nodes are pushed back.
+    fn next_batch(
+        &mut self,
+        graph: &CHPreparationGraph<'a>,
+        weighting: &(impl Weighting<CHPreparationGraph<'a>> + Sync),
+        priority_queue: &mut PriorityQueue<i32>,
+        hierarchies: &[usize],
+        reserved: &mut [bool],
+        reserved_nodes: &mut Vec<NodeId>,
+    ) -> Vec<NodeId> {
+        let mut candidates = Vec::with_capacity(self.params.batch_size);
+        while candidates.len() < self.params.batch_size.max(1)
+            && let Some(candidate) = priority_queue.pop()
+        {
+            candidates.push(candidate);
+        }
+
+        // Lazy recomputation of the priorities, the nodes whose recomputed priority is higher
+        // than the next node to be contracted are pushed back
+        self.recompute_priority_stopwatch.start();
+        let least_priority = priority_queue.peek().map(|&(_, priority)| priority);
+        let recomputed = candidates
+            .iter()
+            .filter(|&&(_, priority)| priority != i32::MIN && least_priority.is_some())
+            .map(|&(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        let mut recomputed = self
+            .calc_priorities(graph, weighting, hierarchies, &recomputed)
+            .into_iter();
+        self.recompute_priority_stopwatch.stop();
+
+        let mut batch = Vec::with_capacity(candidates.len());
+        for (node_id, priority) in candidates {
+            let priority = if priority != i32::MIN && least_priority.is_some() {
+                recomputed.next().unwrap().1
+            } else {
+                priority
+            };
+
+            let independent = !reserved[node_id]
+                && graph
+                    .node_edges_iter(node_id)
+                    .all(|edge_id| !reserved[graph.edge(edge_id).adj_node(node_id)]);
+            if !independent || least_priority.is_some_and(|least| priority > least) {
+                priority_queue
+                    .push(node_id, priority)
+                    .unwrap_or_else(|err| panic!("{}", err));
+                continue;
+            }
+
+            reserved[node_id] = true;
+            reserved_nodes.push(node_id);
+            for edge_id in graph.node_edges_iter(node_id) {
+                let adj_node = graph.edge(edge_id).adj_node(node_id);
+                reserved[adj_node] = true;
+                reserved_nodes.push(adj_node);
+            }
+            batch.push(node_id);
+        }
+
+        batch
+    }
+
+    /// Adds the remaining edges of the node to the hierarchy, returns its neighbors
+    fn add_node_edges<W>(
+        &mut self,
+        ch_storage: &mut CHStorage,
+        graph: &CHPreparationGraph<'a>,
+        weighting: &W,
+        node_id: NodeId,
+    ) -> Vec<NodeId>
+    where
+        W: Weighting<BaseGraph> + Send + Sync,
+    {
+        let mut neighbors = Vec::new();
+
+        for edge_id in graph.node_edges_iter(node_id) {
+            let edge = graph.edge(edge_id);
+            let adj_node = edge.adj_node(node_id);
+
+            if node_id != adj_node {
+                neighbors.push(adj_node);
+            }
+
+            match edge {
+                CHPreparationGraphEdge::Edge(base_edge) => {
+                    ch_storage.add_edge(CHBaseEdge::new(base_edge, weighting));
+                }
+                CHPreparationGraphEdge::Shortcut(shortcut) => {
+                    ch_storage.add_shortcut(shortcut.clone());
+                    self.added_shortcuts += 1;
+                }
+            }
+        }
+
+        neighbors
+    }
+
     fn report_timings(&self, preparation_graph: &CHPreparationGraph) {
         let current_duration = self.build_stopwatch.elapsed();
         let contract_node_duration = self.contract_node_stopwatch.total_duration();
@@ -250,6 +410,13 @@ impl<'a> CHGraphBuilder<'a> {
         let recompute_neighbors_duration =
             self.recompute_neighbors_priority_stopwatch.total_duration();
 
+        let processed_nodes = self.contracted_nodes + self.skipped_nodes;
+        info!(
+            "Contracted {:.1}% of the nodes, {} shortcuts added",
+            processed_nodes as f64 / self.base_graph.node_count().max(1) as f64 * 100.0,
+            self.added_shortcuts
+        );
+
         println!(
             "{:20} {:20} {:20} {:20} {:20} {:20} {:20} {:20} {:20}",
             "Total",
@@ -285,37 +452,41 @@ impl<'a> CHGraphBuilder<'a> {
         format!("{percentage:.2}%")
     }
 
-    fn contract_node(
-        &mut self,
-        graph: &mut CHPreparationGraph<'a>,
-        witness_search: &mut WitnessSearch,
-        weighting: &impl Weighting<CHPreparationGraph<'a>>,
-        node: NodeId,
-    ) {
-        self.contract_node_stopwatch.start();
-
-        let shortcuts = CHGraphBuilder::find_shortcuts(
-            graph,
-            witness_search,
-            weighting,
-            node,
-            (graph.mean_degree() * 200.0).round() as usize,
-        );
-
-        for shortcut in shortcuts {
-            graph.add_shortcut(shortcut);
-        }
-
-        graph.disconnect_node(node);
-
-        self.contract_node_stopwatch.stop();
+    /// Priorities of the nodes, computed in parallel with a witness search per thread
+    fn calc_priorities(
+        &self,
+        graph: &CHPreparationGraph<'a>,
+        weighting: &(impl Weighting<CHPreparationGraph<'a>> + Sync),
+        hierarchies: &[usize],
+        nodes: &[NodeId],
+    ) -> Vec<(NodeId, i32)> {
+        let params = &self.params;
+        let max_settled_nodes =
+            (graph.mean_degree() * params.priority_settled_nodes_factor).round() as usize;
+
+        nodes
+            .par_iter()
+            .map_init(WitnessSearch::new, |witness_search, &node| {
+                let priority = CHGraphBuilder::calc_priority(
+                    graph,
+                    witness_search,
+                    weighting,
+                    params,
+                    max_settled_nodes,
+                    hierarchies[node],
+                    node,
+                );
+                (node, priority)
+            })
+            .collect()
     }
 
     fn calc_priority(
-        &mut self,
-        graph: &mut CHPreparationGraph<'a>,
+        graph: &CHPreparationGraph<'a>,
         witness_search: &mut WitnessSearch,
         weighting: &impl Weighting<CHPreparationGraph<'a>>,
+        params: &CHPreparationParams,
+        max_settled_nodes: usize,
         hierarchy: usize,
         node: NodeId,
     ) -> i32 {
@@ -324,7 +495,8 @@ impl<'a> CHGraphBuilder<'a> {
             witness_search,
             weighting,
             node,
-            (graph.mean_degree() * 5.0).round() as usize,
+            max_settled_nodes,
+            params.max_hops,
         );
 
         let degree = graph.node_degree(node);
@@ -335,16 +507,18 @@ impl<'a> CHGraphBuilder<'a> {
         }
 
         let edge_quotient = (shortcuts.len() as f32) / (degree as f32);
-        let priority = (hierarchy as f32 * 20.0) + (edge_quotient * 100.0);
+        let priority = (hierarchy as f32 * params.hierarchy_weight)
+            + (edge_quotient * params.edge_quotient_weight);
         (priority * 1000.0).round() as i32
     }
 
     fn find_shortcuts(
-        graph: &mut CHPreparationGraph<'a>,
+        graph: &CHPreparationGraph<'a>,
         witness_search: &mut WitnessSearch,
         weighting: &impl Weighting<CHPreparationGraph<'a>>,
         node: NodeId,
         max_settled_nodes: usize,
+        max_hops: Option<u16>,
     ) -> Vec<PreparationShortcut> {
         let mut shortcuts = Vec::new();
 
@@ -382,6 +556,12 @@ impl<'a> CHGraphBuilder<'a> {
                     continue;
                 }
 
+                // The path through the node is forbidden, it must not be reachable with a
+                // shortcut either
+                if !graph.is_turn_allowed(incoming_edge_id, node, outgoing_edge_id) {
+                    continue;
+                }
+
                 let outgoing_direction = graph.edge_direction(outgoing_edge_id, node);
 
                 let weight = weighting.calc_edge_weight(incoming_edge, incoming_direction)
@@ -393,7 +573,7 @@ impl<'a> CHGraphBuilder<'a> {
                     outgoing_edge_adj_node,
                     weight,
                     max_settled_nodes,
-                    10,
+                    max_hops,
                 );
 
                 if witness_search_weight <= weight {
@@ -416,3 +596,108 @@ impl<'a> CHGraphBuilder<'a> {
         shortcuts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use crate::{
+        ch::{ch_graph::CHGraph, ch_weighting::CHWeighting},
+        geopoint::GeoPoint,
+        matrix::{
+            dijkstra_matrix_algorithm::DijkstraMatrixAlgorithm, matrix_algorithm::MatrixAlgorithm,
+            one_to_many_algorithm::OneToManyAlgorithm,
+        },
+        osm::{
+            osm_change::OsmChange,
+            osm_reader::{OsmWay, parse_way_properties},
+        },
+        query::query_graph::QueryGraph,
+        weighting::CarWeighting,
+    };
+
+    use super::*;
+
+    const NODE_COUNT: usize = 300;
+
+    /// Connected graph of residential roads between random points, the first edges form a tree
+    /// and the other ones close cycles
+    fn create_random_graph(seed: u64) -> BaseGraph {
+        let ways = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="1"><tag k="highway" v="residential"/></way>
+                <way id="2"><tag k="highway" v="residential"/><tag k="oneway" v="yes"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap()
+        .ways;
+        let properties = |index: usize| {
+            parse_way_properties(&OsmWay::new(ways[index].id as usize, &ways[index].tags))
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let points = (0..NODE_COUNT)
+            .map(|_| GeoPoint::new(rng.random_range(0.0..0.1), rng.random_range(0.0..0.1)))
+            .collect::<Vec<_>>();
+
+        let mut graph = BaseGraph::default();
+        for node in 0..NODE_COUNT {
+            graph.add_node(node);
+        }
+
+        let add_edge = |graph: &mut BaseGraph, from: usize, to: usize, way: usize| {
+            graph.add_edge(from, to, properties(way), vec![points[from], points[to]]);
+        };
+
+        for node in 1..NODE_COUNT {
+            let parent = rng.random_range(0..node);
+            add_edge(&mut graph, parent, node, 0);
+        }
+
+        for _ in 0..NODE_COUNT * 2 {
+            let from = rng.random_range(0..NODE_COUNT);
+            let to = rng.random_range(0..NODE_COUNT);
+            if from != to {
+                let way = rng.random_range(0..2);
+                add_edge(&mut graph, from, to, way);
+            }
+        }
+
+        graph
+    }
+
+    #[test]
+    fn test_parallel_contraction_matches_dijkstra() {
+        for seed in 0..3 {
+            let graph = create_random_graph(seed);
+            let weighting = CarWeighting::new();
+
+            let ch_storage = CHGraphBuilder::from_base_graph(&graph)
+                .set_params(CHPreparationParams {
+                    batch_size: 64,
+                    ..CHPreparationParams::default()
+                })
+                .build(&weighting);
+
+            let ch_graph = CHGraph::new(&ch_storage, &graph);
+            let query_graph = QueryGraph::from_graph(&ch_graph, &graph, &mut []);
+            let ch_weighting = CHWeighting::new();
+            let one_to_many = OneToManyAlgorithm::new(&query_graph, &ch_weighting);
+
+            let nodes = (0..graph.node_count()).collect::<Vec<_>>();
+            let expected =
+                DijkstraMatrixAlgorithm::new(&graph, &weighting).calc_matrix(&nodes, &nodes);
+
+            for &source in &nodes {
+                let contracted = one_to_many.calc_one_to_many(source, &nodes);
+                for &target in &nodes {
+                    assert_eq!(
+                        contracted.matrix.weight(0, target),
+                        expected.matrix.weight(source, target),
+                        "seed {seed}: {source} -> {target}"
+                    );
+                }
+            }
+        }
+    }
+}
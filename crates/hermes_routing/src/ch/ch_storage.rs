@@ -4,10 +4,11 @@ use crate::{
     base_graph::BaseGraph,
     constants::{INVALID_EDGE, INVALID_NODE, MAX_DURATION, MAX_WEIGHT},
     distance::{Distance, Meters},
+    error::StorageError,
     graph::Graph,
     graph_edge::GraphEdge,
     meters,
-    storage::{read_bytes, write_bytes},
+    storage::{FileKind, read_file, write_file},
     types::{EdgeId, NodeId},
     weighting::Weight,
 };
@@ -34,16 +35,17 @@ pub struct CHStorage {
 impl CHStorage {
     pub fn save_to_file(&self, path: &str) -> Result<(), std::io::Error> {
         let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(self).expect("to_bytes failed");
-        write_bytes(&bytes[..], path)
+        write_file(path, FileKind::CHGraph, &bytes[..])
     }
 
-    pub fn from_file(path: &str) -> Self {
+    pub fn from_file(path: &str) -> Result<Self, StorageError> {
         debug!("Reading from path {}", path);
-        let bytes = read_bytes(path);
+        let bytes = read_file(path, FileKind::CHGraph)?;
         debug!("Read from path {}, size {}", path, bytes.len());
-        let data = rkyv::from_bytes::<Self, rkyv::rancor::Error>(&bytes[..]).unwrap();
+        let data = rkyv::from_bytes::<Self, rkyv::rancor::Error>(&bytes[..])
+            .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
         info!("Deserialized ch storage from buffer");
-        data
+        Ok(data)
     }
 
     pub fn new(base_graph: &BaseGraph) -> Self {
@@ -77,6 +79,10 @@ impl CHStorage {
         self.ranks[node_id]
     }
 
+    pub fn ranks(&self) -> &[usize] {
+        &self.ranks
+    }
+
     pub fn set_node_rank(&mut self, node: NodeId, rank: usize) {
         self.ranks[node] = rank;
     }
@@ -1,8 +1,10 @@
+pub(crate) mod cch_topology;
 pub(crate) mod ch_edge;
 pub mod ch_graph;
 pub mod ch_graph_builder;
 pub(crate) mod ch_storage;
 pub(crate) mod ch_weighting;
+pub(crate) mod nested_dissection;
 mod preparation_graph;
 mod priority_queue;
 mod shortcut;
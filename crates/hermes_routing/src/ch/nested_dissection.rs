@@ -0,0 +1,119 @@
+use tracing::info;
+
+use crate::{
+    base_graph::BaseGraph,
+    graph::{GeometryAccess, Graph},
+    graph_edge::GraphEdge,
+    stopwatch::Stopwatch,
+    types::NodeId,
+};
+
+/// The parts with fewer nodes are not split anymore
+const MAX_LEAF_SIZE: usize = 16;
+
+/// Part of the nodes already given a rank
+const SEPARATED: usize = usize::MAX;
+
+/// Contraction order of a CCH topology by nested dissection. The nodes are split in two halves at
+/// the median of their widest coordinate, the nodes of the first half linked to the second one
+/// separate them and are contracted after both halves, which are split in turn. The upper
+/// neighbors of a node then stay within its part and its separators, which keeps the topology
+/// small whatever the weighting.
+pub fn nested_dissection_ranks(base_graph: &BaseGraph) -> Vec<usize> {
+    let mut stopwatch = Stopwatch::new(String::from("nested_dissection"));
+    stopwatch.start();
+
+    let node_count = base_graph.node_count();
+    let mut ranks = vec![0; node_count];
+    let mut parts = vec![0; node_count];
+    let mut part_count = 1;
+
+    // The ranks are given from the top, the separators of a part before its halves
+    let mut next_rank = node_count;
+
+    // The nodes without edges have no geometry, they are contracted first
+    let (isolated, connected): (Vec<NodeId>, Vec<NodeId>) =
+        (0..node_count).partition(|&node| base_graph.node_edges(node).is_empty());
+
+    let mut stack = vec![connected];
+    while let Some(mut nodes) = stack.pop() {
+        if nodes.len() <= MAX_LEAF_SIZE {
+            assign_ranks(&mut ranks, &mut next_rank, &nodes);
+            continue;
+        }
+
+        let by_longitude = is_wider(base_graph, &nodes);
+        let coordinate = |node: NodeId| {
+            let point = base_graph.node_geometry(node);
+            if by_longitude {
+                point.lon()
+            } else {
+                point.lat()
+            }
+        };
+
+        let middle = nodes.len() / 2;
+        nodes.select_nth_unstable_by(middle, |&a, &b| coordinate(a).total_cmp(&coordinate(b)));
+        let second_half = nodes.split_off(middle);
+
+        let (first_part, second_part) = (part_count, part_count + 1);
+        part_count += 2;
+        for &node in &nodes {
+            parts[node] = first_part;
+        }
+        for &node in &second_half {
+            parts[node] = second_part;
+        }
+
+        let (separator, first_half): (Vec<NodeId>, Vec<NodeId>) =
+            nodes.into_iter().partition(|&node| {
+                base_graph
+                    .node_edges(node)
+                    .iter()
+                    .any(|&edge| parts[base_graph.edge(edge).adj_node(node)] == second_part)
+            });
+
+        for &node in &separator {
+            parts[node] = SEPARATED;
+        }
+        assign_ranks(&mut ranks, &mut next_rank, &separator);
+
+        stack.push(first_half);
+        stack.push(second_half);
+    }
+
+    assign_ranks(&mut ranks, &mut next_rank, &isolated);
+
+    stopwatch.stop();
+    info!(
+        "Computed the nested dissection order of {} nodes in {}ms",
+        node_count,
+        stopwatch.total_duration().as_millis()
+    );
+
+    ranks
+}
+
+/// Gives the highest ranks not taken yet to the nodes
+fn assign_ranks(ranks: &mut [usize], next_rank: &mut usize, nodes: &[NodeId]) {
+    for &node in nodes {
+        *next_rank -= 1;
+        ranks[node] = *next_rank;
+    }
+}
+
+/// Whether the nodes spread more in longitude than in latitude
+fn is_wider(base_graph: &BaseGraph, nodes: &[NodeId]) -> bool {
+    let (mut min_lon, mut max_lon) = (f64::MAX, f64::MIN);
+    let (mut min_lat, mut max_lat) = (f64::MAX, f64::MIN);
+
+    for &node in nodes {
+        let point = base_graph.node_geometry(node);
+        min_lon = min_lon.min(point.lon());
+        max_lon = max_lon.max(point.lon());
+        min_lat = min_lat.min(point.lat());
+        max_lat = max_lat.max(point.lat());
+    }
+
+    max_lon - min_lon >= max_lat - min_lat
+}
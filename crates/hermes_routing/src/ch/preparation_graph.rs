@@ -5,7 +5,7 @@ use crate::{
     constants::MAX_WEIGHT,
     distance::{Distance, Meters},
     edge_direction::EdgeDirection,
-    graph::{Graph, UndirectedEdgeAccess},
+    graph::{Graph, TurnRestrictionAccess, UndirectedEdgeAccess},
     graph_edge::GraphEdge,
     properties::property_map::EdgePropertyMap,
     types::{EdgeId, NodeId},
@@ -207,6 +207,40 @@ impl<'a> CHPreparationGraph<'a> {
     pub fn outgoing_edges(&self, node_id: NodeId) -> &[EdgeId] {
         &self.outgoing_edges[node_id]
     }
+
+    /// Base edge of `edge_id` ending at `node`, the edges skipped by the shortcuts are unfolded
+    fn base_edge_at(&self, edge_id: EdgeId, node: NodeId) -> EdgeId {
+        match &self.edges[edge_id] {
+            CHPreparationGraphEdge::Edge(base_edge) => base_edge.id(),
+            CHPreparationGraphEdge::Shortcut(shortcut) if shortcut.end == node => {
+                self.base_edge_at(shortcut.outgoing_edge, node)
+            }
+            CHPreparationGraphEdge::Shortcut(shortcut) => {
+                self.base_edge_at(shortcut.incoming_edge, node)
+            }
+        }
+    }
+}
+
+/// The turns through shortcuts are the turns between the base edges they start and end with
+impl TurnRestrictionAccess for CHPreparationGraph<'_> {
+    fn has_turn_restrictions(&self, node_id: NodeId) -> bool {
+        self.base_graph.has_turn_restrictions(node_id)
+    }
+
+    fn is_turn_allowed(
+        &self,
+        from_edge_id: EdgeId,
+        via_node_id: NodeId,
+        to_edge_id: EdgeId,
+    ) -> bool {
+        !self.has_turn_restrictions(via_node_id)
+            || self.base_graph.is_turn_allowed(
+                self.base_edge_at(from_edge_id, via_node_id),
+                via_node_id,
+                self.base_edge_at(to_edge_id, via_node_id),
+            )
+    }
 }
 
 impl<'a> Graph for CHPreparationGraph<'a> {
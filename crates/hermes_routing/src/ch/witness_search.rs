@@ -133,7 +133,7 @@ impl WitnessSearch {
         target: NodeId,
         max_weight: Weight,
         max_settled_nodes: usize,
-        _max_hops: HopLimit,
+        max_hops: Option<HopLimit>,
     ) -> Weight {
         if self.start_node == target {
             return 0;
@@ -182,9 +182,9 @@ impl WitnessSearch {
                 }
 
                 let next_hops = current_hops + 1;
-                // if next_hops > max_hops {
-                //     continue;
-                // }
+                if max_hops.is_some_and(|max_hops| next_hops > max_hops) {
+                    continue;
+                }
 
                 let next_weight = weight + edge_weight;
 
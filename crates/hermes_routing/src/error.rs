@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::storage::FileKind;
+
 #[derive(Error, Debug)]
 pub enum ImportError {
     #[error("Failed to save graph file")]
@@ -7,7 +9,61 @@ pub enum ImportError {
     #[error("Failed to save LM file")]
     SaveLandmarks(std::io::Error),
     #[error("Failed to save location index file")]
-    SaveLocationIndex(bincode::error::EncodeError),
+    SaveLocationIndex(std::io::Error),
     #[error("Failed to save CH Graph")]
     SaveCHGraph(std::io::Error),
+    #[error("Failed to save CCH topology")]
+    SaveCCHTopology(std::io::Error),
+    #[error("Failed to save administrative areas file")]
+    SaveAdminAreas(std::io::Error),
+    #[error("Failed to open OSM file")]
+    OpenOsmFile(std::io::Error),
+    #[error("Failed to read the boundaries of the OSM file")]
+    ReadBoundaries(osmpbfreader::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("Failed to read {0}")]
+    Read(String, #[source] std::io::Error),
+    #[error("{0} is not a {1} file of this version of Hermes, import the graph again")]
+    MissingHeader(String, FileKind),
+    #[error("{0} is not a {1} file")]
+    WrongKind(String, FileKind),
+    #[error(
+        "{path} has format version {version} (written by {build}) but version {expected} is \
+         expected, import the graph again"
+    )]
+    UnsupportedVersion {
+        path: String,
+        version: u32,
+        expected: u32,
+        build: String,
+    },
+    #[error("{0} is truncated or corrupted, its checksum does not match")]
+    Corrupted(String),
+    #[error("Failed to deserialize {0}: {1}")]
+    Deserialize(String, String),
+}
+
+#[derive(Error, Debug)]
+pub enum RegionError {
+    #[error("Failed to read region file")]
+    Read(std::io::Error),
+    #[error("Invalid GeoJSON region: {0}")]
+    InvalidGeoJson(#[from] geojson::Error),
+    #[error("The region must be a Polygon or a MultiPolygon")]
+    NotAPolygon,
+    #[error("No edge of the graph is within the region")]
+    EmptyRegion,
+    #[error(transparent)]
+    Import(#[from] ImportError),
+}
+
+#[derive(Error, Debug)]
+pub enum OsmChangeError {
+    #[error("Failed to read osmChange file")]
+    Read(std::io::Error),
+    #[error("Invalid osmChange XML: {0}")]
+    InvalidXml(String),
 }
@@ -7,7 +7,7 @@ use crate::{
 pub fn compute_geometry_distance(geometry: &[GeoPoint]) -> Distance<Meters> {
     let mut distance = meters!(0);
     for i in 0..geometry.len() - 1 {
-        distance = distance + geometry[i].haversine_distance(&geometry[i + 1]);
+        distance = distance + meters!(geometry[i].haversine_distance(&geometry[i + 1]));
     }
 
     distance
@@ -20,7 +20,7 @@ pub fn closest_point_index(points: &[GeoPoint], point: &GeoPoint) -> Option<usiz
         .min_by(|(_, p), (_, p2)| {
             point
                 .haversine_distance(p)
-                .cmp(&point.haversine_distance(p2))
+                .total_cmp(&point.haversine_distance(p2))
         })
         .map(|v| v.0)
 }
@@ -62,7 +62,7 @@ pub fn create_virtual_geometry_between_points(
 
     sorted_points.sort_by(|a, b| {
         a.haversine_distance(&geometry[0])
-            .cmp(&b.haversine_distance(&geometry[0]))
+            .total_cmp(&b.haversine_distance(&geometry[0]))
     });
 
     sorted_points.into_iter().cloned().collect::<Vec<_>>()
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+use crate::geopoint::GeoPoint;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Format of the geometries returned by the API, the raw coordinates are the heaviest
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometryEncoding {
+    /// `[lon, lat]` arrays
+    #[default]
+    Coordinates,
+    /// Encoded polyline with 5 decimals, as Google Maps
+    ///
+    /// https://developers.google.com/maps/documentation/utilities/polylinealgorithm
+    Polyline5,
+    /// Encoded polyline with 6 decimals, as OSRM and Valhalla
+    Polyline6,
+    /// Base64 of the deltas of the coordinates in 1e-7 degrees as zigzag varints, lossless and
+    /// the most compact, see `encode_binary`
+    Binary,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum EncodedGeometry {
+    Coordinates(Vec<[f64; 2]>),
+    Encoded(String),
+}
+
+impl GeometryEncoding {
+    pub fn encode(self, points: &[GeoPoint]) -> EncodedGeometry {
+        match self {
+            GeometryEncoding::Coordinates => EncodedGeometry::Coordinates(
+                points
+                    .iter()
+                    .map(|point| [point.lon(), point.lat()])
+                    .collect(),
+            ),
+            GeometryEncoding::Polyline5 => EncodedGeometry::Encoded(encode_polyline(points, 5)),
+            GeometryEncoding::Polyline6 => EncodedGeometry::Encoded(encode_polyline(points, 6)),
+            GeometryEncoding::Binary => {
+                EncodedGeometry::Encoded(encode_base64(&encode_binary(points)))
+            }
+        }
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn push_polyline_value(encoded: &mut String, value: i64) {
+    let mut value = zigzag(value);
+    while value >= 0x20 {
+        encoded.push(char::from((0x20 | (value & 0x1f)) as u8 + 63));
+        value >>= 5;
+    }
+    encoded.push(char::from(value as u8 + 63));
+}
+
+/// Latitudes and longitudes rounded to `precision` decimals, each one as the difference with the
+/// previous point
+pub fn encode_polyline(points: &[GeoPoint], precision: u32) -> String {
+    let factor = 10_f64.powi(precision as i32);
+    let mut encoded = String::new();
+    let (mut previous_lat, mut previous_lon) = (0, 0);
+
+    for point in points {
+        let lat = (point.lat() * factor).round() as i64;
+        let lon = (point.lon() * factor).round() as i64;
+        push_polyline_value(&mut encoded, lat - previous_lat);
+        push_polyline_value(&mut encoded, lon - previous_lon);
+        (previous_lat, previous_lon) = (lat, lon);
+    }
+
+    encoded
+}
+
+/// `None` when `encoded` is not a polyline
+pub fn decode_polyline(encoded: &str, precision: u32) -> Option<Vec<GeoPoint>> {
+    let factor = 10_f64.powi(precision as i32);
+    let mut bytes = encoded.bytes();
+    let mut next_value = || -> Option<Option<i64>> {
+        let mut value = 0_u64;
+        let mut shift = 0;
+        for byte in bytes.by_ref() {
+            // Longer than a 64 bits value
+            if shift > 60 {
+                return None;
+            }
+
+            let chunk = u64::from(byte.checked_sub(63)?);
+            value |= (chunk & 0x1f) << shift;
+            shift += 5;
+            if chunk < 0x20 {
+                return Some(Some(unzigzag(value)));
+            }
+        }
+
+        // The end of the polyline, unless a value was cut
+        (shift == 0).then_some(None)
+    };
+
+    let mut points = vec![];
+    let (mut lat, mut lon) = (0, 0);
+    while let Some(lat_delta) = next_value()? {
+        lat += lat_delta;
+        lon += next_value()??;
+        points.push(GeoPoint::new(lon as f64 / factor, lat as f64 / factor));
+    }
+
+    Some(points)
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Option<u64> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes.next()?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// The number of points then the latitude and longitude of each point in 1e-7 degrees, as the
+/// difference with the previous point. Each number is a zigzag varint, as in protobuf, so that
+/// the small differences between the consecutive points of a path take one or two bytes
+pub fn encode_binary(points: &[GeoPoint]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(points.len() * 4 + 2);
+    push_varint(&mut bytes, points.len() as u64);
+
+    let (mut previous_lat, mut previous_lon) = (0, 0);
+    for point in points {
+        let (lat, lon) = (i64::from(point.lat_nano()), i64::from(point.lon_nano()));
+        push_varint(&mut bytes, zigzag(lat - previous_lat));
+        push_varint(&mut bytes, zigzag(lon - previous_lon));
+        (previous_lat, previous_lon) = (lat, lon);
+    }
+
+    bytes
+}
+
+/// `None` when `bytes` is not a geometry of `encode_binary`
+pub fn decode_binary(bytes: &[u8]) -> Option<Vec<GeoPoint>> {
+    let mut bytes = bytes.iter().copied();
+    let count = read_varint(&mut bytes)?;
+
+    let mut points = vec![];
+    let (mut lat, mut lon) = (0_i64, 0_i64);
+    for _ in 0..count {
+        lat += unzigzag(read_varint(&mut bytes)?);
+        lon += unzigzag(read_varint(&mut bytes)?);
+        points.push(GeoPoint::from_nano(
+            i32::try_from(lon).ok()?,
+            i32::try_from(lat).ok()?,
+        ));
+    }
+
+    bytes.next().is_none().then_some(points)
+}
+
+/// Standard base64 with padding, to send the binary geometries in JSON
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |group, (index, &byte)| {
+                group | (u32::from(byte) << (16 - 8 * index))
+            });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                encoded.push(char::from(BASE64_ALPHABET[sextet as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn google_example() -> Vec<GeoPoint> {
+        vec![
+            GeoPoint::new(-120.2, 38.5),
+            GeoPoint::new(-120.95, 40.7),
+            GeoPoint::new(-126.453, 43.252),
+        ]
+    }
+
+    #[test]
+    fn test_polyline() {
+        let encoded = encode_polyline(&google_example(), 5);
+        assert_eq!(encoded, "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+
+        let decoded = decode_polyline(&encoded, 5).unwrap();
+        for (decoded, point) in decoded.iter().zip(google_example()) {
+            assert!((decoded.lat() - point.lat()).abs() < 1e-5);
+            assert!((decoded.lon() - point.lon()).abs() < 1e-5);
+        }
+
+        let decoded = decode_polyline(&encode_polyline(&google_example(), 6), 6).unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert!((decoded[2].lon() - -126.453).abs() < 1e-6);
+
+        // Cut in the middle of a value
+        assert_eq!(decode_polyline("_p~iF~ps|U_ul", 5), None);
+    }
+
+    #[test]
+    fn test_binary() {
+        let points = google_example();
+        let bytes = encode_binary(&points);
+
+        assert_eq!(decode_binary(&bytes), Some(points));
+        assert_eq!(decode_binary(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(decode_binary(&encode_binary(&[])), Some(vec![]));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(encode_base64(b"Man"), "TWFu");
+        assert_eq!(encode_base64(b"Ma"), "TWE=");
+        assert_eq!(encode_base64(b"M"), "TQ==");
+        assert_eq!(encode_base64(b""), "");
+    }
+}
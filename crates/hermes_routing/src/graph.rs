@@ -34,6 +34,19 @@ pub trait DirectedEdgeAccess {
     fn node_outgoing_edges_iter(&self, node_id: NodeId) -> Self::EdgeIterator<'_>;
 }
 
+/// Turn table of a graph, see `TurnRestrictions`
+pub trait TurnRestrictionAccess {
+    /// The search algorithms only need to know the edge a node was reached from when the node has
+    /// turn restrictions
+    fn has_turn_restrictions(&self, node_id: NodeId) -> bool;
+    fn is_turn_allowed(
+        &self,
+        from_edge_id: EdgeId,
+        via_node_id: NodeId,
+        to_edge_id: EdgeId,
+    ) -> bool;
+}
+
 pub trait GeometryAccess {
     fn edge_geometry(&self, edge_id: EdgeId) -> &[GeoPoint];
     fn node_geometry(&self, node_id: NodeId) -> &GeoPoint;
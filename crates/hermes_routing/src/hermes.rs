@@ -1,45 +1,81 @@
-use crate::base_graph::BaseGraph;
+use std::path::Path;
+use std::sync::{Arc, mpsc};
+
+use fxhash::FxHashMap;
+use geo::MultiPolygon;
+use rayon::{iter::IndexedParallelIterator, iter::ParallelIterator, slice::ParallelSlice};
+use tracing::info;
+
+use crate::admin_areas::{AdminArea, AdminAreas};
+use crate::avoid::{AvoidOptions, AvoidWeighting, AvoidedEdges};
+use crate::base_graph::{BaseGraph, BaseGraphEdge};
+use crate::ch::cch_topology::CCHTopology;
 use crate::ch::ch_graph::CHGraph;
 use crate::ch::ch_graph_builder::CHGraphBuilder;
 use crate::ch::ch_storage::CHStorage;
 use crate::ch::ch_weighting::CHWeighting;
-use crate::error::ImportError;
+use crate::ch::nested_dissection::nested_dissection_ranks;
+use crate::error::{ImportError, RegionError, StorageError};
+use crate::geometry_encoding::EncodedGeometry;
 use crate::geopoint::GeoPoint;
 use crate::graph::{GeometryAccess, Graph};
+use crate::graph_edge::GraphEdge;
 use crate::landmarks::lm_bidirectional_astar::LMBidirectionalAstar;
 use crate::landmarks::lm_data::LMData;
 use crate::landmarks::lm_preparation::LMPreparation;
 use crate::location_index::LocationIndex;
-use crate::matrix::matrix_algorithm::{MatrixAlgorithm, MatrixAlgorithmResult};
+use crate::matrix::dijkstra_matrix_algorithm::DijkstraMatrixAlgorithm;
+use crate::matrix::matrix::Matrix;
+use crate::matrix::matrix_algorithm::{MatrixAlgorithm, MatrixAlgorithmResult, MatrixBlockResult};
 use crate::matrix::matrix_request::MatrixRequest;
+use crate::matrix::one_to_many_algorithm::OneToManyAlgorithm;
 use crate::matrix::sbi_matrix_algorithm::SBIMatrixAlgorithm;
+use crate::osm::osm_change::{OsmChange, OsmChangeSummary};
+use crate::profile::ProfileConfig;
+use crate::properties::property::Property;
 use crate::query::query_graph::QueryGraph;
 use crate::routing::astar::AStar;
 use crate::routing::bidirectional_astar::BidirectionalAStar;
 use crate::routing::ch_bidirectional_dijkstra::CHBidirectionalAStar;
 use crate::routing::dijkstra::Dijkstra;
 use crate::routing::routing_request::{RoutingAlgorithm, RoutingRequest};
+use crate::routing::search_direction::SearchDirection;
 
 use crate::routing::shortest_path_algorithm::{CalcPath, CalcPathOptions, CalcPathResult};
-use crate::snap::Snap;
+use crate::snap::{Snap, SnapOptions, SnapReport};
+use crate::stopwatch::Stopwatch;
 use crate::storage::binary_file_path;
 use crate::types::NodeId;
-use crate::weighting::{CarWeighting, Weighting};
+use crate::weighting::{CarWeighting, ProfileWeighting, VehicleDimensions};
 
 pub struct Hermes {
     graph: BaseGraph,
     index: LocationIndex,
-    // TODO: Sync + Send, I don't know what I'm doing here
-    // profiles: HashMap<String, Box<dyn Weighting + Sync + Send>>,
-    // car_weighting: CarWeighting<QueryGraph<'a>>,
+    profiles: FxHashMap<String, Arc<ProfileConfig>>,
     lm: LMData,
     ch_storage: Option<CHStorage>,
+    cch_topology: CCHTopology,
+    /// Contraction hierarchies customized on `cch_topology` for the profiles to customize
+    customized_ch: FxHashMap<String, CHStorage>,
+    admin_areas: AdminAreas,
 }
 
 const GRAPH_FILE_NAME: &str = "graph.bin";
 const LANDMARKS_FILE_NAME: &str = "lm.bin";
 const LOCATION_INDEX_FILE_NAME: &str = "location_index.bin";
 const CH_GRAPH_FILE_NAME: &str = "ch_graph.bin";
+const CCH_TOPOLOGY_FILE_NAME: &str = "cch_topology.bin";
+const ADMIN_AREAS_FILE_NAME: &str = "admin_areas.bin";
+const PROFILES_FILE_NAME: &str = "profiles.json";
+
+/// Sources computed together by `Hermes::matrix`
+const MAX_MATRIX_BLOCK_SIZE: usize = 128;
+
+/// Computed blocks of a matrix waiting to be received before the computation is paused
+const MAX_PENDING_MATRIX_BLOCKS: usize = 4;
+
+/// Profile the contraction hierarchies are prepared for, when it has the weights of `CarWeighting`
+const CH_PROFILE: &str = "car";
 
 impl Hermes {
     pub fn save(&self, dir_path: &str) -> Result<(), ImportError> {
@@ -61,56 +97,199 @@ impl Hermes {
                 .map_err(ImportError::SaveCHGraph)?;
         }
 
+        self.cch_topology
+            .save_to_file(binary_file_path(dir_path, CCH_TOPOLOGY_FILE_NAME).as_str())
+            .map_err(ImportError::SaveCCHTopology)?;
+
+        self.admin_areas
+            .save_to_file(binary_file_path(dir_path, ADMIN_AREAS_FILE_NAME).as_str())
+            .map_err(ImportError::SaveAdminAreas)?;
+
         Ok(())
     }
 
-    pub fn from_directory(dir_path: &str) -> Hermes {
-        let graph = BaseGraph::from_file(binary_file_path(dir_path, GRAPH_FILE_NAME).as_str());
+    /// Fails when a file is missing, corrupted or written by a version of Hermes with another
+    /// layout, the graph must then be imported again
+    pub fn from_directory(dir_path: &str) -> Result<Hermes, StorageError> {
+        let graph = BaseGraph::from_file(binary_file_path(dir_path, GRAPH_FILE_NAME).as_str())?;
         let location_index = LocationIndex::load_from_file(
             binary_file_path(dir_path, LOCATION_INDEX_FILE_NAME).as_str(),
-        );
-
-        let lm = LMData::from_file(binary_file_path(dir_path, LANDMARKS_FILE_NAME).as_str());
+        )?;
 
-        // let mut profiles: HashMap<String, Box<dyn Weighting + Sync + Send>> = HashMap::new();
-        // Add default profile
-        // profiles.insert("car".to_string(), Box::from(CarWeighting::new()));
+        let lm = LMData::from_file(binary_file_path(dir_path, LANDMARKS_FILE_NAME).as_str())?;
+        let profiles =
+            ProfileConfig::load_from_file(binary_file_path(dir_path, PROFILES_FILE_NAME).as_str())?;
 
         let ch_storage =
-            CHStorage::from_file(binary_file_path(dir_path, CH_GRAPH_FILE_NAME).as_str());
+            CHStorage::from_file(binary_file_path(dir_path, CH_GRAPH_FILE_NAME).as_str())?;
+
+        // Graphs imported before the CCH topology was saved with them contract it in the order
+        // of their hierarchy, which doesn't need a partition of the graph
+        let cch_topology_path = binary_file_path(dir_path, CCH_TOPOLOGY_FILE_NAME);
+        let cch_topology = if Path::new(&cch_topology_path).exists() {
+            CCHTopology::from_file(cch_topology_path.as_str())?
+        } else {
+            info!("{cch_topology_path} is missing, building the CCH topology from the ranks");
+            CCHTopology::build(&graph, ch_storage.ranks())
+        };
 
-        Hermes {
+        let admin_areas =
+            AdminAreas::load_from_file(binary_file_path(dir_path, ADMIN_AREAS_FILE_NAME).as_str())?;
+
+        let mut hermes = Hermes {
             graph,
             index: location_index,
+            profiles: Self::profiles_by_name(profiles),
             lm,
             ch_storage: Some(ch_storage),
-        }
+            cch_topology,
+            customized_ch: FxHashMap::default(),
+            admin_areas,
+        };
+        hermes.customize_profiles();
+
+        Ok(hermes)
     }
 
-    pub fn from_osm_file(file_path: &str) -> Hermes {
-        let graph = BaseGraph::from_osm_file(file_path);
+    pub fn from_osm_file(file_path: &str) -> Result<Hermes, ImportError> {
+        let admin_areas = AdminAreas::from_osm_file(file_path)?;
+        Ok(Hermes::from_graph(
+            BaseGraph::from_osm_file(file_path),
+            admin_areas,
+        ))
+    }
 
-        // let mut profiles: HashMap<String, Box<dyn Weighting + Sync + Send>> = HashMap::new();
-        // // Add default profile
-        // profiles.insert("car".to_string(), Box::from(CarWeighting::new()));
+    /// Writes to `out_dir` the part of the graph within the region, with its own landmarks, location
+    /// index and contraction hierarchy, so that it can be loaded with `Hermes::from_directory`
+    pub fn extract_region(
+        &self,
+        region: &MultiPolygon<f64>,
+        out_dir: &str,
+    ) -> Result<Hermes, RegionError> {
+        let graph = self.graph.extract_region(region);
+
+        if graph.edge_count() == 0 {
+            return Err(RegionError::EmptyRegion);
+        }
 
-        let weighting = CarWeighting::new();
-        let lm_preparation = LMPreparation::new(&graph, &weighting);
-        let lm = lm_preparation.create_landmarks(10);
+        let hermes = Hermes::from_graph(graph, self.admin_areas.within(region));
+        hermes.save(out_dir)?;
 
-        let index = LocationIndex::build_from_graph(&graph);
+        Ok(hermes)
+    }
 
-        let mut ch_builder = CHGraphBuilder::from_base_graph(&graph);
-        let ch_storage = ch_builder.build(&weighting);
+    fn from_graph(graph: BaseGraph, admin_areas: AdminAreas) -> Hermes {
+        let index = LocationIndex::build_from_graph(&graph);
+        let (lm, ch_storage, cch_topology) = Self::prepare(&graph);
 
-        Hermes {
+        let mut hermes = Hermes {
             graph,
             index,
+            profiles: Self::profiles_by_name(ProfileConfig::defaults()),
             lm,
             ch_storage: Some(ch_storage),
+            cch_topology,
+            customized_ch: FxHashMap::default(),
+            admin_areas,
+        };
+        hermes.customize_profiles();
+
+        hermes
+    }
+
+    /// Landmarks and contraction hierarchies of the car profile. The CCH topology is contracted in
+    /// a nested dissection order, which doesn't favor the car weights over the other profiles
+    fn prepare(graph: &BaseGraph) -> (LMData, CHStorage, CCHTopology) {
+        let weighting = CarWeighting::new();
+        let lm_preparation = LMPreparation::new(graph, &weighting);
+        let lm = lm_preparation.create_landmarks(10);
+
+        let mut ch_builder = CHGraphBuilder::from_base_graph(graph);
+        let ch_storage = ch_builder.build(&weighting);
+        let cch_topology = CCHTopology::build(graph, &nested_dissection_ranks(graph));
+
+        (lm, ch_storage, cch_topology)
+    }
+
+    fn profiles_by_name(profiles: Vec<ProfileConfig>) -> FxHashMap<String, Arc<ProfileConfig>> {
+        profiles
+            .into_iter()
+            .map(|profile| (profile.name.clone(), Arc::new(profile)))
+            .collect()
+    }
+
+    /// Names of the routing profiles of the graph
+    pub fn profile_names(&self) -> Vec<&str> {
+        let mut names = self.profiles.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Customizes the contraction hierarchies of the profiles to customize, in seconds where
+    /// contracting the graph takes minutes
+    fn customize_profiles(&mut self) {
+        let profiles = self
+            .profiles
+            .values()
+            .filter(|profile| profile.customize)
+            .map(|profile| profile.name.clone())
+            .collect::<Vec<_>>();
+
+        for profile in profiles {
+            self.customize_profile(&profile)
+                .expect("the customized profiles exist");
         }
     }
 
+    /// Customizes the contraction hierarchies of the profile on the CCH topology, replacing the
+    /// previous ones
+    pub fn customize_profile(&mut self, profile: &str) -> Result<(), String> {
+        let weighting = self.create_weighting(profile, None)?;
+        let ch_storage = self.cch_topology.customize(&self.graph, &weighting);
+        self.customized_ch.insert(profile.to_owned(), ch_storage);
+
+        Ok(())
+    }
+
+    /// Applies the modified and deleted ways of an osmChange diff, the customized profiles are
+    /// always customized again. When a car weight changed, the weights of the landmarks are
+    /// computed again and the car contraction hierarchies are customized on the CCH topology
+    /// rather than contracted again, which takes minutes. The CCH topology and the location index
+    /// are kept since the topology and the geometries of the edges do not change.
+    pub fn apply_osm_change(&mut self, change: &OsmChange) -> OsmChangeSummary {
+        let summary = self.graph.apply_osm_change(change);
+
+        if summary.car_weights_changed {
+            let weighting = CarWeighting::new();
+            self.lm = LMPreparation::new(&self.graph, &weighting).update_landmarks(&self.lm);
+            self.ch_storage = Some(self.cch_topology.customize(&self.graph, &weighting));
+        }
+
+        let profiles = self.customized_ch.keys().cloned().collect::<Vec<_>>();
+        for profile in profiles {
+            self.customize_profile(&profile)
+                .expect("the customized profiles exist");
+        }
+
+        summary
+    }
+
+    /// Contraction hierarchies of the profile, the customized ones only hold for unknown vehicle
+    /// dimensions
+    fn ch_storage(
+        &self,
+        profile: &str,
+        vehicle_dimensions: Option<VehicleDimensions>,
+    ) -> Option<&CHStorage> {
+        if profile == CH_PROFILE && self.profiles.get(profile).is_some_and(|car| car.is_car()) {
+            return self.ch_storage.as_ref();
+        }
+
+        self.customized_ch
+            .get(profile)
+            .filter(|_| vehicle_dimensions.is_none_or(|dimensions| dimensions.is_unknown()))
+    }
+
     pub fn graph(&self) -> &BaseGraph {
         &self.graph
     }
@@ -119,6 +298,11 @@ impl Hermes {
         &self.index
     }
 
+    /// Administrative and postal areas containing the point, from the largest to the smallest
+    pub fn admin_areas(&self, point: &GeoPoint) -> Vec<&AdminArea> {
+        self.admin_areas.areas_at(point)
+    }
+
     pub fn get_landmarks(&self) -> Vec<GeoPoint> {
         self.lm
             .get_node_ids()
@@ -129,28 +313,51 @@ impl Hermes {
     }
 
     pub fn route(&self, request: RoutingRequest) -> Result<CalcPathResult, String> {
-        let base_graph_weighting = self.create_weighting(&request.profile);
+        let request_options = request.options.as_ref();
+        let avoided_edges =
+            self.avoided_edges(request_options.and_then(|options| options.avoid.as_ref()));
+
+        let base_graph_weighting = self.create_avoid_weighting(
+            &request.profile,
+            request.vehicle_dimensions,
+            avoided_edges.as_ref(),
+        )?;
 
         let start_snap = self
             .index()
             .snap(&self.graph, &base_graph_weighting, &request.start)
-            .expect("no way to avenue closest way");
+            .ok_or_else(|| format!("No road found near the start {:?}", request.start))?;
 
         let end_snap = self
             .index()
             .snap(&self.graph, &base_graph_weighting, &request.end)
-            .expect("no way to rue des palais way");
+            .ok_or_else(|| format!("No road found near the end {:?}", request.end))?;
 
         let mut snaps = [start_snap, end_snap];
 
-        let request_options = request.options.as_ref();
         let options = CalcPathOptions {
             include_debug_info: request_options.and_then(|options| options.include_debug_info),
         };
 
-        match request_options.and_then(|options| options.algorithm) {
+        let algorithm = match request_options.and_then(|options| options.algorithm) {
+            // The contraction hierarchies are prepared without the avoided edges
+            Some(RoutingAlgorithm::ContractionHierarchies) if avoided_edges.is_some() => None,
+            // The bidirectional searches don't respect the turn restrictions
+            Some(
+                RoutingAlgorithm::ContractionHierarchies
+                | RoutingAlgorithm::BidirectionalAstar
+                | RoutingAlgorithm::Landmarks,
+            ) if self.has_turn_restrictions() => None,
+            algorithm => algorithm,
+        };
+
+        match algorithm {
             Some(RoutingAlgorithm::Dijkstra) => {
-                let weighting = self.create_weighting(&request.profile);
+                let weighting = self.create_avoid_weighting(
+                    &request.profile,
+                    request.vehicle_dimensions,
+                    avoided_edges.as_ref(),
+                )?;
                 let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
                 let start = snaps[0].closest_node();
                 let end = snaps[1].closest_node();
@@ -159,7 +366,11 @@ impl Hermes {
                 dijkstra.calc_path(&weighting, start, end, Some(options))
             }
             Some(RoutingAlgorithm::Astar) => {
-                let weighting = self.create_weighting(&request.profile);
+                let weighting = self.create_avoid_weighting(
+                    &request.profile,
+                    request.vehicle_dimensions,
+                    avoided_edges.as_ref(),
+                )?;
                 let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
                 let start = snaps[0].closest_node();
                 let end = snaps[1].closest_node();
@@ -168,7 +379,11 @@ impl Hermes {
                 astar.calc_path(&weighting, start, end, Some(options))
             }
             Some(RoutingAlgorithm::BidirectionalAstar) => {
-                let weighting = self.create_weighting(&request.profile);
+                let weighting = self.create_avoid_weighting(
+                    &request.profile,
+                    request.vehicle_dimensions,
+                    avoided_edges.as_ref(),
+                )?;
                 let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
                 let start = snaps[0].closest_node();
                 let end = snaps[1].closest_node();
@@ -177,7 +392,11 @@ impl Hermes {
             }
 
             Some(RoutingAlgorithm::Landmarks) => {
-                let weighting = self.create_weighting(&request.profile);
+                let weighting = self.create_avoid_weighting(
+                    &request.profile,
+                    request.vehicle_dimensions,
+                    avoided_edges.as_ref(),
+                )?;
                 let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
                 let start = snaps[0].closest_node();
                 let end = snaps[1].closest_node();
@@ -191,31 +410,53 @@ impl Hermes {
                 landmarks_astar.calc_path(&weighting, start, end, Some(options))
             }
 
-            Some(RoutingAlgorithm::ContractionHierarchies) => match &self.ch_storage {
-                Some(ch_storage) => {
-                    let weighting = CHWeighting::new();
+            Some(RoutingAlgorithm::ContractionHierarchies) => {
+                match self.ch_storage(&request.profile, request.vehicle_dimensions) {
+                    Some(ch_storage) => {
+                        let weighting = CHWeighting::new();
 
-                    let ch_graph = CHGraph::new(ch_storage, &self.graph);
+                        let ch_graph = CHGraph::new(ch_storage, &self.graph);
 
-                    let query_graph =
-                        QueryGraph::from_graph(&ch_graph, &self.graph, &mut snaps[..]);
-                    let start = snaps[0].closest_node();
-                    let end = snaps[1].closest_node();
+                        let query_graph =
+                            QueryGraph::from_graph(&ch_graph, &self.graph, &mut snaps[..]);
+                        let start = snaps[0].closest_node();
+                        let end = snaps[1].closest_node();
 
-                    // let mut ch_bidirectional_dijkstra = CHBidirectionalDijkstra::new(&query_graph);
+                        // let mut ch_bidirectional_dijkstra = CHBidirectionalDijkstra::new(&query_graph);
 
-                    let mut ch_bidirectional_dijkstra = CHBidirectionalAStar::new(&query_graph);
+                        let mut ch_bidirectional_dijkstra = CHBidirectionalAStar::new(&query_graph);
 
-                    // let mut ch_bidirectional_dijkstra =
-                    //     CHLMAstar::from_landmarks(&query_graph, &weighting, &self.lm, start, end);
+                        // let mut ch_bidirectional_dijkstra =
+                        //     CHLMAstar::from_landmarks(&query_graph, &weighting, &self.lm, start, end);
 
-                    ch_bidirectional_dijkstra.calc_path(&weighting, start, end, Some(options))
+                        ch_bidirectional_dijkstra.calc_path(&weighting, start, end, Some(options))
+                    }
+                    None => Err(format!(
+                        "Contraction hierarchies are not prepared for the {} profile",
+                        request.profile
+                    )),
                 }
-                None => Err(String::from("CH Graph not found")),
-            },
+            }
 
+            None if self.has_turn_restrictions() => {
+                let weighting = self.create_avoid_weighting(
+                    &request.profile,
+                    request.vehicle_dimensions,
+                    avoided_edges.as_ref(),
+                )?;
+                let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
+                let start = snaps[0].closest_node();
+                let end = snaps[1].closest_node();
+
+                let mut astar = AStar::new(&query_graph);
+                astar.calc_path(&weighting, start, end, Some(options))
+            }
             None => {
-                let weighting = self.create_weighting(&request.profile);
+                let weighting = self.create_avoid_weighting(
+                    &request.profile,
+                    request.vehicle_dimensions,
+                    avoided_edges.as_ref(),
+                )?;
                 let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
                 let start = snaps[0].closest_node();
                 let end = snaps[1].closest_node();
@@ -226,37 +467,177 @@ impl Hermes {
     }
 
     pub fn matrix(&self, request: MatrixRequest) -> Result<MatrixAlgorithmResult, String> {
-        let base_graph_weighting = self.create_weighting(&request.profile);
+        let mut stopwatch = Stopwatch::new(String::from("matrix"));
+        stopwatch.start();
+
+        let sources = request.sources.len();
+        let targets = request.targets.len();
+
+        // Enough blocks to keep every thread busy
+        let block_size = sources
+            .div_ceil(rayon::current_num_threads())
+            .clamp(1, MAX_MATRIX_BLOCK_SIZE);
+
+        let mut matrix = Matrix::new(sources, targets);
+        let mut visited_nodes = 0;
+        self.matrix_in_blocks(request, block_size, |block| {
+            visited_nodes += block.result.visited_nodes;
+            matrix.set_block(block.first_source, block.result.matrix);
+        })?;
+
+        stopwatch.stop();
+        Ok(MatrixAlgorithmResult {
+            matrix,
+            visited_nodes,
+            duration: stopwatch.elapsed(),
+        })
+    }
 
-        let source_snaps: Vec<Snap> = request
-            .sources
-            .iter()
-            .map(|source| {
-                self.index
-                    .snap(&self.graph, &base_graph_weighting, source)
-                    .unwrap_or_else(|| panic!("Source not found"))
-            })
-            .collect();
+    /// Points of the graph the sources and targets of the request are snapped to, encoded with
+    /// `snapped_points_encoding`, `None` when the option is not set. A point too far from the
+    /// roads of the profile is returned as is
+    pub fn snapped_points(
+        &self,
+        request: &MatrixRequest,
+    ) -> Option<(EncodedGeometry, EncodedGeometry)> {
+        let encoding = request.options.as_ref()?.snapped_points_encoding?;
+        let avoided_edges = self.avoided_edges(request.avoid());
+        let weighting = self
+            .create_avoid_weighting(
+                &request.profile,
+                request.vehicle_dimensions,
+                avoided_edges.as_ref(),
+            )
+            .ok()?;
+        let snap_options = request.snap_options();
+        let snap_points = |points: &[GeoPoint]| {
+            let snapped = points
+                .iter()
+                .map(|point| {
+                    self.index
+                        .snap_with_options(&self.graph, &weighting, point, &snap_options)
+                        .map_or(*point, |snap| snap.coordinates)
+                })
+                .collect::<Vec<_>>();
+            encoding.encode(&snapped)
+        };
 
-        let target_snaps: Vec<Snap> = request
-            .targets
-            .iter()
-            .map(|target| {
-                self.index
-                    .snap(&self.graph, &base_graph_weighting, target)
-                    .unwrap_or_else(|| panic!("target not found"))
-            })
-            .collect();
+        Some((snap_points(&request.sources), snap_points(&request.targets)))
+    }
+
+    /// Roads the sources and targets of the request are snapped to, `None` when
+    /// `include_snap_reports` is not set. The points that can't be snapped have no report
+    pub fn snap_reports(
+        &self,
+        request: &MatrixRequest,
+    ) -> Option<(Vec<Option<SnapReport>>, Vec<Option<SnapReport>>)> {
+        if !request.options.as_ref()?.include_snap_reports? {
+            return None;
+        }
+
+        let avoided_edges = self.avoided_edges(request.avoid());
+        let weighting = self
+            .create_avoid_weighting(
+                &request.profile,
+                request.vehicle_dimensions,
+                avoided_edges.as_ref(),
+            )
+            .ok()?;
+        let snap_options = request.snap_options();
+        let snap_reports = |points: &[GeoPoint]| {
+            points
+                .iter()
+                .map(|point| {
+                    self.index
+                        .snap_with_options(&self.graph, &weighting, point, &snap_options)
+                        .map(|snap| self.snap_report(&snap))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        Some((
+            snap_reports(&request.sources),
+            snap_reports(&request.targets),
+        ))
+    }
+
+    /// Road of the profile the point is snapped to, `None` when no road is allowed by the options
+    pub fn nearest(
+        &self,
+        point: &GeoPoint,
+        profile: &str,
+        vehicle_dimensions: Option<VehicleDimensions>,
+        options: &SnapOptions,
+    ) -> Result<Option<SnapReport>, String> {
+        let weighting = self.create_weighting(profile, vehicle_dimensions)?;
+
+        Ok(self
+            .index
+            .snap_with_options(&self.graph, &weighting, point, options)
+            .map(|snap| self.snap_report(&snap)))
+    }
+
+    fn snap_report(&self, snap: &Snap) -> SnapReport {
+        let properties = self.graph.edge(snap.edge_id).properties();
+        let geometry = self.graph.edge_geometry(snap.edge_id);
+
+        SnapReport {
+            coordinates: snap.coordinates,
+            distance: snap.distance(),
+            street_name: properties
+                .get_string(Property::StreetName)
+                .map(str::to_owned),
+            osm_way_id: properties.get_usize(Property::OsmId),
+            clamped: geometry.first() == Some(&snap.coordinates)
+                || geometry.last() == Some(&snap.coordinates),
+        }
+    }
+
+    /// Computes the matrix `block_size` sources at a time on the rayon thread pool, `on_block`
+    /// receives each block as soon as it is computed, not necessarily in the order of the sources.
+    ///
+    /// The backward search from the targets is run again for each block, smaller blocks make the
+    /// first rows available sooner but the whole matrix slower to compute. At most
+    /// `MAX_PENDING_MATRIX_BLOCKS` computed blocks wait for `on_block`, the computation is paused
+    /// until it catches up so that memory stays bounded.
+    pub fn matrix_in_blocks(
+        &self,
+        request: MatrixRequest,
+        block_size: usize,
+        mut on_block: impl FnMut(MatrixBlockResult),
+    ) -> Result<(), String> {
+        if block_size == 0 {
+            return Err(String::from("The block size must be positive"));
+        }
+
+        let avoided_edges = self.avoided_edges(request.avoid());
+        let base_graph_weighting = self.create_avoid_weighting(
+            &request.profile,
+            request.vehicle_dimensions,
+            avoided_edges.as_ref(),
+        )?;
+        let metrics = request.metrics();
+
+        let snap_options = request.snap_options();
+        let snap_points = |points: &[GeoPoint], kind: &str| {
+            points
+                .iter()
+                .enumerate()
+                .map(|(index, point)| {
+                    self.index
+                        .snap_with_options(&self.graph, &base_graph_weighting, point, &snap_options)
+                        .ok_or_else(|| format!("No road found for the {kind} {index}"))
+                })
+                .collect::<Result<Vec<Snap>, String>>()
+        };
+
+        let source_snaps = snap_points(&request.sources, "source")?;
+        let target_snaps = snap_points(&request.targets, "target")?;
 
         let mut snaps: Vec<Snap> = vec![];
         snaps.extend(source_snaps);
         snaps.extend(target_snaps);
 
-        let ch_graph = CHGraph::new(self.ch_storage.as_ref().unwrap(), &self.graph);
-        let query_graph = QueryGraph::from_graph(&ch_graph, &self.graph, &mut snaps[..]);
-        let weighting = CHWeighting::new();
-        let mut algorithm = SBIMatrixAlgorithm::new(&query_graph, &weighting);
-
         let sources: Vec<NodeId> = (0..request.sources.len())
             .map(|index| snaps[index].closest_node())
             .collect();
@@ -265,15 +646,353 @@ impl Hermes {
             .map(|index| snaps[request.sources.len() + index].closest_node())
             .collect();
 
-        let result = algorithm.calc_matrix(&sources, &targets);
+        // Computed on the base graph for the profiles and dimensions without contraction hierarchies,
+        // when edges are avoided and when the graph has turn restrictions
+        let Some(ch_storage) = self
+            .ch_storage(&request.profile, request.vehicle_dimensions)
+            .filter(|_| avoided_edges.is_none() && !self.has_turn_restrictions())
+        else {
+            let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
+            let weighting = self.create_avoid_weighting(
+                &request.profile,
+                request.vehicle_dimensions,
+                avoided_edges.as_ref(),
+            )?;
+
+            Self::compute_matrix_blocks(
+                &sources,
+                &targets,
+                block_size,
+                || DijkstraMatrixAlgorithm::new(&query_graph, &weighting),
+                on_block,
+            );
+            return Ok(());
+        };
+
+        let ch_graph = CHGraph::new(ch_storage, &self.graph);
+        let query_graph = QueryGraph::from_graph(&ch_graph, &self.graph, &mut snaps[..]);
+        let weighting = CHWeighting::new();
+
+        Self::compute_matrix_blocks(
+            &sources,
+            &targets,
+            block_size,
+            || SBIMatrixAlgorithm::new(&query_graph, &weighting, metrics),
+            on_block,
+        );
+
+        Ok(())
+    }
+
+    /// Sends the blocks computed by the algorithms of `create_algorithm` to `on_block`, see
+    /// `Hermes::matrix_in_blocks`
+    fn compute_matrix_blocks<A: MatrixAlgorithm>(
+        sources: &[NodeId],
+        targets: &[NodeId],
+        block_size: usize,
+        create_algorithm: impl Fn() -> A + Send + Sync,
+        mut on_block: impl FnMut(MatrixBlockResult),
+    ) {
+        let (sender, receiver) = mpsc::sync_channel(MAX_PENDING_MATRIX_BLOCKS);
+
+        std::thread::scope(|scope| {
+            let create_algorithm = &create_algorithm;
+
+            scope.spawn(move || {
+                sources.par_chunks(block_size).enumerate().for_each_init(
+                    create_algorithm,
+                    |algorithm, (block_index, block)| {
+                        let result = algorithm.calc_matrix(block, targets);
+                        // The receiver is only dropped once every block is received
+                        let _ = sender.send((block_index * block_size, result));
+                    },
+                );
+            });
+
+            let mut computed_sources = 0;
+            for (first_source, result) in receiver {
+                computed_sources += result.matrix.sources();
+                on_block(MatrixBlockResult {
+                    first_source,
+                    result,
+                    computed_sources,
+                    total_sources: sources.len(),
+                });
+            }
+        });
+    }
+
+    /// Single row matrix from the single source of the request to each of its targets. The
+    /// upward search from the source is run once for all the targets, which is faster than
+    /// `Hermes::matrix` with one source
+    pub fn one_to_many(&self, request: &MatrixRequest) -> Result<MatrixAlgorithmResult, String> {
+        let [source] = &request.sources[..] else {
+            return Err(String::from(
+                "A one to many matrix needs exactly one source",
+            ));
+        };
+
+        self.one_to_many_in_direction(request, source, &request.targets, SearchDirection::Forward)
+    }
+
+    /// Single column matrix from each of the sources of the request to its single target, see
+    /// `Hermes::one_to_many`
+    pub fn many_to_one(&self, request: &MatrixRequest) -> Result<MatrixAlgorithmResult, String> {
+        let [target] = &request.targets[..] else {
+            return Err(String::from(
+                "A many to one matrix needs exactly one target",
+            ));
+        };
+
+        self.one_to_many_in_direction(request, target, &request.sources, SearchDirection::Backward)
+    }
+
+    fn one_to_many_in_direction(
+        &self,
+        request: &MatrixRequest,
+        point: &GeoPoint,
+        others: &[GeoPoint],
+        search_direction: SearchDirection,
+    ) -> Result<MatrixAlgorithmResult, String> {
+        let avoided_edges = self.avoided_edges(request.avoid());
+        let base_graph_weighting = self.create_avoid_weighting(
+            &request.profile,
+            request.vehicle_dimensions,
+            avoided_edges.as_ref(),
+        )?;
+
+        let snap_options = request.snap_options();
+        let mut snaps = std::iter::once(point)
+            .chain(others)
+            .map(|point| {
+                self.index
+                    .snap_with_options(&self.graph, &base_graph_weighting, point, &snap_options)
+                    .ok_or_else(|| format!("No road found near {point:?}"))
+            })
+            .collect::<Result<Vec<Snap>, String>>()?;
+
+        // Computed on the base graph for the profiles and dimensions without contraction
+        // hierarchies, when edges are avoided and when the graph has turn restrictions, see
+        // `Hermes::matrix_in_blocks`
+        let Some(ch_storage) = self
+            .ch_storage(&request.profile, request.vehicle_dimensions)
+            .filter(|_| avoided_edges.is_none() && !self.has_turn_restrictions())
+        else {
+            let query_graph = QueryGraph::from_graph(&self.graph, &self.graph, &mut snaps[..]);
+            let mut algorithm = DijkstraMatrixAlgorithm::new(&query_graph, &base_graph_weighting);
+            let nodes: Vec<NodeId> = snaps.iter().map(Snap::closest_node).collect();
+
+            return Ok(match search_direction {
+                SearchDirection::Forward => algorithm.calc_matrix(&nodes[..1], &nodes[1..]),
+                SearchDirection::Backward => algorithm.calc_matrix(&nodes[1..], &nodes[..1]),
+            });
+        };
+
+        let ch_graph = CHGraph::new(ch_storage, &self.graph);
+        let query_graph = QueryGraph::from_graph(&ch_graph, &self.graph, &mut snaps[..]);
+        let weighting = CHWeighting::new();
+        let algorithm = OneToManyAlgorithm::new(&query_graph, &weighting);
+
+        let nodes: Vec<NodeId> = snaps.iter().map(Snap::closest_node).collect();
+
+        Ok(match search_direction {
+            SearchDirection::Forward => algorithm.calc_one_to_many(nodes[0], &nodes[1..]),
+            SearchDirection::Backward => algorithm.calc_many_to_one(&nodes[1..], nodes[0]),
+        })
+    }
+
+    /// The searches on the contraction hierarchies don't know the edge a node was reached from,
+    /// they can't respect the turn restrictions
+    fn has_turn_restrictions(&self) -> bool {
+        !self.graph.turn_restrictions().is_empty()
+    }
+
+    fn avoided_edges(&self, avoid: Option<&AvoidOptions>) -> Option<AvoidedEdges> {
+        avoid.map(|avoid| AvoidedEdges::new(&self.index, avoid))
+    }
 
-        Ok(result)
+    /// Weighting of the profile on which the edges avoided by the request are not accessible
+    fn create_avoid_weighting<'a, G: Graph<Edge = BaseGraphEdge>>(
+        &self,
+        profile: &str,
+        vehicle_dimensions: Option<VehicleDimensions>,
+        avoided_edges: Option<&'a AvoidedEdges>,
+    ) -> Result<AvoidWeighting<'a, ProfileWeighting<G>>, String> {
+        Ok(AvoidWeighting::new(
+            self.create_weighting(profile, vehicle_dimensions)?,
+            avoided_edges,
+        ))
     }
 
-    fn create_weighting<G: Graph>(&self, profile: &str) -> impl Weighting<G> {
-        match profile {
-            "car" => CarWeighting::new(),
-            _ => panic!("No profile found"),
+    fn create_weighting<G: Graph>(
+        &self,
+        profile: &str,
+        vehicle_dimensions: Option<VehicleDimensions>,
+    ) -> Result<ProfileWeighting<G>, String> {
+        let profile = self
+            .profiles
+            .get(profile)
+            .ok_or_else(|| format!("Unknown profile {profile}"))?;
+
+        Ok(ProfileWeighting::new(
+            Arc::clone(profile),
+            vehicle_dimensions.unwrap_or_default(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        osm::osm_reader::{OsmWay, parse_way_properties},
+        routing::routing_request::RoutingRequestOptions,
+        turn_restrictions::{TurnRestriction, TurnRestrictionKind},
+    };
+
+    use super::*;
+
+    /// Square with a tail, the left turn from the tail `0 -> 1` to `1 -> 3` is forbidden so the
+    /// routes go around the square through `2` and `4`
+    fn create_hermes() -> Hermes {
+        let ways = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="1"><tag k="highway" v="residential"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap()
+        .ways;
+        let properties = || parse_way_properties(&OsmWay::new(ways[0].id as usize, &ways[0].tags));
+        let points = [
+            GeoPoint::new(0.0, 0.0),
+            GeoPoint::new(0.01, 0.0),
+            GeoPoint::new(0.02, 0.0),
+            GeoPoint::new(0.01, 0.01),
+            GeoPoint::new(0.02, 0.01),
+        ];
+
+        let mut graph = BaseGraph::default();
+        for node in 0..points.len() {
+            graph.add_node(node);
         }
+        for (from, to) in [(0, 1), (1, 2), (1, 3), (2, 4), (4, 3)] {
+            graph.add_edge(from, to, properties(), vec![points[from], points[to]]);
+        }
+        graph.set_turn_restrictions(vec![TurnRestriction {
+            from_edge: 0,
+            via_node: 1,
+            to_edge: 2,
+            kind: TurnRestrictionKind::No,
+        }]);
+
+        Hermes::from_graph(graph, AdminAreas::default())
+    }
+
+    /// On the tail, before the restricted turn
+    fn start() -> GeoPoint {
+        GeoPoint::new(0.005, 0.0)
+    }
+
+    /// On the top of the square, after the restricted turn
+    fn end() -> GeoPoint {
+        GeoPoint::new(0.015, 0.01)
+    }
+
+    fn route_distance(hermes: &Hermes, algorithm: RoutingAlgorithm) -> f64 {
+        let request = RoutingRequest {
+            start: start(),
+            end: end(),
+            profile: "car".into(),
+            vehicle_dimensions: None,
+            options: Some(RoutingRequestOptions {
+                include_debug_info: None,
+                algorithm: Some(algorithm),
+                avoid: None,
+            }),
+        };
+
+        hermes.route(request).unwrap().path.distance().value()
+    }
+
+    #[test]
+    fn test_route_respects_turn_restrictions() {
+        let hermes = create_hermes();
+        let distance = route_distance(&hermes, RoutingAlgorithm::Dijkstra);
+
+        // The direct path is two thirds of the detour around the square
+        let direct = GeoPoint::new(0.0, 0.0).haversine_distance(&GeoPoint::new(0.02, 0.0));
+        assert!(distance > direct * 1.4);
+
+        for algorithm in [
+            RoutingAlgorithm::Astar,
+            RoutingAlgorithm::BidirectionalAstar,
+            RoutingAlgorithm::Landmarks,
+            RoutingAlgorithm::ContractionHierarchies,
+        ] {
+            assert!((route_distance(&hermes, algorithm) - distance).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_matrix_respects_turn_restrictions() {
+        let hermes = create_hermes();
+        let distance = route_distance(&hermes, RoutingAlgorithm::Dijkstra);
+
+        let result = hermes
+            .matrix(MatrixRequest {
+                sources: vec![start()],
+                targets: vec![end()],
+                profile: "car".into(),
+                vehicle_dimensions: None,
+                options: None,
+            })
+            .unwrap();
+        let entry = result.matrix.entry(0, 0).unwrap();
+        assert!((entry.distance().value() - distance).abs() < 1.0);
+
+        let request = MatrixRequest {
+            sources: vec![start()],
+            targets: vec![end()],
+            profile: "car".into(),
+            vehicle_dimensions: None,
+            options: None,
+        };
+
+        let result = hermes.one_to_many(&request).unwrap();
+        let entry = result.matrix.entry(0, 0).unwrap();
+        assert!((entry.distance().value() - distance).abs() < 1.0);
+
+        let result = hermes.many_to_one(&request).unwrap();
+        let entry = result.matrix.entry(0, 0).unwrap();
+        assert!((entry.distance().value() - distance).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_one_to_many_without_contraction_hierarchies() {
+        let hermes = create_hermes();
+        let mut request = MatrixRequest {
+            sources: vec![start()],
+            targets: vec![end()],
+            profile: "truck".into(),
+            vehicle_dimensions: Some(VehicleDimensions {
+                height: Some(4.0),
+                width: None,
+                weight: None,
+            }),
+            options: None,
+        };
+        assert!(
+            hermes
+                .ch_storage("truck", request.vehicle_dimensions)
+                .is_none()
+        );
+
+        let result = hermes.one_to_many(&request).unwrap();
+        let distance = result.matrix.entry(0, 0).unwrap().distance().value();
+        assert!((distance - route_distance(&hermes, RoutingAlgorithm::Dijkstra)).abs() < 1.0);
+
+        request.targets.push(start());
+        assert!(hermes.many_to_one(&request).is_err());
+        request.profile = "bicycle".into();
+        assert!(hermes.one_to_many(&request).is_err());
     }
 }
@@ -1,7 +1,8 @@
 use tracing::{debug, info};
 
 use crate::{
-    storage::{read_bytes, write_bytes},
+    error::StorageError,
+    storage::{FileKind, read_file, write_file},
     weighting::Weight,
 };
 
@@ -32,18 +33,19 @@ pub(crate) struct LMData {
 }
 
 impl LMData {
-    pub fn from_file(path: &str) -> Self {
+    pub fn from_file(path: &str) -> Result<Self, StorageError> {
         debug!("Reading from path {}", path);
-        let bytes = read_bytes(path);
+        let bytes = read_file(path, FileKind::Landmarks)?;
         debug!("Read from path {}, size {}", path, bytes.len());
-        let data = rkyv::from_bytes::<Self, rkyv::rancor::Error>(&bytes[..]).unwrap();
+        let data = rkyv::from_bytes::<Self, rkyv::rancor::Error>(&bytes[..])
+            .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
         info!("Deserialized landmarks from buffer");
-        data
+        Ok(data)
     }
 
     pub fn save_to_file(&self, path: &str) -> Result<(), std::io::Error> {
         let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(self).expect("to_bytes failed");
-        write_bytes(&bytes[..], path)
+        write_file(path, FileKind::Landmarks, &bytes[..])
     }
 
     pub fn get_node_ids(&self) -> Vec<usize> {
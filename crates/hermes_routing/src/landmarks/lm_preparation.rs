@@ -39,6 +39,18 @@ impl<'a, W: Weighting<BaseGraph> + Send + Sync> LMPreparation<'a, W> {
         LMData::new(landmarks)
     }
 
+    /// Computes the weights of the landmarks of `lm` again, e.g. after the weights of edges
+    /// changed, without searching the landmarks again
+    pub fn update_landmarks(&self, lm: &LMData) -> LMData {
+        let landmarks: Vec<Landmark> = lm
+            .get_node_ids()
+            .par_iter()
+            .map(|&node_id| self.create_landmark(node_id))
+            .collect();
+
+        LMData::new(landmarks)
+    }
+
     fn create_landmark(&self, node_id: usize) -> Landmark {
         let mut weight_from_landmark: Vec<Weight> = Vec::with_capacity(self.graph.node_count());
         let mut weight_to_landmark: Vec<Weight> = Vec::with_capacity(self.graph.node_count());
@@ -1,12 +1,13 @@
+pub mod admin_areas;
+pub mod avoid;
 pub mod base_graph;
 mod ch;
 mod constants;
-mod degrees;
 pub mod distance;
 pub mod edge_direction;
 pub mod error;
 mod geometry;
-pub mod geopoint;
+pub mod geometry_encoding;
 pub mod graph;
 mod graph_edge;
 pub mod hermes;
@@ -14,12 +15,17 @@ mod landmarks;
 pub mod location_index;
 pub mod matrix;
 pub mod osm;
+pub mod profile;
 pub mod properties;
 pub(crate) mod query;
+pub mod region;
 pub mod routing;
-mod snap;
+pub mod snap;
 mod stopwatch;
 mod storage;
 mod test_graph_utils;
+pub mod turn_restrictions;
 mod types;
 pub mod weighting;
+
+pub use hermes_geo::geopoint;
@@ -1,13 +1,16 @@
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
-
 use crate::base_graph::BaseGraph;
+use crate::edge_direction::EdgeDirection;
+use crate::error::StorageError;
 use crate::geopoint::GeoPoint;
 use crate::graph::{GeometryAccess, Graph};
-use crate::snap::Snap;
+use crate::graph_edge::GraphEdge;
+use crate::meters;
+use crate::snap::{Snap, SnapOptions};
 use crate::stopwatch::Stopwatch;
+use crate::storage::{FileKind, read_file, write_file};
+use crate::types::EdgeId;
 use crate::weighting::Weighting;
-use geo::HaversineClosestPoint;
+use geo::{BoundingRect, HaversineClosestPoint, Intersects, MultiPolygon};
 use rstar::primitives::GeomWithData;
 use rstar::{AABB, PointDistance, RTree, RTreeObject};
 use serde::{Deserialize, Serialize};
@@ -83,34 +86,44 @@ impl LocationIndex {
         LocationIndex { tree }
     }
 
-    pub fn save_to_file(&self, path: &str) -> Result<usize, bincode::error::EncodeError> {
+    pub fn save_to_file(&self, path: &str) -> Result<(), std::io::Error> {
         let mut stopwatch = Stopwatch::new(String::from("location_index/save_to_file"));
         stopwatch.start();
-        let mut file = File::create(path).expect("failed to create file");
-        let mut writer = BufWriter::new(&mut file);
-        let result = bincode::serde::encode_into_std_write(
-            &self.tree,
-            &mut writer,
-            bincode::config::standard(),
-        );
+        let bytes = bincode::serde::encode_to_vec(&self.tree, bincode::config::standard())
+            .map_err(std::io::Error::other)?;
+        let result = write_file(path, FileKind::LocationIndex, &bytes);
         stopwatch.stop();
         stopwatch.report();
         result
     }
 
-    pub fn load_from_file(path: &str) -> Self {
+    pub fn load_from_file(path: &str) -> Result<Self, StorageError> {
         let mut stopwatch = Stopwatch::new(String::from("location_index/load_from_file"));
         stopwatch.start();
-        let mut file = File::open(path).expect("failed to open file");
-        let mut reader = BufReader::new(&mut file);
+        let bytes = read_file(path, FileKind::LocationIndex)?;
 
-        let result: Result<RTree<LocationIndexObject>, bincode::error::DecodeError> =
-            bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard());
-        let tree = result.unwrap();
+        let result: Result<(RTree<LocationIndexObject>, usize), bincode::error::DecodeError> =
+            bincode::serde::decode_from_slice(&bytes, bincode::config::standard());
+        let (tree, _) = result
+            .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
 
         stopwatch.stop();
         stopwatch.report();
-        LocationIndex { tree }
+        Ok(LocationIndex { tree })
+    }
+
+    /// Edges of the graph whose geometry crosses or lies within the area
+    pub fn edges_in_area(&self, area: &MultiPolygon<f64>) -> Vec<EdgeId> {
+        let Some(bounds) = area.bounding_rect() else {
+            return vec![];
+        };
+        let envelope = AABB::from_corners(bounds.min().into(), bounds.max().into());
+
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .filter(|object| object.geom().line().intersects(area))
+            .map(|object| object.data.edge_id)
+            .collect()
     }
 
     pub fn snap<G: Graph>(
@@ -119,12 +132,31 @@ impl LocationIndex {
         weighting: &impl Weighting<G>,
         coordinates: &GeoPoint,
     ) -> Option<Snap> {
+        self.snap_with_options(graph, weighting, coordinates, &SnapOptions::default())
+    }
+
+    /// Snaps to the closest road accessible by the weighting profile and allowed by the options
+    pub fn snap_with_options<G: Graph>(
+        &self,
+        graph: &G,
+        weighting: &impl Weighting<G>,
+        coordinates: &GeoPoint,
+        options: &SnapOptions,
+    ) -> Option<Snap> {
+        let is_excluded = |edge: &G::Edge| {
+            options.excluded_road_classes.iter().any(|road_class| {
+                edge.properties()
+                    .get_bool(road_class.property(), EdgeDirection::Forward)
+                    .unwrap_or(false)
+            })
+        };
+
         self.tree
             .nearest_neighbor_iter(&coordinates.into())
             .find(|nearest_neighbor| {
-                let edge_id = nearest_neighbor.data.edge_id;
+                let edge = graph.edge(nearest_neighbor.data.edge_id);
                 // We only consider edges that can be accessed by the weighting profile
-                weighting.can_access_edge(graph.edge(edge_id))
+                weighting.can_access_edge(edge) && !is_excluded(edge)
             })
             .map(|nearest_neighbor| {
                 let line = nearest_neighbor.geom().line();
@@ -140,8 +172,90 @@ impl LocationIndex {
                 Snap::new(
                     nearest_neighbor.data.edge_id,
                     closest_point,
-                    coordinates.haversine_distance(&closest_point),
+                    meters!(coordinates.haversine_distance(&closest_point)),
                 )
             })
+            .filter(|snap| {
+                options
+                    .max_radius
+                    .is_none_or(|radius| snap.distance() <= radius)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        avoid::RoadClass,
+        base_graph::BaseGraph,
+        geopoint::GeoPoint,
+        meters,
+        osm::{
+            osm_change::OsmChange,
+            osm_reader::{OsmWay, parse_way_properties},
+        },
+        snap::SnapOptions,
+        weighting::CarWeighting,
+    };
+
+    use super::LocationIndex;
+
+    /// A motorway along the equator and a residential road about 220 meters north of it
+    fn create_graph() -> BaseGraph {
+        let ways = OsmChange::parse(
+            r#"<osmChange><create>
+                <way id="1"><tag k="highway" v="motorway"/></way>
+                <way id="2"><tag k="highway" v="residential"/></way>
+            </create></osmChange>"#,
+        )
+        .unwrap()
+        .ways;
+
+        let mut graph = BaseGraph::default();
+        for node in 0..4 {
+            graph.add_node(node);
+        }
+
+        for (index, way) in ways.iter().enumerate() {
+            let lat = index as f64 * 0.002;
+            graph.add_edge(
+                index * 2,
+                index * 2 + 1,
+                parse_way_properties(&OsmWay::new(way.id as usize, &way.tags)),
+                vec![GeoPoint::new(0.0, lat), GeoPoint::new(0.01, lat)],
+            );
+        }
+
+        graph
+    }
+
+    #[test]
+    fn test_snap_with_options() {
+        let graph = create_graph();
+        let index = LocationIndex::build_from_graph(&graph);
+        let weighting = CarWeighting::new();
+        let point = GeoPoint::new(0.005, 0.0005);
+
+        let snap = index.snap(&graph, &weighting, &point).unwrap();
+        assert_eq!(snap.edge_id, 0);
+
+        let options = SnapOptions {
+            max_radius: None,
+            excluded_road_classes: vec![RoadClass::Motorway],
+        };
+        let snap = index
+            .snap_with_options(&graph, &weighting, &point, &options)
+            .unwrap();
+        assert_eq!(snap.edge_id, 1);
+
+        let options = SnapOptions {
+            max_radius: Some(meters!(100)),
+            ..options
+        };
+        assert!(
+            index
+                .snap_with_options(&graph, &weighting, &point, &options)
+                .is_none()
+        );
     }
 }
@@ -0,0 +1,230 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    constants::{INVALID_EDGE, MAX_WEIGHT},
+    distance::{Distance, Meters},
+    graph::{Graph, TurnRestrictionAccess, UndirectedEdgeAccess},
+    graph_edge::GraphEdge,
+    stopwatch::Stopwatch,
+    types::{EdgeId, NodeId},
+    weighting::{Milliseconds, Weight, Weighting},
+};
+
+use super::{
+    matrix::Matrix,
+    matrix_algorithm::{MatrixAlgorithm, MatrixAlgorithmResult},
+};
+
+/// Node with the edge it was reached from, the edge is only kept at the nodes with turn
+/// restrictions, as in `AStar`
+type SearchState = (NodeId, EdgeId);
+
+#[derive(Clone, Copy)]
+struct SearchEntry {
+    weight: Weight,
+    time: Milliseconds,
+    distance: Distance<Meters>,
+    settled: bool,
+}
+
+/// Matrices on the base graph, for the profiles the contraction hierarchies are not prepared
+/// for. A Dijkstra is run from each source until every target is settled
+pub(crate) struct DijkstraMatrixAlgorithm<'a, G, W>
+where
+    G: Graph + UndirectedEdgeAccess + TurnRestrictionAccess,
+    W: Weighting<G>,
+{
+    graph: &'a G,
+    weighting: &'a W,
+
+    heap: BinaryHeap<Reverse<(Weight, NodeId, EdgeId)>>,
+    data: FxHashMap<SearchState, SearchEntry>,
+}
+
+impl<'a, G, W> DijkstraMatrixAlgorithm<'a, G, W>
+where
+    G: Graph + UndirectedEdgeAccess + TurnRestrictionAccess,
+    W: Weighting<G>,
+{
+    pub fn new(graph: &'a G, weighting: &'a W) -> Self {
+        DijkstraMatrixAlgorithm {
+            graph,
+            weighting,
+            heap: BinaryHeap::new(),
+            data: FxHashMap::default(),
+        }
+    }
+
+    fn search_state(&self, node: NodeId, edge_id: EdgeId) -> SearchState {
+        if self.graph.has_turn_restrictions(node) {
+            (node, edge_id)
+        } else {
+            (node, INVALID_EDGE)
+        }
+    }
+
+    /// Fills the row `source_index` of `matrix`, returns the number of visited nodes
+    fn calc_row(
+        &mut self,
+        matrix: &mut Matrix,
+        source_index: usize,
+        source: NodeId,
+        targets: &[NodeId],
+    ) -> usize {
+        self.heap.clear();
+        self.data.clear();
+
+        let mut remaining_targets = targets.iter().copied().collect::<FxHashSet<_>>();
+
+        self.data.insert(
+            (source, INVALID_EDGE),
+            SearchEntry {
+                weight: 0,
+                time: 0,
+                distance: Distance::default(),
+                settled: false,
+            },
+        );
+        self.heap.push(Reverse((0, source, INVALID_EDGE)));
+
+        let mut visited_nodes = 0;
+        while let Some(Reverse((weight, node, incoming_edge))) = self.heap.pop() {
+            let state = (node, incoming_edge);
+            let entry = self.data[&state];
+            if entry.settled || entry.weight < weight {
+                continue;
+            }
+
+            self.data.get_mut(&state).unwrap().settled = true;
+            visited_nodes += 1;
+
+            // The first state of a node to be settled is its lightest one
+            if remaining_targets.remove(&node) {
+                for (target_index, _) in targets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, target)| **target == node)
+                {
+                    matrix.update_entry(
+                        source_index,
+                        target_index,
+                        entry.weight,
+                        entry.distance,
+                        entry.time,
+                    );
+                }
+
+                if remaining_targets.is_empty() {
+                    break;
+                }
+            }
+
+            for edge_id in self.graph.node_edges_iter(node) {
+                if incoming_edge != INVALID_EDGE
+                    && !self.graph.is_turn_allowed(incoming_edge, node, edge_id)
+                {
+                    continue;
+                }
+
+                let edge = self.graph.edge(edge_id);
+                let direction = self.graph.edge_direction(edge_id, node);
+
+                let edge_weight = self.weighting.calc_edge_weight(edge, direction);
+                if edge_weight == MAX_WEIGHT {
+                    continue;
+                }
+
+                let adj_node = edge.adj_node(node);
+                let adj_state = self.search_state(adj_node, edge_id);
+                let next_weight = entry.weight + edge_weight;
+
+                if self
+                    .data
+                    .get(&adj_state)
+                    .is_none_or(|adj_entry| !adj_entry.settled && next_weight < adj_entry.weight)
+                {
+                    self.data.insert(
+                        adj_state,
+                        SearchEntry {
+                            weight: next_weight,
+                            time: entry.time + self.weighting.calc_edge_ms(edge, direction),
+                            distance: entry.distance + edge.distance(),
+                            settled: false,
+                        },
+                    );
+                    self.heap
+                        .push(Reverse((next_weight, adj_state.0, adj_state.1)));
+                }
+            }
+        }
+
+        visited_nodes
+    }
+}
+
+impl<G, W> MatrixAlgorithm for DijkstraMatrixAlgorithm<'_, G, W>
+where
+    G: Graph + UndirectedEdgeAccess + TurnRestrictionAccess,
+    W: Weighting<G>,
+{
+    fn calc_matrix(&mut self, sources: &[NodeId], targets: &[NodeId]) -> MatrixAlgorithmResult {
+        let mut stopwatch = Stopwatch::new(String::from("calc_dijkstra_matrix"));
+        stopwatch.start();
+
+        let mut matrix = Matrix::new(sources.len(), targets.len());
+        let mut visited_nodes = 0;
+        for (source_index, &source) in sources.iter().enumerate() {
+            visited_nodes += self.calc_row(&mut matrix, source_index, source, targets);
+        }
+
+        stopwatch.stop();
+        MatrixAlgorithmResult {
+            matrix,
+            visited_nodes,
+            duration: stopwatch.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        kilometers,
+        matrix::matrix_algorithm::MatrixAlgorithm,
+        meters,
+        test_graph_utils::test_graph::{RomaniaGraphCity, TestGraph, TestWeighting},
+    };
+
+    use super::DijkstraMatrixAlgorithm;
+
+    #[test]
+    fn test_calc_matrix() {
+        let graph = TestGraph::create_romania_graph();
+        let weighting = TestWeighting;
+        let mut algorithm = DijkstraMatrixAlgorithm::new(&graph, &weighting);
+
+        let sources = [
+            RomaniaGraphCity::Oradea.into(),
+            RomaniaGraphCity::Iasi.into(),
+        ];
+        let targets = [
+            RomaniaGraphCity::Bucharest.into(),
+            RomaniaGraphCity::Timisoara.into(),
+            RomaniaGraphCity::Oradea.into(),
+        ];
+
+        let result = algorithm.calc_matrix(&sources, &targets);
+
+        assert_eq!(
+            result.matrix.entry(0, 0).unwrap().distance(),
+            kilometers!(429)
+        );
+        assert_eq!(
+            result.matrix.entry(1, 1).unwrap().distance(),
+            kilometers!(855)
+        );
+        assert_eq!(result.matrix.entry(0, 2).unwrap().distance(), meters!(0));
+    }
+}
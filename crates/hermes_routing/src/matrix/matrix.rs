@@ -66,6 +66,17 @@ impl Matrix {
         });
     }
 
+    pub fn sources(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Moves the rows of `block` in the matrix from `first_source`, both have the same targets
+    pub fn set_block(&mut self, first_source: usize, block: Matrix) {
+        for (row, entries) in block.entries.into_iter().enumerate() {
+            self.entries[first_source + row] = entries;
+        }
+    }
+
     pub fn weight(&self, source_index: usize, target_index: usize) -> Weight {
         match &self.entries[source_index][target_index] {
             Some(entry) => entry.weight,
@@ -10,6 +10,16 @@ pub struct MatrixAlgorithmResult {
     pub duration: Duration,
 }
 
+/// Rows of a matrix computed together, see `Hermes::matrix_in_blocks`
+pub struct MatrixBlockResult {
+    /// Index of the first source of the block, the rows of `result` start from it
+    pub first_source: usize,
+    pub result: MatrixAlgorithmResult,
+    /// Sources computed so far, this block included
+    pub computed_sources: usize,
+    pub total_sources: usize,
+}
+
 pub trait MatrixAlgorithm {
     fn calc_matrix(&mut self, sources: &[NodeId], targets: &[NodeId]) -> MatrixAlgorithmResult;
 }
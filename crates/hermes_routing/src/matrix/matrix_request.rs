@@ -1,12 +1,73 @@
-use crate::geopoint::GeoPoint;
+use serde::Deserialize;
+
+use crate::{
+    avoid::AvoidOptions, geometry_encoding::GeometryEncoding, geopoint::GeoPoint,
+    snap::SnapOptions, weighting::VehicleDimensions,
+};
+
+/// Values accumulated for each entry of the matrix, the paths are the fastest ones in every case
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixMetrics {
+    #[default]
+    DurationsAndDistances,
+    /// The distances of the entries are left at 0
+    Durations,
+    /// The times of the entries are left at 0
+    Distances,
+}
+
+impl MatrixMetrics {
+    pub fn includes_durations(self) -> bool {
+        self != MatrixMetrics::Distances
+    }
+
+    pub fn includes_distances(self) -> bool {
+        self != MatrixMetrics::Durations
+    }
+}
 
 pub struct MatrixRequestOptions {
     pub include_debug_info: Option<bool>,
+    pub metrics: Option<MatrixMetrics>,
+    /// Encoding of the points the sources and targets are snapped to, see
+    /// `Hermes::snapped_points`, the snapped points are not computed when not set
+    pub snapped_points_encoding: Option<GeometryEncoding>,
+    /// Computed on the base graph without the contraction hierarchies, much slower
+    pub avoid: Option<AvoidOptions>,
+    /// The request fails when a source or a target can't be snapped within these options
+    pub snap: Option<SnapOptions>,
+    /// Reports the roads the sources and targets are snapped to, see `Hermes::snap_reports`
+    pub include_snap_reports: Option<bool>,
 }
 
 pub struct MatrixRequest {
     pub sources: Vec<GeoPoint>,
     pub targets: Vec<GeoPoint>,
     pub profile: String,
+    /// Only used by the `"truck"` profile
+    pub vehicle_dimensions: Option<VehicleDimensions>,
     pub options: Option<MatrixRequestOptions>,
 }
+
+impl MatrixRequest {
+    pub fn metrics(&self) -> MatrixMetrics {
+        self.options
+            .as_ref()
+            .and_then(|options| options.metrics)
+            .unwrap_or_default()
+    }
+
+    pub fn snap_options(&self) -> SnapOptions {
+        self.options
+            .as_ref()
+            .and_then(|options| options.snap.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn avoid(&self) -> Option<&AvoidOptions> {
+        self.options
+            .as_ref()
+            .and_then(|options| options.avoid.as_ref())
+    }
+}
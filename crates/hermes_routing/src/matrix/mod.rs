@@ -1,5 +1,7 @@
+pub(crate) mod dijkstra_matrix_algorithm;
 pub mod matrix;
 pub(crate) mod matrix_algorithm;
 pub mod matrix_request;
+pub(crate) mod one_to_many_algorithm;
 mod ranked_node;
 pub(crate) mod sbi_matrix_algorithm;
@@ -0,0 +1,186 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    ch::ch_graph::NodeRank,
+    constants::MAX_WEIGHT,
+    distance::{Distance, Meters},
+    graph::{DirectedEdgeAccess, Graph},
+    graph_edge::GraphEdge,
+    routing::search_direction::SearchDirection,
+    stopwatch::Stopwatch,
+    types::NodeId,
+    weighting::{Milliseconds, Weight, Weighting},
+};
+
+use super::{matrix::Matrix, matrix_algorithm::MatrixAlgorithmResult};
+
+#[derive(Clone, Copy, Default)]
+struct SearchEntry {
+    weight: Weight,
+    time: Milliseconds,
+    distance: Distance<Meters>,
+}
+
+/// Nodes reached by the upward search from a node, with the weight to reach them
+type SearchSpace = FxHashMap<NodeId, SearchEntry>;
+
+/// Matrices with a single source or a single target.
+///
+/// The upward search of the single node is run once and intersected with the upward search of
+/// each other node, instead of one bidirectional query per pair
+pub(crate) struct OneToManyAlgorithm<'a, G, W>
+where
+    G: Graph + DirectedEdgeAccess + NodeRank,
+    W: Weighting<G>,
+{
+    graph: &'a G,
+    weighting: &'a W,
+}
+
+impl<'a, G, W> OneToManyAlgorithm<'a, G, W>
+where
+    G: Graph + DirectedEdgeAccess + NodeRank,
+    W: Weighting<G>,
+{
+    pub fn new(graph: &'a G, weighting: &'a W) -> Self {
+        OneToManyAlgorithm { graph, weighting }
+    }
+
+    /// Dijkstra on the edges towards higher ranked nodes, run until the heap is empty so that the
+    /// search space can be intersected with the one of any other node
+    fn upward_search(&self, start: NodeId, search_direction: SearchDirection) -> SearchSpace {
+        let mut search_space = SearchSpace::default();
+        let mut heap = BinaryHeap::new();
+
+        search_space.insert(start, SearchEntry::default());
+        heap.push(Reverse((0, start)));
+
+        while let Some(Reverse((weight, node))) = heap.pop() {
+            let entry = search_space[&node];
+            if entry.weight < weight {
+                continue;
+            }
+
+            let iter = match search_direction {
+                SearchDirection::Forward => self.graph.node_outgoing_edges_iter(node),
+                SearchDirection::Backward => self.graph.node_incoming_edges_iter(node),
+            };
+
+            for edge_id in iter {
+                let edge = self.graph.edge(edge_id);
+                let adj_node = edge.adj_node(node);
+
+                if !self.graph.is_virtual_node(node)
+                    && !self.graph.is_virtual_node(adj_node)
+                    && self.graph.node_rank(node) >= self.graph.node_rank(adj_node)
+                {
+                    continue;
+                }
+
+                let edge_direction = match search_direction {
+                    SearchDirection::Forward => self.graph.edge_direction(edge_id, node),
+                    SearchDirection::Backward => {
+                        self.graph.edge_direction(edge_id, node).opposite()
+                    }
+                };
+
+                let edge_weight = self.weighting.calc_edge_weight(edge, edge_direction);
+                if edge_weight == MAX_WEIGHT {
+                    continue;
+                }
+
+                let next_weight = entry.weight + edge_weight;
+                if search_space
+                    .get(&adj_node)
+                    .is_none_or(|adj_entry| next_weight < adj_entry.weight)
+                {
+                    search_space.insert(
+                        adj_node,
+                        SearchEntry {
+                            weight: next_weight,
+                            time: entry.time + self.weighting.calc_edge_ms(edge, edge_direction),
+                            distance: entry.distance + edge.distance(),
+                        },
+                    );
+                    heap.push(Reverse((next_weight, adj_node)));
+                }
+            }
+        }
+
+        search_space
+    }
+
+    /// Lightest path through a node reached by both searches, `None` when they don't meet
+    fn meet(forward: &SearchSpace, backward: &SearchSpace) -> Option<SearchEntry> {
+        let (smaller, larger) = if forward.len() <= backward.len() {
+            (forward, backward)
+        } else {
+            (backward, forward)
+        };
+
+        smaller
+            .iter()
+            .filter_map(|(node, entry)| {
+                larger.get(node).map(|other| SearchEntry {
+                    weight: entry.weight + other.weight,
+                    time: entry.time + other.time,
+                    distance: entry.distance + other.distance,
+                })
+            })
+            .min_by_key(|entry| entry.weight)
+    }
+
+    /// Single row matrix from `source` to each of `targets`
+    pub fn calc_one_to_many(&self, source: NodeId, targets: &[NodeId]) -> MatrixAlgorithmResult {
+        self.calc(source, targets, SearchDirection::Forward)
+    }
+
+    /// Single column matrix from each of `sources` to `target`
+    pub fn calc_many_to_one(&self, sources: &[NodeId], target: NodeId) -> MatrixAlgorithmResult {
+        self.calc(target, sources, SearchDirection::Backward)
+    }
+
+    /// Searches from `node` in `search_direction` once, then from each of `others` in the
+    /// opposite direction
+    fn calc(
+        &self,
+        node: NodeId,
+        others: &[NodeId],
+        search_direction: SearchDirection,
+    ) -> MatrixAlgorithmResult {
+        let mut stopwatch = Stopwatch::new(String::from("calc_one_to_many"));
+        stopwatch.start();
+
+        let mut matrix = match search_direction {
+            SearchDirection::Forward => Matrix::new(1, others.len()),
+            SearchDirection::Backward => Matrix::new(others.len(), 1),
+        };
+
+        let search_space = self.upward_search(node, search_direction);
+        let mut visited_nodes = search_space.len();
+
+        for (index, &other) in others.iter().enumerate() {
+            let other_search_space = self.upward_search(other, search_direction.opposite());
+            visited_nodes += other_search_space.len();
+
+            let Some(entry) = Self::meet(&search_space, &other_search_space) else {
+                continue;
+            };
+
+            let (source, target) = match search_direction {
+                SearchDirection::Forward => (0, index),
+                SearchDirection::Backward => (index, 0),
+            };
+            matrix.update_entry(source, target, entry.weight, entry.distance, entry.time);
+        }
+
+        stopwatch.stop();
+        MatrixAlgorithmResult {
+            matrix,
+            visited_nodes,
+            duration: stopwatch.elapsed(),
+        }
+    }
+}
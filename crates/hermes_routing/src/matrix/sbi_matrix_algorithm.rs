@@ -17,6 +17,7 @@ use crate::{
 use super::{
     matrix::Matrix,
     matrix_algorithm::{MatrixAlgorithm, MatrixAlgorithmResult},
+    matrix_request::MatrixMetrics,
     ranked_node::RankedNode,
 };
 
@@ -45,6 +46,8 @@ where
     up_vertices: FxHashMap<NodeId, Vec<UpEdge>>,
 
     traversed_nodes: FxHashSet<NodeId>,
+
+    metrics: MatrixMetrics,
 }
 
 impl<'a, G, W> SBIMatrixAlgorithm<'a, G, W>
@@ -52,7 +55,7 @@ where
     G: Graph + DirectedEdgeAccess + NodeRank,
     W: Weighting<G>,
 {
-    pub fn new(graph: &'a G, weighting: &'a W) -> Self {
+    pub fn new(graph: &'a G, weighting: &'a W, metrics: MatrixMetrics) -> Self {
         SBIMatrixAlgorithm {
             graph,
             weighting,
@@ -64,6 +67,20 @@ where
             down_vertices: FxHashMap::default(),
             up_vertices: FxHashMap::default(),
             traversed_nodes: FxHashSet::default(),
+            metrics,
+        }
+    }
+
+    /// Clears the state of the previous search, the algorithm is reused for each block of sources
+    fn reset(&mut self, search_direction: SearchDirection) {
+        self.heap.clear();
+        self.traversed_nodes.clear();
+        self.up_vertices.clear();
+        self.down_vertices.clear();
+
+        match search_direction {
+            SearchDirection::Forward => self.forward_buckets.clear(),
+            SearchDirection::Backward => self.backward_buckets.clear(),
         }
     }
 
@@ -100,6 +117,8 @@ where
     }
 
     fn initialize_backward_search(&mut self, targets: &[NodeId]) {
+        self.reset(SearchDirection::Backward);
+
         for &target in targets {
             self.heap.push(RankedNode {
                 node_id: target,
@@ -119,10 +138,7 @@ where
     }
 
     fn initialize_forward_search(&mut self, sources: &[NodeId]) {
-        self.heap.clear();
-        self.traversed_nodes.clear();
-        self.up_vertices.clear();
-        self.down_vertices.clear();
+        self.reset(SearchDirection::Forward);
 
         for &source in sources {
             self.heap.push(RankedNode {
@@ -183,8 +199,16 @@ where
                 .push(DownEdge {
                     node_id: node,
                     weight,
-                    time: self.weighting.calc_edge_ms(edge, edge_direction),
-                    distance: edge.distance(),
+                    time: if self.metrics.includes_durations() {
+                        self.weighting.calc_edge_ms(edge, edge_direction)
+                    } else {
+                        0
+                    },
+                    distance: if self.metrics.includes_distances() {
+                        edge.distance()
+                    } else {
+                        Distance::default()
+                    },
                 });
         }
     }
@@ -270,9 +294,10 @@ where
                         if let Some(current_weight) = node_bucket
                             .and_then(|bucket| bucket.get(&source_or_target))
                             .map(|entry| entry.weight)
-                            && entry.weight > up_edge.weight + current_weight {
-                                nodes_to_prune.push((up_edge.node_id, source_or_target));
-                            }
+                            && entry.weight > up_edge.weight + current_weight
+                        {
+                            nodes_to_prune.push((up_edge.node_id, source_or_target));
+                        }
                     }
                 }
             }
@@ -1 +1,2 @@
-pub mod osm_reader;
\ No newline at end of file
+pub mod osm_change;
+pub mod osm_reader;
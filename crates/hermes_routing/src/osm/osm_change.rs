@@ -0,0 +1,351 @@
+use std::{io::Read, path::Path};
+
+use flate2::read::GzDecoder;
+
+use crate::error::OsmChangeError;
+
+/// https://wiki.openstreetmap.org/wiki/OsmChange
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OsmChangeAction {
+    Create,
+    Modify,
+    Delete,
+}
+
+impl OsmChangeAction {
+    fn from_element(name: &str) -> Option<Self> {
+        match name {
+            "create" => Some(OsmChangeAction::Create),
+            "modify" => Some(OsmChangeAction::Modify),
+            "delete" => Some(OsmChangeAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// New version of a way, the deleted ways have no nodes nor tags
+pub struct OsmChangeWay {
+    pub id: i64,
+    pub action: OsmChangeAction,
+    pub nodes: Vec<i64>,
+    pub tags: osmpbfreader::Tags,
+}
+
+/// Ways of an osmChange file. The nodes and relations are only counted, the graph does not keep
+/// the OSM nodes so their moves cannot be applied without a full import
+#[derive(Default)]
+pub struct OsmChange {
+    pub ways: Vec<OsmChangeWay>,
+    pub node_changes: usize,
+    pub relation_changes: usize,
+}
+
+/// Changes applied to a graph by `BaseGraph::apply_osm_change`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct OsmChangeSummary {
+    pub modified_ways: usize,
+    pub deleted_ways: usize,
+    pub updated_edges: usize,
+    /// New ways, ways that were not roads of the graph and ways whose nodes changed, they need a
+    /// full import
+    pub skipped_ways: usize,
+    /// Whether the car weight of an edge changed, the contraction hierarchies and the landmarks of
+    /// the car profile are then prepared again
+    pub car_weights_changed: bool,
+}
+
+struct XmlElement<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+    is_closing: bool,
+    is_self_closing: bool,
+}
+
+impl XmlElement<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn id_attribute(&self, name: &str) -> Result<i64, OsmChangeError> {
+        self.attribute(name)
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| {
+                OsmChangeError::InvalidXml(format!("<{}> without a valid {name}", self.name))
+            })
+    }
+}
+
+/// Replaces the predefined entities and the character references
+fn unescape(value: &str) -> Result<String, OsmChangeError> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .map(|end| start + end)
+            .ok_or_else(|| OsmChangeError::InvalidXml(format!("Unterminated entity in {value}")))?;
+
+        let entity = &rest[start + 1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+
+        unescaped.push(
+            character
+                .ok_or_else(|| OsmChangeError::InvalidXml(format!("Unknown entity &{entity};")))?,
+        );
+        rest = &rest[end + 1..];
+    }
+
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+/// Index of the `>` closing the element, `>` can appear in the quoted attribute values
+fn element_end(content: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, character) in content.char_indices() {
+        match (quote, character) {
+            (None, '>') => return Some(index),
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if open == character => quote = None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_element(content: &str) -> Result<XmlElement<'_>, OsmChangeError> {
+    let invalid = || OsmChangeError::InvalidXml(format!("Invalid element <{content}>"));
+
+    if let Some(name) = content.strip_prefix('/') {
+        return Ok(XmlElement {
+            name: name.trim(),
+            attributes: vec![],
+            is_closing: true,
+            is_self_closing: false,
+        });
+    }
+
+    let (content, is_self_closing) = match content.trim_end().strip_suffix('/') {
+        Some(content) => (content, true),
+        None => (content, false),
+    };
+
+    let name_end = content.find(char::is_whitespace).unwrap_or(content.len());
+    let mut rest = content[name_end..].trim_start();
+    let mut attributes = vec![];
+
+    while !rest.is_empty() {
+        let (name, value) = rest.split_once('=').ok_or_else(invalid)?;
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'');
+        let quote = quote.ok_or_else(invalid)?;
+        let value_end = value[1..].find(quote).ok_or_else(invalid)? + 1;
+
+        attributes.push((name.trim(), unescape(&value[1..value_end])?));
+        rest = value[value_end + 1..].trim_start();
+    }
+
+    Ok(XmlElement {
+        name: &content[..name_end],
+        attributes,
+        is_closing: false,
+        is_self_closing,
+    })
+}
+
+impl OsmChange {
+    /// Reads an `.osc` file, or an `.osc.gz` file as published by the replication servers
+    pub fn from_file(path: impl AsRef<Path>) -> Result<OsmChange, OsmChangeError> {
+        let path = path.as_ref();
+        let xml = if path.extension().is_some_and(|extension| extension == "gz") {
+            let file = std::fs::File::open(path).map_err(OsmChangeError::Read)?;
+            let mut xml = String::new();
+            GzDecoder::new(file)
+                .read_to_string(&mut xml)
+                .map_err(OsmChangeError::Read)?;
+            xml
+        } else {
+            std::fs::read_to_string(path).map_err(OsmChangeError::Read)?
+        };
+
+        OsmChange::parse(&xml)
+    }
+
+    pub fn parse(xml: &str) -> Result<OsmChange, OsmChangeError> {
+        let mut change = OsmChange::default();
+        let mut action = None;
+        let mut way: Option<OsmChangeWay> = None;
+        let mut rest = xml;
+
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+
+            if let Some(comment) = rest.strip_prefix("!--") {
+                let end = comment.find("-->").ok_or_else(|| {
+                    OsmChangeError::InvalidXml(String::from("Unterminated comment"))
+                })?;
+                rest = &comment[end + 3..];
+                continue;
+            }
+
+            let end = element_end(rest)
+                .ok_or_else(|| OsmChangeError::InvalidXml(String::from("Unterminated element")))?;
+            let content = &rest[..end];
+            rest = &rest[end + 1..];
+
+            // Declaration and doctype
+            if content.starts_with('?') || content.starts_with('!') {
+                continue;
+            }
+
+            let element = parse_element(content)?;
+
+            if let Some(element_action) = OsmChangeAction::from_element(element.name) {
+                action = (!element.is_closing).then_some(element_action);
+                continue;
+            }
+
+            if element.is_closing {
+                if element.name == "way" {
+                    change.ways.extend(way.take());
+                }
+                continue;
+            }
+
+            match element.name {
+                "node" if action.is_some() => change.node_changes += 1,
+                "relation" if action.is_some() => change.relation_changes += 1,
+                "way" => {
+                    let action = action.ok_or_else(|| {
+                        OsmChangeError::InvalidXml(String::from(
+                            "<way> outside of <create>, <modify> or <delete>",
+                        ))
+                    })?;
+
+                    let changed_way = OsmChangeWay {
+                        id: element.id_attribute("id")?,
+                        action,
+                        nodes: vec![],
+                        tags: osmpbfreader::Tags::new(),
+                    };
+
+                    if element.is_self_closing {
+                        change.ways.push(changed_way);
+                    } else {
+                        way = Some(changed_way);
+                    }
+                }
+                "nd" => {
+                    if let Some(way) = &mut way {
+                        way.nodes.push(element.id_attribute("ref")?);
+                    }
+                }
+                "tag" => {
+                    if let (Some(way), Some(key), Some(value)) =
+                        (&mut way, element.attribute("k"), element.attribute("v"))
+                    {
+                        way.tags.insert(key.into(), value.into());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use super::*;
+
+    const OSM_CHANGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osmChange version="0.6" generator="test">
+  <modify>
+    <node id="1" version="2" lat="50.1" lon="4.1"/>
+    <way id="10" version="3">
+      <nd ref="1"/>
+      <nd ref="2"/>
+      <tag k="highway" v="residential"/>
+      <tag k="name" v="Rue &quot;Haute&quot; &amp; Basse"/>
+      <tag k="oneway" v='yes'/>
+    </way>
+  </modify>
+  <!-- <way id="99"> -->
+  <delete>
+    <way id="11" version="4" user="a > b"/>
+    <relation id="100" version="2"/>
+  </delete>
+  <create>
+    <way id="12" version="1">
+      <nd ref="2"/>
+      <nd ref="3"/>
+      <tag k="highway" v="service"/>
+    </way>
+  </create>
+</osmChange>
+"#;
+
+    #[test]
+    fn test_parse() {
+        let change = OsmChange::parse(OSM_CHANGE).unwrap();
+
+        assert_eq!(change.node_changes, 1);
+        assert_eq!(change.relation_changes, 1);
+        assert_eq!(change.ways.len(), 3);
+
+        let modified = &change.ways[0];
+        assert_eq!(modified.id, 10);
+        assert_eq!(modified.action, OsmChangeAction::Modify);
+        assert_eq!(modified.nodes, vec![1, 2]);
+        assert!(modified.tags.contains("name", "Rue \"Haute\" & Basse"));
+        assert!(modified.tags.contains("oneway", "yes"));
+
+        assert_eq!(change.ways[1].id, 11);
+        assert_eq!(change.ways[1].action, OsmChangeAction::Delete);
+        assert!(change.ways[1].tags.is_empty());
+
+        assert_eq!(change.ways[2].action, OsmChangeAction::Create);
+    }
+
+    #[test]
+    fn test_from_gzip_file() {
+        let path = std::env::temp_dir().join("hermes_osm_change.osc.gz");
+        let file = std::fs::File::create(&path).unwrap();
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(OSM_CHANGE.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let change = OsmChange::from_file(&path).unwrap();
+        assert_eq!(change.ways.len(), 3);
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("a &lt;b&gt; &#233;&#x41;").unwrap(), "a <b> éA");
+        assert!(unescape("a & b").is_err());
+        assert!(OsmChange::parse("<osmChange><way id=\"1\"/></osmChange>").is_err());
+    }
+}
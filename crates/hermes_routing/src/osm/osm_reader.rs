@@ -2,9 +2,10 @@ use crate::geopoint::GeoPoint;
 use crate::properties::property::Property;
 use crate::properties::property_map::EdgePropertyMap;
 use crate::properties::tag_parser::parse_way_tags;
+use crate::turn_restrictions::TurnRestrictionKind;
 
 use fxhash::FxHashMap;
-use osmpbfreader::{NodeId, OsmObj, OsmPbfReader};
+use osmpbfreader::{NodeId, OsmId, OsmObj, OsmPbfReader, WayId};
 use std::{fs::File, path::Path};
 use tracing::info;
 
@@ -13,12 +14,21 @@ struct OsmNode {
     // tags: HashMap<String, String>,
 }
 
+/// Roads and ferry routes, most ferry routes have no `highway` tag
+pub(crate) fn is_road(tags: &osmpbfreader::Tags) -> bool {
+    tags.contains_key("highway") || tags.contains("route", "ferry")
+}
+
 pub struct OsmWay<'a> {
     osm_id: usize,
     tags: &'a osmpbfreader::Tags,
 }
 
-impl OsmWay<'_> {
+impl<'a> OsmWay<'a> {
+    pub(crate) fn new(osm_id: usize, tags: &'a osmpbfreader::Tags) -> Self {
+        OsmWay { osm_id, tags }
+    }
+
     pub fn osm_id(&self) -> usize {
         self.osm_id
     }
@@ -42,13 +52,44 @@ enum OsmNodeType {
     Geometry,
 }
 
+/// Properties of the edges of the way, also used to update the edges from an osmChange file
+pub(crate) fn parse_way_properties(way: &OsmWay) -> EdgePropertyMap {
+    let mut properties = EdgePropertyMap::default();
+
+    parse_way_tags(way, &mut properties, Property::MaxSpeed);
+    parse_way_tags(way, &mut properties, Property::CarVehicleAccess);
+    parse_way_tags(way, &mut properties, Property::CarAverageSpeed);
+    parse_way_tags(way, &mut properties, Property::OsmId);
+    parse_way_tags(way, &mut properties, Property::MaxHeight);
+    parse_way_tags(way, &mut properties, Property::MaxWidth);
+    parse_way_tags(way, &mut properties, Property::MaxWeight);
+    parse_way_tags(way, &mut properties, Property::HgvAccess);
+    parse_way_tags(way, &mut properties, Property::StreetName);
+    parse_way_tags(way, &mut properties, Property::StreetRef);
+    parse_way_tags(way, &mut properties, Property::Roundabout);
+    parse_way_tags(way, &mut properties, Property::Motorway);
+    parse_way_tags(way, &mut properties, Property::Toll);
+    parse_way_tags(way, &mut properties, Property::Ferry);
+
+    properties
+}
+
 pub struct OsmWaySegment {
+    pub osm_way_id: i64,
     pub start_node: usize,
     pub end_node: usize,
     pub geometry: Vec<GeoPoint>,
     pub properties: EdgePropertyMap,
 }
 
+/// Turn restriction relation through a node, the restrictions through a way are not supported
+pub struct OsmTurnRestriction {
+    pub from_way: i64,
+    pub via_node: i64,
+    pub to_way: i64,
+    pub kind: TurnRestrictionKind,
+}
+
 #[derive(Default)]
 pub struct OsmReader {
     accepted_ways: usize,
@@ -57,6 +98,7 @@ pub struct OsmReader {
     geometry_nodes: Vec<OsmNode>,
     osm_node_id_to_node_type: FxHashMap<i64, OsmNodeType>,
     osm_node_id_to_node_id: FxHashMap<i64, usize>,
+    turn_restrictions: Vec<OsmTurnRestriction>,
 }
 
 impl OsmReader {
@@ -93,20 +135,70 @@ impl OsmReader {
             return false;
         }
 
-        if !way.tags.contains_key("highway") {
-            return false;
+        is_road(&way.tags)
+    }
+
+    /// Restriction for cars, `None` for the other relations
+    fn parse_turn_restriction(relation: &osmpbfreader::Relation) -> Option<OsmTurnRestriction> {
+        if !relation.tags.contains("type", "restriction") {
+            return None;
+        }
+
+        if relation
+            .tags
+            .get("except")
+            .is_some_and(|except| except.split(';').any(|vehicle| vehicle == "motorcar"))
+        {
+            return None;
+        }
+
+        let restriction = relation
+            .tags
+            .get("restriction:motorcar")
+            .or_else(|| relation.tags.get("restriction"))?;
+
+        let kind = if restriction.starts_with("no_") {
+            TurnRestrictionKind::No
+        } else if restriction.starts_with("only_") {
+            TurnRestrictionKind::Only
+        } else {
+            return None;
+        };
+
+        let mut from_way = None;
+        let mut via_node = None;
+        let mut to_way = None;
+
+        for member in &relation.refs {
+            match (member.role.as_str(), member.member) {
+                ("from", OsmId::Way(WayId(way_id))) => from_way = Some(way_id),
+                ("via", OsmId::Node(NodeId(node_id))) => via_node = Some(node_id),
+                ("to", OsmId::Way(WayId(way_id))) => to_way = Some(way_id),
+                ("via", _) => return None,
+                _ => {}
+            }
         }
 
-        true
+        Some(OsmTurnRestriction {
+            from_way: from_way?,
+            via_node: via_node?,
+            to_way: to_way?,
+            kind,
+        })
     }
 
-    /// First pass reads the ways, stores each node with its type
+    /// First pass reads the ways, stores each node with its type, and the turn restrictions
     fn handle_element_first_pass(&mut self, reader: &mut OsmPbfReader<File>) {
-        for object in reader
-            .par_iter()
-            .filter_map(Result::ok)
-            .filter(|object| object.is_way() && OsmReader::accept_way(object.way().unwrap()))
-        {
+        for object in reader.par_iter().filter_map(Result::ok).filter(|object| {
+            object.way().is_some_and(OsmReader::accept_way) || object.is_relation()
+        }) {
+            if let Some(restriction) = object
+                .relation()
+                .and_then(OsmReader::parse_turn_restriction)
+            {
+                self.turn_restrictions.push(restriction);
+            }
+
             if let Some(way) = object.way() {
                 let way_node_count = way.nodes.len();
 
@@ -132,6 +224,10 @@ impl OsmReader {
         }
 
         info!("Preprocessed {} ways", self.accepted_ways);
+        info!(
+            "Preprocessed {} turn restrictions",
+            self.turn_restrictions.len()
+        );
     }
 
     /// Second pass reads all the nodes, for each node contained in a way from the first pass, it stores the coordinates of that node
@@ -159,13 +255,7 @@ impl OsmReader {
                         tags: &raw_way.tags,
                     };
 
-                    let mut properties = EdgePropertyMap::default();
-
-                    // TODO: move somewhere else
-                    parse_way_tags(&way, &mut properties, Property::MaxSpeed);
-                    parse_way_tags(&way, &mut properties, Property::CarVehicleAccess);
-                    parse_way_tags(&way, &mut properties, Property::CarAverageSpeed);
-                    parse_way_tags(&way, &mut properties, Property::OsmId);
+                    let properties = parse_way_properties(&way);
 
                     let nodes: Vec<i64> = raw_way
                         .nodes
@@ -210,6 +300,7 @@ impl OsmReader {
                             .collect();
 
                         handle_edge(OsmWaySegment {
+                            osm_way_id: raw_way.id.0,
                             start_node,
                             end_node,
                             geometry,
@@ -301,6 +392,19 @@ impl OsmReader {
         }
     }
 
+    /// Node ID in the graph of a routing node
+    pub fn routing_node_id(&self, osm_id: i64) -> Option<usize> {
+        if !self.is_routing_node(osm_id) {
+            return None;
+        }
+
+        self.osm_node_id_to_node_id.get(&osm_id).copied()
+    }
+
+    pub fn turn_restrictions(&self) -> &[OsmTurnRestriction] {
+        &self.turn_restrictions
+    }
+
     fn is_geometry_node(&self, osm_id: i64) -> bool {
         matches!(
             self.osm_node_id_to_node_type.get(&osm_id),
@@ -340,6 +444,22 @@ mod tests {
         }
     }
 
+    fn tags(tags: &[(&str, &str)]) -> osmpbfreader::Tags {
+        let mut result = osmpbfreader::Tags::new();
+        for &(key, value) in tags {
+            result.insert(key.into(), value.into());
+        }
+        result
+    }
+
+    #[test]
+    fn test_is_road() {
+        assert!(is_road(&tags(&[("highway", "residential")])));
+        assert!(is_road(&tags(&[("route", "ferry"), ("motorcar", "yes")])));
+        assert!(!is_road(&tags(&[("route", "bus")])));
+        assert!(!is_road(&tags(&[("waterway", "river")])));
+    }
+
     #[test]
     fn test_split_way_no_junctions() {
         let mut reader = create_test_osm_reader();
@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{avoid::RoadClass, error::StorageError, weighting::TRUCK_MAX_SPEED};
+
+/// Weights the paths of a profile are compared by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Travel time, with a small influence of the distance
+    #[default]
+    Fastest,
+    Shortest,
+}
+
+/// Routing profile, see `ProfileWeighting`. The profiles of a graph are read from the
+/// `profiles.json` file of its directory, a list of profiles, `ProfileConfig::defaults` otherwise
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub name: String,
+    #[serde(default)]
+    pub metric: Metric,
+    /// In km/h, the car speeds above it are capped
+    pub max_speed: Option<f32>,
+    /// Heavy goods vehicles don't use the ways forbidden to them, nor the ways too low, narrow or
+    /// weak for the `VehicleDimensions` of the request
+    #[serde(default)]
+    pub hgv: bool,
+    /// Road classes the profile never uses
+    #[serde(default)]
+    pub avoid: Vec<RoadClass>,
+    /// The contraction hierarchies of the profile are customized when the graph is loaded, the
+    /// other profiles are computed on the base graph
+    #[serde(default)]
+    pub customize: bool,
+}
+
+impl ProfileConfig {
+    /// `car`, `car_shortest`, `car_avoid_tolls` and `truck`, all but `car` customized
+    pub fn defaults() -> Vec<ProfileConfig> {
+        let car = ProfileConfig {
+            name: String::from("car"),
+            metric: Metric::Fastest,
+            max_speed: None,
+            hgv: false,
+            avoid: vec![],
+            customize: false,
+        };
+
+        vec![
+            ProfileConfig {
+                name: String::from("car_shortest"),
+                metric: Metric::Shortest,
+                customize: true,
+                ..car.clone()
+            },
+            ProfileConfig {
+                name: String::from("car_avoid_tolls"),
+                avoid: vec![RoadClass::Toll],
+                customize: true,
+                ..car.clone()
+            },
+            ProfileConfig {
+                name: String::from("truck"),
+                max_speed: Some(TRUCK_MAX_SPEED),
+                hgv: true,
+                customize: true,
+                ..car.clone()
+            },
+            car,
+        ]
+    }
+
+    /// Same weights as `CarWeighting`, which the contraction hierarchies are prepared with
+    pub fn is_car(&self) -> bool {
+        self.metric == Metric::Fastest
+            && self.max_speed.is_none()
+            && !self.hgv
+            && self.avoid.is_empty()
+    }
+
+    /// `ProfileConfig::defaults` when the file doesn't exist
+    pub fn load_from_file(path: &str) -> Result<Vec<ProfileConfig>, StorageError> {
+        if !Path::new(path).exists() {
+            return Ok(Self::defaults());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|error| StorageError::Read(path.to_owned(), error))?;
+        let profiles = serde_json::from_str::<Vec<ProfileConfig>>(&content)
+            .map_err(|error| StorageError::Deserialize(path.to_owned(), error.to_string()))?;
+
+        if let Some(profile) = profiles
+            .iter()
+            .find(|profile| profile.max_speed.is_some_and(|speed| speed <= 0.0))
+        {
+            return Err(StorageError::Deserialize(
+                path.to_owned(),
+                format!("the max_speed of profile {} must be positive", profile.name),
+            ));
+        }
+
+        for (index, profile) in profiles.iter().enumerate() {
+            if profiles[..index]
+                .iter()
+                .any(|other| other.name == profile.name)
+            {
+                return Err(StorageError::Deserialize(
+                    path.to_owned(),
+                    format!("profile {} is defined twice", profile.name),
+                ));
+            }
+        }
+
+        Ok(profiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_profiles(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("hermes_profiles_{name}.json"));
+        std::fs::write(&path, content).unwrap();
+        path.into_os_string().into_string().unwrap()
+    }
+
+    #[test]
+    fn test_load_profiles() {
+        let path = write_profiles(
+            "valid",
+            r#"[
+                { "name": "car" },
+                { "name": "van", "max_speed": 110, "avoid": ["ferry"], "customize": true }
+            ]"#,
+        );
+        let profiles = ProfileConfig::load_from_file(&path).unwrap();
+
+        assert_eq!(profiles.len(), 2);
+        assert!(profiles[0].is_car());
+        assert_eq!(profiles[1].max_speed, Some(110.0));
+        assert_eq!(profiles[1].avoid, vec![RoadClass::Ferry]);
+        assert!(!profiles[1].is_car());
+    }
+
+    #[test]
+    fn test_load_missing_profiles() {
+        let profiles = ProfileConfig::load_from_file("/nonexistent/profiles.json").unwrap();
+        assert_eq!(profiles, ProfileConfig::defaults());
+    }
+
+    #[test]
+    fn test_load_invalid_profiles() {
+        let path = write_profiles(
+            "duplicate",
+            r#"[{ "name": "car" }, { "name": "car", "hgv": true }]"#,
+        );
+        assert!(ProfileConfig::load_from_file(&path).is_err());
+
+        let path = write_profiles("speed", r#"[{ "name": "car", "max_speed": 0 }]"#);
+        assert!(ProfileConfig::load_from_file(&path).is_err());
+
+        let path = write_profiles("unknown", r#"[{ "name": "car", "speed": 50 }]"#);
+        assert!(ProfileConfig::load_from_file(&path).is_err());
+    }
+}
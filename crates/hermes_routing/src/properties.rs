@@ -4,4 +4,7 @@ mod max_speed_parser;
 mod osm_id_parser;
 pub mod property;
 pub mod property_map;
+mod road_class_parser;
+mod street_parser;
 pub mod tag_parser;
+mod truck_restrictions_parser;
@@ -29,6 +29,18 @@ static ONEWAYS: [&str; 4] = ["yes", "true", "1", "-1"];
 pub struct CarAccessParser;
 
 fn car_access(way: &OsmWay) -> WayAccess {
+    // https://wiki.openstreetmap.org/wiki/Tag:route%3Dferry
+    if way.has_tag("route", "ferry") {
+        // The ferries without access tags are assumed to carry cars unless they are for pedestrians
+        // or bicycles
+        let motorcar = way.tag("motorcar").or_else(|| way.tag("motor_vehicle"));
+        return match motorcar {
+            Some("yes") => WayAccess::Way,
+            None if way.tag("foot").is_none() && way.tag("bicycle").is_none() => WayAccess::Way,
+            _ => WayAccess::None,
+        };
+    }
+
     let highway = way.tag("highway");
 
     if highway.is_none() {
@@ -97,3 +109,32 @@ enum WayAccess {
     Way,
     None,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(tags: &[(&str, &str)]) -> bool {
+        let mut osm_tags = osmpbfreader::Tags::new();
+        for &(key, value) in tags {
+            osm_tags.insert(key.into(), value.into());
+        }
+
+        matches!(car_access(&OsmWay::new(1, &osm_tags)), WayAccess::Way)
+    }
+
+    #[test]
+    fn test_ferry_access() {
+        assert!(access(&[("route", "ferry")]));
+        assert!(access(&[("route", "ferry"), ("motor_vehicle", "yes")]));
+        assert!(access(&[
+            ("route", "ferry"),
+            ("foot", "yes"),
+            ("motorcar", "yes")
+        ]));
+        assert!(!access(&[("route", "ferry"), ("foot", "yes")]));
+        assert!(!access(&[("route", "ferry"), ("motorcar", "no")]));
+        assert!(access(&[("highway", "residential")]));
+        assert!(!access(&[("highway", "footway")]));
+    }
+}
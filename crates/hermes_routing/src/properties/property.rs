@@ -11,6 +11,18 @@ pub enum Property {
     CarAverageSpeed,
     CarVehicleAccess,
     OsmId,
+    MaxHeight,
+    MaxWidth,
+    MaxWeight,
+    HgvAccess,
+    StreetName,
+    StreetRef,
+    Roundabout,
+    /// A road leaves the roundabout at the end of the edge, computed from the edges around it
+    RoundaboutExit,
+    Motorway,
+    Toll,
+    Ferry,
 }
 
 impl std::fmt::Display for Property {
@@ -20,6 +32,17 @@ impl std::fmt::Display for Property {
             Property::CarAverageSpeed => write!(f, "car_average_speed"),
             Property::CarVehicleAccess => write!(f, "car_vehicle_access"),
             Property::OsmId => write!(f, "osm_id"),
+            Property::MaxHeight => write!(f, "maxheight"),
+            Property::MaxWidth => write!(f, "maxwidth"),
+            Property::MaxWeight => write!(f, "maxweight"),
+            Property::HgvAccess => write!(f, "hgv_access"),
+            Property::StreetName => write!(f, "street_name"),
+            Property::StreetRef => write!(f, "street_ref"),
+            Property::Roundabout => write!(f, "roundabout"),
+            Property::RoundaboutExit => write!(f, "roundabout_exit"),
+            Property::Motorway => write!(f, "motorway"),
+            Property::Toll => write!(f, "toll"),
+            Property::Ferry => write!(f, "ferry"),
         }
     }
 }
@@ -1,3 +1,7 @@
+use std::sync::Arc;
+
+use fxhash::FxHashSet;
+
 use crate::{edge_direction::EdgeDirection, properties::property::Property};
 
 #[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Clone, Debug, Default)]
@@ -8,7 +12,10 @@ impl<T> VectorMap<T> {
     }
 
     fn insert(&mut self, property: Property, value: T) {
-        self.0.push((property, value));
+        match self.0.iter_mut().find(|(p, _)| *p == property) {
+            Some((_, previous)) => *previous = value,
+            None => self.0.push((property, value)),
+        }
     }
 }
 
@@ -42,6 +49,8 @@ pub struct EdgePropertyMap {
     bool_values: DirectionalMap<bool>,
     u8_values: DirectionalMap<u8>,
     usize_values: VectorMap<usize>,
+    /// Shared by the edges with the same value, see `StringInterner`
+    string_values: VectorMap<Arc<str>>,
 }
 
 macro_rules! define_directional_access_functions {
@@ -67,7 +76,79 @@ impl EdgePropertyMap {
         self.usize_values.insert(property, value);
     }
 
+    pub fn get_string(&self, property: Property) -> Option<&str> {
+        self.string_values.get(&property).map(|value| &**value)
+    }
+
+    pub fn insert_string(&mut self, property: Property, value: &str) {
+        self.string_values.insert(property, Arc::from(value));
+    }
+
+    /// Replaces the strings by the equal ones already interned
+    pub fn intern_strings(&mut self, interner: &mut StringInterner) {
+        for (_, value) in &mut self.string_values.0 {
+            *value = interner.intern(value);
+        }
+    }
+
     define_directional_access_functions!(f32, f32_values);
     define_directional_access_functions!(u8, u8_values);
     define_directional_access_functions!(bool, bool_values);
 }
+
+/// Street names and refs repeat over the many edges of a street, the edges of an import share a
+/// single allocation per string. The graph file stores the shared strings once.
+#[derive(Default)]
+pub struct StringInterner {
+    strings: FxHashSet<Arc<str>>,
+}
+
+impl StringInterner {
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(value) {
+            return Arc::clone(interned);
+        }
+
+        let interned = Arc::<str>::from(value);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_strings() {
+        let mut interner = StringInterner::default();
+        let mut first = EdgePropertyMap::default();
+        let mut second = EdgePropertyMap::default();
+        first.insert_string(Property::StreetName, "Rue de la Loi");
+        second.insert_string(Property::StreetName, "Rue de la Loi");
+
+        first.intern_strings(&mut interner);
+        second.intern_strings(&mut interner);
+
+        assert!(Arc::ptr_eq(
+            first.string_values.get(&Property::StreetName).unwrap(),
+            second.string_values.get(&Property::StreetName).unwrap()
+        ));
+        assert_eq!(
+            second.get_string(Property::StreetName),
+            Some("Rue de la Loi")
+        );
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut properties = EdgePropertyMap::default();
+        properties.insert_bool(Property::Roundabout, EdgeDirection::Forward, false);
+        properties.insert_bool(Property::Roundabout, EdgeDirection::Forward, true);
+
+        assert_eq!(
+            properties.get_bool(Property::Roundabout, EdgeDirection::Forward),
+            Some(true)
+        );
+    }
+}
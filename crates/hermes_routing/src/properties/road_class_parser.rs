@@ -0,0 +1,44 @@
+use crate::edge_direction::EdgeDirection;
+use crate::osm::osm_reader::OsmWay;
+use crate::properties::property::Property;
+use crate::properties::tag_parser::TagParser;
+
+use super::property_map::EdgePropertyMap;
+
+/// Only the matching ways are tagged, the other edges have no value
+fn insert_flag(properties: &mut EdgePropertyMap, property: Property) {
+    properties.insert_bool(property.clone(), EdgeDirection::Forward, true);
+    properties.insert_bool(property, EdgeDirection::Backward, true);
+}
+
+pub struct MotorwayParser;
+
+impl TagParser for MotorwayParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        if way.has_tag("highway", "motorway") || way.has_tag("highway", "motorway_link") {
+            insert_flag(properties, Property::Motorway);
+        }
+    }
+}
+
+// https://wiki.openstreetmap.org/wiki/Key:toll
+pub struct TollParser;
+
+impl TagParser for TollParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        if way.has_tag("toll", "yes") {
+            insert_flag(properties, Property::Toll);
+        }
+    }
+}
+
+// https://wiki.openstreetmap.org/wiki/Tag:route%3Dferry
+pub struct FerryParser;
+
+impl TagParser for FerryParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        if way.has_tag("route", "ferry") {
+            insert_flag(properties, Property::Ferry);
+        }
+    }
+}
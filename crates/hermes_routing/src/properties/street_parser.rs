@@ -0,0 +1,46 @@
+use crate::edge_direction::EdgeDirection;
+use crate::osm::osm_reader::OsmWay;
+use crate::properties::property::Property;
+use crate::properties::tag_parser::TagParser;
+
+use super::property_map::EdgePropertyMap;
+
+fn insert_tag(way: &OsmWay, properties: &mut EdgePropertyMap, tag: &str, property: Property) {
+    if let Some(value) = way
+        .tag(tag)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        properties.insert_string(property, value);
+    }
+}
+
+pub struct StreetNameParser;
+
+impl TagParser for StreetNameParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        insert_tag(way, properties, "name", Property::StreetName);
+    }
+}
+
+/// Road number, e.g. `E40` or `N4;N90`
+pub struct StreetRefParser;
+
+impl TagParser for StreetRefParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        insert_tag(way, properties, "ref", Property::StreetRef);
+    }
+}
+
+pub struct RoundaboutParser;
+
+// https://wiki.openstreetmap.org/wiki/Tag:junction%3Droundabout
+impl TagParser for RoundaboutParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        // Only the roundabouts are tagged, the other edges have no value
+        if way.has_tag("junction", "roundabout") || way.has_tag("junction", "circular") {
+            properties.insert_bool(Property::Roundabout, EdgeDirection::Forward, true);
+            properties.insert_bool(Property::Roundabout, EdgeDirection::Backward, true);
+        }
+    }
+}
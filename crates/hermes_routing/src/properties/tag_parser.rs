@@ -3,6 +3,11 @@ use crate::properties::car_access_parser::CarAccessParser;
 use crate::properties::max_speed_parser::MaxSpeedParser;
 use crate::properties::osm_id_parser::OsmIdParser;
 use crate::properties::property::Property;
+use crate::properties::road_class_parser::{FerryParser, MotorwayParser, TollParser};
+use crate::properties::street_parser::{RoundaboutParser, StreetNameParser, StreetRefParser};
+use crate::properties::truck_restrictions_parser::{
+    HgvAccessParser, MaxHeightParser, MaxWeightParser, MaxWidthParser,
+};
 
 use super::car_average_speed_parser::CarAverageSpeedParser;
 use super::property_map::EdgePropertyMap;
@@ -21,5 +26,17 @@ pub fn parse_way_tags(way: &OsmWay, properties: &mut EdgePropertyMap, property:
             CarAverageSpeedParser::parse_way(way, properties);
         }
         Property::OsmId => OsmIdParser::parse_way(way, properties),
+        Property::MaxHeight => MaxHeightParser::parse_way(way, properties),
+        Property::MaxWidth => MaxWidthParser::parse_way(way, properties),
+        Property::MaxWeight => MaxWeightParser::parse_way(way, properties),
+        Property::HgvAccess => HgvAccessParser::parse_way(way, properties),
+        Property::StreetName => StreetNameParser::parse_way(way, properties),
+        Property::StreetRef => StreetRefParser::parse_way(way, properties),
+        Property::Roundabout => RoundaboutParser::parse_way(way, properties),
+        // Not a tag of the way, see `BaseGraph::mark_roundabout_exits`
+        Property::RoundaboutExit => {}
+        Property::Motorway => MotorwayParser::parse_way(way, properties),
+        Property::Toll => TollParser::parse_way(way, properties),
+        Property::Ferry => FerryParser::parse_way(way, properties),
     }
 }
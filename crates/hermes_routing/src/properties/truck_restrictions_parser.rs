@@ -0,0 +1,168 @@
+use crate::edge_direction::EdgeDirection;
+use crate::osm::osm_reader::OsmWay;
+use crate::properties::property::Property;
+use crate::properties::tag_parser::TagParser;
+
+use super::property_map::EdgePropertyMap;
+
+const FOOT_TO_METERS: f32 = 0.3048;
+const INCH_TO_METERS: f32 = 0.0254;
+const POUND_TO_TONNES: f32 = 0.000_453_592;
+
+/// Values meaning that the way has no signed limit
+static NO_LIMIT_VALUES: [&str; 5] = ["none", "default", "below_default", "no_sign", "unsigned"];
+
+static HGV_DENIED_VALUES: [&str; 2] = ["no", "private"];
+
+fn parse_number(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| *value > 0.0)
+}
+
+/// Length in meters, e.g. `3.5`, `3.5 m` or `11'6"`
+///
+/// https://wiki.openstreetmap.org/wiki/Key:maxheight
+fn parse_length_tag(tag: Option<&str>) -> Option<f32> {
+    let value = tag?.trim();
+    if NO_LIMIT_VALUES.contains(&value) {
+        return None;
+    }
+
+    if let Some((feet, inches)) = value.split_once('\'') {
+        let inches = inches.trim().trim_end_matches('"');
+        let inches = if inches.is_empty() {
+            0.0
+        } else {
+            parse_number(inches)?
+        };
+        return Some(parse_number(feet)? * FOOT_TO_METERS + inches * INCH_TO_METERS);
+    }
+
+    if let Some(feet) = value.strip_suffix("ft") {
+        return parse_number(feet).map(|feet| feet * FOOT_TO_METERS);
+    }
+
+    parse_number(value.strip_suffix('m').unwrap_or(value))
+}
+
+/// Weight in tonnes, e.g. `7.5`, `7.5 t`, `7500 kg` or `16000 lbs`
+///
+/// https://wiki.openstreetmap.org/wiki/Key:maxweight
+fn parse_weight_tag(tag: Option<&str>) -> Option<f32> {
+    let value = tag?.trim();
+    if NO_LIMIT_VALUES.contains(&value) {
+        return None;
+    }
+
+    if let Some(kilograms) = value.strip_suffix("kg") {
+        return parse_number(kilograms).map(|kilograms| kilograms / 1000.0);
+    }
+
+    if let Some(pounds) = value.strip_suffix("lbs") {
+        return parse_number(pounds).map(|pounds| pounds * POUND_TO_TONNES);
+    }
+
+    parse_number(value.strip_suffix('t').unwrap_or(value))
+}
+
+fn insert_limit(properties: &mut EdgePropertyMap, property: Property, limit: Option<f32>) {
+    if let Some(limit) = limit {
+        properties.insert_f32(property.clone(), EdgeDirection::Forward, limit);
+        properties.insert_f32(property, EdgeDirection::Backward, limit);
+    }
+}
+
+pub struct MaxHeightParser;
+
+impl TagParser for MaxHeightParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        let max_height = parse_length_tag(way.tag("maxheight"));
+        insert_limit(properties, Property::MaxHeight, max_height);
+    }
+}
+
+pub struct MaxWidthParser;
+
+impl TagParser for MaxWidthParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        let max_width = parse_length_tag(way.tag("maxwidth"));
+        insert_limit(properties, Property::MaxWidth, max_width);
+    }
+}
+
+pub struct MaxWeightParser;
+
+impl TagParser for MaxWeightParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        let max_weight = parse_weight_tag(way.tag("maxweight"));
+        insert_limit(properties, Property::MaxWeight, max_weight);
+    }
+}
+
+pub struct HgvAccessParser;
+
+impl HgvAccessParser {
+    fn parse_hgv_tag(tag: Option<&str>) -> Option<bool> {
+        tag.map(|value| !HGV_DENIED_VALUES.contains(&value))
+    }
+}
+
+// https://wiki.openstreetmap.org/wiki/Key:hgv
+// The ways without `hgv` tag are accessible as long as they are accessible by car
+impl TagParser for HgvAccessParser {
+    fn parse_way(way: &OsmWay, properties: &mut EdgePropertyMap) {
+        let hgv = way.tag("hgv");
+
+        let forward = HgvAccessParser::parse_hgv_tag(way.tag("hgv:forward").or(hgv));
+        if let Some(access) = forward {
+            properties.insert_bool(Property::HgvAccess, EdgeDirection::Forward, access);
+        }
+
+        let backward = HgvAccessParser::parse_hgv_tag(way.tag("hgv:backward").or(hgv));
+        if let Some(access) = backward {
+            properties.insert_bool(Property::HgvAccess, EdgeDirection::Backward, access);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    #[test]
+    fn test_parse_length_tag() {
+        assert!((parse_length_tag(Some("3.5")).unwrap() - 3.5).abs() < EPSILON);
+        assert!((parse_length_tag(Some("3.5 m")).unwrap() - 3.5).abs() < EPSILON);
+        assert!((parse_length_tag(Some("11'6\"")).unwrap() - 3.505).abs() < EPSILON);
+        assert!((parse_length_tag(Some("12'")).unwrap() - 3.658).abs() < EPSILON);
+        assert!((parse_length_tag(Some("12 ft")).unwrap() - 3.658).abs() < EPSILON);
+        assert_eq!(parse_length_tag(Some("default")), None);
+        assert_eq!(parse_length_tag(Some("below_default")), None);
+        assert_eq!(parse_length_tag(None), None);
+    }
+
+    #[test]
+    fn test_parse_weight_tag() {
+        assert!((parse_weight_tag(Some("7.5")).unwrap() - 7.5).abs() < EPSILON);
+        assert!((parse_weight_tag(Some("7.5 t")).unwrap() - 7.5).abs() < EPSILON);
+        assert!((parse_weight_tag(Some("3500 kg")).unwrap() - 3.5).abs() < EPSILON);
+        assert!((parse_weight_tag(Some("10000 lbs")).unwrap() - 4.536).abs() < EPSILON);
+        assert_eq!(parse_weight_tag(Some("none")), None);
+        assert_eq!(parse_weight_tag(Some("unknown")), None);
+    }
+
+    #[test]
+    fn test_parse_hgv_tag() {
+        assert_eq!(HgvAccessParser::parse_hgv_tag(Some("no")), Some(false));
+        assert_eq!(
+            HgvAccessParser::parse_hgv_tag(Some("designated")),
+            Some(true)
+        );
+        assert_eq!(HgvAccessParser::parse_hgv_tag(None), None);
+    }
+}
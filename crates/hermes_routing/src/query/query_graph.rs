@@ -11,7 +11,10 @@ use crate::{
         create_virtual_geometry_between_points,
     },
     geopoint::GeoPoint,
-    graph::{DirectedEdgeAccess, GeometryAccess, Graph, UndirectedEdgeAccess, UnfoldEdge},
+    graph::{
+        DirectedEdgeAccess, GeometryAccess, Graph, TurnRestrictionAccess, UndirectedEdgeAccess,
+        UnfoldEdge,
+    },
     graph_edge::GraphEdge,
     snap::Snap,
     types::{EdgeId, NodeId},
@@ -104,6 +107,7 @@ where
                 edge,
             ),
             virtual_geometry_1,
+            edge_id,
         );
 
         // Connect the start node to the virtual edge
@@ -119,6 +123,7 @@ where
                 edge,
             ),
             virtual_geometry_2,
+            edge_id,
         );
 
         // Connect the end node to the virtual edge
@@ -170,6 +175,7 @@ where
                             edge,
                         ),
                         virtual_geometry,
+                        snap_i.edge_id,
                     );
 
                     // Add the edge to the adjacency list of both virtual nodes
@@ -258,6 +264,31 @@ where
     }
 }
 
+/// The virtual nodes have no restrictions, the turns from or to virtual edges are the turns from or
+/// to the edges they split
+impl<G> TurnRestrictionAccess for QueryGraph<'_, G>
+where
+    G: Graph + GeometryAccess + BuildVirtualEdge + TurnRestrictionAccess,
+{
+    fn has_turn_restrictions(&self, node_id: NodeId) -> bool {
+        !self.is_virtual_node(node_id) && self.graph.has_turn_restrictions(node_id)
+    }
+
+    fn is_turn_allowed(
+        &self,
+        from_edge_id: EdgeId,
+        via_node_id: NodeId,
+        to_edge_id: EdgeId,
+    ) -> bool {
+        !self.has_turn_restrictions(via_node_id)
+            || self.graph.is_turn_allowed(
+                self.overlay.edge_origin(from_edge_id),
+                via_node_id,
+                self.overlay.edge_origin(to_edge_id),
+            )
+    }
+}
+
 impl UndirectedEdgeAccess for QueryGraph<'_, BaseGraph> {
     type EdgeIterator<'b>
         = QueryGraphEdgeIterator<'b>
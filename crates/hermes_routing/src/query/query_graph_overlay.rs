@@ -21,6 +21,9 @@ pub(crate) struct QueryGraphOverlay<'a, G: Graph> {
     virtual_edges: Vec<G::Edge>,
     virtual_edge_geometry: Vec<Vec<GeoPoint>>,
 
+    // Edge of the queried graph each virtual edge is part of
+    virtual_edge_origins: Vec<EdgeId>,
+
     // New edges for new "virtual" nodes
     virtual_adjacency_list: Vec<Vec<EdgeId>>,
 
@@ -35,6 +38,7 @@ impl<'a, G: Graph> QueryGraphOverlay<'a, G> {
             virtual_nodes: 0,
             virtual_edges: Vec::new(),
             virtual_edge_geometry: Vec::new(),
+            virtual_edge_origins: Vec::new(),
             virtual_adjacency_list: Vec::new(),
             virtual_adjacency_list_existing_nodes: FxHashMap::default(),
         }
@@ -64,9 +68,19 @@ impl<'a, G: Graph> QueryGraphOverlay<'a, G> {
         }
     }
 
-    pub fn add_virtual_edge(&mut self, edge: G::Edge, geometry: Vec<GeoPoint>) {
+    pub fn add_virtual_edge(&mut self, edge: G::Edge, geometry: Vec<GeoPoint>, origin: EdgeId) {
         self.virtual_edges.push(edge);
         self.virtual_edge_geometry.push(geometry);
+        self.virtual_edge_origins.push(origin);
+    }
+
+    /// Edge of the queried graph `edge_id` is part of, `edge_id` itself when it is not virtual
+    pub fn edge_origin(&self, edge_id: EdgeId) -> EdgeId {
+        if self.is_virtual_edge(edge_id) {
+            self.virtual_edge_origins[self.virtual_edge_id(edge_id)]
+        } else {
+            edge_id
+        }
     }
 
     pub fn add_virtual_node(&mut self) -> usize {
@@ -0,0 +1,35 @@
+use geo::MultiPolygon;
+use geojson::{GeoJson, Geometry};
+
+use crate::error::RegionError;
+
+/// Reads the region to extract from a GeoJSON file, either a geometry, a feature or the first
+/// feature of a collection
+pub fn read_region_file(path: &str) -> Result<MultiPolygon<f64>, RegionError> {
+    let content = std::fs::read_to_string(path).map_err(RegionError::Read)?;
+    parse_region(&content)
+}
+
+pub fn parse_region(content: &str) -> Result<MultiPolygon<f64>, RegionError> {
+    let geometry = match content.parse::<GeoJson>()? {
+        GeoJson::Geometry(geometry) => Some(geometry),
+        GeoJson::Feature(feature) => feature.geometry,
+        GeoJson::FeatureCollection(collection) => collection
+            .features
+            .into_iter()
+            .next()
+            .and_then(|feature| feature.geometry),
+    }
+    .ok_or(RegionError::NotAPolygon)?;
+
+    to_multi_polygon(geometry)
+}
+
+/// Polygon or MultiPolygon geometry, e.g. an area to avoid
+pub fn to_multi_polygon(geometry: Geometry) -> Result<MultiPolygon<f64>, RegionError> {
+    match geo::Geometry::<f64>::try_from(geometry)? {
+        geo::Geometry::Polygon(polygon) => Ok(MultiPolygon::new(vec![polygon])),
+        geo::Geometry::MultiPolygon(multi_polygon) => Ok(multi_polygon),
+        _ => Err(RegionError::NotAPolygon),
+    }
+}
@@ -3,7 +3,7 @@ use fxhash::FxHashMap;
 use crate::constants::{DISTANCE_INFLUENCE, INVALID_EDGE, INVALID_NODE, MAX_WEIGHT};
 use crate::edge_direction::EdgeDirection;
 use crate::geopoint::GeoPoint;
-use crate::graph::{GeometryAccess, Graph, UndirectedEdgeAccess};
+use crate::graph::{GeometryAccess, Graph, TurnRestrictionAccess, UndirectedEdgeAccess};
 use crate::graph_edge::GraphEdge;
 use crate::routing::astar_heuristic::AStarHeuristic;
 use crate::stopwatch::Stopwatch;
@@ -11,17 +11,26 @@ use crate::weighting::{Weight, Weighting};
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
-use super::routing_path::{RoutingPath, RoutingPathLeg};
+use super::routing_path::{RoutingPath, RoutingPathLeg, Street};
 use super::shortest_path_algorithm::{
     CalcPath, CalcPathOptions, CalcPathResult, ShortestPathDebugInfo,
 };
 
 /// https://en.wikipedia.org/wiki/A*_search_algorithm
 
+/// Node with the edge it was reached from, the edge is only kept at the nodes with turn
+/// restrictions, `INVALID_EDGE` otherwise, so that the other nodes are settled once
+type SearchState = (usize, usize);
+
+const START_STATE_PARENT: SearchState = (INVALID_NODE, INVALID_EDGE);
+
 #[derive(Eq, Copy, Clone, Debug)]
 struct HeapItem {
     node_id: usize,
 
+    /// Edge from the parent, only set at the nodes with turn restrictions
+    incoming_edge_id: usize,
+
     /// g_score is the current cheapest weight from start to node "node_id"
     g_score: Weight,
 
@@ -49,13 +58,14 @@ impl Ord for HeapItem {
             .cmp(&self.f_score)
             .then_with(|| other.g_score.cmp(&self.g_score))
             .then_with(|| self.node_id.cmp(&other.node_id))
+            .then_with(|| self.incoming_edge_id.cmp(&other.incoming_edge_id))
     }
 }
 
 struct NodeData {
     settled: bool,
     weight: Weight,
-    parent: usize,
+    parent: SearchState,
     edge_id: usize, // Edge ID from parent to current node
 }
 
@@ -64,7 +74,7 @@ impl NodeData {
         NodeData {
             settled: false,
             weight: MAX_WEIGHT,
-            parent: INVALID_NODE,
+            parent: START_STATE_PARENT,
             edge_id: INVALID_EDGE,
         }
     }
@@ -76,9 +86,7 @@ impl AStarHeuristic for HaversineHeuristic {
     fn estimate<G: Graph + GeometryAccess>(&self, graph: &G, start: usize, end: usize) -> Weight {
         let start_coordinates = graph.node_geometry(start);
         let end_coordinates = graph.node_geometry(end);
-        let distance = start_coordinates
-            .haversine_distance(end_coordinates)
-            .value();
+        let distance = start_coordinates.haversine_distance(end_coordinates);
 
         let speed_kmh = 120.0;
         let speed_ms = speed_kmh / 3.6;
@@ -89,14 +97,14 @@ impl AStarHeuristic for HaversineHeuristic {
 
 pub struct AStar<'a, G, H>
 where
-    G: Graph + UndirectedEdgeAccess + GeometryAccess,
+    G: Graph + UndirectedEdgeAccess + GeometryAccess + TurnRestrictionAccess,
     H: AStarHeuristic,
 {
     graph: &'a G,
 
     heap: BinaryHeap<HeapItem>,
     // Use a HashMap instead of a vector. Creating a vector with a capacity of the entire nodes of the planet is not scalable.
-    data: FxHashMap<usize, NodeData>,
+    data: FxHashMap<SearchState, NodeData>,
 
     debug_visited_nodes: Option<Vec<usize>>,
 
@@ -105,7 +113,7 @@ where
 
 impl<G, H> AStar<'_, G, H>
 where
-    G: Graph + UndirectedEdgeAccess + GeometryAccess,
+    G: Graph + UndirectedEdgeAccess + GeometryAccess + TurnRestrictionAccess,
     H: AStarHeuristic,
 {
     pub fn with_heuristic(graph: &G, heuristic: H) -> AStar<'_, G, H> {
@@ -125,13 +133,29 @@ where
         let h_score = self.heuristic.estimate(self.graph, start, end);
         self.heap.push(HeapItem {
             node_id: start,
+            incoming_edge_id: INVALID_EDGE,
             g_score: 0,
             f_score: h_score,
         });
-        self.update_node_data(start, 0, INVALID_NODE, INVALID_EDGE)
+        self.update_node_data((start, INVALID_EDGE), 0, START_STATE_PARENT, INVALID_EDGE)
+    }
+
+    /// State of `node` reached from `edge_id`
+    fn search_state(&self, node: usize, edge_id: usize) -> SearchState {
+        if self.graph.has_turn_restrictions(node) {
+            (node, edge_id)
+        } else {
+            (node, INVALID_EDGE)
+        }
     }
 
-    fn update_node_data(&mut self, node: usize, weight: Weight, parent: usize, edge_id: usize) {
+    fn update_node_data(
+        &mut self,
+        node: SearchState,
+        weight: Weight,
+        parent: SearchState,
+        edge_id: usize,
+    ) {
         if let Some(data) = self.data.get_mut(&node) {
             data.weight = weight;
             data.settled = false;
@@ -150,21 +174,21 @@ where
         }
     }
 
-    fn node_data(&mut self, node: usize) -> &NodeData {
+    fn node_data(&mut self, node: SearchState) -> &NodeData {
         self.data.entry(node).or_insert_with(NodeData::new)
     }
 
-    fn set_settled(&mut self, node: usize) {
+    fn set_settled(&mut self, node: SearchState) {
         self.data.get_mut(&node).unwrap().settled = true
     }
 
     #[inline(always)]
-    fn is_settled(&mut self, node: usize) -> bool {
+    fn is_settled(&mut self, node: SearchState) -> bool {
         self.node_data(node).settled
     }
 
     #[inline(always)]
-    fn current_shortest_weight(&mut self, node: usize) -> Weight {
+    fn current_shortest_weight(&mut self, node: SearchState) -> Weight {
         self.node_data(node).weight
     }
 
@@ -173,16 +197,16 @@ where
         graph: &G,
         weighting: &impl Weighting<G>,
         _start: usize,
-        end: usize,
+        end: SearchState,
     ) -> RoutingPath {
         let mut path: Vec<RoutingPathLeg> = Vec::with_capacity(32);
 
         let mut node = end;
 
         let mut node_data = self.node_data(node);
-        while node_data.parent != INVALID_NODE {
+        while node_data.parent != START_STATE_PARENT {
             let edge_id = node_data.edge_id;
-            let parent = node_data.parent;
+            let (parent, _) = node_data.parent;
 
             let direction = graph.edge_direction(edge_id, parent);
 
@@ -197,7 +221,12 @@ where
             let distance = edge.distance();
             let time = weighting.calc_edge_ms(edge, direction);
 
-            path.push(RoutingPathLeg::new(distance, time, geometry));
+            path.push(RoutingPathLeg::new(
+                distance,
+                time,
+                geometry,
+                Street::from_edge(edge, direction),
+            ));
             node = node_data.parent;
             node_data = self.node_data(node);
         }
@@ -229,7 +258,7 @@ where
 
 impl<G, H> CalcPath<G> for AStar<'_, G, H>
 where
-    G: Graph + UndirectedEdgeAccess + GeometryAccess,
+    G: Graph + UndirectedEdgeAccess + GeometryAccess + TurnRestrictionAccess,
     H: AStarHeuristic,
 {
     fn calc_path(
@@ -240,7 +269,7 @@ where
         options: Option<CalcPathOptions>,
     ) -> Result<CalcPathResult, String>
     where
-        G: Graph + UndirectedEdgeAccess + GeometryAccess,
+        G: Graph + UndirectedEdgeAccess + GeometryAccess + TurnRestrictionAccess,
     {
         let mut stopwatch = Stopwatch::new(String::from("astar/calc_path"));
         stopwatch.start();
@@ -260,30 +289,48 @@ where
 
         let mut nodes_visited = 0;
 
+        // The end node has a state per incoming edge when it has turn restrictions
+        let mut end_weight = MAX_WEIGHT;
+        let mut end_state = (end, INVALID_EDGE);
+
         while let Some(HeapItem {
-            node_id, g_score, ..
+            node_id,
+            incoming_edge_id,
+            g_score,
+            ..
         }) = self.heap.pop()
         {
+            let state = (node_id, incoming_edge_id);
+
             // Node is already settled, skip
-            if self.is_settled(node_id) {
+            if self.is_settled(state) {
                 continue;
             }
 
             // The weight is bigger than the current shortest weight, skip
-            if g_score > self.current_shortest_weight(node_id) {
+            if g_score > self.current_shortest_weight(state) {
                 continue;
             }
 
-            if g_score > self.current_shortest_weight(end) {
+            if g_score > end_weight {
                 continue;
             }
 
             for edge_id in self.graph.node_edges_iter(node_id) {
+                if incoming_edge_id != INVALID_EDGE
+                    && !self
+                        .graph
+                        .is_turn_allowed(incoming_edge_id, node_id, edge_id)
+                {
+                    continue;
+                }
+
                 let edge = self.graph.edge(edge_id);
 
                 let adj_node = edge.adj_node(node_id);
+                let adj_state = self.search_state(adj_node, edge_id);
 
-                if self.is_settled(adj_node) {
+                if self.is_settled(adj_state) {
                     continue;
                 }
 
@@ -297,14 +344,19 @@ where
 
                 let next_weight = g_score + edge_weight;
 
-                if next_weight < self.current_shortest_weight(adj_node) {
-                    self.update_node_data(adj_node, next_weight, node_id, edge_id);
+                if next_weight < self.current_shortest_weight(adj_state) {
+                    self.update_node_data(adj_state, next_weight, state, edge_id);
                     let h_score = self.heuristic.estimate(self.graph, adj_node, end);
 
+                    if adj_node == end {
+                        end_weight = end_weight.min(next_weight);
+                    }
+
                     self.heap.push(HeapItem {
                         g_score: next_weight,
                         f_score: next_weight + h_score,
                         node_id: adj_node,
+                        incoming_edge_id: adj_state.1,
                     });
                 }
             }
@@ -315,13 +367,14 @@ where
 
             nodes_visited += 1;
 
-            self.set_settled(node_id);
+            self.set_settled(state);
             if node_id == end {
+                end_state = state;
                 break;
             }
         }
 
-        let path = self.build_path(self.graph, weighting, start, end);
+        let path = self.build_path(self.graph, weighting, start, end_state);
 
         stopwatch.stop();
         stopwatch.report();
@@ -343,7 +396,7 @@ where
 
 impl<'a, G> AStar<'a, G, HaversineHeuristic>
 where
-    G: Graph + UndirectedEdgeAccess + GeometryAccess,
+    G: Graph + UndirectedEdgeAccess + GeometryAccess + TurnRestrictionAccess,
 {
     pub fn new(graph: &'a G) -> AStar<'a, G, HaversineHeuristic> {
         Self::with_heuristic(graph, HaversineHeuristic)
@@ -399,4 +452,28 @@ mod tests {
         let path = result.unwrap().path;
         assert_eq!(path.distance(), kilometers!(855))
     }
+
+    #[test]
+    fn test_calc_path_with_turn_restriction() {
+        let mut graph = TestGraph::create_romania_graph();
+        graph.forbid_turn(
+            RomaniaGraphCity::Sibiu,
+            RomaniaGraphCity::RimnicuVilcea,
+            RomaniaGraphCity::Pitesti,
+        );
+
+        let mut astar = AStar::new(&graph);
+        let weighting = TestWeighting;
+
+        let result = astar.calc_path(
+            &weighting,
+            RomaniaGraphCity::Oradea.into(),
+            RomaniaGraphCity::Bucharest.into(),
+            None,
+        );
+
+        // Through Fagaras instead of Rimnicu Vilcea and Pitesti
+        let path = result.unwrap().path;
+        assert_eq!(path.distance(), kilometers!(461))
+    }
 }
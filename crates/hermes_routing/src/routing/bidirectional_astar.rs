@@ -12,7 +12,7 @@ use std::cmp::{Ordering, max};
 use std::collections::{BinaryHeap, HashMap};
 
 use super::astar_heuristic::AStarHeuristic;
-use super::routing_path::{RoutingPath, RoutingPathLeg};
+use super::routing_path::{RoutingPath, RoutingPathLeg, Street};
 use super::search_direction::SearchDirection;
 use super::shortest_path_algorithm::{
     CalcPath, CalcPathOptions, CalcPathResult, ShortestPathDebugInfo,
@@ -81,9 +81,7 @@ impl AStarHeuristic for HaversineHeuristic {
     fn estimate<G: Graph + GeometryAccess>(&self, graph: &G, from: usize, to: usize) -> Weight {
         let start_coordinates = graph.node_geometry(from);
         let end_coordinates = graph.node_geometry(to);
-        let distance = start_coordinates
-            .haversine_distance(end_coordinates)
-            .value();
+        let distance = start_coordinates.haversine_distance(end_coordinates);
 
         let speed_kmh = 120.0;
         let speed_ms = speed_kmh / 3.6;
@@ -349,7 +347,12 @@ where
             let distance = edge.distance();
             let time = weighting.calc_edge_ms(edge, direction);
 
-            path.push(RoutingPathLeg::new(distance, time, geometry));
+            path.push(RoutingPathLeg::new(
+                distance,
+                time,
+                geometry,
+                Street::from_edge(edge, direction),
+            ));
             current_node = parent;
         }
 
@@ -390,7 +393,12 @@ where
             let distance = edge.distance();
             let time = weighting.calc_edge_ms(edge, direction);
 
-            path.push(RoutingPathLeg::new(distance, time, geometry));
+            path.push(RoutingPathLeg::new(
+                distance,
+                time,
+                geometry,
+                Street::from_edge(edge, direction),
+            ));
             current_node = parent;
         }
 
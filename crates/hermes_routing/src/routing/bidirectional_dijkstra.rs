@@ -11,7 +11,7 @@ use crate::weighting::{Weight, Weighting};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 
-use super::routing_path::{RoutingPath, RoutingPathLeg};
+use super::routing_path::{RoutingPath, RoutingPathLeg, Street};
 use super::search_direction::SearchDirection;
 use super::shortest_path_algorithm::{ShortestPathAlgorithm, ShortestPathDebugInfo};
 
@@ -421,7 +421,12 @@ where
             let distance = edge.distance();
             let time = weighting.calc_edge_ms(edge, direction);
 
-            path.push(RoutingPathLeg::new(distance, time, geometry));
+            path.push(RoutingPathLeg::new(
+                distance,
+                time,
+                geometry,
+                Street::from_edge(edge, direction),
+            ));
             current_node = parent;
         }
 
@@ -462,7 +467,12 @@ where
             let distance = edge.distance();
             let time = weighting.calc_edge_ms(edge, direction);
 
-            path.push(RoutingPathLeg::new(distance, time, geometry));
+            path.push(RoutingPathLeg::new(
+                distance,
+                time,
+                geometry,
+                Street::from_edge(edge, direction),
+            ));
             current_node = parent;
         }
 
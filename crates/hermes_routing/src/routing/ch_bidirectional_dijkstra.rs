@@ -85,9 +85,7 @@ impl AStarHeuristic for HaversineHeuristic {
     fn estimate<G: Graph + GeometryAccess>(&self, graph: &G, from: usize, to: usize) -> Weight {
         let start_coordinates = graph.node_geometry(from);
         let end_coordinates = graph.node_geometry(to);
-        let distance = start_coordinates
-            .haversine_distance(end_coordinates)
-            .value();
+        let distance = start_coordinates.haversine_distance(end_coordinates);
 
         let speed_kmh = 120.0;
         let speed_ms = speed_kmh / 3.6;
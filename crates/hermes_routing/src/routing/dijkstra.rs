@@ -1,6 +1,6 @@
 use super::{astar::AStar, astar_heuristic::AStarHeuristic};
 use crate::{
-    graph::{GeometryAccess, Graph, UndirectedEdgeAccess},
+    graph::{GeometryAccess, Graph, TurnRestrictionAccess, UndirectedEdgeAccess},
     weighting::Weight,
 };
 
@@ -20,7 +20,7 @@ impl Dijkstra {
     #[allow(clippy::new_ret_no_self)]
     pub fn new<G>(graph: &G) -> AStar<'_, G, DijkstraHeuristic>
     where
-        G: Graph + UndirectedEdgeAccess + GeometryAccess,
+        G: Graph + UndirectedEdgeAccess + GeometryAccess + TurnRestrictionAccess,
     {
         AStar::with_heuristic(graph, DijkstraHeuristic)
     }
@@ -0,0 +1,403 @@
+use geo::{Bearing, Haversine, Point};
+use serde::Serialize;
+
+use crate::{geopoint::GeoPoint, weighting::Milliseconds};
+
+use super::routing_path::{RoutingPathLeg, Street};
+
+/// Turn angles in degrees under which the path goes straight on
+const STRAIGHT_ANGLE: f64 = 15.0;
+/// Turn angles in degrees under which a turn is slight
+const SLIGHT_TURN_ANGLE: f64 = 45.0;
+/// Turn angles in degrees from which a turn is sharp
+const SHARP_TURN_ANGLE: f64 = 120.0;
+/// Turn angles in degrees from which the path goes back
+const U_TURN_ANGLE: f64 = 170.0;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstructionSign {
+    Depart,
+    Continue,
+    SlightLeft,
+    Left,
+    SharpLeft,
+    SlightRight,
+    Right,
+    SharpRight,
+    UTurn,
+    Roundabout,
+    Arrive,
+}
+
+impl InstructionSign {
+    /// `angle` in degrees, negative to the left
+    fn from_turn_angle(angle: f64) -> InstructionSign {
+        let magnitude = angle.abs();
+        if magnitude < STRAIGHT_ANGLE {
+            InstructionSign::Continue
+        } else if magnitude >= U_TURN_ANGLE {
+            InstructionSign::UTurn
+        } else if angle < 0.0 {
+            if magnitude < SLIGHT_TURN_ANGLE {
+                InstructionSign::SlightLeft
+            } else if magnitude < SHARP_TURN_ANGLE {
+                InstructionSign::Left
+            } else {
+                InstructionSign::SharpLeft
+            }
+        } else if magnitude < SLIGHT_TURN_ANGLE {
+            InstructionSign::SlightRight
+        } else if magnitude < SHARP_TURN_ANGLE {
+            InstructionSign::Right
+        } else {
+            InstructionSign::SharpRight
+        }
+    }
+}
+
+/// Maneuver of a turn-by-turn description of a path
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Instruction {
+    sign: InstructionSign,
+    /// Name of the street followed after the maneuver
+    #[serde(skip_serializing_if = "Option::is_none")]
+    street_name: Option<String>,
+    /// Road number of the street followed after the maneuver, e.g. `E40`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    street_ref: Option<String>,
+    /// Exit taken at a roundabout, counted from its entry. The roads entering the roundabout
+    /// without a way out of it are not exits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_number: Option<usize>,
+    /// Angle between the incoming and outgoing directions in degrees, negative to the left
+    turn_angle: f64,
+    /// Distance in meters until the next instruction
+    distance: f64,
+    /// Time until the next instruction
+    time: Milliseconds,
+    /// Index of the point of the maneuver in the points of the legs of the path, concatenated
+    point_index: usize,
+    text: String,
+}
+
+impl Instruction {
+    fn new(
+        sign: InstructionSign,
+        street: Option<&Street>,
+        turn_angle: f64,
+        point_index: usize,
+    ) -> Instruction {
+        Instruction {
+            sign,
+            street_name: street.and_then(Street::name).map(str::to_owned),
+            street_ref: street.and_then(Street::reference).map(str::to_owned),
+            exit_number: None,
+            turn_angle,
+            distance: 0.0,
+            time: 0,
+            point_index,
+            text: String::new(),
+        }
+    }
+
+    pub fn sign(&self) -> InstructionSign {
+        self.sign
+    }
+
+    pub fn street_name(&self) -> Option<&str> {
+        self.street_name.as_deref()
+    }
+
+    pub fn street_ref(&self) -> Option<&str> {
+        self.street_ref.as_deref()
+    }
+
+    pub fn exit_number(&self) -> Option<usize> {
+        self.exit_number
+    }
+
+    pub fn turn_angle(&self) -> f64 {
+        self.turn_angle
+    }
+
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    pub fn time(&self) -> Milliseconds {
+        self.time
+    }
+
+    pub fn point_index(&self) -> usize {
+        self.point_index
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Name and road number of the street, e.g. `Rue de la Loi (N3)`
+    fn street_label(&self) -> Option<String> {
+        match (&self.street_name, &self.street_ref) {
+            (Some(name), Some(reference)) => Some(format!("{name} ({reference})")),
+            (Some(name), None) => Some(name.clone()),
+            (None, Some(reference)) => Some(reference.clone()),
+            (None, None) => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let street = self.street_label();
+        let onto = street
+            .as_ref()
+            .map(|street| format!(" onto {street}"))
+            .unwrap_or_default();
+
+        match self.sign {
+            InstructionSign::Depart => match &street {
+                Some(street) => format!("Depart on {street}"),
+                None => String::from("Depart"),
+            },
+            InstructionSign::Continue => format!("Continue{onto}"),
+            InstructionSign::SlightLeft => format!("Turn slightly left{onto}"),
+            InstructionSign::Left => format!("Turn left{onto}"),
+            InstructionSign::SharpLeft => format!("Turn sharp left{onto}"),
+            InstructionSign::SlightRight => format!("Turn slightly right{onto}"),
+            InstructionSign::Right => format!("Turn right{onto}"),
+            InstructionSign::SharpRight => format!("Turn sharp right{onto}"),
+            InstructionSign::UTurn => format!("Make a U-turn{onto}"),
+            InstructionSign::Roundabout => match self.exit_number {
+                Some(exit_number) => {
+                    format!("At the roundabout, take exit {exit_number}{onto}")
+                }
+                None => String::from("Enter the roundabout"),
+            },
+            InstructionSign::Arrive => String::from("Arrive at destination"),
+        }
+    }
+}
+
+fn bearing(from: &GeoPoint, to: &GeoPoint) -> f64 {
+    Haversine.bearing(
+        Point::new(from.lon(), from.lat()),
+        Point::new(to.lon(), to.lat()),
+    )
+}
+
+/// Angle in degrees between the end of `previous` and the start of `next`, in `[-180, 180)`
+/// and negative to the left
+fn turn_angle(previous: &RoutingPathLeg, next: &RoutingPathLeg) -> f64 {
+    let [.., from, via] = previous.points() else {
+        return 0.0;
+    };
+    let [_, to, ..] = next.points() else {
+        return 0.0;
+    };
+
+    (bearing(via, to) - bearing(from, via) + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn is_same_street(a: &Street, b: &Street) -> bool {
+    a.name() == b.name() && a.reference() == b.reference()
+}
+
+/// An instruction is generated at each change of street, at each turn sharper than a slight one
+/// and for each roundabout. The exit of a roundabout is the number of edges of the roundabout the
+/// path follows that end at an exit, the last one being the exit taken
+pub(crate) fn generate_instructions(legs: &[RoutingPathLeg]) -> Vec<Instruction> {
+    let Some(first) = legs.first() else {
+        return vec![];
+    };
+
+    let mut instructions = vec![Instruction::new(
+        InstructionSign::Depart,
+        Some(first.street()),
+        0.0,
+        0,
+    )];
+    let mut point_index = 0;
+    let mut roundabout_exits = 0;
+
+    for (index, leg) in legs.iter().enumerate() {
+        if let Some(previous) = index.checked_sub(1).map(|previous| &legs[previous]) {
+            let street = leg.street();
+            let angle = turn_angle(previous, leg);
+
+            if street.is_roundabout() {
+                if !previous.street().is_roundabout() {
+                    roundabout_exits = 0;
+                    instructions.push(Instruction::new(
+                        InstructionSign::Roundabout,
+                        None,
+                        angle,
+                        point_index,
+                    ));
+                }
+            } else if previous.street().is_roundabout() {
+                // The path can start in a roundabout, after the departure
+                let last = instructions.last_mut().unwrap();
+                if last.sign == InstructionSign::Roundabout {
+                    last.exit_number = Some(roundabout_exits);
+                    last.street_name = street.name().map(str::to_owned);
+                    last.street_ref = street.reference().map(str::to_owned);
+                }
+            } else if !is_same_street(previous.street(), street) || angle.abs() >= SLIGHT_TURN_ANGLE
+            {
+                instructions.push(Instruction::new(
+                    InstructionSign::from_turn_angle(angle),
+                    Some(street),
+                    angle,
+                    point_index,
+                ));
+            }
+        }
+
+        if leg.street().is_roundabout_exit() {
+            roundabout_exits += 1;
+        }
+
+        let last = instructions.last_mut().unwrap();
+        last.distance += leg.distance().value();
+        last.time += leg.time();
+        point_index += leg.points().len();
+    }
+
+    instructions.push(Instruction::new(
+        InstructionSign::Arrive,
+        None,
+        0.0,
+        point_index.saturating_sub(1),
+    ));
+
+    for instruction in &mut instructions {
+        instruction.text = instruction.describe();
+    }
+
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{meters, routing::routing_path::RoutingPath};
+
+    use super::*;
+
+    fn leg(points: &[(f64, f64)], street: Street) -> RoutingPathLeg {
+        RoutingPathLeg::new(
+            meters!(100),
+            1000,
+            points
+                .iter()
+                .map(|&(lon, lat)| GeoPoint::new(lon, lat))
+                .collect(),
+            street,
+        )
+    }
+
+    fn named(name: &str) -> Street {
+        Street::new(Some(name.to_owned()), None, false)
+    }
+
+    fn signs(instructions: &[Instruction]) -> Vec<InstructionSign> {
+        instructions
+            .iter()
+            .map(|instruction| instruction.sign())
+            .collect()
+    }
+
+    #[test]
+    fn test_turns_and_street_changes() {
+        let path = RoutingPath::new(vec![
+            leg(&[(4.0, 50.0), (4.0, 50.001)], named("A")),
+            leg(&[(4.0, 50.001), (4.0, 50.002)], named("A")),
+            leg(&[(4.0, 50.002), (4.001, 50.002)], named("B")),
+            leg(
+                &[(4.001, 50.002), (4.002, 50.002)],
+                Street::new(Some(String::from("C")), Some(String::from("N4")), false),
+            ),
+        ]);
+
+        let instructions = path.instructions();
+
+        assert_eq!(
+            signs(&instructions),
+            vec![
+                InstructionSign::Depart,
+                InstructionSign::Right,
+                InstructionSign::Continue,
+                InstructionSign::Arrive,
+            ]
+        );
+        assert_eq!(instructions[0].text(), "Depart on A");
+        assert_eq!(instructions[0].distance(), 200.0);
+        assert_eq!(instructions[1].text(), "Turn right onto B");
+        assert_eq!(instructions[1].point_index(), 4);
+        assert_eq!(instructions[2].text(), "Continue onto C (N4)");
+        assert_eq!(instructions[3].point_index(), 7);
+    }
+
+    #[test]
+    fn test_roundabout_exit() {
+        let roundabout = Street::new(None, None, true);
+        let exit = roundabout.clone().with_roundabout_exit();
+        let path = RoutingPath::new(vec![
+            leg(&[(4.0, 50.0), (4.0, 50.001)], named("A")),
+            leg(&[(4.0, 50.001), (4.0005, 50.0015)], exit.clone()),
+            leg(&[(4.0005, 50.0015), (4.0, 50.002)], exit.clone()),
+            leg(&[(4.0, 50.002), (3.9995, 50.0015)], exit),
+            leg(&[(3.9995, 50.0015), (3.999, 50.0015)], named("B")),
+        ]);
+
+        let instructions = path.instructions();
+
+        assert_eq!(
+            signs(&instructions),
+            vec![
+                InstructionSign::Depart,
+                InstructionSign::Roundabout,
+                InstructionSign::Arrive,
+            ]
+        );
+        assert_eq!(instructions[1].exit_number(), Some(3));
+        assert_eq!(
+            instructions[1].text(),
+            "At the roundabout, take exit 3 onto B"
+        );
+        assert_eq!(instructions[1].distance(), 400.0);
+    }
+
+    #[test]
+    fn test_roundabout_entries_are_not_exits() {
+        let roundabout = Street::new(None, None, true);
+        let exit = roundabout.clone().with_roundabout_exit();
+        // A road only entering the roundabout joins it at the end of the first edge
+        let path = RoutingPath::new(vec![
+            leg(&[(4.0, 50.0), (4.0, 50.001)], named("A")),
+            leg(&[(4.0, 50.001), (4.0005, 50.0015)], roundabout),
+            leg(&[(4.0005, 50.0015), (4.0, 50.002)], exit.clone()),
+            leg(&[(4.0, 50.002), (3.9995, 50.0015)], exit),
+            leg(&[(3.9995, 50.0015), (3.999, 50.0015)], named("B")),
+        ]);
+
+        let instructions = path.instructions();
+
+        assert_eq!(instructions[1].exit_number(), Some(2));
+        assert_eq!(
+            instructions[1].text(),
+            "At the roundabout, take exit 2 onto B"
+        );
+    }
+
+    #[test]
+    fn test_turn_angle() {
+        let north = leg(&[(4.0, 50.0), (4.0, 50.001)], Street::default());
+        let west = leg(&[(4.0, 50.001), (3.999, 50.001)], Street::default());
+
+        assert!((turn_angle(&north, &west) + 90.0).abs() < 1.0);
+        assert_eq!(
+            InstructionSign::from_turn_angle(turn_angle(&north, &west)),
+            InstructionSign::Left
+        );
+    }
+}
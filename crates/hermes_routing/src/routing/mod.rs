@@ -4,6 +4,7 @@ pub(crate) mod bidirectional_astar;
 pub(crate) mod bidirectional_dijkstra;
 pub(crate) mod ch_bidirectional_dijkstra;
 pub(crate) mod dijkstra;
+pub mod instructions;
 pub mod routing_path;
 pub(crate) mod routing_path_builder;
 pub mod routing_request;
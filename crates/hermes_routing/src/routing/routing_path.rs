@@ -1,13 +1,82 @@
 use crate::{
     distance::{Distance, Meters},
+    edge_direction::EdgeDirection,
     geopoint::GeoPoint,
+    graph_edge::GraphEdge,
+    properties::property::Property,
     weighting::Milliseconds,
 };
 
+use super::instructions::{Instruction, generate_instructions};
+
+/// Way of an edge of the path, from its OSM tags
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Street {
+    name: Option<String>,
+    reference: Option<String>,
+    roundabout: bool,
+    /// The edge of the roundabout ends at an exit
+    roundabout_exit: bool,
+}
+
+impl Street {
+    pub fn new(name: Option<String>, reference: Option<String>, roundabout: bool) -> Street {
+        Street {
+            name,
+            reference,
+            roundabout,
+            roundabout_exit: false,
+        }
+    }
+
+    pub fn with_roundabout_exit(self) -> Street {
+        Street {
+            roundabout_exit: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn from_edge(edge: &impl GraphEdge, direction: EdgeDirection) -> Street {
+        let properties = edge.properties();
+        Street {
+            name: properties
+                .get_string(Property::StreetName)
+                .map(str::to_owned),
+            reference: properties
+                .get_string(Property::StreetRef)
+                .map(str::to_owned),
+            roundabout: properties
+                .get_bool(Property::Roundabout, EdgeDirection::Forward)
+                .unwrap_or(false),
+            roundabout_exit: properties
+                .get_bool(Property::RoundaboutExit, direction)
+                .unwrap_or(false),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Road number, e.g. `E40`
+    pub fn reference(&self) -> Option<&str> {
+        self.reference.as_deref()
+    }
+
+    pub fn is_roundabout(&self) -> bool {
+        self.roundabout
+    }
+
+    pub fn is_roundabout_exit(&self) -> bool {
+        self.roundabout_exit
+    }
+}
+
 pub struct RoutingPathLeg {
     distance: Distance<Meters>,
     time: Milliseconds,
     points: Vec<GeoPoint>,
+    street: Street,
 }
 
 impl RoutingPathLeg {
@@ -22,6 +91,10 @@ impl RoutingPathLeg {
     pub fn points(&self) -> &[GeoPoint] {
         &self.points
     }
+
+    pub fn street(&self) -> &Street {
+        &self.street
+    }
 }
 
 impl RoutingPathLeg {
@@ -29,11 +102,13 @@ impl RoutingPathLeg {
         distance: Distance<Meters>,
         time: Milliseconds,
         points: Vec<GeoPoint>,
+        street: Street,
     ) -> RoutingPathLeg {
         RoutingPathLeg {
             points,
             distance,
             time,
+            street,
         }
     }
 }
@@ -66,4 +141,18 @@ impl RoutingPath {
     pub fn legs(&self) -> &[RoutingPathLeg] {
         &self.legs
     }
+
+    /// Points of the legs, concatenated as the `point_index` of the instructions
+    pub fn points(&self) -> Vec<GeoPoint> {
+        self.legs
+            .iter()
+            .flat_map(|leg| leg.points())
+            .copied()
+            .collect()
+    }
+
+    /// Turn-by-turn instructions to follow the path
+    pub fn instructions(&self) -> Vec<Instruction> {
+        generate_instructions(&self.legs)
+    }
 }
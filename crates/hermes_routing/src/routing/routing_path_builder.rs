@@ -7,7 +7,7 @@ use crate::{
     weighting::Weighting,
 };
 
-use super::routing_path::{RoutingPath, RoutingPathLeg};
+use super::routing_path::{RoutingPath, RoutingPathLeg, Street};
 
 pub fn build_routing_path<G>(
     graph: &G,
@@ -31,7 +31,12 @@ where
         let distance = edge.distance();
         let time = weighting.calc_edge_ms(edge, direction);
 
-        legs.push(RoutingPathLeg::new(distance, time, geometry));
+        legs.push(RoutingPathLeg::new(
+            distance,
+            time,
+            geometry,
+            Street::from_edge(edge, direction),
+        ));
     }
 
     RoutingPath::new(legs)
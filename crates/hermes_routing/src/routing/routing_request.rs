@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use crate::geopoint::GeoPoint;
+use crate::{avoid::AvoidOptions, geopoint::GeoPoint, weighting::VehicleDimensions};
 
 #[derive(Clone, Copy, Deserialize)]
 pub enum RoutingAlgorithm {
@@ -14,11 +14,15 @@ pub enum RoutingAlgorithm {
 pub struct RoutingRequestOptions {
     pub include_debug_info: Option<bool>,
     pub algorithm: Option<RoutingAlgorithm>,
+    /// Computed on the base graph, `ContractionHierarchies` falls back to the default algorithm
+    pub avoid: Option<AvoidOptions>,
 }
 
 pub struct RoutingRequest {
     pub start: GeoPoint,
     pub end: GeoPoint,
     pub profile: String,
+    /// Only used by the `"truck"` profile
+    pub vehicle_dimensions: Option<VehicleDimensions>,
     pub options: Option<RoutingRequestOptions>,
 }
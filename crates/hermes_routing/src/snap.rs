@@ -1,4 +1,5 @@
 use crate::{
+    avoid::RoadClass,
     constants::INVALID_NODE,
     distance::{Distance, Meters},
     geopoint::GeoPoint,
@@ -23,6 +24,10 @@ impl Snap {
         }
     }
 
+    pub fn distance(&self) -> Distance<Meters> {
+        self.distance
+    }
+
     pub fn closest_node(&self) -> NodeId {
         match self.closest_node {
             Some(node) => node,
@@ -34,3 +39,24 @@ impl Snap {
         self.closest_node = Some(node_id)
     }
 }
+
+/// Restricts the roads the points of a request can be snapped to
+#[derive(Clone, Debug, Default)]
+pub struct SnapOptions {
+    /// Points further from the accessible roads are not snapped
+    pub max_radius: Option<Distance<Meters>>,
+    /// The points are never snapped to these roads, e.g. a delivery address to a motorway
+    pub excluded_road_classes: Vec<RoadClass>,
+}
+
+/// Road a point is snapped to, to diagnose the points snapped to the wrong road, e.g. the wrong
+/// side of a dual carriageway
+#[derive(Clone, Debug)]
+pub struct SnapReport {
+    pub coordinates: GeoPoint,
+    pub distance: Distance<Meters>,
+    pub street_name: Option<String>,
+    pub osm_way_id: Option<usize>,
+    /// The point is beyond an end of the road, its closest point is the end itself
+    pub clamped: bool,
+}
@@ -1,24 +1,182 @@
 use std::{
+    fmt::Display,
     fs::File,
     io::{BufReader, BufWriter, Read, Write},
     path::Path,
 };
 
-pub(crate) fn read_bytes(path: &str) -> Vec<u8> {
-    let file = File::open(path).expect("Cannot open file");
-    let mut reader = BufReader::new(file);
-    let mut buffer = Vec::new();
-    reader.read_to_end(&mut buffer).unwrap();
-    buffer
+use rkyv::util::AlignedVec;
+
+use crate::error::StorageError;
+
+/// Identifies the files written by `Hermes::save`
+const MAGIC: [u8; 8] = *b"HERMES\0\0";
+
+/// Magic, kind, format version, payload length, checksum and build metadata. A multiple of 16
+/// bytes so that the archived payload stays aligned after the header.
+const HEADER_LEN: usize = 64;
+const BUILD_LEN: usize = 32;
+
+/// Version of the crate which wrote the file, reported when the file cannot be read
+const BUILD: &str = concat!("hermes_routing ", env!("CARGO_PKG_VERSION"));
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    Graph,
+    Landmarks,
+    LocationIndex,
+    CHGraph,
+    CCHTopology,
+    AdminAreas,
+}
+
+impl FileKind {
+    fn tag(self) -> [u8; 4] {
+        match self {
+            FileKind::Graph => *b"GRPH",
+            FileKind::Landmarks => *b"LMRK",
+            FileKind::LocationIndex => *b"LIDX",
+            FileKind::CHGraph => *b"CHGR",
+            FileKind::CCHTopology => *b"CCHT",
+            FileKind::AdminAreas => *b"AREA",
+        }
+    }
+
+    /// Incremented whenever the layout of the file changes, the files of the other versions are
+    /// rejected and must be imported again
+    fn format_version(self) -> u32 {
+        match self {
+            // 2: ferry routes and road class properties
+            // 3: shared street names and roundabout exits
+            FileKind::Graph => 3,
+            FileKind::Landmarks => 1,
+            FileKind::LocationIndex => 1,
+            FileKind::CHGraph => 1,
+            FileKind::CCHTopology => 1,
+            FileKind::AdminAreas => 1,
+        }
+    }
+}
+
+impl Display for FileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileKind::Graph => write!(f, "graph"),
+            FileKind::Landmarks => write!(f, "landmarks"),
+            FileKind::LocationIndex => write!(f, "location index"),
+            FileKind::CHGraph => write!(f, "contraction hierarchies"),
+            FileKind::CCHTopology => write!(f, "customizable contraction hierarchies"),
+            FileKind::AdminAreas => write!(f, "administrative areas"),
+        }
+    }
+}
+
+struct FileHeader {
+    kind: [u8; 4],
+    format_version: u32,
+    payload_len: u64,
+    checksum: u64,
+    build: String,
+}
+
+impl FileHeader {
+    fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        bytes[0..8].copy_from_slice(&MAGIC);
+        bytes[8..12].copy_from_slice(&self.kind);
+        bytes[12..16].copy_from_slice(&self.format_version.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.payload_len.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.checksum.to_le_bytes());
+
+        let build = &self.build.as_bytes()[..self.build.len().min(BUILD_LEN)];
+        bytes[32..32 + build.len()].copy_from_slice(build);
+        bytes
+    }
+
+    /// `None` when the bytes do not start with the magic, e.g. for the files written before the
+    /// header was added
+    fn from_bytes(bytes: &[u8; HEADER_LEN]) -> Option<FileHeader> {
+        if bytes[0..8] != MAGIC {
+            return None;
+        }
+
+        let build = &bytes[32..HEADER_LEN];
+        let build_len = build
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(BUILD_LEN);
+
+        Some(FileHeader {
+            kind: bytes[8..12].try_into().unwrap(),
+            format_version: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+            payload_len: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            checksum: u64::from_le_bytes(bytes[24..32].try_into().unwrap()),
+            build: String::from_utf8_lossy(&build[..build_len]).into_owned(),
+        })
+    }
+}
+
+/// FNV-1a hash of the payload, detects truncated and corrupted files
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
 }
 
-pub(crate) fn write_bytes(bytes: &[u8], path: &str) -> Result<(), std::io::Error> {
-    let file = File::create(path).expect("failed to create file");
-    let mut writer = BufWriter::new(file);
+/// Writes the payload after a header identifying the kind and the format version of the file
+pub(crate) fn write_file(path: &str, kind: FileKind, payload: &[u8]) -> Result<(), std::io::Error> {
+    let header = FileHeader {
+        kind: kind.tag(),
+        format_version: kind.format_version(),
+        payload_len: payload.len() as u64,
+        checksum: checksum(payload),
+        build: String::from(BUILD),
+    };
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&header.to_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Reads the payload of a file written by `write_file`, after checking its header and checksum
+pub(crate) fn read_file(path: &str, kind: FileKind) -> Result<AlignedVec, StorageError> {
+    let read_error = |error| StorageError::Read(path.to_owned(), error);
+    let mut reader = BufReader::new(File::open(path).map_err(read_error)?);
+
+    let mut header_bytes = [0; HEADER_LEN];
+    reader
+        .read_exact(&mut header_bytes)
+        .map_err(|_| StorageError::MissingHeader(path.to_owned(), kind))?;
+    let header = FileHeader::from_bytes(&header_bytes)
+        .ok_or_else(|| StorageError::MissingHeader(path.to_owned(), kind))?;
 
-    writer.write_all(bytes).expect("failed to write buffer");
-    writer.flush().expect("failed to flush buffer");
-    Ok(())
+    if header.kind != kind.tag() {
+        return Err(StorageError::WrongKind(path.to_owned(), kind));
+    }
+
+    if header.format_version != kind.format_version() {
+        return Err(StorageError::UnsupportedVersion {
+            path: path.to_owned(),
+            version: header.format_version,
+            expected: kind.format_version(),
+            build: header.build,
+        });
+    }
+
+    let mut payload = AlignedVec::<16>::with_capacity(header.payload_len as usize);
+    payload
+        .extend_from_reader(&mut reader)
+        .map_err(read_error)?;
+
+    if payload.len() as u64 != header.payload_len || checksum(&payload) != header.checksum {
+        return Err(StorageError::Corrupted(path.to_owned()));
+    }
+
+    Ok(payload)
 }
 
 pub(crate) fn binary_file_path(directory: &str, filename: &str) -> String {
@@ -26,3 +184,63 @@ pub(crate) fn binary_file_path(directory: &str, filename: &str) -> String {
     let graph_file = directory.join(filename);
     graph_file.into_os_string().into_string().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("hermes_storage_{}_{name}", std::process::id()))
+            .into_os_string()
+            .into_string()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_and_read_file() {
+        let path = temp_path("roundtrip.bin");
+        write_file(&path, FileKind::Landmarks, &[1, 2, 3, 4]).unwrap();
+
+        assert_eq!(
+            &read_file(&path, FileKind::Landmarks).unwrap()[..],
+            &[1, 2, 3, 4]
+        );
+        assert!(matches!(
+            read_file(&path, FileKind::Graph),
+            Err(StorageError::WrongKind(_, FileKind::Graph))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_detect_invalid_files() {
+        let path = temp_path("corrupted.bin");
+        write_file(&path, FileKind::Graph, &[1, 2, 3, 4]).unwrap();
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[HEADER_LEN] = 42;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            read_file(&path, FileKind::Graph),
+            Err(StorageError::Corrupted(_))
+        ));
+
+        bytes[12] = 0;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            read_file(&path, FileKind::Graph),
+            Err(StorageError::UnsupportedVersion { version: 0, .. })
+        ));
+
+        // Written before the header was added
+        std::fs::write(&path, [0; 128]).unwrap();
+        assert!(matches!(
+            read_file(&path, FileKind::Graph),
+            Err(StorageError::MissingHeader(..))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -8,10 +8,11 @@ pub mod test_graph {
         distance::{Distance, Kilometers, Meters},
         edge_direction::EdgeDirection,
         geopoint::GeoPoint,
-        graph::{GeometryAccess, Graph, UndirectedEdgeAccess},
+        graph::{GeometryAccess, Graph, TurnRestrictionAccess, UndirectedEdgeAccess},
         graph_edge::GraphEdge,
         kilometers,
         properties::property_map::EdgePropertyMap,
+        turn_restrictions::{TurnRestriction, TurnRestrictionKind, TurnRestrictions},
         weighting::{Milliseconds, Weight, Weighting},
     };
 
@@ -19,6 +20,7 @@ pub mod test_graph {
         nodes: usize,
         edges: Vec<BaseGraphEdge>,
         adjacency_list: Vec<Vec<usize>>,
+        turn_restrictions: TurnRestrictions,
 
         mock_geopoint: GeoPoint,
     }
@@ -180,6 +182,7 @@ pub mod test_graph {
                 nodes: 0,
                 edges: Vec::new(),
                 adjacency_list: Vec::new(),
+                turn_restrictions: TurnRestrictions::default(),
                 mock_geopoint: GeoPoint::new(0.0, 0.0),
             }
         }
@@ -196,6 +199,31 @@ pub mod test_graph {
             graph
         }
 
+        /// Edge between `a` and `b`, in either direction
+        pub fn edge_between<A: Into<usize>, B: Into<usize>>(&self, a: A, b: B) -> usize {
+            let (a, b) = (a.into(), b.into());
+            self.edges
+                .iter()
+                .position(|edge| {
+                    (edge.start_node() == a && edge.end_node() == b)
+                        || (edge.start_node() == b && edge.end_node() == a)
+                })
+                .unwrap_or_else(|| panic!("No edge between {a} and {b}"))
+        }
+
+        /// Forbids the turn at the node `via` between the edges from `from` and to `to`
+        pub fn forbid_turn<N: Into<usize>>(&mut self, from: N, via: N, to: N) {
+            let via = via.into();
+            let mut restrictions = self.turn_restrictions.iter().copied().collect::<Vec<_>>();
+            restrictions.push(TurnRestriction {
+                from_edge: self.edge_between(from, via),
+                via_node: via,
+                to_edge: self.edge_between(via, to),
+                kind: TurnRestrictionKind::No,
+            });
+            self.turn_restrictions = TurnRestrictions::new(restrictions);
+        }
+
         fn add_node(&mut self, node_id: usize) {
             self.nodes = cmp::max(self.nodes, node_id + 1);
 
@@ -242,6 +270,22 @@ pub mod test_graph {
         }
     }
 
+    impl TurnRestrictionAccess for TestGraph {
+        fn has_turn_restrictions(&self, node_id: usize) -> bool {
+            self.turn_restrictions.has_restrictions(node_id)
+        }
+
+        fn is_turn_allowed(
+            &self,
+            from_edge_id: usize,
+            via_node_id: usize,
+            to_edge_id: usize,
+        ) -> bool {
+            self.turn_restrictions
+                .is_turn_allowed(from_edge_id, via_node_id, to_edge_id)
+        }
+    }
+
     impl GeometryAccess for TestGraph {
         fn edge_geometry(&self, _: usize) -> &[GeoPoint] {
             &[]
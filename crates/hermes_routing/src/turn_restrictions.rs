@@ -0,0 +1,117 @@
+use crate::types::{EdgeId, NodeId};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub enum TurnRestrictionKind {
+    /// The turn from `from_edge` to `to_edge` is forbidden, e.g. `no_left_turn`
+    No,
+    /// `to_edge` is the only turn allowed from `from_edge`, e.g. `only_straight_on`
+    Only,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct TurnRestriction {
+    pub from_edge: EdgeId,
+    pub via_node: NodeId,
+    pub to_edge: EdgeId,
+    pub kind: TurnRestrictionKind,
+}
+
+/// Turn table of a graph, the turns that are not restricted are allowed
+#[derive(Default, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct TurnRestrictions {
+    /// Sorted by via node, then by from edge
+    restrictions: Vec<TurnRestriction>,
+}
+
+impl TurnRestrictions {
+    pub fn new(mut restrictions: Vec<TurnRestriction>) -> Self {
+        restrictions.sort_by_key(|restriction| (restriction.via_node, restriction.from_edge));
+        restrictions.dedup();
+        TurnRestrictions { restrictions }
+    }
+
+    pub fn len(&self) -> usize {
+        self.restrictions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.restrictions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TurnRestriction> {
+        self.restrictions.iter()
+    }
+
+    fn restrictions_at(&self, via_node: NodeId) -> &[TurnRestriction] {
+        let start = self
+            .restrictions
+            .partition_point(|restriction| restriction.via_node < via_node);
+        let end = self
+            .restrictions
+            .partition_point(|restriction| restriction.via_node <= via_node);
+
+        &self.restrictions[start..end]
+    }
+
+    /// `via_node` is the via node of at least one restriction, the search algorithms only need to
+    /// know the edge a node was reached from at these nodes
+    pub fn has_restrictions(&self, via_node: NodeId) -> bool {
+        !self.restrictions_at(via_node).is_empty()
+    }
+
+    pub fn is_turn_allowed(&self, from_edge: EdgeId, via_node: NodeId, to_edge: EdgeId) -> bool {
+        let restrictions = self
+            .restrictions_at(via_node)
+            .iter()
+            .filter(|restriction| restriction.from_edge == from_edge);
+
+        let mut has_only_turns = false;
+        let mut is_only_turn = false;
+        for restriction in restrictions {
+            match restriction.kind {
+                TurnRestrictionKind::No if restriction.to_edge == to_edge => return false,
+                TurnRestrictionKind::No => {}
+                TurnRestrictionKind::Only => {
+                    has_only_turns = true;
+                    is_only_turn |= restriction.to_edge == to_edge;
+                }
+            }
+        }
+
+        !has_only_turns || is_only_turn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TurnRestriction, TurnRestrictionKind, TurnRestrictions};
+
+    #[test]
+    fn test_is_turn_allowed() {
+        let restrictions = TurnRestrictions::new(vec![
+            TurnRestriction {
+                from_edge: 0,
+                via_node: 1,
+                to_edge: 1,
+                kind: TurnRestrictionKind::No,
+            },
+            TurnRestriction {
+                from_edge: 2,
+                via_node: 1,
+                to_edge: 3,
+                kind: TurnRestrictionKind::Only,
+            },
+        ]);
+
+        assert!(!restrictions.is_turn_allowed(0, 1, 1));
+        assert!(restrictions.is_turn_allowed(0, 1, 2));
+
+        assert!(restrictions.is_turn_allowed(2, 1, 3));
+        assert!(!restrictions.is_turn_allowed(2, 1, 0));
+
+        // Other via node
+        assert!(restrictions.is_turn_allowed(0, 2, 1));
+        assert!(restrictions.has_restrictions(1));
+        assert!(!restrictions.has_restrictions(2));
+    }
+}
@@ -1,9 +1,13 @@
 use std::f64;
+use std::sync::Arc;
+
+use serde::Deserialize;
 
 use crate::constants::{DISTANCE_INFLUENCE, MAX_DURATION, MAX_WEIGHT};
 use crate::edge_direction::EdgeDirection;
 use crate::graph::Graph;
 use crate::graph_edge::GraphEdge;
+use crate::profile::{Metric, ProfileConfig};
 use crate::properties::property::Property;
 
 pub type Weight = u32;
@@ -49,27 +53,170 @@ impl<G: Graph> CarWeighting<G> {
     }
 }
 
+fn edge_weight(distance: f64, ms: Milliseconds) -> Weight {
+    if ms == MAX_DURATION {
+        return MAX_WEIGHT;
+    }
+
+    let distance_costs = distance * DISTANCE_INFLUENCE;
+    (ms as f64 + distance_costs).round() as Weight
+}
+
+fn edge_ms(distance: f64, speed: f32) -> Milliseconds {
+    if speed == 0.0 {
+        return MAX_DURATION;
+    }
+
+    let speed_meters_per_second = speed as f64 / 3.6;
+    let ms = (distance / speed_meters_per_second) * 1000.0;
+
+    ms.round() as Milliseconds
+}
+
 impl<G: Graph> Weighting<G> for CarWeighting<G> {
     fn calc_edge_weight(&self, edge: &G::Edge, direction: EdgeDirection) -> Weight {
         let ms = self.calc_edge_ms(edge, direction);
+        edge_weight(edge.distance().value(), ms)
+    }
+
+    fn calc_edge_ms(&self, edge: &G::Edge, direction: EdgeDirection) -> Milliseconds {
+        edge_ms(edge.distance().value(), Self::speed(edge, direction))
+    }
+}
+
+/// Trucks don't drive faster than this speed in km/h, whatever the speed limit
+pub(crate) const TRUCK_MAX_SPEED: f32 = 90.0;
+
+/// Dimensions of a truck, the edges whose `maxheight`, `maxwidth` or `maxweight` is lower are
+/// not accessible. Unknown dimensions are not checked
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct VehicleDimensions {
+    /// In meters
+    pub height: Option<f32>,
+    /// In meters
+    pub width: Option<f32>,
+    /// In tonnes, with the load
+    pub weight: Option<f32>,
+}
+
+impl VehicleDimensions {
+    /// Only the ways forbidden to heavy goods vehicles are avoided
+    pub fn is_unknown(&self) -> bool {
+        self.height.is_none() && self.width.is_none() && self.weight.is_none()
+    }
+}
+
+/// Car speeds capped at `TRUCK_MAX_SPEED` on the edges accessible by car and not forbidden to
+/// heavy goods vehicles or to the dimensions of the truck
+pub struct TruckWeighting<G> {
+    dimensions: VehicleDimensions,
+    _phantom: std::marker::PhantomData<G>,
+}
+
+impl<G: Graph> TruckWeighting<G> {
+    pub fn new(dimensions: VehicleDimensions) -> Self {
+        TruckWeighting {
+            dimensions,
+            _phantom: std::marker::PhantomData,
+        }
+    }
 
-        if ms == MAX_DURATION {
-            return MAX_WEIGHT;
+    fn fits(
+        edge: &G::Edge,
+        direction: EdgeDirection,
+        property: Property,
+        value: Option<f32>,
+    ) -> bool {
+        match (value, edge.properties().get_f32(property, direction)) {
+            (Some(value), Some(limit)) => value <= limit,
+            _ => true,
         }
+    }
+
+    fn is_allowed(&self, edge: &G::Edge, direction: EdgeDirection) -> bool {
+        let hgv_access = edge
+            .properties()
+            .get_bool(Property::HgvAccess, direction)
+            .unwrap_or(true);
 
-        let distance_costs = edge.distance().value() * DISTANCE_INFLUENCE;
-        (ms as f64 + distance_costs).round() as Weight
+        hgv_access
+            && Self::fits(edge, direction, Property::MaxHeight, self.dimensions.height)
+            && Self::fits(edge, direction, Property::MaxWidth, self.dimensions.width)
+            && Self::fits(edge, direction, Property::MaxWeight, self.dimensions.weight)
+    }
+}
+
+impl<G: Graph> Weighting<G> for TruckWeighting<G> {
+    fn calc_edge_weight(&self, edge: &G::Edge, direction: EdgeDirection) -> Weight {
+        let ms = self.calc_edge_ms(edge, direction);
+        edge_weight(edge.distance().value(), ms)
     }
 
     fn calc_edge_ms(&self, edge: &G::Edge, direction: EdgeDirection) -> Milliseconds {
-        let speed = Self::speed(edge, direction);
-        if speed == 0.0 {
+        if !self.is_allowed(edge, direction) {
             return MAX_DURATION;
         }
 
-        let speed_meters_per_second = speed as f64 / 3.6;
-        let ms = (edge.distance().value() / speed_meters_per_second) * 1000.0;
+        let speed = CarWeighting::<G>::speed(edge, direction).min(TRUCK_MAX_SPEED);
+        edge_ms(edge.distance().value(), speed)
+    }
+}
+
+/// Weighting of a routing profile: the car speeds, capped at the `max_speed` of the profile, on
+/// the edges the profile can use
+pub struct ProfileWeighting<G> {
+    profile: Arc<ProfileConfig>,
+    /// Set for the heavy goods vehicle profiles
+    truck: Option<TruckWeighting<G>>,
+    avoided_properties: Vec<Property>,
+}
+
+impl<G: Graph> ProfileWeighting<G> {
+    pub fn new(profile: Arc<ProfileConfig>, dimensions: VehicleDimensions) -> Self {
+        ProfileWeighting {
+            truck: profile.hgv.then(|| TruckWeighting::new(dimensions)),
+            avoided_properties: profile
+                .avoid
+                .iter()
+                .map(|road_class| road_class.property())
+                .collect(),
+            profile,
+        }
+    }
+
+    fn is_allowed(&self, edge: &G::Edge, direction: EdgeDirection) -> bool {
+        self.truck
+            .as_ref()
+            .is_none_or(|truck| truck.is_allowed(edge, direction))
+            && !self.avoided_properties.iter().any(|property| {
+                edge.properties()
+                    .get_bool(property.clone(), direction)
+                    .unwrap_or(false)
+            })
+    }
+}
+
+impl<G: Graph> Weighting<G> for ProfileWeighting<G> {
+    fn calc_edge_weight(&self, edge: &G::Edge, direction: EdgeDirection) -> Weight {
+        let ms = self.calc_edge_ms(edge, direction);
+
+        match self.profile.metric {
+            Metric::Fastest => edge_weight(edge.distance().value(), ms),
+            Metric::Shortest if ms == MAX_DURATION => MAX_WEIGHT,
+            Metric::Shortest => edge.distance().value().round() as Weight,
+        }
+    }
+
+    fn calc_edge_ms(&self, edge: &G::Edge, direction: EdgeDirection) -> Milliseconds {
+        if !self.is_allowed(edge, direction) {
+            return MAX_DURATION;
+        }
 
-        ms.round() as Milliseconds
+        let speed = CarWeighting::<G>::speed(edge, direction);
+        let speed = self
+            .profile
+            .max_speed
+            .map_or(speed, |max_speed| speed.min(max_speed));
+        edge_ms(edge.distance().value(), speed)
     }
 }
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use hermes_matrix_providers::travel_matrices::TravelMatrices;
+use hermes_optimizer::json::{checkpoint::JsonCheckpoint, types::JsonVehicleRoutingProblem};
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Incremented when the format of the backup changes, restoring a backup of another version fails
+pub const BACKUP_VERSION: u32 = 2;
+
+/// Snapshot of the state of the service, restoring it on another instance brings back the jobs with
+/// their matrices, best solutions and learned weights, and the uploaded matrices.
+/// Cached matrices are not included, they are fetched again when needed.
+#[derive(Serialize, Deserialize)]
+pub struct ServiceBackup {
+    pub version: u32,
+    pub created_at: Timestamp,
+    pub jobs: Vec<JobBackup>,
+    pub matrices: HashMap<String, TravelMatrices>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JobBackup {
+    pub job_id: String,
+    pub input: JsonVehicleRoutingProblem,
+    /// Matrices of the vehicle profiles of `input`, in the same order, the job is restored without
+    /// fetching them again
+    pub profile_matrices: Vec<TravelMatrices>,
+    pub checkpoint: JsonCheckpoint,
+}
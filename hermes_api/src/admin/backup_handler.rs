@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use jiff::Timestamp;
+
+use crate::{
+    admin::backup::{BACKUP_VERSION, JobBackup, ServiceBackup},
+    state::AppState,
+};
+
+pub async fn backup_handler(State(state): State<Arc<AppState>>) -> Json<ServiceBackup> {
+    let job_inputs = state.job_inputs.read().await;
+
+    let mut jobs = Vec::with_capacity(job_inputs.len());
+    for (job_id, input) in job_inputs.iter() {
+        if let Some(solver) = state.solver_manager.solver(job_id).await {
+            jobs.push(JobBackup {
+                job_id: job_id.clone(),
+                input: input.clone(),
+                profile_matrices: solver
+                    .problem()
+                    .vehicle_profiles()
+                    .iter()
+                    .map(|profile| profile.travel_costs().to_travel_matrices())
+                    .collect(),
+                checkpoint: solver.json_checkpoint(),
+            });
+        }
+    }
+
+    Json(ServiceBackup {
+        version: BACKUP_VERSION,
+        created_at: Timestamp::now(),
+        jobs,
+        matrices: state.matrix_client.matrix_store().export(),
+    })
+}
@@ -0,0 +1,3 @@
+pub mod backup;
+pub mod backup_handler;
+pub mod restore_handler;
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use hermes_matrix_providers::travel_matrices::TravelMatrices;
+use hermes_optimizer::{
+    json::{checkpoint::JsonCheckpoint, types::JsonVehicleRoutingProblem},
+    problem::{travel_cost_matrix, vehicle_profile::VehicleProfile},
+    solver::{solver::Solver, solver_manager::SolverManager},
+};
+use serde::Serialize;
+
+use crate::{
+    admin::backup::{BACKUP_VERSION, ServiceBackup},
+    error::ApiError,
+    state::AppState,
+    trace::trace_parent::job_span,
+    vrp::job_store::{JobKind, JobRecord},
+};
+
+#[derive(Serialize)]
+pub struct RestoreResponse {
+    jobs: usize,
+    matrices: usize,
+}
+
+/// Restores a backup taken with `backup_handler`, jobs with the same ID are replaced.
+/// Restored jobs are pending and continue from their backed up state when started.
+///
+/// Every job of the backup is built and restored before the state is changed, nothing is
+/// restored when one of them is invalid
+pub async fn restore_handler(
+    State(state): State<Arc<AppState>>,
+    Json(backup): Json<ServiceBackup>,
+) -> Result<Json<RestoreResponse>, ApiError> {
+    if backup.version != BACKUP_VERSION {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported backup version {}, expected {BACKUP_VERSION}",
+            backup.version
+        )));
+    }
+
+    let mut prepared_jobs = Vec::with_capacity(backup.jobs.len());
+    for job in backup.jobs {
+        let prepared_job = prepare_job(
+            &state,
+            &job.job_id,
+            job.input,
+            Some(job.profile_matrices),
+            Some(&job.checkpoint),
+        )
+        .await
+        .map_err(|error| {
+            ApiError::BadRequest(format!("Invalid job {}: {}", job.job_id, error.message()))
+        })?;
+        prepared_jobs.push(prepared_job);
+    }
+
+    let matrices = backup.matrices.len();
+    for (matrix_id, matrices) in backup.matrices {
+        state
+            .matrix_client
+            .matrix_store()
+            .import(matrix_id, matrices);
+    }
+
+    let jobs = prepared_jobs.len();
+    for prepared_job in prepared_jobs {
+        let input = prepared_job.input.clone();
+        let job_id = prepared_job.insert(&state).await;
+        state
+            .jobs
+            .insert(JobRecord::new(job_id, JobKind::Vrp { input }))
+            .await;
+    }
+
+    Ok(Json(RestoreResponse { jobs, matrices }))
+}
+
+/// Job built and restored from its checkpoint, it is only visible once added with `insert`
+pub struct PreparedJob {
+    solver: Solver,
+    input: JsonVehicleRoutingProblem,
+}
+
+impl PreparedJob {
+    /// Adds the job, pending until it is started. The job with the same ID is replaced
+    pub async fn insert(self, state: &AppState) -> String {
+        state.annotate_location_areas(self.solver.problem());
+        let job_id = state.solver_manager.insert_job(self.solver).await;
+        state
+            .job_inputs
+            .write()
+            .await
+            .insert(job_id.clone(), self.input);
+
+        job_id
+    }
+}
+
+/// Builds the job of `input` again under `job_id` and restores its checkpoint. The matrices of
+/// the profiles are fetched again unless `profile_matrices` is given
+pub async fn prepare_job(
+    state: &AppState,
+    job_id: &str,
+    mut input: JsonVehicleRoutingProblem,
+    profile_matrices: Option<Vec<TravelMatrices>>,
+    checkpoint: Option<&JsonCheckpoint>,
+) -> Result<PreparedJob, ApiError> {
+    // The ID is generated when missing from the input, the restored job must keep the same one
+    input.id = Some(job_id.to_owned());
+
+    let problem = match profile_matrices {
+        Some(profile_matrices) => {
+            if profile_matrices.len() != input.vehicle_profiles.len() {
+                return Err(ApiError::BadRequest(format!(
+                    "Expected the matrices of {} profiles, got {}",
+                    input.vehicle_profiles.len(),
+                    profile_matrices.len()
+                )));
+            }
+
+            let vehicle_profiles = input
+                .vehicle_profiles
+                .iter()
+                .zip(profile_matrices)
+                .map(|(profile, matrices)| {
+                    VehicleProfile::new(
+                        profile.id.clone(),
+                        travel_cost_matrix::TravelMatrices::from_travel_matrices(matrices),
+                    )
+                })
+                .collect();
+
+            input
+                .clone()
+                .build_problem_with_profiles(vehicle_profiles)
+                .map_err(|error| ApiError::BadRequest(error.to_string()))?
+        }
+        None => {
+            input
+                .clone()
+                .build_problem_with_providers(
+                    &state.matrix_client,
+                    &state.profiles.travel_time_providers(&state.matrix_cache),
+                )
+                .await?
+        }
+    };
+
+    let solver = SolverManager::job_solver(Arc::new(problem), None, job_span(job_id, None));
+
+    if let Some(checkpoint) = checkpoint {
+        solver
+            .restore_json_checkpoint(checkpoint)
+            .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+    }
+
+    Ok(PreparedJob { solver, input })
+}
+
+/// Creates the job of `input` again under `job_id` from its checkpoint, the job is pending until
+/// it is started
+pub async fn restore_job(
+    state: &AppState,
+    job_id: &str,
+    input: JsonVehicleRoutingProblem,
+    checkpoint: Option<&JsonCheckpoint>,
+) -> Result<String, ApiError> {
+    let prepared_job = prepare_job(state, job_id, input, None, checkpoint).await?;
+    Ok(prepared_job.insert(state).await)
+}
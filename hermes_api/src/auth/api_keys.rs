@@ -0,0 +1,190 @@
+use std::{collections::HashMap, fs::File, io::BufReader, sync::Arc, time::Duration};
+
+use hermes_optimizer::solver::solver_manager::JobOwner;
+use serde::Deserialize;
+
+use crate::auth::rate_limiter::RateLimiter;
+
+/// Entry of the `API_KEYS_FILE`, the limits are unlimited when not set
+#[derive(Deserialize)]
+pub(super) struct ApiKeyConfig {
+    /// Team or service using the key, the jobs are owned by this name
+    name: String,
+    key: String,
+    requests_per_minute: Option<u32>,
+    max_running_jobs: Option<usize>,
+    /// Administrators access the `/admin` endpoints and the jobs of every tenant
+    #[serde(default)]
+    admin: bool,
+}
+
+/// Team or service calling the API with one of the configured keys
+pub struct Tenant {
+    name: String,
+    rate_limiter: Option<RateLimiter>,
+    max_running_jobs: Option<usize>,
+    admin: bool,
+}
+
+impl Tenant {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin
+    }
+
+    /// Counts a request, or returns how long to wait until the next one is accepted
+    pub fn check_rate_limit(&self) -> Result<(), Duration> {
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.try_acquire(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn job_owner(&self) -> JobOwner {
+        JobOwner {
+            name: self.name.clone(),
+            max_running_jobs: self.max_running_jobs,
+        }
+    }
+}
+
+/// Keys accepted by the API. Authentication is disabled when no key is configured
+#[derive(Default)]
+pub struct ApiKeys {
+    tenants: HashMap<String, Arc<Tenant>>,
+}
+
+impl ApiKeys {
+    /// Reads the keys of the JSON file `API_KEYS_FILE` and of `API_KEYS`, a comma separated list
+    /// of `name:key` pairs without limits, `name:key:admin` for the administrators
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut configs = Vec::new();
+
+        if let Ok(path) = std::env::var("API_KEYS_FILE") {
+            let file = File::open(&path)
+                .map_err(|error| anyhow::anyhow!("Failed to open {path}: {error}"))?;
+            let file_configs: Vec<ApiKeyConfig> = serde_json::from_reader(BufReader::new(file))?;
+            configs.extend(file_configs);
+        }
+
+        if let Ok(keys) = std::env::var("API_KEYS") {
+            configs.extend(parse_keys(&keys)?);
+        }
+
+        Self::from_configs(configs)
+    }
+
+    pub(super) fn from_configs(configs: Vec<ApiKeyConfig>) -> anyhow::Result<Self> {
+        let mut tenants = HashMap::new();
+        for config in configs {
+            if config.key.is_empty() {
+                anyhow::bail!("The API key of {} is empty", config.name);
+            }
+
+            let tenant = Tenant {
+                name: config.name,
+                rate_limiter: config.requests_per_minute.map(RateLimiter::per_minute),
+                max_running_jobs: config.max_running_jobs,
+                admin: config.admin,
+            };
+
+            if tenants.insert(config.key, Arc::new(tenant)).is_some() {
+                anyhow::bail!("The same API key is configured twice");
+            }
+        }
+
+        Ok(ApiKeys { tenants })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tenants.is_empty()
+    }
+
+    pub fn authenticate(&self, key: &str) -> Option<Arc<Tenant>> {
+        self.tenants.get(key).cloned()
+    }
+
+    /// Whether `tenant` may access a job owned by `owner`: its own jobs, or every job for the
+    /// administrators. Every job is accessible when authentication is disabled
+    pub fn can_access(&self, tenant: Option<&Tenant>, owner: Option<&str>) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+
+        tenant.is_some_and(|tenant| tenant.admin || owner == Some(tenant.name.as_str()))
+    }
+
+    /// Tenant by name, to restore the owner of a persisted job
+    pub fn tenant(&self, name: &str) -> Option<Arc<Tenant>> {
+        self.tenants
+            .values()
+            .find(|tenant| tenant.name == name)
+            .cloned()
+    }
+}
+
+pub(crate) fn parse_keys(keys: &str) -> anyhow::Result<Vec<ApiKeyConfig>> {
+    keys.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (name, key) = entry
+                .trim()
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("Expected name:key in API_KEYS"))?;
+            let (key, admin) = match key.strip_suffix(":admin") {
+                Some(key) => (key, true),
+                None => (key, false),
+            };
+
+            Ok(ApiKeyConfig {
+                name: name.to_owned(),
+                key: key.to_owned(),
+                requests_per_minute: None,
+                max_running_jobs: None,
+                admin,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() {
+        let keys =
+            ApiKeys::from_configs(parse_keys("ops:secret:admin, team:key,").unwrap()).unwrap();
+
+        let ops = keys.authenticate("secret").unwrap();
+        assert_eq!(ops.name(), "ops");
+        assert!(ops.is_admin());
+
+        let team = keys.authenticate("key").unwrap();
+        assert_eq!(team.name(), "team");
+        assert!(!team.is_admin());
+        assert_eq!(keys.tenant("team").unwrap().name(), "team");
+
+        assert!(parse_keys("team").is_err());
+        assert!(ApiKeys::from_configs(parse_keys("team:key,other:key").unwrap()).is_err());
+        assert!(ApiKeys::from_configs(parse_keys("team:").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_can_access() {
+        let keys = ApiKeys::from_configs(parse_keys("ops:secret:admin,team:key").unwrap()).unwrap();
+        let ops = keys.authenticate("secret").unwrap();
+        let team = keys.authenticate("key").unwrap();
+
+        assert!(keys.can_access(Some(&team), Some("team")));
+        assert!(!keys.can_access(Some(&team), Some("other")));
+        assert!(!keys.can_access(Some(&team), None));
+        assert!(keys.can_access(Some(&ops), Some("other")));
+        assert!(!keys.can_access(None, Some("team")));
+
+        assert!(ApiKeys::default().can_access(None, None));
+    }
+}
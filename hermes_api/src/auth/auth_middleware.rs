@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension,
+    extract::{Path, Request, State},
+    http::{HeaderMap, header::AUTHORIZATION},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::{
+    auth::api_keys::{ApiKeys, Tenant},
+    error::ApiError,
+    state::AppState,
+    vrp::{job::JobPath, plan::get_plan::PlanPath},
+};
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Key of the `X-Api-Key` header, or of a bearer `Authorization` header
+fn api_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(API_KEY_HEADER) {
+        return key.to_str().ok();
+    }
+
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Tenant of the API key of the request, `None` when authentication is disabled or for the
+/// documentation
+fn authenticate(
+    api_keys: &ApiKeys,
+    path: &str,
+    headers: &HeaderMap,
+) -> Result<Option<Arc<Tenant>>, ApiError> {
+    if !api_keys.is_enabled() || path.starts_with("/docs") {
+        return Ok(None);
+    }
+
+    let key =
+        api_key(headers).ok_or_else(|| ApiError::Unauthorized(String::from("Missing API key")))?;
+    let tenant = api_keys
+        .authenticate(key)
+        .ok_or_else(|| ApiError::Unauthorized(String::from("Unknown API key")))?;
+
+    if let Err(retry_after) = tenant.check_rate_limit() {
+        warn!(tenant = tenant.name(), "Rate limiting request");
+        return Err(ApiError::TooManyRequests {
+            message: format!("Rate limit of {} exceeded", tenant.name()),
+            retry_after_secs: retry_after.as_secs_f64().ceil() as u64,
+        });
+    }
+
+    Ok(Some(tenant))
+}
+
+/// Rejects the requests without a known API key and the ones above the rate limit of their key,
+/// to be installed with `axum::middleware::from_fn_with_state`. The tenant of the key is added to
+/// the extensions of the request.
+///
+/// The documentation stays public, every request is accepted when no key is configured
+pub async fn auth_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match authenticate(&state.api_keys, request.uri().path(), request.headers()) {
+        Ok(Some(tenant)) => {
+            request.extensions_mut().insert(tenant);
+            next.run(request).await
+        }
+        Ok(None) => next.run(request).await,
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Rejects the requests of the keys which are not administrator keys, to be installed on the
+/// `/admin` and profile reload routes after `auth_middleware`
+pub async fn admin_middleware(
+    State(state): State<Arc<AppState>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.api_keys.is_enabled() && !tenant.is_some_and(|Extension(tenant)| tenant.is_admin()) {
+        return ApiError::Forbidden(String::from("An administrator API key is required"))
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Rejects the requests on the jobs of the other tenants, to be installed on the routes of a
+/// `{job_id}` after `auth_middleware`
+pub async fn job_access_middleware(
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<JobPath>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let tenant = tenant.as_ref().map(|Extension(tenant)| tenant.as_ref());
+    if let Err(error) = state.check_job_access(tenant, &path.job_id.to_string()) {
+        return error.into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Rejects the requests on the plans of the jobs of the other tenants, to be installed on the
+/// routes of a `{plan_id}` after `auth_middleware`
+pub async fn plan_access_middleware(
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<PlanPath>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let plan_id = path.plan_id.to_string();
+    let tenant = tenant.as_ref().map(|Extension(tenant)| tenant.as_ref());
+    if let Some(plan) = state.plans.get(&plan_id)
+        && state.check_job_access(tenant, plan.job_id()).is_err()
+    {
+        return ApiError::NotFound(plan_id).into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use crate::auth::api_keys::parse_keys;
+
+    use super::*;
+
+    fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_api_key() {
+        assert_eq!(api_key(&headers("x-api-key", "key")), Some("key"));
+        assert_eq!(
+            api_key(&headers("authorization", "Bearer key")),
+            Some("key")
+        );
+        assert_eq!(api_key(&headers("authorization", "Basic key")), None);
+        assert_eq!(api_key(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let api_keys = ApiKeys::from_configs(parse_keys("team:key").unwrap()).unwrap();
+
+        let tenant = authenticate(&api_keys, "/v1/vrp/jobs", &headers("x-api-key", "key"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(tenant.name(), "team");
+
+        assert!(matches!(
+            authenticate(&api_keys, "/v1/vrp/jobs", &HeaderMap::new()),
+            Err(ApiError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            authenticate(&api_keys, "/v1/vrp/jobs", &headers("x-api-key", "other")),
+            Err(ApiError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            authenticate(&api_keys, "/docs/openapi.json", &HeaderMap::new()),
+            Ok(None)
+        ));
+        assert!(matches!(
+            authenticate(&ApiKeys::default(), "/v1/vrp/jobs", &HeaderMap::new()),
+            Ok(None)
+        ));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod api_keys;
+pub mod auth_middleware;
+pub mod rate_limiter;
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket refilled continuously, the requests of a whole minute can be made in a burst
+pub struct RateLimiter {
+    capacity: f64,
+    tokens_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        let capacity = f64::from(requests.max(1));
+
+        RateLimiter {
+            capacity,
+            tokens_per_second: capacity / 60.0,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, or returns how long to wait until the next one
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock();
+        let now = Instant::now();
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.tokens_per_second).min(self.capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.tokens_per_second,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_wait() {
+        let rate_limiter = RateLimiter::per_minute(2);
+        assert!(rate_limiter.try_acquire().is_ok());
+        assert!(rate_limiter.try_acquire().is_ok());
+
+        // One token every 30 seconds
+        let retry_after = rate_limiter.try_acquire().unwrap_err();
+        assert!(retry_after > Duration::from_secs(29));
+        assert!(retry_after <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_refill() {
+        let rate_limiter = RateLimiter::per_minute(60);
+        for _ in 0..60 {
+            assert!(rate_limiter.try_acquire().is_ok());
+        }
+        assert!(rate_limiter.try_acquire().is_err());
+
+        // A token is refilled every second
+        rate_limiter.bucket.lock().refilled_at -= Duration::from_secs(2);
+        assert!(rate_limiter.try_acquire().is_ok());
+        assert!(rate_limiter.try_acquire().is_ok());
+        assert!(rate_limiter.try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_zero_requests_allows_one() {
+        let rate_limiter = RateLimiter::per_minute(0);
+        assert!(rate_limiter.try_acquire().is_ok());
+        assert!(rate_limiter.try_acquire().is_err());
+    }
+}
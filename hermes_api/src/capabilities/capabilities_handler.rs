@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use hermes_optimizer::json::capabilities::JsonSolverCapabilities;
+use serde::Serialize;
+
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct CapabilitiesResponse {
+    version: &'static str,
+    solver: JsonSolverCapabilities,
+    /// Routing profiles usable as vehicle profiles of the `/vrp` problems
+    profiles: Vec<String>,
+    /// Largest number of locations of a `/vrp` problem, unlimited when null
+    max_problem_locations: Option<usize>,
+}
+
+/// Features supported by the running deployment
+pub async fn capabilities_handler(
+    State(state): State<Arc<AppState>>,
+) -> Json<CapabilitiesResponse> {
+    Json(CapabilitiesResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        solver: JsonSolverCapabilities::CURRENT,
+        profiles: state.profiles.names(),
+        max_problem_locations: state.max_problem_locations,
+    })
+}
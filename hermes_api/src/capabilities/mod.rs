@@ -0,0 +1 @@
+pub mod capabilities_handler;
@@ -1,6 +1,11 @@
 use aide::{OperationOutput, generate::GenContext, openapi::Operation};
 
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    response::IntoResponse,
+};
+use hermes_optimizer::solver::solver_manager::SolverManagerError;
 use schemars::JsonSchema;
 use serde::Serialize;
 
@@ -9,6 +14,45 @@ pub enum ApiError {
     BadRequest(String),
     InternalServerError(String),
     NotFound(String),
+    /// Missing or unknown API key
+    Unauthorized(String),
+    /// The API key is not allowed to call the endpoint
+    Forbidden(String),
+    /// The request conflicts with the jobs already running, e.g. the concurrency limit of the
+    /// API key is reached
+    Conflict(String),
+    /// The rate limit of the API key is exceeded
+    TooManyRequests {
+        message: String,
+        retry_after_secs: u64,
+    },
+    /// The server is shutting down and no longer starts jobs
+    ServiceUnavailable(String),
+}
+
+impl ApiError {
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::InternalServerError(message)
+            | ApiError::NotFound(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::Conflict(message)
+            | ApiError::TooManyRequests { message, .. }
+            | ApiError::ServiceUnavailable(message) => message,
+        }
+    }
+}
+
+impl From<SolverManagerError> for ApiError {
+    fn from(error: SolverManagerError) -> Self {
+        match error {
+            SolverManagerError::JobNotFound(job_id) => ApiError::NotFound(job_id),
+            SolverManagerError::TooManyRunningJobs { .. } => ApiError::Conflict(error.to_string()),
+            SolverManagerError::InvalidParams(_) => ApiError::BadRequest(error.to_string()),
+        }
+    }
 }
 
 impl From<anyhow::Error> for ApiError {
@@ -25,6 +69,22 @@ impl IntoResponse for ApiError {
             }
             ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
             ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
+            ApiError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message).into_response(),
+            ApiError::Forbidden(message) => (StatusCode::FORBIDDEN, message).into_response(),
+            ApiError::Conflict(message) => (StatusCode::CONFLICT, message).into_response(),
+            ApiError::TooManyRequests {
+                message,
+                retry_after_secs,
+            } => {
+                let mut response = (StatusCode::TOO_MANY_REQUESTS, message).into_response();
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+                response
+            }
+            ApiError::ServiceUnavailable(message) => {
+                (StatusCode::SERVICE_UNAVAILABLE, message).into_response()
+            }
         }
     }
 }
@@ -54,6 +114,41 @@ impl OperationOutput for ApiError {
                         ..res.clone()
                     },
                 ),
+                (
+                    Some(aide::openapi::StatusCode::Code(401)),
+                    aide::openapi::Response {
+                        description: "Missing or unknown API key".into(),
+                        ..res.clone()
+                    },
+                ),
+                (
+                    Some(aide::openapi::StatusCode::Code(403)),
+                    aide::openapi::Response {
+                        description: "The API key is not an administrator key".into(),
+                        ..res.clone()
+                    },
+                ),
+                (
+                    Some(aide::openapi::StatusCode::Code(409)),
+                    aide::openapi::Response {
+                        description: "Too many running jobs for the API key".into(),
+                        ..res.clone()
+                    },
+                ),
+                (
+                    Some(aide::openapi::StatusCode::Code(429)),
+                    aide::openapi::Response {
+                        description: "Rate limit of the API key exceeded".into(),
+                        ..res.clone()
+                    },
+                ),
+                (
+                    Some(aide::openapi::StatusCode::Code(503)),
+                    aide::openapi::Response {
+                        description: "The server is shutting down".into(),
+                        ..res.clone()
+                    },
+                ),
                 (
                     Some(aide::openapi::StatusCode::Code(500)),
                     aide::openapi::Response {
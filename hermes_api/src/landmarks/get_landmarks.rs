@@ -21,7 +21,11 @@ impl IntoResponse for GetLandmarksResponse {
 pub async fn get_landmarks(
     State(state): State<Arc<AppState>>,
 ) -> Result<GetLandmarksResponse, ApiError> {
-    let landmarks = state.hermes.get_landmarks();
+    let landmarks = state
+        .profiles
+        .get("car")
+        .ok_or_else(|| ApiError::NotFound(String::from("Profile car not found")))?
+        .get_landmarks();
 
     /*
     let forward_feature = Feature {
@@ -1,30 +1,64 @@
+mod admin;
+mod auth;
+mod capabilities;
 mod docs;
 mod error;
 mod landmarks;
+mod matrix;
+mod nearest;
 mod pagination;
+mod profiles;
 mod route;
+mod shutdown;
+mod slo;
 mod state;
+mod trace;
+mod versioning;
 mod vrp;
 
+use crate::admin::backup_handler::backup_handler;
+use crate::admin::restore_handler::restore_handler;
+use crate::auth::api_keys::ApiKeys;
+use crate::auth::auth_middleware::{admin_middleware, auth_middleware, job_access_middleware};
+use crate::capabilities::capabilities_handler::capabilities_handler;
 use crate::docs::docs_routes;
 use crate::get_landmarks::get_landmarks;
+use crate::matrix::cache_metrics_handler::cache_metrics_handler;
+use crate::matrix::matrix_cache::{DEFAULT_MAX_ENTRIES, MatrixCache};
+use crate::matrix::poll_job_handler::poll_job_handler;
+use crate::matrix::post_job_handler::post_job_handler;
+use crate::matrix::upload_handler::upload_handler;
+use crate::nearest::nearest_handler::nearest_handler;
+use crate::profiles::list_handler::list_handler;
+use crate::profiles::profile_registry::ProfileRegistry;
+use crate::profiles::reload_handler::reload_handler;
 use crate::route::route_handler::route_handler;
+use crate::shutdown::shutdown_signal;
+use crate::slo::endpoint_slo::EndpointSlo;
+use crate::slo::slo_middleware::slo_middleware;
 use crate::state::AppState;
+use crate::trace::trace_middleware::trace_middleware;
+use crate::versioning::V1_PREFIX;
+use crate::versioning::legacy_paths::legacy_paths_middleware;
+use crate::vrp::job_store::{self, JobStore};
 use crate::vrp::routes::vrp_routes;
+use aide::axum::ApiRouter;
 use aide::openapi::OpenApi;
 use aide::transform::TransformOpenApi;
+use axum::extract::{DefaultBodyLimit, Request};
 use axum::http::Method;
+use axum::middleware::{from_fn, from_fn_with_state};
 use axum::routing::{get, post};
-use axum::{Extension, serve};
+use axum::{Extension, ServiceExt, serve};
 use hermes_matrix_providers::travel_matrix_client::TravelMatrixClient;
 use hermes_optimizer::solver::solver_manager::SolverManager;
 use hermes_osrm::client::{OsrmClient, OsrmClientParams};
-use hermes_routing::hermes::Hermes;
 use landmarks::get_landmarks;
 use std::sync::Arc;
-use tower::ServiceBuilder;
+use std::time::Duration;
+use tower::{Layer, ServiceBuilder};
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{Level, info};
+use tracing::{Level, info, warn};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::fmt::format::FmtSpan;
 
@@ -33,6 +67,12 @@ use mimalloc::MiMalloc;
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+const DEFAULT_JOB_STORE_DIR: &str = "./data/jobs";
+/// The running jobs lose at most this much of their search when the API stops
+const JOB_STORE_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+/// Time given to the search threads of the running jobs to stop once the server shuts down
+const JOB_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[tokio::main]
 async fn main() {
     // console_subscriber::init();
@@ -53,18 +93,51 @@ async fn main() {
     aide::generate::on_error(|error| tracing::error!("{}", error));
     aide::generate::extract_schemas(true);
 
-    let hermes = Hermes::from_directory("./data/be");
+    let profiles = ProfileRegistry::default();
+    if let Err(error) = profiles.load("car", "./data/be") {
+        panic!("Failed to load the car profile: {error}");
+    }
+
+    let api_keys =
+        ApiKeys::from_env().unwrap_or_else(|error| panic!("Failed to load the API keys: {error}"));
+    if !api_keys.is_enabled() {
+        warn!("No API key is configured, the API is open to every caller");
+    }
 
     let state = Arc::new(AppState {
-        hermes,
+        api_keys,
+        profiles,
         solver_manager: SolverManager::default(),
+        job_inputs: Default::default(),
+        jobs: JobStore::open(
+            std::env::var("JOB_STORE_DIR").unwrap_or(String::from(DEFAULT_JOB_STORE_DIR)),
+        )
+        .unwrap_or_else(|error| panic!("Failed to open the job store: {error}")),
         matrix_client: TravelMatrixClient::default(),
         osrm_client: OsrmClient::new(OsrmClientParams {
             osrm_url: std::env::var("OSRM_URL")
                 .unwrap_or(String::from("http://router.project-osrm.org")),
         }),
+        matrix_jobs: Default::default(),
+        matrix_cache: Arc::new(MatrixCache::new(
+            std::env::var("MATRIX_CACHE_SIZE")
+                .ok()
+                .and_then(|size| size.parse().ok())
+                .unwrap_or(DEFAULT_MAX_ENTRIES),
+        )),
+        service_durations: Default::default(),
+        location_areas: Default::default(),
+        plans: Default::default(),
+        max_problem_locations: std::env::var("MAX_PROBLEM_LOCATIONS").ok().map(|size| {
+            size.parse()
+                .unwrap_or_else(|error| panic!("Invalid MAX_PROBLEM_LOCATIONS {size}: {error}"))
+        }),
+        shutdown: tokio::sync::watch::Sender::new(false),
     });
 
+    job_store::restore_jobs(&state).await;
+    job_store::schedule_sync(&state, JOB_STORE_SYNC_INTERVAL);
+
     let cors_layer = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST])
         .allow_origin(Any)
@@ -72,27 +145,9 @@ async fn main() {
 
     let mut api = OpenApi::default();
 
-    let app = aide::axum::ApiRouter::new()
+    let app = ApiRouter::new()
         .nest_api_service("/docs", docs_routes(state.clone()))
-        .route("/route", post(route_handler))
-        .route("/landmarks", get(get_landmarks))
-        .nest_api_service("/vrp", vrp_routes(state.clone()))
-        .route(
-            "/vrp/benchmark",
-            post(vrp::benchmark::post_benchmark::post_benchmark_handler),
-        )
-        .route(
-            "/vrp/benchmark/{category}/{name}",
-            get(vrp::benchmark::get_benchmark::get_benchmark_handler),
-        )
-        .route(
-            "/vrp/benchmark/poll/{job_id}",
-            get(vrp::benchmark::poll_benchmark::poll_handler),
-        )
-        .route(
-            "/vrp/benchmark/stop/{job_id}",
-            post(vrp::benchmark::stop_benchmark::stop_benchmark_handler),
-        )
+        .nest_api_service(V1_PREFIX, v1_routes(state.clone()))
         .finish_api_with(&mut api, api_docs);
 
     if std::env::args().any(|a| a == "--generate-openapi") {
@@ -107,15 +162,92 @@ async fn main() {
     }
 
     let app = app
-        .layer(ServiceBuilder::new().layer(cors_layer))
+        .layer(from_fn_with_state(state.clone(), auth_middleware))
+        .layer(
+            ServiceBuilder::new()
+                .layer(cors_layer)
+                .layer(from_fn(trace_middleware)),
+        )
         .layer(Extension(Arc::new(api)))
-        .with_state(state);
+        .with_state(state.clone());
+
+    // Rewriting the unversioned paths has to happen before the routing
+    let app = from_fn(legacy_paths_middleware).layer(app);
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:8080")
         .await
         .unwrap();
 
-    serve(listener, app).await.unwrap();
+    serve(listener, ServiceExt::<Request>::into_make_service(app))
+        .with_graceful_shutdown(shutdown_signal(state.clone()))
+        .await
+        .unwrap();
+
+    job_store::drain_jobs(&state, JOB_DRAIN_TIMEOUT).await;
+    info!("Shut down");
+}
+
+/// Routes of the first version of the API, also served on the unversioned paths by
+/// `legacy_paths_middleware`
+fn v1_routes(state: Arc<AppState>) -> ApiRouter {
+    ApiRouter::new()
+        // A storm of matrix jobs must not make the routing queries miss their latency objective
+        .route(
+            "/route",
+            post(route_handler).layer(from_fn_with_state(
+                EndpointSlo::from_env("/route", "ROUTE", Duration::from_millis(500), 64),
+                slo_middleware,
+            )),
+        )
+        .route("/nearest", post(nearest_handler))
+        .route("/landmarks", get(get_landmarks))
+        .route("/capabilities", get(capabilities_handler))
+        .route("/matrix/upload", post(upload_handler))
+        .route(
+            "/matrix/jobs",
+            post(post_job_handler).layer(from_fn_with_state(
+                EndpointSlo::from_env("/matrix/jobs", "MATRIX_JOBS", Duration::from_secs(5), 4),
+                slo_middleware,
+            )),
+        )
+        .route("/matrix/jobs/{job_id}/poll", get(poll_job_handler))
+        .route("/matrix/cache", get(cache_metrics_handler))
+        .route(
+            "/admin/backup",
+            get(backup_handler).layer(from_fn_with_state(state.clone(), admin_middleware)),
+        )
+        // Backups contain the uploaded matrices and can be far above the default body limit
+        .route(
+            "/admin/restore",
+            post(restore_handler)
+                .layer(DefaultBodyLimit::disable())
+                .layer(from_fn_with_state(state.clone(), admin_middleware)),
+        )
+        .route("/profiles", get(list_handler))
+        .route(
+            "/profiles/{name}/reload",
+            post(reload_handler).layer(from_fn_with_state(state.clone(), admin_middleware)),
+        )
+        .nest_api_service("/vrp", vrp_routes(state.clone()))
+        .route(
+            "/vrp/benchmark",
+            post(vrp::benchmark::post_benchmark::post_benchmark_handler),
+        )
+        .route(
+            "/vrp/benchmark/{category}/{name}",
+            get(vrp::benchmark::get_benchmark::get_benchmark_handler),
+        )
+        .route(
+            "/vrp/benchmark/poll/{job_id}",
+            get(vrp::benchmark::poll_benchmark::poll_handler)
+                .layer(from_fn_with_state(state.clone(), job_access_middleware)),
+        )
+        .route(
+            "/vrp/benchmark/stop/{job_id}",
+            post(vrp::benchmark::stop_benchmark::stop_benchmark_handler)
+                .layer(from_fn_with_state(state.clone(), job_access_middleware)),
+        )
+        .with_state(state)
 }
 
 fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
@@ -0,0 +1,10 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+
+use crate::{matrix::matrix_cache::MatrixCacheMetrics, state::AppState};
+
+/// Hit rate and size of the matrix cache shared by the `/vrp` requests
+pub async fn cache_metrics_handler(State(state): State<Arc<AppState>>) -> Json<MatrixCacheMetrics> {
+    Json(state.matrix_cache.metrics())
+}
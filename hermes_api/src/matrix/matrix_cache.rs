@@ -0,0 +1,322 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use hermes_optimizer::problem::location::Location;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Coordinates are rounded to 5 decimals, about a meter, before being used as keys
+const COORDINATES_PRECISION: f64 = 1e5;
+
+pub const DEFAULT_MAX_ENTRIES: usize = 1_000_000;
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+pub struct CachedPoint {
+    lat: i64,
+    lon: i64,
+}
+
+impl CachedPoint {
+    pub fn new(location: &Location) -> Self {
+        CachedPoint {
+            lat: (location.lat() * COORDINATES_PRECISION).round() as i64,
+            lon: (location.lon() * COORDINATES_PRECISION).round() as i64,
+        }
+    }
+}
+
+#[derive(Hash, PartialEq, Eq, Clone)]
+struct EntryKey {
+    profile: Arc<str>,
+    /// `TravelTimeOptions::cache_key` of the vehicle the entry was routed for
+    options: Arc<str>,
+    source: CachedPoint,
+    target: CachedPoint,
+}
+
+/// Travel time (in seconds) and distance (in meters) from a source to a target
+#[derive(Clone, Copy)]
+pub struct CachedEntry {
+    pub time: f64,
+    pub distance: f64,
+}
+
+#[derive(Serialize)]
+pub struct MatrixCacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of the entries found in the cache, 0 when nothing was requested yet
+    pub hit_rate: f64,
+    pub entries: usize,
+    pub max_entries: usize,
+}
+
+#[derive(Default)]
+struct Entries {
+    entries: HashMap<EntryKey, (CachedEntry, u64)>,
+    /// Keys by the tick of their last use, the first one is the least recently used
+    recency: BTreeMap<u64, EntryKey>,
+    tick: u64,
+}
+
+impl Entries {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, key: &EntryKey) -> Option<CachedEntry> {
+        let tick = self.next_tick();
+        let (entry, last_used) = self.entries.get_mut(key)?;
+
+        let key = self
+            .recency
+            .remove(last_used)
+            .expect("every entry has a recency");
+        *last_used = tick;
+        self.recency.insert(tick, key);
+
+        Some(*entry)
+    }
+
+    fn insert(&mut self, key: EntryKey, entry: CachedEntry, max_entries: usize) {
+        let tick = self.next_tick();
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (entry, tick)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(tick, key);
+
+        while self.entries.len() > max_entries {
+            let Some((_, key)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Travel matrix entries of the routing profiles, shared by the `/vrp` requests so that problems
+/// with overlapping locations don't route the same pairs again.
+///
+/// The least recently used entries are evicted once the cache holds `max_entries`.
+pub struct MatrixCache {
+    entries: Mutex<Entries>,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl MatrixCache {
+    pub fn new(max_entries: usize) -> Self {
+        MatrixCache {
+            entries: Mutex::new(Entries::default()),
+            max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cached entries from each source to each target, `None` for the pairs not in the cache
+    pub fn get_block(
+        &self,
+        profile: &Arc<str>,
+        options: &Arc<str>,
+        sources: &[CachedPoint],
+        targets: &[CachedPoint],
+    ) -> Vec<Vec<Option<CachedEntry>>> {
+        let mut entries = self.entries.lock();
+        let mut hits = 0;
+        let mut misses = 0;
+
+        let block = sources
+            .iter()
+            .map(|&source| {
+                targets
+                    .iter()
+                    .map(|&target| {
+                        let entry = entries.get(&EntryKey {
+                            profile: Arc::clone(profile),
+                            options: Arc::clone(options),
+                            source,
+                            target,
+                        });
+
+                        if entry.is_some() {
+                            hits += 1;
+                        } else {
+                            misses += 1;
+                        }
+
+                        entry
+                    })
+                    .collect()
+            })
+            .collect();
+
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+        self.misses.fetch_add(misses, Ordering::Relaxed);
+
+        block
+    }
+
+    pub fn insert(
+        &self,
+        profile: &Arc<str>,
+        options: &Arc<str>,
+        entries: impl IntoIterator<Item = (CachedPoint, CachedPoint, CachedEntry)>,
+    ) {
+        let mut cached_entries = self.entries.lock();
+        for (source, target, entry) in entries {
+            cached_entries.insert(
+                EntryKey {
+                    profile: Arc::clone(profile),
+                    options: Arc::clone(options),
+                    source,
+                    target,
+                },
+                entry,
+                self.max_entries,
+            );
+        }
+    }
+
+    /// Removes the entries of `profile` whatever their options, e.g. when its graph is reloaded
+    pub fn clear_profile(&self, profile: &str) {
+        let mut entries = self.entries.lock();
+        entries.entries.retain(|key, _| &*key.profile != profile);
+        entries.recency.retain(|_, key| &*key.profile != profile);
+    }
+
+    pub fn metrics(&self) -> MatrixCacheMetrics {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+
+        MatrixCacheMetrics {
+            hits,
+            misses,
+            hit_rate: if hits + misses == 0 {
+                0.0
+            } else {
+                hits as f64 / (hits + misses) as f64
+            },
+            entries: self.entries.lock().entries.len(),
+            max_entries: self.max_entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(lat: f64, lon: f64) -> CachedPoint {
+        CachedPoint::new(&Location::from_lat_lon(lat, lon))
+    }
+
+    fn entry(time: f64) -> CachedEntry {
+        CachedEntry {
+            time,
+            distance: time * 10.0,
+        }
+    }
+
+    fn cached_time(
+        cache: &MatrixCache,
+        profile: &Arc<str>,
+        options: &Arc<str>,
+        source: CachedPoint,
+        target: CachedPoint,
+    ) -> Option<f64> {
+        cache.get_block(profile, options, &[source], &[target])[0][0].map(|entry| entry.time)
+    }
+
+    #[test]
+    fn test_cached_point_precision() {
+        assert!(point(50.123451, 4.123451) == point(50.1234512, 4.1234509));
+        assert!(point(50.12345, 4.12345) != point(50.12346, 4.12345));
+    }
+
+    #[test]
+    fn test_get_block() {
+        let cache = MatrixCache::new(DEFAULT_MAX_ENTRIES);
+        let profile: Arc<str> = Arc::from("car");
+        let options: Arc<str> = Arc::from("");
+        let (a, b, c) = (point(50.0, 4.0), point(50.1, 4.1), point(50.2, 4.2));
+
+        cache.insert(
+            &profile,
+            &options,
+            [(a, b, entry(60.0)), (b, a, entry(70.0))],
+        );
+
+        let block = cache.get_block(&profile, &options, &[a, b], &[a, b, c]);
+        let times = block
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|entry| entry.map(|entry| entry.time))
+                    .collect()
+            })
+            .collect::<Vec<Vec<Option<f64>>>>();
+        assert_eq!(
+            times,
+            vec![vec![None, Some(60.0), None], vec![Some(70.0), None, None]]
+        );
+        assert_eq!(block[0][1].map(|entry| entry.distance), Some(600.0));
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(metrics.misses, 4);
+        assert!((metrics.hit_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(metrics.entries, 2);
+    }
+
+    #[test]
+    fn test_least_recently_used_entries_are_evicted() {
+        let cache = MatrixCache::new(2);
+        let profile: Arc<str> = Arc::from("car");
+        let options: Arc<str> = Arc::from("");
+        let (a, b, c) = (point(50.0, 4.0), point(50.1, 4.1), point(50.2, 4.2));
+
+        cache.insert(&profile, &options, [(a, b, entry(1.0))]);
+        cache.insert(&profile, &options, [(a, c, entry(2.0))]);
+
+        // Reading a -> b makes a -> c the least recently used entry
+        assert_eq!(cached_time(&cache, &profile, &options, a, b), Some(1.0));
+        cache.insert(&profile, &options, [(b, c, entry(3.0))]);
+
+        assert_eq!(cached_time(&cache, &profile, &options, a, b), Some(1.0));
+        assert_eq!(cached_time(&cache, &profile, &options, a, c), None);
+        assert_eq!(cached_time(&cache, &profile, &options, b, c), Some(3.0));
+        assert_eq!(cache.metrics().entries, 2);
+    }
+
+    #[test]
+    fn test_entries_by_profile_and_options() {
+        let cache = MatrixCache::new(DEFAULT_MAX_ENTRIES);
+        let (car, truck): (Arc<str>, Arc<str>) = (Arc::from("car"), Arc::from("truck"));
+        let (default, heavy): (Arc<str>, Arc<str>) = (Arc::from(""), Arc::from("weight=40000"));
+        let (a, b) = (point(50.0, 4.0), point(50.1, 4.1));
+
+        cache.insert(&car, &default, [(a, b, entry(1.0))]);
+        cache.insert(&truck, &default, [(a, b, entry(2.0))]);
+        cache.insert(&truck, &heavy, [(a, b, entry(3.0))]);
+
+        assert_eq!(cached_time(&cache, &car, &default, a, b), Some(1.0));
+        assert_eq!(cached_time(&cache, &car, &heavy, a, b), None);
+        assert_eq!(cached_time(&cache, &truck, &default, a, b), Some(2.0));
+        assert_eq!(cached_time(&cache, &truck, &heavy, a, b), Some(3.0));
+
+        cache.clear_profile("truck");
+
+        assert_eq!(cached_time(&cache, &car, &default, a, b), Some(1.0));
+        assert_eq!(cached_time(&cache, &truck, &default, a, b), None);
+        assert_eq!(cached_time(&cache, &truck, &heavy, a, b), None);
+        assert_eq!(cache.metrics().entries, 1);
+    }
+}
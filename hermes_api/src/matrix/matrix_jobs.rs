@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use hermes_routing::matrix::{matrix::Matrix, matrix_request::MatrixMetrics};
+use parking_lot::RwLock;
+use serde::Serialize;
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MatrixJobStatus {
+    Running,
+    Completed,
+    Error { message: String },
+}
+
+/// Rows of the matrix computed together, `times[i][j]` is the time from source `first_source + i`
+/// to target `j`, `None` when the target is unreachable. Only the requested metrics are set.
+#[derive(Clone, Serialize)]
+pub struct MatrixBlock {
+    first_source: usize,
+    rows: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    times: Option<Vec<Vec<Option<u32>>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distances: Option<Vec<Vec<Option<f64>>>>,
+}
+
+fn block_values<T>(
+    rows: usize,
+    targets: usize,
+    value: impl Fn(usize, usize) -> Option<T>,
+) -> Vec<Vec<Option<T>>> {
+    (0..rows)
+        .map(|source| (0..targets).map(|target| value(source, target)).collect())
+        .collect()
+}
+
+impl MatrixBlock {
+    pub fn new(
+        first_source: usize,
+        rows: usize,
+        targets: usize,
+        matrix: &Matrix,
+        metrics: MatrixMetrics,
+    ) -> Self {
+        MatrixBlock {
+            first_source,
+            rows,
+            times: metrics.includes_durations().then(|| {
+                block_values(rows, targets, |source, target| {
+                    matrix.entry(source, target).map(|entry| entry.time())
+                })
+            }),
+            distances: metrics.includes_distances().then(|| {
+                block_values(rows, targets, |source, target| {
+                    matrix
+                        .entry(source, target)
+                        .map(|entry| entry.distance().value())
+                })
+            }),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+}
+
+/// Time a completed or failed matrix job can still be polled
+const MATRIX_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Matrix computed in the background, its blocks are kept in the order they were completed
+pub struct MatrixJob {
+    sources: usize,
+    status: RwLock<MatrixJobStatus>,
+    blocks: RwLock<Vec<MatrixBlock>>,
+    finished_at: RwLock<Option<Instant>>,
+}
+
+impl MatrixJob {
+    pub fn new(sources: usize) -> Self {
+        MatrixJob {
+            sources,
+            status: RwLock::new(MatrixJobStatus::Running),
+            blocks: RwLock::new(Vec::new()),
+            finished_at: RwLock::new(None),
+        }
+    }
+
+    pub fn sources(&self) -> usize {
+        self.sources
+    }
+
+    pub fn status(&self) -> MatrixJobStatus {
+        self.status.read().clone()
+    }
+
+    pub fn complete(&self) {
+        *self.status.write() = MatrixJobStatus::Completed;
+        *self.finished_at.write() = Some(Instant::now());
+    }
+
+    pub fn fail(&self, message: String) {
+        *self.status.write() = MatrixJobStatus::Error { message };
+        *self.finished_at.write() = Some(Instant::now());
+    }
+
+    /// Whether the job finished more than `ttl` ago, the running jobs never expire
+    fn is_expired(&self, ttl: Duration) -> bool {
+        self.finished_at
+            .read()
+            .is_some_and(|finished_at| finished_at.elapsed() >= ttl)
+    }
+
+    pub fn add_block(&self, block: MatrixBlock) {
+        self.blocks.write().push(block);
+    }
+
+    /// Blocks completed since `from_block`, so that pollers only receive the new rows
+    pub fn blocks_from(&self, from_block: usize) -> Vec<MatrixBlock> {
+        self.blocks
+            .read()
+            .get(from_block..)
+            .map(<[MatrixBlock]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    pub fn completed_rows(&self) -> usize {
+        self.blocks.read().iter().map(MatrixBlock::rows).sum()
+    }
+
+    /// Percentage of the rows of the matrix already computed
+    pub fn progress(&self) -> f64 {
+        if self.sources == 0 {
+            return 100.0;
+        }
+
+        self.completed_rows() as f64 / self.sources as f64 * 100.0
+    }
+}
+
+/// Matrix jobs by id, the jobs are removed `ttl` after they finished
+pub struct MatrixJobs {
+    ttl: Duration,
+    jobs: RwLock<HashMap<String, Arc<MatrixJob>>>,
+}
+
+impl Default for MatrixJobs {
+    fn default() -> Self {
+        MatrixJobs::new(MATRIX_JOB_TTL)
+    }
+}
+
+impl MatrixJobs {
+    pub fn new(ttl: Duration) -> Self {
+        MatrixJobs {
+            ttl,
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Also removes the expired jobs
+    pub fn insert(&self, job_id: String, job: Arc<MatrixJob>) {
+        let mut jobs = self.jobs.write();
+        jobs.retain(|_, job| !job.is_expired(self.ttl));
+        jobs.insert(job_id, job);
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<Arc<MatrixJob>> {
+        let job = self.jobs.read().get(job_id).cloned()?;
+        if job.is_expired(self.ttl) {
+            self.jobs.write().remove(job_id);
+            return None;
+        }
+
+        Some(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_jobs_never_expire() {
+        let jobs = MatrixJobs::new(Duration::ZERO);
+        jobs.insert(String::from("running"), Arc::new(MatrixJob::new(10)));
+
+        assert!(jobs.get("running").is_some());
+    }
+
+    #[test]
+    fn test_finished_jobs_expire() {
+        let jobs = MatrixJobs::new(Duration::ZERO);
+
+        let completed = Arc::new(MatrixJob::new(10));
+        jobs.insert(String::from("completed"), Arc::clone(&completed));
+        let failed = Arc::new(MatrixJob::new(10));
+        jobs.insert(String::from("failed"), Arc::clone(&failed));
+
+        completed.complete();
+        failed.fail(String::from("unreachable"));
+
+        assert!(jobs.get("completed").is_none());
+        assert_eq!(jobs.jobs.read().len(), 1);
+
+        // The other expired jobs are removed on the next insertion
+        jobs.insert(String::from("running"), Arc::new(MatrixJob::new(10)));
+        assert_eq!(jobs.jobs.read().len(), 1);
+        assert!(jobs.get("running").is_some());
+    }
+
+    #[test]
+    fn test_finished_jobs_are_kept_until_their_ttl() {
+        let jobs = MatrixJobs::new(Duration::from_secs(60));
+
+        let job = Arc::new(MatrixJob::new(10));
+        jobs.insert(String::from("completed"), Arc::clone(&job));
+        job.complete();
+
+        assert!(jobs.get("completed").is_some());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod cache_metrics_handler;
+pub mod matrix_cache;
+pub mod matrix_jobs;
+pub mod poll_job_handler;
+pub mod post_job_handler;
+pub mod upload_handler;
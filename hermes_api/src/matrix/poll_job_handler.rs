@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApiError,
+    matrix::matrix_jobs::{MatrixBlock, MatrixJobStatus},
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct PollMatrixJobQuery {
+    /// Only return the blocks completed from this one, usually the `next_block` of the previous poll
+    #[serde(default)]
+    from_block: usize,
+}
+
+#[derive(Serialize)]
+pub struct PollMatrixJobResponse {
+    #[serde(flatten)]
+    status: MatrixJobStatus,
+    /// Percentage of the rows already computed
+    progress: f64,
+    completed_rows: usize,
+    total_rows: usize,
+    blocks: Vec<MatrixBlock>,
+    next_block: usize,
+}
+
+pub async fn poll_job_handler(
+    Path(job_id): Path<String>,
+    Query(query): Query<PollMatrixJobQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PollMatrixJobResponse>, ApiError> {
+    let job = state
+        .matrix_jobs
+        .get(&job_id)
+        .ok_or(ApiError::NotFound(job_id))?;
+
+    // The status is read first, a completed job then always returns its last blocks
+    let status = job.status();
+    let blocks = job.blocks_from(query.from_block);
+
+    Ok(Json(PollMatrixJobResponse {
+        status,
+        progress: job.progress(),
+        completed_rows: job.completed_rows(),
+        total_rows: job.sources(),
+        next_block: query.from_block + blocks.len(),
+        blocks,
+    }))
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use hermes_routing::{
+    avoid::AvoidOptions,
+    geometry_encoding::{EncodedGeometry, GeometryEncoding},
+    matrix::matrix_request::{MatrixMetrics, MatrixRequest, MatrixRequestOptions},
+    snap::{SnapOptions, SnapReport},
+    weighting::VehicleDimensions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    matrix::matrix_jobs::{MatrixBlock, MatrixJob},
+    route::route_handler::{AvoidBody, GeoPointBody, SnapBody, SnapReportResponse},
+    slo::slo_ticket::SloTicket,
+    state::AppState,
+};
+
+const DEFAULT_BLOCK_SIZE: usize = 100;
+
+#[derive(Deserialize)]
+pub struct PostMatrixJobBody {
+    sources: Vec<GeoPointBody>,
+    /// Defaults to the sources
+    targets: Option<Vec<GeoPointBody>>,
+    /// Number of sources computed together, the rows of a block are available as soon as it is
+    /// computed
+    block_size: Option<usize>,
+    /// Only computing the durations or the distances of the entries is faster, defaults to both
+    metrics: Option<MatrixMetrics>,
+    /// Matrix of a truck of these dimensions, see the route request. Slower when a dimension is
+    /// set, the contraction hierarchies of trucks are customized for unknown dimensions
+    truck: Option<VehicleDimensions>,
+    /// Returns the points of the roads the sources and targets are snapped to, in this encoding
+    snapped_points: Option<GeometryEncoding>,
+    /// Parts of the road network the paths must not use, see the route request. Much slower, the
+    /// matrix is computed without the contraction hierarchies
+    avoid: Option<AvoidBody>,
+    /// Restricts the roads the sources and targets are snapped to, the job fails when a point
+    /// can't be snapped
+    snap: Option<SnapBody>,
+    /// Returns the roads the sources and targets are snapped to, to diagnose the points snapped
+    /// to the wrong road
+    include_snap_reports: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct PostMatrixJobResponse {
+    job_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapped_sources: Option<EncodedGeometry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapped_targets: Option<EncodedGeometry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snap_reports: Option<SnapReportsResponse>,
+}
+
+/// The report of a point is null when it can't be snapped
+#[derive(Serialize)]
+pub struct SnapReportsResponse {
+    sources: Vec<Option<SnapReportResponse>>,
+    targets: Vec<Option<SnapReportResponse>>,
+}
+
+impl SnapReportsResponse {
+    fn new(sources: Vec<Option<SnapReport>>, targets: Vec<Option<SnapReport>>) -> Self {
+        let responses = |reports: Vec<Option<SnapReport>>| {
+            reports
+                .into_iter()
+                .map(|report| report.map(SnapReportResponse::from))
+                .collect()
+        };
+
+        SnapReportsResponse {
+            sources: responses(sources),
+            targets: responses(targets),
+        }
+    }
+}
+
+/// Starts computing a matrix in the background, poll the job to get the rows already computed.
+/// The job counts towards the in-flight matrix requests until the matrix is computed
+pub async fn post_job_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(ticket): Extension<SloTicket>,
+    Json(body): Json<PostMatrixJobBody>,
+) -> Result<Json<PostMatrixJobResponse>, ApiError> {
+    state.check_accepting_jobs()?;
+
+    let hermes = state
+        .profiles
+        .get("car")
+        .ok_or_else(|| ApiError::NotFound(String::from("Profile car not found")))?;
+
+    let block_size = body.block_size.unwrap_or(DEFAULT_BLOCK_SIZE);
+    if block_size == 0 {
+        return Err(ApiError::BadRequest(String::from(
+            "block_size must be positive",
+        )));
+    }
+
+    let avoid = body.avoid.map(AvoidOptions::try_from).transpose()?;
+    let snap = body.snap.map(SnapOptions::try_from).transpose()?;
+
+    let sources: Vec<_> = body.sources.into_iter().map(Into::into).collect();
+    let targets = match body.targets {
+        Some(targets) => targets.into_iter().map(Into::into).collect(),
+        None => sources.clone(),
+    };
+    let target_count = targets.len();
+    let source_count = sources.len();
+    state.check_matrix_size(source_count, target_count)?;
+
+    let job_id = Uuid::new_v4().to_string();
+    let job = Arc::new(MatrixJob::new(source_count));
+    state.matrix_jobs.insert(job_id.clone(), Arc::clone(&job));
+
+    let request = MatrixRequest {
+        sources,
+        targets,
+        profile: String::from(if body.truck.is_some() { "truck" } else { "car" }),
+        vehicle_dimensions: body.truck,
+        options: Some(MatrixRequestOptions {
+            include_debug_info: None,
+            metrics: body.metrics,
+            snapped_points_encoding: body.snapped_points,
+            avoid,
+            snap,
+            include_snap_reports: body.include_snap_reports,
+        }),
+    };
+    let metrics = request.metrics();
+    let (snapped_sources, snapped_targets) = hermes.snapped_points(&request).unzip();
+    let snap_reports = hermes
+        .snap_reports(&request)
+        .map(|(sources, targets)| SnapReportsResponse::new(sources, targets));
+
+    tokio::task::spawn_blocking(move || {
+        let _ticket = ticket;
+        let result = hermes.matrix_in_blocks(request, block_size, |block| {
+            job.add_block(MatrixBlock::new(
+                block.first_source,
+                block.result.matrix.sources(),
+                target_count,
+                &block.result.matrix,
+                metrics,
+            ));
+        });
+
+        match result {
+            Ok(()) => job.complete(),
+            Err(error) => {
+                warn!("Matrix job failed: {error}");
+                job.fail(error);
+            }
+        }
+    });
+
+    Ok(Json(PostMatrixJobResponse {
+        job_id,
+        snapped_sources,
+        snapped_targets,
+        snap_reports,
+    }))
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use hermes_matrix_providers::{
+    matrix_store::MatrixStoreError, travel_matrix_provider::CustomMatrices,
+};
+use serde::Serialize;
+
+use crate::{error::ApiError, state::AppState, versioning::versioned_json::VersionedJson};
+
+#[derive(Serialize)]
+pub struct UploadResponse {
+    matrix_id: String,
+}
+
+/// Stores a master matrix which problems can reference with the `uploaded` travel matrix provider
+pub async fn upload_handler(
+    State(state): State<Arc<AppState>>,
+    VersionedJson(matrices): VersionedJson<CustomMatrices>,
+) -> Result<Json<UploadResponse>, ApiError> {
+    let matrix_id = state
+        .matrix_client
+        .matrix_store()
+        .upload(matrices)
+        .map_err(|error| match error {
+            MatrixStoreError::StoreFull(_) => ApiError::ServiceUnavailable(error.to_string()),
+            _ => ApiError::BadRequest(error.to_string()),
+        })?;
+
+    Ok(Json(UploadResponse { matrix_id }))
+}
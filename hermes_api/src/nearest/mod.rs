@@ -0,0 +1 @@
+pub mod nearest_handler;
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use hermes_geo::GeoPoint;
+use hermes_routing::{snap::SnapOptions, weighting::VehicleDimensions};
+use serde::Deserialize;
+
+use crate::{
+    error::ApiError,
+    route::route_handler::{GeoPointBody, SnapBody, SnapReportResponse},
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct NearestRequestBody {
+    point: GeoPointBody,
+    /// Only snaps to the roads accessible to a truck of these dimensions, see the route request
+    truck: Option<VehicleDimensions>,
+    #[serde(flatten)]
+    snap: SnapBody,
+}
+
+/// Road the point is snapped to by the route and matrix requests
+pub async fn nearest_handler(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<NearestRequestBody>,
+) -> Result<Json<SnapReportResponse>, ApiError> {
+    let hermes = state
+        .profiles
+        .get("car")
+        .ok_or_else(|| ApiError::NotFound(String::from("Profile car not found")))?;
+
+    let options = SnapOptions::try_from(body.snap)?;
+    let point = GeoPoint::from(body.point);
+    let profile = if body.truck.is_some() { "truck" } else { "car" };
+
+    hermes
+        .nearest(&point, profile, body.truck, &options)
+        .map_err(ApiError::BadRequest)?
+        .map(|report| Json(report.into()))
+        .ok_or_else(|| ApiError::NotFound(String::from("No road found near the point")))
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use hermes_optimizer::problem::{
+    location::Location,
+    travel_time_provider::{
+        TravelMatrixBlock, TravelMatrixBlockFuture, TravelTimeOptions, TravelTimeProvider,
+    },
+};
+
+use crate::matrix::matrix_cache::{CachedEntry, CachedPoint, MatrixCache};
+
+/// Looks the entries of a block up in the matrix cache, only the sources with missing entries are
+/// fetched from the provider
+pub struct CachedTravelTimeProvider {
+    profile: Arc<str>,
+    provider: Arc<dyn TravelTimeProvider>,
+    cache: Arc<MatrixCache>,
+}
+
+impl CachedTravelTimeProvider {
+    pub fn new(
+        profile: &str,
+        provider: Arc<dyn TravelTimeProvider>,
+        cache: Arc<MatrixCache>,
+    ) -> Self {
+        CachedTravelTimeProvider {
+            profile: Arc::from(profile),
+            provider,
+            cache,
+        }
+    }
+}
+
+impl TravelTimeProvider for CachedTravelTimeProvider {
+    fn max_locations_per_request(&self) -> usize {
+        self.provider.max_locations_per_request()
+    }
+
+    fn max_concurrent_requests(&self) -> usize {
+        self.provider.max_concurrent_requests()
+    }
+
+    fn fetch_block<'a>(
+        &'a self,
+        sources: &'a [Location],
+        targets: &'a [Location],
+        options: &'a TravelTimeOptions,
+    ) -> TravelMatrixBlockFuture<'a> {
+        Box::pin(async move {
+            let options_key: Arc<str> = Arc::from(options.cache_key());
+            let source_points = sources.iter().map(CachedPoint::new).collect::<Vec<_>>();
+            let target_points = targets.iter().map(CachedPoint::new).collect::<Vec<_>>();
+
+            let mut block =
+                self.cache
+                    .get_block(&self.profile, &options_key, &source_points, &target_points);
+
+            let missing_sources = (0..sources.len())
+                .filter(|&source| block[source].iter().any(Option::is_none))
+                .collect::<Vec<_>>();
+
+            if !missing_sources.is_empty() {
+                let locations = missing_sources
+                    .iter()
+                    .map(|&source| {
+                        Location::from_lat_lon(sources[source].lat(), sources[source].lon())
+                    })
+                    .collect::<Vec<_>>();
+                let fetched = self
+                    .provider
+                    .fetch_block(&locations, targets, options)
+                    .await?;
+
+                if fetched.times.len() != missing_sources.len()
+                    || fetched.distances.len() != missing_sources.len()
+                    || fetched
+                        .times
+                        .iter()
+                        .chain(&fetched.distances)
+                        .any(|row| row.len() != targets.len())
+                {
+                    return Err(anyhow::anyhow!(
+                        "Expected a {}x{} block from the travel time provider",
+                        missing_sources.len(),
+                        targets.len()
+                    ));
+                }
+
+                let mut fetched_entries = Vec::with_capacity(missing_sources.len() * targets.len());
+                for (row, &source) in missing_sources.iter().enumerate() {
+                    for (target, &target_point) in target_points.iter().enumerate() {
+                        let entry = CachedEntry {
+                            time: fetched.times[row][target],
+                            distance: fetched.distances[row][target],
+                        };
+
+                        block[source][target] = Some(entry);
+                        fetched_entries.push((source_points[source], target_point, entry));
+                    }
+                }
+
+                self.cache
+                    .insert(&self.profile, &options_key, fetched_entries);
+            }
+
+            let entries = block
+                .into_iter()
+                .map(|row| row.into_iter().flatten().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+
+            Ok(TravelMatrixBlock {
+                times: entries
+                    .iter()
+                    .map(|row| row.iter().map(|entry| entry.time).collect())
+                    .collect(),
+                distances: entries
+                    .iter()
+                    .map(|row| row.iter().map(|entry| entry.distance).collect())
+                    .collect(),
+            })
+        })
+    }
+}
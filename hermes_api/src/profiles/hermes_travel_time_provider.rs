@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use hermes_geo::GeoPoint;
+use hermes_optimizer::problem::{
+    location::Location,
+    travel_time_provider::{
+        TravelMatrixBlock, TravelMatrixBlockFuture, TravelTimeOptions, TravelTimeProvider,
+    },
+};
+use hermes_routing::{
+    hermes::Hermes, matrix::matrix_request::MatrixRequest, weighting::VehicleDimensions,
+};
+
+const MAX_LOCATIONS_PER_REQUEST: usize = 1000;
+
+/// Routing profile of the vehicle profiles that don't set one
+const DEFAULT_PROFILE: &str = "car";
+
+/// Computes the travel times of the problems with the graph of a loaded profile
+pub struct HermesTravelTimeProvider {
+    hermes: Arc<Hermes>,
+}
+
+impl HermesTravelTimeProvider {
+    pub fn new(hermes: Arc<Hermes>) -> Self {
+        HermesTravelTimeProvider { hermes }
+    }
+}
+
+fn geo_points(locations: &[Location]) -> Vec<GeoPoint> {
+    locations.iter().map(Location::geo_point).collect()
+}
+
+fn block_rows(
+    sources: usize,
+    targets: usize,
+    value: impl Fn(usize, usize) -> Option<f64>,
+) -> anyhow::Result<Vec<Vec<f64>>> {
+    (0..sources)
+        .map(|source| {
+            (0..targets)
+                .map(|target| {
+                    value(source, target).ok_or_else(|| {
+                        anyhow::anyhow!("Target {target} is unreachable from source {source}")
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl TravelTimeProvider for HermesTravelTimeProvider {
+    fn max_locations_per_request(&self) -> usize {
+        MAX_LOCATIONS_PER_REQUEST
+    }
+
+    /// The blocks are computed on the blocking threads, one per core
+    fn max_concurrent_requests(&self) -> usize {
+        std::thread::available_parallelism().map_or(1, |cores| cores.get())
+    }
+
+    fn fetch_block<'a>(
+        &'a self,
+        sources: &'a [Location],
+        targets: &'a [Location],
+        options: &'a TravelTimeOptions,
+    ) -> TravelMatrixBlockFuture<'a> {
+        let hermes = Arc::clone(&self.hermes);
+        let request = MatrixRequest {
+            sources: geo_points(sources),
+            targets: geo_points(targets),
+            profile: options
+                .profile
+                .clone()
+                .unwrap_or_else(|| String::from(DEFAULT_PROFILE)),
+            vehicle_dimensions: options
+                .vehicle_dimensions
+                .map(|dimensions| VehicleDimensions {
+                    height: dimensions.height.map(|height| height as f32),
+                    width: dimensions.width.map(|width| width as f32),
+                    weight: dimensions.weight.map(|weight| weight as f32),
+                }),
+            options: None,
+        };
+
+        Box::pin(async move {
+            // A single row or column, e.g. a location missing from the matrix cache, only needs
+            // one search from the single location
+            let result = tokio::task::spawn_blocking(move || {
+                if request.sources.len() == 1 {
+                    hermes.one_to_many(&request)
+                } else if request.targets.len() == 1 {
+                    hermes.many_to_one(&request)
+                } else {
+                    hermes.matrix(request)
+                }
+            })
+            .await?
+            .map_err(anyhow::Error::msg)?;
+            let matrix = &result.matrix;
+
+            Ok(TravelMatrixBlock {
+                // The router gives milliseconds, the matrices are in seconds
+                times: block_rows(sources.len(), targets.len(), |source, target| {
+                    matrix
+                        .entry(source, target)
+                        .map(|entry| entry.time() as f64 / 1000.0)
+                })?,
+                distances: block_rows(sources.len(), targets.len(), |source, target| {
+                    matrix
+                        .entry(source, target)
+                        .map(|entry| entry.distance().value())
+                })?,
+            })
+        })
+    }
+}
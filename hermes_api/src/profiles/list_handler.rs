@@ -0,0 +1,9 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+
+use crate::{profiles::profile_registry::ProfileInfo, state::AppState};
+
+pub async fn list_handler(State(state): State<Arc<AppState>>) -> Json<Vec<ProfileInfo>> {
+    Json(state.profiles.list())
+}
@@ -0,0 +1,5 @@
+pub mod cached_travel_time_provider;
+pub mod hermes_travel_time_provider;
+pub mod list_handler;
+pub mod profile_registry;
+pub mod reload_handler;
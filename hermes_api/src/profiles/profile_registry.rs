@@ -0,0 +1,144 @@
+use std::{collections::HashMap, sync::Arc};
+
+use hermes_optimizer::problem::travel_time_provider::TravelTimeProviders;
+use hermes_routing::{error::StorageError, graph::Graph, hermes::Hermes};
+use jiff::Timestamp;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::{
+    matrix::matrix_cache::MatrixCache,
+    profiles::{
+        cached_travel_time_provider::CachedTravelTimeProvider,
+        hermes_travel_time_provider::HermesTravelTimeProvider,
+    },
+};
+
+struct Profile {
+    hermes: Arc<Hermes>,
+    data_dir: String,
+    loaded_at: Timestamp,
+}
+
+#[derive(Serialize)]
+pub struct ProfileInfo {
+    name: String,
+    data_dir: String,
+    loaded_at: Timestamp,
+    node_count: usize,
+    edge_count: usize,
+}
+
+impl ProfileInfo {
+    fn new(name: &str, profile: &Profile) -> Self {
+        ProfileInfo {
+            name: name.to_owned(),
+            data_dir: profile.data_dir.clone(),
+            loaded_at: profile.loaded_at,
+            node_count: profile.hermes.graph().node_count(),
+            edge_count: profile.hermes.graph().edge_count(),
+        }
+    }
+}
+
+/// Routing profiles loaded by the API.
+///
+/// Queries hold an `Arc` to the graph data of their profile, reloading a profile swaps the `Arc` so
+/// that in-flight queries finish on the previous data while new queries use the reloaded one.
+#[derive(Default)]
+pub struct ProfileRegistry {
+    profiles: RwLock<HashMap<String, Profile>>,
+}
+
+impl ProfileRegistry {
+    pub fn load(&self, name: &str, data_dir: &str) -> Result<(), StorageError> {
+        let profile = Profile {
+            hermes: Arc::new(Hermes::from_directory(data_dir)?),
+            data_dir: data_dir.to_owned(),
+            loaded_at: Timestamp::now(),
+        };
+
+        self.profiles.write().insert(name.to_owned(), profile);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Hermes>> {
+        self.profiles
+            .read()
+            .get(name)
+            .map(|profile| Arc::clone(&profile.hermes))
+    }
+
+    /// Each profile is a travel time provider of the same name, with its currently loaded graph,
+    /// the entries already in `cache` are not routed again
+    pub fn travel_time_providers(&self, cache: &Arc<MatrixCache>) -> TravelTimeProviders {
+        let mut providers = TravelTimeProviders::default();
+        for (name, profile) in self.profiles.read().iter() {
+            providers.register(
+                name.clone(),
+                Arc::new(CachedTravelTimeProvider::new(
+                    name,
+                    Arc::new(HermesTravelTimeProvider::new(Arc::clone(&profile.hermes))),
+                    Arc::clone(cache),
+                )),
+            );
+        }
+
+        providers
+    }
+
+    pub fn list(&self) -> Vec<ProfileInfo> {
+        let mut profiles = self
+            .profiles
+            .read()
+            .iter()
+            .map(|(name, profile)| ProfileInfo::new(name, profile))
+            .collect::<Vec<_>>();
+
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.profiles.read().keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Reloads the graph data of the profile from its directory, returns `None` if the profile doesn't exist
+    pub async fn reload(&self, name: &str) -> Option<anyhow::Result<ProfileInfo>> {
+        let data_dir = self.profiles.read().get(name)?.data_dir.clone();
+
+        // Loading the graph is blocking and can take a while on large regions
+        let hermes = tokio::task::spawn_blocking({
+            let data_dir = data_dir.clone();
+            move || Hermes::from_directory(&data_dir)
+        })
+        .await;
+
+        let hermes = match hermes {
+            Ok(Ok(hermes)) => hermes,
+            Ok(Err(error)) => {
+                return Some(Err(anyhow::anyhow!(
+                    "Failed to load profile {name} from {data_dir}: {error}"
+                )));
+            }
+            Err(error) => {
+                return Some(Err(anyhow::anyhow!(
+                    "Failed to load profile {name} from {data_dir}: {error}"
+                )));
+            }
+        };
+
+        let profile = Profile {
+            hermes: Arc::new(hermes),
+            data_dir,
+            loaded_at: Timestamp::now(),
+        };
+        let info = ProfileInfo::new(name, &profile);
+
+        self.profiles.write().insert(name.to_owned(), profile);
+
+        Some(Ok(info))
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{error::ApiError, profiles::profile_registry::ProfileInfo, state::AppState};
+
+/// Reloads the graph data of a profile without restarting the API, queries already running keep
+/// using the previous data
+pub async fn reload_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<ProfileInfo>, ApiError> {
+    match state.profiles.reload(&name).await {
+        Some(info) => {
+            let info = info?;
+            // The cached entries were routed on the previous graph
+            state.matrix_cache.clear_profile(&name);
+            Ok(Json(info))
+        }
+        None => Err(ApiError::NotFound(format!("Profile {name} not found"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        middleware::from_fn_with_state,
+        routing::post,
+    };
+    use hermes_matrix_providers::{cache::FileCache, travel_matrix_client::TravelMatrixClient};
+    use hermes_osrm::client::{OsrmClient, OsrmClientParams};
+    use tower::ServiceExt;
+
+    use crate::{
+        auth::{
+            api_keys::{ApiKeys, parse_keys},
+            auth_middleware::{admin_middleware, auth_middleware},
+        },
+        matrix::matrix_cache::{DEFAULT_MAX_ENTRIES, MatrixCache},
+        vrp::job_store::JobStore,
+    };
+
+    use super::*;
+
+    fn test_state() -> Arc<AppState> {
+        let directory = std::env::temp_dir().join("hermes_reload_handler");
+        std::fs::create_dir_all(&directory).unwrap();
+
+        Arc::new(AppState {
+            api_keys: ApiKeys::from_configs(parse_keys("ops:secret:admin,team:key").unwrap())
+                .unwrap(),
+            profiles: Default::default(),
+            solver_manager: Default::default(),
+            job_inputs: Default::default(),
+            jobs: JobStore::open(directory.join("jobs")).unwrap(),
+            matrix_client: TravelMatrixClient::new(FileCache::new(directory.to_str().unwrap())),
+            osrm_client: OsrmClient::new(OsrmClientParams {
+                osrm_url: String::from("http://localhost:5000"),
+            }),
+            matrix_jobs: Default::default(),
+            matrix_cache: Arc::new(MatrixCache::new(DEFAULT_MAX_ENTRIES)),
+            service_durations: Default::default(),
+            location_areas: Default::default(),
+            plans: Default::default(),
+            max_problem_locations: None,
+            shutdown: tokio::sync::watch::Sender::new(false),
+        })
+    }
+
+    async fn reload(api_key: Option<&'static str>) -> StatusCode {
+        let state = test_state();
+        let router = Router::new()
+            .route(
+                "/profiles/{name}/reload",
+                post(reload_handler).layer(from_fn_with_state(state.clone(), admin_middleware)),
+            )
+            .layer(from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state);
+
+        let mut request = Request::post("/profiles/car/reload");
+        if let Some(api_key) = api_key {
+            request = request.header("x-api-key", api_key);
+        }
+
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_reload_requires_an_admin_key() {
+        assert_eq!(reload(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(reload(Some("key")).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_reload_unknown_profile() {
+        assert_eq!(reload(Some("secret")).await, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,16 +1,23 @@
 use crate::error::ApiError;
+use crate::slo::slo_ticket::SloTicket;
 use crate::state::AppState;
-use axum::Json;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
 use geojson::Value::{LineString, MultiPoint};
 use geojson::feature::Id;
 use geojson::{Feature, FeatureCollection, GeoJson, Geometry, JsonValue};
-use hermes_routing::geopoint::GeoPoint;
+use hermes_geo::GeoPoint;
+use hermes_routing::avoid::{AvoidOptions, RoadClass};
+use hermes_routing::distance::{Distance, Meters};
+use hermes_routing::geometry_encoding::{EncodedGeometry, GeometryEncoding};
+use hermes_routing::region::to_multi_polygon;
 use hermes_routing::routing::routing_request::{
     RoutingAlgorithm, RoutingRequest, RoutingRequestOptions,
 };
+use hermes_routing::snap::{SnapOptions, SnapReport};
+use hermes_routing::weighting::VehicleDimensions;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -35,43 +42,137 @@ impl From<GeoPointBody> for GeoPoint {
     }
 }
 
+/// Parts of the road network the paths must not use
+#[derive(Deserialize)]
+pub struct AvoidBody {
+    /// GeoJSON Polygon or MultiPolygon, the roads crossing it are avoided
+    areas: Option<Geometry>,
+    #[serde(default)]
+    osm_way_ids: Vec<usize>,
+    #[serde(default)]
+    road_classes: Vec<RoadClass>,
+}
+
+impl TryFrom<AvoidBody> for AvoidOptions {
+    type Error = ApiError;
+
+    fn try_from(value: AvoidBody) -> Result<Self, Self::Error> {
+        let areas = match value.areas {
+            Some(areas) => to_multi_polygon(areas)
+                .map_err(|error| ApiError::BadRequest(format!("avoid.areas: {error}")))?,
+            None => geo::MultiPolygon::new(vec![]),
+        };
+
+        Ok(AvoidOptions {
+            areas,
+            osm_way_ids: value.osm_way_ids,
+            road_classes: value.road_classes,
+        })
+    }
+}
+
+/// Restricts the roads the points are snapped to
+#[derive(Deserialize)]
+pub struct SnapBody {
+    /// In meters, the points further from any road are rejected
+    max_snap_radius: Option<f64>,
+    #[serde(default)]
+    excluded_road_classes: Vec<RoadClass>,
+}
+
+impl TryFrom<SnapBody> for SnapOptions {
+    type Error = ApiError;
+
+    fn try_from(value: SnapBody) -> Result<Self, Self::Error> {
+        if value
+            .max_snap_radius
+            .is_some_and(|radius| !radius.is_finite() || radius <= 0.0)
+        {
+            return Err(ApiError::BadRequest(String::from(
+                "max_snap_radius must be positive",
+            )));
+        }
+
+        Ok(SnapOptions {
+            max_radius: value.max_snap_radius.map(Distance::<Meters>::from),
+            excluded_road_classes: value.excluded_road_classes,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct SnapReportResponse {
+    lat: f64,
+    lon: f64,
+    /// In meters, from the requested point
+    distance: f64,
+    street_name: Option<String>,
+    osm_way_id: Option<usize>,
+    /// The requested point is beyond an end of the road
+    clamped: bool,
+}
+
+impl From<SnapReport> for SnapReportResponse {
+    fn from(value: SnapReport) -> Self {
+        SnapReportResponse {
+            lat: value.coordinates.lat(),
+            lon: value.coordinates.lon(),
+            distance: value.distance.value(),
+            street_name: value.street_name,
+            osm_way_id: value.osm_way_id,
+            clamped: value.clamped,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct RouteRequestBody {
     start: GeoPointBody,
     end: GeoPointBody,
     include_debug_info: Option<bool>,
     algorithm: Option<RoutingAlgorithm>,
+    /// Routes a truck of these dimensions, the ways forbidden to heavy goods vehicles or too low,
+    /// narrow or weak for the truck are avoided
+    truck: Option<VehicleDimensions>,
+    /// Adds the turn-by-turn instructions of the path to the properties of the route
+    include_instructions: Option<bool>,
+    /// Encoding of the geometry of the route, the polylines and the binary encoding are set in the
+    /// `geometry` property instead of the GeoJSON geometry. Defaults to the coordinates
+    geometry_encoding: Option<GeometryEncoding>,
+    /// Slower, the route is computed without the contraction hierarchies
+    avoid: Option<AvoidBody>,
 }
 
 pub async fn route_handler(
     State(state): State<Arc<AppState>>,
+    Extension(ticket): Extension<SloTicket>,
     Json(body): Json<RouteRequestBody>,
 ) -> Result<RouteResponse, ApiError> {
-    let result = state.hermes.route(RoutingRequest {
+    let hermes = state
+        .profiles
+        .get("car")
+        .ok_or_else(|| ApiError::NotFound(String::from("Profile car not found")))?;
+
+    let avoid = body.avoid.map(AvoidOptions::try_from).transpose()?;
+    let request = RoutingRequest {
         start: body.start.into(),
         end: body.end.into(),
-        profile: String::from("car"),
+        profile: String::from(if body.truck.is_some() { "truck" } else { "car" }),
+        vehicle_dimensions: body.truck,
         options: Some(RoutingRequestOptions {
             algorithm: body.algorithm,
             include_debug_info: body.include_debug_info,
+            avoid,
         }),
-    });
+    };
+
+    // Off the async runtime so that the endpoint timeout can fire while the query is running
+    let result = ticket.spawn_blocking(move || hermes.route(request)).await?;
 
     result
         .map(|result| {
             let mut features: Vec<Feature> = vec![];
 
-            let points: Vec<Vec<f64>> = result
-                .path
-                .legs()
-                .iter()
-                .flat_map(|leg| {
-                    leg.points()
-                        .iter()
-                        .map(|point| vec![point.lon(), point.lat()])
-                })
-                .collect();
-
             let mut properties = serde_json::Map::new();
             properties.insert(String::from("id"), JsonValue::from(String::from("route")));
 
@@ -87,10 +188,34 @@ pub async fn route_handler(
                 JsonValue::from(result.duration.as_millis() as u64),
             );
 
+            if body.include_instructions.unwrap_or(false)
+                && let Ok(instructions) = serde_json::to_value(result.path.instructions())
+            {
+                properties.insert(String::from("instructions"), instructions);
+            }
+
+            let geometry = match body
+                .geometry_encoding
+                .unwrap_or_default()
+                .encode(&result.path.points())
+            {
+                EncodedGeometry::Coordinates(coordinates) => Some(Geometry::new(LineString(
+                    coordinates.into_iter().map(Vec::from).collect(),
+                ))),
+                EncodedGeometry::Encoded(encoded) => {
+                    properties.insert(String::from("geometry"), JsonValue::from(encoded));
+                    properties.insert(
+                        String::from("geometry_encoding"),
+                        serde_json::to_value(body.geometry_encoding).unwrap_or_default(),
+                    );
+                    None
+                }
+            };
+
             let feature = Feature {
                 properties: Some(properties),
                 id: Some(Id::String(String::from("route"))),
-                geometry: Some(Geometry::new(LineString(points))),
+                geometry,
                 ..Default::default()
             };
 
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use tokio::{signal, sync::watch};
+use tracing::info;
+
+use crate::state::AppState;
+
+/// Completes on SIGINT or SIGTERM, to be given to `axum::serve::with_graceful_shutdown`. The
+/// new jobs are rejected and the websockets are closed from then on, see `AppState::shutdown`
+pub async fn shutdown_signal(state: Arc<AppState>) {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install the SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install the SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutting down, waiting for the in-flight requests");
+    state.shutdown.send_replace(true);
+}
+
+/// Completes once the server is shutting down, for the long-lived connections which have to be
+/// closed by the server
+pub async fn shutting_down(shutdown: &mut watch::Receiver<bool>) {
+    // The sender lives in the state, it is only dropped with the server
+    let _ = shutdown.wait_for(|&shutting_down| shutting_down).await;
+}
@@ -0,0 +1,93 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::Semaphore;
+
+/// Latency objective of an endpoint, requests are rejected with a 503 when they take longer than
+/// `timeout` or when `max_in_flight` requests are already being processed, see `SloTicket`
+#[derive(Clone)]
+pub struct EndpointSlo {
+    endpoint: &'static str,
+    timeout: Duration,
+    max_in_flight: usize,
+    in_flight: Arc<Semaphore>,
+}
+
+impl EndpointSlo {
+    pub fn new(endpoint: &'static str, timeout: Duration, max_in_flight: usize) -> Self {
+        EndpointSlo {
+            endpoint,
+            timeout,
+            max_in_flight,
+            in_flight: Arc::new(Semaphore::new(max_in_flight)),
+        }
+    }
+
+    /// `EndpointSlo::new` with the limits overridden by the `{prefix}_TIMEOUT_MS` and
+    /// `{prefix}_MAX_IN_FLIGHT` environment variables
+    pub fn from_env(
+        endpoint: &'static str,
+        prefix: &str,
+        timeout: Duration,
+        max_in_flight: usize,
+    ) -> Self {
+        let timeout_var = format!("{prefix}_TIMEOUT_MS");
+        let max_in_flight_var = format!("{prefix}_MAX_IN_FLIGHT");
+
+        EndpointSlo::new(
+            endpoint,
+            positive_limit(&timeout_var, std::env::var(&timeout_var).ok())
+                .map(Duration::from_millis)
+                .unwrap_or(timeout),
+            positive_limit(&max_in_flight_var, std::env::var(&max_in_flight_var).ok())
+                .map(|limit| limit as usize)
+                .unwrap_or(max_in_flight),
+        )
+    }
+
+    pub fn endpoint(&self) -> &'static str {
+        self.endpoint
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.max_in_flight - self.in_flight.available_permits()
+    }
+
+    pub(super) fn semaphore(&self) -> &Arc<Semaphore> {
+        &self.in_flight
+    }
+}
+
+fn positive_limit(name: &str, value: Option<String>) -> Option<u64> {
+    value.map(|value| match value.parse() {
+        Ok(limit) if limit > 0 => limit,
+        _ => panic!("Invalid {name} {value}, expected a positive integer"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positive_limit() {
+        assert_eq!(
+            positive_limit("ROUTE_TIMEOUT_MS", Some(String::from("250"))),
+            Some(250)
+        );
+        assert_eq!(positive_limit("ROUTE_TIMEOUT_MS", None), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid ROUTE_MAX_IN_FLIGHT 0")]
+    fn test_zero_limit() {
+        positive_limit("ROUTE_MAX_IN_FLIGHT", Some(String::from("0")));
+    }
+}
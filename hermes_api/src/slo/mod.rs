@@ -0,0 +1,3 @@
+pub mod endpoint_slo;
+pub mod slo_middleware;
+pub mod slo_ticket;
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::slo::{endpoint_slo::EndpointSlo, slo_ticket::SloTicket};
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SloRejectionReason {
+    /// Too many requests were already in flight for the endpoint
+    Overloaded,
+    /// The request did not complete within the timeout of the endpoint
+    Timeout,
+}
+
+#[derive(Serialize)]
+pub struct SloRejection {
+    endpoint: &'static str,
+    reason: SloRejectionReason,
+    message: String,
+}
+
+impl IntoResponse for SloRejection {
+    fn into_response(self) -> Response {
+        let mut response = (StatusCode::SERVICE_UNAVAILABLE, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from_static("1"));
+        response
+    }
+}
+
+/// Sheds the request when the endpoint is at capacity and aborts it when it exceeds its timeout,
+/// to be installed with `axum::middleware::from_fn_with_state`.
+///
+/// The handlers run their blocking work with `SloTicket::spawn_blocking`, so that a request
+/// that timed out doesn't start it and that the work still running counts towards the limit
+pub async fn slo_middleware(
+    State(slo): State<EndpointSlo>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Ok(permit) = Arc::clone(slo.semaphore()).try_acquire_owned() else {
+        warn!(
+            endpoint = slo.endpoint(),
+            in_flight = slo.in_flight(),
+            "Shedding request"
+        );
+        return SloRejection {
+            endpoint: slo.endpoint(),
+            reason: SloRejectionReason::Overloaded,
+            message: format!(
+                "{} requests are already in flight for {}",
+                slo.max_in_flight(),
+                slo.endpoint()
+            ),
+        }
+        .into_response();
+    };
+
+    request.extensions_mut().insert(SloTicket::new(
+        slo.endpoint(),
+        Instant::now() + slo.timeout(),
+        permit,
+    ));
+
+    match tokio::time::timeout(slo.timeout(), next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(
+                endpoint = slo.endpoint(),
+                timeout_ms = slo.timeout().as_millis() as u64,
+                "Request timed out"
+            );
+            SloRejection {
+                endpoint: slo.endpoint(),
+                reason: SloRejectionReason::Timeout,
+                message: format!(
+                    "{} did not respond within {} ms",
+                    slo.endpoint(),
+                    slo.timeout().as_millis()
+                ),
+            }
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        Extension, Router,
+        body::{Body, to_bytes},
+        middleware::from_fn_with_state,
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    use crate::error::ApiError;
+
+    use super::*;
+
+    async fn blocking_handler(Extension(ticket): Extension<SloTicket>) -> Response {
+        match ticket
+            .spawn_blocking(|| std::thread::sleep(Duration::from_millis(200)))
+            .await
+        {
+            Ok(()) => StatusCode::OK.into_response(),
+            Err(error) => error.into_response(),
+        }
+    }
+
+    async fn send(slo: &EndpointSlo) -> (StatusCode, String) {
+        let router = Router::new()
+            .route("/route", get(blocking_handler))
+            .layer(from_fn_with_state(slo.clone(), slo_middleware));
+        let request = axum::http::Request::get("/route")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_within_timeout() {
+        let slo = EndpointSlo::new("/route", Duration::from_secs(5), 1);
+
+        let (status, _) = send(&slo).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(slo.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_keeps_blocking_work_in_flight() {
+        let slo = EndpointSlo::new("/route", Duration::from_millis(20), 1);
+
+        let (status, body) = send(&slo).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains("\"timeout\""));
+
+        // The blocking work of the request that timed out is still running
+        assert_eq!(slo.in_flight(), 1);
+        let (status, body) = send(&slo).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains("\"overloaded\""));
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(slo.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_expired_work_is_skipped() {
+        let slo = EndpointSlo::new("/route", Duration::from_secs(5), 1);
+        let permit = Arc::clone(slo.semaphore()).try_acquire_owned().unwrap();
+        let ticket = SloTicket::new("/route", Instant::now(), permit);
+
+        let result = ticket.spawn_blocking(|| 1).await;
+        assert!(matches!(result, Err(ApiError::ServiceUnavailable(_))));
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use tokio::{sync::OwnedSemaphorePermit, time::Instant};
+
+use crate::error::ApiError;
+
+/// Admission of a request by `slo_middleware`, given to the handler as an extension. The request
+/// counts towards the in-flight limit of its endpoint as long as a clone of its ticket is alive,
+/// also after it timed out
+#[derive(Clone)]
+pub struct SloTicket {
+    endpoint: &'static str,
+    deadline: Instant,
+    _permit: Arc<OwnedSemaphorePermit>,
+}
+
+impl SloTicket {
+    pub(super) fn new(
+        endpoint: &'static str,
+        deadline: Instant,
+        permit: OwnedSemaphorePermit,
+    ) -> Self {
+        SloTicket {
+            endpoint,
+            deadline,
+            _permit: Arc::new(permit),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// Runs `work` on the blocking threads. The work of a request that timed out before a thread
+    /// picked it up is skipped, the work already running can't be interrupted and keeps the
+    /// request in flight until it returns
+    pub async fn spawn_blocking<T: Send + 'static>(
+        &self,
+        work: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, ApiError> {
+        let ticket = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if ticket.is_expired() {
+                return None;
+            }
+
+            let result = work();
+            drop(ticket);
+            Some(result)
+        })
+        .await
+        .map_err(|error| ApiError::InternalServerError(error.to_string()))?
+        .ok_or_else(|| {
+            ApiError::ServiceUnavailable(format!("{} timed out before it started", self.endpoint))
+        })
+    }
+}
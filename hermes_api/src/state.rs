@@ -1,11 +1,115 @@
+use std::{collections::HashMap, sync::Arc};
+
 use hermes_matrix_providers::{cache::FileCache, travel_matrix_client::TravelMatrixClient};
-use hermes_optimizer::solver::solver_manager::SolverManager;
+use hermes_optimizer::{
+    json::{service_duration_history::ServiceDurationHistory, types::JsonVehicleRoutingProblem},
+    problem::vehicle_routing_problem::VehicleRoutingProblem,
+    solver::solver_manager::SolverManager,
+};
 use hermes_osrm::client::OsrmClient;
-use hermes_routing::hermes::Hermes;
+use parking_lot::RwLock;
+use tokio::sync::watch;
+
+use crate::{
+    auth::api_keys::{ApiKeys, Tenant},
+    error::ApiError,
+    matrix::{matrix_cache::MatrixCache, matrix_jobs::MatrixJobs},
+    profiles::profile_registry::ProfileRegistry,
+    vrp::{job_store::JobStore, location_areas::LocationAreas, plan::plan::Plans},
+};
 
 pub struct AppState {
-    pub hermes: Hermes,
+    /// Keys of the teams calling the API, with their rate and concurrency limits
+    pub api_keys: ApiKeys,
+    pub profiles: ProfileRegistry,
     pub solver_manager: SolverManager,
+    /// Input of each job, kept to apply updates and rebuild the problem
+    pub job_inputs: tokio::sync::RwLock<HashMap<String, JsonVehicleRoutingProblem>>,
+    /// Jobs persisted across restarts, see `job_store::sync_jobs`
+    pub jobs: JobStore,
     pub matrix_client: TravelMatrixClient<FileCache>,
     pub osrm_client: OsrmClient,
+    /// Matrices computed in the background, their rows are polled block by block
+    pub matrix_jobs: MatrixJobs,
+    /// Travel matrix entries of the routing profiles, reused across the `/vrp` requests
+    pub matrix_cache: Arc<MatrixCache>,
+    /// Durations of the completed stops, used to calibrate the durations of the new jobs
+    pub service_durations: RwLock<ServiceDurationHistory>,
+    /// Administrative and postal areas of the locations of each job routed by a loaded profile
+    pub location_areas: RwLock<HashMap<String, Arc<LocationAreas>>>,
+    /// Standing plans re-optimizing their job on the triggers of their policy
+    pub plans: Plans,
+    /// Problems and matrix jobs with more locations are rejected, unlimited when not set
+    pub max_problem_locations: Option<usize>,
+    /// Set once a shutdown signal is received, see `shutdown::shutdown_signal`
+    pub shutdown: watch::Sender<bool>,
+}
+
+impl AppState {
+    pub fn check_problem_size(&self, problem: &JsonVehicleRoutingProblem) -> Result<(), ApiError> {
+        check_location_count(self.max_problem_locations, problem.locations.len())
+    }
+
+    /// The sources and the targets of a matrix job are checked on their own
+    pub fn check_matrix_size(&self, sources: usize, targets: usize) -> Result<(), ApiError> {
+        check_location_count(self.max_problem_locations, sources.max(targets))
+    }
+
+    /// Rejects the requests starting a search or a matrix computation once the server is
+    /// shutting down, the running jobs are drained instead
+    pub fn check_accepting_jobs(&self) -> Result<(), ApiError> {
+        if *self.shutdown.borrow() {
+            Err(ApiError::ServiceUnavailable(String::from(
+                "The server is shutting down",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Rejects the tenants which don't own the job, see `ApiKeys::can_access`. The job is reported
+    /// as not found so that the jobs of the other tenants are not disclosed
+    pub fn check_job_access(&self, tenant: Option<&Tenant>, job_id: &str) -> Result<(), ApiError> {
+        let owner = self.jobs.owner(job_id).flatten();
+        if self.api_keys.can_access(tenant, owner.as_deref()) {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound(job_id.to_owned()))
+        }
+    }
+
+    /// Annotates the locations of the job with their areas, to aggregate its solutions per area.
+    /// The areas are looked up before taking the lock, the other jobs keep reading theirs
+    pub fn annotate_location_areas(&self, problem: &VehicleRoutingProblem) {
+        let areas = LocationAreas::annotate(&self.profiles, problem);
+        let mut location_areas = self.location_areas.write();
+        match areas {
+            Some(areas) => location_areas.insert(problem.id().to_owned(), Arc::new(areas)),
+            None => location_areas.remove(problem.id()),
+        };
+    }
+}
+
+fn check_location_count(max_locations: Option<usize>, locations: usize) -> Result<(), ApiError> {
+    match max_locations {
+        Some(max_locations) if locations > max_locations => Err(ApiError::BadRequest(format!(
+            "The request has {locations} locations, at most {max_locations} are supported"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_location_count() {
+        assert!(check_location_count(None, 10_000).is_ok());
+        assert!(check_location_count(Some(100), 100).is_ok());
+        assert!(matches!(
+            check_location_count(Some(100), 101),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
 }
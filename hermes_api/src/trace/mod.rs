@@ -0,0 +1,2 @@
+pub mod trace_middleware;
+pub mod trace_parent;
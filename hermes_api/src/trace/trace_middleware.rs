@@ -0,0 +1,32 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use tracing::{Instrument, field::Empty, info_span};
+
+use crate::trace::trace_parent::{TRACEPARENT, TraceParent};
+
+/// Runs the request within a span carrying its W3C trace context and makes the context available to
+/// the handlers as an `Extension<TraceParent>`, to be installed with `axum::middleware::from_fn`.
+///
+/// A missing or invalid `traceparent` header is ignored
+pub async fn trace_middleware(mut request: Request, next: Next) -> Response {
+    let trace_parent = request
+        .headers()
+        .get(&TRACEPARENT)
+        .and_then(|value| value.to_str().ok())
+        .and_then(TraceParent::parse);
+
+    let span = info_span!(
+        "request",
+        method = %request.method(),
+        path = request.uri().path(),
+        trace_id = Empty,
+        parent_id = Empty
+    );
+
+    if let Some(trace_parent) = trace_parent {
+        span.record("trace_id", trace_parent.trace_id());
+        span.record("parent_id", trace_parent.parent_id());
+        request.extensions_mut().insert(trace_parent);
+    }
+
+    next.run(request).instrument(span).await
+}
@@ -0,0 +1,97 @@
+use axum::http::HeaderName;
+use tracing::{Span, field::Empty, info_span};
+
+pub static TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+
+/// W3C trace context received in the `traceparent` header of a request, see
+/// https://www.w3.org/TR/trace-context/#traceparent-header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceParent {
+    trace_id: String,
+    parent_id: String,
+}
+
+impl TraceParent {
+    /// Returns `None` for an invalid header, the caller then starts a new trace as required by the
+    /// specification
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+
+        if !is_lower_hex(version, 2) || version == "ff" {
+            return None;
+        }
+
+        // Later versions can append fields, only version 00 is known to have exactly four
+        if version == "00" && parts.next().is_some() {
+            return None;
+        }
+
+        if !is_lower_hex(trace_id, 32) || is_zero(trace_id) {
+            return None;
+        }
+
+        if !is_lower_hex(parent_id, 16) || is_zero(parent_id) {
+            return None;
+        }
+
+        if !is_lower_hex(flags, 2) {
+            return None;
+        }
+
+        Some(TraceParent {
+            trace_id: trace_id.to_owned(),
+            parent_id: parent_id.to_owned(),
+        })
+    }
+
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    pub fn parent_id(&self) -> &str {
+        &self.parent_id
+    }
+
+    /// Header forwarding the trace to the webhook callbacks, the flags of the received header are
+    /// not kept and the trace is marked as sampled
+    pub fn header_value(&self) -> String {
+        format!("00-{}-{}-01", self.trace_id, self.parent_id)
+    }
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn is_zero(value: &str) -> bool {
+    value.bytes().all(|b| b == b'0')
+}
+
+/// Span of the search of a job.
+///
+/// Jobs outlive the request that created them, the span is a root span carrying the trace of the
+/// request so that the logs of the search can be correlated with the caller
+pub fn job_span(job_id: &str, trace_parent: Option<&TraceParent>) -> Span {
+    let span = info_span!(
+        parent: None,
+        "solver_job",
+        job_id,
+        trace_id = Empty,
+        parent_id = Empty
+    );
+
+    if let Some(trace_parent) = trace_parent {
+        span.record("trace_id", trace_parent.trace_id());
+        span.record("parent_id", trace_parent.parent_id());
+    }
+
+    span
+}
@@ -0,0 +1,110 @@
+use axum::{
+    extract::Request,
+    http::{HeaderValue, Uri, header::LINK, uri::PathAndQuery},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::versioning::V1_PREFIX;
+
+/// Marks the requests received on an unversioned path, see `legacy_paths_middleware`
+#[derive(Clone, Copy)]
+pub struct LegacyPath;
+
+fn is_versioned(path: &str) -> bool {
+    path == V1_PREFIX || path.starts_with(&format!("{V1_PREFIX}/")) || path.starts_with("/docs")
+}
+
+/// Serves the unversioned paths, e.g. `/vrp/jobs`, with the routes of `/v1`. It has to wrap the
+/// router, a router layer only runs once the route is matched.
+///
+/// The responses are marked as deprecated with a link to their versioned path. The requests keep
+/// the lenient schema of the unversioned API, see `VersionedJson`
+pub async fn legacy_paths_middleware(mut request: Request, next: Next) -> Response {
+    if is_versioned(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let path = format!("{V1_PREFIX}{}", request.uri().path());
+    let path_and_query = match request.uri().query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.clone(),
+    };
+
+    let mut parts = request.uri().clone().into_parts();
+    parts.path_and_query = PathAndQuery::try_from(path_and_query).ok();
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *request.uri_mut() = uri;
+    }
+    request.extensions_mut().insert(LegacyPath);
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::try_from(format!("<{path}>; rel=\"successor-version\"")) {
+        headers.insert(LINK, link);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Extension, Router,
+        body::{Body, to_bytes},
+        middleware::from_fn,
+        routing::get,
+    };
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+
+    async fn jobs_handler(legacy: Option<Extension<LegacyPath>>, uri: Uri) -> String {
+        format!("{} {uri}", legacy.is_some())
+    }
+
+    async fn send(uri: &str) -> (Response, String) {
+        let router = Router::new().route("/v1/jobs", get(jobs_handler));
+        let request = axum::http::Request::get(uri).body(Body::empty()).unwrap();
+
+        let mut response = from_fn(legacy_paths_middleware)
+            .layer(router)
+            .oneshot(request)
+            .await
+            .unwrap();
+        let body = std::mem::take(response.body_mut());
+        let body = to_bytes(body, usize::MAX).await.unwrap();
+
+        (response, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_is_versioned() {
+        assert!(is_versioned("/v1"));
+        assert!(is_versioned("/v1/vrp/jobs"));
+        assert!(is_versioned("/docs/api.json"));
+        assert!(!is_versioned("/vrp/jobs"));
+        assert!(!is_versioned("/v10/jobs"));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_path() {
+        let (response, body) = send("/jobs?page=2").await;
+
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(
+            response.headers()[LINK],
+            "</v1/jobs>; rel=\"successor-version\""
+        );
+        assert_eq!(body, "true /v1/jobs?page=2");
+    }
+
+    #[tokio::test]
+    async fn test_versioned_path() {
+        let (response, body) = send("/v1/jobs").await;
+
+        assert!(response.headers().get("deprecation").is_none());
+        assert_eq!(body, "false /v1/jobs");
+    }
+}
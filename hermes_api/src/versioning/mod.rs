@@ -0,0 +1,6 @@
+pub mod legacy_paths;
+pub mod schema_policy;
+pub mod versioned_json;
+
+/// Prefix of the routes of the current version of the API
+pub const V1_PREFIX: &str = "/v1";
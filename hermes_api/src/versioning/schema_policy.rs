@@ -0,0 +1,209 @@
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+use parking_lot::RwLock;
+use schemars::JsonSchema;
+use serde_json::{Map, Value};
+
+/// Schemas of the request types by type, generated on the first request
+static SCHEMAS: OnceLock<RwLock<HashMap<TypeId, Arc<Value>>>> = OnceLock::new();
+
+fn schema<T: JsonSchema + 'static>() -> Arc<Value> {
+    let schemas = SCHEMAS.get_or_init(Default::default);
+    if let Some(schema) = schemas.read().get(&TypeId::of::<T>()) {
+        return Arc::clone(schema);
+    }
+
+    let schema = Arc::new(schemars::schema_for!(T).to_value());
+    Arc::clone(schemas.write().entry(TypeId::of::<T>()).or_insert(schema))
+}
+
+/// Fields of `value` which are not in the schema of `T`, as paths like `vehicles[2].shift`.
+///
+/// The schema of the v1 requests is frozen, a field it doesn't know comes from a newer version of
+/// the API and would be silently ignored by the deserialization. Objects whose schema lists no
+/// properties, e.g. maps, accept any field
+pub fn unknown_fields<T: JsonSchema + 'static>(value: &Value) -> Vec<String> {
+    let schema = schema::<T>();
+    let root = schema.as_ref();
+
+    let mut unknown = vec![];
+    collect_unknown_fields(value, &[root], root, "", &mut unknown);
+    unknown
+}
+
+/// Schema pointed by the `$ref` of `schema`, or `schema` itself
+fn resolve<'a>(schema: &'a Value, root: &'a Value) -> &'a Value {
+    match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .map_or(schema, |target| resolve(target, root)),
+        None => schema,
+    }
+}
+
+/// `schema` and the schemas it combines, a value may match any of them
+fn alternatives<'a>(schema: &'a Value, root: &'a Value, found: &mut Vec<&'a Value>) {
+    let schema = resolve(schema, root);
+    found.push(schema);
+
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        if let Some(subschemas) = schema.get(keyword).and_then(Value::as_array) {
+            for subschema in subschemas {
+                alternatives(subschema, root, found);
+            }
+        }
+    }
+}
+
+fn collect_unknown_fields(
+    value: &Value,
+    schemas: &[&Value],
+    root: &Value,
+    path: &str,
+    unknown: &mut Vec<String>,
+) {
+    let mut candidates = vec![];
+    for schema in schemas {
+        alternatives(schema, root, &mut candidates);
+    }
+
+    match value {
+        Value::Object(fields) => {
+            collect_unknown_object_fields(fields, &candidates, root, path, unknown)
+        }
+        Value::Array(items) => {
+            let item_schemas = candidates
+                .iter()
+                .filter_map(|schema| schema.get("items"))
+                .filter(|items| items.is_object())
+                .collect::<Vec<_>>();
+
+            if item_schemas.is_empty() {
+                return;
+            }
+
+            for (index, item) in items.iter().enumerate() {
+                collect_unknown_fields(
+                    item,
+                    &item_schemas,
+                    root,
+                    &format!("{path}[{index}]"),
+                    unknown,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_unknown_object_fields(
+    fields: &Map<String, Value>,
+    candidates: &[&Value],
+    root: &Value,
+    path: &str,
+    unknown: &mut Vec<String>,
+) {
+    let properties = candidates
+        .iter()
+        .filter_map(|schema| schema.get("properties").and_then(Value::as_object))
+        .collect::<Vec<_>>();
+    let additional_properties = candidates
+        .iter()
+        .filter_map(|schema| schema.get("additionalProperties"))
+        .filter(|schema| schema.is_object())
+        .collect::<Vec<_>>();
+    let accepts_any_field = properties.is_empty()
+        || candidates
+            .iter()
+            .any(|schema| schema.get("additionalProperties") == Some(&Value::Bool(true)));
+
+    for (name, field) in fields {
+        let field_path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{path}.{name}")
+        };
+
+        let field_schemas = properties
+            .iter()
+            .filter_map(|properties| properties.get(name))
+            .collect::<Vec<_>>();
+
+        if !field_schemas.is_empty() {
+            collect_unknown_fields(field, &field_schemas, root, &field_path, unknown);
+        } else if !additional_properties.is_empty() {
+            collect_unknown_fields(field, &additional_properties, root, &field_path, unknown);
+        } else if !accepts_any_field {
+            unknown.push(field_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Request {
+        vehicles: Vec<Vehicle>,
+        tags: HashMap<String, String>,
+        shift: Option<Shift>,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Vehicle {
+        id: String,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Shift {
+        Day { start: String },
+        Night { end: String },
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let value = json!({
+            "vehicles": [{ "id": "a" }, { "id": "b", "color": "red" }],
+            "tags": { "any": "tag" },
+            "shift": { "type": "night", "end": "06:00", "break": true },
+            "priority": 1,
+        });
+
+        let mut unknown = unknown_fields::<Request>(&value);
+        unknown.sort();
+
+        assert_eq!(
+            unknown,
+            vec!["priority", "shift.break", "vehicles[1].color"]
+        );
+    }
+
+    #[test]
+    fn test_known_fields() {
+        let value = json!({
+            "vehicles": [],
+            "tags": {},
+            "shift": { "type": "day", "start": "08:00" },
+        });
+
+        assert!(unknown_fields::<Request>(&value).is_empty());
+    }
+
+    #[test]
+    fn test_cached_schema() {
+        assert!(Arc::ptr_eq(&schema::<Request>(), &schema::<Request>()));
+        assert!(!Arc::ptr_eq(&schema::<Request>(), &schema::<Vehicle>()));
+    }
+}
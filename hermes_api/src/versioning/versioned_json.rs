@@ -0,0 +1,52 @@
+use aide::{OperationInput, generate::GenContext, openapi::Operation};
+use axum::{
+    Json,
+    extract::{FromRequest, Request},
+};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::ApiError,
+    versioning::{legacy_paths::LegacyPath, schema_policy::unknown_fields},
+};
+
+/// JSON body of the versioned routes, rejected with a 400 when it contains fields of a newer
+/// version of the API. The requests on the unversioned paths ignore the unknown fields, as they
+/// always did
+pub struct VersionedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for VersionedJson<T>
+where
+    T: DeserializeOwned + JsonSchema + 'static,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_legacy = request.extensions().get::<LegacyPath>().is_some();
+        let Json(value) = Json::<serde_json::Value>::from_request(request, state)
+            .await
+            .map_err(|rejection| ApiError::BadRequest(rejection.body_text()))?;
+
+        if !is_legacy {
+            let unknown = unknown_fields::<T>(&value);
+            if !unknown.is_empty() {
+                return Err(ApiError::BadRequest(format!(
+                    "Fields {} are not supported by API v1, they may come from a newer version",
+                    unknown.join(", ")
+                )));
+            }
+        }
+
+        serde_json::from_value(value)
+            .map(VersionedJson)
+            .map_err(|error| ApiError::BadRequest(error.to_string()))
+    }
+}
+
+impl<T: JsonSchema> OperationInput for VersionedJson<T> {
+    fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+        Json::<T>::operation_input(ctx, operation);
+    }
+}
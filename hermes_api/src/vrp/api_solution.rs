@@ -1,30 +1,84 @@
 use geojson::Feature;
 use hermes_optimizer::{
     problem::{capacity::Capacity, meters::Meters},
-    solver::score::{Score, ScoreAnalysis},
+    solver::{
+        score::{Score, ScoreAnalysis},
+        time_window_compliance::TimeWindowCompliance,
+        unassigned_reason::UnassignedReasonCode,
+    },
 };
 use jiff::{SignedDuration, Timestamp};
 use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::Serialize;
 
+use super::{drawing_hints::ApiRouteDrawingHints, location_areas::ApiAreaSummary};
+
 #[derive(Serialize, JsonSchema)]
 pub struct ApiServiceActivity {
     pub id: String,
     pub arrival_time: Timestamp,
     pub departure_time: Timestamp,
     pub waiting_duration: SignedDuration,
+    /// Index of the job time window the activity is served in
+    pub time_window_index: Option<usize>,
+    pub time_window_penalty: f64,
+    /// Energy left in the battery on arrival in kWh, for the vehicles with a battery
+    pub battery_level: Option<f64>,
+    /// Weights on the axles when leaving the activity, for the trucks with axle limits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axle_weights: Option<ApiAxleWeights>,
+    /// `[lon, lat]` of the job location, `[x, y]` when cartesian, set with `include_coordinates`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<[f64; 2]>,
+    /// Stops closer than `cluster_radius` share the same cluster ID, only set with
+    /// `include_drawing_hints`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster_id: Option<usize>,
+}
+
+/// Weights in kg, estimated from the weight and the loading meters of the load
+#[derive(Serialize, JsonSchema)]
+pub struct ApiAxleWeights {
+    pub front: f64,
+    pub rear: f64,
+    /// Weight over the limits of the axles, the `axle_load` constraint is violated when positive
+    pub overload: f64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ApiChargeActivity {
+    /// ID of the charging station
+    pub station_id: String,
+    pub arrival_time: Timestamp,
+    pub departure_time: Timestamp,
+    pub waiting_duration: SignedDuration,
+    /// Energy left in the battery on arrival in kWh, the vehicle leaves fully charged
+    pub battery_level: f64,
+    /// `[lon, lat]` of the charging station, set with `include_coordinates`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<[f64; 2]>,
+    /// Stops closer than `cluster_radius` share the same cluster ID, only set with
+    /// `include_drawing_hints`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster_id: Option<usize>,
 }
 
 #[derive(Serialize, JsonSchema)]
 pub struct ApiStartActivity {
     pub arrival_time: Timestamp,
     pub departure_time: Timestamp,
+    /// `[lon, lat]` of the start depot of the vehicle, set with `include_coordinates`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<[f64; 2]>,
 }
 
 #[derive(Serialize, JsonSchema)]
 pub struct ApiEndActivity {
     pub arrival_time: Timestamp,
     pub departure_time: Timestamp,
+    /// `[lon, lat]` of the end depot of the vehicle, set with `include_coordinates`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<[f64; 2]>,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -32,6 +86,7 @@ pub struct ApiEndActivity {
 pub enum ApiSolutionActivity {
     Start(ApiStartActivity),
     Service(ApiServiceActivity),
+    Charge(ApiChargeActivity),
     End(ApiEndActivity),
 }
 
@@ -72,6 +127,8 @@ fn feature_schema(_gen: &mut SchemaGenerator) -> Schema {
 #[derive(Serialize, JsonSchema)]
 pub struct ApiSolutionRoute {
     pub duration: SignedDuration,
+    /// Departure from the start depot, postponed as much as possible to reduce waiting
+    pub start_time: Timestamp,
     pub transport_duration: SignedDuration,
     pub activities: Vec<ApiSolutionActivity>,
     pub distance: Meters,
@@ -81,14 +138,96 @@ pub struct ApiSolutionRoute {
     #[schemars(schema_with = "feature_schema")]
     pub polyline: Feature,
     pub vehicle_max_load: f64,
+    /// Trips of the vehicle, one more than its reloads
+    pub trips: usize,
+    /// Maximum delay the route can absorb from its start time, not set when the route has no time constraints
+    pub time_slack: Option<SignedDuration>,
+    /// Capacity left for additional deliveries
+    pub delivery_load_slack: Capacity,
+    /// Capacity left for additional pickups
+    pub pickup_load_slack: Capacity,
+    /// Only set with `include_drawing_hints`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drawing_hints: Option<ApiRouteDrawingHints>,
+}
+
+/// Why a job is unassigned, from its insertions in the final solution
+#[derive(Serialize, JsonSchema)]
+pub struct ApiUnassignedReason {
+    pub job_id: String,
+    pub code: UnassignedReasonCode,
+    /// Hard constraint which rejected the best insertion of the job, e.g. `capacity`
+    pub constraint: Option<String>,
+    /// Insertions of the job rejected by `constraint`
+    pub rejected_insertions: usize,
+}
+
+/// Overtime a vehicle would need to serve an unassigned job
+#[derive(Serialize, JsonSchema)]
+pub struct ApiShiftExtension {
+    pub job_id: String,
+    pub vehicle_id: String,
+    pub extension_minutes: i64,
+}
+
+/// Deliveries loaded at a depot with a limited stock
+#[derive(Serialize, JsonSchema)]
+pub struct ApiDepotInventoryUsage {
+    pub location_id: usize,
+    pub stock: Capacity,
+    pub usage: Capacity,
+}
+
+/// Margins between the arrival at the stops and the end of their time window, negative when late
+#[derive(Serialize, JsonSchema)]
+pub struct ApiTimeWindowCompliance {
+    /// Stops with a time window end
+    pub stops: usize,
+    pub min_margin: SignedDuration,
+    pub p10_margin: SignedDuration,
+    pub p25_margin: SignedDuration,
+    pub median_margin: SignedDuration,
+    pub p90_margin: SignedDuration,
+    /// Stops served less than 5 minutes before the end of their window
+    pub near_violations: usize,
+    pub violations: usize,
+}
+
+impl From<TimeWindowCompliance> for ApiTimeWindowCompliance {
+    fn from(compliance: TimeWindowCompliance) -> Self {
+        ApiTimeWindowCompliance {
+            stops: compliance.stops,
+            min_margin: compliance.min_margin,
+            p10_margin: compliance.p10_margin,
+            p25_margin: compliance.p25_margin,
+            median_margin: compliance.median_margin,
+            p90_margin: compliance.p90_margin,
+            near_violations: compliance.near_violations,
+            violations: compliance.violations,
+        }
+    }
 }
 
 #[derive(Serialize, JsonSchema)]
 pub struct ApiSolution {
+    /// Sorted by vehicle ID, the routes of a same vehicle in the order they were created
     pub routes: Vec<ApiSolutionRoute>,
     pub duration: SignedDuration,
     pub distance: Meters,
     pub score: Score,
     pub score_analysis: ScoreAnalysis,
+    /// In the order of the jobs of the problem
     pub unassigned_jobs: Vec<String>,
+    /// Reason of each unassigned job, in the same order as `unassigned_jobs`
+    pub unassigned_reasons: Vec<ApiUnassignedReason>,
+    pub shift_extensions: Vec<ApiShiftExtension>,
+    pub depot_inventories: Vec<ApiDepotInventoryUsage>,
+    /// How much delay the plan can absorb before missing time windows, not set when no stop has a
+    /// time window end
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_window_compliance: Option<ApiTimeWindowCompliance>,
+    /// Stops and distance per administrative and postal area, only set when the job is routed by
+    /// a loaded profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub areas: Option<Vec<ApiAreaSummary>>,
 }
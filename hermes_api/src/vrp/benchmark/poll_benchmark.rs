@@ -44,8 +44,9 @@ fn transform_solution(accepted_solution: &AcceptedSolution) -> BenchmarkSolution
     let problem = accepted_solution.solution.problem();
     let routes: Vec<BenchmarkSolutionRoute> = accepted_solution
         .solution
-        .non_empty_routes_iter()
-        .map(|route| {
+        .ordered_non_empty_routes()
+        .into_iter()
+        .map(|(_, route)| {
             let mut activities: Vec<BenchmarkSolutionActivity> = vec![];
 
             activities.extend(route.activity_ids().iter().map(|activity| {
@@ -96,7 +97,7 @@ pub async fn poll_handler(
     match status {
         SolverStatus::Pending => Ok(PollBenchmarkResponse::Pending),
         SolverStatus::Error => Ok(PollBenchmarkResponse::Error),
-        SolverStatus::Running => {
+        SolverStatus::Running | SolverStatus::Paused => {
             let solution = solver
                 .current_best_solution()
                 .map(|solution| transform_solution(&solution));
@@ -107,7 +108,7 @@ pub async fn poll_handler(
                 statistics,
             }))
         }
-        SolverStatus::Completed => {
+        SolverStatus::Completed | SolverStatus::Cancelled => {
             let solution = solver
                 .current_best_solution()
                 .map(|solution| transform_solution(&solution));
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
-    Json,
+    Extension, Json,
     extract::State,
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -10,7 +10,12 @@ use hermes_optimizer::parsers::parser::parse_dataset;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{error::ApiError, state::AppState};
+use crate::{
+    auth::api_keys::Tenant,
+    error::ApiError,
+    state::AppState,
+    vrp::job_store::{JobKind, JobRecord},
+};
 
 #[derive(Serialize)]
 pub struct PostBenchmarkResponse {
@@ -29,17 +34,38 @@ impl IntoResponse for PostBenchmarkResponse {
     }
 }
 
+/// Dataset file of a benchmark
+pub fn benchmark_path(category: &str, name: &str) -> String {
+    format!("./data/vrptw/solomon/{category}/{name}.txt")
+}
+
 pub async fn post_benchmark_handler(
     State(state): State<Arc<AppState>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
     Json(body): Json<PostBenchmarkBody>,
 ) -> Result<PostBenchmarkResponse, ApiError> {
     let solver_manager = &state.solver_manager;
+    state.check_accepting_jobs()?;
 
     let job_id = Uuid::new_v4().to_string();
 
-    let file = format!("./data/vrptw/solomon/{}/{}.txt", body.category, body.name);
+    let vrp = parse_dataset(benchmark_path(&body.category, &body.name))
+        .ok()
+        .unwrap();
+
+    let mut record = JobRecord::new(
+        job_id.clone(),
+        JobKind::Benchmark {
+            category: body.category,
+            name: body.name,
+        },
+    );
+    if let Some(Extension(tenant)) = &tenant {
+        solver_manager.set_owner(&job_id, tenant.job_owner());
+        record.owner = Some(tenant.name().to_owned());
+    }
 
-    let vrp = parse_dataset(&file).ok().unwrap();
-    solver_manager.solve(job_id.clone(), vrp).await;
+    solver_manager.solve(job_id.clone(), vrp).await?;
+    state.jobs.insert(record).await;
     Ok(PostBenchmarkResponse { job_id })
 }
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use geo::{Centroid, ConvexHull, Distance, Euclidean, Haversine, InteriorPoint, MultiPoint, Point};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Meters in a degree of latitude
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Distance in meters under which stops share a cluster
+pub const DEFAULT_CLUSTER_RADIUS: f64 = 10.0;
+
+/// Geometry of the stops of a route for map UIs, the coordinates are `[lon, lat]`
+#[derive(Serialize, JsonSchema)]
+pub struct ApiRouteDrawingHints {
+    /// Closed ring around the stops, empty when the route has no stop
+    pub convex_hull: Vec<[f64; 2]>,
+    pub centroid: Option<[f64; 2]>,
+    /// Point inside the convex hull to place the label of the route
+    pub label_anchor: Option<[f64; 2]>,
+}
+
+fn coordinates(point: Point) -> [f64; 2] {
+    [point.x(), point.y()]
+}
+
+pub fn route_drawing_hints(stops: &[[f64; 2]]) -> ApiRouteDrawingHints {
+    if stops.is_empty() {
+        return ApiRouteDrawingHints {
+            convex_hull: vec![],
+            centroid: None,
+            label_anchor: None,
+        };
+    }
+
+    let points = stops
+        .iter()
+        .map(|&[x, y]| Point::new(x, y))
+        .collect::<MultiPoint>();
+    let convex_hull = points.convex_hull();
+    let centroid = points.centroid();
+
+    ApiRouteDrawingHints {
+        convex_hull: convex_hull.exterior().points().map(coordinates).collect(),
+        centroid: centroid.map(coordinates),
+        label_anchor: convex_hull.interior_point().or(centroid).map(coordinates),
+    }
+}
+
+fn find(parents: &mut [usize], stop: usize) -> usize {
+    let mut root = stop;
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    // Path compression
+    let mut current = stop;
+    while parents[current] != root {
+        current = std::mem::replace(&mut parents[current], root);
+    }
+
+    root
+}
+
+/// Cluster ID of each stop of each route, the stops closer than `radius`, whatever their route,
+/// share the same cluster so that their markers can be grouped. The `radius` is in meters for
+/// `[lon, lat]` coordinates when `geographic`, in the unit of the coordinates otherwise.
+///
+/// The IDs are numbered in the order of the routes and their stops
+pub fn stop_clusters(routes: &[Vec<[f64; 2]>], radius: f64, geographic: bool) -> Vec<Vec<usize>> {
+    let stops = routes.iter().flatten().copied().collect::<Vec<_>>();
+    let mut parents = (0..stops.len()).collect::<Vec<_>>();
+
+    // Stops are only compared with the ones of the neighboring cells of a grid of `radius` wide
+    // cells, the longitude cells are widened for the highest latitude of the stops
+    let (cell_width, cell_height) = if geographic {
+        let max_latitude = stops
+            .iter()
+            .map(|&[_, lat]| lat.abs())
+            .fold(0.0, f64::max)
+            .min(85.0);
+        let cell_height = (radius / METERS_PER_DEGREE).max(f64::EPSILON);
+        (cell_height / max_latitude.to_radians().cos(), cell_height)
+    } else {
+        (radius, radius)
+    };
+
+    let distance = |from: [f64; 2], to: [f64; 2]| {
+        let (from, to) = (Point::from(from), Point::from(to));
+        if geographic {
+            Haversine.distance(from, to)
+        } else {
+            Euclidean.distance(from, to)
+        }
+    };
+
+    let cell = |[lon, lat]: [f64; 2]| {
+        (
+            (lon / cell_width).floor() as i64,
+            (lat / cell_height).floor() as i64,
+        )
+    };
+
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (stop, &coordinates) in stops.iter().enumerate() {
+        let (x, y) = cell(coordinates);
+
+        for neighbor_x in x - 1..=x + 1 {
+            for neighbor_y in y - 1..=y + 1 {
+                let Some(neighbors) = grid.get(&(neighbor_x, neighbor_y)) else {
+                    continue;
+                };
+
+                for &neighbor in neighbors {
+                    if distance(coordinates, stops[neighbor]) <= radius {
+                        let (root, neighbor_root) =
+                            (find(&mut parents, stop), find(&mut parents, neighbor));
+                        parents[root.max(neighbor_root)] = root.min(neighbor_root);
+                    }
+                }
+            }
+        }
+
+        grid.entry((x, y)).or_default().push(stop);
+    }
+
+    let mut cluster_ids = HashMap::new();
+    let mut clusters = (0..stops.len())
+        .map(|stop| {
+            let root = find(&mut parents, stop);
+            let next_id = cluster_ids.len();
+            *cluster_ids.entry(root).or_insert(next_id)
+        })
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    routes
+        .iter()
+        .map(|route| clusters.by_ref().take(route.len()).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Degrees of latitude of `meters`
+    fn north(meters: f64) -> f64 {
+        meters / METERS_PER_DEGREE
+    }
+
+    #[test]
+    fn test_stop_clusters() {
+        let routes = vec![
+            vec![[4.35, 50.85], [4.35, 50.85 + north(5.0)], [4.36, 50.85]],
+            vec![[4.36, 50.85 + north(8.0)], [4.35, 50.85 + north(30.0)]],
+        ];
+
+        let clusters = stop_clusters(&routes, DEFAULT_CLUSTER_RADIUS, true);
+
+        assert_eq!(clusters, vec![vec![0, 0, 1], vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_stop_clusters_chain_across_cells() {
+        // Each stop is within the radius of the next one only
+        let route = (0..5)
+            .map(|index| [4.35, 50.85 + north(9.0 * index as f64)])
+            .collect::<Vec<_>>();
+
+        let clusters = stop_clusters(&[route], DEFAULT_CLUSTER_RADIUS, true);
+
+        assert_eq!(clusters, vec![vec![0; 5]]);
+    }
+
+    #[test]
+    fn test_stop_clusters_cartesian() {
+        // Benchmark coordinates, far outside of the range of a longitude
+        let routes = vec![vec![[500.0, 20.0], [504.0, 23.0]], vec![[520.0, 20.0]]];
+
+        let clusters = stop_clusters(&routes, 6.0, false);
+
+        assert_eq!(clusters, vec![vec![0, 0], vec![1]]);
+    }
+
+    #[test]
+    fn test_route_drawing_hints() {
+        let hints = route_drawing_hints(&[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]]);
+
+        assert_eq!(hints.convex_hull.len(), 5);
+        assert_eq!(hints.convex_hull.first(), hints.convex_hull.last());
+        assert_eq!(hints.centroid, Some([1.0, 1.0]));
+        assert!(hints.label_anchor.is_some());
+
+        let empty = route_drawing_hints(&[]);
+        assert!(empty.convex_hull.is_empty());
+        assert_eq!(empty.centroid, None);
+    }
+}
@@ -7,13 +7,26 @@ use axum::{
 use geo::{Coord, Point, Simplify};
 use geojson::{Feature, Geometry};
 use hermes_optimizer::{
-    json::types::{FromProblem as _, JsonLocation, JsonService, JsonVehicle},
-    problem::{job::Job, meters::Meters, vehicle_routing_problem::VehicleRoutingProblem},
+    json::{
+        geojson::solution_to_geojson,
+        types::{FromProblem as _, JsonLocation, JsonService, JsonVehicle},
+    },
+    problem::{
+        job::Job,
+        location::{Location, LocationIdx},
+        meters::Meters,
+        vehicle_routing_problem::VehicleRoutingProblem,
+    },
     solver::{
-        accepted_solution::AcceptedSolution, alns_weights::AlnsWeights,
-        recreate::recreate_strategy::RecreateStrategy, ruin::ruin_strategy::RuinStrategy,
-        solution::route::WorkingSolutionRoute, solver::SolverStatus,
+        accepted_solution::AcceptedSolution,
+        alns_weights::AlnsWeights,
+        recreate::recreate_strategy::RecreateStrategy,
+        ruin::ruin_strategy::RuinStrategy,
+        shift_extension::compute_shift_extensions,
+        solution::route::WorkingSolutionRoute,
+        solver::{Solver, SolverFailure, SolverStatus},
         statistics::AggregatedStatistics,
+        time_window_compliance::compute_time_window_compliance,
     },
 };
 use jiff::SignedDuration;
@@ -24,9 +37,11 @@ use uuid::Uuid;
 use crate::{error::ApiError, state::AppState};
 
 use super::api_solution::{
-    ApiEndActivity, ApiServiceActivity, ApiSolution, ApiSolutionActivity, ApiSolutionRoute,
-    ApiStartActivity,
+    ApiAxleWeights, ApiChargeActivity, ApiDepotInventoryUsage, ApiEndActivity, ApiServiceActivity,
+    ApiShiftExtension, ApiSolution, ApiSolutionActivity, ApiSolutionRoute, ApiStartActivity,
+    ApiTimeWindowCompliance, ApiUnassignedReason,
 };
+use super::drawing_hints::{DEFAULT_CLUSTER_RADIUS, route_drawing_hints, stop_clusters};
 
 #[derive(Serialize, JsonSchema)]
 struct OperatorWeights {
@@ -48,13 +63,37 @@ pub struct PollSolverCompleted {
     weights: OperatorWeights,
 }
 
+#[derive(Serialize, JsonSchema)]
+pub struct PollSolverPaused {
+    solution: Option<ApiSolution>,
+    statistics: AggregatedStatistics,
+    weights: OperatorWeights,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PollSolverCancelled {
+    solution: Option<ApiSolution>,
+    statistics: AggregatedStatistics,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PollSolverError {
+    /// Best solution found before the failure
+    solution: Option<ApiSolution>,
+
+    /// Failed runs of the job, the last one stopped it
+    failures: Vec<SolverFailure>,
+}
+
 #[derive(Serialize, JsonSchema)]
 #[serde(tag = "status")]
 pub enum PollResponse {
     Pending,
     Running(PollSolverRunning),
+    Paused(PollSolverPaused),
     Completed(PollSolverCompleted),
-    Error,
+    Cancelled(PollSolverCancelled),
+    Error(PollSolverError),
 }
 
 #[derive(Deserialize, JsonSchema)]
@@ -144,75 +183,174 @@ async fn compute_polyline(
 
 async fn transform_solution(
     accepted_solution: Arc<AcceptedSolution>,
+    solver: &Solver,
     state: &Arc<AppState>,
-    with_geojson: bool,
+    query: &PollQuery,
 ) -> ApiSolution {
-    let mut routes: Vec<ApiSolutionRoute> = accepted_solution
+    let include_coordinates = query.include_coordinates.unwrap_or(false);
+
+    // Stable order, identical plans serialize identically
+    let route_ids = accepted_solution
         .solution
-        .non_empty_routes_iter()
-        .map(|route| {
+        .ordered_non_empty_routes()
+        .into_iter()
+        .map(|(route_id, _)| route_id)
+        .collect::<Vec<_>>();
+
+    let mut routes: Vec<ApiSolutionRoute> = route_ids
+        .iter()
+        .map(|&route_id| {
+            let route = accepted_solution.solution.route(route_id);
             let problem = accepted_solution.solution.problem();
             let vehicle = problem.vehicle(route.vehicle_id());
+            let coordinates = |location_id: Option<LocationIdx>| {
+                location_id
+                    .filter(|_| include_coordinates)
+                    .map(|location_id| {
+                        let location = problem.location(location_id);
+                        [location.x(), location.y()]
+                    })
+            };
+
             let mut activities: Vec<ApiSolutionActivity> = vec![];
             if route.has_start(problem) {
                 activities.push(ApiSolutionActivity::Start(ApiStartActivity {
-                    arrival_time: route.start(problem),
-                    departure_time: route.start(problem) + vehicle.depot_duration(),
+                    arrival_time: route.optimized_start(problem),
+                    departure_time: route.optimized_start(problem) + vehicle.depot_duration(),
+                    coordinates: coordinates(vehicle.depot_location_id()),
                 }));
             }
 
-            activities.extend(route.activities_iter().map(|activity| {
-                ApiSolutionActivity::Service(ApiServiceActivity {
-                    id: problem
-                        .job(activity.activity_id().job_id())
-                        .external_id()
-                        .to_owned(),
-                    arrival_time: activity.arrival_time(),
-                    departure_time: activity.departure_time(),
-                    waiting_duration: activity.waiting_duration(),
-                })
-            }));
+            let energy_levels = route.energy_levels(problem);
+            activities.extend(route.optimized_activities_iter().enumerate().map(
+                |(position, activity)| {
+                    let job_id = activity.activity_id().job_id();
+                    let battery_level = energy_levels.get(position).copied();
+                    let location_id = problem.job_activity(activity.activity_id()).location_id();
+
+                    if let Some(station) = problem.charging_station(job_id) {
+                        return ApiSolutionActivity::Charge(ApiChargeActivity {
+                            station_id: station.external_id().to_owned(),
+                            arrival_time: activity.arrival_time(),
+                            departure_time: activity.departure_time(),
+                            waiting_duration: activity.waiting_duration(),
+                            battery_level: battery_level.unwrap_or_default(),
+                            coordinates: coordinates(Some(location_id)),
+                            cluster_id: None,
+                        });
+                    }
+
+                    let time_windows = problem.job_activity(activity.activity_id()).time_windows();
+                    let axle_weights = vehicle.axle_loads().map(|axle_loads| {
+                        let load = route.load_at(position);
+                        let (front, rear) = axle_loads.axle_weights(load);
+                        ApiAxleWeights {
+                            front: front.value(),
+                            rear: rear.value(),
+                            overload: axle_loads.overload(load).value(),
+                        }
+                    });
+                    ApiSolutionActivity::Service(ApiServiceActivity {
+                        id: problem.job(job_id).external_id().to_owned(),
+                        arrival_time: activity.arrival_time(),
+                        departure_time: activity.departure_time(),
+                        waiting_duration: activity.waiting_duration(),
+                        time_window_index: time_windows
+                            .served_window_index(activity.arrival_time()),
+                        time_window_penalty: time_windows
+                            .preference_penalty(activity.arrival_time()),
+                        battery_level,
+                        axle_weights,
+                        coordinates: coordinates(Some(location_id)),
+                        cluster_id: None,
+                    })
+                },
+            ));
 
             if route.has_end(problem) {
                 activities.push(ApiSolutionActivity::End(ApiEndActivity {
                     arrival_time: route.end(problem) - vehicle.end_depot_duration(),
                     departure_time: route.end(problem),
+                    coordinates: coordinates(vehicle.depot_location_id()),
                 }));
             }
 
             ApiSolutionRoute {
                 distance: route.distance(problem),
-                duration: route.duration(problem),
+                duration: route.optimized_duration(problem),
+                start_time: route.optimized_start(problem),
                 transport_duration: route.transport_duration(problem),
                 total_demand: route.total_initial_load().clone(),
                 vehicle_id: route.vehicle(problem).external_id().to_owned(),
-                waiting_duration: route.total_waiting_duration(),
+                waiting_duration: route.optimized_total_waiting_duration(),
                 activities,
                 polyline: Feature::default(),
                 vehicle_max_load: route.max_load(problem),
+                trips: route.trip_starts(problem).len(),
+                time_slack: route.time_slack(),
+                delivery_load_slack: route.delivery_load_slack().clone(),
+                pickup_load_slack: route.pickup_load_slack().clone(),
+                drawing_hints: None,
             }
         })
         .collect();
 
-    if with_geojson {
-        let handles = accepted_solution
-            .solution
-            .routes()
+    if query.include_drawing_hints.unwrap_or(false) {
+        let problem = accepted_solution.solution.problem();
+        let geographic = problem.locations().iter().all(Location::is_geographic);
+        let stops = route_ids
             .iter()
-            .enumerate()
-            .filter_map(|(i, r)| {
-                if r.is_empty() {
-                    return None;
-                }
+            .map(|&route_id| {
+                accepted_solution
+                    .solution
+                    .route(route_id)
+                    .optimized_activities_iter()
+                    .map(|activity| {
+                        let location_id =
+                            problem.job_activity(activity.activity_id()).location_id();
+                        let location = problem.location(location_id);
+                        [location.x(), location.y()]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
 
-                Some(tokio::spawn({
+        let clusters = stop_clusters(
+            &stops,
+            query.cluster_radius.unwrap_or(DEFAULT_CLUSTER_RADIUS),
+            geographic,
+        );
+
+        for ((route, stops), clusters) in routes.iter_mut().zip(&stops).zip(clusters) {
+            route.drawing_hints = Some(route_drawing_hints(stops));
+
+            let stop_cluster_ids =
+                route
+                    .activities
+                    .iter_mut()
+                    .filter_map(|activity| match activity {
+                        ApiSolutionActivity::Service(service) => Some(&mut service.cluster_id),
+                        ApiSolutionActivity::Charge(charge) => Some(&mut charge.cluster_id),
+                        ApiSolutionActivity::Start(_) | ApiSolutionActivity::End(_) => None,
+                    });
+            for (cluster_id, cluster) in stop_cluster_ids.zip(clusters) {
+                *cluster_id = Some(cluster);
+            }
+        }
+    }
+
+    if query.geojson.unwrap_or(true) {
+        let handles = route_ids
+            .iter()
+            .map(|&route_id| {
+                tokio::spawn({
                     let state = state.clone();
                     let accepted_solution = accepted_solution.clone();
                     async move {
-                        let route = &accepted_solution.solution.route(i.into());
+                        let route = &accepted_solution.solution.route(route_id);
                         compute_polyline(accepted_solution.solution.problem(), route, &state).await
                     }
-                }))
+                })
             })
             .collect::<Vec<_>>();
 
@@ -222,6 +360,13 @@ async fn transform_solution(
         }
     }
 
+    let areas = state
+        .location_areas
+        .read()
+        .get(accepted_solution.solution.problem().id())
+        .cloned()
+        .map(|location_areas| location_areas.summaries(&accepted_solution.solution));
+
     ApiSolution {
         score: accepted_solution.score,
         score_analysis: accepted_solution.score_analysis.clone(),
@@ -234,23 +379,91 @@ async fn transform_solution(
         routes,
         unassigned_jobs: accepted_solution
             .solution
-            .unassigned_jobs()
-            .iter()
+            .ordered_unassigned_jobs()
+            .into_iter()
             .map(|job_id| {
                 accepted_solution
                     .solution
                     .problem()
-                    .job(*job_id)
+                    .job(job_id)
                     .external_id()
                     .to_owned()
             })
             .collect::<Vec<_>>(),
+        unassigned_reasons: solver
+            .unassigned_reasons(&accepted_solution.solution)
+            .into_iter()
+            .map(|reason| ApiUnassignedReason {
+                job_id: accepted_solution
+                    .solution
+                    .problem()
+                    .job(reason.job_id)
+                    .external_id()
+                    .to_owned(),
+                code: reason.code,
+                constraint: reason.constraint.map(String::from),
+                rejected_insertions: reason.rejected_insertions,
+            })
+            .collect(),
+        shift_extensions: compute_shift_extensions(&accepted_solution.solution)
+            .into_iter()
+            .map(|extension| {
+                let problem = accepted_solution.solution.problem();
+                ApiShiftExtension {
+                    job_id: problem.job(extension.job_id).external_id().to_owned(),
+                    vehicle_id: problem
+                        .vehicle(extension.vehicle_id)
+                        .external_id()
+                        .to_owned(),
+                    extension_minutes: extension.extension_minutes(),
+                }
+            })
+            .collect(),
+        depot_inventories: accepted_solution
+            .solution
+            .problem()
+            .depot_inventories()
+            .iter()
+            .map(|inventory| ApiDepotInventoryUsage {
+                location_id: inventory.location_id().get(),
+                stock: inventory.stock().clone(),
+                usage: accepted_solution
+                    .solution
+                    .depot_inventory_usage(inventory.location_id()),
+            })
+            .collect(),
+        time_window_compliance: compute_time_window_compliance(&accepted_solution.solution)
+            .map(ApiTimeWindowCompliance::from),
+        areas,
     }
 }
 
+#[derive(Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PollFormat {
+    Json,
+    /// GeoJSON FeatureCollection of the current best solution
+    Geojson,
+}
+
 #[derive(Deserialize, JsonSchema)]
 pub struct PollQuery {
     geojson: Option<bool>,
+    format: Option<PollFormat>,
+    /// Adds the coordinates of each activity location to the routes
+    include_coordinates: Option<bool>,
+    /// Adds the convex hull, centroid and label anchor of each route and the cluster of each stop
+    include_drawing_hints: Option<bool>,
+    /// Distance in meters under which stops share a cluster, 10 by default. In the unit of the
+    /// coordinates for the problems with cartesian locations, e.g. the benchmark instances
+    cluster_radius: Option<f64>,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PollOutput {
+    Json(PollResponse),
+    GeoJson(serde_json::Value),
 }
 
 #[axum::debug_handler]
@@ -258,53 +471,79 @@ pub async fn poll_handler(
     Path(path): Path<JobPath>,
     State(state): State<Arc<AppState>>,
     Query(query): Query<PollQuery>,
-) -> Result<Json<PollResponse>, ApiError> {
+) -> Result<Json<PollOutput>, ApiError> {
     let solver = state
         .solver_manager
         .solver(&path.job_id.to_string())
         .await
         .ok_or(ApiError::NotFound(path.job_id.to_string()))?;
 
-    match solver.status() {
-        SolverStatus::Pending => Ok(Json(PollResponse::Pending)),
-        SolverStatus::Error => Ok(Json(PollResponse::Error)),
-        SolverStatus::Running => {
-            let solution = solver.current_best_solution().map(|solution| {
-                transform_solution(Arc::new(solution), &state, query.geojson.unwrap_or(true))
-            });
-            let statistics = solver.statistics().aggregate();
-            let weights = solver.weights();
-            Ok(Json(PollResponse::Running(PollSolverRunning {
-                solution: match solution {
-                    Some(solution) => Some(solution.await),
-                    None => None,
-                },
-                statistics,
-                weights: OperatorWeights {
-                    ruin: weights.0,
-                    recreate: weights.1,
-                },
-            })))
-        }
+    if let Some(radius) = query.cluster_radius
+        && (!radius.is_finite() || radius <= 0.0)
+    {
+        return Err(ApiError::BadRequest(String::from(
+            "cluster_radius must be a positive distance",
+        )));
+    }
 
-        SolverStatus::Completed => {
-            let solution = solver.current_best_solution().map(|solution| {
-                transform_solution(Arc::new(solution), &state, query.geojson.unwrap_or(true))
-            });
-            let statistics = solver.statistics().aggregate();
-            let weights = solver.weights();
-            Ok(Json(PollResponse::Completed(PollSolverCompleted {
-                solution: match solution {
-                    Some(solution) => Some(solution.await),
-                    None => None,
-                },
-                statistics,
-                weights: OperatorWeights {
-                    ruin: weights.0,
-                    recreate: weights.1,
-                },
-            })))
+    if query.format == Some(PollFormat::Geojson) {
+        let geojson = match solver.current_best_solution() {
+            Some(best) => solution_to_geojson(&best.solution),
+            None => serde_json::json!({ "type": "FeatureCollection", "features": [] }),
+        };
+
+        return Ok(Json(PollOutput::GeoJson(geojson)));
+    }
+
+    poll_json(&solver, &state, &query)
+        .await
+        .map(|response| Json(PollOutput::Json(response)))
+}
+
+async fn poll_json(
+    solver: &Solver,
+    state: &Arc<AppState>,
+    query: &PollQuery,
+) -> Result<PollResponse, ApiError> {
+    let solution = || async move {
+        match solver.current_best_solution() {
+            Some(solution) => {
+                Some(transform_solution(Arc::new(solution), solver, state, query).await)
+            }
+            None => None,
         }
+    };
+
+    let weights = || {
+        let (ruin, recreate) = solver.weights();
+        OperatorWeights { ruin, recreate }
+    };
+
+    match solver.status() {
+        SolverStatus::Pending => Ok(PollResponse::Pending),
+        SolverStatus::Error => Ok(PollResponse::Error(PollSolverError {
+            solution: solution().await,
+            failures: solver.failures(),
+        })),
+        SolverStatus::Running => Ok(PollResponse::Running(PollSolverRunning {
+            solution: solution().await,
+            statistics: solver.statistics().aggregate(),
+            weights: weights(),
+        })),
+        SolverStatus::Paused => Ok(PollResponse::Paused(PollSolverPaused {
+            solution: solution().await,
+            statistics: solver.statistics().aggregate(),
+            weights: weights(),
+        })),
+        SolverStatus::Completed => Ok(PollResponse::Completed(PollSolverCompleted {
+            solution: solution().await,
+            statistics: solver.statistics().aggregate(),
+            weights: weights(),
+        })),
+        SolverStatus::Cancelled => Ok(PollResponse::Cancelled(PollSolverCancelled {
+            solution: solution().await,
+            statistics: solver.statistics().aggregate(),
+        })),
     }
 }
 
@@ -312,20 +551,60 @@ pub async fn start_handler(
     Path(path): Path<JobPath>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<bool>, ApiError> {
-    state.solver_manager.start(&path.job_id.to_string()).await;
+    state.check_accepting_jobs()?;
+    state.solver_manager.start(&path.job_id.to_string()).await?;
+
+    Ok(Json(true))
+}
+
+pub async fn stop_handler(
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<bool>, ApiError> {
+    let result = state.solver_manager.stop(&path.job_id.to_string()).await;
 
-    if true {
+    if result {
         Ok(Json(true))
     } else {
         Err(ApiError::NotFound(path.job_id.to_string()))
     }
 }
 
-pub async fn stop_handler(
+/// Pauses a running job, its search can be resumed later from where it stopped
+pub async fn pause_handler(
     Path(path): Path<JobPath>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<bool>, ApiError> {
-    let result = state.solver_manager.stop(&path.job_id.to_string()).await;
+    let job_id = path.job_id.to_string();
+
+    match state.solver_manager.pause(&job_id).await {
+        Some(true) => Ok(Json(true)),
+        Some(false) => Err(ApiError::BadRequest(format!("Job {job_id} is not running"))),
+        None => Err(ApiError::NotFound(job_id)),
+    }
+}
+
+/// Resumes a paused job from its population and operator weights at the time it was paused
+pub async fn resume_handler(
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<bool>, ApiError> {
+    let job_id = path.job_id.to_string();
+    state.check_accepting_jobs()?;
+
+    if state.solver_manager.resume(&job_id).await? {
+        Ok(Json(true))
+    } else {
+        Err(ApiError::BadRequest(format!("Job {job_id} is not paused")))
+    }
+}
+
+/// Cancels a job, it cannot be resumed afterwards but its best solution can still be polled
+pub async fn cancel_handler(
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<bool>, ApiError> {
+    let result = state.solver_manager.cancel(&path.job_id.to_string()).await;
 
     if result {
         Ok(Json(true))
@@ -334,6 +613,26 @@ pub async fn stop_handler(
     }
 }
 
+/// Deletes a job with its input and persisted state, its search is cancelled
+pub async fn delete_handler(
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<bool>, ApiError> {
+    let job_id = path.job_id.to_string();
+
+    let removed_solver = state.solver_manager.remove(&job_id).await;
+    let removed_record = state.jobs.remove(&job_id).await;
+    if !removed_solver && !removed_record {
+        return Err(ApiError::NotFound(job_id));
+    }
+
+    state.job_inputs.write().await.remove(&job_id);
+    state.location_areas.write().remove(&job_id);
+    state.plans.remove_job(&job_id);
+
+    Ok(Json(true))
+}
+
 #[derive(Serialize, JsonSchema)]
 pub struct VehicleRoutingJobInput {
     pub id: String,
@@ -360,6 +659,8 @@ impl From<&VehicleRoutingProblem> for VehicleRoutingJobInput {
                 .jobs()
                 .iter()
                 .filter_map(|job| match job {
+                    // Reloads and charges are generated from the vehicles
+                    Job::Service(_) if job.is_vehicle_stop() => None,
                     Job::Service(service) => Some(JsonService::from_problem(service, problem)),
                     _ => None,
                 })
@@ -402,9 +703,8 @@ pub async fn neighbors_handler(
     let problem = solver.problem();
 
     let neighbors = problem
-        .neighbors(query.location_id.into())
-        .iter()
-        .map(|&activity_id| problem.job_activity(activity_id).location_id().get())
+        .neighbors(query.location_id.into(), solver.params().neighborhood_size)
+        .map(|activity_id| problem.job_activity(activity_id).location_id().get())
         .collect::<Vec<_>>();
 
     Ok(Json(neighbors))
@@ -0,0 +1,615 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use hermes_optimizer::{
+    json::{checkpoint::JsonCheckpoint, types::JsonVehicleRoutingProblem},
+    parsers::parser::parse_dataset,
+    solver::{solver::SolverStatus, solver_manager::JobOwner},
+};
+use jiff::Timestamp;
+use parking_lot::RwLock;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    admin::restore_handler::restore_job,
+    state::AppState,
+    vrp::{benchmark::post_benchmark::benchmark_path, sensitivity_handler::SensitivityReport},
+};
+
+/// Lifecycle of a persisted job, `queued` until its search starts for the first time
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    /// Paused or cancelled, the best solution stays available
+    Stopped,
+}
+
+impl JobState {
+    /// `None` while the solver is pending, a restored job keeps its persisted state until it is
+    /// started again
+    fn from_solver(status: SolverStatus) -> Option<Self> {
+        match status {
+            SolverStatus::Pending => None,
+            SolverStatus::Running => Some(JobState::Running),
+            SolverStatus::Completed => Some(JobState::Done),
+            SolverStatus::Error => Some(JobState::Failed),
+            SolverStatus::Paused | SolverStatus::Cancelled => Some(JobState::Stopped),
+        }
+    }
+
+    fn is_terminal(self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed | JobState::Stopped)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobType {
+    Vrp,
+    Benchmark,
+    Sensitivity,
+    Tournament,
+}
+
+/// What is needed to create the solver of the job again after a restart
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobKind {
+    Vrp {
+        input: JsonVehicleRoutingProblem,
+    },
+    /// Dataset of `./data/vrptw/solomon`
+    Benchmark {
+        category: String,
+        name: String,
+    },
+    /// Analysis run in the background, see `/vrp/sensitivity`. It is not run again after a
+    /// restart
+    Sensitivity {
+        scenarios: usize,
+        /// Set once the analysis is done
+        #[serde(default)]
+        report: Option<SensitivityReport>,
+    },
+    /// Ranking answered within its request, see `/vrp/tournament`. It is not run again after a
+    /// restart
+    Tournament {
+        plans: usize,
+    },
+}
+
+impl JobKind {
+    pub fn job_type(&self) -> JobType {
+        match self {
+            JobKind::Vrp { .. } => JobType::Vrp,
+            JobKind::Benchmark { .. } => JobType::Benchmark,
+            JobKind::Sensitivity { .. } => JobType::Sensitivity,
+            JobKind::Tournament { .. } => JobType::Tournament,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JobRecord {
+    pub job_id: String,
+    #[serde(flatten)]
+    pub kind: JobKind,
+    pub state: JobState,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    /// Best solution and learned weights when the job was last persisted
+    pub checkpoint: Option<JsonCheckpoint>,
+    /// Reason of the last failure of the search
+    pub failure: Option<String>,
+    /// Name of the API key which created the job
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+impl JobRecord {
+    pub fn new(job_id: String, kind: JobKind) -> Self {
+        let now = Timestamp::now();
+
+        JobRecord {
+            job_id,
+            kind,
+            state: JobState::Queued,
+            created_at: now,
+            updated_at: now,
+            checkpoint: None,
+            failure: None,
+            owner: None,
+        }
+    }
+}
+
+/// Persisted state of a job as listed by `/vrp/jobs`
+pub struct JobSummary {
+    pub job_id: String,
+    pub job_type: JobType,
+    pub state: JobState,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    pub failure: Option<String>,
+    pub owner: Option<String>,
+}
+
+struct JobRestore {
+    state: JobState,
+    kind: JobKind,
+    checkpoint: Option<JsonCheckpoint>,
+    owner: Option<String>,
+}
+
+/// Jobs persisted to a directory, one JSON file per job, so that they survive a restart of the
+/// API. The state of the solvers is copied to the store by `sync_jobs`
+pub struct JobStore {
+    directory: PathBuf,
+    records: RwLock<HashMap<String, JobRecord>>,
+    /// Held while a file is written, a slow write never overwrites a more recent version
+    writes: tokio::sync::Mutex<()>,
+}
+
+impl JobStore {
+    /// Loads the jobs persisted in `directory`, which is created when missing. The files which
+    /// can't be read are skipped
+    pub fn open(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+
+        let mut records = HashMap::new();
+        for entry in fs::read_dir(&directory)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            match read_record(&path) {
+                Ok(record) => {
+                    records.insert(record.job_id.clone(), record);
+                }
+                Err(error) => warn!("Skipping the job file {}: {error}", path.display()),
+            }
+        }
+
+        Ok(JobStore {
+            directory,
+            records: RwLock::new(records),
+            writes: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Adds or replaces the job and persists it
+    pub async fn insert(&self, record: JobRecord) {
+        let job_id = record.job_id.clone();
+        self.records.write().insert(job_id.clone(), record);
+        self.persist(&job_id).await;
+    }
+
+    /// Applies `update` to the job and persists it when `update` returns true
+    pub async fn update(&self, job_id: &str, update: impl FnOnce(&mut JobRecord) -> bool) {
+        let updated = match self.records.write().get_mut(job_id) {
+            Some(record) if update(record) => {
+                record.updated_at = Timestamp::now();
+                true
+            }
+            _ => false,
+        };
+
+        if updated {
+            self.persist(job_id).await;
+        }
+    }
+
+    /// Records a new version of the input of a vrp job, whose solver is created again and waits
+    /// to be started. The job is added when it was not persisted yet
+    pub async fn update_input(&self, job_id: &str, input: JsonVehicleRoutingProblem) {
+        {
+            let mut records = self.records.write();
+            let record = records.entry(job_id.to_owned()).or_insert_with(|| {
+                JobRecord::new(
+                    job_id.to_owned(),
+                    JobKind::Vrp {
+                        input: input.clone(),
+                    },
+                )
+            });
+
+            record.kind = JobKind::Vrp { input };
+            record.state = JobState::Queued;
+            record.failure = None;
+            record.updated_at = Timestamp::now();
+        }
+
+        self.persist(job_id).await;
+    }
+
+    /// Removes the job and its file, false when the job is not in the store
+    pub async fn remove(&self, job_id: &str) -> bool {
+        let removed = self.records.write().remove(job_id).is_some();
+        if removed {
+            self.persist(job_id).await;
+        }
+
+        removed
+    }
+
+    /// Most recent jobs first
+    pub fn list(&self) -> Vec<JobSummary> {
+        let mut jobs = self
+            .records
+            .read()
+            .values()
+            .map(|record| JobSummary {
+                job_id: record.job_id.clone(),
+                job_type: record.kind.job_type(),
+                state: record.state,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                failure: record.failure.clone(),
+                owner: record.owner.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        jobs.sort_by(|job1, job2| job2.created_at.cmp(&job1.created_at));
+        jobs
+    }
+
+    /// State, kind and checkpoint of the job to create its solver again. The checkpoint stays in
+    /// the store, a job which fails to be restored keeps it for the next restart
+    fn restore_of(&self, job_id: &str) -> Option<JobRestore> {
+        let records = self.records.read();
+        let record = records.get(job_id)?;
+
+        Some(JobRestore {
+            state: record.state,
+            kind: record.kind.clone(),
+            checkpoint: record.checkpoint.clone(),
+            owner: record.owner.clone(),
+        })
+    }
+
+    /// Reads the job, `None` when the job is not in the store
+    pub fn read<T>(&self, job_id: &str, read: impl FnOnce(&JobRecord) -> T) -> Option<T> {
+        self.records.read().get(job_id).map(read)
+    }
+
+    /// Name of the API key which created the job, `None` when the job is not in the store
+    pub fn owner(&self, job_id: &str) -> Option<Option<String>> {
+        self.records
+            .read()
+            .get(job_id)
+            .map(|record| record.owner.clone())
+    }
+
+    fn job_ids(&self) -> Vec<String> {
+        self.records.read().keys().cloned().collect()
+    }
+
+    /// Writes the current version of the job, or deletes its file when it was removed. The file
+    /// is written on the blocking pool, outside of the lock of the records
+    async fn persist(&self, job_id: &str) {
+        let _writes = self.writes.lock().await;
+        let content = self.records.read().get(job_id).map(serde_json::to_vec);
+
+        let directory = self.directory.clone();
+        let path_job_id = job_id.to_owned();
+        let result = tokio::task::spawn_blocking(move || match content {
+            Some(content) => write_record(&directory, &path_job_id, &content?),
+            None => remove_record(&directory, &path_job_id),
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+
+        if let Err(error) = result {
+            warn!("Failed to persist the job {job_id}: {error}");
+        }
+    }
+}
+
+fn read_record(path: &Path) -> anyhow::Result<JobRecord> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// The IDs of the vrp jobs come from their input, the bytes which are not safe in a file name are
+/// escaped as `%XX`
+fn file_stem(job_id: &str) -> String {
+    job_id
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn record_path(directory: &Path, job_id: &str) -> PathBuf {
+    directory.join(format!("{}.json", file_stem(job_id)))
+}
+
+/// Written to a temporary file first, a crash while writing keeps the previous version
+fn write_record(directory: &Path, job_id: &str, content: &[u8]) -> anyhow::Result<()> {
+    let path = record_path(directory, job_id);
+    let temporary_path = path.with_extension("json.tmp");
+
+    let mut writer = BufWriter::new(File::create(&temporary_path)?);
+    writer.write_all(content)?;
+    writer.flush()?;
+    fs::rename(&temporary_path, &path)?;
+
+    Ok(())
+}
+
+fn remove_record(directory: &Path, job_id: &str) -> anyhow::Result<()> {
+    match fs::remove_file(record_path(directory, job_id)) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Copies the state of the solvers to the store. The running jobs are persisted on every call with
+/// their best solution so far, the other ones only when their state changes
+async fn sync_jobs(state: &AppState) {
+    for job_id in state.jobs.job_ids() {
+        let Some(solver) = state.solver_manager.solver(&job_id).await else {
+            continue;
+        };
+        let Some(job_state) = JobState::from_solver(solver.status()) else {
+            continue;
+        };
+        let input = state.job_inputs.read().await.get(&job_id).cloned();
+
+        state
+            .jobs
+            .update(&job_id, |record| {
+                if record.state == job_state && job_state.is_terminal() {
+                    return false;
+                }
+
+                record.state = job_state;
+                record.checkpoint = Some(solver.json_checkpoint());
+                record.failure = solver
+                    .failures()
+                    .last()
+                    .map(|failure| failure.reason.clone());
+                // The input changes when the job is updated
+                if let (JobKind::Vrp { input: stored }, Some(input)) = (&mut record.kind, input) {
+                    *stored = input;
+                }
+
+                true
+            })
+            .await;
+    }
+}
+
+/// Syncs the store every `every` until the server shuts down, the last sync is done by
+/// `drain_jobs`
+pub fn schedule_sync(state: &Arc<AppState>, every: Duration) {
+    let state = Arc::clone(state);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            if *state.shutdown.borrow() {
+                break;
+            }
+            sync_jobs(&state).await;
+        }
+    });
+}
+
+/// Pauses the running jobs and persists them with their best solution so far before the server
+/// exits. They are persisted as running, `restore_jobs` continues them from their checkpoint after
+/// the restart. The searches which have not stopped within `timeout` are persisted as they are
+pub async fn drain_jobs(state: &AppState, timeout: Duration) {
+    let paused = state.solver_manager.pause_all().await;
+    info!("Draining {} running jobs", paused.len());
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    while state.solver_manager.running_searches() > 0 && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let running_searches = state.solver_manager.running_searches();
+    if running_searches > 0 {
+        warn!("{running_searches} searches did not stop within {timeout:?}");
+    }
+
+    sync_jobs(state).await;
+    for job_id in &paused {
+        state
+            .jobs
+            .update(job_id, |record| {
+                record.state = JobState::Running;
+                true
+            })
+            .await;
+    }
+}
+
+/// Creates the solvers of the persisted jobs again. The vrp jobs continue from their checkpoint
+/// and are started again when they were running, the unfinished benchmarks start over. The
+/// sensitivity analyses and tournaments interrupted by the restart are failed
+pub async fn restore_jobs(state: &AppState) {
+    let mut restored = 0;
+
+    for job_id in state.jobs.job_ids() {
+        let Some(JobRestore {
+            state: job_state,
+            kind,
+            checkpoint,
+            owner,
+        }) = state.jobs.restore_of(&job_id)
+        else {
+            continue;
+        };
+
+        // The limits of a key which is no longer configured are lifted
+        if let Some(owner) = owner {
+            let job_owner = match state.api_keys.tenant(&owner) {
+                Some(tenant) => tenant.job_owner(),
+                None => JobOwner {
+                    name: owner,
+                    max_running_jobs: None,
+                },
+            };
+            state.solver_manager.set_owner(&job_id, job_owner);
+        }
+
+        let result = match kind {
+            JobKind::Vrp { input } => restore_job(state, &job_id, input, checkpoint.as_ref())
+                .await
+                .map(|_| job_state == JobState::Running)
+                .map_err(|error| error.message().to_owned()),
+            JobKind::Benchmark { .. } if job_state.is_terminal() => Ok(false),
+            JobKind::Benchmark { category, name } => {
+                match parse_dataset(benchmark_path(&category, &name)) {
+                    Ok(problem) => state
+                        .solver_manager
+                        .solve(job_id.clone(), problem)
+                        .await
+                        .map(|_| false)
+                        .map_err(|error| error.to_string()),
+                    Err(error) => Err(error.to_string()),
+                }
+            }
+            JobKind::Sensitivity { .. } | JobKind::Tournament { .. } if job_state.is_terminal() => {
+                continue;
+            }
+            JobKind::Sensitivity { .. } | JobKind::Tournament { .. } => {
+                Err(String::from("Interrupted by a restart of the server"))
+            }
+        };
+
+        match result {
+            Ok(start) => {
+                if start && let Err(error) = state.solver_manager.start(&job_id).await {
+                    warn!("Failed to start the restored job {job_id}: {error}");
+                }
+                restored += 1;
+            }
+            Err(error) => {
+                warn!("Failed to restore the job {job_id}: {error}");
+                state
+                    .jobs
+                    .update(&job_id, |record| {
+                        record.state = JobState::Failed;
+                        record.failure = Some(error);
+                        true
+                    })
+                    .await;
+            }
+        }
+    }
+
+    info!("Restored {restored} persisted jobs");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("hermes_job_store_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    fn benchmark_record(job_id: &str) -> JobRecord {
+        JobRecord::new(
+            job_id.to_owned(),
+            JobKind::Benchmark {
+                category: String::from("c1"),
+                name: String::from("c101"),
+            },
+        )
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("job-1_a"), "job-1_a");
+        assert_eq!(file_stem("../job 1"), "%2E%2E%2Fjob%201");
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_open() {
+        let directory = store_directory("insert");
+        let store = JobStore::open(&directory).unwrap();
+
+        store.insert(benchmark_record("job/1")).await;
+        store
+            .update("job/1", |record| {
+                record.state = JobState::Done;
+                true
+            })
+            .await;
+        assert!(directory.join("job%2F1.json").exists());
+
+        let store = JobStore::open(&directory).unwrap();
+        let jobs = store.list();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].job_id, "job/1");
+        assert_eq!(jobs[0].job_type, JobType::Benchmark);
+        assert_eq!(jobs[0].state, JobState::Done);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let directory = store_directory("remove");
+        let store = JobStore::open(&directory).unwrap();
+
+        store.insert(benchmark_record("job")).await;
+        assert!(store.remove("job").await);
+        assert!(!store.remove("job").await);
+        assert!(store.list().is_empty());
+        assert!(!directory.join("job.json").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_keeps_checkpoint() {
+        let directory = store_directory("restore");
+        let store = JobStore::open(&directory).unwrap();
+
+        let mut record = benchmark_record("job");
+        record.checkpoint = Some(JsonCheckpoint {
+            problem_id: String::from("job"),
+            iterations: 10,
+            seed: 0,
+            solutions: vec![],
+            ruin_weights: Default::default(),
+            recreate_weights: Default::default(),
+            cancelled_jobs: vec![],
+        });
+        store.insert(record).await;
+
+        let restore = store.restore_of("job").unwrap();
+        assert_eq!(restore.checkpoint.unwrap().iterations, 10);
+
+        // A failed restore is retried from the same checkpoint
+        let restore = store.restore_of("job").unwrap();
+        assert!(restore.checkpoint.is_some());
+        assert!(store.restore_of("missing").is_none());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
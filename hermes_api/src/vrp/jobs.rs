@@ -1,42 +1,93 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::State};
-use hermes_optimizer::solver::solver::SolverStatus;
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use hermes_optimizer::solver::{solver::SolverStatus, solver_manager::SolverFailureMetrics};
 use jiff::Timestamp;
 use schemars::JsonSchema;
 use serde::Serialize;
 
-use crate::{error::ApiError, pagination::PaginatedResponse, state::AppState};
+use crate::{
+    auth::api_keys::Tenant,
+    error::ApiError,
+    pagination::{PaginatedResponse, Pagination},
+    state::AppState,
+    vrp::job_store::{JobState, JobType},
+};
 
 #[derive(Serialize, JsonSchema)]
 pub struct VehicleRoutingJob {
     pub job_id: String,
-    pub status: SolverStatus,
+    #[serde(rename = "type")]
+    pub job_type: JobType,
+    /// Persisted state of the job, kept across restarts
+    pub state: JobState,
+    /// Missing when the job has no solver, e.g. a finished benchmark after a restart
+    pub status: Option<SolverStatus>,
     pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    /// Reason of the last failure of the search
+    pub failure: Option<String>,
 }
 
+/// Jobs of the store owned by the API key, every job for the administrator keys, most recent
+/// first
 pub async fn jobs_handler(
     State(state): State<Arc<AppState>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Query(pagination): Query<Pagination>,
 ) -> Result<Json<PaginatedResponse<VehicleRoutingJob>>, ApiError> {
-    let solver_manager = &state.solver_manager;
-    let solvers = solver_manager.list_solvers().await;
+    if pagination.per_page == 0 {
+        return Err(ApiError::BadRequest(String::from(
+            "per_page must be positive",
+        )));
+    }
 
-    let mut jobs: Vec<VehicleRoutingJob> = solvers
+    let tenant = tenant.as_ref().map(|Extension(tenant)| tenant.as_ref());
+    let summaries = state
+        .jobs
+        .list()
         .into_iter()
-        .map(|(job_id, solver)| VehicleRoutingJob {
-            job_id,
-            status: solver.status(),
-            created_at: solver.created_at(),
-        })
-        .collect();
+        .filter(|summary| state.api_keys.can_access(tenant, summary.owner.as_deref()))
+        .collect::<Vec<_>>();
+    let total = summaries.len();
+    let per_page = pagination.per_page as usize;
 
-    jobs.sort_by(|job1, job2| job2.created_at.cmp(&job1.created_at));
+    let mut jobs = Vec::with_capacity(per_page);
+    for summary in summaries
+        .into_iter()
+        .skip(pagination.offset() as usize)
+        .take(per_page)
+    {
+        let status = state
+            .solver_manager
+            .solver(&summary.job_id)
+            .await
+            .map(|solver| solver.status());
+
+        jobs.push(VehicleRoutingJob {
+            job_id: summary.job_id,
+            job_type: summary.job_type,
+            state: summary.state,
+            status,
+            created_at: summary.created_at,
+            updated_at: summary.updated_at,
+            failure: summary.failure,
+        });
+    }
 
     Ok(Json(PaginatedResponse {
-        page: 1,
-        per_page: jobs.len(),
-        total: jobs.len(),
+        page: pagination.page as usize,
+        per_page,
+        total,
         data: jobs,
-        total_pages: 1,
+        total_pages: total.div_ceil(per_page),
     }))
 }
+
+/// Failed searches since the server started, see `SolverParams::retry_on_failure` for the retries
+pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> Json<SolverFailureMetrics> {
+    Json(state.solver_manager.failure_metrics())
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use hermes_optimizer::{
+    problem::{meters::Meters, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::solution::working_solution::WorkingSolution,
+};
+use hermes_routing::admin_areas::AdminAreaKind;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::profiles::profile_registry::ProfileRegistry;
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct ApiArea {
+    /// ID of the OSM boundary relation
+    pub id: i64,
+    /// Name of the administrative area or postal code
+    pub name: String,
+    /// `admin_level` of the administrative areas, e.g. 2 for a country and 8 for a city, not set
+    /// for the postal codes
+    pub admin_level: Option<u8>,
+}
+
+impl ApiArea {
+    /// The administrative areas from the largest to the smallest, then the postal codes
+    fn sort_key(&self) -> (bool, Option<u8>, &str) {
+        (self.admin_level.is_none(), self.admin_level, &self.name)
+    }
+}
+
+/// KPIs of the routes in an area
+#[derive(Serialize, JsonSchema)]
+pub struct ApiAreaSummary {
+    #[serde(flatten)]
+    pub area: ApiArea,
+    /// Activities at a location of the area
+    pub stops: usize,
+    /// Distance between the locations of the area, a leg crossing the boundary of the area counts
+    /// for half of its distance
+    pub distance: Meters,
+}
+
+/// Administrative and postal areas of each location of a problem, annotated when the problem is
+/// built with the OSM boundaries of its routing profile
+#[derive(Default)]
+pub struct LocationAreas {
+    areas: Vec<ApiArea>,
+    /// Indices in `areas` of the areas containing each location
+    location_areas: Vec<Vec<usize>>,
+}
+
+impl LocationAreas {
+    /// `None` when none of the vehicle profiles is routed by a loaded profile
+    pub fn annotate(
+        profiles: &ProfileRegistry,
+        problem: &VehicleRoutingProblem,
+    ) -> Option<LocationAreas> {
+        let hermes = problem
+            .vehicle_profiles()
+            .iter()
+            .find_map(|profile| profiles.get(profile.external_id()))?;
+
+        let mut annotated = LocationAreas::default();
+        let mut area_indices = HashMap::new();
+        for location in problem.locations() {
+            let areas = hermes
+                .admin_areas(&location.geo_point())
+                .into_iter()
+                .map(|area| {
+                    *area_indices.entry(area.id()).or_insert_with(|| {
+                        annotated.areas.push(ApiArea {
+                            id: area.id(),
+                            name: area.name().to_owned(),
+                            admin_level: match area.kind() {
+                                AdminAreaKind::Administrative { level } => Some(level),
+                                AdminAreaKind::PostalCode => None,
+                            },
+                        });
+                        annotated.areas.len() - 1
+                    })
+                })
+                .collect();
+
+            annotated.location_areas.push(areas);
+        }
+
+        Some(annotated)
+    }
+
+    /// Stops and distance per area, from the largest areas to the smallest ones, the postal codes
+    /// last. The areas without stops nor distance are left out
+    pub fn summaries(&self, solution: &WorkingSolution) -> Vec<ApiAreaSummary> {
+        let problem = solution.problem();
+        let mut stops = vec![0; self.areas.len()];
+        let mut distances = vec![0.0; self.areas.len()];
+
+        for (_, route) in solution.ordered_non_empty_routes() {
+            for activity in route.optimized_activities_iter() {
+                let location_id = problem.job_activity(activity.activity_id()).location_id();
+                for &area in &self.location_areas[location_id.get()] {
+                    stops[area] += 1;
+                }
+            }
+
+            let vehicle = route.vehicle(problem);
+            for leg in route.compute_location_ids(problem).windows(2) {
+                let half_distance = problem.travel_distance(vehicle, leg[0], leg[1]).value() / 2.0;
+                for location_id in leg {
+                    for &area in &self.location_areas[location_id.get()] {
+                        distances[area] += half_distance;
+                    }
+                }
+            }
+        }
+
+        let mut summaries = self
+            .areas
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| stops[index] > 0 || distances[index] > 0.0)
+            .map(|(index, area)| ApiAreaSummary {
+                area: area.clone(),
+                stops: stops[index],
+                distance: Meters::new(distances[index]),
+            })
+            .collect::<Vec<_>>();
+
+        summaries.sort_by(|a, b| a.area.sort_key().cmp(&b.area.sort_key()));
+        summaries
+    }
+}
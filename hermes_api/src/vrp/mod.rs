@@ -1,7 +1,16 @@
 pub mod api_solution;
 pub mod benchmark;
+pub mod drawing_hints;
 pub mod job;
+pub mod job_store;
 pub mod jobs;
+pub mod location_areas;
+pub mod plan;
 pub mod post_handler;
+pub mod replay;
 pub mod routes;
+pub mod sensitivity_handler;
+pub mod service_durations_handler;
+pub mod tournament_handler;
+pub mod update_handler;
 pub mod ws;
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    error::ApiError,
+    state::AppState,
+    vrp::plan::{get_plan::PlanPath, plan::ApiPlan},
+};
+
+/// Stops the re-optimizations of the plan, its job keeps running
+pub async fn delete_plan_handler(
+    Path(path): Path<PlanPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiPlan>, ApiError> {
+    let plan_id = path.plan_id.to_string();
+    let plan = state
+        .plans
+        .remove(&plan_id)
+        .ok_or(ApiError::NotFound(plan_id))?;
+
+    Ok(Json(ApiPlan::from(plan.as_ref())))
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{error::ApiError, state::AppState, vrp::plan::plan::ApiPlan};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PlanPath {
+    pub plan_id: Uuid,
+}
+
+pub async fn get_plan_handler(
+    Path(path): Path<PlanPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiPlan>, ApiError> {
+    let plan_id = path.plan_id.to_string();
+    let plan = state
+        .plans
+        .get(&plan_id)
+        .ok_or(ApiError::NotFound(plan_id))?;
+
+    Ok(Json(ApiPlan::from(plan.as_ref())))
+}
@@ -0,0 +1,7 @@
+pub mod delete_plan;
+pub mod get_plan;
+pub mod plan;
+pub mod post_deviation;
+pub mod post_plan;
+pub mod post_updates;
+pub mod reoptimize_plan;
@@ -0,0 +1,548 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::Duration,
+};
+
+use hermes_optimizer::json::{problem_update::JsonProblemUpdate, types::JsonVehicleRoutingProblem};
+use jiff::Timestamp;
+use parking_lot::RwLock;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    error::ApiError,
+    state::AppState,
+    trace::trace_parent::{TRACEPARENT, TraceParent},
+    vrp::update_handler::reoptimize_job,
+};
+
+/// Longest period of the periodic re-optimizations, a week
+const MAX_EVERY_MINUTES: u64 = 7 * 24 * 60;
+
+/// When a plan is re-optimized, the triggers are combined and any of them starts a
+/// re-optimization. A plan without triggers is only re-optimized on demand
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Debug, Default)]
+pub struct TriggerPolicy {
+    /// Re-optimizes every N minutes, with the updates received in between
+    pub every_minutes: Option<u64>,
+    /// Re-optimizes as soon as K new jobs are waiting to be inserted
+    pub new_jobs: Option<usize>,
+    /// Re-optimizes as soon as a vehicle is late or early by more than X minutes
+    pub max_deviation_minutes: Option<f64>,
+}
+
+impl TriggerPolicy {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if let Some(every_minutes) = self.every_minutes
+            && every_minutes > MAX_EVERY_MINUTES
+        {
+            return Err(ApiError::BadRequest(format!(
+                "every_minutes must be at most {MAX_EVERY_MINUTES}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Period of the periodic re-optimizations, `None` without `every_minutes`
+    fn period(&self) -> Option<Duration> {
+        self.every_minutes
+            .filter(|&minutes| minutes > 0)
+            .map(|minutes| Duration::from_secs(minutes.min(MAX_EVERY_MINUTES) * 60))
+    }
+}
+
+/// Rejects the webhook URLs which are not HTTP or point to the loopback or a private network,
+/// which would let the callbacks reach the internal services of the server
+pub fn validate_webhook_url(webhook_url: &str) -> Result<(), ApiError> {
+    let invalid = || ApiError::BadRequest(format!("Invalid webhook URL {webhook_url}"));
+
+    let url = reqwest::Url::parse(webhook_url).map_err(|_| invalid())?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid());
+    }
+
+    let host = url.host_str().ok_or_else(invalid)?;
+    let is_internal = match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(IpAddr::V4(ip)) => is_internal_ipv4(ip),
+        Ok(IpAddr::V6(ip)) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip.to_ipv4_mapped().is_some_and(is_internal_ipv4)
+        }
+        Err(_) => host.eq_ignore_ascii_case("localhost") || host.ends_with(".localhost"),
+    };
+
+    if is_internal { Err(invalid()) } else { Ok(()) }
+}
+
+fn is_internal_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // Shared address space of the carrier-grade NATs
+        || (first == 100 && second & 0xc0 == 64)
+}
+
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanTrigger {
+    Periodic,
+    NewJobs,
+    Deviation,
+    Manual,
+}
+
+#[derive(Serialize, JsonSchema, Clone, Default)]
+pub struct PlanStatus {
+    /// Number of re-optimizations of the plan
+    pub revision: usize,
+    pub last_trigger: Option<PlanTrigger>,
+    pub last_reoptimized_at: Option<Timestamp>,
+    /// Updates received since the last re-optimization
+    pub pending_updates: usize,
+    /// New jobs of the pending updates
+    pub pending_new_jobs: usize,
+    /// Error of the last re-optimization. The updates which cannot be applied to the job are
+    /// dropped, the others stay pending until a re-optimization succeeds
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ApiPlan {
+    pub plan_id: String,
+    pub job_id: String,
+    pub policy: TriggerPolicy,
+    #[serde(flatten)]
+    pub status: PlanStatus,
+}
+
+impl From<&Plan> for ApiPlan {
+    fn from(plan: &Plan) -> Self {
+        ApiPlan {
+            plan_id: plan.id.clone(),
+            job_id: plan.job_id.clone(),
+            policy: plan.policy,
+            status: plan.status(),
+        }
+    }
+}
+
+/// Body of the callbacks sent to the webhook of a plan after each re-optimization
+#[derive(Serialize)]
+struct PlanWebhookEvent<'a> {
+    plan_id: &'a str,
+    job_id: &'a str,
+    trigger: PlanTrigger,
+    #[serde(flatten)]
+    status: &'a PlanStatus,
+}
+
+#[derive(Default)]
+struct PendingUpdates {
+    updates: Vec<JsonProblemUpdate>,
+    new_jobs: usize,
+}
+
+impl PendingUpdates {
+    fn push(&mut self, update: JsonProblemUpdate) {
+        self.new_jobs += new_jobs(&update);
+        self.updates.push(update);
+    }
+
+    /// Drops the updates which cannot be applied to `input` after the ones before them and
+    /// returns their errors
+    fn drop_invalid(&mut self, input: &JsonVehicleRoutingProblem) -> Vec<String> {
+        let mut input = input.clone();
+        let mut errors = vec![];
+        self.updates
+            .retain(|update| match update.apply(&mut input) {
+                Ok(()) => true,
+                Err(error) => {
+                    errors.push(error.to_string());
+                    false
+                }
+            });
+        self.new_jobs = self.updates.iter().map(new_jobs).sum();
+
+        errors
+    }
+}
+
+fn new_jobs(update: &JsonProblemUpdate) -> usize {
+    update.new_services.as_ref().map_or(0, Vec::len)
+}
+
+/// Standing plan of a job, the updates are queued and applied at once when one of the triggers of
+/// its policy fires
+pub struct Plan {
+    id: String,
+    job_id: String,
+    policy: TriggerPolicy,
+    webhook_url: Option<String>,
+    /// Trace of the request creating the plan, attached to the re-optimizations and callbacks
+    trace_parent: Option<TraceParent>,
+    /// Also held during a re-optimization so that a job is not re-optimized twice at once
+    pending: tokio::sync::Mutex<PendingUpdates>,
+    status: RwLock<PlanStatus>,
+}
+
+impl Plan {
+    pub fn new(
+        id: String,
+        job_id: String,
+        policy: TriggerPolicy,
+        webhook_url: Option<String>,
+        trace_parent: Option<TraceParent>,
+    ) -> Self {
+        Plan {
+            id,
+            job_id,
+            policy,
+            webhook_url,
+            trace_parent,
+            pending: Default::default(),
+            status: Default::default(),
+        }
+    }
+
+    pub fn job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    pub fn status(&self) -> PlanStatus {
+        self.status.read().clone()
+    }
+
+    /// Queues the update until the next re-optimization, returns whether enough new jobs are
+    /// waiting to re-optimize now
+    pub async fn enqueue(&self, update: JsonProblemUpdate) -> bool {
+        let mut pending = self.pending.lock().await;
+        pending.push(update);
+
+        let mut status = self.status.write();
+        status.pending_updates = pending.updates.len();
+        status.pending_new_jobs = pending.new_jobs;
+
+        self.policy
+            .new_jobs
+            .is_some_and(|new_jobs| pending.new_jobs >= new_jobs)
+    }
+
+    /// Whether a vehicle late or early by `deviation_minutes` re-optimizes the plan
+    pub fn exceeds_deviation(&self, deviation_minutes: f64) -> bool {
+        self.policy
+            .max_deviation_minutes
+            .is_some_and(|max_deviation| deviation_minutes.abs() > max_deviation)
+    }
+
+    pub async fn has_pending_updates(&self) -> bool {
+        !self.pending.lock().await.updates.is_empty()
+    }
+
+    /// Applies the pending updates to the job and restarts its search, see `reoptimize_job`. The
+    /// updates which cannot be applied are dropped on their own, the others are kept when the
+    /// re-optimization fails
+    pub async fn reoptimize(
+        &self,
+        state: &AppState,
+        trigger: PlanTrigger,
+    ) -> Result<PlanStatus, ApiError> {
+        let mut pending = self.pending.lock().await;
+        let rejected = match state.job_inputs.read().await.get(&self.job_id) {
+            Some(input) => pending.drop_invalid(input),
+            None => vec![],
+        };
+
+        let result = reoptimize_job(
+            state,
+            &self.job_id,
+            &pending.updates,
+            self.trace_parent.as_ref(),
+        )
+        .await;
+        if result.is_ok() {
+            *pending = PendingUpdates::default();
+        }
+
+        let status = {
+            let mut status = self.status.write();
+            status.pending_updates = pending.updates.len();
+            status.pending_new_jobs = pending.new_jobs;
+            status.last_trigger = Some(trigger);
+            status.last_reoptimized_at = Some(Timestamp::now());
+            match &result {
+                Ok(_) => {
+                    status.revision += 1;
+                    status.last_error = (!rejected.is_empty()).then(|| rejected.join(", "));
+                }
+                Err(error) => status.last_error = Some(error.message().to_owned()),
+            }
+            status.clone()
+        };
+
+        info!(
+            plan_id = %self.id,
+            revision = status.revision,
+            "Re-optimized plan on {trigger:?}"
+        );
+        self.notify(state, trigger, &status);
+
+        result.map(|_| status)
+    }
+
+    /// Starts the periodic re-optimizations of the policy, they stop once the plan is removed.
+    /// A period without updates doesn't re-optimize the plan
+    pub fn schedule(plan: &Arc<Plan>, state: &Arc<AppState>) {
+        let Some(period) = plan.policy.period() else {
+            return;
+        };
+
+        let state = Arc::clone(state);
+        let weak_plan = Arc::downgrade(plan);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            // The first tick completes immediately
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                let Some(plan) = weak_plan.upgrade() else {
+                    break;
+                };
+                if !plan.has_pending_updates().await {
+                    continue;
+                }
+
+                if let Err(ApiError::NotFound(_)) =
+                    plan.reoptimize(&state, PlanTrigger::Periodic).await
+                {
+                    warn!("Job {} of plan {} not found", plan.job_id, plan.id);
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Sends the event to the webhook in the background, the failures are only logged
+    fn notify(&self, state: &AppState, trigger: PlanTrigger, status: &PlanStatus) {
+        let Some(webhook_url) = self.webhook_url.clone() else {
+            return;
+        };
+
+        let mut request = state
+            .plans
+            .webhook_client
+            .post(&webhook_url)
+            .json(&PlanWebhookEvent {
+                plan_id: &self.id,
+                job_id: &self.job_id,
+                trigger,
+                status,
+            });
+        if let Some(trace_parent) = &self.trace_parent {
+            request = request.header(&TRACEPARENT, trace_parent.header_value());
+        }
+
+        tokio::spawn(async move {
+            if let Err(error) = request
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+            {
+                warn!("Plan webhook {webhook_url} failed: {error}");
+            }
+        });
+    }
+}
+
+pub struct Plans {
+    plans: RwLock<HashMap<String, Arc<Plan>>>,
+    webhook_client: reqwest::Client,
+}
+
+impl Default for Plans {
+    fn default() -> Self {
+        Plans {
+            plans: Default::default(),
+            // A redirect could lead the callbacks to the addresses `validate_webhook_url` rejects
+            webhook_client: reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Plans {
+    pub fn get(&self, plan_id: &str) -> Option<Arc<Plan>> {
+        self.plans.read().get(plan_id).cloned()
+    }
+
+    pub fn remove(&self, plan_id: &str) -> Option<Arc<Plan>> {
+        self.plans.write().remove(plan_id)
+    }
+
+    /// Removes the plans of a removed job, their periodic re-optimizations stop
+    pub fn remove_job(&self, job_id: &str) {
+        self.plans.write().retain(|_, plan| plan.job_id != job_id);
+    }
+
+    pub fn insert(&self, plan: Plan) -> Arc<Plan> {
+        let plan = Arc::new(plan);
+        self.plans
+            .write()
+            .insert(plan.id.clone(), Arc::clone(&plan));
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(value: serde_json::Value) -> JsonProblemUpdate {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn plan(policy: TriggerPolicy) -> Plan {
+        Plan::new(
+            String::from("plan"),
+            String::from("job"),
+            policy,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert!(validate_webhook_url("https://example.com/hooks/plan").is_ok());
+        assert!(validate_webhook_url("http://93.184.216.34:8080").is_ok());
+
+        for webhook_url in [
+            "example.com",
+            "ftp://example.com",
+            "http://localhost:3000",
+            "http://api.localhost",
+            "http://127.0.0.1",
+            "http://0x7f.1",
+            "http://10.0.0.1",
+            "http://192.168.1.1",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1",
+            "http://0.0.0.0",
+            "http://[::1]",
+            "http://[fd00::1]",
+            "http://[::ffff:192.168.1.1]",
+        ] {
+            assert!(
+                validate_webhook_url(webhook_url).is_err(),
+                "{webhook_url} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_policy_period() {
+        let policy = TriggerPolicy {
+            every_minutes: Some(u64::MAX),
+            ..TriggerPolicy::default()
+        };
+        assert!(policy.validate().is_err());
+
+        let policy = TriggerPolicy {
+            every_minutes: Some(15),
+            ..TriggerPolicy::default()
+        };
+        assert!(policy.validate().is_ok());
+        assert_eq!(policy.period(), Some(Duration::from_secs(900)));
+
+        let policy = TriggerPolicy {
+            every_minutes: Some(0),
+            ..TriggerPolicy::default()
+        };
+        assert_eq!(policy.period(), None);
+    }
+
+    #[test]
+    fn test_drop_invalid_updates() {
+        let input: JsonVehicleRoutingProblem = serde_json::from_value(serde_json::json!({
+            "locations": [],
+            "services": [{ "id": "a", "location_id": 0 }],
+            "vehicle_profiles": [],
+            "vehicles": [{ "id": "v1", "profile": "car" }],
+        }))
+        .unwrap();
+
+        let mut pending = PendingUpdates::default();
+        pending.push(update(serde_json::json!({ "cancelled_jobs": ["a"] })));
+        // Already cancelled by the previous update
+        pending.push(update(serde_json::json!({
+            "cancelled_jobs": ["a"],
+            "new_services": [{ "id": "d", "location_id": 0 }]
+        })));
+        pending.push(update(serde_json::json!({
+            "new_services": [{ "id": "b", "location_id": 0 }, { "id": "c", "location_id": 0 }]
+        })));
+        pending.push(update(
+            serde_json::json!({ "unavailable_vehicles": ["v2"] }),
+        ));
+        assert_eq!(pending.new_jobs, 3);
+
+        let errors = pending.drop_invalid(&input);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(pending.updates.len(), 2);
+        assert_eq!(pending.new_jobs, 2);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue() {
+        let plan = plan(TriggerPolicy {
+            new_jobs: Some(2),
+            ..TriggerPolicy::default()
+        });
+        assert!(!plan.has_pending_updates().await);
+
+        let new_job =
+            || update(serde_json::json!({ "new_services": [{ "id": "b", "location_id": 0 }] }));
+        assert!(!plan.enqueue(new_job()).await);
+        assert!(plan.has_pending_updates().await);
+        assert!(plan.enqueue(new_job()).await);
+
+        let status = plan.status();
+        assert_eq!(status.pending_updates, 2);
+        assert_eq!(status.pending_new_jobs, 2);
+    }
+
+    #[test]
+    fn test_remove_job() {
+        let plans = Plans::default();
+        plans.insert(plan(TriggerPolicy::default()));
+        plans.insert(Plan::new(
+            String::from("other_plan"),
+            String::from("other_job"),
+            TriggerPolicy::default(),
+            None,
+            None,
+        ));
+
+        plans.remove_job("job");
+
+        assert!(plans.get("plan").is_none());
+        assert!(plans.get("other_plan").is_some());
+    }
+}
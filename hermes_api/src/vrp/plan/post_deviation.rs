@@ -0,0 +1,63 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use hermes_optimizer::json::problem_update::JsonProblemUpdate;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    error::ApiError,
+    state::AppState,
+    versioning::versioned_json::VersionedJson,
+    vrp::plan::{
+        get_plan::PlanPath,
+        plan::{ApiPlan, PlanTrigger},
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostDeviationRequest {
+    vehicle_id: String,
+    /// Delay of the vehicle on its route in minutes, negative when it is ahead of schedule
+    deviation_minutes: f64,
+    /// Activities already dispatched at the start of the route of the vehicle, they stay locked
+    /// when re-optimizing
+    dispatched_activities: Option<usize>,
+}
+
+/// Reports how far a vehicle is from its schedule, the plan is re-optimized when the deviation
+/// exceeds the one of its policy
+pub async fn post_deviation_handler(
+    Path(path): Path<PlanPath>,
+    State(state): State<Arc<AppState>>,
+    VersionedJson(body): VersionedJson<PostDeviationRequest>,
+) -> Result<Json<ApiPlan>, ApiError> {
+    let plan_id = path.plan_id.to_string();
+    let plan = state
+        .plans
+        .get(&plan_id)
+        .ok_or(ApiError::NotFound(plan_id))?;
+
+    if let Some(dispatched_activities) = body.dispatched_activities {
+        plan.enqueue(JsonProblemUpdate {
+            new_locations: None,
+            new_services: None,
+            cancelled_jobs: None,
+            unavailable_vehicles: None,
+            dispatched_activities: Some(HashMap::from([(
+                body.vehicle_id.clone(),
+                dispatched_activities,
+            )])),
+        })
+        .await;
+    }
+
+    if plan.exceeds_deviation(body.deviation_minutes) {
+        plan.reoptimize(&state, PlanTrigger::Deviation).await?;
+    }
+
+    Ok(Json(ApiPlan::from(plan.as_ref())))
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::api_keys::Tenant,
+    error::ApiError,
+    state::AppState,
+    trace::trace_parent::TraceParent,
+    versioning::versioned_json::VersionedJson,
+    vrp::plan::plan::{ApiPlan, Plan, TriggerPolicy, validate_webhook_url},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostPlanRequest {
+    /// Job created with `/vrp/jobs`, re-optimized in place by the plan
+    job_id: Uuid,
+    #[serde(default)]
+    policy: TriggerPolicy,
+    /// URL receiving a POST request with the status of the plan after each re-optimization
+    webhook_url: Option<String>,
+}
+
+/// Creates a standing plan re-optimizing a job periodically or when enough changes accumulate
+pub async fn post_plan_handler(
+    State(state): State<Arc<AppState>>,
+    trace_parent: Option<Extension<TraceParent>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    VersionedJson(body): VersionedJson<PostPlanRequest>,
+) -> Result<Json<ApiPlan>, ApiError> {
+    let job_id = body.job_id.to_string();
+    state.check_job_access(
+        tenant.as_ref().map(|Extension(tenant)| tenant.as_ref()),
+        &job_id,
+    )?;
+    if state.solver_manager.solver(&job_id).await.is_none() {
+        return Err(ApiError::NotFound(job_id));
+    }
+
+    body.policy.validate()?;
+    if let Some(webhook_url) = &body.webhook_url {
+        validate_webhook_url(webhook_url)?;
+    }
+
+    let plan = state.plans.insert(Plan::new(
+        Uuid::new_v4().to_string(),
+        job_id,
+        body.policy,
+        body.webhook_url,
+        trace_parent.map(|Extension(trace_parent)| trace_parent),
+    ));
+    Plan::schedule(&plan, &state);
+
+    Ok(Json(ApiPlan::from(plan.as_ref())))
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use hermes_optimizer::json::problem_update::JsonProblemUpdate;
+
+use crate::{
+    error::ApiError,
+    state::AppState,
+    versioning::versioned_json::VersionedJson,
+    vrp::plan::{
+        get_plan::PlanPath,
+        plan::{ApiPlan, PlanTrigger},
+    },
+};
+
+/// Queues changes of the problem of the plan, see `/vrp/{job_id}/update`. They are applied at
+/// the next re-optimization, right away when enough new jobs are waiting
+pub async fn post_updates_handler(
+    Path(path): Path<PlanPath>,
+    State(state): State<Arc<AppState>>,
+    VersionedJson(update): VersionedJson<JsonProblemUpdate>,
+) -> Result<Json<ApiPlan>, ApiError> {
+    let plan_id = path.plan_id.to_string();
+    let plan = state
+        .plans
+        .get(&plan_id)
+        .ok_or(ApiError::NotFound(plan_id))?;
+
+    if plan.enqueue(update).await {
+        plan.reoptimize(&state, PlanTrigger::NewJobs).await?;
+    }
+
+    Ok(Json(ApiPlan::from(plan.as_ref())))
+}
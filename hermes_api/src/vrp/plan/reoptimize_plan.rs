@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    error::ApiError,
+    state::AppState,
+    vrp::plan::{
+        get_plan::PlanPath,
+        plan::{ApiPlan, PlanTrigger},
+    },
+};
+
+/// Applies the pending updates of the plan now, whatever its policy
+pub async fn reoptimize_plan_handler(
+    Path(path): Path<PlanPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiPlan>, ApiError> {
+    let plan_id = path.plan_id.to_string();
+    let plan = state
+        .plans
+        .get(&plan_id)
+        .ok_or(ApiError::NotFound(plan_id))?;
+
+    plan.reoptimize(&state, PlanTrigger::Manual).await?;
+
+    Ok(Json(ApiPlan::from(plan.as_ref())))
+}
@@ -1,11 +1,34 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::State};
-use hermes_optimizer::json::types::JsonVehicleRoutingProblem;
+use axum::{Extension, Json, extract::State};
+use hermes_optimizer::json::{
+    initial_solution::JsonInitialSolution,
+    service_duration_history::JsonServiceDurationCalibration, types::JsonVehicleRoutingProblem,
+};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{error::ApiError, state::AppState};
+use crate::{
+    auth::api_keys::Tenant,
+    error::ApiError,
+    state::AppState,
+    trace::trace_parent::{TraceParent, job_span},
+    versioning::versioned_json::VersionedJson,
+    vrp::job_store::{JobKind, JobRecord},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PostRequest {
+    #[serde(flatten)]
+    problem: JsonVehicleRoutingProblem,
+
+    /// Routes to warm start the solver from, e.g. a previous solution of a slightly different problem
+    initial_solution: Option<JsonInitialSolution>,
+
+    /// Replaces the service durations with the ones learned from the completed stops, see
+    /// `/vrp/service-durations`
+    calibrate_service_durations: Option<JsonServiceDurationCalibration>,
+}
 
 #[derive(Serialize, JsonSchema)]
 pub struct PostResponse {
@@ -14,12 +37,58 @@ pub struct PostResponse {
 
 pub async fn post_handler(
     State(state): State<Arc<AppState>>,
-    Json(body): Json<JsonVehicleRoutingProblem>,
+    trace_parent: Option<Extension<TraceParent>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    VersionedJson(mut body): VersionedJson<PostRequest>,
 ) -> Result<Json<PostResponse>, ApiError> {
     let solver_manager = &state.solver_manager;
+    state.check_accepting_jobs()?;
+    state.check_problem_size(&body.problem)?;
+
+    if let Some(calibration) = &body.calibrate_service_durations {
+        state
+            .service_durations
+            .read()
+            .calibrate(&mut body.problem, calibration);
+    }
+
+    // The calibrated durations are kept for the updates of the job
+    let input = body.problem.clone();
+    let problem = Arc::new(
+        body.problem
+            .build_problem_with_providers(
+                &state.matrix_client,
+                &state.profiles.travel_time_providers(&state.matrix_cache),
+            )
+            .await?,
+    );
+
+    let initial_solution = body
+        .initial_solution
+        .map(|initial_solution| initial_solution.build_solution(Arc::clone(&problem)))
+        .transpose()
+        .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+
+    // Only annotated once the job is sure to be created, a rejected request leaves no areas
+    state.annotate_location_areas(&problem);
+
+    let span = job_span(problem.id(), trace_parent.as_deref());
+    let job_id = solver_manager
+        .create_job(problem, initial_solution, span)
+        .await;
 
-    let problem = body.build_problem(&state.matrix_client).await?;
-    let job_id = solver_manager.create_job(problem).await;
+    let mut record = JobRecord::new(
+        job_id.clone(),
+        JobKind::Vrp {
+            input: input.clone(),
+        },
+    );
+    if let Some(Extension(tenant)) = &tenant {
+        solver_manager.set_owner(&job_id, tenant.job_owner());
+        record.owner = Some(tenant.name().to_owned());
+    }
+    state.jobs.insert(record).await;
+    state.job_inputs.write().await.insert(job_id.clone(), input);
 
     Ok(Json(PostResponse { job_id }))
 }
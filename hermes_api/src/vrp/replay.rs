@@ -0,0 +1,504 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{
+        Path, Query, State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    response::Response,
+};
+use geo::{Coord, LineInterpolatePoint, LineString, Point};
+use hermes_optimizer::{
+    problem::{location::LocationIdx, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::solution::route::WorkingSolutionRoute,
+};
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApiError,
+    shutdown::shutting_down,
+    state::AppState,
+    vrp::{job::JobPath, ws},
+};
+
+const DEFAULT_SPEED: f64 = 60.0;
+/// A simulated day per real second
+const MAX_SPEED: f64 = 86_400.0;
+const DEFAULT_FRAME_INTERVAL_MS: u64 = 1000;
+const MIN_FRAME_INTERVAL_MS: u64 = 100;
+
+#[derive(Deserialize)]
+pub struct ReplayQuery {
+    /// Simulated seconds per real second, defaults to 60, at most a day
+    speed: Option<f64>,
+    /// Milliseconds between two frames, defaults to 1000
+    interval_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Pause,
+    Resume,
+    SetSpeed {
+        speed: f64,
+    },
+    /// Jumps to a simulated time, e.g. to replay a part of the day again
+    Seek {
+        time: Timestamp,
+    },
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum VehicleStatus {
+    NotStarted,
+    AtDepot,
+    Driving,
+    Waiting,
+    Serving,
+    Finished,
+}
+
+#[derive(Serialize)]
+struct VehiclePosition<'a> {
+    vehicle_id: &'a str,
+    /// `[lon, lat]`, interpolated along the geometry of the leg while driving
+    coordinates: [f64; 2],
+    status: VehicleStatus,
+    /// Job served, or the next job while driving
+    job_id: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    Frame {
+        time: Timestamp,
+        speed: f64,
+        vehicles: Vec<VehiclePosition<'a>>,
+    },
+    Finished {
+        time: Timestamp,
+    },
+    /// Last message before the server closes the socket to shut down
+    Shutdown {
+        time: Timestamp,
+    },
+    Error(String),
+}
+
+/// Stop of a vehicle, the depots have no job
+struct ReplayStop {
+    job_id: Option<String>,
+    coordinates: [f64; 2],
+    arrival: Timestamp,
+    /// The service starts once the vehicle is done waiting
+    service_start: Timestamp,
+    departure: Timestamp,
+}
+
+struct VehicleTimeline {
+    vehicle_id: String,
+    stops: Vec<ReplayStop>,
+    /// Geometries of the legs between two consecutive stops
+    legs: Vec<LineString>,
+}
+
+impl VehicleTimeline {
+    fn new(problem: &VehicleRoutingProblem, route: &WorkingSolutionRoute) -> Self {
+        let vehicle = route.vehicle(problem);
+        let coordinates = |location_id: LocationIdx| {
+            let location = problem.location(location_id);
+            [location.x(), location.y()]
+        };
+
+        let mut stops = vec![];
+        if route.has_start(problem)
+            && let Some(depot_location_id) = vehicle.depot_location_id()
+        {
+            let start = route.optimized_start(problem);
+            stops.push(ReplayStop {
+                job_id: None,
+                coordinates: coordinates(depot_location_id),
+                arrival: start,
+                service_start: start,
+                departure: start + vehicle.depot_duration(),
+            });
+        }
+
+        stops.extend(route.optimized_activities_iter().map(|activity| {
+            let activity_id = activity.activity_id();
+            ReplayStop {
+                job_id: Some(problem.job(activity_id.job_id()).external_id().to_owned()),
+                coordinates: coordinates(problem.job_activity(activity_id).location_id()),
+                arrival: activity.arrival_time(),
+                service_start: activity.arrival_time() + activity.waiting_duration(),
+                departure: activity.departure_time(),
+            }
+        }));
+
+        if route.has_end(problem)
+            && let Some(depot_location_id) = vehicle.depot_location_id()
+        {
+            let end = route.end(problem);
+            stops.push(ReplayStop {
+                job_id: None,
+                coordinates: coordinates(depot_location_id),
+                arrival: end - vehicle.end_depot_duration(),
+                service_start: end - vehicle.end_depot_duration(),
+                departure: end,
+            });
+        }
+
+        VehicleTimeline {
+            vehicle_id: vehicle.external_id().to_owned(),
+            stops,
+            legs: vec![],
+        }
+    }
+
+    /// Fetches the road geometry of each leg, a leg without geometry is a straight line
+    async fn fetch_legs(&mut self, state: &AppState) {
+        let mut legs = Vec::with_capacity(self.stops.len().saturating_sub(1));
+        for stops in self.stops.windows(2) {
+            let [from, to] = [stops[0].coordinates, stops[1].coordinates];
+            let points = [Point::new(from[0], from[1]), Point::new(to[0], to[1])];
+            let leg = match state.osrm_client.fetch_geometry(&points).await {
+                Ok(geometry) if geometry.len() >= 2 => LineString::new(
+                    geometry
+                        .into_iter()
+                        .map(|coord| Coord {
+                            x: coord.x as f64,
+                            y: coord.y as f64,
+                        })
+                        .collect(),
+                ),
+                _ => LineString::new(vec![Coord::from(from), Coord::from(to)]),
+            };
+            legs.push(leg);
+        }
+
+        self.legs = legs;
+    }
+
+    fn start(&self) -> Option<Timestamp> {
+        self.stops.first().map(|stop| stop.arrival)
+    }
+
+    fn end(&self) -> Option<Timestamp> {
+        self.stops.last().map(|stop| stop.departure)
+    }
+
+    fn stop_position<'a>(
+        &'a self,
+        stop: &'a ReplayStop,
+        status: VehicleStatus,
+    ) -> VehiclePosition<'a> {
+        VehiclePosition {
+            vehicle_id: &self.vehicle_id,
+            coordinates: stop.coordinates,
+            status,
+            job_id: stop.job_id.as_deref(),
+        }
+    }
+
+    fn position(&self, time: Timestamp) -> Option<VehiclePosition<'_>> {
+        let first = self.stops.first()?;
+        let last = self.stops.last()?;
+
+        if time < first.arrival {
+            return Some(self.stop_position(first, VehicleStatus::NotStarted));
+        }
+
+        for (index, stop) in self.stops.iter().enumerate() {
+            if time < stop.arrival {
+                let previous = &self.stops[index - 1];
+                let leg_duration = stop.arrival.duration_since(previous.departure);
+                let fraction = if leg_duration > SignedDuration::ZERO {
+                    time.duration_since(previous.departure).as_secs_f64()
+                        / leg_duration.as_secs_f64()
+                } else {
+                    1.0
+                };
+
+                let coordinates = self.legs[index - 1]
+                    .line_interpolate_point(fraction.clamp(0.0, 1.0))
+                    .map_or(stop.coordinates, |point| [point.x(), point.y()]);
+
+                return Some(VehiclePosition {
+                    coordinates,
+                    ..self.stop_position(stop, VehicleStatus::Driving)
+                });
+            }
+
+            if time < stop.departure {
+                let status = match stop.job_id {
+                    None => VehicleStatus::AtDepot,
+                    Some(_) if time < stop.service_start => VehicleStatus::Waiting,
+                    Some(_) => VehicleStatus::Serving,
+                };
+                return Some(self.stop_position(stop, status));
+            }
+        }
+
+        Some(self.stop_position(last, VehicleStatus::Finished))
+    }
+}
+
+/// Simulated time running `speed` times faster than the real time
+struct SimulationClock {
+    /// Simulated time at `anchor`
+    time: Timestamp,
+    anchor: Instant,
+    speed: f64,
+    is_paused: bool,
+}
+
+impl SimulationClock {
+    fn new(time: Timestamp, speed: f64) -> Self {
+        SimulationClock {
+            time,
+            anchor: Instant::now(),
+            speed,
+            is_paused: false,
+        }
+    }
+
+    fn now(&self) -> Timestamp {
+        if self.is_paused {
+            return self.time;
+        }
+
+        self.time + SignedDuration::from_secs_f64(self.anchor.elapsed().as_secs_f64() * self.speed)
+    }
+
+    fn pause(&mut self) {
+        self.time = self.now();
+        self.is_paused = true;
+    }
+
+    fn resume(&mut self) {
+        if self.is_paused {
+            self.anchor = Instant::now();
+            self.is_paused = false;
+        }
+    }
+
+    fn set_speed(&mut self, speed: f64) {
+        self.seek(self.now());
+        self.speed = speed;
+    }
+
+    fn seek(&mut self, time: Timestamp) {
+        self.time = time;
+        self.anchor = Instant::now();
+    }
+}
+
+/// Replays the best solution of a job as a stream of vehicle positions, for demos and
+/// dispatcher training.
+///
+/// The simulated time starts at the first departure and runs `speed` times faster than the real
+/// time, a frame with the position of each vehicle is sent every `interval_ms`. The clients can
+/// send `{"type": "pause"}`, `{"type": "resume"}`, `{"type": "set_speed", "speed": 120}` and
+/// `{"type": "seek", "time": "2025-06-10T10:00:00Z"}`. The stream ends with a `finished` message
+/// once every vehicle is back.
+pub async fn handler(
+    ws: WebSocketUpgrade,
+    Path(path): Path<JobPath>,
+    Query(query): Query<ReplayQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, ApiError> {
+    let speed = query
+        .speed
+        .map(validate_speed)
+        .transpose()
+        .map_err(ApiError::BadRequest)?
+        .unwrap_or(DEFAULT_SPEED);
+    let interval_ms = query
+        .interval_ms
+        .unwrap_or(DEFAULT_FRAME_INTERVAL_MS)
+        .max(MIN_FRAME_INTERVAL_MS);
+
+    let job_id = path.job_id.to_string();
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, job_id, speed, interval_ms)))
+}
+
+/// The clock converts the speed to a duration, it must be finite and bounded
+fn validate_speed(speed: f64) -> Result<f64, String> {
+    if speed.is_finite() && speed > 0.0 && speed <= MAX_SPEED {
+        Ok(speed)
+    } else {
+        Err(format!(
+            "Invalid speed {speed}, it must be positive and at most {MAX_SPEED}"
+        ))
+    }
+}
+
+async fn replay_timelines(
+    state: &Arc<AppState>,
+    job_id: &str,
+) -> Result<Vec<VehicleTimeline>, String> {
+    let solver = state
+        .solver_manager
+        .solver(job_id)
+        .await
+        .ok_or_else(|| format!("Job {job_id} not found"))?;
+    let best = solver
+        .current_best_solution()
+        .ok_or_else(|| format!("Job {job_id} has no solution yet"))?;
+    let best = Arc::new(best);
+
+    let handles = best
+        .solution
+        .ordered_non_empty_routes()
+        .into_iter()
+        .map(|(route_id, _)| {
+            let state = Arc::clone(state);
+            let best = Arc::clone(&best);
+            tokio::spawn(async move {
+                let solution = &best.solution;
+                let mut timeline =
+                    VehicleTimeline::new(solution.problem(), solution.route(route_id));
+                timeline.fetch_legs(&state).await;
+                timeline
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut timelines = Vec::with_capacity(handles.len());
+    for handle in handles {
+        timelines.push(handle.await.map_err(|error| error.to_string())?);
+    }
+
+    Ok(timelines)
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    state: Arc<AppState>,
+    job_id: String,
+    speed: f64,
+    interval_ms: u64,
+) {
+    let timelines = match replay_timelines(&state, &job_id).await {
+        Ok(timelines) => timelines,
+        Err(error) => {
+            let _ = send(&mut socket, &ServerMessage::Error(error)).await;
+            return;
+        }
+    };
+
+    let start = timelines.iter().filter_map(VehicleTimeline::start).min();
+    let end = timelines.iter().filter_map(VehicleTimeline::end).max();
+    let (Some(start), Some(end)) = (start, end) else {
+        let message = ServerMessage::Error(format!("Job {job_id} has no routes"));
+        let _ = send(&mut socket, &message).await;
+        return;
+    };
+
+    let mut clock = SimulationClock::new(start, speed);
+    let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+    let mut shutdown = state.shutdown.subscribe();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+
+                let error = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Pause) => {
+                        clock.pause();
+                        continue;
+                    }
+                    Ok(ClientMessage::Resume) => {
+                        clock.resume();
+                        continue;
+                    }
+                    Ok(ClientMessage::SetSpeed { speed }) => match validate_speed(speed) {
+                        Ok(speed) => {
+                            clock.set_speed(speed);
+                            continue;
+                        }
+                        Err(error) => error,
+                    },
+                    Ok(ClientMessage::Seek { time }) => {
+                        clock.seek(time.clamp(start, end));
+                        continue;
+                    }
+                    Err(error) => error.to_string(),
+                };
+
+                if send(&mut socket, &ServerMessage::Error(error)).await.is_err() {
+                    break;
+                }
+            }
+            _ = shutting_down(&mut shutdown) => {
+                let time = clock.now().min(end);
+                if send(&mut socket, &ServerMessage::Shutdown { time }).await.is_ok() {
+                    let _ = ws::close(&mut socket).await;
+                }
+                break;
+            }
+            _ = interval.tick() => {
+                let time = clock.now().min(end);
+                let frame = ServerMessage::Frame {
+                    time,
+                    speed: clock.speed,
+                    vehicles: timelines
+                        .iter()
+                        .filter_map(|timeline| timeline.position(time))
+                        .collect(),
+                };
+
+                if send(&mut socket, &frame).await.is_err() {
+                    break;
+                }
+
+                if time >= end {
+                    let _ = send(&mut socket, &ServerMessage::Finished { time }).await;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage<'_>) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("Failed to serialize websocket message");
+    socket.send(Message::Text(text.into())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_speed() {
+        assert_eq!(validate_speed(120.0), Ok(120.0));
+        assert_eq!(validate_speed(MAX_SPEED), Ok(MAX_SPEED));
+
+        for speed in [0.0, -1.0, MAX_SPEED + 1.0, f64::INFINITY, f64::NAN] {
+            assert!(validate_speed(speed).is_err(), "{speed} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_client_set_speed() {
+        let message = r#"{"type": "set_speed", "speed": 1e308}"#;
+        let Ok(ClientMessage::SetSpeed { speed }) = serde_json::from_str(message) else {
+            panic!("Invalid message");
+        };
+
+        assert!(validate_speed(speed).is_err());
+    }
+}
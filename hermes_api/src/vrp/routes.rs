@@ -4,28 +4,40 @@ use aide::axum::{
     ApiRouter,
     routing::{get_with, post_with},
 };
+use axum::{middleware::from_fn_with_state, routing::get};
 
 use crate::{
+    auth::auth_middleware::{job_access_middleware, plan_access_middleware},
     state::AppState,
     vrp::{
         job::{self, stop_handler},
-        jobs::jobs_handler,
+        jobs::{jobs_handler, metrics_handler},
+        plan::{
+            delete_plan::delete_plan_handler, get_plan::get_plan_handler,
+            post_deviation::post_deviation_handler, post_plan::post_plan_handler,
+            post_updates::post_updates_handler, reoptimize_plan::reoptimize_plan_handler,
+        },
         post_handler::post_handler,
+        replay,
+        sensitivity_handler::{sensitivity_handler, sensitivity_report_handler},
+        service_durations_handler::service_durations_handler,
+        tournament_handler::tournament_handler,
+        update_handler::update_handler,
+        ws,
     },
 };
 
 pub fn vrp_routes(state: Arc<AppState>) -> ApiRouter {
     aide::generate::infer_responses(true);
-    let router = ApiRouter::new()
-        .api_route(
-            "/jobs",
-            get_with(jobs_handler, |op| op.id("listJobs"))
-                .post_with(post_handler, |op| op.id("createJob")),
-        )
+    let job_routes = ApiRouter::new()
         .api_route(
             "/jobs/{job_id}",
             get_with(job::job_handler, |op| {
                 op.description("Get the job input").id("getJob")
+            })
+            .delete_with(job::delete_handler, |op| {
+                op.description("Delete a job, its search is cancelled")
+                    .id("deleteJob")
             }),
         )
         .api_route(
@@ -53,6 +65,134 @@ pub fn vrp_routes(state: Arc<AppState>) -> ApiRouter {
             "/jobs/{job_id}/stop",
             post_with(stop_handler, |op| op.id("stopJob")),
         )
+        .api_route(
+            "/jobs/{job_id}/pause",
+            post_with(job::pause_handler, |op| {
+                op.description("Pause a running job, it can be resumed later")
+                    .id("pauseJob")
+            }),
+        )
+        .api_route(
+            "/jobs/{job_id}/resume",
+            post_with(job::resume_handler, |op| {
+                op.description("Resume a paused job from where it stopped")
+                    .id("resumeJob")
+            }),
+        )
+        .api_route(
+            "/jobs/{job_id}/cancel",
+            post_with(job::cancel_handler, |op| {
+                op.description("Cancel a job, its best solution stays available")
+                    .id("cancelJob")
+            }),
+        )
+        .api_route(
+            "/jobs/{job_id}/sensitivity",
+            get_with(sensitivity_report_handler, |op| {
+                op.description("Get the report of a sensitivity analysis")
+                    .id("getSensitivityReport")
+            }),
+        )
+        .api_route(
+            "/{job_id}/update",
+            post_with(update_handler, |op| {
+                op.description("Apply changes to a job and re-optimize from its best solution")
+                    .id("updateJob")
+            }),
+        )
+        // Websocket streaming the telemetry of a running job, see `ws::handler` for the protocol
+        .route("/jobs/{job_id}/ws", get(ws::handler))
+        // Websocket replaying the best solution of a job, see `replay::handler`
+        .route("/jobs/{job_id}/replay", get(replay::handler))
+        .route_layer(from_fn_with_state(
+            Arc::clone(&state),
+            job_access_middleware,
+        ));
+
+    let plan_routes = ApiRouter::new()
+        .api_route(
+            "/plans/{plan_id}",
+            get_with(get_plan_handler, |op| op.id("getPlan")).delete_with(
+                delete_plan_handler,
+                |op| {
+                    op.description("Stop re-optimizing the job of a plan")
+                        .id("deletePlan")
+                },
+            ),
+        )
+        .api_route(
+            "/plans/{plan_id}/updates",
+            post_with(post_updates_handler, |op| {
+                op.description("Queue changes until the next re-optimization of a plan")
+                    .id("updatePlan")
+            }),
+        )
+        .api_route(
+            "/plans/{plan_id}/deviations",
+            post_with(post_deviation_handler, |op| {
+                op.description("Report how late or early a vehicle of a plan is")
+                    .id("reportPlanDeviation")
+            }),
+        )
+        .api_route(
+            "/plans/{plan_id}/reoptimize",
+            post_with(reoptimize_plan_handler, |op| {
+                op.description("Re-optimize a plan with its pending changes now")
+                    .id("reoptimizePlan")
+            }),
+        )
+        .route_layer(from_fn_with_state(
+            Arc::clone(&state),
+            plan_access_middleware,
+        ));
+
+    let router = ApiRouter::new()
+        .api_route(
+            "/jobs",
+            get_with(jobs_handler, |op| op.id("listJobs"))
+                .post_with(post_handler, |op| op.id("createJob")),
+        )
+        .api_route(
+            "/plans",
+            post_with(post_plan_handler, |op| {
+                op.description(
+                    "Create a plan re-optimizing a job periodically or when changes accumulate",
+                )
+                .id("createPlan")
+            }),
+        )
+        .api_route(
+            "/metrics",
+            get_with(metrics_handler, |op| {
+                op.description("Count the failed and retried jobs")
+                    .id("getJobMetrics")
+            }),
+        )
+        .api_route(
+            "/sensitivity",
+            post_with(sensitivity_handler, |op| {
+                op.description(
+                    "Start re-solving the problem with fleet and shift changes to compare costs",
+                )
+                .id("analyzeSensitivity")
+            }),
+        )
+        .api_route(
+            "/tournament",
+            post_with(tournament_handler, |op| {
+                op.description("Score and rank plans of the same problem without solving it")
+                    .id("rankPlans")
+            }),
+        )
+        .api_route(
+            "/service-durations",
+            post_with(service_durations_handler, |op| {
+                op.description("Record the actual durations of completed stops")
+                    .id("recordServiceDurations")
+            }),
+        )
+        .merge(job_routes)
+        .merge(plan_routes)
         .with_state(state);
 
     aide::generate::infer_responses(false);
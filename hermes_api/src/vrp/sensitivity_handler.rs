@@ -0,0 +1,283 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use hermes_optimizer::{
+    json::{sensitivity::SensitivityScenario, types::JsonVehicleRoutingProblem},
+    problem::{meters::Meters, vehicle_routing_problem::VehicleRoutingProblem},
+    solver::{accepted_solution::AcceptedSolution, score::Score, solver_params::Termination},
+};
+use jiff::SignedDuration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    auth::api_keys::Tenant,
+    error::ApiError,
+    state::AppState,
+    versioning::versioned_json::VersionedJson,
+    vrp::{
+        job::JobPath,
+        job_store::{JobKind, JobRecord, JobState},
+    },
+};
+
+/// Scenarios of an analysis, each one is solved on its own thread
+const MAX_SCENARIOS: usize = 16;
+
+/// Search time of the scenarios of an analysis
+const MAX_DURATION: SignedDuration = SignedDuration::from_mins(5);
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SensitivityRequest {
+    #[serde(flatten)]
+    problem: JsonVehicleRoutingProblem,
+
+    /// Scenarios compared to the problem, defaults to the fleet ± 1 vehicle and the shifts relaxed
+    /// and tightened by `shift_delta`
+    scenarios: Option<Vec<SensitivityScenario>>,
+
+    /// Defaults to 1 hour
+    shift_delta: Option<SignedDuration>,
+
+    /// Search time of each scenario, they are solved side by side, defaults to 10 seconds and
+    /// at most 5 minutes
+    duration: Option<SignedDuration>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct SensitivityOutcome {
+    pub score: Score,
+    pub distance: Meters,
+    pub vehicles: usize,
+    pub unassigned_jobs: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct SensitivityScenarioOutcome {
+    pub scenario: SensitivityScenario,
+
+    /// Not set when no solution was found in the search time
+    pub outcome: Option<SensitivityOutcome>,
+
+    /// Soft score of the scenario minus the one of the baseline, negative when the scenario is
+    /// cheaper
+    pub cost_delta: Option<f64>,
+    pub distance_delta: Option<f64>,
+    pub unassigned_jobs_delta: Option<i64>,
+}
+
+/// Outcomes of a finished analysis, persisted with its job
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct SensitivityReport {
+    pub baseline: Option<SensitivityOutcome>,
+    pub scenarios: Vec<SensitivityScenarioOutcome>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SensitivityResponse {
+    /// The analysis is listed with the other jobs by `/vrp/jobs`, its report is fetched from
+    /// `/vrp/jobs/{job_id}/sensitivity`
+    pub job_id: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SensitivityReportResponse {
+    pub job_id: String,
+    pub state: JobState,
+    pub failure: Option<String>,
+    /// Not set until the analysis is done
+    pub report: Option<SensitivityReport>,
+}
+
+impl From<&AcceptedSolution> for SensitivityOutcome {
+    fn from(accepted_solution: &AcceptedSolution) -> Self {
+        SensitivityOutcome {
+            score: accepted_solution.score,
+            distance: accepted_solution.solution.distance(),
+            vehicles: accepted_solution.solution.non_empty_routes_count(),
+            unassigned_jobs: accepted_solution.solution.unassigned_jobs().len(),
+        }
+    }
+}
+
+/// Re-solves the problem for each scenario with a short search time and measures their marginal
+/// effect on the cost, e.g. what one more vehicle buys.
+///
+/// The travel matrices are fetched once and shared by every scenario. The analysis runs as a job
+/// of the API key, its report is fetched once it is done.
+pub async fn sensitivity_handler(
+    State(state): State<Arc<AppState>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    VersionedJson(body): VersionedJson<SensitivityRequest>,
+) -> Result<Json<SensitivityResponse>, ApiError> {
+    state.check_accepting_jobs()?;
+    state.check_problem_size(&body.problem)?;
+
+    let scenarios = body.scenarios.unwrap_or_else(|| {
+        SensitivityScenario::defaults(body.shift_delta.unwrap_or(SignedDuration::from_hours(1)))
+    });
+    if scenarios.len() > MAX_SCENARIOS {
+        return Err(ApiError::BadRequest(format!(
+            "An analysis has at most {MAX_SCENARIOS} scenarios"
+        )));
+    }
+
+    let duration = body.duration.unwrap_or(SignedDuration::from_secs(10));
+    if !duration.is_positive() || duration > MAX_DURATION {
+        return Err(ApiError::BadRequest(format!(
+            "duration must be positive and at most {MAX_DURATION:#}"
+        )));
+    }
+
+    let vehicle_profiles = body
+        .problem
+        .fetch_vehicle_profiles(
+            &state.matrix_client,
+            &state.profiles.travel_time_providers(&state.matrix_cache),
+        )
+        .await?;
+
+    let batch_id = Uuid::new_v4().to_string();
+    let mut inputs = vec![body.problem.clone()];
+    for scenario in &scenarios {
+        let mut input = body.problem.clone();
+        scenario
+            .apply(&mut input)
+            .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+        inputs.push(input);
+    }
+
+    let problems = inputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut input)| {
+            input.id = Some(format!("{batch_id}-{index}"));
+            input
+                .build_problem_with_profiles(vehicle_profiles.clone())
+                .map(Arc::new)
+                .map_err(|error| ApiError::BadRequest(error.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut record = JobRecord::new(
+        batch_id.clone(),
+        JobKind::Sensitivity {
+            scenarios: scenarios.len(),
+            report: None,
+        },
+    );
+    record.state = JobState::Running;
+    if let Some(Extension(tenant)) = &tenant {
+        state
+            .solver_manager
+            .set_owner(&batch_id, tenant.job_owner());
+        record.owner = Some(tenant.name().to_owned());
+    }
+    state.jobs.insert(record).await;
+
+    tokio::spawn(run_analysis(
+        Arc::clone(&state),
+        batch_id.clone(),
+        scenarios,
+        problems,
+        duration,
+    ));
+
+    Ok(Json(SensitivityResponse { job_id: batch_id }))
+}
+
+/// Solves the baseline and the scenarios side by side and persists the report with the job
+async fn run_analysis(
+    state: Arc<AppState>,
+    batch_id: String,
+    scenarios: Vec<SensitivityScenario>,
+    problems: Vec<Arc<VehicleRoutingProblem>>,
+    duration: SignedDuration,
+) {
+    let solutions = state
+        .solver_manager
+        .solve_batch(&batch_id, problems, vec![Termination::Duration(duration)])
+        .await;
+
+    state
+        .jobs
+        .update(&batch_id, |record| {
+            match &solutions {
+                Ok(solutions) => {
+                    record.state = JobState::Done;
+                    if let JobKind::Sensitivity { report, .. } = &mut record.kind {
+                        *report = Some(SensitivityReport::new(&scenarios, solutions));
+                    }
+                }
+                Err(error) => {
+                    record.state = JobState::Failed;
+                    record.failure = Some(error.to_string());
+                }
+            }
+            true
+        })
+        .await;
+}
+
+impl SensitivityReport {
+    /// The first solution is the one of the baseline, the others follow the scenarios
+    fn new(scenarios: &[SensitivityScenario], solutions: &[Option<AcceptedSolution>]) -> Self {
+        let mut outcomes = solutions
+            .iter()
+            .map(|solution| solution.as_ref().map(SensitivityOutcome::from));
+        let baseline = outcomes.next().flatten();
+
+        let scenarios = scenarios
+            .iter()
+            .cloned()
+            .zip(outcomes)
+            .map(|(scenario, outcome)| {
+                let deltas = baseline.as_ref().zip(outcome.as_ref());
+                SensitivityScenarioOutcome {
+                    scenario,
+                    cost_delta: deltas.map(|(baseline, outcome)| {
+                        outcome.score.soft_score - baseline.score.soft_score
+                    }),
+                    distance_delta: deltas.map(|(baseline, outcome)| {
+                        outcome.distance.value() - baseline.distance.value()
+                    }),
+                    unassigned_jobs_delta: deltas.map(|(baseline, outcome)| {
+                        outcome.unassigned_jobs as i64 - baseline.unassigned_jobs as i64
+                    }),
+                    outcome,
+                }
+            })
+            .collect();
+
+        SensitivityReport {
+            baseline,
+            scenarios,
+        }
+    }
+}
+
+/// Report of a sensitivity analysis created by `/vrp/sensitivity`
+pub async fn sensitivity_report_handler(
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SensitivityReportResponse>, ApiError> {
+    let job_id = path.job_id.to_string();
+    let response = state
+        .jobs
+        .read(&job_id, |record| match &record.kind {
+            JobKind::Sensitivity { report, .. } => Some(SensitivityReportResponse {
+                job_id: record.job_id.clone(),
+                state: record.state,
+                failure: record.failure.clone(),
+                report: report.clone(),
+            }),
+            _ => None,
+        })
+        .flatten();
+
+    response.map(Json).ok_or(ApiError::NotFound(job_id))
+}
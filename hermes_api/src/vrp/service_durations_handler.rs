@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use hermes_optimizer::json::service_duration_history::JsonServiceDurationSample;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{state::AppState, versioning::versioned_json::VersionedJson};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ServiceDurationsRequest {
+    samples: Vec<JsonServiceDurationSample>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ServiceDurationsResponse {
+    /// Samples kept for the calibration, over every location and vehicle type
+    sample_count: usize,
+}
+
+/// Records the actual durations of completed stops, the jobs created with
+/// `calibrate_service_durations` use them instead of the planned durations
+pub async fn service_durations_handler(
+    State(state): State<Arc<AppState>>,
+    VersionedJson(body): VersionedJson<ServiceDurationsRequest>,
+) -> Json<ServiceDurationsResponse> {
+    let mut service_durations = state.service_durations.write();
+    for sample in &body.samples {
+        service_durations.add_sample(sample);
+    }
+
+    Json(ServiceDurationsResponse {
+        sample_count: service_durations.sample_count(),
+    })
+}
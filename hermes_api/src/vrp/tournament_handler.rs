@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use hermes_optimizer::{
+    json::{
+        initial_solution::JsonInitialSolution,
+        plan_tournament::{PlanEvaluation, evaluate_plans},
+        types::JsonVehicleRoutingProblem,
+    },
+    solver::solver_params::SolverParams,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    auth::api_keys::Tenant,
+    error::ApiError,
+    state::AppState,
+    versioning::versioned_json::VersionedJson,
+    vrp::job_store::{JobKind, JobRecord, JobState},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TournamentRequest {
+    #[serde(flatten)]
+    problem: JsonVehicleRoutingProblem,
+
+    /// Plans of the problem generated outside of the solver, in the format of the initial solutions
+    plans: Vec<JsonInitialSolution>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct TournamentResponse {
+    /// The ranking is listed with the other jobs by `/vrp/jobs`
+    pub job_id: String,
+    /// Best plan first, the plans which don't match the problem come last
+    pub evaluations: Vec<PlanEvaluation>,
+}
+
+/// Scores and ranks externally generated plans of the same problem without solving it.
+///
+/// The travel matrices are fetched once and shared by every plan, the plans are scored with the
+/// constraints and objective of a job created from the same problem.
+pub async fn tournament_handler(
+    State(state): State<Arc<AppState>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    VersionedJson(body): VersionedJson<TournamentRequest>,
+) -> Result<Json<TournamentResponse>, ApiError> {
+    state.check_problem_size(&body.problem)?;
+
+    let problem = Arc::new(
+        body.problem
+            .build_problem_with_providers(
+                &state.matrix_client,
+                &state.profiles.travel_time_providers(&state.matrix_cache),
+            )
+            .await?,
+    );
+
+    let job_id = Uuid::new_v4().to_string();
+    let mut record = JobRecord::new(
+        job_id.clone(),
+        JobKind::Tournament {
+            plans: body.plans.len(),
+        },
+    );
+    record.state = JobState::Running;
+    record.owner = tenant.map(|Extension(tenant)| tenant.name().to_owned());
+    state.jobs.insert(record).await;
+
+    let plans = body.plans;
+    let result = tokio::task::spawn_blocking(move || {
+        let params = SolverParams::default_from_problem(&problem);
+        let constraints = params.objective.apply(&params.constraints);
+
+        evaluate_plans(&problem, &plans, constraints.constraints())
+    })
+    .await;
+
+    state
+        .jobs
+        .update(&job_id, |record| {
+            match &result {
+                Ok(_) => record.state = JobState::Done,
+                Err(error) => {
+                    record.state = JobState::Failed;
+                    record.failure = Some(error.to_string());
+                }
+            }
+            true
+        })
+        .await;
+
+    let evaluations = result.map_err(|error| ApiError::InternalServerError(error.to_string()))?;
+
+    Ok(Json(TournamentResponse {
+        job_id,
+        evaluations,
+    }))
+}
@@ -0,0 +1,157 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use hermes_optimizer::{
+    json::problem_update::JsonProblemUpdate, problem::job::JobIdx,
+    solver::solver_manager::SolverManager,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    error::ApiError,
+    state::AppState,
+    trace::trace_parent::{TraceParent, job_span},
+    versioning::versioned_json::VersionedJson,
+    vrp::job::JobPath,
+};
+
+/// Longest wait for the search of the replaced solver to return before the job is started again
+const REPLACED_SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, JsonSchema)]
+pub struct UpdateResponse {
+    job_id: String,
+}
+
+/// Patches the problem of a job and restarts the search from its current solutions.
+///
+/// Cancelled jobs are removed from the routes, the jobs of unavailable vehicles and the new jobs
+/// start unassigned and are inserted by the search. Dispatched activities are locked in their route.
+/// An update which only cancels jobs is applied to the running search without restarting it.
+pub async fn update_handler(
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+    trace_parent: Option<Extension<TraceParent>>,
+    VersionedJson(update): VersionedJson<JsonProblemUpdate>,
+) -> Result<Json<UpdateResponse>, ApiError> {
+    let job_id = reoptimize_job(
+        &state,
+        &path.job_id.to_string(),
+        std::slice::from_ref(&update),
+        trace_parent.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(UpdateResponse { job_id }))
+}
+
+/// Applies the updates in order to the problem of the job and restarts the search from the
+/// solutions of its current population, see `update_handler`. Updates which only cancel jobs are
+/// applied to the running search instead. The job keeps its ID, which is returned
+pub async fn reoptimize_job(
+    state: &AppState,
+    job_id: &str,
+    updates: &[JsonProblemUpdate],
+    trace_parent: Option<&TraceParent>,
+) -> Result<String, ApiError> {
+    let solver_manager = &state.solver_manager;
+    state.check_accepting_jobs()?;
+
+    let solver = solver_manager
+        .solver(job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(job_id.to_owned()))?;
+
+    let mut input = state
+        .job_inputs
+        .read()
+        .await
+        .get(job_id)
+        .cloned()
+        .ok_or_else(|| ApiError::NotFound(job_id.to_owned()))?;
+
+    for update in updates {
+        update
+            .apply(&mut input)
+            .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+    }
+    // The updates add locations, the input is kept to apply the next ones
+    state.check_problem_size(&input)?;
+
+    if updates.iter().all(JsonProblemUpdate::only_cancels_jobs) {
+        let problem = solver.problem();
+        let cancelled_jobs = updates
+            .iter()
+            .flat_map(JsonProblemUpdate::cancelled_job_ids)
+            .filter_map(|external_id| {
+                problem
+                    .jobs()
+                    .iter()
+                    .position(|job| job.external_id() == external_id)
+                    .map(JobIdx::new)
+            })
+            .collect::<Vec<_>>();
+
+        solver.cancel_jobs(cancelled_jobs);
+        state.jobs.update_input(job_id, input.clone()).await;
+        state
+            .job_inputs
+            .write()
+            .await
+            .insert(job_id.to_owned(), input);
+
+        return Ok(job_id.to_owned());
+    }
+
+    // The job keeps searching while the updated problem is built, it is only replaced once the
+    // update succeeded. Keep the same job ID for the updated problem
+    input.id = Some(job_id.to_owned());
+    let problem = Arc::new(
+        input
+            .clone()
+            .build_problem_with_providers(
+                &state.matrix_client,
+                &state.profiles.travel_time_providers(&state.matrix_cache),
+            )
+            .await?,
+    );
+
+    // Every solution of the population is kept, not only the best one
+    let mut previous_solutions = solver.json_checkpoint().solutions;
+    for previous_solution in &mut previous_solutions {
+        for update in updates {
+            update.apply_to_solution(previous_solution);
+        }
+    }
+
+    let initial_solutions = previous_solutions
+        .iter()
+        .map(|previous_solution| previous_solution.build_solution(Arc::clone(&problem)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| ApiError::BadRequest(error.to_string()))?;
+
+    state.annotate_location_areas(&problem);
+
+    let span = job_span(job_id, trace_parent);
+    let new_solver = SolverManager::job_solver(problem, None, span);
+    for initial_solution in initial_solutions {
+        new_solver.set_initial_solution(initial_solution);
+    }
+    // The replaced solver is stopped
+    let job_id = solver_manager.insert_job(new_solver).await;
+    state.jobs.update_input(&job_id, input.clone()).await;
+    state.job_inputs.write().await.insert(job_id.clone(), input);
+
+    // The search of the replaced solver counts against the limit of the owner until it returns
+    let deadline = tokio::time::Instant::now() + REPLACED_SEARCH_TIMEOUT;
+    while solver_manager.is_searching(&job_id) && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    solver_manager.start(&job_id).await?;
+
+    Ok(job_id)
+}
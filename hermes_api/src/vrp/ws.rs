@@ -1,21 +1,216 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use axum::{
     extract::{
-        State, WebSocketUpgrade,
-        ws::{Message, WebSocket},
+        Path, State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket, close_code},
     },
     response::Response,
 };
+use hermes_optimizer::solver::{
+    events::SolverEvent, solver::SolverStatus, statistics::SearchTelemetry,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::broadcast::{Receiver, error::RecvError},
+    time::Interval,
+};
+
+use crate::{shutdown::shutting_down, state::AppState, vrp::job::JobPath};
+
+const DEFAULT_TELEMETRY_INTERVAL_SECONDS: u64 = 5;
+const DEFAULT_PROGRESS_INTERVAL_SECONDS: u64 = 1;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Channel {
+    /// Per-thread operator weights, acceptance rates and scores of the population
+    Telemetry,
+    /// Construction, best solutions, weights updates, progress and termination of the solver
+    Events,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe {
+        channel: Channel,
+        interval_seconds: Option<u64>,
+    },
+    Unsubscribe {
+        channel: Channel,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum ServerMessage {
+    Telemetry(SearchTelemetry),
+    Event(SolverEvent),
+    Error(String),
+    /// Last message before the server closes the socket to shut down, the job is persisted with
+    /// this status and continues after the restart when it was running
+    Shutdown {
+        status: Option<SolverStatus>,
+    },
+}
+
+pub async fn handler(
+    ws: WebSocketUpgrade,
+    Path(path): Path<JobPath>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let job_id = path.job_id.to_string();
+    ws.on_upgrade(|socket| handle_socket(socket, state, job_id))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: Arc<AppState>, job_id: String) {
+    let mut telemetry_interval: Option<Interval> = None;
+    let mut progress_interval: Option<Interval> = None;
+    let mut events: Option<Receiver<SolverEvent>> = None;
+    let mut shutdown = state.shutdown.subscribe();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+
+                match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe {
+                        channel: Channel::Telemetry,
+                        interval_seconds,
+                    }) => {
+                        let seconds = interval_seconds
+                            .unwrap_or(DEFAULT_TELEMETRY_INTERVAL_SECONDS)
+                            .max(1);
+                        telemetry_interval =
+                            Some(tokio::time::interval(Duration::from_secs(seconds)));
+                    }
+                    Ok(ClientMessage::Subscribe {
+                        channel: Channel::Events,
+                        interval_seconds,
+                    }) => match state.solver_manager.solver(&job_id).await {
+                        Some(solver) => {
+                            let seconds = interval_seconds
+                                .unwrap_or(DEFAULT_PROGRESS_INTERVAL_SECONDS)
+                                .max(1);
+                            events = Some(solver.subscribe_events());
+                            progress_interval =
+                                Some(tokio::time::interval(Duration::from_secs(seconds)));
+                        }
+                        None => {
+                            let message = ServerMessage::Error(format!("Job {job_id} not found"));
+                            if send(&mut socket, &message).await.is_err() {
+                                break;
+                            }
+                        }
+                    },
+                    Ok(ClientMessage::Unsubscribe {
+                        channel: Channel::Telemetry,
+                    }) => {
+                        telemetry_interval = None;
+                    }
+                    Ok(ClientMessage::Unsubscribe {
+                        channel: Channel::Events,
+                    }) => {
+                        events = None;
+                        progress_interval = None;
+                    }
+                    Err(error) => {
+                        if send(&mut socket, &ServerMessage::Error(error.to_string()))
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ = tick(&mut telemetry_interval) => {
+                let message = match state.solver_manager.solver(&job_id).await {
+                    Some(solver) => ServerMessage::Telemetry(solver.telemetry()),
+                    None => ServerMessage::Error(format!("Job {job_id} not found")),
+                };
+
+                if send(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+            event = next_event(&mut events) => {
+                let message = match event {
+                    Ok(event) => ServerMessage::Event(event),
+                    // The subscriber was too slow, the missed events are dropped
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => {
+                        events = None;
+                        progress_interval = None;
+                        continue;
+                    }
+                };
+
+                if send(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+            _ = shutting_down(&mut shutdown) => {
+                let status = state
+                    .solver_manager
+                    .solver(&job_id)
+                    .await
+                    .map(|solver| solver.status());
+
+                if send(&mut socket, &ServerMessage::Shutdown { status }).await.is_ok() {
+                    let _ = close(&mut socket).await;
+                }
+                break;
+            }
+            _ = tick(&mut progress_interval) => {
+                let message = match state.solver_manager.solver(&job_id).await {
+                    Some(solver) => ServerMessage::Event(solver.progress()),
+                    None => ServerMessage::Error(format!("Job {job_id} not found")),
+                };
 
-use crate::state::AppState;
+                if send(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
 
-pub async fn handler(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+/// Waits for the next tick of the interval, or forever when not subscribed
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
 }
 
-async fn handle_socket(mut socket: WebSocket, _state: Arc<AppState>) {
-    while let Some(Ok(msg)) = socket.recv().await {
-        if let Message::Text(_msg) = msg {}
+/// Waits for the next solver event, or forever when not subscribed
+async fn next_event(events: &mut Option<Receiver<SolverEvent>>) -> Result<SolverEvent, RecvError> {
+    match events {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
     }
 }
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("Failed to serialize websocket message");
+    socket.send(Message::Text(text.into())).await
+}
+
+/// Closes the socket with the `going away` code of a server shutting down
+pub async fn close(socket: &mut WebSocket) -> Result<(), axum::Error> {
+    socket
+        .send(Message::Close(Some(CloseFrame {
+            code: close_code::AWAY,
+            reason: "Server shutting down".into(),
+        })))
+        .await
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use hermes_routing::{hermes::Hermes, osm::osm_change::OsmChange};
+use tracing::info;
+
+#[derive(Args)]
+pub struct ApplyOsmChangeArgs {
+    /// Directory of the imported graph
+    #[arg(short = 'g', long)]
+    graph: PathBuf,
+
+    /// osmChange file (.osc or .osc.gz)
+    #[arg(short = 'c', long)]
+    change: PathBuf,
+
+    /// Directory where the updated graph is written, the graph directory by default
+    #[arg(short = 'o', long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: ApplyOsmChangeArgs) -> anyhow::Result<()> {
+    let change = OsmChange::from_file(&args.change)?;
+    let mut hermes = Hermes::from_directory(&args.graph.to_string_lossy())?;
+
+    let summary = hermes.apply_osm_change(&change);
+
+    let out = args.out.unwrap_or(args.graph);
+    std::fs::create_dir_all(&out)?;
+    hermes.save(&out.to_string_lossy())?;
+
+    info!(
+        "Updated {} edges of {} modified and {} deleted ways into {}",
+        summary.updated_edges,
+        summary.modified_ways,
+        summary.deleted_ways,
+        out.display()
+    );
+    if summary.skipped_ways > 0 || change.node_changes > 0 || change.relation_changes > 0 {
+        info!(
+            "Skipped {} ways, {} nodes and {} relations, they need a full import",
+            summary.skipped_ways, change.node_changes, change.relation_changes
+        );
+    }
+
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use std::{
     collections::HashMap,
     fs::File,
-    io::{BufReader, BufWriter},
-    path::PathBuf,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
 };
 
 use clap::{Args, Subcommand};
@@ -56,9 +56,18 @@ pub struct RunBenchmarkArgs {
     #[arg(long, short = 'n')]
     iterations: Option<usize>,
 
-    /// Output folder into .sol files
+    /// Output folder of the results, written to `<name>.json`
     #[arg(long, short = 'o')]
     out: PathBuf,
+
+    /// Also write the results to `<name>.csv`
+    #[arg(long)]
+    csv: bool,
+
+    /// Keep the instances already in `<name>.json` and only run the other ones, e.g. after an
+    /// interrupted run. The results are saved after each instance
+    #[arg(long)]
+    resume: bool,
 }
 
 #[derive(Args)]
@@ -122,7 +131,15 @@ fn run_benchmark(args: RunBenchmarkArgs) -> Result<(), anyhow::Error> {
         files
     };
 
-    let mut benchmark_run = BenchmarkRun::default();
+    let mut out_path = args.out.clone();
+    out_path.push(&args.name);
+    out_path.set_extension("json");
+
+    let mut benchmark_run = if args.resume && out_path.exists() {
+        read_benchmark_run(out_path.clone())?
+    } else {
+        BenchmarkRun::default()
+    };
 
     let style = ProgressStyle::with_template("{msg}").unwrap();
 
@@ -134,6 +151,19 @@ fn run_benchmark(args: RunBenchmarkArgs) -> Result<(), anyhow::Error> {
             .map(|ext| ext == "txt" || ext == "vrp")
             .unwrap_or(false)
     }) {
+        let instance_name = path
+            .strip_prefix("./data")
+            .or_else(|_| path.strip_prefix("data"))
+            .unwrap_or(path)
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/")
+            .to_string();
+
+        if benchmark_run.instances.contains_key(&instance_name) {
+            continue;
+        }
+
         // Try to load an accompanying .sol file for optimal solution reference
         let mut solution_path = path.clone();
         solution_path.set_extension("sol");
@@ -144,15 +174,6 @@ fn run_benchmark(args: RunBenchmarkArgs) -> Result<(), anyhow::Error> {
             parse_bks_for_file(path).ok()
         };
 
-        let instance_name = path
-            .strip_prefix("./data")
-            .or_else(|_| path.strip_prefix("data"))
-            .unwrap_or(path)
-            .with_extension("")
-            .to_string_lossy()
-            .replace('\\', "/")
-            .to_string();
-
         progress_bar.set_message(format!(
             "Running benchmark instance {} ({}/{})",
             instance_name,
@@ -185,11 +206,12 @@ fn run_benchmark(args: RunBenchmarkArgs) -> Result<(), anyhow::Error> {
             insertion_threads: Threads::Multi(args.ithreads as usize),
             debug_options: SolverParamsDebugOptions {
                 enable_local_search: true,
+                verify_after_local_search: false,
             },
             ..SolverParams::default_from_problem(&vrp)
         };
 
-        let solver = Solver::new(vrp, solver_params);
+        let solver = Solver::new(vrp, solver_params)?;
 
         let result = solver.solve()?;
         let best_solution = result
@@ -210,15 +232,189 @@ fn run_benchmark(args: RunBenchmarkArgs) -> Result<(), anyhow::Error> {
         benchmark_run
             .instances
             .insert(instance_name, instance_result);
+
+        // An interrupted run can be resumed from the instances completed so far
+        write_benchmark_run(&benchmark_run, &out_path)?;
     }
 
-    let mut out_path = args.out.clone();
-    out_path.push(args.name);
-    out_path.set_extension("json");
+    progress_bar.finish_and_clear();
+    print_run_table(&benchmark_run);
+
+    write_benchmark_run(&benchmark_run, &out_path)?;
+
+    if args.csv {
+        out_path.set_extension("csv");
+        write_run_csv(&benchmark_run, &out_path)?;
+    }
+
+    Ok(())
+}
+
+/// Instances sorted by name so that the table and CSV of two runs can be compared line by line
+fn sorted_instances(run: &BenchmarkRun) -> Vec<&InstanceResult> {
+    let mut instances: Vec<_> = run.instances.values().collect();
+    instances.sort_by(|a, b| a.instance.cmp(&b.instance));
+    instances
+}
+
+#[derive(Debug, PartialEq)]
+struct RunSummary {
+    pub instances: usize,
+    pub feasible: usize,
+    pub bks_reached: usize,
+    /// Only the instances with a best known solution have a gap
+    pub mean_gap_percent: Option<f64>,
+    pub max_gap_percent: Option<f64>,
+    pub total_duration: SignedDuration,
+}
+
+fn summarize_run(run: &BenchmarkRun) -> RunSummary {
+    let gaps: Vec<f64> = run
+        .instances
+        .values()
+        .filter_map(InstanceResult::gap_percent)
+        .collect();
+
+    RunSummary {
+        instances: run.instances.len(),
+        feasible: run
+            .instances
+            .values()
+            .filter(|result| result.feasible)
+            .count(),
+        bks_reached: run
+            .instances
+            .values()
+            .filter(|result| result.is_bks())
+            .count(),
+        mean_gap_percent: if gaps.is_empty() {
+            None
+        } else {
+            Some(gaps.iter().sum::<f64>() / gaps.len() as f64)
+        },
+        max_gap_percent: gaps.iter().copied().reduce(f64::max),
+        total_duration: run.instances.values().map(|result| result.duration).sum(),
+    }
+}
+
+fn format_gap(gap: Option<f64>) -> String {
+    gap.map(|gap| format!("{:+.2}%", gap))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn print_run_table(run: &BenchmarkRun) {
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Instance",
+        "Cost",
+        "BKS",
+        "Gap%",
+        "Vehicles",
+        "BKS Vehicles",
+        "Iterations",
+        "Duration",
+    ]);
+
+    for result in sorted_instances(run) {
+        let color = if !result.feasible {
+            Some(Color::Red)
+        } else if result.is_bks() {
+            Some(Color::Green)
+        } else {
+            None
+        };
+
+        let mut row = vec![
+            Cell::new(&result.instance),
+            Cell::new(format!("{:.1}", result.cost)),
+            Cell::new(
+                result
+                    .bks
+                    .map(|bks| format!("{:.1}", bks.cost))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::new(format_gap(result.gap_percent())),
+            Cell::new(result.vehicles),
+            Cell::new(
+                result
+                    .bks
+                    .map(|bks| bks.vehicles.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::new(result.iterations),
+            Cell::new(format!("{:#}", result.duration)),
+        ];
+
+        if let Some(color) = color {
+            row = row.into_iter().map(|c| c.fg(color)).collect();
+        }
+
+        table.add_row(row);
+    }
+
+    let summary = summarize_run(run);
+
+    println!("{table}");
+    println!(
+        "\n{} instance(s), {} feasible, {} at BKS, mean gap {}, max gap {}, total duration {:#}",
+        summary.instances,
+        summary.feasible,
+        summary.bks_reached,
+        format_gap(summary.mean_gap_percent),
+        format_gap(summary.max_gap_percent),
+        summary.total_duration,
+    );
+}
+
+fn write_run_csv(run: &BenchmarkRun, path: &Path) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(
+        writer,
+        "instance,cost,vehicles,bks_cost,bks_vehicles,gap_percent,feasible,iterations,duration_ms"
+    )?;
+
+    for result in sorted_instances(run) {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            result.instance,
+            result.cost,
+            result.vehicles,
+            result
+                .bks
+                .map(|bks| bks.cost.to_string())
+                .unwrap_or_default(),
+            result
+                .bks
+                .map(|bks| bks.vehicles.to_string())
+                .unwrap_or_default(),
+            result
+                .gap_percent()
+                .map(|gap| gap.to_string())
+                .unwrap_or_default(),
+            result.feasible,
+            result.iterations,
+            result.duration.as_millis(),
+        )?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Writes the run to a temporary file first, an interruption while writing leaves the previous
+/// results intact
+fn write_benchmark_run(run: &BenchmarkRun, path: &Path) -> anyhow::Result<()> {
+    let temporary_path = path.with_extension("json.tmp");
+
+    let mut writer = BufWriter::new(File::create(&temporary_path)?);
+    serde_json::to_writer_pretty(&mut writer, run)?;
+    writer.flush()?;
+    drop(writer);
 
-    let file = File::create(out_path)?;
-    let writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(writer, &benchmark_run)?;
+    std::fs::rename(temporary_path, path)?;
 
     Ok(())
 }
@@ -337,3 +533,76 @@ fn compare_benchmarks(args: CompareBenchmarkArgs) -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use hermes_optimizer::parsers::cvrplib::Bks;
+    use jiff::SignedDuration;
+
+    use super::{
+        BenchmarkRun, InstanceResult, read_benchmark_run, summarize_run, write_benchmark_run,
+    };
+
+    fn instance_result(instance: &str, cost: f64, bks: Option<Bks>) -> InstanceResult {
+        InstanceResult {
+            instance: instance.to_string(),
+            cost,
+            vehicles: 2,
+            duration: SignedDuration::from_secs(1),
+            feasible: true,
+            iterations: 100,
+            bks,
+        }
+    }
+
+    #[test]
+    fn test_summarize_run() {
+        let mut run = BenchmarkRun::default();
+        for result in [
+            instance_result(
+                "a",
+                100.0,
+                Some(Bks {
+                    vehicles: 2,
+                    cost: 100.0,
+                }),
+            ),
+            instance_result(
+                "b",
+                110.0,
+                Some(Bks {
+                    vehicles: 2,
+                    cost: 100.0,
+                }),
+            ),
+            instance_result("c", 50.0, None),
+        ] {
+            run.instances.insert(result.instance.clone(), result);
+        }
+
+        let summary = summarize_run(&run);
+
+        assert_eq!(summary.instances, 3);
+        assert_eq!(summary.feasible, 3);
+        assert_eq!(summary.bks_reached, 1);
+        assert_eq!(summary.mean_gap_percent, Some(5.0));
+        assert_eq!(summary.max_gap_percent, Some(10.0));
+        assert_eq!(summary.total_duration, SignedDuration::from_secs(3));
+    }
+
+    #[test]
+    fn test_saved_run_can_be_resumed() {
+        let path = std::env::temp_dir().join(format!("benchmark_{}.json", std::process::id()));
+
+        let mut run = BenchmarkRun::default();
+        run.instances
+            .insert(String::from("a"), instance_result("a", 100.0, None));
+        write_benchmark_run(&run, &path).unwrap();
+
+        let resumed = read_benchmark_run(path.clone()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(resumed.instances.len(), 1);
+        assert_eq!(resumed.instances["a"].cost, 100.0);
+    }
+}
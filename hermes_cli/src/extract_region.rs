@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use hermes_routing::{graph::Graph, hermes::Hermes, region::read_region_file};
+use tracing::info;
+
+#[derive(Args)]
+pub struct ExtractRegionArgs {
+    /// Directory of the imported graph
+    #[arg(short = 'g', long)]
+    graph: PathBuf,
+
+    /// GeoJSON file with the Polygon or MultiPolygon of the region to keep
+    #[arg(short = 'r', long)]
+    region: PathBuf,
+
+    /// Directory where the graph of the region is written
+    #[arg(short = 'o', long)]
+    out: PathBuf,
+}
+
+pub fn run(args: ExtractRegionArgs) -> anyhow::Result<()> {
+    let region = read_region_file(&args.region.to_string_lossy())?;
+    let hermes = Hermes::from_directory(&args.graph.to_string_lossy())?;
+
+    std::fs::create_dir_all(&args.out)?;
+    let extracted = hermes.extract_region(&region, &args.out.to_string_lossy())?;
+
+    info!(
+        "Extracted {} of {} edges into {}",
+        extracted.graph().edge_count(),
+        hermes.graph().edge_count(),
+        args.out.display()
+    );
+
+    Ok(())
+}
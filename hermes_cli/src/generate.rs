@@ -1,6 +1,12 @@
-use std::path::PathBuf;
+use std::{fs::File, io::BufWriter, path::PathBuf};
 
-use clap::Subcommand;
+use clap::{Args, Subcommand, ValueEnum};
+use hermes_optimizer::json::generator::{
+    CustomerDistribution, DemandDistribution, GeneratorParams, generate_instance,
+};
+use jiff::{SignedDuration, Timestamp};
+
+use crate::parsers;
 
 #[derive(Subcommand)]
 pub enum GenerateSubcommands {
@@ -9,6 +15,122 @@ pub enum GenerateSubcommands {
         #[arg(long, short = 'o')]
         out: PathBuf,
     },
+    /// Writes a synthetic instance in the JSON format of the optimizer, the same arguments always
+    /// generate the same instance
+    Instance {
+        #[command(flatten)]
+        args: GenerateInstanceArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct GenerateInstanceArgs {
+    #[arg(long, short = 'o')]
+    out: PathBuf,
+
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    #[arg(long, short = 'n', default_value_t = 100)]
+    customers: usize,
+
+    #[arg(long, value_enum, default_value_t = Distribution::Uniform)]
+    distribution: Distribution,
+
+    /// Number of clusters of the `clustered` distribution
+    #[arg(long, default_value_t = 5)]
+    clusters: usize,
+
+    /// Depot longitude
+    #[arg(long, default_value_t = 4.3517)]
+    lon: f64,
+
+    /// Depot latitude
+    #[arg(long, default_value_t = 50.8503)]
+    lat: f64,
+
+    #[arg(long, default_value_t = 20.0)]
+    radius_km: f64,
+
+    #[arg(long, default_value = "2025-01-06T08:00:00Z")]
+    start: Timestamp,
+
+    #[arg(long, value_parser = parsers::parse_duration, default_value = "10h")]
+    horizon: SignedDuration,
+
+    /// Width of the time windows as a share of the horizon, from 0 (tight) to 1, no time windows
+    /// when omitted
+    #[arg(long)]
+    tw_tightness: Option<f64>,
+
+    #[arg(long, value_parser = parsers::parse_duration, default_value = "5m")]
+    service_duration: SignedDuration,
+
+    #[arg(long, value_enum, default_value_t = Demand::Uniform)]
+    demand: Demand,
+
+    #[arg(long, default_value_t = 1.0)]
+    min_demand: f64,
+
+    #[arg(long, default_value_t = 10.0)]
+    max_demand: f64,
+
+    #[arg(long, default_value_t = 10)]
+    vehicles: usize,
+
+    /// Capacities of the vehicles, cycled over the fleet, e.g. `100,200` for half small and half
+    /// large vehicles
+    #[arg(long, value_delimiter = ',', default_value = "100")]
+    capacities: Vec<f64>,
+
+    #[arg(long, default_value_t = 40.0)]
+    speed_kmh: f64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Distribution {
+    Uniform,
+    Clustered,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Demand {
+    /// Every customer has `max-demand`
+    Constant,
+    Uniform,
+    /// Mostly small demands with a few large ones
+    Skewed,
+}
+
+impl GenerateInstanceArgs {
+    fn generator_params(&self) -> GeneratorParams {
+        let (min, max) = (self.min_demand, self.max_demand);
+
+        GeneratorParams {
+            seed: self.seed,
+            customers: self.customers,
+            distribution: match self.distribution {
+                Distribution::Uniform => CustomerDistribution::Uniform,
+                Distribution::Clustered => CustomerDistribution::Clustered {
+                    clusters: self.clusters,
+                },
+            },
+            center: [self.lon, self.lat],
+            radius_km: self.radius_km,
+            horizon_start: self.start,
+            horizon: self.horizon,
+            time_window_tightness: self.tw_tightness,
+            service_duration: self.service_duration,
+            demand: match self.demand {
+                Demand::Constant => DemandDistribution::Constant(max),
+                Demand::Uniform => DemandDistribution::Uniform { min, max },
+                Demand::Skewed => DemandDistribution::Skewed { min, max },
+            },
+            vehicle_capacities: self.capacities.clone(),
+            vehicles: self.vehicles,
+            speed_kmh: self.speed_kmh,
+        }
+    }
 }
 
 pub fn run(subcommand: GenerateSubcommands) -> Result<(), anyhow::Error> {
@@ -22,6 +144,16 @@ pub fn run(subcommand: GenerateSubcommands) -> Result<(), anyhow::Error> {
 
             std::fs::write(out, schema)?;
         }
+        GenerateSubcommands::Instance { args } => {
+            let instance = generate_instance(&args.generator_params());
+
+            if let Some(parent) = args.out.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            let writer = BufWriter::new(File::create(&args.out)?);
+            serde_json::to_writer_pretty(writer, &instance)?;
+        }
     }
 
     Ok(())
@@ -24,10 +24,8 @@ async fn fetch_matrix(
     let content: JsonVehicleRoutingProblem = serde_json::from_reader(reader)?;
 
     for profile in content.vehicle_profiles {
-        let cost_provider = profile.cost_provider;
-
         client
-            .fetch_matrix(&content.locations, cost_provider)
+            .fetch_matrix(&content.locations, profile.cost_provider())
             .await?;
     }
 
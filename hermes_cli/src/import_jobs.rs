@@ -0,0 +1,494 @@
+use std::{collections::HashMap, fs::File, io::BufWriter, path::PathBuf};
+
+use clap::Args;
+use hermes_optimizer::{
+    json::types::{JsonLocation, JsonService, JsonVehicleProfile, JsonVehicleRoutingProblem},
+    problem::time_window::TimeWindow,
+};
+use jiff::{SignedDuration, Timestamp, civil::DateTime, tz::TimeZone};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::parsers;
+
+#[derive(Args)]
+pub struct ImportJobsArgs {
+    /// CSV export of the orders, with a header row
+    csv: PathBuf,
+
+    /// TOML file mapping the columns of the CSV to the fields of the jobs
+    #[arg(long, short = 'm')]
+    mapping: PathBuf,
+
+    /// JSON problem written with the imported jobs, the vehicles are left to add
+    #[arg(long, short = 'o')]
+    out: PathBuf,
+
+    /// Vehicle profile added to the problem, e.g. one of the routing profiles of the API
+    #[arg(long, default_value = "car")]
+    profile: String,
+
+    /// Writes the valid rows when some rows are invalid instead of failing
+    #[arg(long)]
+    skip_invalid: bool,
+}
+
+/// Columns of the CSV, e.g.
+///
+/// ```toml
+/// id = "order_id"
+/// lat = "latitude"
+/// lon = "longitude"
+/// demand = ["weight", "volume"]
+/// duration = "service_minutes"
+/// duration_unit = "minutes"
+/// time_window_start = "from"
+/// time_window_end = "to"
+/// time_zone = "Europe/Brussels"
+/// ```
+///
+/// Only the id and coordinates are required. The time windows are either timestamps, or local
+/// date times in `time_zone`. The durations are numbers in `duration_unit`, seconds by default,
+/// or durations like `5m`. The orders must be geocoded, addresses are not supported
+struct ColumnMapping {
+    id: String,
+    lat: String,
+    lon: String,
+    demand: Vec<String>,
+    duration: Option<String>,
+    duration_unit: SignedDuration,
+    time_window_start: Option<String>,
+    time_window_end: Option<String>,
+    time_zone: TimeZone,
+    delimiter: char,
+}
+
+/// Content of the mapping file, see [`ColumnMapping`]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnMappingFile {
+    id: String,
+    lat: String,
+    lon: String,
+    demand: Option<DemandColumns>,
+    duration: Option<String>,
+    #[serde(default)]
+    duration_unit: DurationUnit,
+    time_window_start: Option<String>,
+    time_window_end: Option<String>,
+    time_zone: Option<String>,
+    delimiter: Option<String>,
+}
+
+/// A column per dimension of the demand, as an array or a comma separated list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DemandColumns {
+    List(String),
+    Array(Vec<String>),
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum DurationUnit {
+    #[default]
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+impl ColumnMapping {
+    fn parse(content: &str) -> anyhow::Result<ColumnMapping> {
+        let file = toml::from_str::<ColumnMappingFile>(content)?;
+
+        Ok(ColumnMapping {
+            id: file.id,
+            lat: file.lat,
+            lon: file.lon,
+            demand: match file.demand {
+                Some(DemandColumns::List(columns)) => columns
+                    .split(',')
+                    .map(|column| column.trim().to_owned())
+                    .collect(),
+                Some(DemandColumns::Array(columns)) => columns,
+                None => vec![],
+            },
+            duration: file.duration,
+            duration_unit: match file.duration_unit {
+                DurationUnit::Seconds => SignedDuration::from_secs(1),
+                DurationUnit::Minutes => SignedDuration::from_mins(1),
+                DurationUnit::Hours => SignedDuration::from_hours(1),
+            },
+            time_window_start: file.time_window_start,
+            time_window_end: file.time_window_end,
+            time_zone: match file.time_zone {
+                Some(time_zone) => TimeZone::get(&time_zone)?,
+                None => TimeZone::UTC,
+            },
+            delimiter: match file.delimiter.as_deref() {
+                None => ',',
+                Some("\\t") => '\t',
+                Some(delimiter) if delimiter.chars().count() == 1 => {
+                    delimiter.chars().next().unwrap()
+                }
+                Some(delimiter) => anyhow::bail!("The delimiter `{delimiter}` is not a character"),
+            },
+        })
+    }
+}
+
+/// Fields of a CSV line, the quoted fields can contain the delimiter and `""` for a quote but
+/// not line breaks
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            char if char == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            char => field.push(char),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+struct CsvRow<'a> {
+    header: &'a HashMap<String, usize>,
+    fields: Vec<String>,
+}
+
+impl CsvRow<'_> {
+    /// Trimmed value of the column, `None` when empty
+    fn get(&self, column: &str) -> Result<Option<&str>, String> {
+        let index = self
+            .header
+            .get(column)
+            .ok_or_else(|| format!("no column `{column}`"))?;
+
+        Ok(self
+            .fields
+            .get(*index)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty()))
+    }
+
+    fn required(&self, column: &str) -> Result<&str, String> {
+        self.get(column)?
+            .ok_or_else(|| format!("`{column}` is empty"))
+    }
+
+    /// Finite number, `inf` and `NaN` are rejected
+    fn number(&self, column: &str) -> Result<f64, String> {
+        let value = self.required(column)?;
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| format!("`{column}` is not a number: {value}"))
+    }
+}
+
+fn parse_timestamp(value: &str, time_zone: &TimeZone) -> Result<Timestamp, String> {
+    if let Ok(timestamp) = value.parse::<Timestamp>() {
+        return Ok(timestamp);
+    }
+
+    value
+        .parse::<DateTime>()
+        .and_then(|date_time| date_time.to_zoned(time_zone.clone()))
+        .map(|zoned| zoned.timestamp())
+        .map_err(|_| format!("invalid date time: {value}"))
+}
+
+/// Service of a row, the locations are shared by the rows with the same coordinates. `ids` are
+/// the lines of the ids already imported
+fn parse_row(
+    row: &CsvRow,
+    mapping: &ColumnMapping,
+    ids: &HashMap<String, usize>,
+    location_ids: &mut HashMap<(u64, u64), usize>,
+    locations: &mut Vec<JsonLocation>,
+) -> Result<JsonService, String> {
+    let id = row.required(&mapping.id)?;
+    if let Some(line) = ids.get(id) {
+        return Err(format!("duplicate id `{id}`, already used on line {line}"));
+    }
+
+    let coordinates = [row.number(&mapping.lon)?, row.number(&mapping.lat)?];
+    if !(-180.0..=180.0).contains(&coordinates[0]) || !(-90.0..=90.0).contains(&coordinates[1]) {
+        return Err(format!("invalid coordinates: {coordinates:?}"));
+    }
+
+    let duration = match &mapping.duration {
+        Some(column) => row
+            .get(column)?
+            .map(|value| {
+                let duration = match value.parse::<f64>() {
+                    Ok(number) => SignedDuration::try_from_secs_f64(
+                        number * mapping.duration_unit.as_secs_f64(),
+                    )
+                    .map_err(|_| format!("`{column}` is out of range: {value}"))?,
+                    Err(_) => parsers::parse_duration(value)
+                        .map_err(|_| format!("`{column}` is not a duration: {value}"))?,
+                };
+
+                if duration.is_negative() {
+                    return Err(format!("`{column}` is negative: {value}"));
+                }
+
+                Ok(duration)
+            })
+            .transpose()?,
+        None => None,
+    };
+
+    let demand = mapping
+        .demand
+        .iter()
+        .map(|column| match row.number(column)? {
+            demand if demand < 0.0 => Err(format!("`{column}` is negative: {demand}")),
+            demand => Ok(demand),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let timestamp = |column: &Option<String>| -> Result<Option<Timestamp>, String> {
+        match column {
+            Some(column) => row
+                .get(column)?
+                .map(|value| parse_timestamp(value, &mapping.time_zone))
+                .transpose(),
+            None => Ok(None),
+        }
+    };
+    let start = timestamp(&mapping.time_window_start)?;
+    let end = timestamp(&mapping.time_window_end)?;
+    if let (Some(start), Some(end)) = (start, end)
+        && end < start
+    {
+        return Err(String::from("the time window ends before it starts"));
+    }
+
+    let location_id = *location_ids
+        .entry((coordinates[0].to_bits(), coordinates[1].to_bits()))
+        .or_insert_with(|| {
+            locations.push(JsonLocation { coordinates });
+            locations.len() - 1
+        });
+
+    Ok(JsonService {
+        id: id.to_owned(),
+        location_id,
+        duration,
+        demand: (!demand.is_empty()).then_some(demand),
+        skills: None,
+        tags: None,
+        time_windows: (start.is_some() || end.is_some()).then(|| vec![TimeWindow::new(start, end)]),
+        service_type: None,
+        position_preference: None,
+        stop_sequence: None,
+    })
+}
+
+struct ImportedJobs {
+    locations: Vec<JsonLocation>,
+    services: Vec<JsonService>,
+    /// Line number and error of each invalid row
+    errors: Vec<(usize, String)>,
+}
+
+fn import_jobs(content: &str, mapping: &ColumnMapping) -> anyhow::Result<ImportedJobs> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The CSV file is empty"))?;
+    let header = split_csv_line(header, mapping.delimiter)
+        .into_iter()
+        .enumerate()
+        .map(|(index, column)| (column.trim().to_owned(), index))
+        .collect::<HashMap<_, _>>();
+
+    let mut imported = ImportedJobs {
+        locations: vec![],
+        services: vec![],
+        errors: vec![],
+    };
+    let mut location_ids = HashMap::new();
+    // Line of the first row of each id
+    let mut ids = HashMap::new();
+
+    for (index, line) in lines {
+        let row = CsvRow {
+            header: &header,
+            fields: split_csv_line(line, mapping.delimiter),
+        };
+
+        let line_number = index + 1;
+        match parse_row(
+            &row,
+            mapping,
+            &ids,
+            &mut location_ids,
+            &mut imported.locations,
+        ) {
+            Ok(service) => {
+                ids.insert(service.id.clone(), line_number);
+                imported.services.push(service);
+            }
+            Err(error) => imported.errors.push((line_number, error)),
+        }
+    }
+
+    Ok(imported)
+}
+
+pub fn run(args: ImportJobsArgs) -> anyhow::Result<()> {
+    let mapping = ColumnMapping::parse(&std::fs::read_to_string(&args.mapping)?)?;
+    let imported = import_jobs(&std::fs::read_to_string(&args.csv)?, &mapping)?;
+
+    for (line, error) in &imported.errors {
+        warn!("{}:{line}: {error}", args.csv.display());
+    }
+
+    if !imported.errors.is_empty() && !args.skip_invalid {
+        anyhow::bail!(
+            "{} invalid rows, fix them or use --skip-invalid to import the valid ones",
+            imported.errors.len()
+        );
+    }
+
+    let problem = JsonVehicleRoutingProblem {
+        id: None,
+        locations: imported.locations,
+        services: imported.services,
+        vehicle_profiles: vec![JsonVehicleProfile {
+            id: args.profile,
+            cost_provider: None,
+            routing: None,
+        }],
+        vehicles: vec![],
+        relations: None,
+        depots: None,
+        depot_inventories: None,
+        charging_stations: None,
+        tolerances: None,
+        constraint_overrides: None,
+    };
+
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let writer = BufWriter::new(File::create(&args.out)?);
+    serde_json::to_writer_pretty(writer, &problem)?;
+
+    info!(
+        "Imported {} jobs at {} locations into {}, {} rows skipped",
+        problem.services.len(),
+        problem.locations.len(),
+        args.out.display(),
+        imported.errors.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPING: &str = r#"
+        # Export of the shop
+        id = "order"
+        lat = "lat"
+        lon = "lon"
+        demand = "weight"
+        duration = "minutes"
+        duration_unit = "minutes"
+        time_window_start = "from"
+        time_window_end = "to"
+        delimiter = ";"
+    "#;
+
+    #[test]
+    fn test_parse_mapping() {
+        let mapping = ColumnMapping::parse(MAPPING).unwrap();
+
+        assert_eq!(mapping.id, "order");
+        assert_eq!(mapping.demand, vec![String::from("weight")]);
+        assert_eq!(mapping.duration_unit, SignedDuration::from_mins(1));
+        assert_eq!(mapping.delimiter, ';');
+
+        assert!(ColumnMapping::parse("id = \"order\"").is_err());
+        assert!(ColumnMapping::parse(&format!("{MAPPING}\ncolour = \"red\"")).is_err());
+        assert!(
+            ColumnMapping::parse(&MAPPING.replace("unit = \"minutes\"", "unit = \"days\""))
+                .is_err()
+        );
+
+        let mapping = ColumnMapping::parse(
+            "id = \"order\"\nlat = \"lat\"\nlon = \"lon\"\ndemand = [\"weight\", \"volume\"]",
+        )
+        .unwrap();
+        assert_eq!(mapping.demand, vec!["weight", "volume"]);
+    }
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(
+            split_csv_line(r#"1,"Rue de la Loi, 16","say ""hi""",,"#, ','),
+            vec!["1", "Rue de la Loi, 16", "say \"hi\"", "", ""]
+        );
+    }
+
+    #[test]
+    fn test_import_jobs() {
+        let mapping = ColumnMapping::parse(MAPPING).unwrap();
+        let csv = "\
+order;lat;lon;weight;minutes;from;to
+A;50.85;4.35;12.5;10;2025-01-06T08:00:00;2025-01-06T12:00:00
+B;50.85;4.35;3;;;
+C;50.85;not a number;3;5;;
+D;50.86;4.36;;5;;
+E;50.86;4.36;-1;5;;
+F;50.86;4.36;1;1e300;;
+G;50.86;4.36;1;NaN;;
+A;50.86;4.36;1;5;;
+";
+
+        let imported = import_jobs(csv, &mapping).unwrap();
+
+        assert_eq!(imported.services.len(), 2);
+        assert_eq!(imported.locations.len(), 1);
+        assert_eq!(imported.services[0].id, "A");
+        assert_eq!(imported.services[0].demand, Some(vec![12.5]));
+        assert_eq!(
+            imported.services[0].duration,
+            Some(SignedDuration::from_mins(10))
+        );
+        assert!(imported.services[0].time_windows.is_some());
+        assert_eq!(imported.services[1].location_id, 0);
+        assert!(imported.services[1].time_windows.is_none());
+        assert_eq!(
+            imported
+                .errors
+                .iter()
+                .map(|(line, _)| *line)
+                .collect::<Vec<_>>(),
+            vec![4, 5, 6, 7, 8, 9]
+        );
+        assert!(imported.errors[5].1.contains("duplicate id `A`"));
+    }
+}
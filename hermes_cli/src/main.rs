@@ -5,17 +5,23 @@ use mimalloc::MiMalloc;
 use tracing_subscriber::fmt::format::FmtSpan;
 
 use crate::{
-    benchmark::BenchmarkSubcommands, generate::GenerateSubcommands, get_matrix::GetMatrixArgs,
-    optimize::OptimizeArgs, optimize_dataset::OptimizeDatasetArgs,
+    apply_osm_change::ApplyOsmChangeArgs, benchmark::BenchmarkSubcommands,
+    extract_region::ExtractRegionArgs, generate::GenerateSubcommands, get_matrix::GetMatrixArgs,
+    import_jobs::ImportJobsArgs, optimize::OptimizeArgs, optimize_dataset::OptimizeDatasetArgs,
+    verify_solution::VerifySolutionArgs,
 };
 
+mod apply_osm_change;
 mod benchmark;
+mod extract_region;
 mod file_utils;
 mod generate;
 mod get_matrix;
+mod import_jobs;
 mod optimize;
 mod optimize_dataset;
 mod parsers;
+mod verify_solution;
 
 #[cfg(feature = "dhat-heap")]
 #[global_allocator]
@@ -61,6 +67,26 @@ enum Commands {
         #[command(flatten)]
         args: GetMatrixArgs,
     },
+    /// Writes a smaller graph with only the roads of a region
+    ExtractRegion {
+        #[command(flatten)]
+        args: ExtractRegionArgs,
+    },
+    /// Applies an osmChange diff to an imported graph
+    ApplyOsmChange {
+        #[command(flatten)]
+        args: ApplyOsmChangeArgs,
+    },
+    /// Converts a CSV export of orders into the JSON problem format
+    ImportJobs {
+        #[command(flatten)]
+        args: ImportJobsArgs,
+    },
+    /// Checks a solution against the hard constraints of its problem
+    VerifySolution {
+        #[command(flatten)]
+        args: VerifySolutionArgs,
+    },
 }
 
 #[tokio::main]
@@ -89,6 +115,10 @@ async fn main() -> Result<(), anyhow::Error> {
         Some(Commands::Generate { commands }) => generate::run(commands)?,
         Some(Commands::GetMatrix { args }) => get_matrix::run(args).await?,
         Some(Commands::Benchmark { commands }) => benchmark::run(commands)?,
+        Some(Commands::ExtractRegion { args }) => extract_region::run(args)?,
+        Some(Commands::ApplyOsmChange { args }) => apply_osm_change::run(args)?,
+        Some(Commands::ImportJobs { args }) => import_jobs::run(args)?,
+        Some(Commands::VerifySolution { args }) => verify_solution::run(args).await?,
         None => {
             // Handle no command provided
         }
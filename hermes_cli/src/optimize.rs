@@ -1,12 +1,14 @@
-use std::{fs::File, io::BufReader, path::PathBuf};
+use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc};
 
-use clap::Args;
+use clap::{Args, ValueEnum};
 use hermes_matrix_providers::travel_matrix_client::TravelMatrixClient;
 use hermes_optimizer::{
-    json::types::JsonVehicleRoutingProblem,
+    json::{initial_solution::JsonInitialSolution, types::JsonVehicleRoutingProblem},
     solver::{
         solver::Solver,
-        solver_params::{SolverParams, Termination, Threads},
+        solver_params::{CheckpointParams, SolverParams, Termination, Threads},
+        statistics_sink::{StatisticsSinkFormat, StatisticsSinkParams},
+        time_window_compliance::compute_time_window_compliance,
     },
 };
 
@@ -29,9 +31,63 @@ pub struct OptimizeArgs {
     #[arg(long, short = 'n')]
     iterations: Option<usize>,
 
+    /// Stop when the best score improved by less than `min_improvement` over this window
+    #[arg(long, value_parser=parsers::parse_duration)]
+    stagnation_window: Option<jiff::SignedDuration>,
+
+    /// Minimum relative improvement of the best score over the stagnation window, 0.001 is 0.1%
+    #[arg(long, default_value_t = 0.001, requires = "stagnation_window")]
+    min_improvement: f64,
+
     /// Output folder into .sol files
     #[arg(long, short = 'o')]
     out: Option<PathBuf>,
+
+    /// JSON file with the routes to warm start the solver from
+    #[arg(long)]
+    initial_solution: Option<PathBuf>,
+
+    /// File where the state of the search is periodically saved
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    #[arg(long, value_parser=parsers::parse_duration, default_value = "1m")]
+    checkpoint_interval: jiff::SignedDuration,
+
+    /// Continue the search from the checkpoint file if it exists
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// File where the statistics of each iteration are written as the search runs, requires the
+    /// `statistics` feature
+    #[arg(long)]
+    statistics: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = StatisticsFormat::Csv)]
+    statistics_format: StatisticsFormat,
+
+    /// Always give the same solution for the same input, seed and threads, the search must be
+    /// stopped by a number of iterations
+    #[arg(long, requires = "iterations")]
+    deterministic: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StatisticsFormat {
+    Csv,
+    JsonLines,
+}
+
+impl From<StatisticsFormat> for StatisticsSinkFormat {
+    fn from(value: StatisticsFormat) -> Self {
+        match value {
+            StatisticsFormat::Csv => StatisticsSinkFormat::Csv,
+            StatisticsFormat::JsonLines => StatisticsSinkFormat::JsonLines,
+        }
+    }
 }
 
 pub async fn run(args: OptimizeArgs) -> anyhow::Result<()> {
@@ -44,14 +100,53 @@ pub async fn run(args: OptimizeArgs) -> anyhow::Result<()> {
     let client = TravelMatrixClient::default();
     let problem = content.build_problem(&client).await?;
 
+    let default_params = SolverParams::default_from_problem(&problem);
+    let mut terminations = match args.iterations {
+        // The duration would stop the search at a different iteration on every run
+        Some(iterations) if args.deterministic => vec![Termination::Iterations(iterations)],
+        Some(iterations) => vec![
+            Termination::Duration(args.timeout),
+            Termination::Iterations(iterations),
+        ],
+        None => vec![Termination::Duration(args.timeout)],
+    };
+    if let Some(window) = args.stagnation_window {
+        terminations.push(Termination::Stagnation {
+            window,
+            min_improvement: args.min_improvement,
+        });
+    }
+
     let solver_params = SolverParams {
-        terminations: vec![Termination::Duration(args.timeout)],
+        terminations,
         insertion_threads: Threads::Multi(args.threads as usize),
         run_intensify_search: true,
-        ..SolverParams::default_from_problem(&problem)
+        checkpoint: args.checkpoint.clone().map(|path| CheckpointParams {
+            path,
+            interval: args.checkpoint_interval,
+        }),
+        statistics_sink: args.statistics.map(|path| StatisticsSinkParams {
+            path,
+            format: args.statistics_format.into(),
+        }),
+        seed: args.seed.unwrap_or(default_params.seed),
+        deterministic: args.deterministic,
+        ..default_params
     };
 
-    let solver = Solver::new(problem, solver_params);
+    let solver = match &args.checkpoint {
+        Some(checkpoint) if args.resume && checkpoint.exists() => {
+            info!("Resuming from checkpoint {}", checkpoint.display());
+            Solver::resume_from_checkpoint(problem, solver_params, checkpoint)?
+        }
+        _ => Solver::new(problem, solver_params)?,
+    };
+
+    if let Some(initial_solution) = args.initial_solution {
+        let f = File::open(initial_solution)?;
+        let initial_solution: JsonInitialSolution = serde_json::from_reader(BufReader::new(f))?;
+        solver.set_initial_solution(initial_solution.build_solution(Arc::clone(solver.problem()))?);
+    }
 
     // let closure_loading_bar = Arc::clone(&loading_bar);
     // solver.on_best_solution(move |best_solution| {
@@ -75,6 +170,29 @@ pub async fn run(args: OptimizeArgs) -> anyhow::Result<()> {
             total_transport_cost,
             best_solution.solution.unassigned_jobs().len(),
         );
+        if let Some(compliance) = compute_time_window_compliance(&best_solution.solution) {
+            info!(
+                "Time windows: {} stops, min margin = {:#}, median = {:#}, {} near misses, {} late",
+                compliance.stops,
+                compliance.min_margin,
+                compliance.median_margin,
+                compliance.near_violations,
+                compliance.violations,
+            );
+        }
+        for reason in solver.unassigned_reasons(&best_solution.solution) {
+            info!(
+                "Unassigned job {}: {:?} ({}, {} rejected insertions)",
+                best_solution
+                    .solution
+                    .problem()
+                    .job(reason.job_id)
+                    .external_id(),
+                reason.code,
+                reason.constraint.unwrap_or("no rejection"),
+                reason.rejected_insertions,
+            );
+        }
         // loading_bar.lock().finish_with_message(format!(
         //     "Finished: routes = {}, costs = {}, unassigned = {}",
         //     n_routes,
@@ -1,4 +1,13 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
 
 use clap::Args;
 use hermes_optimizer::{
@@ -38,11 +47,21 @@ pub struct OptimizeDatasetArgs {
     /// Output folder into .sol files
     #[arg(long, short = 'o')]
     out: Option<PathBuf>,
+
+    /// Number of instances optimized in parallel, the threads of each instance are reduced so that
+    /// all the instances fit on the available cores
+    #[arg(long, short = 'j', default_value_t = 1)]
+    jobs: usize,
+
+    /// Skip the instances whose .sol file is already in the output folder, e.g. after an
+    /// interrupted run
+    #[arg(long, requires = "out")]
+    resume: bool,
 }
 
 pub fn run(args: OptimizeDatasetArgs) -> Result<(), anyhow::Error> {
     let paths = if args.dataset.is_file() {
-        vec![args.dataset]
+        vec![args.dataset.clone()]
     } else {
         let mut files = read_folder(&args.dataset)?;
         files.retain(|path| {
@@ -67,110 +86,220 @@ pub fn run(args: OptimizeDatasetArgs) -> Result<(), anyhow::Error> {
         })
         .collect();
 
-    for (i, path) in paths.iter().enumerate().filter(|(_, p)| {
-        p.extension()
-            .map(|ext| ext == "txt" || ext == "vrp")
-            .unwrap_or(false)
-    }) {
-        // Try to load an accompanying .sol file for optimal solution reference
-        let mut solution_path = path.clone();
-        solution_path.set_extension("sol");
-        let bks = if let Some(bks) = parse_solution_file(solution_path) {
-            Some(bks)
-        } else {
-            parse_bks_for_file(path).ok()
-        };
-
-        let vrp = parse_dataset(path)?;
-
-        let mut terminations: Vec<Termination> = vec![];
-
-        if let Some(timeout) = args.timeout {
-            terminations.push(Termination::Duration(timeout));
+    let summary_bar = multi_bar.add(ProgressBar::new(paths.len() as u64));
+    summary_bar.set_style(
+        ProgressStyle::with_template("{prefix:.bold} [{elapsed_precise}] {pos}/{len} {msg}")
+            .unwrap(),
+    );
+    summary_bar.set_prefix("dataset");
+
+    let jobs = args.jobs.max(1).min(paths.len().max(1));
+    let budget = InstanceThreads::budget(&args, jobs);
+
+    let next_instance = AtomicUsize::new(0);
+    let failures = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                loop {
+                    let i = next_instance.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(i) else {
+                        break;
+                    };
+
+                    if args.resume
+                        && let Some(out) = &args.out
+                        && solution_out_path(out, path).exists()
+                    {
+                        bars[i]
+                            .lock()
+                            .finish_with_message("Skipped - solved by a previous run");
+                        summary_bar.inc(1);
+                        continue;
+                    }
+
+                    // A failing or panicking instance must not stop the other ones
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        optimize_instance(path, &args, budget, &bars[i], &style)
+                    }))
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("the solver panicked")));
+
+                    if let Err(error) = result {
+                        bars[i]
+                            .lock()
+                            .finish_with_message(format!("Failed - {error}"));
+                        failures.lock().push(path.clone());
+                    }
+
+                    summary_bar.inc(1);
+                    summary_bar.set_message(format!("{} failed", failures.lock().len()));
+                }
+            });
         }
+    });
+
+    summary_bar.finish();
+
+    let failures = failures.into_inner();
+    if !failures.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} instance(s) failed: {}",
+            failures.len(),
+            failures
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
 
-        if let Some(iterations) = args.iterations {
-            terminations.push(Termination::Iterations(iterations));
+    Ok(())
+}
+
+/// Threads given to each instance, so that the instances running in parallel do not use more
+/// threads than the machine has
+#[derive(Clone, Copy)]
+struct InstanceThreads {
+    search_threads: usize,
+    insertion_threads: usize,
+}
+
+impl InstanceThreads {
+    fn budget(args: &OptimizeDatasetArgs, jobs: usize) -> Self {
+        if jobs == 1 {
+            return InstanceThreads {
+                search_threads: args.sthreads as usize,
+                insertion_threads: args.ithreads as usize,
+            };
         }
 
-        if let Some(optimal_sol) = bks {
-            terminations.push(Termination::VehiclesAndCosts {
-                vehicles: optimal_sol.vehicles,
-                costs: optimal_sol.cost,
-            });
+        let available = thread::available_parallelism().map_or(1, |n| n.get());
+        let per_instance = (available / jobs).max(1);
+
+        let search_threads = (args.sthreads as usize).clamp(1, per_instance);
+        let insertion_threads =
+            (args.ithreads as usize).clamp(1, (per_instance / search_threads).max(1));
+
+        InstanceThreads {
+            search_threads,
+            insertion_threads,
         }
+    }
+}
 
-        let solver_params = SolverParams {
-            terminations,
-            search_threads: Threads::Multi(args.sthreads as usize),
-            insertion_threads: Threads::Multi(args.ithreads as usize),
-            debug_options: SolverParamsDebugOptions {
-                enable_local_search: true,
-            },
-            ..SolverParams::default_from_problem(&vrp)
-        };
-
-        let mut solver = Solver::new(vrp, solver_params);
-
-        let bar = Arc::clone(&bars[i]);
-        bar.lock().set_message("running...");
-        bar.lock().reset_elapsed();
-        bar.lock().enable_steady_tick(Duration::from_millis(100));
-
-        bar.lock().set_style(style.clone());
-
-        let callback_bar = Arc::clone(&bars[i]);
-        solver.on_best_solution(move |s| {
-            let n_routes = s.solution.non_empty_routes_count();
-            let total_transport_cost = s.solution.total_transport_costs();
-            callback_bar.lock().finish_with_message(format!(
-                "Running... Routes = {}{}, costs = {}, unassigned = {}, gap = {}",
-                n_routes,
-                bks.map(|os| format!(" (optimal: {})", os.vehicles))
-                    .unwrap_or_default(),
-                total_transport_cost,
-                s.solution.unassigned_jobs().len(),
-                bks.map(|oc| format!("{:+.2}%", gap_percent(total_transport_cost, oc.cost)))
-                    .unwrap_or_else(|| "n/a".to_string())
-            ));
+fn optimize_instance(
+    path: &Path,
+    args: &OptimizeDatasetArgs,
+    threads: InstanceThreads,
+    bar: &Arc<Mutex<ProgressBar>>,
+    style: &ProgressStyle,
+) -> Result<(), anyhow::Error> {
+    // Try to load an accompanying .sol file for optimal solution reference
+    let mut solution_path = path.to_path_buf();
+    solution_path.set_extension("sol");
+    let bks = if let Some(bks) = parse_solution_file(solution_path) {
+        Some(bks)
+    } else {
+        parse_bks_for_file(path).ok()
+    };
+
+    let vrp = parse_dataset(path)?;
+
+    let mut terminations: Vec<Termination> = vec![];
+
+    if let Some(timeout) = args.timeout {
+        terminations.push(Termination::Duration(timeout));
+    }
+
+    if let Some(iterations) = args.iterations {
+        terminations.push(Termination::Iterations(iterations));
+    }
+
+    if let Some(optimal_sol) = bks {
+        terminations.push(Termination::VehiclesAndCosts {
+            vehicles: optimal_sol.vehicles,
+            costs: optimal_sol.cost,
         });
+    }
 
-        solver.solve()?;
-        let best_solution = solver.current_best_solution();
-
-        if let Some(best_solution) = best_solution {
-            let n_routes = best_solution.solution.non_empty_routes_count();
-            let total_transport_cost = best_solution.solution.total_transport_costs();
-            bar.lock().finish_with_message(format!(
-                "Finished - routes = {}{}, costs = {}, unassigned = {}, gap = {}",
-                n_routes,
-                bks.map(|os| format!(" (optimal: {})", os.vehicles))
-                    .unwrap_or_default(),
-                total_transport_cost,
-                best_solution.solution.unassigned_jobs().len(),
-                bks.map(|bks| format!("{:+.2}%", gap_percent(total_transport_cost, bks.cost)))
-                    .unwrap_or_else(|| "n/a".to_string())
-            ));
-
-            if let Some(out) = &args.out {
-                let mut out_path = out.clone();
-                if out_path.is_dir() {
-                    let file_stem = path.file_stem().unwrap();
-                    out_path.push(file_stem);
-                    out_path.set_extension("sol");
-                }
-                std::fs::write(out_path, create_sol_file_contents(&best_solution.solution))?;
-            }
+    let solver_params = SolverParams {
+        terminations,
+        search_threads: Threads::Multi(threads.search_threads),
+        insertion_threads: Threads::Multi(threads.insertion_threads),
+        debug_options: SolverParamsDebugOptions {
+            enable_local_search: true,
+            verify_after_local_search: false,
+        },
+        ..SolverParams::default_from_problem(&vrp)
+    };
 
-            // println!("{}", create_sol_file_contents(&best_solution.solution));
-        } else {
-            bar.lock().finish_with_message("No solution".to_string());
+    let mut solver = Solver::new(vrp, solver_params)?;
+
+    bar.lock().set_message("running...");
+    bar.lock().reset_elapsed();
+    bar.lock().enable_steady_tick(Duration::from_millis(100));
+
+    bar.lock().set_style(style.clone());
+
+    let callback_bar = Arc::clone(bar);
+    solver.on_best_solution(move |s| {
+        let n_routes = s.solution.non_empty_routes_count();
+        let total_transport_cost = s.solution.total_transport_costs();
+        callback_bar.lock().finish_with_message(format!(
+            "Running... Routes = {}{}, costs = {}, unassigned = {}, gap = {}",
+            n_routes,
+            bks.map(|os| format!(" (optimal: {})", os.vehicles))
+                .unwrap_or_default(),
+            total_transport_cost,
+            s.solution.unassigned_jobs().len(),
+            bks.map(|oc| format!("{:+.2}%", gap_percent(total_transport_cost, oc.cost)))
+                .unwrap_or_else(|| "n/a".to_string())
+        ));
+    });
+
+    solver.solve()?;
+    let best_solution = solver.current_best_solution();
+
+    if let Some(best_solution) = best_solution {
+        let n_routes = best_solution.solution.non_empty_routes_count();
+        let total_transport_cost = best_solution.solution.total_transport_costs();
+        bar.lock().finish_with_message(format!(
+            "Finished - routes = {}{}, costs = {}, unassigned = {}, gap = {}",
+            n_routes,
+            bks.map(|os| format!(" (optimal: {})", os.vehicles))
+                .unwrap_or_default(),
+            total_transport_cost,
+            best_solution.solution.unassigned_jobs().len(),
+            bks.map(|bks| format!("{:+.2}%", gap_percent(total_transport_cost, bks.cost)))
+                .unwrap_or_else(|| "n/a".to_string())
+        ));
+
+        if let Some(out) = &args.out {
+            std::fs::write(
+                solution_out_path(out, path),
+                create_sol_file_contents(&best_solution.solution),
+            )?;
         }
+    } else {
+        bar.lock().finish_with_message("No solution".to_string());
     }
 
     Ok(())
 }
 
+/// `.sol` file of the instance at `path` in the `out` folder, or `out` itself when it is a file
+fn solution_out_path(out: &Path, path: &Path) -> PathBuf {
+    let mut out_path = out.to_path_buf();
+    if out_path.is_dir() {
+        let file_stem = path.file_stem().unwrap();
+        out_path.push(file_stem);
+        out_path.set_extension("sol");
+    }
+
+    out_path
+}
+
 fn create_sol_file_contents(solution: &WorkingSolution) -> String {
     // Should create content like this:
     /*
@@ -185,7 +314,7 @@ fn create_sol_file_contents(solution: &WorkingSolution) -> String {
     let mut contents = String::new();
     let problem = solution.problem();
 
-    for (idx, route) in solution.non_empty_routes_iter().enumerate() {
+    for (idx, (_, route)) in solution.ordered_non_empty_routes().into_iter().enumerate() {
         let route_number = idx + 1;
         contents.push_str(&format!("Route #{}:", route_number));
 
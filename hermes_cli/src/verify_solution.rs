@@ -0,0 +1,70 @@
+use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc};
+
+use anyhow::bail;
+use clap::Args;
+use hermes_matrix_providers::travel_matrix_client::TravelMatrixClient;
+use hermes_optimizer::{
+    json::{initial_solution::JsonInitialSolution, types::JsonVehicleRoutingProblem},
+    solver::{solution::verify::verify_solution, solver_params::SolverParams},
+};
+use tracing::{error, info};
+
+#[derive(Args)]
+pub struct VerifySolutionArgs {
+    /// The problem file
+    #[arg(short = 'i', long)]
+    input: PathBuf,
+
+    /// JSON file with the routes of the solution, in the format of the initial solutions
+    #[arg(short, long)]
+    solution: PathBuf,
+
+    /// Prints the full report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub async fn run(args: VerifySolutionArgs) -> anyhow::Result<()> {
+    let f = File::open(args.input)?;
+    let content: JsonVehicleRoutingProblem = serde_json::from_reader(BufReader::new(f))?;
+    let client = TravelMatrixClient::default();
+    let problem = Arc::new(content.build_problem(&client).await?);
+
+    let f = File::open(args.solution)?;
+    let solution: JsonInitialSolution = serde_json::from_reader(BufReader::new(f))?;
+    let solution = solution.build_solution(Arc::clone(&problem))?;
+
+    let params = SolverParams::default_from_problem(&problem);
+    let report = verify_solution(&solution, params.constraints.constraints());
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+
+    for route in &report.routes {
+        info!(
+            "Route of {}: {} activities, {} - {}, max load = {:?}",
+            route.vehicle_id,
+            route.activities,
+            route.start,
+            route.end,
+            route.max_load.to_vec(),
+        );
+    }
+
+    for violation in &report.violations {
+        error!("{:?}", violation);
+    }
+
+    if !report.is_valid() {
+        bail!("Invalid solution: {} violations", report.violations.len());
+    }
+
+    info!(
+        "Valid solution: {} routes, {} unassigned",
+        report.routes.len(),
+        report.unassigned_jobs
+    );
+
+    Ok(())
+}